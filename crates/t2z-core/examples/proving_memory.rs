@@ -0,0 +1,75 @@
+//! Measures the peak memory of Orchard proving against t2z's estimates.
+//!
+//! Peak resident memory is a per-process high-water mark, so each run
+//! measures one proof: building the proving key, then proving a PCZT padded
+//! to the given number of Orchard actions. Linux only (`VmHWM`).
+//!
+//! ```sh
+//! cargo run --release -p t2z-core --example proving_memory -- 2
+//! ```
+//!
+//! `just bench-proving-memory` runs it for 2 to 16 actions. Re-run it after
+//! upgrading `orchard` or `halo2_proofs` and adjust the
+//! `PROVING_*_MEMORY_ESTIMATE` constants if a measurement exceeds them.
+
+use t2z_core::{
+    Network, OrchardAccount, Payment, ProposalOptions, TransactionRequest, TransparentInput,
+    derive_transparent_keypair, estimate_proving_memory, propose_transaction_with_options,
+    prove_transaction_with_metrics,
+};
+
+fn main() {
+    let num_actions: usize = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("number of Orchard actions"))
+        .unwrap_or(2);
+
+    let keypair = derive_transparent_keypair(&[1u8; 32], Network::Testnet, 0, 0).unwrap();
+    let orchard_address = OrchardAccount::from_seed(&[2u8; 32], Network::Testnet, 0)
+        .unwrap()
+        .address(0, Network::Testnet)
+        .unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![3u8; 32],
+        prevout_index: 0,
+        value: 100_000_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: orchard_address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let options = ProposalOptions {
+        min_orchard_actions: num_actions,
+        ..ProposalOptions::new(3_000_000)
+    };
+    let pczt = propose_transaction_with_options(
+        &inputs,
+        request,
+        Some(&orchard_address),
+        Network::Testnet,
+        options,
+    )
+    .unwrap();
+
+    let (_, metrics) = prove_transaction_with_metrics(pczt).unwrap();
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "actions={} estimated_mib={:.0} (key {:.0}) measured_peak_mib={} prove_ms={:.0} key_ms={:.0}",
+        metrics.orchard_actions,
+        mib(metrics.estimated_memory_bytes),
+        mib(estimate_proving_memory(metrics.orchard_actions, true)
+            - estimate_proving_memory(metrics.orchard_actions, false)),
+        metrics
+            .peak_memory_bytes
+            .map_or("n/a".to_string(), |bytes| format!("{:.0}", mib(bytes))),
+        metrics.wall_time_ms - metrics.proving_key_ms,
+        metrics.proving_key_ms,
+    );
+}
@@ -0,0 +1,159 @@
+//! Proving control of an Orchard address without a transaction.
+//!
+//! Before a large payout, the payer can ask the payee to prove that a
+//! shielded address is really theirs. The proof is an Orchard key
+//! agreement, the one note encryption uses:
+//! 1. The verifier calls `create_address_ownership_challenge`, which picks a
+//!    random ephemeral key `esk` and sends `epk = [esk] g_d` to the prover.
+//! 2. The prover answers with `prove_address_ownership`, which computes the
+//!    shared secret `[ivk] epk` from its viewing key.
+//! 3. `verify_address_ownership` compares the answer with `[esk] pk_d`,
+//!    which only equals `[ivk] epk` for the address's own `ivk`.
+//!
+//! No key is revealed, and a challenge cannot be answered twice since it is
+//! random. Holding the incoming viewing key is enough to answer, so the
+//! proof shows the prover can see the address's funds, not that it can
+//! spend them. A prover could also relay the challenge to the real owner;
+//! the proof says nothing about who is at the other end of the channel.
+
+use orchard::keys::{FullViewingKey, PreparedIncomingViewingKey};
+use orchard::note_encryption::OrchardDomain;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zcash_address::unified::{Container, Encoding, Fvk, Ufvk};
+use zcash_note_encryption::{Domain, EphemeralKeyBytes};
+
+use crate::disclosure::orchard_note;
+use crate::keys::OrchardAccount;
+use crate::{Network, T2ZError, parse_orchard_receiver};
+
+const OWNERSHIP_RESPONSE_PERSONALIZATION: &[u8; 16] = b"t2z-addr-ownersh";
+
+/// A challenge for `prove_address_ownership`, kept by the verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressOwnershipChallenge {
+    /// The address whose ownership is being proved
+    pub address: String,
+    /// Sent to the prover (an ephemeral public key)
+    pub challenge: [u8; 32],
+    /// The answer only the address's owner can compute. Keep it from the
+    /// prover.
+    pub expected_response: [u8; 32],
+}
+
+/// Creates a fresh challenge for the owner of an Orchard (unified) address.
+pub fn create_address_ownership_challenge(
+    address: &str,
+    network: Network,
+) -> Result<AddressOwnershipChallenge, T2ZError> {
+    let recipient = parse_address(address, network)?;
+
+    // An ephemeral key is derived from a note; any note to the address will do
+    let recipient_bytes = recipient.to_raw_address_bytes();
+    let note = loop {
+        let mut rho = [0u8; 32];
+        let mut rseed = [0u8; 32];
+        OsRng.fill_bytes(&mut rho);
+        OsRng.fill_bytes(&mut rseed);
+        // Not every 32 bytes are a valid rho
+        if let Ok(note) = orchard_note(&recipient_bytes, 0, &rho, &rseed) {
+            break note;
+        }
+    };
+    let esk = OrchardDomain::derive_esk(&note).expect("Orchard notes always have an esk");
+    let epk = OrchardDomain::epk_bytes(&OrchardDomain::ka_derive_public(&note, &esk));
+    let shared_secret = OrchardDomain::ka_agree_enc(&esk, &OrchardDomain::get_pk_d(&note));
+
+    let challenge = epk.0;
+    Ok(AddressOwnershipChallenge {
+        address: address.to_string(),
+        challenge,
+        expected_response: response(
+            OrchardDomain::kdf(shared_secret, &epk).as_bytes(),
+            &challenge,
+            &recipient,
+        ),
+    })
+}
+
+/// Answers an ownership challenge for `address`.
+///
+/// # Arguments
+/// * `ufvk_or_sk` - Unified full viewing key (`uview1...`), or the hex
+///   Orchard spending key, of the account the address belongs to
+/// * `address` - The address being proved, as given to the verifier
+/// * `challenge` - `AddressOwnershipChallenge::challenge` from the verifier
+/// * `network` - Mainnet or Testnet
+pub fn prove_address_ownership(
+    ufvk_or_sk: &str,
+    address: &str,
+    challenge: &[u8; 32],
+    network: Network,
+) -> Result<[u8; 32], T2ZError> {
+    let recipient = parse_address(address, network)?;
+    let fvk = full_viewing_key(ufvk_or_sk)?;
+    let scope = fvk.scope_for_address(&recipient).ok_or_else(|| {
+        T2ZError::InvalidInput("The address does not belong to this key".to_string())
+    })?;
+
+    let epk_bytes = EphemeralKeyBytes(*challenge);
+    let epk = OrchardDomain::epk(&epk_bytes)
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid ownership challenge".to_string()))?;
+    let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(scope));
+    let shared_secret = OrchardDomain::ka_agree_dec(&ivk, &OrchardDomain::prepare_epk(epk));
+
+    Ok(response(
+        OrchardDomain::kdf(shared_secret, &epk_bytes).as_bytes(),
+        challenge,
+        &recipient,
+    ))
+}
+
+/// Checks the prover's answer to a challenge.
+pub fn verify_address_ownership(
+    challenge: &AddressOwnershipChallenge,
+    response: &[u8; 32],
+) -> bool {
+    challenge.expected_response == *response
+}
+
+fn parse_address(address: &str, network: Network) -> Result<orchard::Address, T2ZError> {
+    let address = zcash_address::ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+    parse_orchard_receiver(&address, network.to_network_type())
+}
+
+/// The Orchard full viewing key in a UFVK, or of a hex spending key
+fn full_viewing_key(ufvk_or_sk: &str) -> Result<FullViewingKey, T2ZError> {
+    if let Ok(bytes) = hex::decode(ufvk_or_sk) {
+        let spending_key: [u8; 32] = bytes.try_into().map_err(|_| {
+            T2ZError::InvalidInput("Orchard spending key must be 32 bytes".to_string())
+        })?;
+        return Ok(OrchardAccount::from_spending_key(&spending_key)?
+            .full_viewing_key()
+            .clone());
+    }
+
+    let (_, ufvk) = Ufvk::decode(ufvk_or_sk)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid UFVK: {:?}", e)))?;
+    ufvk.items()
+        .into_iter()
+        .find_map(|item| match item {
+            Fvk::Orchard(bytes) => Some(bytes),
+            _ => None,
+        })
+        .and_then(|bytes| FullViewingKey::from_bytes(&bytes))
+        .ok_or_else(|| T2ZError::InvalidInput("UFVK has no valid Orchard FVK".to_string()))
+}
+
+/// Binds the shared key to the challenge and the address
+fn response(key: &[u8], challenge: &[u8; 32], recipient: &orchard::Address) -> [u8; 32] {
+    let mut h = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(OWNERSHIP_RESPONSE_PERSONALIZATION)
+        .to_state();
+    h.update(key);
+    h.update(challenge);
+    h.update(&recipient.to_raw_address_bytes());
+    h.finalize().as_bytes().try_into().expect("32 bytes")
+}
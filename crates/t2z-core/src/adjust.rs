@@ -0,0 +1,203 @@
+//! Swapping transparent inputs without re-proving.
+//!
+//! When an input of a proposal turns out to be unusable (spent elsewhere,
+//! or too small once the fee rises), proposing again means new Orchard
+//! actions and seconds of proving. The Orchard proof does not commit to the
+//! transparent bundle, though: `adjust_transparent_side` replaces the inputs
+//! and the transparent change of an existing PCZT, recomputes the fee, and
+//! keeps the Orchard actions, and any proof of them, as they are.
+//!
+//! The value moved into Orchard is fixed by those actions, so the new
+//! inputs must cover it along with the transparent payments and the fee.
+//! Proposing marks transparent change with `CHANGE_OUTPUT_KEY`; only that
+//! output is recomputed, so a payment to the change address is kept.
+//! Dummy Orchard spends are re-signed, since their signatures commit to the
+//! whole transaction.
+
+use std::collections::BTreeMap;
+
+use zcash_transparent::address::TransparentAddress;
+
+use crate::fees::zip317_fee;
+use crate::keys::p2pkh_script;
+use crate::shadow::{PcztShadow, TransparentInputShadow, TransparentOutputShadow};
+use crate::{
+    FeeStrategy, Network, Pczt, ProposalRng, T2ZError, TransparentInput, checked_sum,
+    parse_transparent_address, resign_dummy_spends, transparent_input_parts,
+};
+
+/// `SIGHASH_ALL`, the hash type of every T2Z input
+const SIGHASH_ALL: u8 = 0x01;
+
+/// Proprietary key of the transparent output a proposal made as change
+pub(crate) const CHANGE_OUTPUT_KEY: &str = "t2z:change";
+
+/// Replaces the transparent inputs (and change) of a proposed PCZT, keeping
+/// its Orchard bundle.
+///
+/// The transparent change output of the proposal is recomputed; every
+/// other output is kept. Signatures on the old inputs are dropped, so the
+/// PCZT must be signed again, but not proved again.
+///
+/// # Arguments
+/// * `pczt` - A proposed PCZT, proved or not
+/// * `new_inputs` - The transparent inputs to spend instead
+/// * `change_address` - Transparent address for change, as in the proposal
+/// * `network` - Mainnet or Testnet
+/// * `fee_strategy` - The fee to pay, usually as in the proposal (ZIP 317
+///   or fixed; custom fee rules are not supported)
+pub fn adjust_transparent_side<FR>(
+    pczt: Pczt,
+    new_inputs: &[TransparentInput],
+    change_address: Option<&str>,
+    network: Network,
+    fee_strategy: &FeeStrategy<FR>,
+) -> Result<Pczt, T2ZError> {
+    if new_inputs.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "At least one transparent input is required".to_string(),
+        ));
+    }
+    if matches!(fee_strategy, FeeStrategy::Custom(_)) {
+        return Err(T2ZError::InvalidInput(
+            "Adjusting a PCZT requires a ZIP 317 or fixed fee".to_string(),
+        ));
+    }
+    let change_script = change_address
+        .map(|address| change_script(address, network))
+        .transpose()?;
+
+    let (header, mut pczt_shadow) = PcztShadow::from_pczt(&pczt)?;
+    if !pczt_shadow.sapling.spends.is_empty() || !pczt_shadow.sapling.outputs.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "Sapling bundles are not supported".to_string(),
+        ));
+    }
+
+    // T2Z bundles only receive value: outputs, and dummy spends of nothing
+    let (orchard_magnitude, orchard_negative) = pczt_shadow.orchard.value_sum;
+    if !orchard_negative && orchard_magnitude != 0 {
+        return Err(T2ZError::InvalidInput(
+            "Orchard bundle spends value; only T2Z bundles can be adjusted".to_string(),
+        ));
+    }
+    let orchard_actions = pczt_shadow.orchard.actions.len();
+
+    let mut outputs: Vec<TransparentOutputShadow> = pczt_shadow
+        .transparent
+        .outputs
+        .into_iter()
+        .filter(|output| !output.proprietary.contains_key(CHANGE_OUTPUT_KEY))
+        .collect();
+
+    let mut inputs = Vec::with_capacity(new_inputs.len());
+    for input in new_inputs {
+        let (_, outpoint, _) = transparent_input_parts(input)?;
+        inputs.push(TransparentInputShadow {
+            prevout_txid: *outpoint.hash(),
+            prevout_index: input.prevout_index,
+            sequence: input.sequence,
+            required_time_lock_time: None,
+            required_height_lock_time: None,
+            script_sig: None,
+            value: input.value,
+            script_pubkey: input.script_pubkey.clone(),
+            redeem_script: None,
+            partial_signatures: BTreeMap::new(),
+            sighash_type: SIGHASH_ALL,
+            bip32_derivation: BTreeMap::new(),
+            ripemd160_preimages: BTreeMap::new(),
+            sha256_preimages: BTreeMap::new(),
+            hash160_preimages: BTreeMap::new(),
+            hash256_preimages: BTreeMap::new(),
+            proprietary: BTreeMap::new(),
+        });
+    }
+
+    let total_input = checked_sum(new_inputs.iter().map(|i| i.value), "Input total")?.into_u64();
+    let payment = checked_sum(
+        outputs
+            .iter()
+            .map(|output| output.value)
+            .chain([orchard_magnitude]),
+        "Payment total",
+    )?
+    .into_u64();
+    let fee = adjusted_fee(fee_strategy, inputs.len(), outputs.len(), orchard_actions);
+    let surplus = total_input
+        .checked_sub(payment + fee)
+        .ok_or(T2ZError::InsufficientFunds {
+            available: total_input,
+            required: payment + fee,
+            payment,
+            fee,
+        })?;
+
+    if surplus > 0 {
+        let change_script = change_script.ok_or(T2ZError::ChangeRequired { change: surplus })?;
+        // A surplus smaller than the change output's own fee is left to the fee
+        let fee_with_change = adjusted_fee(
+            fee_strategy,
+            inputs.len(),
+            outputs.len() + 1,
+            orchard_actions,
+        );
+        let change_fee = fee_with_change - fee;
+        if surplus > change_fee {
+            outputs.push(TransparentOutputShadow {
+                value: surplus - change_fee,
+                script_pubkey: change_script,
+                redeem_script: None,
+                bip32_derivation: BTreeMap::new(),
+                user_address: change_address.map(str::to_string),
+                proprietary: BTreeMap::from([(CHANGE_OUTPUT_KEY.to_string(), Vec::new())]),
+            });
+        }
+    }
+
+    pczt_shadow.transparent.inputs = inputs;
+    pczt_shadow.transparent.outputs = outputs;
+    for action in &mut pczt_shadow.orchard.actions {
+        action.spend.spend_auth_sig = None;
+    }
+    let adjusted = pczt_shadow.into_pczt(&header)?;
+
+    resign_dummy_spends(&adjusted, adjusted.clone(), &mut ProposalRng::new(None))
+}
+
+/// Fee for a transaction of this shape under `fee_strategy`, which is not
+/// `Custom`
+fn adjusted_fee<FR>(
+    fee_strategy: &FeeStrategy<FR>,
+    transparent_inputs: usize,
+    transparent_outputs: usize,
+    orchard_actions: usize,
+) -> u64 {
+    match fee_strategy {
+        FeeStrategy::Fixed(fee) => *fee,
+        _ => zip317_fee(transparent_inputs, transparent_outputs, orchard_actions),
+    }
+}
+
+/// scriptPubKey of a transparent change address
+fn change_script(address: &str, network: Network) -> Result<Vec<u8>, T2ZError> {
+    let address = zcash_address::ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid change address: {:?}", e)))?;
+    if !address.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+        return Err(T2ZError::InvalidAddress(
+            "Change address must be transparent".to_string(),
+        ));
+    }
+
+    Ok(
+        match parse_transparent_address(&address, network.to_network_type())? {
+            TransparentAddress::PublicKeyHash(hash) => p2pkh_script(&hash),
+            TransparentAddress::ScriptHash(hash) => {
+                let mut script = vec![0xa9, 0x14];
+                script.extend_from_slice(&hash);
+                script.push(0x87);
+                script
+            }
+        },
+    )
+}
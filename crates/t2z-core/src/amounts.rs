@@ -0,0 +1,141 @@
+//! Zatoshi amounts as JSON strings.
+//!
+//! Amounts are `u64`s, but JavaScript numbers, and JSON parsers that read
+//! every number as a double, only hold integers exactly up to 2^53. Callers
+//! whose JSON passes through such code can have every zatoshi amount this
+//! crate serializes written as a decimal string instead:
+//! `set_amounts_as_strings` turns this on for the whole process, and
+//! `with_amounts_as_strings` for whatever one closure serializes.
+//!
+//! Only human-readable formats are affected; the binary encodings
+//! (`to_bytes`, PCZTs) are unchanged. Deserializing accepts amounts as
+//! either numbers or strings, whatever the setting.
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+static AMOUNTS_AS_STRINGS: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Set by `with_amounts_as_strings`, overriding the global setting
+    static AMOUNTS_AS_STRINGS_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Whether zatoshi amounts are serialized as strings in human-readable
+/// formats (JSON) from now on, on every thread.
+pub fn set_amounts_as_strings(enabled: bool) {
+    AMOUNTS_AS_STRINGS.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether zatoshi amounts are currently serialized as strings on this
+/// thread.
+pub fn amounts_as_strings() -> bool {
+    AMOUNTS_AS_STRINGS_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or_else(|| AMOUNTS_AS_STRINGS.load(Ordering::Relaxed))
+}
+
+/// Runs `f` with amounts serialized as strings (or not), whatever the
+/// global setting, e.g. `with_amounts_as_strings(true, || serde_json::to_string(&info))`.
+pub fn with_amounts_as_strings<T>(enabled: bool, f: impl FnOnce() -> T) -> T {
+    /// Restores the previous override even if `f` panics
+    struct Restore(Option<bool>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            AMOUNTS_AS_STRINGS_OVERRIDE.with(|o| o.set(self.0));
+        }
+    }
+
+    let _restore = Restore(AMOUNTS_AS_STRINGS_OVERRIDE.with(|o| o.replace(Some(enabled))));
+    f()
+}
+
+/// `#[serde(with = "t2z_core::amounts::amount")]` for `u64` zatoshi fields
+pub mod amount {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() && amounts_as_strings() {
+            serializer.collect_str(value)
+        } else {
+            serializer.serialize_u64(*value)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AmountVisitor)
+        } else {
+            deserializer.deserialize_u64(AmountVisitor)
+        }
+    }
+}
+
+/// `#[serde(with = "t2z_core::amounts::option_amount")]` for `Option<u64>`
+/// zatoshi fields
+pub mod option_amount {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.serialize_some(&AsAmount(*value)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        let amount: Option<FromAmount> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(amount.map(|amount| amount.0))
+    }
+
+    struct AsAmount(u64);
+
+    impl serde::Serialize for AsAmount {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            amount::serialize(&self.0, serializer)
+        }
+    }
+
+    struct FromAmount(u64);
+
+    impl<'de> serde::Deserialize<'de> for FromAmount {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            amount::deserialize(deserializer).map(FromAmount)
+        }
+    }
+}
+
+struct AmountVisitor;
+
+impl Visitor<'_> for AmountVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an amount in zatoshis, as a number or decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        u64::try_from(value).map_err(|_| E::custom(format!("negative amount {}", value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        // Digits only: no sign, whitespace, or decimal point
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(E::custom(format!("invalid amount {:?}", value)));
+        }
+        value
+            .parse()
+            .map_err(|_| E::custom(format!("amount {} out of range", value)))
+    }
+}
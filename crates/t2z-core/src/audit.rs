@@ -0,0 +1,143 @@
+//! Audit records for signing events.
+//!
+//! Custodial integrators log what they sign. `export_audit_record` captures a
+//! PCZT's inputs, outputs, fee, the payments it was built for (memos are
+//! recorded as hashes, not plaintext), and the txid once it is computable, as
+//! a JSON-serializable `AuditRecord`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    PcztOrchardOutput, PcztTransparentInput, PcztTransparentOutput, Pczt, SpendFinalizer, T2ZError,
+    TransactionExtractor, TransactionRequest, inspect_pczt,
+};
+
+/// Version of the `AuditRecord` layout
+pub const AUDIT_RECORD_VERSION: u32 = 1;
+
+/// BLAKE2b personalization for memo hashes in audit records
+const AUDIT_MEMO_PERSONALIZATION: &[u8; 16] = b"t2z_AuditMemoHsh";
+
+/// A payment as recorded in an audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPayment {
+    /// Recipient address
+    pub address: String,
+    /// Amount in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub amount: u64,
+    /// BLAKE2b-256 of the memo bytes (hex), if the payment has a memo
+    pub memo_hash: Option<String>,
+    /// Payment label, if any
+    pub label: Option<String>,
+}
+
+/// Everything worth logging about a PCZT at signing time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Layout version (`AUDIT_RECORD_VERSION`)
+    pub version: u32,
+    /// Caller-supplied event time (Unix seconds)
+    pub timestamp: u64,
+    /// Transaction ID (hex, display order), if the PCZT is complete enough to extract
+    pub txid: Option<String>,
+    /// Consensus branch ID the transaction is built for
+    pub consensus_branch_id: u32,
+    /// Expiry height
+    pub expiry_height: u32,
+    /// Payments from the transaction request
+    pub payments: Vec<AuditPayment>,
+    /// Transparent inputs
+    pub transparent_inputs: Vec<PcztTransparentInput>,
+    /// Transparent outputs
+    pub transparent_outputs: Vec<PcztTransparentOutput>,
+    /// Orchard outputs
+    pub orchard_outputs: Vec<PcztOrchardOutput>,
+    /// Total input value (zatoshis)
+    #[serde(with = "crate::amounts::amount")]
+    pub total_input: u64,
+    /// Fee (zatoshis)
+    #[serde(with = "crate::amounts::amount")]
+    pub fee: u64,
+    /// Whether all transparent inputs are signed
+    pub all_inputs_signed: bool,
+    /// Whether the Orchard bundle has proofs
+    pub has_orchard_proofs: bool,
+}
+
+impl AuditRecord {
+    /// Serializes the record as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("audit record serializes to JSON")
+    }
+
+    /// Parses a record from JSON.
+    pub fn from_json(json: &str) -> Result<Self, T2ZError> {
+        serde_json::from_str(json)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid audit record: {}", e)))
+    }
+}
+
+/// Builds an audit record for `pczt`, proposed from `request`.
+///
+/// The txid is only filled in once every input is signed and the Orchard
+/// bundle (if any) is proved, since it is taken from the extracted transaction.
+///
+/// # Arguments
+/// * `pczt` - The PCZT being signed
+/// * `request` - The transaction request it was proposed from
+/// * `timestamp` - Event time in Unix seconds (supplied by the caller, as WASM has no clock)
+pub fn export_audit_record(
+    pczt: &Pczt,
+    request: &TransactionRequest,
+    timestamp: u64,
+) -> Result<AuditRecord, T2ZError> {
+    let info = inspect_pczt(pczt)?;
+
+    let payments = request
+        .payments
+        .iter()
+        .map(|payment| AuditPayment {
+            address: payment.address.clone(),
+            amount: payment.amount,
+            memo_hash: payment.memo.as_ref().map(|memo| {
+                hex::encode(
+                    blake2b_simd::Params::new()
+                        .hash_length(32)
+                        .personal(AUDIT_MEMO_PERSONALIZATION)
+                        .hash(memo)
+                        .as_bytes(),
+                )
+            }),
+            label: payment.label.clone(),
+        })
+        .collect();
+
+    let is_complete =
+        info.all_inputs_signed && (info.num_orchard_actions == 0 || info.has_orchard_proofs);
+    let txid = if is_complete { extract_txid(pczt) } else { None };
+
+    Ok(AuditRecord {
+        version: AUDIT_RECORD_VERSION,
+        timestamp,
+        txid,
+        consensus_branch_id: *pczt.global().consensus_branch_id(),
+        expiry_height: info.expiry_height,
+        payments,
+        transparent_inputs: info.transparent_inputs,
+        transparent_outputs: info.transparent_outputs,
+        orchard_outputs: info.orchard_outputs,
+        total_input: info.total_input,
+        fee: info.implied_fee,
+        all_inputs_signed: info.all_inputs_signed,
+        has_orchard_proofs: info.has_orchard_proofs,
+    })
+}
+
+/// Transaction ID (hex, display order) of the extracted transaction, if
+/// the PCZT can be finalized and extracted.
+pub(crate) fn extract_txid(pczt: &Pczt) -> Option<String> {
+    let pczt = SpendFinalizer::new(pczt.clone()).finalize_spends().ok()?;
+    let transaction = TransactionExtractor::new(pczt).extract().ok()?;
+    Some(transaction.txid().to_string())
+}
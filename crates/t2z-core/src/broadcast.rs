@@ -0,0 +1,342 @@
+//! Outgoing transaction queue (feature `wallet_lite`).
+//!
+//! A signed transaction is only worth something once a node has it.
+//! `BroadcastQueue` holds finalized transactions until they are mined or
+//! expire, and decides when to submit each one and where: submissions go to
+//! the best endpoint in its `EndpointPool`, so after a failure the next
+//! attempt goes to the next lightwalletd or RPC endpoint, and once every
+//! endpoint has failed it waits before the next round, doubling the delay
+//! each time.
+//!
+//! Like `Account`, the queue talks to no network. `poll` returns the
+//! submissions that are due, callers send them however they reach their
+//! nodes and `report` how each went, and `mark_mined` records the mined
+//! height once their indexer sees the transaction. The queue serializes with
+//! `to_bytes` (or `save_broadcast_queue`), so a transaction signed just
+//! before the app loses connectivity or is killed is still sent later.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Listeners;
+use crate::zip244::read_transaction;
+use crate::{EndpointPool, Expiry, T2ZError, WalletEvent, WalletListener};
+
+/// How long to wait after every endpoint has failed (or, in an
+/// `EndpointPool`, how long a failing endpoint stays out of rotation)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Delay after the first failed round, in seconds
+    pub initial_delay_secs: u64,
+    /// Longest delay between rounds, in seconds
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            initial_delay_secs: 10,
+            max_delay_secs: 600,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after `rounds` (at least 1) consecutive failed rounds.
+    pub(crate) fn delay(&self, rounds: u32) -> u64 {
+        self.initial_delay_secs
+            .saturating_mul(2u64.saturating_pow(rounds.saturating_sub(1)))
+            .min(self.max_delay_secs)
+    }
+}
+
+/// Where a queued transaction is in its lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BroadcastStatus {
+    /// Not yet accepted by any endpoint
+    Pending,
+    /// Accepted into `endpoint`'s mempool; waiting to be mined
+    Accepted { endpoint: String },
+    /// Mined at the given height (final)
+    Mined { height: u32 },
+    /// Reached its expiry height without being mined (final)
+    Expired,
+    /// Rejected as invalid by an endpoint (final)
+    Rejected { reason: String },
+}
+
+impl BroadcastStatus {
+    /// Whether the queue is done with the transaction.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            BroadcastStatus::Mined { .. }
+                | BroadcastStatus::Expired
+                | BroadcastStatus::Rejected { .. }
+        )
+    }
+}
+
+/// A finalized transaction held by a `BroadcastQueue`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedTransaction {
+    /// Transaction ID (hex, display order)
+    pub txid: String,
+    /// The raw transaction
+    pub transaction: Vec<u8>,
+    /// Last height the transaction can be mined at (0 for no expiry)
+    pub expiry_height: u32,
+    /// Lifecycle status
+    pub status: BroadcastStatus,
+    /// Failed submissions so far
+    pub attempts: u32,
+    /// Endpoint of the latest submission
+    pub endpoint: Option<String>,
+    /// Error from the last failed submission
+    pub last_error: Option<String>,
+    /// Earliest time of the next submission (Unix seconds)
+    pub next_attempt_at: u64,
+}
+
+/// A submission `poll` wants made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastAttempt {
+    /// Transaction ID (hex, display order), to pass back to `report`
+    pub txid: String,
+    /// Endpoint to submit to
+    pub endpoint: String,
+    /// The raw transaction
+    pub transaction: Vec<u8>,
+    /// SOCKS5 proxy URL to submit through, if the endpoint pool has a proxy
+    /// (isolated per transaction)
+    pub proxy: Option<String>,
+}
+
+/// How a submission went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubmitResult {
+    /// The endpoint accepted the transaction (or already had it)
+    Accepted,
+    /// The endpoint could not be reached or did not answer; try elsewhere
+    Failed { error: String },
+    /// The endpoint rejected the transaction as invalid; no endpoint will
+    /// accept it
+    Rejected { reason: String },
+}
+
+/// Finalized transactions waiting to be broadcast and mined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastQueue {
+    endpoints: EndpointPool,
+    retry: RetryPolicy,
+    transactions: BTreeMap<String, QueuedTransaction>,
+    #[serde(skip)]
+    listeners: Listeners,
+}
+
+impl BroadcastQueue {
+    /// Creates an empty queue submitting to `endpoints`, in order of
+    /// preference.
+    pub fn new(endpoints: Vec<String>, retry: RetryPolicy) -> Result<Self, T2ZError> {
+        Ok(BroadcastQueue {
+            endpoints: EndpointPool::new(endpoints, retry)?,
+            retry,
+            transactions: BTreeMap::new(),
+            listeners: Listeners::default(),
+        })
+    }
+
+    /// Replaces the endpoints (e.g. from updated app settings after
+    /// `from_bytes`), forgetting their health but keeping the proxy.
+    pub fn set_endpoints(&mut self, endpoints: Vec<String>) -> Result<(), T2ZError> {
+        let proxy = self.endpoints.proxy().cloned();
+        self.endpoints = EndpointPool::new(endpoints, self.retry)?;
+        self.endpoints.set_proxy(proxy);
+        Ok(())
+    }
+
+    /// The endpoints and their health.
+    pub fn endpoints(&self) -> &EndpointPool {
+        &self.endpoints
+    }
+
+    /// The endpoints, e.g. to record health checks or the results of other
+    /// requests so broadcasts avoid failing endpoints too.
+    pub fn endpoints_mut(&mut self) -> &mut EndpointPool {
+        &mut self.endpoints
+    }
+
+    /// Calls `listener` with a `TxConfirmed` event when a transaction is
+    /// mined and a `TxExpired` event when one expires or is rejected.
+    pub fn subscribe(&mut self, listener: impl WalletListener + 'static) {
+        self.listeners.subscribe(listener);
+    }
+
+    /// Queues a finalized transaction (e.g. from `finalize_and_extract`) for
+    /// submission at the next `poll`, and returns its txid.
+    ///
+    /// Queuing a transaction that is already queued changes nothing.
+    pub fn enqueue(&mut self, tx_bytes: &[u8], now: u64) -> Result<String, T2ZError> {
+        let transaction = read_transaction(tx_bytes)?;
+        let txid = transaction.txid().to_string();
+
+        if !self.transactions.contains_key(&txid) {
+            self.transactions.insert(
+                txid.clone(),
+                QueuedTransaction {
+                    txid: txid.clone(),
+                    transaction: tx_bytes.to_vec(),
+                    expiry_height: transaction.expiry_height().into(),
+                    status: BroadcastStatus::Pending,
+                    attempts: 0,
+                    endpoint: None,
+                    last_error: None,
+                    next_attempt_at: now,
+                },
+            );
+        }
+        Ok(txid)
+    }
+
+    /// Expires transactions that can no longer be mined after `tip_height`,
+    /// and returns the submissions due at `now`: at most one per pending
+    /// transaction. Report each one's result with `report`.
+    ///
+    /// Each submission goes to the pool's pick at `now`, so after a failure
+    /// the next one goes to the next healthy endpoint.
+    pub fn poll(&mut self, now: u64, tip_height: u32) -> Vec<BroadcastAttempt> {
+        let mut attempts = Vec::new();
+        let mut expired = Vec::new();
+
+        for tx in self.transactions.values_mut() {
+            if tx.status.is_final() {
+                continue;
+            }
+            let expiry = Expiry {
+                expiry_height: tx.expiry_height,
+            };
+            if expiry.is_expired(tip_height) {
+                tx.status = BroadcastStatus::Expired;
+                expired.push(tx.txid.clone());
+                continue;
+            }
+
+            if tx.status == BroadcastStatus::Pending && now >= tx.next_attempt_at {
+                let endpoint = self.endpoints.pick(now).to_string();
+                tx.endpoint = Some(endpoint.clone());
+                attempts.push(BroadcastAttempt {
+                    txid: tx.txid.clone(),
+                    endpoint,
+                    transaction: tx.transaction.clone(),
+                    proxy: self.endpoints.proxy_url(&tx.txid),
+                });
+            }
+        }
+
+        for txid in expired {
+            self.listeners.emit(WalletEvent::TxExpired { txid });
+        }
+        attempts
+    }
+
+    /// Records the result of submitting `txid` to the endpoint `poll` chose.
+    ///
+    /// The result also updates the endpoint's health. A failure moves the
+    /// transaction on to the next endpoint right away; once it has failed as
+    /// many times in a row as there are endpoints, the next round waits
+    /// according to the retry policy. Results for transactions that are no
+    /// longer pending (e.g. a late report after expiry) are ignored.
+    pub fn report(&mut self, txid: &str, result: SubmitResult, now: u64) -> Result<(), T2ZError> {
+        let tx = self
+            .transactions
+            .get_mut(txid)
+            .ok_or_else(|| T2ZError::InvalidInput(format!("Transaction {} is not queued", txid)))?;
+        if tx.status != BroadcastStatus::Pending {
+            return Ok(());
+        }
+        let endpoint = tx.endpoint.clone().ok_or_else(|| {
+            T2ZError::InvalidInput(format!("Transaction {} has not been submitted", txid))
+        })?;
+
+        match result {
+            SubmitResult::Accepted => {
+                self.endpoints.record_success(&endpoint);
+                tx.status = BroadcastStatus::Accepted { endpoint };
+                tx.last_error = None;
+            }
+            SubmitResult::Failed { error } => {
+                self.endpoints.record_failure(&endpoint, &error, now);
+                tx.attempts = tx.attempts.saturating_add(1);
+                tx.last_error = Some(error);
+
+                let endpoints = self.endpoints.endpoints().len() as u32;
+                tx.next_attempt_at = if tx.attempts % endpoints == 0 {
+                    now.saturating_add(self.retry.delay(tx.attempts / endpoints))
+                } else {
+                    now
+                };
+            }
+            SubmitResult::Rejected { reason } => {
+                // The endpoint answered; the transaction is what failed
+                self.endpoints.record_success(&endpoint);
+                tx.attempts = tx.attempts.saturating_add(1);
+                tx.last_error = Some(reason.clone());
+                tx.status = BroadcastStatus::Rejected { reason };
+                self.listeners.emit(WalletEvent::TxExpired {
+                    txid: txid.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that `txid` was mined at `height`. Returns whether it was
+    /// queued.
+    pub fn mark_mined(&mut self, txid: &str, height: u32) -> bool {
+        let Some(tx) = self.transactions.get_mut(txid) else {
+            return false;
+        };
+        tx.status = BroadcastStatus::Mined { height };
+        self.listeners.emit(WalletEvent::TxConfirmed {
+            txid: txid.to_string(),
+            height: Some(height),
+        });
+        true
+    }
+
+    /// The queued transaction with `txid`, if any.
+    pub fn get(&self, txid: &str) -> Option<&QueuedTransaction> {
+        self.transactions.get(txid)
+    }
+
+    /// All queued transactions, in txid order.
+    pub fn transactions(&self) -> impl Iterator<Item = &QueuedTransaction> {
+        self.transactions.values()
+    }
+
+    /// Removes and returns the mined, expired, and rejected transactions.
+    pub fn remove_finished(&mut self) -> Vec<QueuedTransaction> {
+        let finished: Vec<String> = self
+            .transactions
+            .values()
+            .filter(|tx| tx.status.is_final())
+            .map(|tx| tx.txid.clone())
+            .collect();
+        finished
+            .iter()
+            .filter_map(|txid| self.transactions.remove(txid))
+            .collect()
+    }
+
+    /// Serializes the queue.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized queue.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid broadcast queue: {:?}", e)))
+    }
+}
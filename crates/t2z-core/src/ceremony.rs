@@ -0,0 +1,162 @@
+//! Signed, transport-agnostic messages for signing ceremonies.
+//!
+//! `CoordinationMessage` covers every step of a ceremony (the proposal,
+//! partial signatures, the Orchard proof, and the final transaction) so
+//! integrators can run one over email, Nostr, QR codes, or anything else
+//! that carries bytes. Each message is signed with the sender's secp256k1
+//! key, and recipients verify it against the key they know for the sender
+//! before acting on it.
+//!
+//! Partial-signature and proof messages convert to `SignerContribution`s
+//! for a `SigningSession`, which still checks what each one changed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Participant, Pczt, SignerContribution, T2ZError};
+
+/// BLAKE2b personalization for coordination message signatures
+const MESSAGE_DIGEST_PERSONALIZATION: &[u8; 16] = b"t2z_Coordination";
+
+/// What a coordination message carries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CoordinationPayload {
+    /// Coordinator -> participants: the proposed PCZT and who does what
+    Proposal {
+        pczt: Vec<u8>,
+        participants: Vec<Participant>,
+    },
+    /// Signer -> coordinator: the PCZT with the signer's signatures added
+    PartialSignature { pczt: Vec<u8> },
+    /// Prover -> coordinator: the PCZT with the Orchard proof added
+    Proof { pczt: Vec<u8> },
+    /// Coordinator -> participants: the extracted transaction
+    Final { transaction: Vec<u8> },
+}
+
+/// One step of a ceremony, from one participant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CoordinationMessage {
+    /// Session this message belongs to
+    pub session_id: String,
+    /// Participant that sent it
+    pub sender: String,
+    /// The step itself
+    pub payload: CoordinationPayload,
+}
+
+/// A coordination message with its sender's signature
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedCoordinationMessage {
+    /// The signed message (not trustworthy until `verify` succeeds)
+    pub message: CoordinationMessage,
+    /// Compact (64-byte) ECDSA signature over the message digest
+    pub signature: Vec<u8>,
+}
+
+impl CoordinationMessage {
+    /// Creates a message.
+    pub fn new(
+        session_id: impl Into<String>,
+        sender: impl Into<String>,
+        payload: CoordinationPayload,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            sender: sender.into(),
+            payload,
+        }
+    }
+
+    /// The PCZT carried by a proposal, partial-signature, or proof message.
+    pub fn pczt(&self) -> Result<Pczt, T2ZError> {
+        match &self.payload {
+            CoordinationPayload::Proposal { pczt, .. }
+            | CoordinationPayload::PartialSignature { pczt }
+            | CoordinationPayload::Proof { pczt } => crate::parse_pczt(pczt),
+            CoordinationPayload::Final { .. } => Err(T2ZError::InvalidInput(
+                "Final messages carry a transaction, not a PCZT".to_string(),
+            )),
+        }
+    }
+
+    /// The contribution to add to a `SigningSession`, for partial-signature
+    /// and proof messages.
+    pub fn contribution(&self) -> Option<SignerContribution> {
+        match &self.payload {
+            CoordinationPayload::PartialSignature { pczt }
+            | CoordinationPayload::Proof { pczt } => Some(SignerContribution {
+                session_id: self.session_id.clone(),
+                participant_id: self.sender.clone(),
+                pczt: pczt.clone(),
+            }),
+            CoordinationPayload::Proposal { .. } | CoordinationPayload::Final { .. } => None,
+        }
+    }
+
+    /// Signs the message with the sender's secp256k1 secret key.
+    pub fn sign(self, secret_key: &[u8; 32]) -> Result<SignedCoordinationMessage, T2ZError> {
+        let secret_key = secp256k1::SecretKey::from_slice(secret_key)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+
+        let message = secp256k1::Message::from_digest(self.digest());
+        let signature = secp256k1::Secp256k1::signing_only()
+            .sign_ecdsa(&message, &secret_key)
+            .serialize_compact()
+            .to_vec();
+
+        Ok(SignedCoordinationMessage {
+            message: self,
+            signature,
+        })
+    }
+
+    fn digest(&self) -> [u8; 32] {
+        let bytes = postcard::to_allocvec(self).expect("can serialize into memory");
+        let hash = blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(MESSAGE_DIGEST_PERSONALIZATION)
+            .hash(&bytes);
+
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(hash.as_bytes());
+        digest
+    }
+}
+
+impl SignedCoordinationMessage {
+    /// Checks the signature against the sender's public key (33 bytes,
+    /// compressed) and returns the message.
+    ///
+    /// Callers look up `message.sender`'s key from their own records; a key
+    /// supplied alongside the message proves nothing.
+    pub fn verify(&self, public_key: &[u8; 33]) -> Result<&CoordinationMessage, T2ZError> {
+        let public_key = secp256k1::PublicKey::from_slice(public_key)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
+        let signature = secp256k1::ecdsa::Signature::from_compact(&self.signature)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid signature: {}", e)))?;
+
+        let digest = secp256k1::Message::from_digest(self.message.digest());
+        secp256k1::Secp256k1::verification_only()
+            .verify_ecdsa(&digest, &signature, &public_key)
+            .map_err(|_| {
+                T2ZError::InvalidInput(format!(
+                    "Message signature does not match participant {}",
+                    self.message.sender
+                ))
+            })?;
+
+        Ok(&self.message)
+    }
+
+    /// Serializes the message for transport.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a message received over the wire.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes).map_err(|e| {
+            T2ZError::InvalidInput(format!("Invalid coordination message: {:?}", e))
+        })
+    }
+}
@@ -0,0 +1,162 @@
+//! Spending change before it confirms (zero-conf chaining).
+//!
+//! A service that pays out more often than blocks arrive cannot wait for
+//! each transaction's change to confirm before spending it again.
+//! `spend_unconfirmed_change` builds a child transaction spending the
+//! transparent change of a parent that is still in the mempool. The parent
+//! can be given as a PCZT (signed or not: v5 txids do not commit to
+//! signatures) or as the raw transaction that was broadcast.
+//!
+//! Zcash has no replace-by-fee: a stuck transaction is replaced by letting
+//! it expire and spending its inputs again. A child can never be mined
+//! without its parent, so the child is refused an expiry height later than
+//! the parent's; when the parent expires, the whole chain is gone and every
+//! input is free to spend again.
+
+use crate::keys::p2pkh_script;
+use crate::shadow::PcztShadow;
+use crate::status::pubkey_hash;
+use crate::zip244::{pczt_txid, read_transaction};
+use crate::{
+    InputConfirmations, Network, Pczt, ProposalOptions, T2ZError, TransactionRequest,
+    TransparentInput, propose_transaction_with_options,
+};
+
+/// The transaction whose change a child spends
+#[derive(Debug, Clone, Copy)]
+pub enum ParentTransaction<'a> {
+    /// A PCZT, signed or not
+    Pczt(&'a Pczt),
+    /// A raw transaction
+    Transaction(&'a [u8]),
+}
+
+/// A parent's ID and transparent outputs
+struct ParentOutputs {
+    txid: [u8; 32],
+    /// (value, scriptPubKey) of each transparent output
+    outputs: Vec<(u64, Vec<u8>)>,
+    expiry_height: u32,
+}
+
+impl ParentTransaction<'_> {
+    fn outputs(&self) -> Result<ParentOutputs, T2ZError> {
+        match self {
+            ParentTransaction::Pczt(pczt) => {
+                let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+                Ok(ParentOutputs {
+                    txid: pczt_txid(pczt)?,
+                    outputs: pczt_shadow
+                        .transparent
+                        .outputs
+                        .into_iter()
+                        .map(|output| (output.value, output.script_pubkey))
+                        .collect(),
+                    expiry_height: pczt_shadow.global.expiry_height,
+                })
+            }
+            ParentTransaction::Transaction(tx_bytes) => {
+                let transaction = read_transaction(tx_bytes)?;
+                let outputs = transaction
+                    .transparent_bundle()
+                    .map(|bundle| {
+                        bundle
+                            .vout
+                            .iter()
+                            .map(|out| (out.value().into_u64(), out.script_pubkey().0.0.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Ok(ParentOutputs {
+                    txid: *transaction.txid().as_ref(),
+                    outputs,
+                    expiry_height: u32::from(transaction.expiry_height()),
+                })
+            }
+        }
+    }
+}
+
+/// The parent's transparent outputs paying `change_pubkey` (33-byte
+/// compressed public key), as inputs for a child transaction.
+pub fn unconfirmed_change_inputs(
+    parent: ParentTransaction<'_>,
+    change_pubkey: &[u8],
+) -> Result<Vec<TransparentInput>, T2ZError> {
+    change_inputs(parent.outputs()?, change_pubkey)
+}
+
+fn change_inputs(
+    parent: ParentOutputs,
+    change_pubkey: &[u8],
+) -> Result<Vec<TransparentInput>, T2ZError> {
+    let pubkey = secp256k1::PublicKey::from_slice(change_pubkey)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
+    let script_pubkey = p2pkh_script(&pubkey_hash(&pubkey));
+
+    let inputs: Vec<TransparentInput> = parent
+        .outputs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (_, script))| *script == script_pubkey)
+        .map(|(index, (value, script))| TransparentInput {
+            pubkey: change_pubkey.to_vec(),
+            prevout_txid: parent.txid.to_vec(),
+            prevout_index: index as u32,
+            value,
+            script_pubkey: script,
+            sequence: None,
+        })
+        .collect();
+
+    if inputs.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "Parent transaction has no transparent output paying the change key".to_string(),
+        ));
+    }
+    Ok(inputs)
+}
+
+/// Proposes a child transaction spending the parent's unconfirmed
+/// transparent change.
+///
+/// # Arguments
+/// * `parent` - The transaction whose change is spent
+/// * `change_pubkey` - Public key of the parent's transparent change address
+/// * `request` - ZIP 321 transaction request (payments only)
+/// * `change_address` - Optional address for the child's own change
+/// * `network` - Mainnet or Testnet
+/// * `options` - Fee, expiry, padding, change, and policy options; the
+///   expiry may not be later than the parent's, and the inputs are recorded
+///   as unconfirmed, so a `ConfirmationPolicy` needing confirmations fails
+pub fn spend_unconfirmed_change(
+    parent: ParentTransaction<'_>,
+    change_pubkey: &[u8],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    options: ProposalOptions<'_>,
+) -> Result<Pczt, T2ZError> {
+    let parent = parent.outputs()?;
+    let parent_expiry = parent.expiry_height;
+    let inputs = change_inputs(parent, change_pubkey)?;
+
+    let expiry_height = options.expiry.expiry_height(options.current_height)?;
+    if parent_expiry != 0 && (expiry_height == 0 || expiry_height > parent_expiry) {
+        return Err(T2ZError::InvalidInput(format!(
+            "Child would expire at height {} but its parent expires at {}",
+            expiry_height, parent_expiry
+        )));
+    }
+
+    propose_transaction_with_options(
+        &inputs,
+        request,
+        change_address,
+        network,
+        ProposalOptions {
+            input_confirmations: vec![InputConfirmations::default(); inputs.len()],
+            ..options
+        },
+    )
+}
@@ -0,0 +1,114 @@
+//! Minimum-confirmation checks on the inputs of a proposal.
+//!
+//! `TransparentInput` says nothing about where its UTXO is in the chain, so
+//! a service that feeds freshly seen deposits straight into a proposal can
+//! end up spending mempool outputs, which vanish if their transaction never
+//! confirms, or coinbase outputs the consensus rules do not let it spend yet.
+//! Pass each input's confirmations in `ProposalOptions::input_confirmations`
+//! and set `ProposalOptions::confirmations` to refuse such inputs.
+//!
+//! Confirmations count the block the output was mined in: an output mined at
+//! the chain tip has one, a mempool output has none.
+//!
+//! By consensus, coinbase outputs may only be spent by fully shielded
+//! transactions, with no transparent outputs at all, so marking an input as
+//! coinbase makes proposing refuse transparent payments and change. Mining
+//! pools that do not track which UTXOs are coinbase can check the funding
+//! transaction with `is_coinbase_transaction`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::T2ZError;
+use crate::zip244::read_transaction;
+
+/// Confirmations a coinbase output needs before it can be spent
+pub const COINBASE_MATURITY: u32 = 100;
+
+/// Whether the raw transaction `tx_bytes` (e.g. from `getrawtransaction`)
+/// is a coinbase transaction, i.e. whether its outputs are coinbase outputs.
+pub fn is_coinbase_transaction(tx_bytes: &[u8]) -> Result<bool, T2ZError> {
+    let transaction = read_transaction(tx_bytes)?;
+    Ok(transaction
+        .transparent_bundle()
+        .is_some_and(|bundle| bundle.is_coinbase()))
+}
+
+/// Where one input's UTXO is in the chain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputConfirmations {
+    /// Blocks mined on top of the output, counting its own (0 if unmined)
+    pub confirmations: u32,
+    /// Whether the output is from a coinbase transaction
+    pub coinbase: bool,
+}
+
+impl InputConfirmations {
+    /// Confirmations of an output mined at `mined_height` (`None` if only in
+    /// the mempool) when the chain tip is at `tip_height`.
+    pub fn at_height(mined_height: Option<u32>, tip_height: u32, coinbase: bool) -> Self {
+        let confirmations = match mined_height {
+            Some(height) if height <= tip_height => tip_height - height + 1,
+            _ => 0,
+        };
+        InputConfirmations {
+            confirmations,
+            coinbase,
+        }
+    }
+}
+
+/// Confirmations inputs need before a proposal may spend them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationPolicy {
+    /// Confirmations every input needs
+    pub min_confirmations: u32,
+    /// Confirmations coinbase inputs need (never less than
+    /// `COINBASE_MATURITY`, whatever this says)
+    pub min_coinbase_confirmations: u32,
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        ConfirmationPolicy {
+            min_confirmations: 1,
+            min_coinbase_confirmations: COINBASE_MATURITY,
+        }
+    }
+}
+
+impl ConfirmationPolicy {
+    /// Confirmations an input needs.
+    pub fn required(&self, input: &InputConfirmations) -> u32 {
+        if input.coinbase {
+            self.min_confirmations
+                .max(self.min_coinbase_confirmations)
+                .max(COINBASE_MATURITY)
+        } else {
+            self.min_confirmations
+        }
+    }
+
+    /// Fails with `T2ZError::InsufficientConfirmations` for the first input
+    /// with too few confirmations. `inputs` lists the confirmations of every
+    /// input of the proposal, in order.
+    pub fn check(&self, input_count: usize, inputs: &[InputConfirmations]) -> Result<(), T2ZError> {
+        if inputs.len() != input_count {
+            return Err(T2ZError::InvalidInput(format!(
+                "Confirmations given for {} inputs, but there are {}",
+                inputs.len(),
+                input_count
+            )));
+        }
+        for (index, input) in inputs.iter().enumerate() {
+            let required = self.required(input);
+            if input.confirmations < required {
+                return Err(T2ZError::InsufficientConfirmations {
+                    index,
+                    confirmations: input.confirmations,
+                    required,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+//! Stateless pre-broadcast checks on extracted transactions.
+//!
+//! `check_consensus_rules` catches the mistakes that make a node reject a
+//! transaction without looking at the chain: it is too big to relay, built
+//! for the wrong network upgrade, already expired (or about to be), pays out
+//! more than can exist, or has non-standard scripts. Broadcast services can
+//! run it before spending an attempt (and a rate-limited endpoint's patience)
+//! on a transaction that was never going to be accepted.
+//!
+//! The checks are local only. Whether the inputs exist, are unspent, and
+//! cover the outputs needs the UTXO set, and signatures need the spent
+//! outputs; nodes check those.
+
+use serde::{Deserialize, Serialize};
+use zcash_primitives::transaction::Transaction;
+use zcash_protocol::consensus::{BlockHeight, BranchId, MainNetwork, TestNetwork};
+use zcash_protocol::value::MAX_MONEY;
+
+use crate::script_sig::is_p2pkh;
+use crate::{Network, T2ZError};
+
+/// Largest transaction zcashd relays, in bytes
+pub const MAX_STANDARD_TX_SIZE: usize = 100_000;
+
+/// Largest standard scriptSig, in bytes (a 15-of-15 P2SH multisig spend)
+pub const MAX_STANDARD_SCRIPT_SIG_SIZE: usize = 1650;
+
+/// zcashd refuses transactions expiring within this many blocks of the
+/// height they would be mined at
+pub const TX_EXPIRING_SOON_THRESHOLD: u32 = 3;
+
+/// Expiry heights must be below this
+const TX_EXPIRY_HEIGHT_THRESHOLD: u32 = 500_000_000;
+
+/// (header, version group ID) of v4 and v5 transactions
+const V4_VERSION: (u32, u32) = (0x8000_0004, 0x892F_2085);
+const V5_VERSION: (u32, u32) = (0x8000_0005, 0x26A7_270A);
+
+/// What is wrong with a transaction
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TxIssueKind {
+    /// Larger than `MAX_STANDARD_TX_SIZE`
+    TooLarge,
+    /// Not a v4 or v5 transaction
+    UnsupportedVersion,
+    /// Built for a different consensus branch than the one at the height
+    WrongBranch,
+    /// Expiry height is before the height
+    Expired,
+    /// Expires within `TX_EXPIRING_SOON_THRESHOLD` blocks of the height
+    ExpiringSoon,
+    /// Expiry height is not a valid block height
+    InvalidExpiry,
+    /// No inputs (transparent or shielded) or no outputs
+    Empty,
+    /// The transparent outputs total more than 21M ZEC
+    ValueOutOfRange,
+    /// A transparent output that is not P2PKH or P2SH
+    NonStandardOutput,
+    /// A transparent input without a signature
+    UnsignedInput,
+    /// A transparent input whose scriptSig is not small and push-only
+    NonStandardInput,
+}
+
+/// One problem found by `check_consensus_rules`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TxIssue {
+    /// Index of the transparent input or output the issue is about
+    pub index: Option<usize>,
+    /// What is wrong
+    pub kind: TxIssueKind,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl TxIssue {
+    fn new(index: Option<usize>, kind: TxIssueKind, message: String) -> Self {
+        TxIssue {
+            index,
+            kind,
+            message,
+        }
+    }
+}
+
+/// Checks a raw transaction for problems that would get it rejected if
+/// broadcast for inclusion at `height` (usually the chain tip plus one).
+///
+/// Returns every problem found (empty if none); fails only if `tx_bytes` is
+/// not a transaction.
+pub fn check_consensus_rules(
+    tx_bytes: &[u8],
+    network: Network,
+    height: u32,
+) -> Result<Vec<TxIssue>, T2ZError> {
+    let block_height = BlockHeight::from_u32(height);
+    let branch_id = match network {
+        Network::Mainnet => BranchId::for_height(&MainNetwork, block_height),
+        Network::Testnet => BranchId::for_height(&TestNetwork, block_height),
+    };
+    // v5 transactions carry their own branch ID; this one only applies to v4
+    let transaction = Transaction::read(tx_bytes, branch_id)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid transaction: {}", e)))?;
+
+    let mut issues = Vec::new();
+
+    if tx_bytes.len() > MAX_STANDARD_TX_SIZE {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::TooLarge,
+            format!(
+                "Transaction is {} bytes; nodes relay at most {}",
+                tx_bytes.len(),
+                MAX_STANDARD_TX_SIZE
+            ),
+        ));
+    }
+
+    let version = transaction.version();
+    let version = (version.header(), version.version_group_id());
+    if version != V4_VERSION && version != V5_VERSION {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::UnsupportedVersion,
+            format!(
+                "Transaction version {:#010x} (group {:#010x}) is not v4 or v5",
+                version.0, version.1
+            ),
+        ));
+    }
+    if transaction.consensus_branch_id() != branch_id {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::WrongBranch,
+            format!(
+                "Transaction is for branch {:?}, but height {} is in {:?}",
+                transaction.consensus_branch_id(),
+                height,
+                branch_id
+            ),
+        ));
+    }
+
+    check_expiry(u32::from(transaction.expiry_height()), height, &mut issues);
+    check_transparent(&transaction, &mut issues);
+
+    Ok(issues)
+}
+
+fn check_expiry(expiry_height: u32, height: u32, issues: &mut Vec<TxIssue>) {
+    if expiry_height == 0 {
+        return;
+    }
+    if expiry_height >= TX_EXPIRY_HEIGHT_THRESHOLD {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::InvalidExpiry,
+            format!("Expiry height {} is not a block height", expiry_height),
+        ));
+    } else if expiry_height < height {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::Expired,
+            format!("Transaction expired at height {}", expiry_height),
+        ));
+    } else if expiry_height < height.saturating_add(TX_EXPIRING_SOON_THRESHOLD) {
+        // zcashd's IsExpiringSoonTx: already expired `TX_EXPIRING_SOON_THRESHOLD`
+        // blocks past the height, so expiring exactly then is still relayed
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::ExpiringSoon,
+            format!(
+                "Transaction expires at height {}, too soon after {} to be relayed",
+                expiry_height, height
+            ),
+        ));
+    }
+}
+
+fn check_transparent(transaction: &Transaction, issues: &mut Vec<TxIssue>) {
+    let (vin, vout) = match transaction.transparent_bundle() {
+        Some(bundle) => (&bundle.vin[..], &bundle.vout[..]),
+        None => (&[][..], &[][..]),
+    };
+    let orchard = transaction.orchard_bundle();
+    let sapling = transaction.sapling_bundle();
+
+    let shielded_inputs = orchard.is_some_and(|b| !b.actions().is_empty())
+        || sapling.is_some_and(|b| !b.shielded_spends().is_empty());
+    let shielded_outputs = orchard.is_some_and(|b| !b.actions().is_empty())
+        || sapling.is_some_and(|b| !b.shielded_outputs().is_empty());
+    if (vin.is_empty() && !shielded_inputs) || (vout.is_empty() && !shielded_outputs) {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::Empty,
+            "Transaction needs both inputs and outputs".to_string(),
+        ));
+    }
+
+    let mut total = 0u64;
+    for (index, output) in vout.iter().enumerate() {
+        total = total.saturating_add(output.value().into_u64());
+        let script = &output.script_pubkey().0.0;
+        if !is_p2pkh(script) && !is_p2sh(script) {
+            issues.push(TxIssue::new(
+                Some(index),
+                TxIssueKind::NonStandardOutput,
+                format!("Output {} script is neither P2PKH nor P2SH", index),
+            ));
+        }
+    }
+    if total > MAX_MONEY {
+        issues.push(TxIssue::new(
+            None,
+            TxIssueKind::ValueOutOfRange,
+            "Transparent outputs total more than 21M ZEC".to_string(),
+        ));
+    }
+
+    for (index, input) in vin.iter().enumerate() {
+        let script_sig = &input.script_sig().0.0;
+        if script_sig.is_empty() {
+            issues.push(TxIssue::new(
+                Some(index),
+                TxIssueKind::UnsignedInput,
+                format!("Input {} is not signed", index),
+            ));
+        } else if script_sig.len() > MAX_STANDARD_SCRIPT_SIG_SIZE || !is_push_only(script_sig) {
+            issues.push(TxIssue::new(
+                Some(index),
+                TxIssueKind::NonStandardInput,
+                format!("Input {} scriptSig is not a small push-only script", index),
+            ));
+        }
+    }
+}
+
+fn is_p2sh(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 23 && script_pubkey[..2] == [0xa9, 0x14] && script_pubkey[22] == 0x87
+}
+
+/// Whether `script` only pushes data (opcodes up to OP_16)
+fn is_push_only(script: &[u8]) -> bool {
+    let mut pos = 0;
+    while pos < script.len() {
+        let opcode = script[pos];
+        pos += 1;
+        let len = match opcode {
+            0x01..=0x4b => opcode as usize,
+            0x4c..=0x4e => {
+                let width = 1 << (opcode - 0x4c);
+                let Some(bytes) = script.get(pos..pos + width) else {
+                    return false;
+                };
+                pos += width;
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0usize, |len, &b| (len << 8) | b as usize)
+            }
+            0x00 | 0x4f..=0x60 => 0,
+            _ => return false,
+        };
+        pos = match pos.checked_add(len) {
+            Some(end) if end <= script.len() => end,
+            _ => return false,
+        };
+    }
+    true
+}
@@ -0,0 +1,334 @@
+//! Multi-party signing coordination.
+//!
+//! A simple, transport-agnostic message flow for M-of-N shielding ceremonies
+//! built around the Combiner role:
+//!
+//! 1. The coordinator proposes a PCZT and opens a `SigningSession`, assigning
+//!    transparent inputs to participants.
+//! 2. `SigningSession::signing_requests` produces one `SigningRequest` per
+//!    signer, each carrying its own copy of the PCZT.
+//! 3. Each signer signs its inputs and answers with `SignerContribution::new`.
+//! 4. The coordinator feeds contributions back with `add_contribution` (which
+//!    checks that only the assigned inputs gained signatures) and calls
+//!    `combine` once every signer has contributed.
+//!
+//! All messages are serde-serializable and have `to_bytes`/`from_bytes`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::shadow::PcztShadow;
+use crate::{Pczt, T2ZError};
+
+/// BLAKE2b personalization for comparing PCZTs with signatures stripped
+const UNSIGNED_DIGEST_PERSONALIZATION: &[u8; 16] = b"t2z_UnsignedPczt";
+
+/// What a participant does in the ceremony
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Role {
+    /// Signs the transparent inputs assigned to it
+    Signer,
+    /// Adds Orchard proofs (receives a copy but signs nothing)
+    Prover,
+    /// Only reviews the PCZT (e.g. a compliance approver)
+    Observer,
+}
+
+/// A participant and the transparent inputs it is responsible for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Participant {
+    /// Caller-chosen identifier, unique within the session
+    pub id: String,
+    /// Role in the ceremony
+    pub role: Role,
+    /// Transparent input indices this participant signs (Signers only)
+    pub input_indices: Vec<u32>,
+}
+
+/// Coordinator -> participant: a PCZT copy to act on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningRequest {
+    /// Session this request belongs to
+    pub session_id: String,
+    /// Participant the request is addressed to
+    pub participant_id: String,
+    /// Role the participant plays
+    pub role: Role,
+    /// Transparent input indices the participant should sign
+    pub input_indices: Vec<u32>,
+    /// Serialized PCZT
+    pub pczt: Vec<u8>,
+}
+
+/// Participant -> coordinator: the participant's updated PCZT copy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerContribution {
+    /// Session this contribution belongs to
+    pub session_id: String,
+    /// Participant that produced it
+    pub participant_id: String,
+    /// Serialized PCZT with the participant's signatures (or proofs) added
+    pub pczt: Vec<u8>,
+}
+
+macro_rules! impl_message_bytes {
+    ($ty:ty, $name:literal) => {
+        impl $ty {
+            /// Serializes the message for transport.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                postcard::to_allocvec(self).expect("can serialize into memory")
+            }
+
+            /// Parses a message received over the wire.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+                postcard::from_bytes(bytes).map_err(|e| {
+                    T2ZError::InvalidInput(format!(concat!("Invalid ", $name, ": {:?}"), e))
+                })
+            }
+        }
+    };
+}
+
+impl_message_bytes!(SigningRequest, "signing request");
+impl_message_bytes!(SignerContribution, "signer contribution");
+impl_message_bytes!(SigningSession, "signing session");
+
+impl SigningRequest {
+    /// Parses the PCZT carried by this request.
+    pub fn pczt(&self) -> Result<Pczt, T2ZError> {
+        crate::parse_pczt(&self.pczt)
+    }
+}
+
+impl SignerContribution {
+    /// Wraps a participant's updated PCZT as a reply to `request`.
+    pub fn new(request: &SigningRequest, pczt: &Pczt) -> Self {
+        Self {
+            session_id: request.session_id.clone(),
+            participant_id: request.participant_id.clone(),
+            pczt: pczt.serialize(),
+        }
+    }
+}
+
+/// Coordinator-side state for one ceremony.
+///
+/// Serializable so a coordinator can persist sessions between messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningSession {
+    /// Caller-chosen session identifier
+    pub session_id: String,
+    /// The proposed PCZT every participant starts from
+    pub pczt: Vec<u8>,
+    /// Participants and their assignments
+    pub participants: Vec<Participant>,
+    /// Accepted contributions (at most one per participant)
+    pub contributions: Vec<SignerContribution>,
+}
+
+impl SigningSession {
+    /// Opens a session for `pczt`.
+    ///
+    /// Every transparent input must be assigned to at least one Signer, and
+    /// participant ids must be unique. Inputs may be assigned to several
+    /// signers (e.g. P2SH multisig).
+    pub fn new(
+        session_id: impl Into<String>,
+        pczt: &Pczt,
+        participants: Vec<Participant>,
+    ) -> Result<Self, T2ZError> {
+        let num_inputs = pczt.transparent().inputs().len();
+        let mut covered = vec![false; num_inputs];
+
+        for (idx, participant) in participants.iter().enumerate() {
+            if participants[..idx].iter().any(|p| p.id == participant.id) {
+                return Err(T2ZError::InvalidInput(format!(
+                    "Duplicate participant id: {}",
+                    participant.id
+                )));
+            }
+
+            if participant.role != Role::Signer && !participant.input_indices.is_empty() {
+                return Err(T2ZError::InvalidInput(format!(
+                    "Participant {} is not a Signer but has inputs assigned",
+                    participant.id
+                )));
+            }
+
+            for &input_index in &participant.input_indices {
+                let slot = covered.get_mut(input_index as usize).ok_or_else(|| {
+                    T2ZError::InvalidInput(format!(
+                        "Participant {} assigned nonexistent input {}",
+                        participant.id, input_index
+                    ))
+                })?;
+                *slot = true;
+            }
+        }
+
+        if let Some(unassigned) = covered.iter().position(|c| !c) {
+            return Err(T2ZError::InvalidInput(format!(
+                "Input {} is not assigned to any signer",
+                unassigned
+            )));
+        }
+
+        Ok(Self {
+            session_id: session_id.into(),
+            pczt: pczt.serialize(),
+            participants,
+            contributions: Vec::new(),
+        })
+    }
+
+    /// One request per participant, each with its own PCZT copy.
+    pub fn signing_requests(&self) -> Vec<SigningRequest> {
+        self.participants
+            .iter()
+            .map(|p| SigningRequest {
+                session_id: self.session_id.clone(),
+                participant_id: p.id.clone(),
+                role: p.role,
+                input_indices: p.input_indices.clone(),
+                pczt: self.pczt.clone(),
+            })
+            .collect()
+    }
+
+    fn has_contributed(&self, participant_id: &str) -> bool {
+        self.contributions
+            .iter()
+            .any(|c| c.participant_id == participant_id)
+    }
+
+    /// Validates and records a participant's contribution.
+    ///
+    /// Signers may only add signatures to their assigned inputs; everything
+    /// else about the PCZT must be unchanged. Provers may only add the Orchard
+    /// proof. Observers don't contribute.
+    pub fn add_contribution(&mut self, contribution: SignerContribution) -> Result<(), T2ZError> {
+        if contribution.session_id != self.session_id {
+            return Err(T2ZError::InvalidInput(format!(
+                "Contribution is for session {}, not {}",
+                contribution.session_id, self.session_id
+            )));
+        }
+
+        let participant = self
+            .participants
+            .iter()
+            .find(|p| p.id == contribution.participant_id)
+            .ok_or_else(|| {
+                T2ZError::InvalidInput(format!(
+                    "Unknown participant: {}",
+                    contribution.participant_id
+                ))
+            })?;
+
+        if self.has_contributed(&participant.id) {
+            return Err(T2ZError::InvalidInput(format!(
+                "Participant {} already contributed",
+                participant.id
+            )));
+        }
+
+        let (_, original) = PcztShadow::decode(&self.pczt)?;
+        let (_, updated) = PcztShadow::decode(&contribution.pczt)?;
+
+        match participant.role {
+            Role::Observer => {
+                return Err(T2ZError::InvalidInput(format!(
+                    "Observer {} cannot contribute",
+                    participant.id
+                )));
+            }
+            Role::Signer => {
+                for (idx, (before, after)) in original
+                    .transparent
+                    .inputs
+                    .iter()
+                    .zip(&updated.transparent.inputs)
+                    .enumerate()
+                {
+                    let assigned = participant.input_indices.contains(&(idx as u32));
+                    if !assigned && before.partial_signatures != after.partial_signatures {
+                        return Err(T2ZError::InvalidInput(format!(
+                            "Participant {} changed signatures on unassigned input {}",
+                            participant.id, idx
+                        )));
+                    }
+                }
+                if original.orchard.zkproof != updated.orchard.zkproof {
+                    return Err(T2ZError::InvalidInput(format!(
+                        "Signer {} changed the Orchard proof",
+                        participant.id
+                    )));
+                }
+            }
+            Role::Prover => {
+                if original
+                    .transparent
+                    .inputs
+                    .iter()
+                    .zip(&updated.transparent.inputs)
+                    .any(|(before, after)| before.partial_signatures != after.partial_signatures)
+                {
+                    return Err(T2ZError::InvalidInput(format!(
+                        "Prover {} changed transparent signatures",
+                        participant.id
+                    )));
+                }
+            }
+        }
+
+        if unsigned_digest(&original)? != unsigned_digest(&updated)? {
+            return Err(T2ZError::InvalidInput(format!(
+                "Participant {} modified the transaction",
+                participant.id
+            )));
+        }
+
+        self.contributions.push(contribution);
+        Ok(())
+    }
+
+    /// Participants that still owe a contribution (Observers excluded).
+    pub fn pending_participants(&self) -> Vec<&Participant> {
+        self.participants
+            .iter()
+            .filter(|p| p.role != Role::Observer && !self.has_contributed(&p.id))
+            .collect()
+    }
+
+    /// Whether every Signer and Prover has contributed.
+    pub fn is_complete(&self) -> bool {
+        self.pending_participants().is_empty()
+    }
+
+    /// Combines all contributions into a single PCZT.
+    pub fn combine(&self) -> Result<Pczt, T2ZError> {
+        if let Some(pending) = self.pending_participants().first() {
+            return Err(T2ZError::InvalidInput(format!(
+                "Still waiting on participant {}",
+                pending.id
+            )));
+        }
+
+        let mut pczts = vec![crate::parse_pczt(&self.pczt)?];
+        for contribution in &self.contributions {
+            pczts.push(crate::parse_pczt(&contribution.pczt)?);
+        }
+
+        crate::combine(pczts)
+    }
+}
+
+/// Digest of a PCZT with signatures and proofs removed, so contributions can
+/// be compared against the proposal.
+fn unsigned_digest(pczt_shadow: &PcztShadow) -> Result<[u8; 32], T2ZError> {
+    let mut unsigned = pczt_shadow.clone();
+    for input in &mut unsigned.transparent.inputs {
+        input.partial_signatures.clear();
+    }
+    unsigned.orchard.zkproof = None;
+    unsigned.digest(UNSIGNED_DIGEST_PERSONALIZATION)
+}
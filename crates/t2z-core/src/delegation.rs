@@ -0,0 +1,260 @@
+//! Delegated proving protocol.
+//!
+//! Lets a low-power device hand the PCZT Prover role to a remote service and
+//! check, when the PCZT comes back, that the only thing the service changed
+//! was adding the Orchard proof.
+//!
+//! Flow (transport is up to the caller, messages serialize with `to_bytes`):
+//! 1. Client: `create_proof_request(&pczt, redact)` -> `ProofRequest`
+//! 2. Server: `handle_proof_request(&request)` -> `ProofResponse`
+//! 3. Client: `accept_proof_response(pczt, &request, &response)` -> proved PCZT
+//!
+//! Integrity is checked with a BLAKE2b digest over the PCZT with its Orchard
+//! proof cleared: the digest of the returned PCZT must equal the digest of the
+//! request, and the request must have been derived from the client's PCZT.
+//!
+//! Detached flow, for provers that should see as little as possible:
+//! 1. Client: `export_proof_request(&pczt)` -> PCZT bytes holding only the
+//!    Orchard bundle (no transparent inputs or outputs, metadata, or binding
+//!    signing key)
+//! 2. Server: `handle_detached_proof_request(&bytes)` -> raw Orchard proof
+//! 3. Client: `import_proof(pczt, &proof)` -> proved PCZT
+
+use serde::{Deserialize, Serialize};
+
+use crate::preview::{ORCHARD_PROOF_BASE_SIZE, ORCHARD_PROOF_PER_ACTION_SIZE};
+use crate::shadow::PcztShadow;
+use crate::{Pczt, T2ZError, update_pczt_shadow};
+
+/// BLAKE2b personalization for proof request digests
+const PROOF_REQUEST_PERSONALIZATION: &[u8; 16] = b"t2z_ProofReqHash";
+
+/// A PCZT sent to a remote prover
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofRequest {
+    /// Serialized PCZT to prove (possibly redacted)
+    pub pczt: Vec<u8>,
+    /// Whether metadata the prover doesn't need was removed from `pczt`
+    pub redacted: bool,
+    /// Digest of `pczt` with the Orchard proof cleared
+    pub digest: [u8; 32],
+}
+
+/// A proved PCZT returned by a remote prover
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofResponse {
+    /// Serialized PCZT with the Orchard proof added
+    pub pczt: Vec<u8>,
+}
+
+impl ProofRequest {
+    /// Serializes the request for transport.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a request received over the wire.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid proof request: {:?}", e)))
+    }
+}
+
+impl ProofResponse {
+    /// Serializes the response for transport.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a response received over the wire.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid proof response: {:?}", e)))
+    }
+}
+
+/// Digest of everything in the PCZT except the Orchard proof
+fn digest_without_proof(pczt_shadow: &PcztShadow) -> Result<[u8; 32], T2ZError> {
+    let mut unproved = pczt_shadow.clone();
+    unproved.orchard.zkproof = None;
+    unproved.digest(PROOF_REQUEST_PERSONALIZATION)
+}
+
+/// Removes metadata the Prover role doesn't need (derivation paths, user
+/// addresses, proprietary fields, dummy spend keys).
+fn redact_for_prover(pczt_shadow: &mut PcztShadow) {
+    pczt_shadow.global.proprietary.clear();
+
+    for input in &mut pczt_shadow.transparent.inputs {
+        input.bip32_derivation.clear();
+        input.proprietary.clear();
+    }
+
+    for output in &mut pczt_shadow.transparent.outputs {
+        output.bip32_derivation.clear();
+        output.user_address = None;
+        output.proprietary.clear();
+    }
+
+    for action in &mut pczt_shadow.orchard.actions {
+        action.spend.zip32_derivation = None;
+        action.spend.dummy_sk = None;
+        action.spend.proprietary.clear();
+        action.output.zip32_derivation = None;
+        action.output.user_address = None;
+        action.output.proprietary.clear();
+    }
+}
+
+/// Builds the request a client sends to a remote prover.
+///
+/// # Arguments
+/// * `pczt` - The PCZT that needs Orchard proofs
+/// * `redact` - Strip metadata the prover doesn't need before sending
+pub fn create_proof_request(pczt: &Pczt, redact: bool) -> Result<ProofRequest, T2ZError> {
+    let (header, mut pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+
+    if pczt_shadow.orchard.actions.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "PCZT has no Orchard actions to prove".to_string(),
+        ));
+    }
+
+    if redact {
+        redact_for_prover(&mut pczt_shadow);
+    }
+
+    Ok(ProofRequest {
+        digest: digest_without_proof(&pczt_shadow)?,
+        pczt: pczt_shadow.encode(&header)?,
+        redacted: redact,
+    })
+}
+
+/// Proves a request on the server side.
+///
+/// Rejects requests whose digest doesn't match their PCZT, so a corrupted
+/// request fails fast instead of after a multi-second proof.
+#[cfg(feature = "prover")]
+pub fn handle_proof_request(request: &ProofRequest) -> Result<ProofResponse, T2ZError> {
+    let (_, pczt_shadow) = PcztShadow::decode(&request.pczt)?;
+    if digest_without_proof(&pczt_shadow)? != request.digest {
+        return Err(T2ZError::InvalidInput(
+            "Proof request digest does not match its PCZT".to_string(),
+        ));
+    }
+
+    let pczt = crate::parse_pczt(&request.pczt)?;
+    let proved = crate::prove_transaction(pczt)?;
+
+    Ok(ProofResponse {
+        pczt: proved.serialize(),
+    })
+}
+
+/// Checks a remote prover's response and merges the proof into the client's PCZT.
+///
+/// # Arguments
+/// * `pczt` - The client's original (unredacted) PCZT
+/// * `request` - The request that was sent for `pczt`
+/// * `response` - The prover's response
+///
+/// # Returns
+/// `pczt` with the Orchard proof from the response added. Fails if the request
+/// wasn't built from `pczt`, or if the prover changed anything besides the proof.
+pub fn accept_proof_response(
+    pczt: Pczt,
+    request: &ProofRequest,
+    response: &ProofResponse,
+) -> Result<Pczt, T2ZError> {
+    let expected = create_proof_request(&pczt, request.redacted)?;
+    if expected.digest != request.digest {
+        return Err(T2ZError::InvalidInput(
+            "Proof request was not created from this PCZT".to_string(),
+        ));
+    }
+
+    let (_, proved_shadow) = PcztShadow::decode(&response.pczt)?;
+    if digest_without_proof(&proved_shadow)? != request.digest {
+        return Err(T2ZError::Proving(
+            "Prover modified the PCZT beyond adding the Orchard proof".to_string(),
+        ));
+    }
+
+    let zkproof = proved_shadow
+        .orchard
+        .zkproof
+        .ok_or_else(|| T2ZError::Proving("Prover response has no Orchard proof".to_string()))?;
+
+    update_pczt_shadow(pczt, |pczt_shadow| {
+        pczt_shadow.orchard.zkproof = Some(zkproof);
+        Ok(())
+    })
+}
+
+/// Builds a detached proof request: the PCZT reduced to what the Orchard
+/// circuit needs.
+///
+/// Transparent inputs and outputs, metadata, and the binding signing key are
+/// removed, so the prover learns nothing about the transparent side.
+pub fn export_proof_request(pczt: &Pczt) -> Result<Vec<u8>, T2ZError> {
+    let (header, mut pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+
+    if pczt_shadow.orchard.actions.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "PCZT has no Orchard actions to prove".to_string(),
+        ));
+    }
+
+    redact_for_prover(&mut pczt_shadow);
+    pczt_shadow.transparent.inputs.clear();
+    pczt_shadow.transparent.outputs.clear();
+    pczt_shadow.orchard.zkproof = None;
+    pczt_shadow.orchard.bsk = None;
+
+    pczt_shadow.encode(&header)
+}
+
+/// Proves a detached proof request on the server side.
+///
+/// # Returns
+/// The Orchard proof bytes, for the client's `import_proof`
+#[cfg(feature = "prover")]
+pub fn handle_detached_proof_request(request: &[u8]) -> Result<Vec<u8>, T2ZError> {
+    let pczt = crate::parse_pczt(request)?;
+    let proved = crate::prove_transaction(pczt)?;
+
+    let (_, proved_shadow) = PcztShadow::from_pczt(&proved)?;
+    proved_shadow
+        .orchard
+        .zkproof
+        .ok_or_else(|| T2ZError::Proving("Prover produced no Orchard proof".to_string()))
+}
+
+/// Adds an Orchard proof returned for `export_proof_request(&pczt)`.
+///
+/// Only the proof's size is checked here; check the proof itself with
+/// `verify_orchard_proof` (feature `verifier`).
+pub fn import_proof(pczt: Pczt, proof: &[u8]) -> Result<Pczt, T2ZError> {
+    update_pczt_shadow(pczt, |pczt_shadow| {
+        let num_actions = pczt_shadow.orchard.actions.len();
+        if num_actions == 0 {
+            return Err(T2ZError::InvalidInput(
+                "PCZT has no Orchard actions to prove".to_string(),
+            ));
+        }
+
+        let expected_len = ORCHARD_PROOF_BASE_SIZE + ORCHARD_PROOF_PER_ACTION_SIZE * num_actions;
+        if proof.len() != expected_len {
+            return Err(T2ZError::Proving(format!(
+                "Orchard proof for {} actions must be {} bytes (got {})",
+                num_actions,
+                expected_len,
+                proof.len()
+            )));
+        }
+
+        pczt_shadow.orchard.zkproof = Some(proof.to_vec());
+        Ok(())
+    })
+}
@@ -0,0 +1,242 @@
+//! Payment disclosures for Orchard outputs.
+//!
+//! A `PaymentDisclosure` lets a sender prove to a third party that a
+//! transaction paid a given Orchard recipient a given amount, without handing
+//! over a viewing key. It carries the note plaintext (recipient, value, memo)
+//! and the note randomness (`rho`, `rseed`); anyone can recompute the note
+//! commitment from them and check that it appears in the transaction. The
+//! memo is not part of the commitment; it is checked by decrypting the
+//! output in the transaction with the ephemeral key the note determines.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pczt, T2ZError, encrypted_action, memo_bytes};
+
+/// Version of the `PaymentDisclosure` layout
+pub const PAYMENT_DISCLOSURE_VERSION: u32 = 1;
+
+/// Proof that a transaction contains a specific Orchard output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentDisclosure {
+    /// Layout version (`PAYMENT_DISCLOSURE_VERSION`)
+    pub version: u32,
+    /// Transaction ID (hex, display order), if the PCZT was complete
+    pub txid: Option<String>,
+    /// Consensus branch ID of the transaction
+    pub consensus_branch_id: u32,
+    /// Index of the Orchard action containing the output
+    pub action_index: u32,
+    /// Raw Orchard recipient address (43 bytes)
+    pub recipient: Vec<u8>,
+    /// Note value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub value: u64,
+    /// Note `rho` (the nullifier of the action's spend)
+    pub rho: [u8; 32],
+    /// Note `rseed`
+    pub rseed: [u8; 32],
+    /// Extracted note commitment the other fields must reproduce
+    pub cmx: [u8; 32],
+    /// Memo attached to the output. Not covered by `cmx`, so only
+    /// `verify_against_transaction` can check it.
+    pub memo: Option<Vec<u8>>,
+}
+
+impl PaymentDisclosure {
+    /// Checks that the note fields reproduce `cmx`.
+    ///
+    /// The memo is not checked; use `verify_against_transaction` for that.
+    pub fn verify(&self) -> Result<(), T2ZError> {
+        self.note().map(|_| ())
+    }
+
+    /// Rebuilds the disclosed note, checking that it reproduces `cmx`.
+    fn note(&self) -> Result<orchard::Note, T2ZError> {
+        let recipient: [u8; 43] = self.recipient.as_slice().try_into().map_err(|_| {
+            T2ZError::InvalidInput("Disclosure recipient must be 43 bytes".to_string())
+        })?;
+
+        let note = orchard_note(&recipient, self.value, &self.rho, &self.rseed)?;
+        if orchard::note::ExtractedNoteCommitment::from(note.commitment()).to_bytes() != self.cmx {
+            return Err(T2ZError::InvalidInput(
+                "Disclosed note does not match its commitment".to_string(),
+            ));
+        }
+
+        Ok(note)
+    }
+
+    /// Verifies the disclosure and checks that `tx_bytes` contains its
+    /// output, encrypted with its memo.
+    ///
+    /// # Arguments
+    /// * `tx_bytes` - The raw transaction (e.g. fetched from a node by txid)
+    pub fn verify_against_transaction(&self, tx_bytes: &[u8]) -> Result<(), T2ZError> {
+        use zcash_primitives::transaction::Transaction;
+        use zcash_protocol::consensus::BranchId;
+
+        let note = self.note()?;
+
+        let branch_id = BranchId::try_from(self.consensus_branch_id)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid branch ID: {}", e)))?;
+        let transaction = Transaction::read(tx_bytes, branch_id)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid transaction: {}", e)))?;
+
+        if let Some(txid) = &self.txid
+            && &transaction.txid().to_string() != txid
+        {
+            return Err(T2ZError::InvalidInput(
+                "Transaction ID does not match the disclosure".to_string(),
+            ));
+        }
+
+        let action = transaction
+            .orchard_bundle()
+            .and_then(|bundle| bundle.actions().get(self.action_index as usize))
+            .ok_or_else(|| {
+                T2ZError::InvalidInput(format!(
+                    "Transaction has no Orchard action {}",
+                    self.action_index
+                ))
+            })?;
+
+        if action.cmx().to_bytes() != self.cmx {
+            return Err(T2ZError::InvalidInput(
+                "Transaction does not contain the disclosed output".to_string(),
+            ));
+        }
+
+        check_memo(&note, action, self.memo.as_deref())
+    }
+
+    /// Serializes the disclosure.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized disclosure.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid payment disclosure: {:?}", e)))
+    }
+}
+
+/// Checks that `action`, whose output is `note`, was encrypted with `memo`.
+///
+/// The sender's ephemeral key is derived from the note, so whoever knows the
+/// note can decrypt the output like its recipient would.
+fn check_memo<A>(
+    note: &orchard::Note,
+    action: &orchard::Action<A>,
+    memo: Option<&[u8]>,
+) -> Result<(), T2ZError> {
+    use orchard::note_encryption::OrchardDomain;
+    use zcash_note_encryption::Domain;
+
+    let esk = OrchardDomain::derive_esk(note).expect("Orchard notes always have an esk");
+    let (_, _, decrypted) = zcash_note_encryption::try_output_recovery_with_pkd_esk(
+        &OrchardDomain::for_action(action),
+        OrchardDomain::get_pk_d(note),
+        esk,
+        action,
+    )
+    .ok_or_else(|| {
+        T2ZError::InvalidInput("Output cannot be decrypted with the disclosed note".to_string())
+    })?;
+
+    if decrypted != *memo_bytes(memo)?.as_array() {
+        return Err(T2ZError::InvalidInput(
+            "Output was not sent with the disclosed memo".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rebuilds an Orchard note from its parts.
+pub(crate) fn orchard_note(
+    recipient: &[u8; 43],
+    value: u64,
+    rho: &[u8; 32],
+    rseed: &[u8; 32],
+) -> Result<orchard::Note, T2ZError> {
+    use orchard::note::{RandomSeed, Rho};
+
+    let recipient = Option::from(orchard::Address::from_raw_address_bytes(recipient))
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard recipient".to_string()))?;
+    let rho = Option::from(Rho::from_bytes(rho))
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid note rho".to_string()))?;
+    let rseed = Option::from(RandomSeed::from_bytes(*rseed, &rho))
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid note rseed".to_string()))?;
+
+    Option::from(orchard::Note::from_parts(
+        recipient,
+        orchard::value::NoteValue::from_raw(value),
+        rho,
+        rseed,
+    ))
+    .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard note".to_string()))
+}
+
+/// Creates a payment disclosure for the Orchard output in `action_index`.
+///
+/// The PCZT must still contain the output's plaintext fields (i.e. must not
+/// have been redacted). The txid is included if the PCZT is fully signed and
+/// proved; otherwise disclose from the final PCZT before extraction.
+///
+/// # Arguments
+/// * `pczt` - The PCZT containing the output
+/// * `action_index` - Index of the Orchard action
+/// * `memo` - Memo that was attached to the payment, if any; must be the
+///   memo the output was encrypted with
+pub fn create_payment_disclosure(
+    pczt: &Pczt,
+    action_index: usize,
+    memo: Option<Vec<u8>>,
+) -> Result<PaymentDisclosure, T2ZError> {
+    let action = pczt.orchard().actions().get(action_index).ok_or_else(|| {
+        T2ZError::InvalidInput(format!("Invalid action index: {}", action_index))
+    })?;
+    let output = action.output();
+
+    let missing =
+        |field: &str| T2ZError::InvalidInput(format!("Action {} has no {}", action_index, field));
+
+    let recipient = (*output.recipient()).ok_or_else(|| missing("recipient"))?;
+    let value = (*output.value()).ok_or_else(|| missing("value"))?;
+    let rseed = (*output.rseed()).ok_or_else(|| missing("rseed"))?;
+    let rho = *action.spend().nullifier();
+    let cmx = *output.cmx();
+
+    let note = orchard_note(&recipient, value, &rho, &rseed)?;
+    if orchard::note::ExtractedNoteCommitment::from(note.commitment()).to_bytes() != cmx {
+        return Err(T2ZError::InvalidInput(format!(
+            "Action {} note fields do not match its commitment",
+            action_index
+        )));
+    }
+
+    let bundle = pczt
+        .orchard()
+        .clone()
+        .into_parsed()
+        .map_err(|e| T2ZError::Builder(format!("Failed to parse Orchard bundle: {:?}", e)))?;
+    check_memo(
+        &note,
+        &encrypted_action(&bundle.actions()[action_index]),
+        memo.as_deref(),
+    )?;
+
+    Ok(PaymentDisclosure {
+        version: PAYMENT_DISCLOSURE_VERSION,
+        txid: crate::audit::extract_txid(pczt),
+        consensus_branch_id: *pczt.global().consensus_branch_id(),
+        action_index: action_index as u32,
+        recipient: recipient.to_vec(),
+        value,
+        rho,
+        rseed,
+        cmx,
+        memo,
+    })
+}
@@ -0,0 +1,227 @@
+//! Endpoint selection with failover (feature `wallet_lite`).
+//!
+//! Wallets usually know several lightwalletd or RPC servers and should not
+//! stall because the first one is down. `EndpointPool` keeps them in order
+//! of preference along with what the wallet has learned about each: a
+//! failed request (or a health check showing the server's chain tip lagging
+//! behind the others) takes an endpoint out of rotation for a while, backing
+//! off further on each consecutive failure, and `pick` returns the most
+//! preferred endpoint still in rotation.
+//!
+//! The pool makes no requests itself. Callers ask it which endpoint to use
+//! for a UTXO fetch, tree state fetch, or broadcast, and report back how the
+//! request went; `BroadcastQueue` does the same for its submissions.
+//!
+//! A pool can also carry a SOCKS5 proxy (usually Tor) that every request
+//! should go through, so that the endpoints do not learn the wallet's IP
+//! address. With circuit isolation, each request gets its own proxy
+//! credentials; Tor (with its default `IsolateSOCKSAuth`) then sends
+//! requests with different credentials over different circuits, so an
+//! endpoint cannot link, say, two addresses' UTXO lookups by exit node.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{RetryPolicy, T2ZError};
+
+/// BLAKE2b personalization for SOCKS5 isolation credentials
+const ISOLATION_PERSONALIZATION: &[u8; 16] = b"t2z_SocksIsolate";
+
+/// A SOCKS5 proxy for network requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Socks5Proxy {
+    /// Proxy address as `host:port`
+    pub address: String,
+    /// Give each request its own credentials (and so its own Tor circuit)
+    pub isolate_circuits: bool,
+}
+
+impl Socks5Proxy {
+    /// The local Tor daemon's SOCKS port, with circuit isolation.
+    pub fn tor() -> Self {
+        Socks5Proxy {
+            address: "127.0.0.1:9050".to_string(),
+            isolate_circuits: true,
+        }
+    }
+
+    /// Proxy URL for a request, for HTTP and gRPC clients that take one.
+    ///
+    /// The `socks5h` scheme has the proxy resolve hostnames, so DNS lookups
+    /// do not leak either. With circuit isolation, requests with different
+    /// `isolation_key`s (e.g. the address being looked up, or the txid being
+    /// broadcast) get different credentials derived from the key.
+    pub fn url_for(&self, isolation_key: &str) -> String {
+        if !self.isolate_circuits {
+            return format!("socks5h://{}", self.address);
+        }
+        let hash = blake2b_simd::Params::new()
+            .hash_length(16)
+            .personal(ISOLATION_PERSONALIZATION)
+            .hash(isolation_key.as_bytes());
+        let credential = hex::encode(hash.as_bytes());
+        format!("socks5h://{0}:{0}@{1}", credential, self.address)
+    }
+}
+
+/// Blocks an endpoint's chain tip may trail the highest tip reported by any
+/// endpoint before it is taken out of rotation
+pub const MAX_TIP_LAG: u32 = 10;
+
+/// What the pool knows about one endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Endpoint URL
+    pub url: String,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Time (Unix seconds) the endpoint returns to rotation; in the past
+    /// for healthy endpoints
+    pub retry_at: u64,
+    /// Error from the last failure
+    pub last_error: Option<String>,
+    /// Chain tip height from the last health check
+    pub tip_height: Option<u32>,
+}
+
+impl EndpointHealth {
+    /// Whether the endpoint is in rotation at `now`.
+    pub fn is_healthy(&self, now: u64) -> bool {
+        now >= self.retry_at
+    }
+}
+
+/// Endpoints in order of preference, with their health
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointPool {
+    endpoints: Vec<EndpointHealth>,
+    retry: RetryPolicy,
+    proxy: Option<Socks5Proxy>,
+}
+
+impl EndpointPool {
+    /// Creates a pool of `urls`, most preferred first, all healthy.
+    /// `retry` sets how long failing endpoints stay out of rotation.
+    pub fn new(urls: Vec<String>, retry: RetryPolicy) -> Result<Self, T2ZError> {
+        if urls.is_empty() {
+            return Err(T2ZError::InvalidInput(
+                "An endpoint pool needs at least one endpoint".to_string(),
+            ));
+        }
+        Ok(EndpointPool {
+            endpoints: urls
+                .into_iter()
+                .map(|url| EndpointHealth {
+                    url,
+                    consecutive_failures: 0,
+                    retry_at: 0,
+                    last_error: None,
+                    tip_height: None,
+                })
+                .collect(),
+            retry,
+            proxy: None,
+        })
+    }
+
+    /// Every endpoint, in order of preference.
+    pub fn endpoints(&self) -> &[EndpointHealth] {
+        &self.endpoints
+    }
+
+    /// Routes every request through `proxy` (or directly, with `None`).
+    pub fn set_proxy(&mut self, proxy: Option<Socks5Proxy>) {
+        self.proxy = proxy;
+    }
+
+    /// The proxy requests go through, if any.
+    pub fn proxy(&self) -> Option<&Socks5Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Proxy URL for a request (see `Socks5Proxy::url_for`), or `None` to
+    /// connect directly.
+    pub fn proxy_url(&self, isolation_key: &str) -> Option<String> {
+        self.proxy.as_ref().map(|proxy| proxy.url_for(isolation_key))
+    }
+
+    /// The endpoint to use at `now`: the most preferred healthy one, or if
+    /// none is healthy, the one returning to rotation soonest.
+    pub fn pick(&self, now: u64) -> &str {
+        self.failover_order(now)[0]
+    }
+
+    /// Every endpoint in the order to try them at `now`: healthy ones by
+    /// preference, then the rest by when they return to rotation. Use this
+    /// to retry one request across endpoints.
+    pub fn failover_order(&self, now: u64) -> Vec<&str> {
+        let mut order: Vec<(usize, &EndpointHealth)> = self.endpoints.iter().enumerate().collect();
+        order.sort_by_key(|(idx, endpoint)| {
+            if endpoint.is_healthy(now) {
+                (false, 0, *idx)
+            } else {
+                (true, endpoint.retry_at, *idx)
+            }
+        });
+        order.into_iter().map(|(_, endpoint)| endpoint.url.as_str()).collect()
+    }
+
+    /// Records a successful request to `url`, returning it to rotation.
+    /// Returns whether `url` is in the pool.
+    pub fn record_success(&mut self, url: &str) -> bool {
+        let Some(endpoint) = self.endpoints.iter_mut().find(|e| e.url == url) else {
+            return false;
+        };
+        endpoint.consecutive_failures = 0;
+        endpoint.retry_at = 0;
+        endpoint.last_error = None;
+        true
+    }
+
+    /// Records a failed request to `url` at `now`, taking it out of
+    /// rotation for the retry policy's delay. Returns whether `url` is in
+    /// the pool.
+    pub fn record_failure(&mut self, url: &str, error: &str, now: u64) -> bool {
+        let Some(endpoint) = self.endpoints.iter_mut().find(|e| e.url == url) else {
+            return false;
+        };
+        endpoint.consecutive_failures = endpoint.consecutive_failures.saturating_add(1);
+        endpoint.retry_at = now.saturating_add(self.retry.delay(endpoint.consecutive_failures));
+        endpoint.last_error = Some(error.to_string());
+        true
+    }
+
+    /// Records a health check of `url` (e.g. lightwalletd's `GetLightdInfo`)
+    /// that reported chain tip `tip_height`. An endpoint more than
+    /// `MAX_TIP_LAG` blocks behind the highest tip any endpoint has reported
+    /// counts as failing. Returns whether `url` is in the pool.
+    pub fn record_tip(&mut self, url: &str, tip_height: u32, now: u64) -> bool {
+        let Some(endpoint) = self.endpoints.iter_mut().find(|e| e.url == url) else {
+            return false;
+        };
+        endpoint.tip_height = Some(tip_height);
+
+        let best = self
+            .endpoints
+            .iter()
+            .filter_map(|e| e.tip_height)
+            .max()
+            .unwrap_or(tip_height);
+        let lag = best.saturating_sub(tip_height);
+        if lag > MAX_TIP_LAG {
+            self.record_failure(url, &format!("Chain tip {} blocks behind", lag), now)
+        } else {
+            self.record_success(url)
+        }
+    }
+
+    /// Serializes the pool, health included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized pool.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid endpoint pool: {:?}", e)))
+    }
+}
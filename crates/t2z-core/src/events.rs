@@ -0,0 +1,72 @@
+//! Wallet state change notifications (feature `wallet_lite`).
+//!
+//! `Account` and `NoteStore` report what changes as callers feed them UTXOs,
+//! broadcasts, confirmations, and scanned blocks, so host apps can update
+//! their UI from events instead of polling balances. Subscribe a
+//! `WalletListener` (or any `Fn(&WalletEvent)`) with `subscribe`.
+//!
+//! Listeners are not part of the serialized state: subscribe again after
+//! `from_bytes`. A transaction tracked by both an account and a note store
+//! is reported by each one that has a listener.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Outpoint, TrackedNote};
+
+/// A change to wallet state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WalletEvent {
+    /// An account started tracking a UTXO
+    UtxoAdded {
+        outpoint: Outpoint,
+        #[serde(with = "crate::amounts::amount")]
+        value: u64,
+    },
+    /// A note store started tracking an Orchard note paid to the wallet
+    NoteReceived { note: TrackedNote },
+    /// A transaction was mined at `height`, if known
+    TxConfirmed { txid: String, height: Option<u32> },
+    /// A transaction was rejected or expired, and what it spent or created
+    /// was released
+    TxExpired { txid: String },
+}
+
+/// Receives wallet events as they happen.
+pub trait WalletListener: Send + Sync {
+    /// Called synchronously, after the state change, for every event.
+    fn on_event(&self, event: &WalletEvent);
+}
+
+impl<F> WalletListener for F
+where
+    F: Fn(&WalletEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &WalletEvent) {
+        self(event)
+    }
+}
+
+/// The listeners subscribed to one `Account` or `NoteStore`
+#[derive(Clone, Default)]
+pub(crate) struct Listeners(Vec<Arc<dyn WalletListener>>);
+
+impl Listeners {
+    pub(crate) fn subscribe(&mut self, listener: impl WalletListener + 'static) {
+        self.0.push(Arc::new(listener));
+    }
+
+    pub(crate) fn emit(&self, event: WalletEvent) {
+        for listener in &self.0 {
+            listener.on_event(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Listeners({})", self.0.len())
+    }
+}
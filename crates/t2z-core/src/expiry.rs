@@ -0,0 +1,52 @@
+//! Expiry checks for broadcast services.
+//!
+//! A transaction with expiry height `E` can only be mined in blocks up to and
+//! including `E` (ZIP 203); `E == 0` means it never expires. Checking this
+//! before broadcasting lets a service skip dead transactions instead of
+//! collecting node rejections for them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::zip244::read_transaction;
+use crate::{Pczt, T2ZError};
+
+/// Expiry height of a PCZT or transaction
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Expiry {
+    /// Last height the transaction can be mined at (0 for no expiry)
+    pub expiry_height: u32,
+}
+
+impl Expiry {
+    /// Expiry of a PCZT.
+    pub fn of_pczt(pczt: &Pczt) -> Self {
+        Expiry {
+            expiry_height: *pczt.global().expiry_height(),
+        }
+    }
+
+    /// Expiry of a raw transaction (e.g. from `finalize_and_extract`).
+    pub fn of_transaction(tx_bytes: &[u8]) -> Result<Self, T2ZError> {
+        let transaction = read_transaction(tx_bytes)?;
+
+        Ok(Expiry {
+            expiry_height: transaction.expiry_height().into(),
+        })
+    }
+
+    /// Whether the transaction can no longer be mined in the block after
+    /// `current_height`.
+    pub fn is_expired(&self, current_height: u32) -> bool {
+        self.expiry_height != 0 && current_height >= self.expiry_height
+    }
+
+    /// Number of blocks after `current_height` that can still include the
+    /// transaction (0 once expired), or `None` if it never expires.
+    pub fn blocks_until_expiry(&self, current_height: u32) -> Option<u32> {
+        if self.expiry_height == 0 {
+            None
+        } else {
+            Some(self.expiry_height.saturating_sub(current_height))
+        }
+    }
+}
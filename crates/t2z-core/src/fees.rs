@@ -0,0 +1,341 @@
+//! ZIP 317 fee arithmetic and per-payment fee attribution.
+//!
+//! Exchanges batching several withdrawals into one shielding transaction need
+//! to book the fee against individual payments. `attribute_fee` splits the
+//! fee of a proposed PCZT across the payments of the request that produced it,
+//! either proportionally to the amounts or by each payment's marginal ZIP 317
+//! cost. Shares always sum to the total fee exactly.
+//!
+//! See: https://zips.z.cash/zip-0317
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pczt, T2ZError, TransactionRequest, inspect_pczt};
+
+/// ZIP 317 marginal fee per logical action (zatoshis)
+pub const MARGINAL_FEE: u64 = 5_000;
+
+/// ZIP 317 number of logical actions covered by the minimum fee
+pub const GRACE_ACTIONS: usize = 2;
+
+/// ZIP 317 standard size of a P2PKH input (bytes)
+pub const P2PKH_STANDARD_INPUT_SIZE: usize = 150;
+
+/// ZIP 317 standard size of a P2PKH output (bytes)
+pub const P2PKH_STANDARD_OUTPUT_SIZE: usize = 34;
+
+/// Minimum number of actions in a non-empty Orchard bundle (padding)
+pub const MIN_ORCHARD_ACTIONS: usize = 2;
+
+/// Number of Orchard actions a bundle with only outputs (no spends) needs
+pub fn orchard_actions_for_outputs(num_outputs: usize) -> usize {
+    if num_outputs == 0 {
+        0
+    } else {
+        num_outputs.max(MIN_ORCHARD_ACTIONS)
+    }
+}
+
+/// ZIP 317 logical actions for a transaction with P2PKH inputs.
+///
+/// # Arguments
+/// * `transparent_inputs` - Number of P2PKH inputs
+/// * `transparent_outputs` - Number of transparent outputs
+/// * `orchard_actions` - Number of Orchard actions (after padding)
+pub fn zip317_logical_actions(
+    transparent_inputs: usize,
+    transparent_outputs: usize,
+    orchard_actions: usize,
+) -> usize {
+    let input_bytes = transparent_inputs * P2PKH_STANDARD_INPUT_SIZE;
+    let output_bytes = transparent_outputs * P2PKH_STANDARD_OUTPUT_SIZE;
+
+    let transparent_actions = input_bytes
+        .div_ceil(P2PKH_STANDARD_INPUT_SIZE)
+        .max(output_bytes.div_ceil(P2PKH_STANDARD_OUTPUT_SIZE));
+
+    transparent_actions + orchard_actions
+}
+
+/// ZIP 317 conventional fee for a transaction with P2PKH inputs.
+pub fn zip317_fee(
+    transparent_inputs: usize,
+    transparent_outputs: usize,
+    orchard_actions: usize,
+) -> u64 {
+    let logical_actions =
+        zip317_logical_actions(transparent_inputs, transparent_outputs, orchard_actions);
+    MARGINAL_FEE * logical_actions.max(GRACE_ACTIONS) as u64
+}
+
+/// How `propose_transaction_with_fee_strategy` sets the fee
+///
+/// `FR` is only used by `Custom`; build the other variants with
+/// `FeeStrategy::zip317()` and `FeeStrategy::fixed(fee)`.
+#[derive(Debug, Clone)]
+pub enum FeeStrategy<FR = zcash_primitives::transaction::fees::zip317::FeeRule> {
+    /// ZIP 317 conventional fee
+    Zip317,
+    /// Exactly this fee, in zatoshis
+    Fixed(u64),
+    /// A caller-provided `zcash_primitives` fee rule
+    Custom(FR),
+}
+
+impl FeeStrategy {
+    /// ZIP 317 conventional fee
+    pub fn zip317() -> Self {
+        FeeStrategy::Zip317
+    }
+
+    /// Exactly `fee` zatoshis
+    pub fn fixed(fee: u64) -> Self {
+        FeeStrategy::Fixed(fee)
+    }
+}
+
+impl Default for FeeStrategy {
+    fn default() -> Self {
+        FeeStrategy::Zip317
+    }
+}
+
+/// How the fee is split across payments
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeAttributionMethod {
+    /// In proportion to each payment's amount
+    Proportional,
+    /// Each payment carries the fee its own output adds; the shared remainder
+    /// (inputs, change, grace actions, padding) is split proportionally
+    Marginal,
+}
+
+/// One payment's share of the fee
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PaymentFeeShare {
+    /// Index of the payment in the transaction request
+    pub payment_index: usize,
+    /// Payment address
+    pub address: String,
+    /// Payment amount in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub amount: u64,
+    /// Fee attributed to this payment in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub fee: u64,
+}
+
+/// Fee attribution report for a proposed transaction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeAttribution {
+    /// Total fee paid by the transaction in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub total_fee: u64,
+    /// Method used to split the fee
+    pub method: FeeAttributionMethod,
+    /// Per-payment shares, in request order (sums to `total_fee`)
+    pub shares: Vec<PaymentFeeShare>,
+}
+
+/// How a PCZT's fee follows from ZIP 317, for explaining it to users
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    /// Fee the PCZT actually pays, in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub fee: u64,
+    /// ZIP 317 conventional fee for the PCZT's shape, in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub zip317_fee: u64,
+    /// Cost of each P2PKH input (one logical action), in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub per_input_cost: u64,
+    /// Cost of each logical action, in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub per_action_cost: u64,
+    /// Logical actions covered by the minimum fee
+    pub grace_actions: usize,
+    /// Logical actions charged: max(inputs, transparent outputs) plus Orchard
+    /// actions, at least `grace_actions`
+    pub logical_actions: usize,
+    /// Number of transparent inputs
+    pub transparent_inputs: usize,
+    /// Number of transparent outputs
+    pub transparent_outputs: usize,
+    /// Number of Orchard actions (including padding)
+    pub orchard_actions: usize,
+}
+
+/// Breaks down the fee of a PCZT into its ZIP 317 components.
+///
+/// `fee` is what the PCZT pays; it differs from `zip317_fee` only if the
+/// PCZT was proposed with a different fee strategy.
+pub fn fee_breakdown(pczt: &Pczt) -> Result<FeeBreakdown, T2ZError> {
+    let info = inspect_pczt(pczt)?;
+    let transparent_inputs = info.transparent_inputs.len();
+    let transparent_outputs = info.transparent_outputs.len();
+    let orchard_actions = info.num_orchard_actions;
+
+    let logical_actions =
+        zip317_logical_actions(transparent_inputs, transparent_outputs, orchard_actions)
+            .max(GRACE_ACTIONS);
+
+    Ok(FeeBreakdown {
+        fee: info.implied_fee,
+        zip317_fee: zip317_fee(transparent_inputs, transparent_outputs, orchard_actions),
+        per_input_cost: MARGINAL_FEE,
+        per_action_cost: MARGINAL_FEE,
+        grace_actions: GRACE_ACTIONS,
+        logical_actions,
+        transparent_inputs,
+        transparent_outputs,
+        orchard_actions,
+    })
+}
+
+/// Attributes the fee of a proposed PCZT across the payments that produced it.
+///
+/// # Arguments
+/// * `pczt` - PCZT returned by `propose_transaction` for `request`
+/// * `request` - The transaction request the PCZT was proposed from
+/// * `method` - How to split the fee
+pub fn attribute_fee(
+    pczt: &Pczt,
+    request: &TransactionRequest,
+    method: FeeAttributionMethod,
+) -> Result<FeeAttribution, T2ZError> {
+    if request.payments.is_empty() {
+        return Err(T2ZError::InvalidInput("No payments specified".to_string()));
+    }
+
+    let info = inspect_pczt(pczt)?;
+    let total_fee = info.implied_fee;
+
+    let weights: Vec<u64> = request.payments.iter().map(|p| p.amount).collect();
+
+    let fees = match method {
+        FeeAttributionMethod::Proportional => split_proportionally(total_fee, &weights),
+        FeeAttributionMethod::Marginal => {
+            let num_inputs = info.transparent_inputs.len();
+            let num_transparent_outputs = info.transparent_outputs.len();
+            let num_orchard_outputs = info
+                .orchard_outputs
+                .iter()
+                .filter(|o| o.value.is_some_and(|v| v > 0))
+                .count();
+
+            let full_fee = zip317_fee(
+                num_inputs,
+                num_transparent_outputs,
+                orchard_actions_for_outputs(num_orchard_outputs),
+            );
+
+            let mut marginal = Vec::with_capacity(request.payments.len());
+            for payment in &request.payments {
+                let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
+                    .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+
+                // Same pool selection as propose_transaction
+                let fee_without = if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+                    zip317_fee(
+                        num_inputs,
+                        num_transparent_outputs.saturating_sub(1),
+                        orchard_actions_for_outputs(num_orchard_outputs),
+                    )
+                } else {
+                    zip317_fee(
+                        num_inputs,
+                        num_transparent_outputs,
+                        orchard_actions_for_outputs(num_orchard_outputs.saturating_sub(1)),
+                    )
+                };
+                marginal.push(full_fee.saturating_sub(fee_without));
+            }
+
+            // Never attribute more than was actually paid
+            let marginal_total: u64 = marginal.iter().sum();
+            if marginal_total > total_fee {
+                split_proportionally(total_fee, &marginal)
+            } else {
+                let shared = split_proportionally(total_fee - marginal_total, &weights);
+                marginal.iter().zip(shared).map(|(m, s)| m + s).collect()
+            }
+        }
+    };
+
+    let shares = request
+        .payments
+        .iter()
+        .zip(fees)
+        .enumerate()
+        .map(|(payment_index, (payment, fee))| PaymentFeeShare {
+            payment_index,
+            address: payment.address.clone(),
+            amount: payment.amount,
+            fee,
+        })
+        .collect();
+
+    Ok(FeeAttribution {
+        total_fee,
+        method,
+        shares,
+    })
+}
+
+/// Splits `total` in proportion to `weights` (largest remainder method), so
+/// the parts always sum to `total`. Zero total weight splits evenly.
+pub(crate) fn split_proportionally(total: u64, weights: &[u64]) -> Vec<u64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+
+    let weight_sum: u128 = weights.iter().map(|&w| w as u128).sum();
+    let (weights, weight_sum): (Vec<u128>, u128) = if weight_sum == 0 {
+        (vec![1; weights.len()], weights.len() as u128)
+    } else {
+        (weights.iter().map(|&w| w as u128).collect(), weight_sum)
+    };
+
+    let mut parts: Vec<u64> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(u128, usize)> = Vec::with_capacity(weights.len());
+    for (idx, weight) in weights.iter().enumerate() {
+        let scaled = total as u128 * weight;
+        parts.push((scaled / weight_sum) as u64);
+        remainders.push((scaled % weight_sum, idx));
+    }
+
+    // Hand out the leftover zatoshis to the largest remainders (ties: earlier payment)
+    let leftover = total - parts.iter().sum::<u64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, idx) in remainders.iter().take(leftover as usize) {
+        parts[idx] += 1;
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip317_fee() {
+        // 1 P2PKH input -> 2 Orchard outputs: 1 + 2 actions
+        assert_eq!(zip317_fee(1, 0, 2), 15_000);
+        // 1 input -> 1 transparent output: grace actions apply
+        assert_eq!(zip317_fee(1, 1, 0), 10_000);
+        assert_eq!(zip317_fee(5, 1, 2), 35_000);
+    }
+
+    #[test]
+    fn test_split_proportionally_sums_to_total() {
+        let parts = split_proportionally(10_000, &[1, 1, 1]);
+        assert_eq!(parts, vec![3_334, 3_333, 3_333]);
+
+        let parts = split_proportionally(15_000, &[300_000, 100_000]);
+        assert_eq!(parts, vec![11_250, 3_750]);
+
+        assert_eq!(split_proportionally(5, &[0, 0]), vec![3, 2]);
+    }
+}
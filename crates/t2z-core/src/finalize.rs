@@ -0,0 +1,81 @@
+//! Specific errors for PCZTs that are not ready to extract.
+//!
+//! The Spend Finalizer and Transaction Extractor report failures as their
+//! own error enums, which say what went wrong but not where ("missing
+//! signature", with no input index). `check_ready_to_extract` looks for the
+//! common causes first and reports them as `T2ZError::MissingSignature`,
+//! `T2ZError::MissingProof` and `T2ZError::ValueBalanceMismatch`, so
+//! bindings can show which input to sign or which step was skipped.
+//! `finalize_and_extract` and `finalize_and_extract_with` run it before
+//! either role; anything it doesn't recognize still surfaces as the upstream
+//! error.
+
+use crate::script_sig::is_p2pkh;
+use crate::shadow::PcztShadow;
+use crate::status::pubkey_hash;
+use crate::{Pczt, T2ZError};
+
+/// Checks that every P2PKH input is signed and the Orchard bundle is proved
+/// and balanced.
+///
+/// Inputs that already have a scriptSig, and inputs locked by other scripts
+/// (which `finalize_and_extract_with` hands to a `ScriptSigFinalizer`), are
+/// not checked.
+pub fn check_ready_to_extract(pczt: &Pczt) -> Result<(), T2ZError> {
+    let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+
+    for (input, shadow) in pczt_shadow.transparent.inputs.iter().enumerate() {
+        if shadow.script_sig.is_some() || !is_p2pkh(&shadow.script_pubkey) {
+            continue;
+        }
+        let locked_to = &shadow.script_pubkey[3..23];
+        let hashes_to_lock = |pubkey: &[u8; 33]| {
+            secp256k1::PublicKey::from_slice(pubkey)
+                .is_ok_and(|pubkey| pubkey_hash(&pubkey) == locked_to)
+        };
+
+        if !shadow.partial_signatures.keys().any(hashes_to_lock) {
+            // The PCZT only records the public key if the Creator added a
+            // derivation for it; otherwise the address hash is all there is
+            let pubkey = shadow
+                .bip32_derivation
+                .keys()
+                .find(|pubkey| hashes_to_lock(pubkey))
+                .map(hex::encode)
+                .unwrap_or_else(|| hex::encode(locked_to));
+            return Err(T2ZError::MissingSignature { input, pubkey });
+        }
+    }
+
+    let orchard = &pczt_shadow.orchard;
+    if orchard.actions.is_empty() {
+        return Ok(());
+    }
+
+    let values: Option<Vec<i128>> = orchard
+        .actions
+        .iter()
+        .map(|action| Some(action.spend.value? as i128 - action.output.value? as i128))
+        .collect();
+    if let Some(values) = values {
+        let (magnitude, is_negative) = orchard.value_sum;
+        let actual = if is_negative {
+            -(magnitude as i128)
+        } else {
+            magnitude as i128
+        };
+        let expected: i128 = values.into_iter().sum();
+        if actual != expected {
+            return Err(T2ZError::ValueBalanceMismatch {
+                expected: expected as i64,
+                actual: actual as i64,
+            });
+        }
+    }
+
+    if orchard.zkproof.is_none() {
+        return Err(T2ZError::MissingProof);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! Human-readable PCZT summaries.
+//!
+//! `format_pczt` renders what `inspect_pczt` reports as multi-line text for
+//! logs, terminals, and support tickets. PCZTs only hold memos encrypted, so
+//! `format_pczt_with_request` takes the request the PCZT was proposed from
+//! and shows each payment's memo next to its output.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PcztInfo, Pczt, T2ZError, TransactionRequest, inspect_pczt, payment_user_address};
+
+/// Zatoshis per ZEC
+const ZATOSHIS_PER_ZEC: u64 = 100_000_000;
+
+/// How much detail `format_pczt` shows
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    /// Totals, fee, expiry, and signing/proving status only
+    Summary,
+    /// Plus one line per input and output (padding actions omitted)
+    #[default]
+    Normal,
+    /// Plus scripts, raw Orchard recipients, and padding actions
+    Full,
+}
+
+impl std::str::FromStr for Verbosity {
+    type Err = T2ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "summary" => Ok(Verbosity::Summary),
+            "normal" => Ok(Verbosity::Normal),
+            "full" => Ok(Verbosity::Full),
+            _ => Err(T2ZError::InvalidInput(format!("Unknown verbosity: {}", s))),
+        }
+    }
+}
+
+/// Renders a PCZT as readable text.
+pub fn format_pczt(pczt: &Pczt, verbosity: Verbosity) -> Result<String, T2ZError> {
+    Ok(render(&inspect_pczt(pczt)?, None, verbosity))
+}
+
+/// Renders a PCZT as readable text, with the memos from `request` (the
+/// request it was proposed from) shown on the outputs that pay them.
+pub fn format_pczt_with_request(
+    pczt: &Pczt,
+    request: &TransactionRequest,
+    verbosity: Verbosity,
+) -> Result<String, T2ZError> {
+    Ok(render(&inspect_pczt(pczt)?, Some(request), verbosity))
+}
+
+fn render(info: &PcztInfo, request: Option<&TransactionRequest>, verbosity: Verbosity) -> String {
+    let signed = info
+        .transparent_inputs
+        .iter()
+        .filter(|input| input.is_signed)
+        .count();
+    let padding = info
+        .orchard_outputs
+        .iter()
+        .filter(|output| is_padding(output.value))
+        .count();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "PCZT: {} transparent input(s), {} transparent output(s), {} Orchard action(s)",
+        info.transparent_inputs.len(),
+        info.transparent_outputs.len(),
+        info.num_orchard_actions,
+    );
+    let _ = writeln!(out, "  Input total:  {}", zec(info.total_input));
+    let _ = writeln!(
+        out,
+        "  Output total: {}",
+        zec(info.total_transparent_output + info.total_orchard_output)
+    );
+    let _ = writeln!(out, "  Fee:          {}", zec(info.implied_fee));
+    let _ = match info.expiry_height {
+        0 => writeln!(out, "  Expiry:       never"),
+        height => writeln!(out, "  Expiry:       height {}", height),
+    };
+    let _ = writeln!(
+        out,
+        "  Signatures:   {}/{} input(s) signed",
+        signed,
+        info.transparent_inputs.len()
+    );
+    if info.num_orchard_actions > 0 {
+        let proofs = if info.has_orchard_proofs { "present" } else { "missing" };
+        let _ = writeln!(out, "  Proof:        {}", proofs);
+    }
+
+    if verbosity == Verbosity::Summary {
+        return out;
+    }
+
+    if !info.transparent_inputs.is_empty() {
+        let _ = writeln!(out, "Inputs:");
+    }
+    for (idx, input) in info.transparent_inputs.iter().enumerate() {
+        let status = match input.num_signatures {
+            0 => "unsigned".to_string(),
+            1 => "signed".to_string(),
+            n => format!("{} signatures", n),
+        };
+        let _ = writeln!(
+            out,
+            "  [{}] {}:{}  {}  {}",
+            idx,
+            input.prevout_txid,
+            input.prevout_index,
+            zec(input.value),
+            status
+        );
+        if verbosity == Verbosity::Full {
+            let _ = writeln!(out, "      script {}", input.script_pubkey);
+        }
+    }
+
+    if !info.transparent_outputs.is_empty() || info.num_orchard_actions > padding {
+        let _ = writeln!(out, "Outputs:");
+    }
+    for (idx, output) in info.transparent_outputs.iter().enumerate() {
+        let to = output.user_address.as_deref().unwrap_or("(unlabeled)");
+        let _ = writeln!(out, "  [t{}] {}  -> {}", idx, zec(output.value), to);
+        if verbosity == Verbosity::Full {
+            let _ = writeln!(out, "      script {}", output.script_pubkey);
+        }
+    }
+    for (idx, output) in info.orchard_outputs.iter().enumerate() {
+        let padding = is_padding(output.value);
+        if padding && verbosity != Verbosity::Full {
+            continue;
+        }
+
+        let value = output.value.map(zec).unwrap_or_else(|| "(redacted)".to_string());
+        let to = match (&output.user_address, padding) {
+            (_, true) => "(padding)",
+            (Some(address), false) => address.as_str(),
+            (None, false) => "(unlabeled)",
+        };
+        let _ = writeln!(out, "  [o{}] {}  -> {}", idx, value, to);
+
+        if let Some(memo) = output
+            .user_address
+            .as_deref()
+            .and_then(|address| payment_memo(request?, address, output.value))
+        {
+            let _ = writeln!(out, "      memo {}", memo);
+        }
+        if verbosity == Verbosity::Full
+            && let Some(recipient) = &output.recipient
+        {
+            let _ = writeln!(out, "      recipient {}", recipient);
+        }
+    }
+    if padding > 0 && verbosity != Verbosity::Full {
+        let _ = writeln!(out, "  ({} padding action(s) not shown)", padding);
+    }
+
+    out
+}
+
+/// Zero-value Orchard outputs are the builder's dummy actions or
+/// `min_orchard_actions` padding.
+fn is_padding(value: Option<u64>) -> bool {
+    value == Some(0)
+}
+
+/// The memo of the payment an output was built from, as text if it is
+/// UTF-8 and hex otherwise.
+fn payment_memo(request: &TransactionRequest, address: &str, value: Option<u64>) -> Option<String> {
+    let payment = request.payments.iter().find(|payment| {
+        payment_user_address(payment) == address && value.is_none_or(|v| v == payment.amount)
+    })?;
+    let memo = payment.memo.as_deref()?;
+
+    let trimmed = &memo[..memo.iter().rposition(|&b| b != 0).map_or(0, |end| end + 1)];
+    Some(match std::str::from_utf8(trimmed) {
+        Ok(text) => format!("{:?}", text),
+        Err(_) => format!("0x{}", hex::encode(memo)),
+    })
+}
+
+/// Formats zatoshis as ZEC, e.g. `0.0015 ZEC (150000 zat)`.
+fn zec(zatoshis: u64) -> String {
+    let whole = zatoshis / ZATOSHIS_PER_ZEC;
+    let fraction = format!("{:08}", zatoshis % ZATOSHIS_PER_ZEC);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{} ZEC ({} zat)", whole, zatoshis)
+    } else {
+        format!("{}.{} ZEC ({} zat)", whole, fraction, zatoshis)
+    }
+}
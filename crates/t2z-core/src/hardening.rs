@@ -0,0 +1,93 @@
+//! Side-channel hardened transparent signing (`hardened-signing` feature).
+//!
+//! By default transparent inputs are signed by the pczt crate's Signer role,
+//! with deterministic RFC 6979 nonces on an unblinded secp256k1 context.
+//! With `hardened-signing`, `sign_transparent_input` (and its in-place
+//! variant) sign through `sign_transparent` instead, which:
+//! - re-randomizes the context from the OS RNG before every signature,
+//!   blinding its scalar multiplications against timing and power analysis
+//! - mixes 32 bytes of auxiliary randomness into the nonce (RFC 6979 3.6),
+//!   so a fault injected into one signing run cannot be replayed
+//! - verifies the signature before it is added, so a faulty one never
+//!   leaves the process
+//! - matches the key against the input's script in constant time, without
+//!   stopping at the first mismatch
+//!
+//! Signatures are no longer reproducible, which is why it is opt-in.
+
+use rand_core::{OsRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::script_sig::is_p2pkh;
+use crate::shadow::{PcztShadow, TransparentInputShadow};
+use crate::status::{parse_multisig, pubkey_hash};
+use crate::{Pczt, T2ZError, get_sighash};
+
+/// `SIGHASH_ALL`, appended to every signature
+const SIGHASH_ALL: u8 = 0x01;
+
+/// Compares two byte strings in time that depends only on their lengths.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
+
+/// Signs a transparent input, returning the public key and the signature
+/// (DER with the sighash type byte appended) to add to it.
+pub(crate) fn sign_transparent(
+    pczt: &Pczt,
+    input_index: usize,
+    secret_key: &secp256k1::SecretKey,
+) -> Result<([u8; 33], Vec<u8>), T2ZError> {
+    let mut seed = [0u8; 32];
+    let mut aux_rand = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    OsRng.fill_bytes(&mut aux_rand);
+
+    let mut secp = secp256k1::Secp256k1::new();
+    secp.seeded_randomize(&seed);
+    seed.zeroize();
+    let pubkey = secp256k1::PublicKey::from_secret_key(&secp, secret_key);
+
+    let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+    let input = pczt_shadow
+        .transparent
+        .inputs
+        .get(input_index)
+        .ok_or_else(|| T2ZError::InvalidInput(format!("Invalid input index: {}", input_index)))?;
+    if !can_sign(&pubkey, input) {
+        return Err(T2ZError::InvalidInput(format!(
+            "Secret key cannot sign input {}",
+            input_index
+        )));
+    }
+
+    let message = secp256k1::Message::from_digest(get_sighash(pczt, input_index)?);
+    let signature = secp.sign_ecdsa_with_noncedata(&message, secret_key, &aux_rand);
+    aux_rand.zeroize();
+    secp.verify_ecdsa(&message, &signature, &pubkey)
+        .map_err(|e| T2ZError::InvalidInput(format!("Signature verification failed: {}", e)))?;
+
+    let mut signature = signature.serialize_der().to_vec();
+    signature.push(SIGHASH_ALL);
+    Ok((pubkey.serialize(), signature))
+}
+
+/// Whether `pubkey` is the input's P2PKH key or one of its P2SH multisig
+/// keys. Every candidate is compared, whatever the earlier ones gave.
+fn can_sign(pubkey: &secp256k1::PublicKey, input: &TransparentInputShadow) -> bool {
+    let mut matched = false;
+    if is_p2pkh(&input.script_pubkey) {
+        matched |= ct_eq(&input.script_pubkey[3..23], &pubkey_hash(pubkey));
+    }
+    if let Some((_, keys)) = input.redeem_script.as_deref().and_then(parse_multisig) {
+        let serialized = pubkey.serialize();
+        for key in keys {
+            matched |= ct_eq(&key.serialize(), &serialized);
+        }
+    }
+    matched
+}
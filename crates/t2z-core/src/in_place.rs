@@ -0,0 +1,124 @@
+//! In-place PCZT updates.
+//!
+//! The signing functions in the crate root take a `Pczt` by value and return
+//! the updated one, so a caller that keeps a PCZT behind a shared handle has
+//! to clone it for every signature. These variants update a `&mut Pczt`
+//! instead: the PCZT is moved into the update, never cloned. Its encoding is
+//! kept until the update succeeds, so on failure (including a PCZT the
+//! Signer role cannot parse) it is restored as it was.
+
+use zcash_protocol::consensus::BranchId;
+
+use crate::{
+    Creator, Pczt, Signer, SignerError, T2ZError, parse_pczt, update_pczt_shadow,
+    verify_transparent_signature,
+};
+
+/// Signs a transparent input in place (see `sign_transparent_input`).
+pub fn sign_transparent_input_in_place(
+    pczt: &mut Pczt,
+    input_index: usize,
+    secret_key_bytes: &[u8; 32],
+) -> Result<(), T2ZError> {
+    let secret_key = secp256k1::SecretKey::from_slice(secret_key_bytes)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+
+    #[cfg(feature = "hardened-signing")]
+    {
+        let (pubkey, signature) =
+            crate::hardening::sign_transparent(pczt, input_index, &secret_key)?;
+        replace_with(pczt, |pczt| {
+            insert_signature(pczt, input_index, pubkey, signature)
+        })
+    }
+
+    #[cfg(not(feature = "hardened-signing"))]
+    {
+        with_signer(pczt, |signer| {
+            signer.sign_transparent(input_index, &secret_key)
+        })
+    }
+}
+
+/// Appends an external transparent signature in place (see `append_signature`).
+pub fn append_signature_in_place(
+    pczt: &mut Pczt,
+    input_index: usize,
+    pubkey: &[u8; 33],
+    signature: &[u8],
+) -> Result<(), T2ZError> {
+    verify_transparent_signature(pczt, input_index, pubkey, signature)?;
+    replace_with(pczt, |pczt| {
+        insert_signature(pczt, input_index, *pubkey, signature.to_vec())
+    })
+}
+
+/// Signs an Orchard spend in place (see `sign_orchard_spend`).
+pub fn sign_orchard_spend_in_place(
+    pczt: &mut Pczt,
+    action_index: usize,
+    spending_key_bytes: &[u8; 32],
+) -> Result<(), T2ZError> {
+    let spending_key = Option::<orchard::keys::SpendingKey>::from(
+        orchard::keys::SpendingKey::from_bytes(*spending_key_bytes),
+    )
+    .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard spending key".to_string()))?;
+    let ask = orchard::keys::SpendAuthorizingKey::from(&spending_key);
+
+    with_signer(pczt, |signer| signer.sign_orchard(action_index, &ask))
+}
+
+/// Runs `f` on a Signer for `pczt`, updating it if `f` succeeds.
+fn with_signer<F>(pczt: &mut Pczt, f: F) -> Result<(), T2ZError>
+where
+    F: FnOnce(&mut Signer) -> Result<(), SignerError>,
+{
+    replace_with(pczt, |pczt| {
+        let mut signer = Signer::new(pczt)?;
+        f(&mut signer)?;
+        Ok(signer.finish())
+    })
+}
+
+/// Replaces `pczt` with `f(pczt)`, or restores it from its encoding if `f`
+/// fails.
+fn replace_with<F>(pczt: &mut Pczt, f: F) -> Result<(), T2ZError>
+where
+    F: FnOnce(Pczt) -> Result<Pczt, T2ZError>,
+{
+    let encoded = pczt.serialize();
+    match f(std::mem::replace(pczt, empty_pczt())) {
+        Ok(updated) => {
+            *pczt = updated;
+            Ok(())
+        }
+        Err(e) => {
+            *pczt = parse_pczt(&encoded)?;
+            Err(e)
+        }
+    }
+}
+
+/// Adds a transparent partial signature without checking it
+fn insert_signature(
+    pczt: Pczt,
+    input_index: usize,
+    pubkey: [u8; 33],
+    signature: Vec<u8>,
+) -> Result<Pczt, T2ZError> {
+    update_pczt_shadow(pczt, |pczt_shadow| {
+        pczt_shadow
+            .transparent
+            .inputs
+            .get_mut(input_index)
+            .ok_or_else(|| T2ZError::InvalidInput(format!("Invalid input index: {}", input_index)))?
+            .partial_signatures
+            .insert(pubkey, signature);
+        Ok(())
+    })
+}
+
+/// Cheap stand-in while the real PCZT is moved into a Signer
+fn empty_pczt() -> Pczt {
+    Creator::new(BranchId::Nu6.into(), 0, 133, [0; 32], [0; 32]).build()
+}
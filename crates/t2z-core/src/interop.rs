@@ -0,0 +1,133 @@
+//! Runtime interoperability checks against librustzcash PCZTs (`interop`
+//! feature).
+//!
+//! t2z reads and edits PCZTs through its own copy of the pczt crate's
+//! layout (`shadow`, `t2z-signer`), and `t2z-signer` computes sighashes
+//! from that layout alone. If the pczt crate changes its encoding, PCZTs
+//! exchanged with zcash_client_backend or Zashi would be silently
+//! misread. `check_interop` runs a PCZT through both implementations and
+//! reports every place they disagree, so the drift shows up when a PCZT
+//! arrives rather than when its transaction is rejected at broadcast.
+
+use pczt::roles::spend_finalizer::SpendFinalizer;
+use pczt::roles::tx_extractor::TransactionExtractor;
+use serde::{Deserialize, Serialize};
+
+use crate::shadow::PcztShadow;
+use crate::version::pczt_version;
+use crate::{Pczt, T2ZError, get_sighash};
+
+/// A disagreement between t2z and librustzcash about a PCZT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InteropIssue {
+    /// t2z's PCZT layout does not re-encode the PCZT to the bytes the pczt
+    /// crate produces (shadow struct drift)
+    ShadowMismatch(String),
+    /// `t2z-signer` cannot read the PCZT, or re-encodes it differently
+    SignerMismatch(String),
+    /// `t2z-signer` and zcash_primitives disagree on an input's sighash
+    SighashMismatch { input_index: usize },
+    /// Every input is signed, but the pczt crate cannot finalize the spends
+    FinalizeFailed(String),
+    /// The PCZT is finalized and proved, but no transaction can be extracted
+    ExtractFailed(String),
+}
+
+/// Result of `check_interop`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteropReport {
+    /// PCZT format version from the header
+    pub version: u32,
+    /// Whether spend finalization was attempted (every input signed)
+    pub finalize_checked: bool,
+    /// Whether transaction extraction was attempted (also proved)
+    pub extract_checked: bool,
+    pub issues: Vec<InteropIssue>,
+}
+
+impl InteropReport {
+    /// Whether t2z and librustzcash agree on everything checked
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks that t2z and librustzcash's pczt crate handle a serialized PCZT
+/// the same way.
+///
+/// Fails only if the pczt crate cannot parse the bytes; everything else is
+/// reported as an `InteropIssue`:
+/// - t2z's layout and `t2z-signer` must re-encode the PCZT byte for byte
+/// - both sighash implementations must agree on every transparent input
+/// - a fully signed PCZT must pass the Spend Finalizer, and a signed and
+///   proved one must also extract
+///
+/// Nothing is modified; the checks run on copies.
+pub fn check_interop(pczt_bytes: &[u8]) -> Result<InteropReport, T2ZError> {
+    let version = pczt_version(pczt_bytes)?;
+    let pczt = Pczt::parse(pczt_bytes)?;
+    let canonical = pczt.serialize();
+    let mut issues = Vec::new();
+
+    match PcztShadow::decode(&canonical).and_then(|(header, shadow)| shadow.encode(&header)) {
+        Ok(encoded) if encoded == canonical => {}
+        Ok(_) => issues.push(InteropIssue::ShadowMismatch(
+            "re-encoding changed the PCZT".to_string(),
+        )),
+        Err(e) => issues.push(InteropIssue::ShadowMismatch(e.to_string())),
+    }
+
+    let signer_pczt = match t2z_signer::Pczt::parse(&canonical) {
+        Ok(signer_pczt) => {
+            match signer_pczt.serialize() {
+                Ok(encoded) if encoded == canonical => {}
+                Ok(_) => issues.push(InteropIssue::SignerMismatch(
+                    "re-encoding changed the PCZT".to_string(),
+                )),
+                Err(e) => issues.push(InteropIssue::SignerMismatch(e.to_string())),
+            }
+            Some(signer_pczt)
+        }
+        Err(e) => {
+            issues.push(InteropIssue::SignerMismatch(e.to_string()));
+            None
+        }
+    };
+
+    let inputs = pczt.transparent().inputs();
+    if let Some(signer_pczt) = &signer_pczt {
+        for input_index in 0..inputs.len() {
+            // An input neither side can hash (e.g. not SIGHASH_ALL) is a match
+            let ours = signer_pczt.transparent_sighash(input_index).ok();
+            let theirs = get_sighash(&pczt, input_index).ok();
+            if ours != theirs {
+                issues.push(InteropIssue::SighashMismatch { input_index });
+            }
+        }
+    }
+
+    let finalize_checked = !inputs.is_empty()
+        && inputs
+            .iter()
+            .all(|input| !input.partial_signatures().is_empty() || input.script_sig().is_some());
+    let mut extract_checked = false;
+    if finalize_checked {
+        match SpendFinalizer::new(pczt.clone()).finalize_spends() {
+            Ok(finalized) => {
+                let orchard = finalized.orchard();
+                extract_checked = orchard.actions().is_empty() || orchard.zkproof().is_some();
+                if extract_checked && let Err(e) = TransactionExtractor::new(finalized).extract() {
+                    issues.push(InteropIssue::ExtractFailed(format!("{:?}", e)));
+                }
+            }
+            Err(e) => issues.push(InteropIssue::FinalizeFailed(format!("{:?}", e))),
+        }
+    }
+
+    Ok(InteropReport {
+        version,
+        finalize_checked,
+        extract_checked,
+        issues,
+    })
+}
@@ -0,0 +1,359 @@
+//! Key management for the shielded side of a shielding wallet.
+//!
+//! `OrchardAccount` wraps an Orchard spending key and derives everything a
+//! shielding-only wallet needs from it: the unified full viewing key, external
+//! receive addresses, and internal (change) addresses. The raw key bytes are
+//! zeroized when the account is dropped; the `orchard` key types can't be,
+//! so the account derives them from the bytes when asked rather than
+//! keeping them around.
+//!
+//! `TransparentKeypair` covers the transparent side: a secp256k1 key with its
+//! P2PKH address and script, either random or derived from a seed (BIP 44).
+//! Secret keys can be moved in and out of WIF, as found in zcashd
+//! `dumpwallet` files.
+
+use orchard::keys::{FullViewingKey, Scope, SpendingKey};
+use rand_core::{OsRng, RngCore};
+use zcash_address::unified::{self, Encoding};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{Network, T2ZError};
+
+/// An Orchard account backed by a spending key
+pub struct OrchardAccount {
+    spending_key_bytes: [u8; 32],
+    fvk: FullViewingKey,
+}
+
+impl OrchardAccount {
+    /// Generates a new account from a random spending key.
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+
+    /// Generates a new account from a spending key drawn from `rng`.
+    pub(crate) fn generate_with<R: RngCore>(rng: &mut R) -> Self {
+        loop {
+            let mut attempt = [0u8; 32];
+            rng.fill_bytes(&mut attempt);
+            let account = Self::from_spending_key(&attempt);
+            attempt.zeroize();
+            if let Ok(account) = account {
+                return account;
+            }
+        }
+    }
+
+    /// Restores an account from its 32-byte spending key.
+    pub fn from_spending_key(spending_key_bytes: &[u8; 32]) -> Result<Self, T2ZError> {
+        let spending_key =
+            Option::<SpendingKey>::from(SpendingKey::from_bytes(*spending_key_bytes))
+                .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard spending key".to_string()))?;
+
+        Ok(Self {
+            spending_key_bytes: *spending_key_bytes,
+            fvk: FullViewingKey::from(&spending_key),
+        })
+    }
+
+    /// Derives an account from a seed (ZIP 32: m/32'/coin_type'/account').
+    ///
+    /// # Arguments
+    /// * `seed` - 32 to 252 byte seed (e.g. a BIP 39 seed)
+    /// * `network` - Selects the coin type (133 for mainnet, 1 for testnet)
+    /// * `account` - ZIP 32 account index
+    pub fn from_seed(seed: &[u8], network: Network, account: u32) -> Result<Self, T2ZError> {
+        let account = zip32::AccountId::try_from(account)
+            .map_err(|_| T2ZError::InvalidInput(format!("Invalid account index: {}", account)))?;
+
+        let spending_key = SpendingKey::from_zip32_seed(seed, coin_type(network), account)
+            .map_err(|e| T2ZError::InvalidInput(format!("Failed to derive spending key: {:?}", e)))?;
+
+        Ok(Self {
+            spending_key_bytes: *spending_key.to_bytes(),
+            fvk: FullViewingKey::from(&spending_key),
+        })
+    }
+
+    /// The raw spending key (keep secret!)
+    pub fn spending_key_bytes(&self) -> &[u8; 32] {
+        &self.spending_key_bytes
+    }
+
+    /// The Orchard spending key. It can't be zeroized, so drop it as soon
+    /// as possible.
+    pub fn spending_key(&self) -> SpendingKey {
+        Option::from(SpendingKey::from_bytes(self.spending_key_bytes))
+            .expect("validated on construction")
+    }
+
+    /// The Orchard full viewing key
+    pub fn full_viewing_key(&self) -> &FullViewingKey {
+        &self.fvk
+    }
+
+    /// Encoded unified full viewing key with the Orchard component
+    /// (`uview1...` on mainnet, `uviewtest1...` on testnet)
+    pub fn ufvk(&self, network: Network) -> Result<String, T2ZError> {
+        let ufvk = unified::Ufvk::try_from_items(vec![unified::Fvk::Orchard(self.fvk.to_bytes())])
+            .map_err(|e| T2ZError::Builder(format!("Failed to create UFVK: {:?}", e)))?;
+        Ok(ufvk.encode(&network.to_network_type()))
+    }
+
+    /// Orchard outgoing viewing key for a scope, for encrypting outputs so
+    /// the sender can recover them (`TxBuilder::orchard_ovk`,
+    /// `inspect_pczt_with_ovk`)
+    pub fn outgoing_viewing_key(&self, scope: Scope) -> [u8; 32] {
+        *self.fvk.to_ovk(scope).as_ref()
+    }
+
+    /// Raw Orchard address at a diversifier index
+    pub fn orchard_address(&self, index: u32, scope: Scope) -> orchard::Address {
+        self.fvk.address_at(index, scope)
+    }
+
+    /// Unified address (Orchard receiver only) at a diversifier index
+    pub fn address(&self, index: u32, network: Network) -> Result<String, T2ZError> {
+        encode_orchard_address(&self.orchard_address(index, Scope::External), network)
+    }
+
+    /// Unified address (Orchard receiver only) for change, from the internal scope
+    pub fn change_address(&self, network: Network) -> Result<String, T2ZError> {
+        encode_orchard_address(&self.orchard_address(0, Scope::Internal), network)
+    }
+}
+
+impl Zeroize for OrchardAccount {
+    fn zeroize(&mut self) {
+        self.spending_key_bytes.zeroize();
+    }
+}
+
+impl Drop for OrchardAccount {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for OrchardAccount {}
+
+impl std::fmt::Debug for OrchardAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrchardAccount").finish_non_exhaustive()
+    }
+}
+
+/// A transparent secp256k1 keypair with its P2PKH address
+pub struct TransparentKeypair {
+    /// Secret key, as expected by `sign_transparent_input` (keep secret!)
+    pub secret_key: [u8; 32],
+    /// Compressed public key (33 bytes)
+    pub public_key: [u8; 33],
+    /// Encoded P2PKH address (t1... / tm...)
+    pub address: String,
+    /// P2PKH script_pubkey for the address
+    pub script_pubkey: Vec<u8>,
+}
+
+impl TransparentKeypair {
+    /// Builds the keypair for an existing secret key.
+    pub fn from_secret_key(secret_key: &[u8; 32], network: Network) -> Result<Self, T2ZError> {
+        let sk = secp256k1::SecretKey::from_slice(secret_key)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+        Ok(Self::from_secp256k1(&sk, network))
+    }
+
+    fn from_secp256k1(sk: &secp256k1::SecretKey, network: Network) -> Self {
+        let secp = secp256k1::Secp256k1::signing_only();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, sk);
+
+        let hash = match zcash_transparent::keys::pubkey_to_address(&public_key) {
+            zcash_transparent::address::TransparentAddress::PublicKeyHash(hash) => hash,
+            zcash_transparent::address::TransparentAddress::ScriptHash(_) => {
+                unreachable!("public keys map to P2PKH addresses")
+            }
+        };
+
+        let address = zcash_address::ZcashAddress::from_transparent_p2pkh(
+            network.to_network_type(),
+            hash,
+        )
+        .encode();
+
+        Self {
+            secret_key: sk.secret_bytes(),
+            public_key: public_key.serialize(),
+            address,
+            script_pubkey: p2pkh_script(&hash),
+        }
+    }
+}
+
+impl Zeroize for TransparentKeypair {
+    fn zeroize(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+impl Drop for TransparentKeypair {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for TransparentKeypair {}
+
+impl std::fmt::Debug for TransparentKeypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransparentKeypair")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Generates a random transparent keypair.
+///
+/// Intended for integration tests and simple tools that need spendable
+/// transparent inputs.
+pub fn generate_transparent_keypair(network: Network) -> TransparentKeypair {
+    loop {
+        let mut attempt = [0u8; 32];
+        OsRng.fill_bytes(&mut attempt);
+        let sk = secp256k1::SecretKey::from_slice(&attempt);
+        attempt.zeroize();
+        if let Ok(sk) = sk {
+            return TransparentKeypair::from_secp256k1(&sk, network);
+        }
+    }
+}
+
+/// Derives the external transparent keypair at
+/// m/44'/coin_type'/account'/0/address_index from a seed.
+pub fn derive_transparent_keypair(
+    seed: &[u8],
+    network: Network,
+    account: u32,
+    address_index: u32,
+) -> Result<TransparentKeypair, T2ZError> {
+    use zcash_protocol::consensus::{MainNetwork, TestNetwork};
+    use zcash_transparent::keys::{AccountPrivKey, NonHardenedChildIndex};
+
+    let account = zip32::AccountId::try_from(account)
+        .map_err(|_| T2ZError::InvalidInput(format!("Invalid account index: {}", account)))?;
+    let child = NonHardenedChildIndex::from_index(address_index).ok_or_else(|| {
+        T2ZError::InvalidInput(format!("Invalid address index: {}", address_index))
+    })?;
+
+    let account_key = match network {
+        Network::Mainnet => AccountPrivKey::from_seed(&MainNetwork, seed, account),
+        Network::Testnet => AccountPrivKey::from_seed(&TestNetwork, seed, account),
+    }
+    .map_err(|e| T2ZError::InvalidInput(format!("Failed to derive account key: {:?}", e)))?;
+
+    let sk = account_key
+        .derive_external_secret_key(child)
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to derive secret key: {:?}", e)))?;
+
+    Ok(TransparentKeypair::from_secp256k1(&sk, network))
+}
+
+/// WIF version byte for mainnet secret keys
+const WIF_MAINNET_PREFIX: u8 = 0x80;
+/// WIF version byte for testnet secret keys
+const WIF_TESTNET_PREFIX: u8 = 0xef;
+/// WIF suffix marking the key's public key as compressed
+const WIF_COMPRESSED_FLAG: u8 = 0x01;
+
+/// Decodes a WIF secret key into the 32-byte key `sign_transparent_input` expects.
+///
+/// Only keys for compressed public keys are accepted (the format zcashd
+/// exports), since the signer always uses compressed public keys.
+pub fn secret_key_from_wif(wif: &str) -> Result<([u8; 32], Network), T2ZError> {
+    let mut data = bs58::decode(wif)
+        .with_check(None)
+        .into_vec()
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid WIF: {}", e)))?;
+
+    let result = (|| {
+        let network = match data.first() {
+            Some(&WIF_MAINNET_PREFIX) => Network::Mainnet,
+            Some(&WIF_TESTNET_PREFIX) => Network::Testnet,
+            _ => return Err(T2ZError::InvalidInput("Unknown WIF version byte".to_string())),
+        };
+
+        match data.len() {
+            34 if data[33] == WIF_COMPRESSED_FLAG => {}
+            33 => {
+                return Err(T2ZError::InvalidInput(
+                    "Uncompressed WIF keys are not supported".to_string(),
+                ));
+            }
+            _ => return Err(T2ZError::InvalidInput("Invalid WIF length".to_string())),
+        }
+
+        let secret_key: [u8; 32] = data[1..33].try_into().expect("length checked above");
+        secp256k1::SecretKey::from_slice(&secret_key)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+
+        Ok((secret_key, network))
+    })();
+
+    data.zeroize();
+    result
+}
+
+/// Encodes a secret key as WIF (compressed public key form).
+pub fn secret_key_to_wif(secret_key: &[u8; 32], network: Network) -> Result<String, T2ZError> {
+    secp256k1::SecretKey::from_slice(secret_key)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+
+    let mut data = Vec::with_capacity(34);
+    data.push(match network {
+        Network::Mainnet => WIF_MAINNET_PREFIX,
+        Network::Testnet => WIF_TESTNET_PREFIX,
+    });
+    data.extend_from_slice(secret_key);
+    data.push(WIF_COMPRESSED_FLAG);
+
+    let wif = bs58::encode(&data).with_check().into_string();
+    data.zeroize();
+    Ok(wif)
+}
+
+/// P2PKH script_pubkey for a public key hash
+pub(crate) fn p2pkh_script(hash: &[u8; 20]) -> Vec<u8> {
+    let mut script = vec![0x76, 0xa9, 0x14]; // OP_DUP OP_HASH160 PUSH20
+    script.extend_from_slice(hash);
+    script.extend_from_slice(&[0x88, 0xac]); // OP_EQUALVERIFY OP_CHECKSIG
+    script
+}
+
+/// SLIP 44 coin type used for ZIP 32 derivation
+pub(crate) fn coin_type(network: Network) -> u32 {
+    match network {
+        Network::Mainnet => 133,
+        Network::Testnet => 1,
+    }
+}
+
+/// The network a ZIP 32 coin type belongs to, if it is a Zcash one
+pub(crate) fn network_for_coin_type(coin_type: u32) -> Option<Network> {
+    match coin_type {
+        133 => Some(Network::Mainnet),
+        1 => Some(Network::Testnet),
+        _ => None,
+    }
+}
+
+/// Encodes an Orchard address as a unified address with a single receiver.
+pub fn encode_orchard_address(
+    address: &orchard::Address,
+    network: Network,
+) -> Result<String, T2ZError> {
+    let ua = unified::Address::try_from_items(vec![unified::Receiver::Orchard(
+        address.to_raw_address_bytes(),
+    )])
+    .map_err(|e| T2ZError::InvalidAddress(format!("Failed to create unified address: {:?}", e)))?;
+
+    Ok(ua.encode(&network.to_network_type()))
+}
@@ -6,6 +6,17 @@
 //! This crate provides the core functionality used by platform-specific bindings:
 //! - `t2z-wasm` for browser/Node.js via WebAssembly
 //! - `t2z-uniffi` for Go, Kotlin, and Java via UniFFI
+//!
+//! `t2z-signer` holds the PCZT layout and transparent sighash without `std`,
+//! for hardware wallets that sign on-device.
+//!
+//! # Features
+//! - `prover` (default): Orchard proof creation. Builds without it ("coordinator"
+//!   builds) can still propose, inspect, sign, combine, and finalize PCZTs, but
+//!   must hand them to a prover before extraction.
+//! - `hardened-signing`: transparent inputs are signed on a re-randomized
+//!   context with randomized nonces and checked before they are added, at the
+//!   cost of reproducible signatures.
 
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
@@ -29,16 +40,22 @@ pub use pczt::roles::{
     combiner::{Combiner, Error as CombinerError},
     creator::Creator,
     io_finalizer::{Error as IoFinalizerError, IoFinalizer},
-    prover::Prover,
     signer::{Error as SignerError, Signer},
     spend_finalizer::{Error as SpendFinalizerError, SpendFinalizer},
     tx_extractor::{Error as TxExtractorError, TransactionExtractor},
 };
 pub use pczt::{ParseError, Pczt};
 
+#[cfg(feature = "prover")]
+pub use pczt::roles::prover::Prover;
+
 // Re-export orchard proving key for WASM crate
+#[cfg(feature = "prover")]
 pub use orchard::circuit::ProvingKey as OrchardProvingKey;
 
+#[cfg(feature = "verifier")]
+pub use orchard::circuit::VerifyingKey as OrchardVerifyingKey;
+
 // ============================================================================
 // Core Types (ZIP 244 and ZIP 321 compliant)
 // ============================================================================
@@ -53,6 +70,7 @@ pub struct TransparentInput {
     /// Previous output index
     pub prevout_index: u32,
     /// Output value in zatoshis (required for sighash per ZIP 244)
+    #[serde(with = "crate::amounts::amount")]
     pub value: u64,
     /// scriptPubKey of the output being spent (required for sighash per ZIP 244)
     pub script_pubkey: Vec<u8>,
@@ -66,11 +84,12 @@ pub struct Payment {
     /// Address string (unified address with Orchard, or transparent P2PKH/P2SH)
     pub address: String,
     /// Amount in zatoshis
+    #[serde(with = "crate::amounts::amount")]
     pub amount: u64,
-    /// Memo bytes (already decoded, max 512 bytes)
+    /// Memo bytes (already decoded, max 512 bytes; shielded recipients only)
     #[serde(with = "serde_bytes")]
     pub memo: Option<Vec<u8>>,
-    /// Optional label for payment
+    /// Optional label for payment (shown to signers as the output's `user_address`)
     pub label: Option<String>,
 }
 
@@ -88,6 +107,7 @@ pub struct ExpectedTxOut {
     /// Address (transparent or Orchard unified address)
     pub address: String,
     /// Amount in zatoshis
+    #[serde(with = "crate::amounts::amount")]
     pub amount: u64,
 }
 
@@ -161,6 +181,57 @@ pub enum T2ZError {
 
     #[error("Proving error: {0}")]
     Proving(String),
+
+    #[error(
+        "Proving needs an estimated {required} bytes of memory, exceeding the budget of {budget} bytes"
+    )]
+    MemoryBudgetExceeded { required: u64, budget: u64 },
+
+    #[error("Expiry height {expiry_height} is not after the current height {current_height}")]
+    ExpiryInPast {
+        expiry_height: u32,
+        current_height: u32,
+    },
+
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Amount out of range: {0}")]
+    AmountOutOfRange(String),
+
+    #[error(
+        "Too many outputs ({orchard_outputs} Orchard, {transparent_outputs} transparent) for one transaction; split the payments into {transactions_needed} transactions"
+    )]
+    TooManyOutputs {
+        orchard_outputs: usize,
+        transparent_outputs: usize,
+        transactions_needed: usize,
+    },
+
+    #[error("Block {height} does not extend the scanned chain; rewind past the fork")]
+    ChainReorg { height: u32 },
+
+    #[error("Input {index} has {confirmations} confirmations; {required} required")]
+    InsufficientConfirmations {
+        index: usize,
+        confirmations: u32,
+        required: u32,
+    },
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(InvalidSignature),
+
+    #[error("Transparent input {input} is not signed (key {pubkey})")]
+    MissingSignature { input: usize, pubkey: String },
+
+    #[error("The Orchard bundle has no proof; prove the PCZT before extracting")]
+    MissingProof,
+
+    #[error("Orchard value balance is {actual} zatoshis but its actions sum to {expected}")]
+    ValueBalanceMismatch { expected: i64, actual: i64 },
 }
 
 impl From<ParseError> for T2ZError {
@@ -208,6 +279,7 @@ impl From<SpendFinalizerError> for T2ZError {
 /// Unlike Sapling/Sprout which require downloading large proving keys from a trusted setup,
 /// Orchard uses Halo 2 which requires NO external parameters or trusted setup.
 /// The proving key is built programmatically from circuit constraints.
+#[cfg(feature = "prover")]
 static ORCHARD_PK: once_cell::sync::OnceCell<OrchardProvingKey> = once_cell::sync::OnceCell::new();
 
 /// Builds the Orchard circuit proving key (synchronous, for native targets)
@@ -223,26 +295,123 @@ static ORCHARD_PK: once_cell::sync::OnceCell<OrchardProvingKey> = once_cell::syn
 /// # Performance
 /// - First call: ~10 seconds to build circuit (one-time cost)
 /// - Subsequent calls: Instant (cached in memory)
+#[cfg(feature = "prover")]
 pub fn load_orchard_proving_key() -> &'static OrchardProvingKey {
     ORCHARD_PK.get_or_init(OrchardProvingKey::build)
 }
 
 /// Get the cached proving key if already loaded
+#[cfg(feature = "prover")]
 pub fn get_cached_proving_key() -> Option<&'static OrchardProvingKey> {
     ORCHARD_PK.get()
 }
 
 /// Check if the proving key is already loaded
+#[cfg(feature = "prover")]
 pub fn is_proving_key_loaded() -> bool {
     ORCHARD_PK.get().is_some()
 }
 
+// Orchard proof verification (verifying key only) - in separate file
+#[cfg(feature = "verifier")]
+pub mod verifier;
+
+#[cfg(feature = "verifier")]
+pub use verifier::{is_verifying_key_loaded, load_orchard_verifying_key, verify_orchard_proof};
+
+// Sapling trusted-setup parameters (load + hash check + cache) - in separate file
+#[cfg(feature = "prover")]
+pub mod sapling_params;
+
+#[cfg(feature = "prover")]
+pub use sapling_params::{
+    SaplingParameters, get_cached_sapling_params, is_sapling_params_loaded,
+    load_sapling_params_from_bytes,
+};
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+pub use sapling_params::load_sapling_params_from_path;
+
 // ============================================================================
 // Address Parsing Helpers
 // ============================================================================
 
+/// Converts a `TransparentInput` into what `Builder::add_transparent_input` takes
+pub(crate) fn transparent_input_parts(
+    input: &TransparentInput,
+) -> Result<
+    (
+        secp256k1::PublicKey,
+        zcash_transparent::bundle::OutPoint,
+        zcash_transparent::bundle::TxOut,
+    ),
+    T2ZError,
+> {
+    let pubkey_bytes: [u8; 33] = input
+        .pubkey
+        .as_slice()
+        .try_into()
+        .map_err(|_| T2ZError::InvalidInput("Public key must be 33 bytes".to_string()))?;
+
+    let pubkey = secp256k1::PublicKey::from_slice(&pubkey_bytes)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
+
+    let txid_bytes: [u8; 32] = input
+        .prevout_txid
+        .as_slice()
+        .try_into()
+        .map_err(|_| T2ZError::InvalidInput("Transaction ID must be 32 bytes".to_string()))?;
+
+    let outpoint = zcash_transparent::bundle::OutPoint::new(txid_bytes, input.prevout_index);
+
+    let script = zcash_script::script::Code(input.script_pubkey.clone());
+    let txout = zcash_transparent::bundle::TxOut::new(
+        checked_zatoshis(input.value, "Input value")?,
+        zcash_transparent::address::Script(script),
+    );
+
+    Ok((pubkey, outpoint, txout))
+}
+
+/// Converts an amount to `Zatoshis`, rejecting values above MAX_MONEY (21M ZEC)
+pub(crate) fn checked_zatoshis(value: u64, what: &str) -> Result<Zatoshis, T2ZError> {
+    Zatoshis::from_u64(value).map_err(|_| {
+        T2ZError::AmountOutOfRange(format!("{} {} exceeds 21M ZEC", what, value))
+    })
+}
+
+/// Sums amounts, rejecting any amount or total above MAX_MONEY (21M ZEC)
+pub(crate) fn checked_sum(
+    values: impl IntoIterator<Item = u64>,
+    what: &str,
+) -> Result<Zatoshis, T2ZError> {
+    let overflow = || T2ZError::AmountOutOfRange(format!("{} exceeds 21M ZEC", what));
+    values.into_iter().try_fold(Zatoshis::ZERO, |total, value| {
+        let value = Zatoshis::from_u64(value).map_err(|_| overflow())?;
+        (total + value).ok_or_else(overflow)
+    })
+}
+
+/// Pads a memo (max 512 bytes) into `MemoBytes`; `None` is the empty memo
+pub(crate) fn memo_bytes(memo: Option<&[u8]>) -> Result<zcash_protocol::memo::MemoBytes, T2ZError> {
+    match memo {
+        Some(memo) => {
+            if memo.len() > 512 {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Memo exceeds 512 bytes ({} bytes)",
+                    memo.len()
+                )));
+            }
+            let mut padded = [0u8; 512];
+            padded[..memo.len()].copy_from_slice(memo);
+            zcash_protocol::memo::MemoBytes::from_bytes(&padded)
+                .map_err(|e| T2ZError::InvalidMemo(format!("Invalid memo: {:?}", e)))
+        }
+        None => Ok(zcash_protocol::memo::MemoBytes::empty()),
+    }
+}
+
 /// Parses a transparent address from a ZcashAddress
-fn parse_transparent_address(
+pub(crate) fn parse_transparent_address(
     addr: &zcash_address::ZcashAddress,
     expected_network: NetworkType,
 ) -> Result<zcash_transparent::address::TransparentAddress, T2ZError> {
@@ -279,7 +448,7 @@ fn parse_transparent_address(
 }
 
 /// Parses an Orchard receiver from a ZcashAddress
-fn parse_orchard_receiver(
+pub(crate) fn parse_orchard_receiver(
     addr: &zcash_address::ZcashAddress,
     expected_network: NetworkType,
 ) -> Result<orchard::Address, T2ZError> {
@@ -362,6 +531,277 @@ pub fn propose_transaction(
     network: Network,
     expiry_height: u32,
 ) -> Result<Pczt, T2ZError> {
+    propose_transaction_at_height(
+        transparent_inputs,
+        request,
+        change_address,
+        network,
+        expiry_height,
+        None,
+    )
+}
+
+/// Like `propose_transaction`, but checks the expiry against the chain tip.
+///
+/// # Expiry
+/// * `expiry_height == 0` builds a transaction that never expires. Consensus
+///   rules are then selected for `current_height + 1`, so `current_height`
+///   is required.
+/// * Otherwise, if `current_height` is given, `expiry_height` must be above it
+///   or `T2ZError::ExpiryInPast` is returned instead of a transaction that
+///   could never be mined.
+///
+/// # Arguments
+/// * `transparent_inputs` - UTXOs to spend
+/// * `request` - ZIP 321 transaction request (payments only)
+/// * `change_address` - Optional address for change (transparent or Orchard)
+/// * `network` - Mainnet or Testnet
+/// * `expiry_height` - Transaction expiry height, or 0 for no expiry
+/// * `current_height` - Current chain tip height, if known
+pub fn propose_transaction_at_height(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    expiry_height: u32,
+    current_height: Option<u32>,
+) -> Result<Pczt, T2ZError> {
+    propose_transaction_with_fee_strategy(
+        transparent_inputs,
+        request,
+        change_address,
+        network,
+        expiry_height,
+        current_height,
+        FeeStrategy::zip317(),
+    )
+}
+
+/// Like `propose_transaction_at_height`, with control over the fee.
+///
+/// `FeeStrategy::Fixed` pays exactly the given fee (e.g. to overpay for
+/// inclusion, or on a testnet); the caller is responsible for it meeting
+/// the network's relay rules. `FeeStrategy::Custom` uses any
+/// `zcash_primitives` fee rule.
+pub fn propose_transaction_with_fee_strategy<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    expiry_height: u32,
+    current_height: Option<u32>,
+    fee_strategy: FeeStrategy<FR>,
+) -> Result<Pczt, T2ZError>
+where
+    FR: zcash_primitives::transaction::fees::FeeRule,
+    FR::Error: std::fmt::Debug,
+{
+    propose_with_fee_strategy(
+        transparent_inputs,
+        request,
+        change_address,
+        network,
+        expiry_height,
+        current_height,
+        fee_strategy,
+        TxDefaults::default(),
+        None,
+        &mut ProposalRng::new(None),
+    )
+}
+
+/// `propose_transaction_with_fee_strategy`, with the Builder's defaults
+/// overridden (see `ProposalOptions::tx_defaults`), optionally against a
+/// given Orchard anchor (see `ProposalOptions::orchard_anchor`), drawing
+/// randomness from `rng` (seeded from `ProposalOptions::rng_seed`).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn propose_with_fee_strategy<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    expiry_height: u32,
+    current_height: Option<u32>,
+    fee_strategy: FeeStrategy<FR>,
+    tx_defaults: TxDefaults,
+    orchard_anchor: Option<orchard::Anchor>,
+    rng: &mut ProposalRng,
+) -> Result<Pczt, T2ZError>
+where
+    FR: zcash_primitives::transaction::fees::FeeRule,
+    FR::Error: std::fmt::Debug,
+{
+    macro_rules! propose_with {
+        ($fee_rule:expr) => {
+            propose_with_fee_rule(
+                transparent_inputs,
+                request,
+                change_address,
+                network,
+                expiry_height,
+                current_height,
+                $fee_rule,
+                tx_defaults,
+                orchard_anchor,
+                rng,
+            )
+        };
+    }
+
+    match fee_strategy {
+        FeeStrategy::Zip317 => propose_with!(&FeeRule::standard()),
+        FeeStrategy::Fixed(fee) => {
+            let fee = checked_zatoshis(fee, "Fee")?;
+            propose_with!(&zcash_primitives::transaction::fees::fixed::FeeRule::non_standard(fee))
+        }
+        FeeStrategy::Custom(fee_rule) => propose_with!(&fee_rule),
+    }
+}
+
+/// Re-proposes a transaction that expired unmined, with a later expiry and a
+/// higher fee.
+///
+/// Spends the same inputs and pays the same request as the original, so a
+/// broadcast service can replace a dead transaction without reconstructing
+/// it. The fee is the ZIP 317 fee times `fee_multiplier`, rounded up, and is
+/// taken out of the change.
+///
+/// # Arguments
+/// * `original_request` - ZIP 321 request of the expired transaction
+/// * `transparent_inputs` - The UTXOs it spent (still unspent, since it expired)
+/// * `change_address` - Change address of the original transaction
+/// * `network` - Mainnet or Testnet
+/// * `new_expiry` - Expiry height of the replacement
+/// * `fee_multiplier` - Fee bump relative to ZIP 317 (at least 1.0)
+pub fn rebuild_with_new_expiry(
+    original_request: &TransactionRequest,
+    transparent_inputs: &[TransparentInput],
+    change_address: Option<&str>,
+    network: Network,
+    new_expiry: u32,
+    fee_multiplier: f64,
+) -> Result<Pczt, T2ZError> {
+    if !fee_multiplier.is_finite() || fee_multiplier < 1.0 {
+        return Err(T2ZError::InvalidInput(format!(
+            "Fee multiplier must be at least 1.0 (got {})",
+            fee_multiplier
+        )));
+    }
+
+    let base_fee =
+        preview_transaction(transparent_inputs, original_request, change_address, network)?.fee;
+    let fee = (base_fee as f64 * fee_multiplier).ceil() as u64;
+
+    propose_transaction_with_fee_strategy(
+        transparent_inputs,
+        original_request.clone(),
+        change_address,
+        network,
+        new_expiry,
+        None,
+        FeeStrategy::fixed(fee),
+    )
+}
+
+/// Moves memos off transparent payments into zero-value Orchard outputs.
+///
+/// Transparent outputs cannot carry a memo, so proposing a request with one
+/// fails. This rewrites the request instead: each such memo is removed from
+/// its payment and sent in a separate zero-value "marker" output to
+/// `orchard_address` (typically the wallet's own Orchard change address),
+/// so the memo is kept on chain for the sender.
+///
+/// # Arguments
+/// * `request` - ZIP 321 transaction request
+/// * `orchard_address` - Address without transparent receivers that can
+///   receive Orchard funds
+pub fn redirect_transparent_memos(
+    mut request: TransactionRequest,
+    orchard_address: &str,
+) -> Result<TransactionRequest, T2ZError> {
+    let marker_addr = zcash_address::ZcashAddress::try_from_encoded(orchard_address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid memo address: {:?}", e)))?;
+    // Addresses with a transparent receiver are paid transparently
+    if marker_addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT)
+        || !marker_addr.can_receive_as(zcash_protocol::PoolType::ORCHARD)
+    {
+        return Err(T2ZError::InvalidAddress(
+            "Memo address must be an Orchard-only address".to_string(),
+        ));
+    }
+
+    let mut markers = Vec::new();
+    for payment in &mut request.payments {
+        if payment.memo.is_none() {
+            continue;
+        }
+
+        let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
+            .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+        if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+            markers.push(Payment {
+                address: orchard_address.to_string(),
+                amount: 0,
+                memo: payment.memo.take(),
+                label: None,
+            });
+        }
+    }
+
+    request.payments.extend(markers);
+    Ok(request)
+}
+
+/// Height whose consensus rules (branch ID) a transaction is built for.
+///
+/// That is the expiry height, or the block after `current_height` for a
+/// transaction that never expires. Fails if the expiry is not after
+/// `current_height`.
+pub(crate) fn target_height(
+    expiry_height: u32,
+    current_height: Option<u32>,
+) -> Result<u32, T2ZError> {
+    if expiry_height == 0 {
+        current_height
+            .and_then(|h| h.checked_add(1))
+            .ok_or_else(|| {
+                T2ZError::InvalidInput(
+                    "expiry_height 0 (no expiry) requires current_height".to_string(),
+                )
+            })
+    } else {
+        if let Some(current_height) = current_height
+            && expiry_height <= current_height
+        {
+            return Err(T2ZError::ExpiryInPast {
+                expiry_height,
+                current_height,
+            });
+        }
+        Ok(expiry_height)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn propose_with_fee_rule<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    expiry_height: u32,
+    current_height: Option<u32>,
+    fee_rule: &FR,
+    tx_defaults: TxDefaults,
+    orchard_anchor: Option<orchard::Anchor>,
+    rng: &mut ProposalRng,
+) -> Result<Pczt, T2ZError>
+where
+    FR: zcash_primitives::transaction::fees::FeeRule,
+    FR::Error: std::fmt::Debug,
+{
+    let no_expiry = expiry_height == 0;
+    let target_height = target_height(expiry_height, current_height)?;
+
     if transparent_inputs.is_empty() {
         return Err(T2ZError::InvalidInput(
             "No transparent inputs provided".to_string(),
@@ -388,10 +828,13 @@ pub fn propose_transaction(
                 input.prevout_txid.len()
             )));
         }
+        checked_zatoshis(input.value, &format!("Input {} value", idx))?;
     }
 
-    // Validate memo sizes (ZIP 321: max 512 bytes)
+    // Validate amounts and memo sizes (ZIP 321: max 512 bytes)
     for (idx, payment) in request.payments.iter().enumerate() {
+        checked_zatoshis(payment.amount, &format!("Payment {} amount", idx))?;
+
         if let Some(memo) = &payment.memo
             && memo.len() > 512
         {
@@ -439,11 +882,18 @@ pub fn propose_transaction(
     let mut _num_transparent_outputs = 0usize;
     let mut num_orchard_outputs = 0usize;
 
-    for payment in &request.payments {
+    for (idx, payment) in request.payments.iter().enumerate() {
         let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
             .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
 
         if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+            // Transparent outputs cannot carry a memo; never drop one silently
+            if payment.memo.is_some() {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} has a memo but pays a transparent receiver, which cannot carry one",
+                    idx
+                )));
+            }
             _num_transparent_outputs += 1;
         } else if addr.can_receive_as(zcash_protocol::PoolType::ORCHARD) {
             num_orchard_outputs += 1;
@@ -456,15 +906,17 @@ pub fn propose_transaction(
     }
 
     // Calculate totals
-    let total_input: u64 = transparent_inputs.iter().map(|i| i.value).sum();
-    let total_payment: u64 = request.payments.iter().map(|p| p.amount).sum();
+    let total_input = checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+    let total_payment = checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?;
 
     // Determine if we'll have any Orchard outputs (affects builder config)
     let has_orchard =
         num_orchard_outputs > 0 || matches!(change_dest_type, Some(ChangeDestination::Orchard(_)));
 
+    // Without spends of real notes any anchor is valid, so the empty tree's
+    // serves unless the caller has one from the chain
     let orchard_anchor = if has_orchard {
-        Some(orchard::Anchor::empty_tree())
+        Some(orchard_anchor.unwrap_or_else(orchard::Anchor::empty_tree))
     } else {
         None
     };
@@ -473,171 +925,157 @@ pub fn propose_transaction(
     // We need to handle this with a macro/match since Builder is generic over Parameters
     macro_rules! build_transaction {
         ($params:expr) => {{
-            let fee_rule = FeeRule::standard();
-
-            let mut builder = Builder::new(
-                $params,
-                BlockHeight::from_u32(expiry_height),
-                BuildConfig::Standard {
-                    sapling_anchor: None,
-                    orchard_anchor,
-                },
-            );
-
-            // Add transparent inputs
-            for input in transparent_inputs {
-                let pubkey_bytes: [u8; 33] = input.pubkey.as_slice().try_into().map_err(|_| {
-                    T2ZError::InvalidInput("Public key must be 33 bytes".to_string())
-                })?;
-
-                let pubkey = secp256k1::PublicKey::from_slice(&pubkey_bytes)
-                    .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
-
-                let txid_bytes: [u8; 32] =
-                    input.prevout_txid.as_slice().try_into().map_err(|_| {
-                        T2ZError::InvalidInput("Transaction ID must be 32 bytes".to_string())
-                    })?;
-
-                let outpoint =
-                    zcash_transparent::bundle::OutPoint::new(txid_bytes, input.prevout_index);
-
-                let script = zcash_script::script::Code(input.script_pubkey.clone());
-                let txout = zcash_transparent::bundle::TxOut::new(
-                    Zatoshis::from_u64(input.value)
-                        .map_err(|e| T2ZError::InvalidInput(format!("Invalid value: {:?}", e)))?,
-                    zcash_transparent::address::Script(script),
+            // Starts a builder with the inputs and payments; rerun if the
+            // Orchard change output turns out to change the fee
+            let start_builder = || -> Result<_, T2ZError> {
+                let mut builder = Builder::new(
+                    $params,
+                    BlockHeight::from_u32(target_height),
+                    BuildConfig::Standard {
+                        sapling_anchor: None,
+                        orchard_anchor,
+                    },
                 );
 
-                builder
-                    .add_transparent_input(pubkey, outpoint, txout)
-                    .map_err(|e| {
-                        T2ZError::Builder(format!("Failed to add transparent input: {:?}", e))
-                    })?;
-            }
-
-            // Add payment outputs
-            for payment in &request.payments {
-                let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
-                    .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+                // Add transparent inputs
+                for input in transparent_inputs {
+                    let (pubkey, outpoint, txout) = transparent_input_parts(input)?;
 
-                if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
-                    let t_addr = parse_transparent_address(&addr, expected_network)?;
                     builder
-                        .add_transparent_output(
-                            &t_addr,
-                            Zatoshis::from_u64(payment.amount).map_err(|e| {
-                                T2ZError::InvalidInput(format!("Invalid amount: {:?}", e))
-                            })?,
-                        )
+                        .add_transparent_input(pubkey, outpoint, txout)
                         .map_err(|e| {
-                            T2ZError::Builder(format!("Failed to add transparent output: {:?}", e))
+                            T2ZError::Builder(format!("Failed to add transparent input: {:?}", e))
                         })?;
-                } else if addr.can_receive_as(zcash_protocol::PoolType::ORCHARD) {
-                    let orchard_receiver = parse_orchard_receiver(&addr, expected_network)?;
-
-                    let memo_bytes = if let Some(memo) = &payment.memo {
-                        let mut padded = [0u8; 512];
-                        padded[..memo.len()].copy_from_slice(memo);
-                        zcash_protocol::memo::MemoBytes::from_bytes(&padded)
-                            .map_err(|e| T2ZError::InvalidMemo(format!("Invalid memo: {:?}", e)))?
-                    } else {
-                        zcash_protocol::memo::MemoBytes::empty()
-                    };
+                }
 
-                    builder
-                        .add_orchard_output::<FeeRule>(
-                            None,
-                            orchard_receiver,
-                            payment.amount,
-                            memo_bytes,
-                        )
-                        .map_err(|e| {
-                            T2ZError::Builder(format!("Failed to add Orchard output: {:?}", e))
-                        })?;
+                // What each output's `user_address` will show signers, in the order
+                // outputs are added to each pool
+                let mut transparent_user_addresses: Vec<String> = Vec::new();
+                let mut orchard_user_addresses: Vec<String> = Vec::new();
+
+                // Add payment outputs
+                for payment in &request.payments {
+                    let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
+                        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+
+                    if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+                        let t_addr = parse_transparent_address(&addr, expected_network)?;
+                        builder
+                            .add_transparent_output(
+                                &t_addr,
+                                checked_zatoshis(payment.amount, "Payment amount")?,
+                            )
+                            .map_err(|e| {
+                                T2ZError::Builder(format!("Failed to add transparent output: {:?}", e))
+                            })?;
+                        transparent_user_addresses.push(payment_user_address(payment));
+                    } else if addr.can_receive_as(zcash_protocol::PoolType::ORCHARD) {
+                        let orchard_receiver = parse_orchard_receiver(&addr, expected_network)?;
+
+                        let memo_bytes = memo_bytes(payment.memo.as_deref())?;
+
+                        builder
+                            .add_orchard_output::<FeeRule>(
+                                None,
+                                orchard_receiver,
+                                payment.amount,
+                                memo_bytes,
+                            )
+                            .map_err(|e| {
+                                T2ZError::Builder(format!("Failed to add Orchard output: {:?}", e))
+                            })?;
+                        orchard_user_addresses.push(payment_user_address(payment));
+                    }
                 }
-            }
+                Ok((builder, transparent_user_addresses, orchard_user_addresses))
+            };
+            let (mut builder, mut transparent_user_addresses, mut orchard_user_addresses) =
+                start_builder()?;
 
             // Calculate fee and change with iteration to handle Orchard change affecting fee.
             // When change goes to Orchard, adding the change output increases the action count,
             // which might increase the ZIP-317 fee. We need to iterate to find the stable values.
             let mut change_added = false;
+            let mut transparent_change = false;
             let mut final_change = 0u64;
 
             // First pass: calculate fee without change
-            let fee = builder.get_fee(&fee_rule)
+            let fee = builder.get_fee(fee_rule)
                 .map_err(|e| T2ZError::Builder(format!("Failed to calculate fee: {:?}", e)))?;
 
             // Calculate initial change
-            let change = total_input
-                .checked_sub(total_payment)
-                .and_then(|v| v.checked_sub(fee.into_u64()))
+            let change = (total_input - total_payment)
+                .and_then(|v| v - fee)
                 .ok_or_else(|| T2ZError::InsufficientFunds {
-                    available: total_input,
-                    required: total_payment + fee.into_u64(),
-                    payment: total_payment,
+                    available: total_input.into_u64(),
+                    required: total_payment.into_u64().saturating_add(fee.into_u64()),
+                    payment: total_payment.into_u64(),
                     fee: fee.into_u64(),
                 })?;
 
             // If there's change, we need a change address
-            if change > 0 && change_dest_type.is_none() {
-                return Err(T2ZError::ChangeRequired { change });
+            if change > Zatoshis::ZERO && change_dest_type.is_none() {
+                return Err(T2ZError::ChangeRequired { change: change.into_u64() });
             }
 
             // Handle change with iteration for Orchard (since adding Orchard change affects fee)
-            if change > 0 {
+            if change > Zatoshis::ZERO {
                 match &change_dest_type {
                     Some(ChangeDestination::Transparent(t_addr)) => {
                         // Transparent change doesn't affect Orchard action count, so no iteration needed
                         builder
-                            .add_transparent_output(
-                                t_addr,
-                                Zatoshis::from_u64(change).map_err(|e| {
-                                    T2ZError::InvalidInput(format!("Invalid change amount: {:?}", e))
-                                })?,
-                            )
+                            .add_transparent_output(t_addr, change)
                             .map_err(|e| {
                                 T2ZError::Builder(format!("Failed to add transparent change output: {:?}", e))
                             })?;
-                        final_change = change;
+                        final_change = change.into_u64();
                         change_added = true;
+                        transparent_change = true;
+                        transparent_user_addresses.extend(change_address.map(str::to_string));
                     }
                     Some(ChangeDestination::Orchard(orchard_addr)) => {
                         // Orchard change affects action count → affects fee. Iterate to stabilize.
                         // Add a placeholder change output to calculate the correct fee
-                        builder
-                            .add_orchard_output::<FeeRule>(
-                                None,
-                                *orchard_addr,
-                                change, // Use current estimate
-                                zcash_protocol::memo::MemoBytes::empty(),
-                            )
-                            .map_err(|e| {
-                                T2ZError::Builder(format!("Failed to add Orchard change output: {:?}", e))
-                            })?;
+                        let add_orchard_change = |builder: &mut Builder<'_, _, _>, change: Zatoshis| {
+                            builder
+                                .add_orchard_output::<FeeRule>(
+                                    None,
+                                    *orchard_addr,
+                                    change.into_u64(),
+                                    zcash_protocol::memo::MemoBytes::empty(),
+                                )
+                                .map_err(|e| {
+                                    T2ZError::Builder(format!("Failed to add Orchard change output: {:?}", e))
+                                })
+                        };
+                        add_orchard_change(&mut builder, change)?;
                         change_added = true;
+                        orchard_user_addresses.extend(change_address.map(str::to_string));
 
                         // Recalculate fee with the change output included
-                        let new_fee = builder.get_fee(&fee_rule)
+                        let new_fee = builder.get_fee(fee_rule)
                             .map_err(|e| T2ZError::Builder(format!("Failed to recalculate fee: {:?}", e)))?;
 
                         // Recalculate change with new fee
-                        let new_change = total_input
-                            .checked_sub(total_payment)
-                            .and_then(|v| v.checked_sub(new_fee.into_u64()))
+                        let new_change = (total_input - total_payment)
+                            .and_then(|v| v - new_fee)
                             .ok_or_else(|| T2ZError::InsufficientFunds {
-                                available: total_input,
-                                required: total_payment + new_fee.into_u64(),
-                                payment: total_payment,
+                                available: total_input.into_u64(),
+                                required: total_payment.into_u64().saturating_add(new_fee.into_u64()),
+                                payment: total_payment.into_u64(),
                                 fee: new_fee.into_u64(),
                             })?;
 
-                        // The change output was already added with the old value.
-                        // The Builder will use the fee_rule at build time, so the actual
-                        // change value embedded in the action may differ from what we calculated.
-                        // However, the Builder's build_for_pczt will enforce the correct fee.
-                        // We just need to make sure we have enough funds.
-                        final_change = new_change;
-                        let _ = new_fee; // Fee was recalculated and validated
+                        // The change output was added at the old value, which no
+                        // longer balances if it raised the action count; start
+                        // over with the change reduced by the extra fee
+                        if new_fee != fee {
+                            (builder, transparent_user_addresses, orchard_user_addresses) =
+                                start_builder()?;
+                            add_orchard_change(&mut builder, new_change)?;
+                            orchard_user_addresses.extend(change_address.map(str::to_string));
+                        }
+                        final_change = new_change.into_u64();
                     }
                     None => unreachable!(), // Already checked above
                 }
@@ -649,24 +1087,249 @@ pub fn propose_transaction(
 
             // Build PCZT using the same fee rule we used to calculate the fee
             let result = builder
-                .build_for_pczt(OsRng, &fee_rule)
+                .build_for_pczt(&mut *rng, fee_rule)
                 .map_err(|e| T2ZError::Builder(format!("Failed to build PCZT: {:?}", e)))?;
 
+            let orchard_meta = result.orchard_meta;
             let pczt = Creator::build_from_parts(result.pczt_parts)
                 .ok_or_else(|| T2ZError::Builder("Failed to create PCZT from parts".to_string()))?;
 
-            IoFinalizer::new(pczt).finalize_io()
+            let pczt = update_pczt_shadow(pczt, |pczt_shadow| {
+                // The Builder always sets an expiry; clear it before IO finalization
+                // so the dummy spend signatures commit to the final value.
+                if no_expiry {
+                    pczt_shadow.global.expiry_height = 0;
+                }
+
+                // Likewise the fields the Builder has no setting for
+                let (tx_version, version_group_id) = tx_defaults.version.header();
+                pczt_shadow.global.tx_version = tx_version;
+                pczt_shadow.global.version_group_id = version_group_id;
+                if let Some(lock_time) = tx_defaults.fallback_lock_time {
+                    pczt_shadow.global.fallback_lock_time = Some(lock_time);
+                }
+                for (shadow, input) in pczt_shadow
+                    .transparent
+                    .inputs
+                    .iter_mut()
+                    .zip(transparent_inputs)
+                {
+                    if let Some(sequence) = input.sequence.or(tx_defaults.sequence) {
+                        shadow.sequence = Some(sequence);
+                    }
+                }
+
+                // Transparent outputs keep their order; Orchard actions are shuffled
+                for (output, user_address) in pczt_shadow
+                    .transparent
+                    .outputs
+                    .iter_mut()
+                    .zip(&transparent_user_addresses)
+                {
+                    output.user_address = Some(user_address.clone());
+                }
+                // Transparent change is added last; mark it so it can be told
+                // apart from a payment to the same address
+                if transparent_change
+                    && let Some(output) = pczt_shadow.transparent.outputs.last_mut()
+                {
+                    output
+                        .proprietary
+                        .insert(adjust::CHANGE_OUTPUT_KEY.to_string(), Vec::new());
+                }
+                for (idx, user_address) in orchard_user_addresses.iter().enumerate() {
+                    if let Some(action) = orchard_meta
+                        .output_action_index(idx)
+                        .and_then(|action_idx| pczt_shadow.orchard.actions.get_mut(action_idx))
+                    {
+                        action.output.user_address = Some(user_address.clone());
+                    }
+                }
+                Ok(())
+            })?;
+
+            let unfinalized = rng.is_seeded().then(|| pczt.clone());
+            let finalized = IoFinalizer::new(pczt).finalize_io()?;
+            match unfinalized {
+                Some(unfinalized) => resign_dummy_spends(&unfinalized, finalized, rng),
+                None => Ok(finalized),
+            }
         }};
     }
 
-    let pczt = match network {
+    match network {
         Network::Mainnet => build_transaction!(MainNetwork),
         Network::Testnet => build_transaction!(TestNetwork),
-    }?;
+    }
+}
 
-    Ok(pczt)
+/// Randomness for building a proposal: the OS RNG, or a ChaCha20 stream
+/// from `ProposalOptions::rng_seed`
+enum ProposalRng {
+    Os(OsRng),
+    Seeded(rand_chacha::ChaCha20Rng),
+}
+
+impl ProposalRng {
+    fn new(seed: Option<[u8; 32]>) -> Self {
+        match seed {
+            Some(seed) => ProposalRng::Seeded(rand_core::SeedableRng::from_seed(seed)),
+            None => ProposalRng::Os(OsRng),
+        }
+    }
+
+    fn is_seeded(&self) -> bool {
+        matches!(self, ProposalRng::Seeded(_))
+    }
+}
+
+impl rand_core::RngCore for ProposalRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            ProposalRng::Os(rng) => rng.next_u32(),
+            ProposalRng::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            ProposalRng::Os(rng) => rng.next_u64(),
+            ProposalRng::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            ProposalRng::Os(rng) => rng.fill_bytes(dest),
+            ProposalRng::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        match self {
+            ProposalRng::Os(rng) => rng.try_fill_bytes(dest),
+            ProposalRng::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
 }
 
+impl rand_core::CryptoRng for ProposalRng {}
+
+/// Re-signs the dummy Orchard spends of `finalized` with `rng`.
+///
+/// The IO Finalizer signs dummy spends with OS randomness, so a seeded
+/// proposal redoes those signatures from the unfinalized bundle to stay
+/// reproducible.
+fn resign_dummy_spends(
+    unfinalized: &Pczt,
+    finalized: Pczt,
+    rng: &mut ProposalRng,
+) -> Result<Pczt, T2ZError> {
+    let sighash = get_shielded_sighash(&finalized)?;
+
+    let mut bundle = unfinalized
+        .orchard()
+        .clone()
+        .into_parsed()
+        .map_err(|e| T2ZError::Builder(format!("Failed to parse Orchard bundle: {:?}", e)))?;
+    bundle
+        .finalize_io(sighash, &mut *rng)
+        .map_err(|e| T2ZError::Builder(format!("Failed to sign dummy spends: {:?}", e)))?;
+    let signatures = bundle
+        .actions()
+        .iter()
+        .map(|action| action.spend().spend_auth_sig().as_ref().map(<[u8; 64]>::from))
+        .collect::<Vec<_>>();
+
+    update_pczt_shadow(finalized, |pczt_shadow| {
+        for (action, signature) in pczt_shadow.orchard.actions.iter_mut().zip(signatures) {
+            if action.spend.dummy_sk.is_some() {
+                action.spend.spend_auth_sig = signature;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// What a payment's output shows in `user_address`: its label if it has one,
+/// otherwise the address exactly as given.
+fn payment_user_address(payment: &Payment) -> String {
+    payment.label.clone().unwrap_or_else(|| payment.address.clone())
+}
+
+// Address screening and other proposal policies - in separate file
+pub mod policy;
+
+pub use policy::{
+    AddressRole, AddressScreen, ProposalPolicy, SigningRule, SigningRules, SpendLimits,
+    propose_transaction_with_policy, verify_before_signing_with_limits,
+    verify_before_signing_with_rules,
+};
+
+// Serializable pre-PCZT proposals - in separate file
+pub mod proposal;
+
+pub use proposal::{Proposal, ProposalBuilder};
+
+// Low-level builder facade for advanced transaction shapes - in separate file
+pub mod tx_builder;
+
+pub use tx_builder::TxBuilder;
+
+// ZIP 321 request validation for UIs - in separate file
+pub mod validation;
+
+pub use validation::{
+    AddressHistory, IssueSeverity, RequestIssue, RequestIssueKind, check_address_reuse,
+    validate_request,
+};
+
+// ZIP 321 payment URIs and their test vectors - in separate file
+pub mod zip321;
+
+pub use zip321::{Zip321Vector, parse_payment_uri, payment_uri};
+
+// Builder-free fee and change previews - in separate file
+pub mod preview;
+
+pub use preview::{Preview, preview_transaction};
+
+// Expiry checks for PCZTs and extracted transactions - in separate file
+pub mod expiry;
+
+pub use expiry::Expiry;
+
+// Stateless pre-broadcast checks on extracted transactions - in separate file
+pub mod consensus;
+
+pub use consensus::{
+    MAX_STANDARD_SCRIPT_SIG_SIZE, MAX_STANDARD_TX_SIZE, TX_EXPIRING_SOON_THRESHOLD, TxIssue,
+    TxIssueKind, check_consensus_rules,
+};
+
+// Proposal options (fee, expiry, padding, change, policy) - in separate file
+pub mod options;
+
+pub use options::{
+    ChangePolicy, DEFAULT_EXPIRY_DELTA, DEFAULT_MAX_ORCHARD_OUTPUTS,
+    DEFAULT_MAX_TRANSPARENT_OUTPUTS, DuplicateRecipients, ExpiryPolicy, OutputLimits,
+    ProposalOptions, TxDefaults, TxVersion, non_round_split_fee, propose_transaction_with_options,
+};
+
+// Minimum-confirmation and coinbase checks on proposal inputs - in separate file
+pub mod confirmations;
+
+pub use confirmations::{
+    COINBASE_MATURITY, ConfirmationPolicy, InputConfirmations, is_coinbase_transaction,
+};
+
+// Reusable payment templates for recurring payouts - in separate file
+pub mod template;
+
+pub use template::{
+    PaymentTemplate, TemplateRecipient, instantiate, propose_coinbase_shielding,
+};
+
 /// Adds Orchard proofs to the PCZT using the Prover role.
 ///
 /// This uses the cached proving key if available, otherwise builds it first.
@@ -674,6 +1337,7 @@ pub fn propose_transaction(
 /// # Performance
 /// - First call: ~10 seconds (builds Halo 2 circuit, no download required)
 /// - Subsequent calls: Fast (uses cached circuit)
+#[cfg(feature = "prover")]
 pub fn prove_transaction(pczt: Pczt) -> Result<Pczt, T2ZError> {
     let proving_key = load_orchard_proving_key();
     prove_transaction_with_key(pczt, proving_key)
@@ -682,6 +1346,7 @@ pub fn prove_transaction(pczt: Pczt) -> Result<Pczt, T2ZError> {
 /// Adds Orchard proofs to the PCZT using the Prover role with a provided key.
 ///
 /// Use this if you want to manage the proving key lifecycle yourself.
+#[cfg(feature = "prover")]
 pub fn prove_transaction_with_key(
     pczt: Pczt,
     proving_key: &OrchardProvingKey,
@@ -697,6 +1362,139 @@ pub fn prove_transaction_with_key(
     Ok(prover.finish())
 }
 
+// ============================================================================
+// Proving Memory Estimates
+// ============================================================================
+//
+// The Orchard circuit has 2^11 rows, one circuit instance per action, so
+// each polynomial is 64 KiB (2^11 field elements of 32 bytes), and several
+// times that on Halo 2's extended evaluation domain. The proving key holds
+// the fixed and permutation polynomials in each form; a proof adds the
+// advice, lookup and permutation-product polynomials of every action. The
+// constants round those counts up generously rather than being exact; check
+// them with `examples/proving_memory.rs` (`just bench-proving-memory`), which
+// prints the measured peak next to the estimate.
+
+/// Estimated memory held by a built Orchard proving key
+pub const PROVING_KEY_MEMORY_ESTIMATE: u64 = 96 * 1024 * 1024;
+
+/// Estimated fixed working memory for a single Halo 2 proof, independent of action count
+pub const PROVING_BASE_MEMORY_ESTIMATE: u64 = 48 * 1024 * 1024;
+
+/// Estimated additional working memory per Orchard action in the proof
+pub const PROVING_PER_ACTION_MEMORY_ESTIMATE: u64 = 24 * 1024 * 1024;
+
+/// Memory limit for local proving.
+///
+/// Halo 2 builds one proof over all Orchard actions at once, so peak memory
+/// grows with the action count and can't be capped mid-proof. Instead the
+/// budgeted proving path estimates the peak up front and refuses to start if
+/// it wouldn't fit, letting the caller delegate proving elsewhere.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProvingBudget {
+    /// Maximum bytes proving may use, including building the proving key if it isn't cached
+    pub max_memory_bytes: u64,
+}
+
+/// Estimates peak memory (bytes) needed to prove `num_actions` Orchard actions.
+///
+/// These are conservative estimates rather than exact figures. Set
+/// `include_proving_key` if the proving key has not been built yet.
+pub fn estimate_proving_memory(num_actions: usize, include_proving_key: bool) -> u64 {
+    if num_actions == 0 {
+        return 0;
+    }
+
+    let key = if include_proving_key {
+        PROVING_KEY_MEMORY_ESTIMATE
+    } else {
+        0
+    };
+
+    key + PROVING_BASE_MEMORY_ESTIMATE + PROVING_PER_ACTION_MEMORY_ESTIMATE * num_actions as u64
+}
+
+/// Estimates peak memory (bytes) needed to prove a PCZT in this process.
+///
+/// Returns 0 if the PCZT has no Orchard actions or already has its proof.
+pub fn estimate_pczt_proving_memory(pczt: &Pczt) -> u64 {
+    let orchard = pczt.orchard();
+    if orchard.zkproof().is_some() {
+        return 0;
+    }
+
+    #[cfg(feature = "prover")]
+    let include_proving_key = !is_proving_key_loaded();
+    #[cfg(not(feature = "prover"))]
+    let include_proving_key = true;
+
+    estimate_proving_memory(orchard.actions().len(), include_proving_key)
+}
+
+/// Adds Orchard proofs only if the estimated peak memory fits within `budget`.
+///
+/// Returns `MemoryBudgetExceeded` without doing any work otherwise.
+#[cfg(feature = "prover")]
+pub fn prove_transaction_with_budget(
+    pczt: Pczt,
+    budget: ProvingBudget,
+) -> Result<Pczt, T2ZError> {
+    let required = estimate_pczt_proving_memory(&pczt);
+    if required > budget.max_memory_bytes {
+        return Err(T2ZError::MemoryBudgetExceeded {
+            required,
+            budget: budget.max_memory_bytes,
+        });
+    }
+
+    prove_transaction(pczt)
+}
+
+// Multi-threaded proving queue for native services - in separate file
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+pub mod prover_pool;
+
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+pub use prover_pool::{ProofHandle, ProverPool};
+
+// Remote (delegated) proving with integrity checks - in separate file
+pub mod delegation;
+
+#[cfg(feature = "prover")]
+pub use delegation::{handle_detached_proof_request, handle_proof_request};
+pub use delegation::{
+    ProofRequest, ProofResponse, accept_proof_response, create_proof_request,
+    export_proof_request, import_proof,
+};
+
+// Proving and signing metrics - in separate file
+pub mod metrics;
+
+#[cfg(feature = "prover")]
+pub use metrics::prove_transaction_with_metrics;
+pub use metrics::{
+    ProveMetrics, SignMetrics, sign_transparent_input_with_metrics,
+    sign_transparent_inputs_with_metrics,
+};
+
+// Reuse of Orchard proofs for unchanged bundles - in separate file
+pub mod proof_cache;
+
+pub use proof_cache::ProofCache;
+
+// Transparent input substitution that keeps the Orchard bundle - in separate file
+pub mod adjust;
+
+pub use adjust::adjust_transparent_side;
+
+// Intermediate ZIP 244 digests for cross-checking signers - in separate file
+pub mod zip244;
+
+pub use zip244::{
+    SighashDigests, TransactionDigests, compute_auth_digest, compute_transaction_digests,
+    dump_sighash_digests,
+};
+
 /// Gets the sighash for a transparent input (per ZIP 244).
 ///
 /// Use this to obtain the 32-byte hash that needs to be signed externally.
@@ -716,6 +1514,14 @@ pub fn prove_transaction_with_key(
 /// # Returns
 /// 32-byte sighash that should be signed with ECDSA using secp256k1
 pub fn get_sighash(pczt: &Pczt, input_index: usize) -> Result<[u8; 32], T2ZError> {
+    Ok(transparent_sighashes(pczt, &[input_index])?[0])
+}
+
+/// Sighashes of several transparent inputs, digesting the transaction once.
+pub(crate) fn transparent_sighashes(
+    pczt: &Pczt,
+    input_indices: &[usize],
+) -> Result<Vec<[u8; 32]>, T2ZError> {
     use zcash_primitives::transaction::{
         sighash::SignableInput, sighash_v5::v5_signature_hash, txid::TxIdDigester,
     };
@@ -729,45 +1535,49 @@ pub fn get_sighash(pczt: &Pczt, input_index: usize) -> Result<[u8; 32], T2ZError
     // Compute the TxId digests needed for sighash
     let txid_parts = tx_data.digest(TxIdDigester);
 
-    // Get the input data from the PCZT's transparent bundle
     let transparent_bundle = pczt.transparent();
-    let input = transparent_bundle
-        .inputs()
-        .get(input_index)
-        .ok_or_else(|| T2ZError::InvalidInput(format!("Invalid input index: {}", input_index)))?;
-
-    // For T2Z (P2PKH inputs), the builder always sets SIGHASH_ALL
-    // and there's no redeem_script, so script_code = script_pubkey
-    let sighash_type = SighashType::ALL;
+    input_indices
+        .iter()
+        .map(|&input_index| {
+            // Get the input data from the PCZT's transparent bundle
+            let input = transparent_bundle
+                .inputs()
+                .get(input_index)
+                .ok_or_else(|| {
+                    T2ZError::InvalidInput(format!("Invalid input index: {}", input_index))
+                })?;
 
-    // Get script_pubkey from the input (has public getter)
-    let script_pubkey_bytes = input.script_pubkey();
+            // For T2Z (P2PKH inputs), the builder always sets SIGHASH_ALL
+            // and there's no redeem_script, so script_code = script_pubkey
+            let sighash_type = SighashType::ALL;
 
-    // For P2PKH, script_code = script_pubkey (no redeem_script)
-    // Create Script by wrapping the bytes in script::Code
-    let script =
-        zcash_transparent::address::Script(zcash_script::script::Code(script_pubkey_bytes.clone()));
+            // For P2PKH, script_code = script_pubkey (no redeem_script)
+            // Create Script by wrapping the bytes in script::Code
+            let script = zcash_transparent::address::Script(zcash_script::script::Code(
+                input.script_pubkey().clone(),
+            ));
 
-    // Get the value (has public getter) - it's a u64 in the serialized form
-    let value = zcash_protocol::value::Zatoshis::from_u64(*input.value())
-        .map_err(|_| T2ZError::InvalidInput("Invalid input value".to_string()))?;
+            // Get the value (has public getter) - it's a u64 in the serialized form
+            let value = checked_zatoshis(*input.value(), "Input value")?;
 
-    // Build the SignableInput for transparent
-    let transparent_signable = TransparentSignableInput::from_parts(
-        sighash_type,
-        input_index,
-        &script, // script_code
-        &script, // script_pubkey (same for P2PKH)
-        value,
-    );
+            // Build the SignableInput for transparent
+            let transparent_signable = TransparentSignableInput::from_parts(
+                sighash_type,
+                input_index,
+                &script, // script_code
+                &script, // script_pubkey (same for P2PKH)
+                value,
+            );
 
-    // Wrap in the enum variant expected by v5_signature_hash
-    let signable_input = SignableInput::Transparent(transparent_signable);
+            // Wrap in the enum variant expected by v5_signature_hash
+            let signable_input = SignableInput::Transparent(transparent_signable);
 
-    // Compute the sighash
-    let sighash = v5_signature_hash(&tx_data, &signable_input, &txid_parts);
+            // Compute the sighash
+            let sighash = v5_signature_hash(&tx_data, &signable_input, &txid_parts);
 
-    Ok(sighash.as_ref().try_into().expect("sighash is 32 bytes"))
+            Ok(sighash.as_ref().try_into().expect("sighash is 32 bytes"))
+        })
+        .collect()
 }
 
 /// Appends a pre-computed ECDSA signature to a transparent input.
@@ -791,6 +1601,57 @@ pub fn append_signature(
     pubkey: &[u8; 33],
     signature: &[u8],
 ) -> Result<Pczt, T2ZError> {
+    verify_transparent_signature(&pczt, input_index, pubkey, signature)?;
+
+    // Use the Combiner to merge the signature into the PCZT
+    // We create a clone of the PCZT with the signature added via the Signer role
+    add_signature_via_signer(&pczt, input_index, pubkey, signature)
+}
+
+/// Signs every transparent input with an external signer (HSM, hardware
+/// wallet, remote service).
+///
+/// For each input `i`, calls `sign(i, sighash, pubkeys[i])` and appends the
+/// returned signature (DER with the sighash type byte appended) as
+/// `append_signature` would, verifying it first. Stops at the first error.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `pubkeys` - 33-byte compressed public key of each transparent input, in order
+/// * `sign` - Produces the signature for one input
+pub fn sign_transparent_inputs_with<F>(
+    mut pczt: Pczt,
+    pubkeys: &[[u8; 33]],
+    mut sign: F,
+) -> Result<Pczt, T2ZError>
+where
+    F: FnMut(usize, &[u8; 32], &[u8; 33]) -> Result<Vec<u8>, T2ZError>,
+{
+    let input_count = pczt.transparent().inputs().len();
+    if pubkeys.len() != input_count {
+        return Err(T2ZError::InvalidInput(format!(
+            "Expected {} public keys, one per transparent input (got {})",
+            input_count,
+            pubkeys.len()
+        )));
+    }
+
+    for (input_index, pubkey) in pubkeys.iter().enumerate() {
+        let sighash = get_sighash(&pczt, input_index)?;
+        let signature = sign(input_index, &sighash, pubkey)?;
+        append_signature_in_place(&mut pczt, input_index, pubkey, &signature)?;
+    }
+
+    Ok(pczt)
+}
+
+/// Checks an external signature against the sighash of a transparent input.
+pub(crate) fn verify_transparent_signature(
+    pczt: &Pczt,
+    input_index: usize,
+    pubkey: &[u8; 33],
+    signature: &[u8],
+) -> Result<(), T2ZError> {
     // Verify the pubkey is valid
     let pk = secp256k1::PublicKey::from_slice(pubkey)
         .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
@@ -806,23 +1667,19 @@ pub fn append_signature(
         .map_err(|e| T2ZError::InvalidInput(format!("Invalid DER signature: {}", e)))?;
 
     // Verify the signature against the sighash
-    let sighash = get_sighash(&pczt, input_index)?;
+    let sighash = get_sighash(pczt, input_index)?;
     let message = secp256k1::Message::from_digest(sighash);
     let secp = secp256k1::Secp256k1::verification_only();
     secp.verify_ecdsa(&message, &sig, &pk)
-        .map_err(|e| T2ZError::InvalidInput(format!("Signature verification failed: {}", e)))?;
-
-    // Use the Combiner to merge the signature into the PCZT
-    // We create a clone of the PCZT with the signature added via the Signer role
-    add_signature_via_signer(pczt, input_index, pubkey, signature)
+        .map_err(|e| T2ZError::InvalidInput(format!("Signature verification failed: {}", e)))
 }
 
 /// Internal helper to add a signature to the PCZT.
 ///
 /// Uses shadow structs to deserialize the PCZT, modify partial_signatures,
 /// and re-serialize.
-fn add_signature_via_signer(
-    pczt: Pczt,
+pub(crate) fn add_signature_via_signer(
+    pczt: &Pczt,
     input_index: usize,
     pubkey: &[u8; 33],
     signature: &[u8],
@@ -849,18 +1706,8 @@ fn modify_pczt_signature(
 ) -> Result<Vec<u8>, T2ZError> {
     use shadow::PcztShadow;
 
-    // PCZT format: 4 bytes magic + 4 bytes version + postcard data
-    if pczt_bytes.len() < 8 {
-        return Err(T2ZError::InvalidInput("PCZT too short".to_string()));
-    }
-
-    let magic = &pczt_bytes[..4];
-    let version = &pczt_bytes[4..8];
-    let data = &pczt_bytes[8..];
-
     // Deserialize the postcard data into our shadow struct
-    let mut pczt_shadow: PcztShadow = postcard::from_bytes(data)
-        .map_err(|e| T2ZError::InvalidInput(format!("Failed to deserialize PCZT: {:?}", e)))?;
+    let (header, mut pczt_shadow) = PcztShadow::decode(pczt_bytes)?;
 
     // Get the input and add the signature
     let input = pczt_shadow
@@ -871,22 +1718,30 @@ fn modify_pczt_signature(
 
     input.partial_signatures.insert(pubkey, signature);
 
-    // Re-serialize
-    let new_data = postcard::to_allocvec(&pczt_shadow)
-        .map_err(|e| T2ZError::InvalidInput(format!("Failed to serialize PCZT: {:?}", e)))?;
-
-    // Reconstruct the full PCZT bytes
-    let mut result = Vec::with_capacity(8 + new_data.len());
-    result.extend_from_slice(magic);
-    result.extend_from_slice(version);
-    result.extend_from_slice(&new_data);
+    // Re-serialize and reconstruct the full PCZT bytes
+    pczt_shadow.encode(&header)
+}
 
-    Ok(result)
+/// Applies an in-place edit to a PCZT through its shadow struct.
+///
+/// Used for fields the pczt crate doesn't expose setters for. The edited PCZT
+/// is re-parsed with the real parser, so layout mistakes surface as errors.
+pub(crate) fn update_pczt_shadow<F>(pczt: Pczt, f: F) -> Result<Pczt, T2ZError>
+where
+    F: FnOnce(&mut shadow::PcztShadow) -> Result<(), T2ZError>,
+{
+    let (header, mut pczt_shadow) = shadow::PcztShadow::from_pczt(&pczt)?;
+    f(&mut pczt_shadow)?;
+    pczt_shadow.into_pczt(&header)
 }
 
 // Shadow structs for PCZT round-tripping - in separate file
 pub(crate) mod shadow;
 
+// Side-channel hardened transparent signing - in separate file
+#[cfg(feature = "hardened-signing")]
+pub(crate) mod hardening;
+
 /// Signs a transparent input with the provided secp256k1 private key.
 ///
 /// This is a convenience function that combines `get_sighash` and `append_signature`.
@@ -907,12 +1762,159 @@ pub fn sign_transparent_input(
     let secret_key = secp256k1::SecretKey::from_slice(secret_key_bytes)
         .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
 
+    #[cfg(feature = "hardened-signing")]
+    {
+        let (pubkey, signature) = hardening::sign_transparent(&pczt, input_index, &secret_key)?;
+        add_signature_via_signer(&pczt, input_index, &pubkey, &signature)
+    }
+
+    #[cfg(not(feature = "hardened-signing"))]
+    {
+        let mut signer = Signer::new(pczt)?;
+        signer.sign_transparent(input_index, &secret_key)?;
+
+        Ok(signer.finish())
+    }
+}
+
+/// Data an external device needs to produce an Orchard spend authorization signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrchardSpendSigningData {
+    /// Shielded sighash (ZIP 244) that the RedPallas signature commits to
+    pub sighash: [u8; 32],
+    /// Spend authorization randomizer; the device signs with `ask + alpha`
+    pub alpha: [u8; 32],
+    /// Randomized verification key the signature must verify under
+    pub rk: [u8; 32],
+}
+
+/// Gets the shielded sighash shared by all Orchard spend authorization signatures.
+pub fn get_shielded_sighash(pczt: &Pczt) -> Result<[u8; 32], T2ZError> {
+    use zcash_primitives::transaction::{
+        sighash::SignableInput, sighash_v5::v5_signature_hash, txid::TxIdDigester,
+    };
+
+    let tx_data = pczt.clone().into_effects().ok_or_else(|| {
+        T2ZError::InvalidInput("Failed to convert PCZT to transaction data".to_string())
+    })?;
+    let txid_parts = tx_data.digest(TxIdDigester);
+
+    let sighash = v5_signature_hash(&tx_data, &SignableInput::Shielded, &txid_parts);
+
+    Ok(sighash.as_ref().try_into().expect("sighash is 32 bytes"))
+}
+
+/// Gets what an external signer needs to authorize an Orchard spend.
+///
+/// Use this for hardware wallets that hold the spend authorizing key:
+/// 1. Call `get_orchard_spend_signing_data` for the action
+/// 2. Produce a RedPallas SpendAuth signature over `sighash` with `ask + alpha`
+/// 3. Call `append_orchard_spend_auth_sig` with the result
+///
+/// # Arguments
+/// * `pczt` - The PCZT
+/// * `action_index` - Index of the Orchard action whose spend to authorize
+pub fn get_orchard_spend_signing_data(
+    pczt: &Pczt,
+    action_index: usize,
+) -> Result<OrchardSpendSigningData, T2ZError> {
+    let action = pczt.orchard().actions().get(action_index).ok_or_else(|| {
+        T2ZError::InvalidInput(format!("Invalid action index: {}", action_index))
+    })?;
+
+    let alpha = (*action.spend().alpha()).ok_or_else(|| {
+        T2ZError::InvalidInput(format!(
+            "Action {} has no spend randomizer (alpha)",
+            action_index
+        ))
+    })?;
+
+    Ok(OrchardSpendSigningData {
+        sighash: get_shielded_sighash(pczt)?,
+        alpha,
+        rk: *action.spend().rk(),
+    })
+}
+
+/// Appends a pre-computed RedPallas spend authorization signature to an Orchard action.
+///
+/// The signature is verified against the action's `rk` and the shielded
+/// sighash before it is added.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to update
+/// * `action_index` - Index of the Orchard action
+/// * `signature` - 64-byte RedPallas SpendAuth signature
+pub fn append_orchard_spend_auth_sig(
+    pczt: Pczt,
+    action_index: usize,
+    signature: &[u8; 64],
+) -> Result<Pczt, T2ZError> {
+    use orchard::primitives::redpallas::{Signature, SpendAuth, VerificationKey};
+
+    let signing_data = get_orchard_spend_signing_data(&pczt, action_index)?;
+
+    let rk = VerificationKey::<SpendAuth>::try_from(signing_data.rk)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid rk: {:?}", e)))?;
+    rk.verify(&signing_data.sighash, &Signature::<SpendAuth>::from(*signature))
+        .map_err(|e| T2ZError::InvalidInput(format!("Signature verification failed: {:?}", e)))?;
+
+    update_pczt_shadow(pczt, |pczt_shadow| {
+        pczt_shadow.orchard.actions[action_index].spend.spend_auth_sig = Some(*signature);
+        Ok(())
+    })
+}
+
+/// Signs an Orchard spend with the spend authorizing key derived from an Orchard spending key.
+///
+/// Only needed for PCZTs that spend real Orchard notes; the dummy spends in
+/// PCZTs built by `propose_transaction` are already signed by the IO Finalizer.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `action_index` - Index of the Orchard action to sign
+/// * `spending_key_bytes` - 32-byte Orchard spending key
+pub fn sign_orchard_spend(
+    pczt: Pczt,
+    action_index: usize,
+    spending_key_bytes: &[u8; 32],
+) -> Result<Pczt, T2ZError> {
+    let spending_key = Option::<orchard::keys::SpendingKey>::from(
+        orchard::keys::SpendingKey::from_bytes(*spending_key_bytes),
+    )
+    .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard spending key".to_string()))?;
+
+    sign_orchard_spend_with_ask(
+        pczt,
+        action_index,
+        &orchard::keys::SpendAuthorizingKey::from(&spending_key),
+    )
+}
+
+/// Signs an Orchard spend with a spend authorizing key.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `action_index` - Index of the Orchard action to sign
+/// * `ask` - The spend authorizing key for the note being spent
+pub fn sign_orchard_spend_with_ask(
+    pczt: Pczt,
+    action_index: usize,
+    ask: &orchard::keys::SpendAuthorizingKey,
+) -> Result<Pczt, T2ZError> {
     let mut signer = Signer::new(pczt)?;
-    signer.sign_transparent(input_index, &secret_key)?;
+    signer.sign_orchard(action_index, ask)?;
 
     Ok(signer.finish())
 }
 
+// In-place signing for PCZTs held behind shared handles - in separate file
+pub mod in_place;
+
+pub use in_place::{
+    append_signature_in_place, sign_orchard_spend_in_place, sign_transparent_input_in_place,
+};
+
 /// Verifies the PCZT matches the original transaction request before signing.
 ///
 /// This implements verification checks that should be performed before signing
@@ -1163,8 +2165,35 @@ pub fn combine(pczts: Vec<Pczt>) -> Result<Pczt, T2ZError> {
     Ok(Combiner::new(pczts).combine()?)
 }
 
+// Multi-party signing sessions built on the Combiner - in separate file
+pub mod coordination;
+
+pub use coordination::{Participant, Role, SignerContribution, SigningRequest, SigningSession};
+
+// Relay protocol for live multi-party signing - in separate file
+pub mod relay;
+
+pub use relay::{RelayClient, RelayMessage};
+
+// Signed, transport-agnostic ceremony messages - in separate file
+pub mod ceremony;
+
+pub use ceremony::{CoordinationMessage, CoordinationPayload, SignedCoordinationMessage};
+
+// Signing packages for air-gapped transparent signers - in separate file
+pub mod signing_package;
+
+pub use signing_package::{
+    PackageSignature, SigningPackage, SigningPackageDerivation, SigningPackageInput,
+    SigningPackageResponse, export_signing_package, import_signatures,
+};
+
 /// Finalizes spends and extracts transaction bytes (Spend Finalizer + Transaction Extractor roles).
+///
+/// Unsigned inputs, a missing proof, and an unbalanced Orchard bundle are
+/// reported as specific errors (see `check_ready_to_extract`).
 pub fn finalize_and_extract(pczt: Pczt) -> Result<Vec<u8>, T2ZError> {
+    check_ready_to_extract(&pczt)?;
     let pczt = SpendFinalizer::new(pczt).finalize_spends()?;
     let extractor = TransactionExtractor::new(pczt);
     let transaction = extractor.extract()?;
@@ -1177,7 +2206,37 @@ pub fn finalize_and_extract(pczt: Pczt) -> Result<Vec<u8>, T2ZError> {
     Ok(tx_bytes)
 }
 
+// Specific errors for PCZTs that are not ready to extract - in separate file
+pub mod finalize;
+
+pub use finalize::check_ready_to_extract;
+
+// Checking every signature in a PCZT before extraction - in separate file
+pub mod signature_check;
+
+pub use signature_check::{
+    InvalidSignature, finalize_and_extract_verified, find_invalid_signatures,
+};
+
+// Custom scriptSig assembly for nonstandard transparent inputs - in separate file
+pub mod script_sig;
+
+pub use script_sig::{ScriptSigContext, ScriptSigFinalizer, finalize_and_extract_with, finalize_spends_with};
+
+// Per-signer signing progress for mixed-key transactions - in separate file
+pub mod status;
+
+pub use status::{InputScriptKind, InputSigningStatus, PcztStatus, pczt_status, signer_id};
+
+// Child transactions spending unconfirmed change - in separate file
+pub mod chaining;
+
+pub use chaining::{ParentTransaction, spend_unconfirmed_change, unconfirmed_change_inputs};
+
 /// Parses a PCZT from bytes.
+///
+/// Only the format version this crate writes is accepted; see
+/// `parse_pczt_versioned` for PCZTs from other implementations.
 pub fn parse_pczt(pczt_bytes: &[u8]) -> Result<Pczt, T2ZError> {
     Ok(Pczt::parse(pczt_bytes)?)
 }
@@ -1187,6 +2246,36 @@ pub fn serialize_pczt(pczt: &Pczt) -> Vec<u8> {
     pczt.serialize()
 }
 
+// Streaming serialization (io::Write / io::Read and hex adapters) - in separate file
+pub mod stream;
+
+pub use stream::{parse_pczt_from_reader, pczt_from_hex, pczt_to_hex, serialize_pczt_into};
+
+// PCZT format version negotiation - in separate file
+pub mod version;
+
+pub use version::{
+    SUPPORTED_PCZT_VERSIONS, VersionedPczt, negotiate_pczt_version, parse_pczt_versioned,
+    pczt_version, serialize_pczt_as,
+};
+
+// Runtime checks against librustzcash's PCZT handling - in separate file
+#[cfg(feature = "interop")]
+pub mod interop;
+
+#[cfg(feature = "interop")]
+pub use interop::{InteropIssue, InteropReport, check_interop};
+
+// Zatoshi amounts as JSON strings, for JavaScript and double-based parsers - in separate file
+pub mod amounts;
+
+pub use amounts::{amounts_as_strings, set_amounts_as_strings, with_amounts_as_strings};
+
+// Zatoshi <-> ZEC conversion with locale separators, for display - in separate file
+pub mod units;
+
+pub use units::{AmountFormat, format_zec, parse_zec};
+
 // ============================================================================
 // PCZT Inspection
 // ============================================================================
@@ -1199,6 +2288,7 @@ pub struct PcztTransparentInput {
     /// Previous output index
     pub prevout_index: u32,
     /// Value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
     pub value: u64,
     /// Script pubkey (hex)
     pub script_pubkey: String,
@@ -1212,6 +2302,7 @@ pub struct PcztTransparentInput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PcztTransparentOutput {
     /// Value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
     pub value: u64,
     /// Script pubkey (hex)
     pub script_pubkey: String,
@@ -1223,16 +2314,48 @@ pub struct PcztTransparentOutput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PcztOrchardOutput {
     /// Value in zatoshis (if known/not redacted)
+    #[serde(default, with = "crate::amounts::option_amount")]
     pub value: Option<u64>,
     /// Recipient address bytes (hex, if not redacted)
     pub recipient: Option<String>,
+    /// `recipient` as a unified address for the PCZT's network, to compare
+    /// against an invoice (if not redacted)
+    #[serde(default)]
+    pub recipient_address: Option<String>,
     /// User-provided address string (if set by Updater)
     pub user_address: Option<String>,
+    /// The output as recovered with the sender's OVK (`inspect_pczt_with_ovk`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decrypted: Option<DecryptedOrchardOutput>,
 }
 
+/// An Orchard output's note plaintext, recovered from its out-ciphertext
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptedOrchardOutput {
+    /// Value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub value: u64,
+    /// Recipient address bytes (hex)
+    pub recipient: String,
+    /// Memo bytes (hex) without trailing zero padding; None for the empty memo
+    pub memo: Option<String>,
+    /// Whether `value` and `recipient` agree with the output's unencrypted
+    /// fields (where those are not redacted)
+    pub matches_pczt: bool,
+}
+
+/// Version of the `PcztInfo` JSON contract, bumped on any change other than
+/// adding optional fields
+pub const PCZT_INFO_SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) that `inspect_pczt_json` output conforms to
+pub const PCZT_INFO_JSON_SCHEMA: &str = include_str!("../schema/pczt-info.schema.json");
+
 /// Complete information about a PCZT's contents
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PcztInfo {
+    /// `PCZT_INFO_SCHEMA_VERSION` of the library that produced this
+    pub schema_version: u32,
     /// Expiry height
     pub expiry_height: u32,
     /// Transparent inputs
@@ -1242,12 +2365,16 @@ pub struct PcztInfo {
     /// Orchard outputs (from actions)
     pub orchard_outputs: Vec<PcztOrchardOutput>,
     /// Total input value (zatoshis)
+    #[serde(with = "crate::amounts::amount")]
     pub total_input: u64,
     /// Total transparent output value (zatoshis)
+    #[serde(with = "crate::amounts::amount")]
     pub total_transparent_output: u64,
     /// Total Orchard output value (zatoshis, only counted if value is known)
+    #[serde(with = "crate::amounts::amount")]
     pub total_orchard_output: u64,
     /// Implied fee (total_input - all outputs)
+    #[serde(with = "crate::amounts::amount")]
     pub implied_fee: u64,
     /// Number of Orchard actions
     pub num_orchard_actions: usize,
@@ -1310,46 +2437,65 @@ pub fn inspect_pczt_bytes(pczt_bytes: &[u8]) -> Result<PcztInfo, T2ZError> {
         })
         .collect();
     
+    // Recipients are shown as addresses for the network the PCZT is for
+    let network = keys::network_for_coin_type(pczt.global.coin_type);
+
     // Extract Orchard outputs from actions
     let orchard_outputs: Vec<PcztOrchardOutput> = pczt.orchard.actions
         .iter()
         .map(|action| PcztOrchardOutput {
             value: action.output.value,
             recipient: action.output.recipient.map(hex::encode),
+            recipient_address: action
+                .output
+                .recipient
+                .and_then(|recipient| unified_orchard_address(&recipient, network?)),
             user_address: action.output.user_address.clone(),
+            decrypted: None,
         })
         .collect();
     
     // Calculate totals
-    let total_input: u64 = transparent_inputs.iter().map(|i| i.value).sum();
-    let total_transparent_output: u64 = transparent_outputs.iter().map(|o| o.value).sum();
-    let total_orchard_output: u64 = orchard_outputs
-        .iter()
-        .filter_map(|o| o.value)
-        .sum();
+    let total_input = checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+    let total_transparent_output = checked_sum(
+        transparent_outputs.iter().map(|o| o.value),
+        "Transparent output total",
+    )?;
+    let total_orchard_output = checked_sum(
+        orchard_outputs.iter().filter_map(|o| o.value),
+        "Orchard output total",
+    )?;
     
     // Fee = inputs - outputs (may include dummy 0-value Orchard outputs)
-    let total_output = total_transparent_output + total_orchard_output;
-    let implied_fee = total_input.saturating_sub(total_output);
+    let total_output = (total_transparent_output + total_orchard_output)
+        .ok_or_else(|| T2ZError::AmountOutOfRange("Output total exceeds 21M ZEC".to_string()))?;
+    let implied_fee = (total_input - total_output).unwrap_or(Zatoshis::ZERO);
     
     let all_inputs_signed = transparent_inputs.iter().all(|i| i.is_signed);
     let has_orchard_proofs = pczt.orchard.zkproof.is_some();
     
     Ok(PcztInfo {
+        schema_version: PCZT_INFO_SCHEMA_VERSION,
         expiry_height: pczt.global.expiry_height,
         transparent_inputs,
         transparent_outputs,
         orchard_outputs,
-        total_input,
-        total_transparent_output,
-        total_orchard_output,
-        implied_fee,
+        total_input: total_input.into_u64(),
+        total_transparent_output: total_transparent_output.into_u64(),
+        total_orchard_output: total_orchard_output.into_u64(),
+        implied_fee: implied_fee.into_u64(),
         num_orchard_actions: pczt.orchard.actions.len(),
         all_inputs_signed,
         has_orchard_proofs,
     })
 }
 
+/// Re-encodes a raw Orchard receiver as a unified address.
+fn unified_orchard_address(recipient: &[u8; 43], network: Network) -> Option<String> {
+    let address = Option::from(orchard::Address::from_raw_address_bytes(recipient))?;
+    encode_orchard_address(&address, network).ok()
+}
+
 /// Inspects a PCZT and returns structured information about its contents.
 /// Convenience wrapper that serializes the PCZT first.
 pub fn inspect_pczt(pczt: &Pczt) -> Result<PcztInfo, T2ZError> {
@@ -1357,6 +2503,207 @@ pub fn inspect_pczt(pczt: &Pczt) -> Result<PcztInfo, T2ZError> {
     inspect_pczt_bytes(&bytes)
 }
 
+/// Inspects a PCZT and returns `PcztInfo` as JSON conforming to
+/// `PCZT_INFO_JSON_SCHEMA`, for UIs and monitoring in other languages.
+pub fn inspect_pczt_json(pczt: &Pczt) -> Result<String, T2ZError> {
+    let info = inspect_pczt(pczt)?;
+    Ok(serde_json::to_string(&info).expect("PCZT info serializes to JSON"))
+}
+
+/// Inspects a PCZT like `inspect_pczt`, also decrypting each Orchard output
+/// with the sender's outgoing viewing key.
+///
+/// The recipient, value, and memo in `PcztOrchardOutput::decrypted` come
+/// from the ciphertext the recipient will receive, so a reviewer does not
+/// have to trust the PCZT's unencrypted fields. Outputs not encrypted to
+/// `ovk` (other senders' outputs, or any output built without an OVK, as
+/// `propose_transaction` does; see `TxBuilder::orchard_ovk`) are left
+/// undecrypted.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to inspect
+/// * `ovk` - Orchard outgoing viewing key (32 bytes), e.g. from
+///   `OrchardAccount::outgoing_viewing_key`
+pub fn inspect_pczt_with_ovk(pczt: &Pczt, ovk: &[u8; 32]) -> Result<PcztInfo, T2ZError> {
+    let mut info = inspect_pczt(pczt)?;
+    let bundle = pczt
+        .orchard()
+        .clone()
+        .into_parsed()
+        .map_err(|e| T2ZError::Builder(format!("Failed to parse Orchard bundle: {:?}", e)))?;
+    let ovk = orchard::keys::OutgoingViewingKey::from(*ovk);
+
+    for (output, action) in info.orchard_outputs.iter_mut().zip(bundle.actions()) {
+        output.decrypted = recover_orchard_output(action, &ovk, output);
+    }
+
+    Ok(info)
+}
+
+/// Decrypts an action's output with an OVK, as the sender's wallet would,
+/// and compares it with `unencrypted`, what the PCZT says the output is.
+fn recover_orchard_output(
+    action: &orchard::pczt::Action,
+    ovk: &orchard::keys::OutgoingViewingKey,
+    unencrypted: &PcztOrchardOutput,
+) -> Option<DecryptedOrchardOutput> {
+    use orchard::note_encryption::OrchardDomain;
+
+    let action = encrypted_action(action);
+    let (note, recipient, memo) = zcash_note_encryption::try_output_recovery_with_ovk(
+        &OrchardDomain::for_action(&action),
+        ovk,
+        &action,
+        action.cv_net(),
+        &action.encrypted_note().out_ciphertext,
+    )?;
+
+    let memo = zcash_protocol::memo::MemoBytes::from_bytes(&memo).ok()?;
+    let memo = (memo != zcash_protocol::memo::MemoBytes::empty()).then(|| {
+        let bytes = memo.as_slice();
+        let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        hex::encode(&bytes[..len])
+    });
+
+    let value = note.value().inner();
+    let recipient = hex::encode(recipient.to_raw_address_bytes());
+    // Redacted fields cannot disagree
+    let value_matches = unencrypted.value.is_none_or(|v| v == value);
+    let recipient_matches = !matches!(&unencrypted.recipient, Some(r) if *r != recipient);
+
+    Some(DecryptedOrchardOutput {
+        value,
+        recipient,
+        memo,
+        matches_pczt: value_matches && recipient_matches,
+    })
+}
+
+/// The parts of a PCZT action that note decryption needs, as an `orchard::Action`.
+pub(crate) fn encrypted_action(action: &orchard::pczt::Action) -> orchard::Action<()> {
+    orchard::Action::from_parts(
+        *action.spend().nullifier(),
+        action.spend().rk().clone(),
+        *action.output().cmx(),
+        action.output().encrypted_note().clone(),
+        action.cv_net().clone(),
+        (),
+    )
+}
+
+// Human-readable PCZT summaries - in separate file
+pub mod format;
+
+pub use format::{Verbosity, format_pczt, format_pczt_with_request};
+
+// Audit records for signing events - in separate file
+pub mod audit;
+
+pub use audit::{AuditPayment, AuditRecord, export_audit_record};
+
+// Payment disclosures for Orchard outputs - in separate file
+pub mod disclosure;
+
+pub use disclosure::{PaymentDisclosure, create_payment_disclosure};
+
+// Viewing-key detection of own outputs - in separate file
+pub mod ownership;
+
+pub use ownership::{OutputOwnership, OutputPool, Ownership, identify_own_outputs};
+
+// Key-agreement proofs of Orchard address ownership - in separate file
+pub mod address_proof;
+
+pub use address_proof::{
+    AddressOwnershipChallenge, create_address_ownership_challenge, prove_address_ownership,
+    verify_address_ownership,
+};
+
+// ZIP 317 fee arithmetic and per-payment fee attribution - in separate file
+pub mod fees;
+
+pub use fees::{
+    FeeAttribution, FeeAttributionMethod, FeeBreakdown, FeeStrategy, PaymentFeeShare, attribute_fee,
+    fee_breakdown,
+};
+
+// Orchard account key management - in separate file
+pub mod keys;
+
+pub use keys::{
+    OrchardAccount, TransparentKeypair, derive_transparent_keypair, encode_orchard_address,
+    generate_transparent_keypair, secret_key_from_wif, secret_key_to_wif,
+};
+
+// Signed messages for transparent addresses (zcashd signmessage) - in separate file
+pub mod message;
+
+pub use message::{sign_message, verify_message};
+
+// BIP 39 mnemonic generation, validation, and seed derivation - in separate file
+pub mod mnemonic;
+
+pub use mnemonic::{MnemonicLanguage, generate_mnemonic, mnemonic_to_seed, validate_mnemonic};
+
+// Minimal account state (UTXO tracking + coin selection) - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod wallet_lite;
+
+#[cfg(feature = "wallet_lite")]
+pub use wallet_lite::{Account, AccountBalance, Outpoint, TrackedUtxo, UtxoStatus};
+
+// Orchard notes created by the wallet's own proposals - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod notes;
+
+#[cfg(feature = "wallet_lite")]
+pub use notes::{MAX_REORG_DEPTH, NoteStatus, NoteStore, TrackedNote};
+
+// Wallet state change notifications - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod events;
+
+#[cfg(feature = "wallet_lite")]
+pub use events::{WalletEvent, WalletListener};
+
+// Endpoint selection with health tracking and failover - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod endpoints;
+
+#[cfg(feature = "wallet_lite")]
+pub use endpoints::{EndpointHealth, EndpointPool, MAX_TIP_LAG, Socks5Proxy};
+
+// Outgoing transaction queue with retry and endpoint failover - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod broadcast;
+
+#[cfg(feature = "wallet_lite")]
+pub use broadcast::{
+    BroadcastAttempt, BroadcastQueue, BroadcastStatus, QueuedTransaction, RetryPolicy,
+    SubmitResult,
+};
+
+// Batched, paginated, rate-limited UTXO lookup - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod utxo_fetch;
+
+#[cfg(feature = "wallet_lite")]
+pub use utxo_fetch::{
+    FetchedUtxo, UtxoFetch, UtxoFetchOptions, UtxoPageRequest, fetch_utxos_for_addresses,
+};
+
+// Key-value storage for wallet_lite state - in separate file
+#[cfg(feature = "wallet_lite")]
+pub mod storage;
+
+#[cfg(feature = "wallet_lite")]
+pub use storage::{
+    BatchOp, MemoryStorage, Storage, WriteBatch, delete_pczt, list_pczt_sessions, load_account,
+    load_broadcast_queue, load_pczt, save_account, save_broadcast_queue, save_pczt,
+};
+#[cfg(feature = "sqlite")]
+pub use storage::SqliteStorage;
+
 // ============================================================================
 // Serde support for byte arrays
 // ============================================================================
@@ -0,0 +1,131 @@
+//! Signed messages for transparent addresses.
+//!
+//! The `signmessage`/`verifymessage` scheme of zcashd (and Bitcoin Core,
+//! with a different magic string): the message is hashed with double
+//! SHA-256 behind a `Zcash Signed Message:\n` prefix, and signed with a
+//! recoverable ECDSA signature. Verifiers recover the public key from the
+//! signature and compare its hash with the address, so no public key needs
+//! to be shared. Exchanges use it to check who controls the transparent
+//! address funds are shielded from.
+//!
+//! Signatures are base64 encoded, 65 bytes: a header byte (27 + recovery
+//! id, + 4 for a compressed public key) followed by `r` and `s`.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use zcash_address::{ConversionError, TryFromAddress, ZcashAddress};
+use zcash_protocol::consensus::NetworkType;
+
+use crate::T2ZError;
+use crate::status::pubkey_hash;
+
+/// Prefix of every signed message, as in zcashd
+const MESSAGE_MAGIC: &[u8] = b"Zcash Signed Message:\n";
+
+/// Header byte of a signature by a compressed public key, less the recovery id
+const COMPRESSED_HEADER: u8 = 27 + 4;
+
+/// Signs a message with a transparent secret key.
+///
+/// The signature verifies against the P2PKH address of the key's
+/// compressed public key, on either network.
+///
+/// # Returns
+/// The signature, base64 encoded
+pub fn sign_message(secret_key: &[u8; 32], message: &[u8]) -> Result<String, T2ZError> {
+    let secret_key = SecretKey::from_slice(secret_key)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid secret key: {}", e)))?;
+
+    let signature = Secp256k1::signing_only()
+        .sign_ecdsa_recoverable(&Message::from_digest(message_hash(message)), &secret_key);
+    let (recovery_id, compact) = signature.serialize_compact();
+
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(COMPRESSED_HEADER + recovery_id.to_i32() as u8);
+    bytes.extend_from_slice(&compact);
+    Ok(BASE64.encode(bytes))
+}
+
+/// Checks a signed message against a transparent P2PKH address.
+///
+/// Returns `Ok(false)` if the signature is well formed but was made by
+/// another key (or for another message), and an error if the address or
+/// signature cannot be parsed.
+pub fn verify_message(address: &str, message: &[u8], signature: &str) -> Result<bool, T2ZError> {
+    let address = ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+    let P2pkhHash(hash) = address
+        .convert::<P2pkhHash>()
+        .map_err(|e| T2ZError::InvalidAddress(format!("Not a P2PKH address: {:?}", e)))?;
+
+    let bytes = BASE64
+        .decode(signature)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid signature base64: {}", e)))?;
+    let [header, compact @ ..]: [u8; 65] = bytes
+        .try_into()
+        .map_err(|_| T2ZError::InvalidInput("Signature must be 65 bytes".to_string()))?;
+    if !(COMPRESSED_HEADER..COMPRESSED_HEADER + 4).contains(&header) {
+        return Err(T2ZError::InvalidInput(
+            "Only signatures by compressed public keys are supported".to_string(),
+        ));
+    }
+
+    let recovery_id = RecoveryId::from_i32((header - COMPRESSED_HEADER) as i32)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid recovery id: {}", e)))?;
+    let signature = RecoverableSignature::from_compact(&compact, recovery_id)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid signature: {}", e)))?;
+
+    // A signature recovers some key for any message; only the hash tells
+    let Ok(pubkey) = Secp256k1::verification_only()
+        .recover_ecdsa(&Message::from_digest(message_hash(message)), &signature)
+    else {
+        return Ok(false);
+    };
+    Ok(pubkey_hash(&pubkey) == hash)
+}
+
+/// Double SHA-256 of the message behind the magic prefix
+fn message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(MESSAGE_MAGIC.len() + message.len() + 10);
+    write_compact_size(&mut data, MESSAGE_MAGIC.len());
+    data.extend_from_slice(MESSAGE_MAGIC);
+    write_compact_size(&mut data, message.len());
+    data.extend_from_slice(message);
+
+    Sha256::digest(Sha256::digest(&data)).into()
+}
+
+fn write_compact_size(data: &mut Vec<u8>, len: usize) {
+    match len {
+        0..=0xfc => data.push(len as u8),
+        0xfd..=0xffff => {
+            data.push(0xfd);
+            data.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            data.push(0xfe);
+            data.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+        _ => {
+            data.push(0xff);
+            data.extend_from_slice(&(len as u64).to_le_bytes());
+        }
+    }
+}
+
+/// The public key hash of a P2PKH address, on any network
+struct P2pkhHash([u8; 20]);
+
+impl TryFromAddress for P2pkhHash {
+    type Error = String;
+
+    fn try_from_transparent_p2pkh(
+        _net: NetworkType,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(P2pkhHash(data))
+    }
+}
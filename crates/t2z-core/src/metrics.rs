@@ -0,0 +1,159 @@
+//! Timing and resource metrics for proving and signing.
+//!
+//! The `*_with_metrics` variants do the same work as the functions they
+//! wrap and also return what it cost, so integrators can record it and
+//! notice when an upgrade makes proving slower or hungrier.
+//!
+//! Wall times come from `std::time::Instant`, which does not exist on
+//! `wasm32-unknown-unknown`; there they are 0 and the WASM bindings time
+//! the call themselves.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pczt, T2ZError};
+
+/// What proving a PCZT cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProveMetrics {
+    /// Total wall time, including building the proving key
+    pub wall_time_ms: f64,
+    /// Wall time spent building the proving key (0 if it was cached)
+    pub proving_key_ms: f64,
+    /// Whether the proving key had to be built for this call
+    pub proving_key_built: bool,
+    /// Orchard actions in the proof
+    pub orchard_actions: usize,
+    /// Size of the Orchard proof (0 if none was needed)
+    pub proof_bytes: usize,
+    /// `estimate_pczt_proving_memory` before proving
+    pub estimated_memory_bytes: u64,
+    /// Peak memory of the whole process (Linux) or WASM instance, where
+    /// measurable. A high-water mark, so it only reflects this proof if
+    /// proving is the largest thing the process has done.
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// What signing a PCZT's transparent inputs cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignMetrics {
+    /// Total wall time, including any external signer's
+    pub wall_time_ms: f64,
+    /// Inputs signed by this call
+    pub inputs_signed: usize,
+    /// Transparent inputs in the PCZT
+    pub transparent_inputs: usize,
+}
+
+/// Proves a PCZT like `prove_transaction`, and reports what it cost.
+#[cfg(feature = "prover")]
+pub fn prove_transaction_with_metrics(pczt: Pczt) -> Result<(Pczt, ProveMetrics), T2ZError> {
+    let estimated_memory_bytes = crate::estimate_pczt_proving_memory(&pczt);
+    let orchard_actions = pczt.orchard().actions().len();
+    let start = Stopwatch::start();
+
+    let proving_key_built = !crate::is_proving_key_loaded();
+    let proving_key = crate::load_orchard_proving_key();
+    let proving_key_ms = start.elapsed_ms();
+
+    let proved = crate::prove_transaction_with_key(pczt, proving_key)?;
+    let wall_time_ms = start.elapsed_ms();
+
+    let proof_bytes = proved.orchard().zkproof().as_ref().map_or(0, Vec::len);
+    Ok((
+        proved,
+        ProveMetrics {
+            wall_time_ms,
+            proving_key_ms,
+            proving_key_built,
+            orchard_actions,
+            proof_bytes,
+            estimated_memory_bytes,
+            peak_memory_bytes: peak_memory_bytes(),
+        },
+    ))
+}
+
+/// Signs a transparent input like `sign_transparent_input`, and reports
+/// what it cost.
+pub fn sign_transparent_input_with_metrics(
+    pczt: Pczt,
+    input_index: usize,
+    secret_key_bytes: &[u8; 32],
+) -> Result<(Pczt, SignMetrics), T2ZError> {
+    let start = Stopwatch::start();
+    let signed = crate::sign_transparent_input(pczt, input_index, secret_key_bytes)?;
+
+    let metrics = SignMetrics {
+        wall_time_ms: start.elapsed_ms(),
+        inputs_signed: 1,
+        transparent_inputs: signed.transparent().inputs().len(),
+    };
+    Ok((signed, metrics))
+}
+
+/// Signs every transparent input with an external signer like
+/// `sign_transparent_inputs_with`, and reports what it cost.
+pub fn sign_transparent_inputs_with_metrics<F>(
+    pczt: Pczt,
+    pubkeys: &[[u8; 33]],
+    sign: F,
+) -> Result<(Pczt, SignMetrics), T2ZError>
+where
+    F: FnMut(usize, &[u8; 32], &[u8; 33]) -> Result<Vec<u8>, T2ZError>,
+{
+    let start = Stopwatch::start();
+    let signed = crate::sign_transparent_inputs_with(pczt, pubkeys, sign)?;
+
+    let transparent_inputs = signed.transparent().inputs().len();
+    let metrics = SignMetrics {
+        wall_time_ms: start.elapsed_ms(),
+        inputs_signed: transparent_inputs,
+        transparent_inputs,
+    };
+    Ok((signed, metrics))
+}
+
+/// Wall clock for metrics, a no-op where `Instant` is unavailable
+struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    fn start() -> Self {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed().as_secs_f64() * 1000.0;
+        #[cfg(target_arch = "wasm32")]
+        return 0.0;
+    }
+}
+
+/// Peak resident memory of the process (`VmHWM`), on Linux
+#[cfg(all(feature = "prover", target_os = "linux"))]
+fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Size of the WASM linear memory, which only ever grows
+#[cfg(all(feature = "prover", target_arch = "wasm32"))]
+fn peak_memory_bytes() -> Option<u64> {
+    Some(core::arch::wasm32::memory_size(0) as u64 * 65536)
+}
+
+#[cfg(all(
+    feature = "prover",
+    not(any(target_os = "linux", target_arch = "wasm32"))
+))]
+fn peak_memory_bytes() -> Option<u64> {
+    None
+}
@@ -0,0 +1,128 @@
+//! BIP 39 mnemonics.
+//!
+//! Generates and validates recovery phrases in any of the BIP 39 word lists
+//! and turns them into the 64-byte seed that `OrchardAccount::from_seed` and
+//! `derive_transparent_keypair` take.
+
+use bip0039::{Count, Language, Mnemonic};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::T2ZError;
+
+/// BIP 39 word list
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MnemonicLanguage {
+    #[default]
+    English,
+    ChineseSimplified,
+    ChineseTraditional,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Portuguese,
+    Spanish,
+}
+
+impl std::str::FromStr for MnemonicLanguage {
+    type Err = T2ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "english" => Ok(MnemonicLanguage::English),
+            "chinese_simplified" => Ok(MnemonicLanguage::ChineseSimplified),
+            "chinese_traditional" => Ok(MnemonicLanguage::ChineseTraditional),
+            "czech" => Ok(MnemonicLanguage::Czech),
+            "french" => Ok(MnemonicLanguage::French),
+            "italian" => Ok(MnemonicLanguage::Italian),
+            "japanese" => Ok(MnemonicLanguage::Japanese),
+            "korean" => Ok(MnemonicLanguage::Korean),
+            "portuguese" => Ok(MnemonicLanguage::Portuguese),
+            "spanish" => Ok(MnemonicLanguage::Spanish),
+            _ => Err(T2ZError::InvalidInput(format!(
+                "Unknown mnemonic language: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Calls `$f::<L>($args)` with the word list type for `$language`.
+macro_rules! with_language {
+    ($language:expr, $f:ident($($args:expr),*)) => {
+        match $language {
+            MnemonicLanguage::English => $f::<bip0039::English>($($args),*),
+            MnemonicLanguage::ChineseSimplified => $f::<bip0039::ChineseSimplified>($($args),*),
+            MnemonicLanguage::ChineseTraditional => $f::<bip0039::ChineseTraditional>($($args),*),
+            MnemonicLanguage::Czech => $f::<bip0039::Czech>($($args),*),
+            MnemonicLanguage::French => $f::<bip0039::French>($($args),*),
+            MnemonicLanguage::Italian => $f::<bip0039::Italian>($($args),*),
+            MnemonicLanguage::Japanese => $f::<bip0039::Japanese>($($args),*),
+            MnemonicLanguage::Korean => $f::<bip0039::Korean>($($args),*),
+            MnemonicLanguage::Portuguese => $f::<bip0039::Portuguese>($($args),*),
+            MnemonicLanguage::Spanish => $f::<bip0039::Spanish>($($args),*),
+        }
+    };
+}
+
+/// Generates a new random mnemonic phrase.
+///
+/// # Arguments
+/// * `word_count` - 12, 15, 18, 21, or 24
+/// * `language` - Word list to use
+pub fn generate_mnemonic(word_count: u32, language: MnemonicLanguage) -> Result<String, T2ZError> {
+    let count = match word_count {
+        12 => Count::Words12,
+        15 => Count::Words15,
+        18 => Count::Words18,
+        21 => Count::Words21,
+        24 => Count::Words24,
+        _ => {
+            return Err(T2ZError::InvalidInput(format!(
+                "Mnemonic must have 12, 15, 18, 21, or 24 words, not {}",
+                word_count
+            )));
+        }
+    };
+
+    fn generate<L: Language>(count: Count) -> String {
+        Mnemonic::<L>::generate(count).phrase().to_string()
+    }
+
+    Ok(with_language!(language, generate(count)))
+}
+
+/// Checks a phrase's words and checksum against a word list.
+pub fn validate_mnemonic(phrase: &str, language: MnemonicLanguage) -> Result<(), T2ZError> {
+    fn validate<L: Language>(phrase: &str) -> Result<(), T2ZError> {
+        Mnemonic::<L>::from_phrase(phrase)
+            .map(|_| ())
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid mnemonic: {}", e)))
+    }
+
+    with_language!(language, validate(phrase))
+}
+
+/// Derives the 64-byte BIP 39 seed from a phrase and optional passphrase.
+///
+/// The phrase is validated first, so a typo fails instead of silently
+/// producing a different wallet. The seed is zeroized when dropped.
+pub fn mnemonic_to_seed(
+    phrase: &str,
+    passphrase: &str,
+    language: MnemonicLanguage,
+) -> Result<Zeroizing<[u8; 64]>, T2ZError> {
+    fn to_seed<L: Language>(
+        phrase: &str,
+        passphrase: &str,
+    ) -> Result<Zeroizing<[u8; 64]>, T2ZError> {
+        let mnemonic = Mnemonic::<L>::from_phrase(phrase)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid mnemonic: {}", e)))?;
+        Ok(Zeroizing::new(mnemonic.to_seed(passphrase)))
+    }
+
+    with_language!(language, to_seed(phrase, passphrase))
+}
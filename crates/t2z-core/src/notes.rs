@@ -0,0 +1,545 @@
+//! Orchard notes created by this wallet's own proposals (feature `wallet_lite`).
+//!
+//! t2z does not scan the chain, but it does not need to in order to find its
+//! own change: a PCZT carries each Orchard output's recipient, value, and
+//! `rseed`, and the nullifier of the action's spend is the note's `rho`.
+//! `NoteStore` records the outputs that pay an address of the wallet's UFVK,
+//! with their nullifiers, and follows them through confirmation and spending
+//! the way `Account` follows UTXOs. A follow-up transaction can then spend
+//! that change without a rescan.
+//!
+//! Spending a note also needs its Merkle path to a recent anchor. Given the
+//! Orchard note commitment tree at some height (`set_chain_state`), the store
+//! keeps an incremental witness for every tracked note: callers feed it each
+//! later block's note commitments (`scan_block`, e.g. from lightwalletd's
+//! compact blocks), and it appends them to the tree and to every witness.
+//!
+//! Each scanned block's hash is checked against its parent's, so a chain
+//! reorganization surfaces as `T2ZError::ChainReorg` instead of silently
+//! corrupting the tree. The store keeps its state as of each of the last
+//! `MAX_REORG_DEPTH` blocks; `rewind` returns to the fork point and puts
+//! notes mined in the abandoned blocks back to pending.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use incrementalmerkletree::frontier::CommitmentTree;
+use incrementalmerkletree::witness::IncrementalWitness;
+use orchard::keys::FullViewingKey;
+use orchard::note::ExtractedNoteCommitment;
+use orchard::tree::MerkleHashOrchard;
+use serde::{Deserialize, Serialize};
+use zcash_address::unified::{Container, Encoding, Fvk, Ufvk};
+use zcash_primitives::merkle_tree::{
+    read_commitment_tree, read_incremental_witness, write_commitment_tree,
+    write_incremental_witness,
+};
+
+use crate::disclosure::orchard_note;
+use crate::events::Listeners;
+use crate::{Pczt, T2ZError, WalletEvent, WalletListener};
+
+/// Depth of the Orchard note commitment tree
+const TREE_DEPTH: u8 = orchard::NOTE_COMMITMENT_TREE_DEPTH as u8;
+
+/// Blocks `NoteStore::rewind` can undo (zcashd's maximum reorg depth)
+pub const MAX_REORG_DEPTH: usize = 100;
+
+type OrchardTree = CommitmentTree<MerkleHashOrchard, TREE_DEPTH>;
+type OrchardWitness = IncrementalWitness<MerkleHashOrchard, TREE_DEPTH>;
+
+/// Where a tracked note is in its lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteStatus {
+    /// Created by a broadcast transaction that has not been mined yet
+    Pending,
+    /// Mined at the given height
+    Confirmed { height: u32 },
+    /// Spent by the given transaction
+    Spent { txid: String },
+}
+
+/// An Orchard note paid to the wallet by one of its own transactions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedNote {
+    /// Transaction that created the note (hex, display order)
+    pub txid: String,
+    /// Index of the Orchard action containing the note
+    pub action_index: u32,
+    /// Raw Orchard recipient address (43 bytes)
+    pub recipient: Vec<u8>,
+    /// Note value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub value: u64,
+    /// Note `rho` (the nullifier of the action's spend)
+    pub rho: [u8; 32],
+    /// Note `rseed`
+    pub rseed: [u8; 32],
+    /// Extracted note commitment
+    pub cmx: [u8; 32],
+    /// Nullifier revealed when the note is spent
+    pub nullifier: [u8; 32],
+    /// Lifecycle status
+    pub status: NoteStatus,
+}
+
+impl TrackedNote {
+    /// The note itself, as an Orchard spend needs it.
+    pub fn to_orchard_note(&self) -> Result<orchard::Note, T2ZError> {
+        let recipient: [u8; 43] = self.recipient.as_slice().try_into().map_err(|_| {
+            T2ZError::InvalidInput("Note recipient must be 43 bytes".to_string())
+        })?;
+        orchard_note(&recipient, self.value, &self.rho, &self.rseed)
+    }
+}
+
+/// Orchard notes created by this wallet's proposals, keyed by nullifier
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "StoredNoteStore", into = "StoredNoteStore")]
+pub struct NoteStore {
+    notes: BTreeMap<[u8; 32], TrackedNote>,
+    chain: Option<ChainState>,
+    /// Earlier chain states, oldest first, for rewinding
+    checkpoints: VecDeque<ChainState>,
+    listeners: Listeners,
+}
+
+/// The Orchard note commitment tree as of a block, and witnesses (by
+/// nullifier) for the tracked notes mined up to it
+#[derive(Debug, Clone)]
+struct ChainState {
+    height: u32,
+    hash: [u8; 32],
+    tree: OrchardTree,
+    witnesses: BTreeMap<[u8; 32], OrchardWitness>,
+}
+
+impl NoteStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `listener` with every `NoteReceived`, `TxConfirmed`, and
+    /// `TxExpired` event from now on.
+    pub fn subscribe(&mut self, listener: impl WalletListener + 'static) {
+        self.listeners.subscribe(listener);
+    }
+
+    /// Records the Orchard outputs of `pczt` that pay an address of `ufvk`
+    /// (its change addresses, or its own external addresses) as pending
+    /// under `txid`, and returns them.
+    ///
+    /// Call this once the transaction has been broadcast, alongside
+    /// `Account::mark_broadcast`. The PCZT must still contain the outputs'
+    /// plaintext fields (i.e. must not have been redacted).
+    pub fn track_pczt(
+        &mut self,
+        pczt: &Pczt,
+        txid: &str,
+        ufvk: &str,
+    ) -> Result<Vec<TrackedNote>, T2ZError> {
+        let fvk = orchard_fvk(ufvk)?;
+
+        let mut tracked = Vec::new();
+        for (action_index, action) in pczt.orchard().actions().iter().enumerate() {
+            let output = action.output();
+            let (Some(recipient), Some(value), Some(rseed)) =
+                (*output.recipient(), *output.value(), *output.rseed())
+            else {
+                continue;
+            };
+            // Zero-value outputs are padding and never worth spending
+            if value == 0 {
+                continue;
+            }
+
+            let address: Option<orchard::Address> =
+                orchard::Address::from_raw_address_bytes(&recipient).into();
+            if address.and_then(|address| fvk.scope_for_address(&address)).is_none() {
+                continue;
+            }
+
+            let rho = *action.spend().nullifier();
+            let note = orchard_note(&recipient, value, &rho, &rseed)?;
+            let cmx = orchard::note::ExtractedNoteCommitment::from(note.commitment()).to_bytes();
+            if cmx != *output.cmx() {
+                return Err(T2ZError::InvalidInput(format!(
+                    "Action {} note fields do not match its commitment",
+                    action_index
+                )));
+            }
+
+            let note = TrackedNote {
+                txid: txid.to_string(),
+                action_index: action_index as u32,
+                recipient: recipient.to_vec(),
+                value,
+                rho,
+                rseed,
+                cmx,
+                nullifier: note.nullifier(&fvk).to_bytes(),
+                status: NoteStatus::Pending,
+            };
+            self.notes.insert(note.nullifier, note.clone());
+            tracked.push(note);
+        }
+        for note in &tracked {
+            self.listeners.emit(WalletEvent::NoteReceived { note: note.clone() });
+        }
+
+        Ok(tracked)
+    }
+
+    /// All tracked notes, in nullifier order.
+    pub fn notes(&self) -> impl Iterator<Item = &TrackedNote> {
+        self.notes.values()
+    }
+
+    /// Confirmed, unspent notes with at least `min_confirmations` at
+    /// `tip_height`, largest first.
+    pub fn spendable_notes(&self, tip_height: u32, min_confirmations: u32) -> Vec<&TrackedNote> {
+        let mut spendable: Vec<&TrackedNote> = self
+            .notes
+            .values()
+            .filter(|note| match note.status {
+                NoteStatus::Confirmed { height } => {
+                    tip_height.saturating_sub(height).saturating_add(1) >= min_confirmations
+                }
+                NoteStatus::Pending | NoteStatus::Spent { .. } => false,
+            })
+            .collect();
+        spendable.sort_by(|a, b| b.value.cmp(&a.value));
+        spendable
+    }
+
+    /// Marks the notes created by `txid` as mined at `height`.
+    pub fn mark_confirmed(&mut self, txid: &str, height: u32) {
+        let mut confirmed = false;
+        for note in self.notes.values_mut() {
+            if note.txid == txid && note.status == NoteStatus::Pending {
+                note.status = NoteStatus::Confirmed { height };
+                confirmed = true;
+            }
+        }
+        if confirmed {
+            self.listeners.emit(WalletEvent::TxConfirmed {
+                txid: txid.to_string(),
+                height: Some(height),
+            });
+        }
+    }
+
+    /// Drops the notes created by `txid` (rejected or expired).
+    pub fn mark_failed(&mut self, txid: &str) {
+        let before = self.notes.len();
+        self.notes
+            .retain(|_, note| !(note.txid == txid && note.status == NoteStatus::Pending));
+        if self.notes.len() < before {
+            self.listeners.emit(WalletEvent::TxExpired {
+                txid: txid.to_string(),
+            });
+        }
+    }
+
+    /// Marks the note with `nullifier` as spent by `txid`. Returns whether
+    /// the nullifier belonged to a tracked note.
+    pub fn mark_spent(&mut self, nullifier: &[u8; 32], txid: &str) -> bool {
+        match self.notes.get_mut(nullifier) {
+            Some(note) => {
+                note.status = NoteStatus::Spent {
+                    txid: txid.to_string(),
+                };
+                if let Some(chain) = &mut self.chain {
+                    chain.witnesses.remove(nullifier);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts following the chain after block `height` (with hash `hash`,
+    /// in internal byte order), from the Orchard note commitment tree as of
+    /// that block in zcashd's encoding (lightwalletd's `GetTreeState`
+    /// returns it hex-encoded as `orchardTree`).
+    ///
+    /// Replaces any earlier chain state, checkpoints, and witnesses, so start
+    /// from a block before the earliest note that has not been mined yet.
+    pub fn set_chain_state(
+        &mut self,
+        height: u32,
+        hash: [u8; 32],
+        orchard_tree: &[u8],
+    ) -> Result<(), T2ZError> {
+        let tree = read_commitment_tree(orchard_tree)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid Orchard tree: {}", e)))?;
+        self.chain = Some(ChainState {
+            height,
+            hash,
+            tree,
+            witnesses: BTreeMap::new(),
+        });
+        self.checkpoints.clear();
+        Ok(())
+    }
+
+    /// Height of the last block scanned (or of the initial tree state).
+    pub fn chain_height(&self) -> Option<u32> {
+        self.chain.as_ref().map(|chain| chain.height)
+    }
+
+    /// Appends the Orchard note commitments of block `height` (every
+    /// action's `cmx`, in block order) to the tree and every witness.
+    ///
+    /// Tracked notes found among them are marked confirmed at `height`,
+    /// start being witnessed, and are returned. Blocks must be scanned in
+    /// order, starting right after the chain state's height. Fails with
+    /// `T2ZError::ChainReorg` if `prev_hash` is not the hash of the last
+    /// block scanned; `rewind` past the fork and scan the new chain from
+    /// there.
+    pub fn scan_block(
+        &mut self,
+        height: u32,
+        hash: [u8; 32],
+        prev_hash: [u8; 32],
+        commitments: &[[u8; 32]],
+    ) -> Result<Vec<TrackedNote>, T2ZError> {
+        let chain = self.chain.as_mut().ok_or_else(|| {
+            T2ZError::InvalidInput("No chain state; call set_chain_state first".to_string())
+        })?;
+        if Some(height) != chain.height.checked_add(1) {
+            return Err(T2ZError::InvalidInput(format!(
+                "Expected block {}, got {}",
+                chain.height.saturating_add(1),
+                height
+            )));
+        }
+        if prev_hash != chain.hash {
+            return Err(T2ZError::ChainReorg { height });
+        }
+
+        let nodes = commitments
+            .iter()
+            .map(|cmx| {
+                Option::from(ExtractedNoteCommitment::from_bytes(cmx))
+                    .map(|cmx| MerkleHashOrchard::from_cmx(&cmx))
+                    .ok_or_else(|| T2ZError::InvalidInput("Invalid note commitment".to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Notes that may appear in this block: not spent, not yet witnessed
+        let awaited: BTreeMap<[u8; 32], [u8; 32]> = self
+            .notes
+            .values()
+            .filter(|note| !matches!(note.status, NoteStatus::Spent { .. }))
+            .filter(|note| !chain.witnesses.contains_key(&note.nullifier))
+            .map(|note| (note.cmx, note.nullifier))
+            .collect();
+
+        if self.checkpoints.len() == MAX_REORG_DEPTH {
+            self.checkpoints.pop_front();
+        }
+        self.checkpoints.push_back(chain.clone());
+
+        let full = || T2ZError::InvalidInput("Orchard note commitment tree is full".to_string());
+        let mut found = Vec::new();
+        for (cmx, node) in commitments.iter().zip(nodes) {
+            chain.tree.append(node).map_err(|_| full())?;
+            for witness in chain.witnesses.values_mut() {
+                witness.append(node).map_err(|_| full())?;
+            }
+
+            if let Some(note) = awaited.get(cmx).and_then(|nf| self.notes.get_mut(nf)) {
+                let witness = IncrementalWitness::from_tree(chain.tree.clone())
+                    .expect("tree is not empty after an append");
+                chain.witnesses.insert(note.nullifier, witness);
+                note.status = NoteStatus::Confirmed { height };
+                found.push(note.clone());
+            }
+        }
+        chain.height = height;
+        chain.hash = hash;
+
+        let mut txids: Vec<&str> = found.iter().map(|note| note.txid.as_str()).collect();
+        txids.dedup();
+        for txid in txids {
+            self.listeners.emit(WalletEvent::TxConfirmed {
+                txid: txid.to_string(),
+                height: Some(height),
+            });
+        }
+
+        Ok(found)
+    }
+
+    /// Returns to the state after block `height`, undoing the blocks scanned
+    /// since. Notes mined in those blocks go back to pending (their
+    /// transactions may be mined again on the new chain) and are returned.
+    ///
+    /// Fails if `height` is more than `MAX_REORG_DEPTH` blocks back or
+    /// before the chain state was set; call `set_chain_state` then.
+    pub fn rewind(&mut self, height: u32) -> Result<Vec<TrackedNote>, T2ZError> {
+        let current = self.chain_height().ok_or_else(|| {
+            T2ZError::InvalidInput("No chain state; call set_chain_state first".to_string())
+        })?;
+        if height > current {
+            return Err(T2ZError::InvalidInput(format!(
+                "Cannot rewind forward from {} to {}",
+                current, height
+            )));
+        }
+
+        if height < current {
+            let idx = self
+                .checkpoints
+                .iter()
+                .position(|checkpoint| checkpoint.height == height)
+                .ok_or_else(|| {
+                    T2ZError::InvalidInput(format!(
+                        "No checkpoint at height {}; set the chain state again",
+                        height
+                    ))
+                })?;
+            self.chain = self.checkpoints.drain(idx..).next();
+        }
+
+        let mut reverted = Vec::new();
+        for note in self.notes.values_mut() {
+            if matches!(note.status, NoteStatus::Confirmed { height: mined } if mined > height) {
+                note.status = NoteStatus::Pending;
+                reverted.push(note.clone());
+            }
+        }
+        Ok(reverted)
+    }
+
+    /// The current Orchard anchor (tree root) that witnessed notes can be
+    /// spent against.
+    pub fn anchor(&self) -> Option<[u8; 32]> {
+        self.chain.as_ref().map(|chain| chain.tree.root().to_bytes())
+    }
+
+    /// Merkle path from the note with `nullifier` to the current anchor, if
+    /// it is being witnessed.
+    pub fn merkle_path(&self, nullifier: &[u8; 32]) -> Option<orchard::tree::MerklePath> {
+        let witness = self.chain.as_ref()?.witnesses.get(nullifier)?;
+        witness.path().map(orchard::tree::MerklePath::from)
+    }
+
+    /// Serializes the store.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized store.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid note store: {:?}", e)))
+    }
+}
+
+/// `NoteStore` as serialized, with the tree and witnesses in zcashd's
+/// encoding
+#[derive(Serialize, Deserialize)]
+struct StoredNoteStore {
+    notes: BTreeMap<[u8; 32], TrackedNote>,
+    chain: Option<StoredChainState>,
+    checkpoints: Vec<StoredChainState>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredChainState {
+    height: u32,
+    hash: [u8; 32],
+    tree: Vec<u8>,
+    witnesses: BTreeMap<[u8; 32], Vec<u8>>,
+}
+
+impl From<NoteStore> for StoredNoteStore {
+    fn from(store: NoteStore) -> Self {
+        StoredNoteStore {
+            notes: store.notes,
+            chain: store.chain.as_ref().map(StoredChainState::from),
+            checkpoints: store.checkpoints.iter().map(StoredChainState::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<StoredNoteStore> for NoteStore {
+    type Error = T2ZError;
+
+    fn try_from(stored: StoredNoteStore) -> Result<Self, Self::Error> {
+        Ok(NoteStore {
+            notes: stored.notes,
+            chain: stored.chain.map(ChainState::try_from).transpose()?,
+            checkpoints: stored
+                .checkpoints
+                .into_iter()
+                .map(ChainState::try_from)
+                .collect::<Result<_, _>>()?,
+            listeners: Listeners::default(),
+        })
+    }
+}
+
+impl From<&ChainState> for StoredChainState {
+    fn from(chain: &ChainState) -> Self {
+        let mut tree = Vec::new();
+        write_commitment_tree(&chain.tree, &mut tree).expect("can serialize into memory");
+        let witnesses = chain
+            .witnesses
+            .iter()
+            .map(|(nullifier, witness)| {
+                let mut bytes = Vec::new();
+                write_incremental_witness(witness, &mut bytes).expect("can serialize into memory");
+                (*nullifier, bytes)
+            })
+            .collect();
+        StoredChainState {
+            height: chain.height,
+            hash: chain.hash,
+            tree,
+            witnesses,
+        }
+    }
+}
+
+impl TryFrom<StoredChainState> for ChainState {
+    type Error = T2ZError;
+
+    fn try_from(stored: StoredChainState) -> Result<Self, Self::Error> {
+        let tree = read_commitment_tree(stored.tree.as_slice())
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid Orchard tree: {}", e)))?;
+        let witnesses = stored
+            .witnesses
+            .into_iter()
+            .map(|(nullifier, bytes)| {
+                read_incremental_witness(bytes.as_slice())
+                    .map(|witness| (nullifier, witness))
+                    .map_err(|e| T2ZError::InvalidInput(format!("Invalid note witness: {}", e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ChainState {
+            height: stored.height,
+            hash: stored.hash,
+            tree,
+            witnesses,
+        })
+    }
+}
+
+/// The Orchard full viewing key of `ufvk`.
+fn orchard_fvk(ufvk: &str) -> Result<FullViewingKey, T2ZError> {
+    let (_, ufvk) = Ufvk::decode(ufvk)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid UFVK: {:?}", e)))?;
+    let fvk = ufvk
+        .items()
+        .into_iter()
+        .find_map(|item| match item {
+            Fvk::Orchard(bytes) => Some(bytes),
+            _ => None,
+        })
+        .ok_or_else(|| T2ZError::InvalidInput("UFVK has no Orchard key".to_string()))?;
+    FullViewingKey::from_bytes(&fvk)
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard FVK in UFVK".to_string()))
+}
@@ -0,0 +1,871 @@
+//! Options for `propose_transaction_with_options`.
+//!
+//! `ProposalOptions` gathers every knob of proposing beyond the inputs,
+//! request, change address, and network, so new options can be added here
+//! (and to the bindings' options objects) without changing any function
+//! signatures.
+
+use orchard::keys::{DiversifierIndex, FullViewingKey, Scope};
+use rand_core::RngCore;
+use zcash_address::unified::{Container, Encoding, Fvk, Ufvk};
+use zcash_primitives::transaction::fees::{FeeRule, zip317};
+use zcash_protocol::consensus::{BlockHeight, BranchId, MainNetwork, TestNetwork};
+
+use crate::{
+    ConfirmationPolicy, FeeStrategy, InputConfirmations, Network, OrchardAccount, Payment, Pczt,
+    ProposalPolicy, ProposalRng, T2ZError, TransactionRequest, TransparentInput, checked_sum,
+    encode_orchard_address, parse_orchard_receiver, preview_transaction, propose_with_fee_strategy,
+    target_height,
+};
+
+/// Default number of blocks before a transaction expires (as in zcashd)
+pub const DEFAULT_EXPIRY_DELTA: u32 = 40;
+
+/// Default cap on Orchard outputs per transaction. An action costs about
+/// 3 KB with its share of the proof, so this keeps a transaction well under
+/// zcashd's 100 KB standard transaction size.
+pub const DEFAULT_MAX_ORCHARD_OUTPUTS: usize = 32;
+
+/// Default cap on transparent outputs per transaction (34 bytes each)
+pub const DEFAULT_MAX_TRANSPARENT_OUTPUTS: usize = 1000;
+
+/// Version group ID of v5 transactions (ZIP 225)
+const V5_VERSION_GROUP_ID: u32 = 0x26A7_270A;
+
+/// Amounts that are multiples of this many zatoshis (0.00001 ZEC) count as
+/// round for `ChangePolicy::SplitNonRound`
+const ROUND_AMOUNT_UNIT: u64 = 1_000;
+
+/// How the expiry height is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryPolicy {
+    /// Expire at exactly this height (0 for no expiry)
+    AtHeight(u32),
+    /// Expire this many blocks after `current_height`
+    AfterBlocks(u32),
+    /// Never expire
+    Never,
+}
+
+impl Default for ExpiryPolicy {
+    fn default() -> Self {
+        ExpiryPolicy::AfterBlocks(DEFAULT_EXPIRY_DELTA)
+    }
+}
+
+impl ExpiryPolicy {
+    /// The expiry height to build with (0 for no expiry).
+    pub fn expiry_height(&self, current_height: Option<u32>) -> Result<u32, T2ZError> {
+        match *self {
+            ExpiryPolicy::AtHeight(height) => Ok(height),
+            ExpiryPolicy::AfterBlocks(blocks) => current_height
+                .ok_or_else(|| {
+                    T2ZError::InvalidInput("Relative expiry requires current_height".to_string())
+                })?
+                .checked_add(blocks)
+                .ok_or_else(|| T2ZError::InvalidInput("Expiry height overflows".to_string())),
+            ExpiryPolicy::Never => Ok(0),
+        }
+    }
+}
+
+/// What happens to value left over after the payments and fee
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ChangePolicy {
+    /// Send it to the change address
+    #[default]
+    Send,
+    /// Add leftovers below `threshold` zatoshis to the fee instead of
+    /// creating a change output (ZIP 317 and fixed fees only)
+    DustToFee { threshold: u64 },
+    /// Send exactly `transparent_amount` zatoshis of it to
+    /// `transparent_address` and the rest to the change address (typically
+    /// Orchard), e.g. to shield funds gradually. The fee covers both change
+    /// outputs, and there must be enough left over for `transparent_amount`.
+    SplitTransparent {
+        transparent_address: String,
+        transparent_amount: u64,
+    },
+    /// Send it to a fresh internal (change) Orchard address of this unified
+    /// full viewing key, so every spend shields what it does not pay out.
+    /// The address uses a random diversifier index, so nothing has to be
+    /// tracked between proposals; the change address argument must be `None`.
+    AutoShield { ufvk: String },
+    /// Split it into two Orchard notes to the (Orchard) change address, at
+    /// a random point between a third and two thirds, so that neither note
+    /// is a round amount. A change note that mirrors a round payment makes
+    /// it easy to tell which output is the payment. The second note may
+    /// cost an extra action's fee; see `non_round_split_fee`.
+    SplitNonRound,
+}
+
+/// Per-transaction output limits, counting any change output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLimits {
+    /// Most Orchard outputs in one transaction (before padding to two actions)
+    pub max_orchard_outputs: usize,
+    /// Most transparent outputs in one transaction
+    pub max_transparent_outputs: usize,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        OutputLimits {
+            max_orchard_outputs: DEFAULT_MAX_ORCHARD_OUTPUTS,
+            max_transparent_outputs: DEFAULT_MAX_TRANSPARENT_OUTPUTS,
+        }
+    }
+}
+
+impl OutputLimits {
+    /// No limits beyond what the Builder enforces
+    pub const UNLIMITED: OutputLimits = OutputLimits {
+        max_orchard_outputs: usize::MAX,
+        max_transparent_outputs: usize::MAX,
+    };
+
+    /// Fails with `T2ZError::TooManyOutputs` if `request` (plus a change
+    /// output to `change_address`) exceeds the limits.
+    ///
+    /// The error says how many transactions the payments would need if each
+    /// one carried its own change output.
+    pub fn check(
+        &self,
+        request: &TransactionRequest,
+        change_address: Option<&str>,
+    ) -> Result<(), T2ZError> {
+        let mut orchard_payments = 0;
+        let mut transparent_payments = 0;
+        for payment in &request.payments {
+            if pays_orchard(&payment.address)? {
+                orchard_payments += 1;
+            } else {
+                transparent_payments += 1;
+            }
+        }
+        let (orchard_change, transparent_change) = match change_address {
+            Some(change_address) if pays_orchard(change_address)? => (1, 0),
+            Some(_) => (0, 1),
+            None => (0, 0),
+        };
+
+        let orchard_outputs = orchard_payments + orchard_change;
+        let transparent_outputs = transparent_payments + transparent_change;
+        if orchard_outputs <= self.max_orchard_outputs
+            && transparent_outputs <= self.max_transparent_outputs
+        {
+            return Ok(());
+        }
+
+        // Every transaction in a split repeats the change output
+        let transactions = |payments: usize, limit: usize, change: usize| match payments {
+            0 => Ok(0),
+            _ if limit <= change => Err(T2ZError::InvalidInput(format!(
+                "Output limit of {} leaves no room for payments",
+                limit
+            ))),
+            _ => Ok(payments.div_ceil(limit - change)),
+        };
+        let orchard_transactions =
+            transactions(orchard_payments, self.max_orchard_outputs, orchard_change)?;
+        let transparent_transactions =
+            transactions(transparent_payments, self.max_transparent_outputs, transparent_change)?;
+
+        Err(T2ZError::TooManyOutputs {
+            orchard_outputs,
+            transparent_outputs,
+            transactions_needed: orchard_transactions.max(transparent_transactions),
+        })
+    }
+}
+
+/// What happens to several payments to the same Orchard receiver
+///
+/// Each payment otherwise gets its own action, and every action past the
+/// second adds 5000 zatoshis to the ZIP 317 fee.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateRecipients {
+    /// Pay each in its own output
+    #[default]
+    Keep,
+    /// Fail if any Orchard receiver is paid more than once
+    Reject,
+    /// Merge them into one output; fails if they carry different memos
+    Merge,
+    /// Merge them into one output, joining their memos with newlines; fails
+    /// if the joined memo exceeds 512 bytes
+    MergeConcatMemos,
+}
+
+/// Transaction version the proposal is built as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TxVersion {
+    /// v5 (ZIP 225), the only version PCZTs can hold so far
+    #[default]
+    V5,
+}
+
+impl TxVersion {
+    /// The `(version, version_group_id)` written into the PCZT
+    pub fn header(self) -> (u32, u32) {
+        match self {
+            TxVersion::V5 => (5, V5_VERSION_GROUP_ID),
+        }
+    }
+}
+
+/// Transaction fields the Builder otherwise fills with fixed defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxDefaults {
+    /// nSequence of inputs that don't set `TransparentInput::sequence`
+    /// (0xFFFFFFFF if `None`)
+    pub sequence: Option<u32>,
+    /// Transaction version and version group
+    pub version: TxVersion,
+    /// Lock time used when no input requires one (0 if `None`). Consensus
+    /// only enforces it if some input's sequence is below 0xFFFFFFFF.
+    pub fallback_lock_time: Option<u32>,
+}
+
+/// Everything about a proposal besides what is spent and paid
+///
+/// Build with struct update syntax from [`ProposalOptions::new`], which
+/// needs the chain tip to set the default expiry:
+///
+/// ```ignore
+/// let options = ProposalOptions {
+///     min_orchard_actions: 4,
+///     ..ProposalOptions::new(tip)
+/// };
+/// ```
+#[derive(Clone)]
+pub struct ProposalOptions<'a, FR = zip317::FeeRule> {
+    /// How the fee is set
+    pub fee_strategy: FeeStrategy<FR>,
+    /// How the expiry height is chosen
+    pub expiry: ExpiryPolicy,
+    /// Current chain tip height, if known
+    pub current_height: Option<u32>,
+    /// Fail unless the transaction is built for this consensus branch ID,
+    /// e.g. to avoid building across a network upgrade by accident
+    pub consensus_branch_id: Option<u32>,
+    /// Pad the Orchard bundle, if there is one, to at least this many actions
+    /// (counting an Orchard change output, if change is made) so the payment
+    /// count does not leak
+    pub min_orchard_actions: usize,
+    /// What happens to leftover value
+    pub change: ChangePolicy,
+    /// What happens to several payments to the same Orchard receiver
+    pub duplicate_recipients: DuplicateRecipients,
+    /// Per-transaction output limits
+    pub output_limits: OutputLimits,
+    /// Default nSequence, version, and fallback lock time
+    pub tx_defaults: TxDefaults,
+    /// Orchard anchor (note commitment tree root) to build the bundle
+    /// against, e.g. from the caller's own chain state, instead of the empty
+    /// tree's. Only bundles that spend real notes need a particular anchor.
+    pub orchard_anchor: Option<[u8; 32]>,
+    /// Address screening and spend limits
+    pub policy: ProposalPolicy<'a>,
+    /// Refuse inputs with too few confirmations; needs `input_confirmations`
+    pub confirmations: Option<ConfirmationPolicy>,
+    /// Confirmations of each input, in the order of `transparent_inputs`.
+    /// If any is a coinbase output, the transaction is fully shielded: no
+    /// transparent payments or change. To give change too small for an
+    /// Orchard output to the fee, use `ChangePolicy::DustToFee`.
+    pub input_confirmations: Vec<InputConfirmations>,
+    /// Seed for every random choice in the proposal (dummy notes, note and
+    /// value commitment randomness, action order, dummy spend signatures),
+    /// so the same inputs give a byte-identical PCZT. For golden-file tests
+    /// only: anyone who knows the seed can recompute the note secrets.
+    pub rng_seed: Option<[u8; 32]>,
+}
+
+impl ProposalOptions<'_> {
+    /// Default options at chain tip `current_height`: ZIP 317 fee, expiry
+    /// [`DEFAULT_EXPIRY_DELTA`] blocks later, no padding, and all leftover
+    /// value sent to change.
+    pub fn new(current_height: u32) -> Self {
+        Self::with_expiry_unchecked(ExpiryPolicy::default(), Some(current_height))
+    }
+
+    /// Default options with the given expiry. `current_height` may only be
+    /// `None` with a fixed, nonzero expiry height; anything else fails here
+    /// rather than when proposing.
+    pub fn with_expiry(
+        expiry: ExpiryPolicy,
+        current_height: Option<u32>,
+    ) -> Result<Self, T2ZError> {
+        target_height(expiry.expiry_height(current_height)?, current_height)?;
+        Ok(Self::with_expiry_unchecked(expiry, current_height))
+    }
+
+    fn with_expiry_unchecked(expiry: ExpiryPolicy, current_height: Option<u32>) -> Self {
+        ProposalOptions {
+            fee_strategy: FeeStrategy::default(),
+            expiry,
+            current_height,
+            consensus_branch_id: None,
+            min_orchard_actions: 0,
+            change: ChangePolicy::default(),
+            duplicate_recipients: DuplicateRecipients::default(),
+            output_limits: OutputLimits::default(),
+            tx_defaults: TxDefaults::default(),
+            orchard_anchor: None,
+            policy: ProposalPolicy::default(),
+            confirmations: None,
+            input_confirmations: Vec::new(),
+            rng_seed: None,
+        }
+    }
+}
+
+/// Proposes a transaction with every option in one place.
+///
+/// # Arguments
+/// * `transparent_inputs` - UTXOs to spend
+/// * `request` - ZIP 321 transaction request (payments only)
+/// * `change_address` - Optional address for change (transparent or Orchard)
+/// * `network` - Mainnet or Testnet
+/// * `options` - Fee, expiry, padding, change, and policy options
+pub fn propose_transaction_with_options<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    options: ProposalOptions<'_, FR>,
+) -> Result<Pczt, T2ZError>
+where
+    FR: FeeRule,
+    FR::Error: std::fmt::Debug,
+{
+    // One stream for every random choice, so a seeded proposal is
+    // reproducible end to end
+    let mut rng = ProposalRng::new(options.rng_seed);
+
+    let auto_shield_address = match &options.change {
+        ChangePolicy::AutoShield { ufvk } => {
+            if change_address.is_some() {
+                return Err(T2ZError::InvalidInput(
+                    "ChangePolicy::AutoShield derives the change address; pass None".to_string(),
+                ));
+            }
+            Some(auto_shield_address(ufvk, network, &mut rng)?)
+        }
+        _ => None,
+    };
+    let change_address = auto_shield_address.as_deref().or(change_address);
+
+    options.policy.check_request(&request, change_address)?;
+    if let Some(confirmations) = &options.confirmations {
+        confirmations.check(transparent_inputs.len(), &options.input_confirmations)?;
+    }
+    let spends_coinbase = options.input_confirmations.iter().any(|input| input.coinbase);
+    if spends_coinbase {
+        check_coinbase_spend(&request, &options.change)?;
+    }
+
+    // The transparent part of split change is built like a payment, so the
+    // fee accounts for it
+    let mut request = request;
+    if let ChangePolicy::SplitTransparent {
+        transparent_address,
+        transparent_amount,
+    } = &options.change
+    {
+        if change_address.is_none() {
+            return Err(T2ZError::InvalidInput(
+                "Split change requires a change address for the remainder".to_string(),
+            ));
+        }
+        if pays_orchard(transparent_address)? {
+            return Err(T2ZError::InvalidAddress(
+                "Split change address must be transparent".to_string(),
+            ));
+        }
+        options
+            .policy
+            .check_request(&TransactionRequest { payments: vec![] }, Some(transparent_address))?;
+        request.payments.push(Payment {
+            address: transparent_address.clone(),
+            amount: *transparent_amount,
+            memo: None,
+            label: None,
+        });
+    }
+
+    let expiry_height = options.expiry.expiry_height(options.current_height)?;
+    let orchard_anchor = options
+        .orchard_anchor
+        .map(|anchor| {
+            Option::<orchard::Anchor>::from(orchard::Anchor::from_bytes(anchor))
+                .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard anchor".to_string()))
+        })
+        .transpose()?;
+
+    if let Some(pinned) = options.consensus_branch_id {
+        let target_height = target_height(expiry_height, options.current_height)?;
+        check_branch_id(network, target_height, pinned)?;
+    }
+
+    let mut request = merge_duplicate_payments(request, network, options.duplicate_recipients)?;
+
+    // The first note of non-round split change is built like a payment to
+    // the change address; its value is set once the fee is known
+    let split_note = match options.change {
+        ChangePolicy::SplitNonRound => {
+            request.payments.push(split_note_payment(change_address)?);
+            Some(request.payments.len() - 1)
+        }
+        _ => None,
+    };
+
+    // An Orchard change output stands in for a padding output only if
+    // change will actually be made
+    let padding_change_address = match change_address {
+        Some(address)
+            if options.min_orchard_actions > 0
+                && pays_orchard(address)?
+                && makes_change(
+                    transparent_inputs,
+                    &request,
+                    address,
+                    network,
+                    &options.fee_strategy,
+                    &options.change,
+                    options.min_orchard_actions,
+                )? =>
+        {
+            Some(address)
+        }
+        _ => None,
+    };
+    let mut request = pad_orchard_outputs(
+        request,
+        padding_change_address,
+        network,
+        options.min_orchard_actions,
+        &mut rng,
+    )?;
+    options.output_limits.check(&request, change_address)?;
+
+    if let Some(idx) = split_note {
+        let change = split_change_total(
+            transparent_inputs,
+            &request,
+            change_address,
+            network,
+            &options.fee_strategy,
+        )?;
+        request.payments[idx].amount = non_round_split(change, &mut rng)?;
+    }
+
+    let (fee_strategy, change_address) = match options.change {
+        ChangePolicy::Send
+        | ChangePolicy::SplitTransparent { .. }
+        | ChangePolicy::AutoShield { .. }
+        | ChangePolicy::SplitNonRound => (options.fee_strategy, change_address),
+        ChangePolicy::DustToFee { threshold } => {
+            match dust_leftover(transparent_inputs, &request, network, &options.fee_strategy)? {
+                // Spend exactly: whatever is not paid out goes to the fee
+                Some(leftover) if leftover < threshold => {
+                    let total_input =
+                        checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+                    let total_payment =
+                        checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?;
+                    (
+                        FeeStrategy::Fixed(total_input.into_u64() - total_payment.into_u64()),
+                        None,
+                    )
+                }
+                _ => (options.fee_strategy, change_address),
+            }
+        }
+    };
+
+    let pczt = propose_with_fee_strategy(
+        transparent_inputs,
+        request,
+        change_address,
+        network,
+        expiry_height,
+        options.current_height,
+        fee_strategy,
+        options.tx_defaults,
+        orchard_anchor,
+        &mut rng,
+    )?;
+
+    if spends_coinbase && !pczt.transparent().outputs().is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "Coinbase outputs must be fully shielded; send change to an Orchard address, \
+             or use ChangePolicy::DustToFee to give small change to the fee"
+                .to_string(),
+        ));
+    }
+
+    if let Some(limits) = options.policy.spend_limits {
+        limits.check_fee(&pczt)?;
+    }
+
+    Ok(pczt)
+}
+
+/// Fails if `request` pays a transparent address, or `change` sends change
+/// to one, which a transaction spending coinbase outputs may not do.
+fn check_coinbase_spend(
+    request: &TransactionRequest,
+    change: &ChangePolicy,
+) -> Result<(), T2ZError> {
+    for payment in &request.payments {
+        if !pays_orchard(&payment.address)? {
+            return Err(T2ZError::InvalidInput(format!(
+                "Coinbase outputs must be fully shielded; cannot pay transparent address {}",
+                payment.address
+            )));
+        }
+    }
+    if matches!(change, ChangePolicy::SplitTransparent { .. }) {
+        return Err(T2ZError::InvalidInput(
+            "Coinbase outputs must be fully shielded; split change is transparent".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A fresh internal Orchard address of `ufvk`, at a diversifier index drawn
+/// from `rng`.
+fn auto_shield_address(
+    ufvk: &str,
+    network: Network,
+    rng: &mut ProposalRng,
+) -> Result<String, T2ZError> {
+    let (ufvk_network, ufvk) = Ufvk::decode(ufvk)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid UFVK: {:?}", e)))?;
+    if ufvk_network != network.to_network_type() {
+        return Err(T2ZError::InvalidInput("UFVK is for a different network".to_string()));
+    }
+
+    let fvk = ufvk
+        .items()
+        .into_iter()
+        .find_map(|item| match item {
+            Fvk::Orchard(bytes) => Some(bytes),
+            _ => None,
+        })
+        .ok_or_else(|| T2ZError::InvalidInput("UFVK has no Orchard key".to_string()))?;
+    let fvk = FullViewingKey::from_bytes(&fvk)
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard FVK in UFVK".to_string()))?;
+
+    let mut index = [0u8; 11];
+    rng.fill_bytes(&mut index);
+    encode_orchard_address(
+        &fvk.address_at(DiversifierIndex::from(index), Scope::Internal),
+        network,
+    )
+}
+
+/// The extra ZIP 317 fee `ChangePolicy::SplitNonRound` adds to a request:
+/// 5000 zatoshis when the second change note needs an action of its own,
+/// nothing when it takes the place of a padding action.
+///
+/// # Arguments
+/// * `transparent_inputs` - UTXOs to spend
+/// * `request` - ZIP 321 transaction request (payments only)
+/// * `change_address` - Orchard change address
+/// * `network` - Mainnet or Testnet
+pub fn non_round_split_fee(
+    transparent_inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    change_address: &str,
+    network: Network,
+) -> Result<u64, T2ZError> {
+    let single = preview_transaction(transparent_inputs, request, Some(change_address), network)?;
+
+    let mut split = request.clone();
+    split.payments.push(split_note_payment(Some(change_address))?);
+    let split = preview_transaction(transparent_inputs, &split, Some(change_address), network)?;
+
+    Ok(split.fee - single.fee)
+}
+
+/// The zero-value placeholder for the first note of non-round split change.
+fn split_note_payment(change_address: Option<&str>) -> Result<Payment, T2ZError> {
+    match change_address {
+        Some(change_address) if pays_orchard(change_address)? => Ok(Payment {
+            address: change_address.to_string(),
+            amount: 0,
+            memo: None,
+            label: None,
+        }),
+        _ => Err(T2ZError::InvalidInput(
+            "ChangePolicy::SplitNonRound requires an Orchard change address".to_string(),
+        )),
+    }
+}
+
+/// Change left for both notes of a non-round split, with the first note
+/// already in `request` at zero value.
+fn split_change_total<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    fee_strategy: &FeeStrategy<FR>,
+) -> Result<u64, T2ZError> {
+    match fee_strategy {
+        FeeStrategy::Zip317 => {
+            Ok(preview_transaction(transparent_inputs, request, change_address, network)?.change)
+        }
+        FeeStrategy::Fixed(fee) => {
+            let total_input =
+                checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+            let total_payment =
+                checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?;
+            let required = total_payment.into_u64().saturating_add(*fee);
+            total_input.into_u64().checked_sub(required).ok_or(T2ZError::InsufficientFunds {
+                available: total_input.into_u64(),
+                required,
+                payment: total_payment.into_u64(),
+                fee: *fee,
+            })
+        }
+        FeeStrategy::Custom(_) => Err(T2ZError::InvalidInput(
+            "ChangePolicy::SplitNonRound requires a ZIP 317 or fixed fee".to_string(),
+        )),
+    }
+}
+
+/// A random amount between a third and two thirds of `change`, nudged so
+/// that neither it nor the rest of `change` is a round amount.
+fn non_round_split(change: u64, rng: &mut impl RngCore) -> Result<u64, T2ZError> {
+    if change < 3 {
+        return Err(T2ZError::InvalidInput(format!(
+            "Change of {} zatoshis is too small to split",
+            change
+        )));
+    }
+
+    let mut amount = change / 3 + rng.next_u64() % (change / 3);
+    // Each holds for at most one of any ROUND_AMOUNT_UNIT consecutive amounts
+    while amount % ROUND_AMOUNT_UNIT == 0 || (change - amount) % ROUND_AMOUNT_UNIT == 0 {
+        amount += 1;
+    }
+    Ok(amount)
+}
+
+/// Fails unless `target_height` is in the branch `pinned`.
+fn check_branch_id(network: Network, target_height: u32, pinned: u32) -> Result<(), T2ZError> {
+    let pinned = BranchId::try_from(pinned)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid branch ID: {}", e)))?;
+
+    let height = BlockHeight::from_u32(target_height);
+    let actual = match network {
+        Network::Mainnet => BranchId::for_height(&MainNetwork, height),
+        Network::Testnet => BranchId::for_height(&TestNetwork, height),
+    };
+
+    if actual != pinned {
+        return Err(T2ZError::InvalidInput(format!(
+            "Height {} uses branch {:?}, not the pinned {:?}",
+            target_height, actual, pinned
+        )));
+    }
+    Ok(())
+}
+
+/// Applies `policy` to payments that share an Orchard receiver.
+///
+/// Receivers are compared by their raw bytes, so two unified addresses with
+/// the same Orchard receiver count as one. A merged payment takes the place
+/// of the first payment to its receiver and keeps the first label.
+pub(crate) fn merge_duplicate_payments(
+    request: TransactionRequest,
+    network: Network,
+    policy: DuplicateRecipients,
+) -> Result<TransactionRequest, T2ZError> {
+    if policy == DuplicateRecipients::Keep {
+        return Ok(request);
+    }
+
+    // Index into `payments` of the payment each Orchard receiver merges into
+    let mut merged_into: Vec<([u8; 43], usize)> = Vec::new();
+    let mut payments: Vec<Payment> = Vec::with_capacity(request.payments.len());
+
+    for (idx, payment) in request.payments.into_iter().enumerate() {
+        let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
+            .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+        // Addresses with a transparent receiver are paid transparently
+        if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT)
+            || !addr.can_receive_as(zcash_protocol::PoolType::ORCHARD)
+        {
+            payments.push(payment);
+            continue;
+        }
+
+        let receiver =
+            parse_orchard_receiver(&addr, network.to_network_type())?.to_raw_address_bytes();
+        let Some(&(_, first)) = merged_into.iter().find(|(r, _)| *r == receiver) else {
+            merged_into.push((receiver, payments.len()));
+            payments.push(payment);
+            continue;
+        };
+
+        if policy == DuplicateRecipients::Reject {
+            return Err(T2ZError::InvalidInput(format!(
+                "Payment {} pays the same Orchard receiver as an earlier payment",
+                idx
+            )));
+        }
+
+        let target = &mut payments[first];
+
+        target.amount =
+            checked_sum([target.amount, payment.amount], "Merged payment amount")?.into_u64();
+        target.memo = match (target.memo.take(), payment.memo) {
+            (memo, None) | (None, memo) => memo,
+            (Some(a), Some(b)) if a == b => Some(a),
+            (Some(_), Some(_)) if policy == DuplicateRecipients::Merge => {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} has a different memo than an earlier payment to the same receiver",
+                    idx
+                )));
+            }
+            (Some(mut a), Some(b)) => {
+                a.push(b'\n');
+                a.extend_from_slice(&b);
+                Some(a)
+            }
+        };
+        if target.label.is_none() {
+            target.label = payment.label;
+        }
+    }
+
+    Ok(TransactionRequest { payments })
+}
+
+/// Adds zero-value Orchard payments to fresh addresses, with keys drawn from
+/// `rng`, until the bundle has `min_actions` outputs. Requests without
+/// Orchard outputs are left alone.
+fn pad_orchard_outputs(
+    mut request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    min_actions: usize,
+    rng: &mut ProposalRng,
+) -> Result<TransactionRequest, T2ZError> {
+    let mut orchard_outputs = orchard_payments(&request)?;
+    if let Some(change_address) = change_address
+        && pays_orchard(change_address)?
+    {
+        orchard_outputs += 1;
+    }
+
+    if orchard_outputs > 0 {
+        for _ in orchard_outputs..min_actions {
+            let dummy = OrchardAccount::generate_with(rng).orchard_address(0, Scope::External);
+            request.payments.push(Payment {
+                address: encode_orchard_address(&dummy, network)?,
+                amount: 0,
+                memo: None,
+                label: None,
+            });
+        }
+    }
+
+    Ok(request)
+}
+
+/// Whether a proposal padded to `min_actions` Orchard actions makes change
+/// to the Orchard `change_address`.
+///
+/// It doesn't if the inputs are spent exactly, or `DustToFee` gives the
+/// leftover to the fee. Zero-value payments to `change_address` stand in for
+/// the padding, which costs the same. Where the leftover can't be worked out
+/// up front (custom fee rules, too little to pay for the padding), change is
+/// assumed and building reports any error.
+fn makes_change<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    change_address: &str,
+    network: Network,
+    fee_strategy: &FeeStrategy<FR>,
+    change: &ChangePolicy,
+    min_actions: usize,
+) -> Result<bool, T2ZError> {
+    let mut padded = request.clone();
+    for _ in orchard_payments(request)?..min_actions {
+        padded.payments.push(Payment {
+            address: change_address.to_string(),
+            amount: 0,
+            memo: None,
+            label: None,
+        });
+    }
+
+    Ok(
+        match dust_leftover(transparent_inputs, &padded, network, fee_strategy) {
+            Ok(None) => false,
+            Ok(Some(leftover)) => match change {
+                ChangePolicy::DustToFee { threshold } => leftover >= *threshold,
+                _ => true,
+            },
+            Err(_) => true,
+        },
+    )
+}
+
+/// Number of payments in `request` made with an Orchard output
+fn orchard_payments(request: &TransactionRequest) -> Result<usize, T2ZError> {
+    let mut orchard_outputs = 0;
+    for payment in &request.payments {
+        if pays_orchard(&payment.address)? {
+            orchard_outputs += 1;
+        }
+    }
+    Ok(orchard_outputs)
+}
+
+/// Whether `address` is paid with an Orchard output (addresses with a
+/// transparent receiver are paid transparently)
+fn pays_orchard(address: &str) -> Result<bool, T2ZError> {
+    let addr = zcash_address::ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+    Ok(!addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT)
+        && addr.can_receive_as(zcash_protocol::PoolType::ORCHARD))
+}
+
+/// Value left over without a change output, if the fee can be computed
+/// without building (`None` if there is none).
+fn dust_leftover<FR>(
+    transparent_inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    network: Network,
+    fee_strategy: &FeeStrategy<FR>,
+) -> Result<Option<u64>, T2ZError> {
+    match fee_strategy {
+        FeeStrategy::Zip317 => {
+            match preview_transaction(transparent_inputs, request, None, network) {
+                Ok(_) => Ok(None),
+                Err(T2ZError::ChangeRequired { change }) => Ok(Some(change)),
+                Err(e) => Err(e),
+            }
+        }
+        FeeStrategy::Fixed(fee) => {
+            let total_input =
+                checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+            let total_payment =
+                checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?;
+            Ok(total_input
+                .into_u64()
+                .checked_sub(total_payment.into_u64())
+                .and_then(|v| v.checked_sub(*fee))
+                .filter(|&leftover| leftover > 0))
+        }
+        FeeStrategy::Custom(_) => Err(T2ZError::InvalidInput(
+            "ChangePolicy::DustToFee requires a ZIP 317 or fixed fee".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,206 @@
+//! Detecting which outputs of a PCZT belong to a viewing key.
+//!
+//! Signers that receive PCZTs from a separate coordinator can use
+//! `identify_own_outputs` to tell change (sent back to the key's internal
+//! scope) apart from payments to other parties, without trusting any
+//! `user_address` annotations in the PCZT.
+
+use serde::{Deserialize, Serialize};
+use zcash_address::unified::{Container, Encoding, Fvk, Ufvk};
+
+use crate::disclosure::orchard_note;
+use crate::{Pczt, T2ZError};
+
+/// Number of transparent addresses per scope checked for a match
+pub const TRANSPARENT_GAP_LIMIT: u32 = 20;
+
+/// Pool an output belongs to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OutputPool {
+    Transparent,
+    Orchard,
+}
+
+/// Who an output pays
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Ownership {
+    /// An internal (change) address of the viewing key
+    Change,
+    /// An external address of the viewing key (a payment to self)
+    OwnExternal,
+    /// Not derivable from the viewing key (a payment to someone else, or an
+    /// Orchard dummy output)
+    Foreign,
+}
+
+/// Ownership of one output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutputOwnership {
+    /// Pool of the output
+    pub pool: OutputPool,
+    /// Transparent output index, or Orchard action index
+    pub index: usize,
+    /// Value in zatoshis, if known
+    #[serde(default, with = "crate::amounts::option_amount")]
+    pub value: Option<u64>,
+    /// Who the output pays
+    pub ownership: Ownership,
+}
+
+/// Classifies every transparent output and Orchard action output of `pczt`
+/// against a unified full viewing key.
+///
+/// Transparent outputs are matched against the first `TRANSPARENT_GAP_LIMIT`
+/// external and internal P2PKH addresses of the key's transparent account.
+///
+/// An Orchard output's recipient, value and `rseed` are plaintext fields any
+/// Constructor can write, so an output only counts as the key's if they
+/// reproduce the action's note commitment. If they don't, the PCZT has been
+/// tampered with (e.g. to pass a payment off as change) and an error is
+/// returned.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to inspect
+/// * `ufvk` - Encoded unified full viewing key (`uview1...` / `uviewtest1...`)
+pub fn identify_own_outputs(pczt: &Pczt, ufvk: &str) -> Result<Vec<OutputOwnership>, T2ZError> {
+    let (_, ufvk) = Ufvk::decode(ufvk)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid UFVK: {:?}", e)))?;
+
+    let mut orchard_fvk = None;
+    let mut transparent_scripts: Vec<(Vec<u8>, Ownership)> = Vec::new();
+
+    for item in ufvk.items() {
+        match item {
+            Fvk::Orchard(bytes) => {
+                orchard_fvk = Some(
+                    orchard::keys::FullViewingKey::from_bytes(&bytes).ok_or_else(|| {
+                        T2ZError::InvalidInput("Invalid Orchard FVK in UFVK".to_string())
+                    })?,
+                );
+            }
+            Fvk::P2pkh(bytes) => {
+                transparent_scripts = derive_transparent_scripts(&bytes)?;
+            }
+            _ => {}
+        }
+    }
+
+    let mut result = Vec::new();
+
+    for (index, output) in pczt.transparent().outputs().iter().enumerate() {
+        let script_pubkey = output.script_pubkey();
+        let ownership = transparent_scripts
+            .iter()
+            .find(|(script, _)| script == script_pubkey)
+            .map_or(Ownership::Foreign, |(_, ownership)| *ownership);
+
+        result.push(OutputOwnership {
+            pool: OutputPool::Transparent,
+            index,
+            value: Some(*output.value()),
+            ownership,
+        });
+    }
+
+    for (index, action) in pczt.orchard().actions().iter().enumerate() {
+        let output = action.output();
+        let recipient = (*output.recipient())
+            .and_then(|bytes| Option::from(orchard::Address::from_raw_address_bytes(&bytes)));
+
+        let ownership = match (&orchard_fvk, recipient) {
+            (Some(fvk), Some(address)) => match fvk.scope_for_address(&address) {
+                Some(scope) => {
+                    check_note_commitment(
+                        index,
+                        &address,
+                        *output.value(),
+                        action.spend().nullifier(),
+                        *output.rseed(),
+                        output.cmx(),
+                    )?;
+                    match scope {
+                        orchard::keys::Scope::Internal => Ownership::Change,
+                        orchard::keys::Scope::External => Ownership::OwnExternal,
+                    }
+                }
+                None => Ownership::Foreign,
+            },
+            _ => Ownership::Foreign,
+        };
+
+        result.push(OutputOwnership {
+            pool: OutputPool::Orchard,
+            index,
+            value: *output.value(),
+            ownership,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Checks that an Orchard output's plaintext fields describe the note its
+/// action commits to.
+fn check_note_commitment(
+    action_index: usize,
+    recipient: &orchard::Address,
+    value: Option<u64>,
+    rho: &[u8; 32],
+    rseed: Option<[u8; 32]>,
+    cmx: &[u8; 32],
+) -> Result<(), T2ZError> {
+    let (Some(value), Some(rseed)) = (value, rseed) else {
+        return Err(T2ZError::InvalidInput(format!(
+            "Action {} pays the viewing key but its note cannot be verified",
+            action_index
+        )));
+    };
+
+    let note = orchard_note(&recipient.to_raw_address_bytes(), value, rho, &rseed)?;
+    if orchard::note::ExtractedNoteCommitment::from(note.commitment()).to_bytes() != *cmx {
+        return Err(T2ZError::InvalidInput(format!(
+            "Action {} note fields do not match its commitment",
+            action_index
+        )));
+    }
+
+    Ok(())
+}
+
+/// P2PKH scripts for the first `TRANSPARENT_GAP_LIMIT` addresses of each scope.
+fn derive_transparent_scripts(
+    account_pubkey: &[u8; 65],
+) -> Result<Vec<(Vec<u8>, Ownership)>, T2ZError> {
+    use zcash_transparent::address::TransparentAddress;
+    use zcash_transparent::keys::{AccountPubKey, IncomingViewingKey, NonHardenedChildIndex};
+
+    let account = AccountPubKey::deserialize(account_pubkey).map_err(|e| {
+        T2ZError::InvalidInput(format!("Invalid transparent FVK in UFVK: {:?}", e))
+    })?;
+
+    let external = account
+        .derive_external_ivk()
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to derive external IVK: {:?}", e)))?;
+    let internal = account
+        .derive_internal_ivk()
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to derive internal IVK: {:?}", e)))?;
+
+    let p2pkh_script = |address: TransparentAddress| match address {
+        TransparentAddress::PublicKeyHash(hash) => Some(crate::keys::p2pkh_script(&hash)),
+        TransparentAddress::ScriptHash(_) => None,
+    };
+
+    let mut scripts = Vec::new();
+    for address_index in 0..TRANSPARENT_GAP_LIMIT {
+        let child = NonHardenedChildIndex::from_index(address_index).expect("below 2^31");
+
+        if let Some(script) = external.derive_address(child).ok().and_then(p2pkh_script) {
+            scripts.push((script, Ownership::OwnExternal));
+        }
+        if let Some(script) = internal.derive_address(child).ok().and_then(p2pkh_script) {
+            scripts.push((script, Ownership::Change));
+        }
+    }
+
+    Ok(scripts)
+}
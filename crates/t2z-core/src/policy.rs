@@ -0,0 +1,288 @@
+//! Proposal policies for regulated and automated deployments.
+//!
+//! An `AddressScreen` is consulted for every recipient and the change address
+//! before a transaction is built; if it rejects an address, proposing fails
+//! with `T2ZError::PolicyViolation`.
+//!
+//! `SpendLimits` caps what an automated signer will produce or sign: total
+//! payment amount, fee, and which recipients are acceptable. They are checked
+//! both when proposing and in `verify_before_signing_with_limits`, so a
+//! signer enforces them even for PCZTs proposed by an upstream service.
+//!
+//! `SigningRules` holds organization-specific checks (e.g. "every payment
+//! memo carries an invoice ID") that `verify_before_signing_with_rules` runs
+//! after the standard verification, so they are part of the same signing
+//! gate.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ExpectedTxOut, Network, Pczt, PcztInfo, T2ZError, TransactionRequest, TransparentInput,
+    inspect_pczt, verify_before_signing,
+};
+
+/// Why an address appears in a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressRole {
+    /// Receives a payment from the transaction request
+    Recipient,
+    /// Receives the transaction's change
+    Change,
+}
+
+/// Compliance hook that approves or rejects addresses before a transaction is built.
+pub trait AddressScreen {
+    /// Returns `Err(reason)` to reject `address`.
+    fn screen(&self, address: &str, role: AddressRole) -> Result<(), String>;
+}
+
+impl<F> AddressScreen for F
+where
+    F: Fn(&str, AddressRole) -> Result<(), String>,
+{
+    fn screen(&self, address: &str, role: AddressRole) -> Result<(), String> {
+        self(address, role)
+    }
+}
+
+/// Caps enforced on every proposed or signed transaction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpendLimits {
+    /// Maximum total of all payments (excluding change and fee), in zatoshis
+    #[serde(default, with = "crate::amounts::option_amount")]
+    pub max_per_tx: Option<u64>,
+    /// Maximum fee, in zatoshis
+    #[serde(default, with = "crate::amounts::option_amount")]
+    pub max_fee: Option<u64>,
+    /// If set, every recipient must be one of these addresses
+    pub allowlist: Option<Vec<String>>,
+    /// Recipients that are always rejected
+    pub denylist: Vec<String>,
+}
+
+impl SpendLimits {
+    /// Checks the payments of a request against the limits.
+    pub fn check_request(&self, request: &TransactionRequest) -> Result<(), T2ZError> {
+        for (idx, payment) in request.payments.iter().enumerate() {
+            if self.denylist.iter().any(|a| a == &payment.address) {
+                return Err(T2ZError::PolicyViolation(format!(
+                    "Payment {} recipient {} is denylisted",
+                    idx, payment.address
+                )));
+            }
+
+            if let Some(allowlist) = &self.allowlist
+                && !allowlist.iter().any(|a| a == &payment.address)
+            {
+                return Err(T2ZError::PolicyViolation(format!(
+                    "Payment {} recipient {} is not allowlisted",
+                    idx, payment.address
+                )));
+            }
+        }
+
+        if let Some(max_per_tx) = self.max_per_tx {
+            let total = crate::checked_sum(
+                request.payments.iter().map(|p| p.amount),
+                "Payment total",
+            )?
+            .into_u64();
+
+            if total > max_per_tx {
+                return Err(T2ZError::PolicyViolation(format!(
+                    "Payments total {} zatoshis, exceeding the per-transaction limit of {}",
+                    total, max_per_tx
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the fee of a PCZT against the limits.
+    pub fn check_fee(&self, pczt: &Pczt) -> Result<(), T2ZError> {
+        if let Some(max_fee) = self.max_fee {
+            let fee = inspect_pczt(pczt)?.implied_fee;
+            if fee > max_fee {
+                return Err(T2ZError::PolicyViolation(format!(
+                    "Fee of {} zatoshis exceeds the limit of {}",
+                    fee, max_fee
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Policies applied while proposing a transaction
+#[derive(Default, Clone, Copy)]
+pub struct ProposalPolicy<'a> {
+    /// Screening hook for recipient and change addresses
+    pub address_screen: Option<&'a dyn AddressScreen>,
+    /// Amount, fee, and recipient limits
+    pub spend_limits: Option<&'a SpendLimits>,
+}
+
+impl ProposalPolicy<'_> {
+    /// Runs every configured check that only needs the request.
+    pub fn check_request(
+        &self,
+        request: &TransactionRequest,
+        change_address: Option<&str>,
+    ) -> Result<(), T2ZError> {
+        if let Some(screen) = self.address_screen {
+            for (idx, payment) in request.payments.iter().enumerate() {
+                screen
+                    .screen(&payment.address, AddressRole::Recipient)
+                    .map_err(|reason| {
+                        T2ZError::PolicyViolation(format!(
+                            "Payment {} address {} rejected: {}",
+                            idx, payment.address, reason
+                        ))
+                    })?;
+            }
+
+            if let Some(change_address) = change_address {
+                screen
+                    .screen(change_address, AddressRole::Change)
+                    .map_err(|reason| {
+                        T2ZError::PolicyViolation(format!(
+                            "Change address {} rejected: {}",
+                            change_address, reason
+                        ))
+                    })?;
+            }
+        }
+
+        if let Some(limits) = self.spend_limits {
+            limits.check_request(request)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Proposes a transaction after checking it against `policy`.
+///
+/// Same as `propose_transaction_at_height`, but fails with
+/// `T2ZError::PolicyViolation` if any policy check rejects the proposal.
+pub fn propose_transaction_with_policy(
+    transparent_inputs: &[TransparentInput],
+    request: TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+    expiry_height: u32,
+    current_height: Option<u32>,
+    policy: &ProposalPolicy<'_>,
+) -> Result<Pczt, T2ZError> {
+    policy.check_request(&request, change_address)?;
+
+    let pczt = crate::propose_transaction_at_height(
+        transparent_inputs,
+        request,
+        change_address,
+        network,
+        expiry_height,
+        current_height,
+    )?;
+
+    if let Some(limits) = policy.spend_limits {
+        limits.check_fee(&pczt)?;
+    }
+
+    Ok(pczt)
+}
+
+/// `verify_before_signing`, then checks the request and fee against `limits`.
+///
+/// Since verification ties every output to a payment or expected change, the
+/// request's payment total is what the PCZT actually sends out.
+pub fn verify_before_signing_with_limits(
+    pczt: &Pczt,
+    transaction_request: &TransactionRequest,
+    expected_change: &[ExpectedTxOut],
+    limits: &SpendLimits,
+) -> Result<(), T2ZError> {
+    verify_before_signing(pczt, transaction_request, expected_change)?;
+    limits.check_request(transaction_request)?;
+    limits.check_fee(pczt)
+}
+
+/// Organization-specific check run before signing.
+///
+/// Rules see the PCZT as `inspect_pczt` describes it, and the request it was
+/// verified against: its payments' recipients and amounts match the PCZT's
+/// outputs. Memos are encrypted in the PCZT, so a rule on a payment's memo
+/// checks the request's memo only.
+pub trait SigningRule {
+    /// Returns `Err(reason)` to refuse signing.
+    fn check(&self, info: &PcztInfo, request: &TransactionRequest) -> Result<(), String>;
+}
+
+impl<F> SigningRule for F
+where
+    F: Fn(&PcztInfo, &TransactionRequest) -> Result<(), String>,
+{
+    fn check(&self, info: &PcztInfo, request: &TransactionRequest) -> Result<(), String> {
+        self(info, request)
+    }
+}
+
+/// Named `SigningRule`s, run in the order they were registered
+#[derive(Default)]
+pub struct SigningRules {
+    rules: Vec<(String, Box<dyn SigningRule + Send + Sync>)>,
+}
+
+impl SigningRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule; `name` identifies it in `PolicyViolation` errors.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        rule: impl SigningRule + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.rules.push((name.into(), Box::new(rule)));
+        self
+    }
+
+    /// Number of registered rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether no rules are registered
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs every rule, failing with the first rejection.
+    pub fn check(&self, pczt: &Pczt, request: &TransactionRequest) -> Result<(), T2ZError> {
+        if self.rules.is_empty() {
+            return Ok(());
+        }
+
+        let info = inspect_pczt(pczt)?;
+        for (name, rule) in &self.rules {
+            rule.check(&info, request).map_err(|reason| {
+                T2ZError::PolicyViolation(format!("Signing rule {} rejected: {}", name, reason))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// `verify_before_signing`, then the caller's own `rules`.
+pub fn verify_before_signing_with_rules(
+    pczt: &Pczt,
+    transaction_request: &TransactionRequest,
+    expected_change: &[ExpectedTxOut],
+    rules: &SigningRules,
+) -> Result<(), T2ZError> {
+    verify_before_signing(pczt, transaction_request, expected_change)?;
+    rules.check(pczt, transaction_request)
+}
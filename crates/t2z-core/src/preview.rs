@@ -0,0 +1,219 @@
+//! Instant fee and change previews.
+//!
+//! `preview_transaction` runs the same checks and ZIP 317 fee/change
+//! calculation as `propose_transaction`, but from output counts alone: it
+//! never invokes the `zcash_primitives` Builder or creates a PCZT, so it is
+//! cheap enough to call on every keystroke while the user edits amounts.
+
+use serde::{Deserialize, Serialize};
+use zcash_protocol::value::Zatoshis;
+
+use crate::fees::{
+    P2PKH_STANDARD_INPUT_SIZE, P2PKH_STANDARD_OUTPUT_SIZE, orchard_actions_for_outputs, zip317_fee,
+};
+use crate::{
+    Network, T2ZError, TransactionRequest, TransparentInput, checked_sum, memo_bytes,
+    parse_orchard_receiver, parse_transparent_address,
+};
+
+/// v5 header, version group, branch ID, lock time, and expiry height (bytes)
+const V5_HEADER_SIZE: usize = 20;
+
+/// Serialized Orchard action: cv, nullifier, rk, cmx, ephemeral key, and
+/// both ciphertexts (bytes)
+const ORCHARD_ACTION_SIZE: usize = 820;
+
+/// Spend authorization signature per Orchard action (bytes)
+const ORCHARD_SPEND_AUTH_SIG_SIZE: usize = 64;
+
+/// Orchard bundle fields outside the actions: flags, value balance, anchor,
+/// and binding signature (bytes)
+const ORCHARD_BUNDLE_FIXED_SIZE: usize = 1 + 8 + 32 + 64;
+
+/// Halo 2 proof size for `n` actions is `2720 + 2272 * n` bytes
+pub(crate) const ORCHARD_PROOF_BASE_SIZE: usize = 2720;
+pub(crate) const ORCHARD_PROOF_PER_ACTION_SIZE: usize = 2272;
+
+/// Fee and change a transaction request would result in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Preview {
+    /// ZIP 317 fee in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub fee: u64,
+    /// Change in zatoshis (0 if the inputs match exactly)
+    #[serde(with = "crate::amounts::amount")]
+    pub change: u64,
+    /// Estimated size of the signed and proven transaction in bytes
+    pub size: usize,
+    /// Orchard actions, after padding (drives proving time)
+    pub action_count: usize,
+}
+
+/// Previews the fee and change of a transaction without building it.
+///
+/// Fails in the same cases as `propose_transaction` (invalid addresses,
+/// memos on transparent recipients, insufficient funds, missing change
+/// address), and for the same inputs the fee and change match the PCZT it
+/// would create.
+///
+/// # Arguments
+/// * `transparent_inputs` - UTXOs to spend
+/// * `request` - ZIP 321 transaction request (payments only)
+/// * `change_address` - Optional address for change (transparent or Orchard)
+/// * `network` - Mainnet or Testnet
+pub fn preview_transaction(
+    transparent_inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    change_address: Option<&str>,
+    network: Network,
+) -> Result<Preview, T2ZError> {
+    if transparent_inputs.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "No transparent inputs provided".to_string(),
+        ));
+    }
+
+    if request.payments.is_empty() {
+        return Err(T2ZError::InvalidInput("No payments specified".to_string()));
+    }
+
+    let expected_network = network.to_network_type();
+
+    let mut transparent_outputs = 0usize;
+    let mut orchard_outputs = 0usize;
+    for (idx, payment) in request.payments.iter().enumerate() {
+        let addr = zcash_address::ZcashAddress::try_from_encoded(&payment.address)
+            .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?;
+
+        if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+            if payment.memo.is_some() {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} has a memo but pays a transparent receiver, which cannot carry one",
+                    idx
+                )));
+            }
+            parse_transparent_address(&addr, expected_network)?;
+            transparent_outputs += 1;
+        } else if addr.can_receive_as(zcash_protocol::PoolType::ORCHARD) {
+            parse_orchard_receiver(&addr, expected_network)?;
+            memo_bytes(payment.memo.as_deref())?;
+            orchard_outputs += 1;
+        } else {
+            return Err(T2ZError::InvalidAddress(format!(
+                "Address {} cannot receive transparent or Orchard funds",
+                payment.address
+            )));
+        }
+    }
+
+    // Pool of the change output, if change is needed: true for Orchard
+    let orchard_change = match change_address {
+        Some(change_address) => {
+            let addr = zcash_address::ZcashAddress::try_from_encoded(change_address).map_err(
+                |e| T2ZError::InvalidAddress(format!("Invalid change address: {:?}", e)),
+            )?;
+
+            if addr.can_receive_as(zcash_protocol::PoolType::TRANSPARENT) {
+                parse_transparent_address(&addr, expected_network)?;
+                Some(false)
+            } else if addr.can_receive_as(zcash_protocol::PoolType::ORCHARD) {
+                parse_orchard_receiver(&addr, expected_network)?;
+                Some(true)
+            } else {
+                return Err(T2ZError::InvalidAddress(
+                    "Change address must be transparent (P2PKH) or Orchard".to_string(),
+                ));
+            }
+        }
+        None => None,
+    };
+
+    let total_input = checked_sum(transparent_inputs.iter().map(|i| i.value), "Input total")?;
+    let total_payment = checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?;
+
+    let change_after = |fee: u64| {
+        (total_input - total_payment)
+            .and_then(|v| v - Zatoshis::from_u64(fee).ok()?)
+            .ok_or_else(|| T2ZError::InsufficientFunds {
+                available: total_input.into_u64(),
+                required: total_payment.into_u64().saturating_add(fee),
+                payment: total_payment.into_u64(),
+                fee,
+            })
+    };
+
+    let fee = zip317_fee(
+        transparent_inputs.len(),
+        transparent_outputs,
+        orchard_actions_for_outputs(orchard_outputs),
+    );
+    let change = change_after(fee)?;
+
+    let (fee, change) = if change == Zatoshis::ZERO {
+        (fee, change)
+    } else {
+        match orchard_change {
+            None => {
+                return Err(T2ZError::ChangeRequired {
+                    change: change.into_u64(),
+                });
+            }
+            Some(true) => orchard_outputs += 1,
+            Some(false) => transparent_outputs += 1,
+        }
+
+        // The change output itself may add a logical action
+        let fee = zip317_fee(
+            transparent_inputs.len(),
+            transparent_outputs,
+            orchard_actions_for_outputs(orchard_outputs),
+        );
+        (fee, change_after(fee)?)
+    };
+
+    let action_count = orchard_actions_for_outputs(orchard_outputs);
+
+    Ok(Preview {
+        fee,
+        change: change.into_u64(),
+        size: estimate_size(transparent_inputs.len(), transparent_outputs, action_count),
+        action_count,
+    })
+}
+
+/// Estimated size of a signed and proven v5 transaction with P2PKH inputs
+fn estimate_size(
+    transparent_inputs: usize,
+    transparent_outputs: usize,
+    orchard_actions: usize,
+) -> usize {
+    let orchard_size = if orchard_actions == 0 {
+        compact_size_len(0)
+    } else {
+        let proof_size = ORCHARD_PROOF_BASE_SIZE + ORCHARD_PROOF_PER_ACTION_SIZE * orchard_actions;
+        compact_size_len(orchard_actions)
+            + orchard_actions * (ORCHARD_ACTION_SIZE + ORCHARD_SPEND_AUTH_SIG_SIZE)
+            + ORCHARD_BUNDLE_FIXED_SIZE
+            + compact_size_len(proof_size)
+            + proof_size
+    };
+
+    V5_HEADER_SIZE
+        + compact_size_len(transparent_inputs)
+        + transparent_inputs * P2PKH_STANDARD_INPUT_SIZE
+        + compact_size_len(transparent_outputs)
+        + transparent_outputs * P2PKH_STANDARD_OUTPUT_SIZE
+        // No Sapling spends or outputs
+        + 2 * compact_size_len(0)
+        + orchard_size
+}
+
+/// Length of a Bitcoin-style CompactSize encoding of `n`
+fn compact_size_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
@@ -0,0 +1,139 @@
+//! Reusing Orchard proofs for unchanged bundles.
+//!
+//! The Orchard proof only commits to the bundle's public inputs: the anchor,
+//! the flags, and each action's value commitment, nullifier, randomized key,
+//! and note commitment. A PCZT whose Orchard bundle is untouched, e.g. one
+//! whose expiry was bumped after a failed broadcast, can reuse the proof it
+//! had before instead of spending seconds of CPU on a new one.
+//!
+//! `ProofCache` keeps recent proofs keyed by a digest of those inputs.
+//! Anything that changes an action (a new proposal, different outputs)
+//! changes the key, so a cached proof is only ever reused where it verifies.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::shadow::PcztShadow;
+use crate::{Pczt, T2ZError, import_proof};
+
+const PROOF_CACHE_KEY_PERSONALIZATION: &[u8; 16] = b"t2z-proof-cache_";
+
+/// Bounded cache of Orchard proofs, evicting the least recently used.
+///
+/// Shareable between threads, e.g. behind an `Arc` in a proving service.
+pub struct ProofCache {
+    capacity: usize,
+    /// (key, proof), most recently used last
+    entries: Mutex<VecDeque<([u8; 32], Vec<u8>)>>,
+}
+
+impl ProofCache {
+    /// Creates a cache holding up to `capacity` proofs.
+    pub fn new(capacity: usize) -> Self {
+        ProofCache {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// The cache key of a PCZT's Orchard bundle, or `None` if it has no
+    /// actions (and so needs no proof).
+    pub fn key(pczt: &Pczt) -> Result<Option<[u8; 32]>, T2ZError> {
+        let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+        let orchard = &pczt_shadow.orchard;
+        if orchard.actions.is_empty() {
+            return Ok(None);
+        }
+
+        let mut h = blake2b_simd::Params::new()
+            .hash_length(32)
+            .personal(PROOF_CACHE_KEY_PERSONALIZATION)
+            .to_state();
+        h.update(&orchard.anchor);
+        h.update(&[orchard.flags]);
+        for action in &orchard.actions {
+            h.update(&action.cv_net);
+            h.update(&action.spend.nullifier);
+            h.update(&action.spend.rk);
+            h.update(&action.output.cmx);
+        }
+
+        Ok(Some(h.finalize().as_bytes().try_into().expect("32 bytes")))
+    }
+
+    /// The cached proof for the PCZT's Orchard bundle, if any.
+    pub fn get(&self, pczt: &Pczt) -> Result<Option<Vec<u8>>, T2ZError> {
+        let Some(key) = Self::key(pczt)? else {
+            return Ok(None);
+        };
+
+        let mut entries = self.entries.lock().expect("proof cache poisoned");
+        let Some(position) = entries.iter().position(|(k, _)| *k == key) else {
+            return Ok(None);
+        };
+        let entry = entries.remove(position).expect("position is in range");
+        let proof = entry.1.clone();
+        entries.push_back(entry);
+        Ok(Some(proof))
+    }
+
+    /// Caches the Orchard proof of a proved PCZT. PCZTs without one are
+    /// ignored.
+    pub fn insert(&self, pczt: &Pczt) -> Result<(), T2ZError> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        let (Some(key), Some(proof)) = (Self::key(pczt)?, pczt.orchard().zkproof()) else {
+            return Ok(());
+        };
+
+        let mut entries = self.entries.lock().expect("proof cache poisoned");
+        entries.retain(|(k, _)| *k != key);
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((key, proof.clone()));
+        Ok(())
+    }
+
+    /// Adds the cached proof to the PCZT if there is one. Returns whether it
+    /// did; the PCZT is returned unchanged otherwise.
+    pub fn apply(&self, pczt: Pczt) -> Result<(Pczt, bool), T2ZError> {
+        if pczt.orchard().zkproof().is_some() {
+            return Ok((pczt, false));
+        }
+        match self.get(&pczt)? {
+            Some(proof) => Ok((import_proof(pczt, &proof)?, true)),
+            None => Ok((pczt, false)),
+        }
+    }
+
+    /// Proves the PCZT like `prove_transaction`, reusing a cached proof when
+    /// its Orchard bundle was proved before and caching new ones.
+    #[cfg(feature = "prover")]
+    pub fn prove(&self, pczt: Pczt) -> Result<Pczt, T2ZError> {
+        let (pczt, reused) = self.apply(pczt)?;
+        if reused {
+            return Ok(pczt);
+        }
+
+        let proved = crate::prove_transaction(pczt)?;
+        self.insert(&proved)?;
+        Ok(proved)
+    }
+
+    /// Number of cached proofs
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("proof cache poisoned").len()
+    }
+
+    /// Whether no proofs are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every cached proof.
+    pub fn clear(&self) {
+        self.entries.lock().expect("proof cache poisoned").clear();
+    }
+}
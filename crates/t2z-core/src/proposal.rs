@@ -0,0 +1,267 @@
+//! Serializable transaction proposals.
+//!
+//! A `Proposal` captures everything `propose_transaction` needs (inputs,
+//! request, change address, network, expiry) together with the fee it
+//! results in. A review service can approve the serialized proposal and a
+//! separate machine can materialize the PCZT from it later; materializing
+//! fails if the resulting transaction would pay a different fee than the one
+//! that was approved. The proposal also carries the seed for the
+//! transaction's randomness, so every materialization yields the same PCZT.
+//!
+//! `ProposalBuilder` is the fluent way to assemble the same arguments and
+//! propose a transaction, validating everything in `propose()`.
+
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ExpiryPolicy, FeeStrategy, Network, Payment, Pczt, ProposalOptions, ProposalRng, T2ZError,
+    TransactionRequest, TransparentInput, TxDefaults, inspect_pczt,
+    propose_transaction_with_options, propose_with_fee_strategy, redirect_transparent_memos,
+};
+
+/// A transaction proposal that can be saved, reviewed, and materialized later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    /// Transparent UTXOs to spend
+    pub transparent_inputs: Vec<TransparentInput>,
+    /// ZIP 321 transaction request (payments only)
+    pub request: TransactionRequest,
+    /// Address for change (transparent or Orchard)
+    pub change_address: Option<String>,
+    /// Mainnet or Testnet
+    pub network: Network,
+    /// Transaction expiry height (0 for no expiry)
+    pub expiry_height: u32,
+    /// Chain tip height the proposal was made at, if known
+    pub current_height: Option<u32>,
+    /// ZIP 317 fee the transaction pays, in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub fee: u64,
+    /// Seed for the PCZT's randomness (see `ProposalOptions::rng_seed`).
+    /// Anyone who knows it can recompute the note secrets, so keep the
+    /// proposal as private as the PCZT.
+    pub rng_seed: [u8; 32],
+}
+
+impl Proposal {
+    /// Creates a proposal, validating it and computing its fee.
+    ///
+    /// Runs the same checks as `propose_transaction_at_height` (funds, change,
+    /// addresses, expiry), so an invalid proposal never gets saved.
+    pub fn new(
+        transparent_inputs: Vec<TransparentInput>,
+        request: TransactionRequest,
+        change_address: Option<String>,
+        network: Network,
+        expiry_height: u32,
+        current_height: Option<u32>,
+    ) -> Result<Self, T2ZError> {
+        let mut rng_seed = [0u8; 32];
+        OsRng.fill_bytes(&mut rng_seed);
+
+        let mut proposal = Self {
+            transparent_inputs,
+            request,
+            change_address,
+            network,
+            expiry_height,
+            current_height,
+            fee: 0,
+            rng_seed,
+        };
+        proposal.fee = inspect_pczt(&proposal.propose()?)?.implied_fee;
+        Ok(proposal)
+    }
+
+    /// Builds the PCZT for this proposal.
+    ///
+    /// The same proposal always yields the same PCZT. Fails if the fee would
+    /// differ from `self.fee`.
+    pub fn to_pczt(&self) -> Result<Pczt, T2ZError> {
+        let pczt = self.propose()?;
+
+        let fee = inspect_pczt(&pczt)?.implied_fee;
+        if fee != self.fee {
+            return Err(T2ZError::InvalidInput(format!(
+                "Proposal fee mismatch: approved {}, would pay {}",
+                self.fee, fee
+            )));
+        }
+
+        Ok(pczt)
+    }
+
+    fn propose(&self) -> Result<Pczt, T2ZError> {
+        propose_with_fee_strategy(
+            &self.transparent_inputs,
+            self.request.clone(),
+            self.change_address.as_deref(),
+            self.network,
+            self.expiry_height,
+            self.current_height,
+            FeeStrategy::zip317(),
+            TxDefaults::default(),
+            None,
+            &mut ProposalRng::new(Some(self.rng_seed)),
+        )
+    }
+
+    /// Serializes the proposal.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized proposal.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid proposal: {:?}", e)))
+    }
+}
+
+/// Fluent builder for `propose_transaction`
+///
+/// ```ignore
+/// let pczt = ProposalBuilder::new(Network::Testnet)
+///     .add_input(input)
+///     .pay("utest1...", 100_000)
+///     .change_to("tm...")
+///     .expiry(3_000_100)
+///     .propose()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProposalBuilder {
+    network: Network,
+    inputs: Vec<TransparentInput>,
+    payments: Vec<Payment>,
+    change_address: Option<String>,
+    expiry_height: Option<u32>,
+    current_height: Option<u32>,
+    fee_strategy: FeeStrategy,
+    redirect_transparent_memos: bool,
+}
+
+impl ProposalBuilder {
+    /// Starts an empty proposal.
+    pub fn new(network: Network) -> Self {
+        ProposalBuilder {
+            network,
+            inputs: Vec::new(),
+            payments: Vec::new(),
+            change_address: None,
+            expiry_height: None,
+            current_height: None,
+            fee_strategy: FeeStrategy::default(),
+            redirect_transparent_memos: false,
+        }
+    }
+
+    /// Adds a transparent input to spend.
+    pub fn add_input(mut self, input: TransparentInput) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Adds several transparent inputs.
+    pub fn add_inputs(mut self, inputs: impl IntoIterator<Item = TransparentInput>) -> Self {
+        self.inputs.extend(inputs);
+        self
+    }
+
+    /// Pays `amount` zatoshis to `address`.
+    pub fn pay(self, address: impl Into<String>, amount: u64) -> Self {
+        self.add_payment(Payment {
+            address: address.into(),
+            amount,
+            memo: None,
+            label: None,
+        })
+    }
+
+    /// Pays `amount` zatoshis to a shielded `address` with a memo.
+    pub fn pay_with_memo(self, address: impl Into<String>, amount: u64, memo: Vec<u8>) -> Self {
+        self.add_payment(Payment {
+            address: address.into(),
+            amount,
+            memo: Some(memo),
+            label: None,
+        })
+    }
+
+    /// Adds a fully specified payment.
+    pub fn add_payment(mut self, payment: Payment) -> Self {
+        self.payments.push(payment);
+        self
+    }
+
+    /// Sends change to `address` (transparent or Orchard).
+    pub fn change_to(mut self, address: impl Into<String>) -> Self {
+        self.change_address = Some(address.into());
+        self
+    }
+
+    /// Sets the expiry height (0 for no expiry). Required.
+    pub fn expiry(mut self, expiry_height: u32) -> Self {
+        self.expiry_height = Some(expiry_height);
+        self
+    }
+
+    /// Sets the chain tip height used to validate the expiry.
+    pub fn current_height(mut self, current_height: u32) -> Self {
+        self.current_height = Some(current_height);
+        self
+    }
+
+    /// Overrides the ZIP 317 fee.
+    pub fn fee(mut self, fee_strategy: FeeStrategy) -> Self {
+        self.fee_strategy = fee_strategy;
+        self
+    }
+
+    /// Sends memos on transparent payments to the Orchard change address.
+    ///
+    /// Without this, a memo on a transparent payment makes `propose()` fail.
+    /// See `redirect_transparent_memos`.
+    pub fn redirect_transparent_memos(mut self) -> Self {
+        self.redirect_transparent_memos = true;
+        self
+    }
+
+    /// The transaction request assembled so far.
+    pub fn request(&self) -> TransactionRequest {
+        TransactionRequest {
+            payments: self.payments.clone(),
+        }
+    }
+
+    /// Validates the proposal and builds the PCZT.
+    pub fn propose(&self) -> Result<Pczt, T2ZError> {
+        let expiry_height = self
+            .expiry_height
+            .ok_or_else(|| T2ZError::InvalidInput("Expiry height not set".to_string()))?;
+
+        let mut request = self.request();
+        if self.redirect_transparent_memos {
+            let change_address = self.change_address.as_deref().ok_or_else(|| {
+                T2ZError::InvalidInput(
+                    "Redirecting memos requires an Orchard change address".to_string(),
+                )
+            })?;
+            request = redirect_transparent_memos(request, change_address)?;
+        }
+
+        propose_transaction_with_options(
+            &self.inputs,
+            request,
+            self.change_address.as_deref(),
+            self.network,
+            ProposalOptions {
+                fee_strategy: self.fee_strategy.clone(),
+                ..ProposalOptions::with_expiry(
+                    ExpiryPolicy::AtHeight(expiry_height),
+                    self.current_height,
+                )?
+            },
+        )
+    }
+}
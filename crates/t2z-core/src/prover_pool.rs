@@ -0,0 +1,284 @@
+//! Concurrent proving service for native backends.
+//!
+//! Halo 2 proving is CPU-bound and takes seconds per transaction. Services that
+//! prove behind a single mutex serialize every request; `ProverPool` instead owns
+//! the proving key, queues PCZTs on a channel, and proves up to one PCZT per
+//! worker thread at a time. Results come back through a `ProofHandle`, which is
+//! both a `Future` (for async services) and blocking via `wait()`.
+//!
+//! Dropping a `ProofHandle` (or calling `cancel()`) cancels its job: a PCZT
+//! still in the queue fails with a cancellation error instead of being proved,
+//! and a proof already running finishes normally. `wait_timeout()` gives
+//! callers a deadline without cancelling anything.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{OrchardProvingKey, Pczt, T2ZError, load_orchard_proving_key, prove_transaction_with_key};
+
+/// Where the pool gets its proving key from
+enum ProvingKeySource {
+    /// The process-wide cached key (built on first use)
+    Cached,
+    /// A key owned by this pool
+    Owned(OrchardProvingKey),
+}
+
+impl ProvingKeySource {
+    fn get(&self) -> &OrchardProvingKey {
+        match self {
+            ProvingKeySource::Cached => load_orchard_proving_key(),
+            ProvingKeySource::Owned(key) => key,
+        }
+    }
+}
+
+/// How a worker proves one PCZT
+type ProveFn = dyn Fn(Pczt) -> Result<Pczt, T2ZError> + Send + Sync;
+
+struct Job {
+    pczt: Pczt,
+    slot: Arc<ResultSlot>,
+}
+
+#[derive(Default)]
+struct SlotState {
+    result: Option<Result<Pczt, T2ZError>>,
+    waker: Option<Waker>,
+}
+
+/// One-shot slot a worker fills in when a job completes
+#[derive(Default)]
+struct ResultSlot {
+    state: Mutex<SlotState>,
+    ready: Condvar,
+    cancelled: AtomicBool,
+}
+
+impl ResultSlot {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    fn complete(&self, result: Result<Pczt, T2ZError>) {
+        let mut state = self.state.lock().expect("result slot poisoned");
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.ready.notify_all();
+    }
+}
+
+/// Handle to a PCZT submitted to a `ProverPool`.
+///
+/// Await it from async code, or call `wait()` to block the current thread.
+/// Dropping it cancels the job.
+pub struct ProofHandle {
+    slot: Arc<ResultSlot>,
+}
+
+impl ProofHandle {
+    /// Blocks until the proof is done and returns the proved PCZT.
+    pub fn wait(self) -> Result<Pczt, T2ZError> {
+        let mut state = self.slot.state.lock().expect("result slot poisoned");
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self.slot.ready.wait(state).expect("result slot poisoned");
+        }
+    }
+
+    /// Waits up to `timeout` for the proof.
+    ///
+    /// Returns `None` if it is still pending; the job keeps running and can be
+    /// waited on again. Once a result has been returned, later calls time out.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<Result<Pczt, T2ZError>> {
+        let state = self.slot.state.lock().expect("result slot poisoned");
+        let (mut state, _) = self
+            .slot
+            .ready
+            .wait_timeout_while(state, timeout, |state| state.result.is_none())
+            .expect("result slot poisoned");
+        state.result.take()
+    }
+
+    /// Whether the proof has finished (successfully or not).
+    pub fn is_ready(&self) -> bool {
+        self.slot
+            .state
+            .lock()
+            .expect("result slot poisoned")
+            .result
+            .is_some()
+    }
+
+    /// Cancels the job if it has not started proving yet.
+    ///
+    /// Dropping the handle does the same.
+    pub fn cancel(&self) {
+        self.slot.cancelled.store(true, Ordering::Release);
+    }
+}
+
+impl Drop for ProofHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl Future for ProofHandle {
+    type Output = Result<Pczt, T2ZError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock().expect("result slot poisoned");
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A fixed set of prover threads sharing one Orchard proving key.
+///
+/// Dropping the pool stops accepting work, lets queued PCZTs finish, and joins
+/// the worker threads.
+pub struct ProverPool {
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ProverPool {
+    /// Creates a pool with one worker per available core, using the cached proving key.
+    pub fn new() -> Self {
+        Self::with_workers(default_num_workers())
+    }
+
+    /// Creates a pool with `num_workers` threads (at least one), using the cached proving key.
+    ///
+    /// The key is built lazily by the first job if it isn't loaded yet; call
+    /// `load_orchard_proving_key` beforehand to pay that cost up front.
+    pub fn with_workers(num_workers: usize) -> Self {
+        Self::spawn(ProvingKeySource::Cached, num_workers)
+    }
+
+    /// Creates a pool that owns `proving_key` instead of using the process-wide cache.
+    pub fn with_proving_key(proving_key: OrchardProvingKey, num_workers: usize) -> Self {
+        Self::spawn(ProvingKeySource::Owned(proving_key), num_workers)
+    }
+
+    fn spawn(key: ProvingKeySource, num_workers: usize) -> Self {
+        Self::spawn_with(
+            Arc::new(move |pczt| prove_transaction_with_key(pczt, key.get())),
+            num_workers,
+        )
+    }
+
+    /// A pool whose workers run `prove` instead of the Orchard prover, so
+    /// tests can control when a job finishes.
+    #[cfg(test)]
+    pub(crate) fn with_prove_fn<F>(prove: F, num_workers: usize) -> Self
+    where
+        F: Fn(Pczt) -> Result<Pczt, T2ZError> + Send + Sync + 'static,
+    {
+        Self::spawn_with(Arc::new(prove), num_workers)
+    }
+
+    fn spawn_with(prove: Arc<ProveFn>, num_workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..num_workers.max(1))
+            .map(|idx| {
+                let prove = Arc::clone(&prove);
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("t2z-prover-{}", idx))
+                    .spawn(move || {
+                        loop {
+                            // Only hold the queue lock while waiting for the next job
+                            let job = receiver.lock().expect("prover queue poisoned").recv();
+                            let Ok(job) = job else {
+                                break;
+                            };
+
+                            if job.slot.is_cancelled() {
+                                job.slot.complete(Err(T2ZError::Proving(
+                                    "Proof cancelled".to_string(),
+                                )));
+                                continue;
+                            }
+
+                            let result = panic::catch_unwind(AssertUnwindSafe(|| prove(job.pczt)))
+                                .unwrap_or_else(|_| {
+                                    Err(T2ZError::Proving("Prover thread panicked".to_string()))
+                                });
+
+                            job.slot.complete(result);
+                        }
+                    })
+                    .expect("failed to spawn prover thread")
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    /// Queues a PCZT for proving and returns a handle to the result.
+    pub fn submit(&self, pczt: Pczt) -> ProofHandle {
+        let slot = Arc::new(ResultSlot::default());
+        let job = Job {
+            pczt,
+            slot: Arc::clone(&slot),
+        };
+
+        let sender = self.sender.as_ref().expect("sender lives until drop");
+        if let Err(mpsc::SendError(job)) = sender.send(job) {
+            job.slot.complete(Err(T2ZError::Proving(
+                "Prover pool has shut down".to_string(),
+            )));
+        }
+
+        ProofHandle { slot }
+    }
+
+    /// Number of worker threads (maximum number of concurrent proofs)
+    pub fn num_workers(&self) -> usize {
+        self.workers.len()
+    }
+}
+
+impl Default for ProverPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProverPool {
+    fn drop(&mut self) {
+        // Closing the channel makes idle workers exit once the queue drains
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn default_num_workers() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
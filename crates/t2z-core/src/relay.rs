@@ -0,0 +1,121 @@
+//! Relay protocol for live multi-party signing.
+//!
+//! Participants connect to a relay (e.g. over a WebSocket), `Join` a session
+//! by id, and `Publish` their PCZT whenever they add signatures or proofs.
+//! The relay forwards each update to everyone in the session as an `Update`.
+//! The Combiner can run on either side: a relay that combines (like the
+//! `t2z-server` reference relay) sends the merged PCZT, and `RelayClient`
+//! merges whatever it receives into its own copy, so a relay that only
+//! forwards works too.
+//!
+//! Messages are binary (`to_bytes`/`from_bytes`); the transport only has to
+//! carry them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pczt, T2ZError, combine, parse_pczt};
+
+/// A message between a participant and a relay
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RelayMessage {
+    /// Client -> relay: join a session (must be the first message)
+    Join {
+        session_id: String,
+        participant_id: String,
+    },
+    /// Client -> relay: the participant's current PCZT (serialized)
+    Publish { pczt: Vec<u8> },
+    /// Relay -> client: a PCZT published in the session (serialized), and
+    /// who published it (`None` for the relay's combined PCZT on joining)
+    Update {
+        from: Option<String>,
+        pczt: Vec<u8>,
+    },
+    /// Relay -> client: the relay rejected a message
+    Error { message: String },
+}
+
+impl RelayMessage {
+    /// Serializes the message for transport.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a message received over the wire.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid relay message: {:?}", e)))
+    }
+}
+
+/// One participant's side of a relayed signing session
+///
+/// Keeps the participant's copy of the PCZT and merges every update from
+/// the relay into it.
+#[derive(Debug, Clone)]
+pub struct RelayClient {
+    session_id: String,
+    participant_id: String,
+    pczt: Pczt,
+}
+
+impl RelayClient {
+    /// Starts a participant with its copy of the proposed PCZT.
+    pub fn new(
+        session_id: impl Into<String>,
+        participant_id: impl Into<String>,
+        pczt: Pczt,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            participant_id: participant_id.into(),
+            pczt,
+        }
+    }
+
+    /// The participant's current PCZT, with every update merged in.
+    pub fn pczt(&self) -> &Pczt {
+        &self.pczt
+    }
+
+    /// The `Join` message to send when connecting.
+    pub fn join_message(&self) -> Vec<u8> {
+        RelayMessage::Join {
+            session_id: self.session_id.clone(),
+            participant_id: self.participant_id.clone(),
+        }
+        .to_bytes()
+    }
+
+    /// Merges a locally updated PCZT (e.g. after signing) into the
+    /// participant's copy and returns the `Publish` message to send.
+    pub fn publish(&mut self, pczt: Pczt) -> Result<Vec<u8>, T2ZError> {
+        self.merge(pczt)?;
+        Ok(RelayMessage::Publish {
+            pczt: self.pczt.serialize(),
+        }
+        .to_bytes())
+    }
+
+    /// Handles a message from the relay.
+    ///
+    /// Updates are merged into the participant's PCZT. A relay `Error`, or an
+    /// update that does not combine (a different proposal), is returned as an
+    /// error and leaves the PCZT unchanged.
+    pub fn receive(&mut self, message: &[u8]) -> Result<(), T2ZError> {
+        match RelayMessage::from_bytes(message)? {
+            RelayMessage::Update { pczt, .. } => self.merge(parse_pczt(&pczt)?),
+            RelayMessage::Error { message } => {
+                Err(T2ZError::InvalidInput(format!("Relay error: {}", message)))
+            }
+            RelayMessage::Join { .. } | RelayMessage::Publish { .. } => {
+                Err(T2ZError::InvalidInput("Unexpected message from relay".to_string()))
+            }
+        }
+    }
+
+    fn merge(&mut self, pczt: Pczt) -> Result<(), T2ZError> {
+        self.pczt = combine(vec![self.pczt.clone(), pczt])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Sapling spend/output parameter management.
+//!
+//! Unlike Orchard, Sapling proofs (Groth16) need the parameters from the
+//! Sapling trusted setup: `sapling-spend.params` (~48 MB) and
+//! `sapling-output.params` (~3.5 MB). This crate does not fetch them itself;
+//! callers download them (e.g. from https://download.z.cash/downloads/) or
+//! bundle them, then hand them over as bytes or file paths. Both files are
+//! checked against their published BLAKE2b-512 hashes before parsing, and the
+//! parsed parameters are cached process-wide like the Orchard proving key.
+
+use once_cell::sync::OnceCell;
+use sapling_crypto::circuit::{OutputParameters, SpendParameters};
+
+use crate::T2ZError;
+
+/// BLAKE2b-512 hash of `sapling-spend.params`
+pub const SAPLING_SPEND_HASH: &str = "8270785a1a0d0bc77196f000ee6d221c9c9894f55307bd9357c3f0105d31ca63991ab91324160d8f53e2bbd3c2633a6eb8bdf5205d822e7f3f73edac51b2b70c";
+
+/// BLAKE2b-512 hash of `sapling-output.params`
+pub const SAPLING_OUTPUT_HASH: &str = "657e3d38dbb5cb5e7dd2970e8b03d69b4787dd907285b5a7f0790dcc8072f60bf593b32cc2d1c030e00ff5ae64bf84c5c3beb84ddc841d48264b4a171744d028";
+
+/// Size of `sapling-spend.params` in bytes
+pub const SAPLING_SPEND_BYTES: u64 = 47_958_396;
+
+/// Size of `sapling-output.params` in bytes
+pub const SAPLING_OUTPUT_BYTES: u64 = 3_592_860;
+
+/// Parsed Sapling spend and output parameters
+pub struct SaplingParameters {
+    pub spend: SpendParameters,
+    pub output: OutputParameters,
+}
+
+/// Sapling parameter cache
+static SAPLING_PARAMS: OnceCell<SaplingParameters> = OnceCell::new();
+
+/// Checks `bytes` against an expected BLAKE2b-512 hash (hex).
+pub fn verify_sapling_param_hash(
+    name: &str,
+    bytes: &[u8],
+    expected_hash: &str,
+) -> Result<(), T2ZError> {
+    let hash = blake2b_simd::Params::new().hash_length(64).hash(bytes);
+    let actual = hex::encode(hash.as_bytes());
+
+    if actual != expected_hash {
+        return Err(T2ZError::InvalidInput(format!(
+            "{} hash mismatch: expected {}, got {}",
+            name, expected_hash, actual
+        )));
+    }
+
+    Ok(())
+}
+
+fn parse_sapling_params(spend: &[u8], output: &[u8]) -> Result<SaplingParameters, T2ZError> {
+    verify_sapling_param_hash("sapling-spend.params", spend, SAPLING_SPEND_HASH)?;
+    verify_sapling_param_hash("sapling-output.params", output, SAPLING_OUTPUT_HASH)?;
+
+    // The hash check already pins the exact bytes, so skip the (slow) point
+    // encoding verification.
+    let spend = SpendParameters::read(spend, false).map_err(|e| {
+        T2ZError::InvalidInput(format!("Failed to parse Sapling spend parameters: {}", e))
+    })?;
+    let output = OutputParameters::read(output, false).map_err(|e| {
+        T2ZError::InvalidInput(format!("Failed to parse Sapling output parameters: {}", e))
+    })?;
+
+    Ok(SaplingParameters { spend, output })
+}
+
+/// Verifies, parses, and caches the Sapling parameters from byte buffers.
+///
+/// If the parameters are already cached, the buffers are ignored and the
+/// cached parameters are returned.
+///
+/// # Arguments
+/// * `spend` - Contents of `sapling-spend.params`
+/// * `output` - Contents of `sapling-output.params`
+pub fn load_sapling_params_from_bytes(
+    spend: &[u8],
+    output: &[u8],
+) -> Result<&'static SaplingParameters, T2ZError> {
+    SAPLING_PARAMS.get_or_try_init(|| parse_sapling_params(spend, output))
+}
+
+/// Verifies, parses, and caches the Sapling parameters from files.
+///
+/// # Arguments
+/// * `spend_path` - Path to `sapling-spend.params`
+/// * `output_path` - Path to `sapling-output.params`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_sapling_params_from_path(
+    spend_path: impl AsRef<std::path::Path>,
+    output_path: impl AsRef<std::path::Path>,
+) -> Result<&'static SaplingParameters, T2ZError> {
+    SAPLING_PARAMS.get_or_try_init(|| {
+        let read = |path: &std::path::Path| {
+            std::fs::read(path).map_err(|e| {
+                T2ZError::InvalidInput(format!("Failed to read {}: {}", path.display(), e))
+            })
+        };
+
+        let spend = read(spend_path.as_ref())?;
+        let output = read(output_path.as_ref())?;
+        parse_sapling_params(&spend, &output)
+    })
+}
+
+/// Returns the cached Sapling parameters, if loaded
+pub fn get_cached_sapling_params() -> Option<&'static SaplingParameters> {
+    SAPLING_PARAMS.get()
+}
+
+/// Checks if the Sapling parameters are loaded
+pub fn is_sapling_params_loaded() -> bool {
+    SAPLING_PARAMS.get().is_some()
+}
@@ -0,0 +1,175 @@
+//! Pluggable scriptSig assembly for nonstandard transparent inputs.
+//!
+//! The pczt Spend Finalizer only knows how to build scriptSigs for standard
+//! scripts. Inputs locked by anything else (CLTV/CSV timelocks, custom P2SH
+//! redeem scripts, hash locks) can be finalized by implementing
+//! `ScriptSigFinalizer` and calling `finalize_and_extract_with`: P2PKH inputs
+//! still get the built-in `<sig> <pubkey>` scriptSig, and every other input is
+//! handed to the finalizer.
+
+use std::collections::BTreeMap;
+
+use crate::shadow::TransparentInputShadow;
+use crate::{Pczt, T2ZError, TransactionExtractor, check_ready_to_extract, update_pczt_shadow};
+
+/// Everything a `ScriptSigFinalizer` may need to assemble one input's scriptSig
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptSigContext<'a> {
+    /// Index of the input in the transparent bundle
+    pub input_index: usize,
+    /// Value of the spent output in zatoshis
+    pub value: u64,
+    /// scriptPubKey of the spent output
+    pub script_pubkey: &'a [u8],
+    /// Redeem script, for P2SH inputs
+    pub redeem_script: Option<&'a [u8]>,
+    /// Signatures added by Signers, keyed by compressed pubkey (DER + sighash type byte)
+    pub partial_signatures: &'a BTreeMap<[u8; 33], Vec<u8>>,
+    /// nSequence the input will be serialized with, if set
+    pub sequence: Option<u32>,
+    /// Hash-lock preimages keyed by their SHA-256 digest
+    pub sha256_preimages: &'a BTreeMap<[u8; 32], Vec<u8>>,
+    /// Hash-lock preimages keyed by their HASH160 digest
+    pub hash160_preimages: &'a BTreeMap<[u8; 20], Vec<u8>>,
+}
+
+/// Builds scriptSigs for inputs the built-in P2PKH path doesn't handle.
+pub trait ScriptSigFinalizer {
+    /// Returns the complete scriptSig for the input, or `None` if this
+    /// finalizer doesn't recognize the script (finalization then fails).
+    fn finalize_script_sig(
+        &self,
+        input: &ScriptSigContext<'_>,
+    ) -> Result<Option<Vec<u8>>, T2ZError>;
+}
+
+impl<F> ScriptSigFinalizer for F
+where
+    F: Fn(&ScriptSigContext<'_>) -> Result<Option<Vec<u8>>, T2ZError>,
+{
+    fn finalize_script_sig(
+        &self,
+        input: &ScriptSigContext<'_>,
+    ) -> Result<Option<Vec<u8>>, T2ZError> {
+        self(input)
+    }
+}
+
+/// Finalizes spends using `finalizer` for non-P2PKH inputs, then extracts the
+/// transaction bytes (Spend Finalizer + Transaction Extractor roles).
+///
+/// Like `finalize_and_extract`, but never fails on a nonstandard script as
+/// long as `finalizer` can handle it.
+pub fn finalize_and_extract_with(
+    pczt: Pczt,
+    finalizer: &dyn ScriptSigFinalizer,
+) -> Result<Vec<u8>, T2ZError> {
+    check_ready_to_extract(&pczt)?;
+    let pczt = finalize_spends_with(pczt, finalizer)?;
+    let transaction = TransactionExtractor::new(pczt).extract()?;
+
+    let mut tx_bytes = Vec::new();
+    transaction
+        .write(&mut tx_bytes)
+        .map_err(|e| T2ZError::Builder(format!("Transaction serialization failed: {:?}", e)))?;
+
+    Ok(tx_bytes)
+}
+
+/// Fills in every transparent input's scriptSig (Spend Finalizer role).
+pub fn finalize_spends_with(
+    pczt: Pczt,
+    finalizer: &dyn ScriptSigFinalizer,
+) -> Result<Pczt, T2ZError> {
+    update_pczt_shadow(pczt, |pczt_shadow| {
+        for (input_index, input) in pczt_shadow.transparent.inputs.iter_mut().enumerate() {
+            if input.script_sig.is_some() {
+                continue;
+            }
+
+            let script_sig = if is_p2pkh(&input.script_pubkey) {
+                p2pkh_script_sig(input_index, input)?
+            } else {
+                let context = ScriptSigContext {
+                    input_index,
+                    value: input.value,
+                    script_pubkey: &input.script_pubkey,
+                    redeem_script: input.redeem_script.as_deref(),
+                    partial_signatures: &input.partial_signatures,
+                    sequence: input.sequence,
+                    sha256_preimages: &input.sha256_preimages,
+                    hash160_preimages: &input.hash160_preimages,
+                };
+                finalizer.finalize_script_sig(&context)?.ok_or_else(|| {
+                    T2ZError::InvalidInput(format!(
+                        "No scriptSig finalizer for input {} (script {})",
+                        input_index,
+                        hex::encode(&input.script_pubkey)
+                    ))
+                })?
+            };
+
+            input.script_sig = Some(script_sig);
+
+            // Finalized inputs no longer need their signing metadata
+            input.partial_signatures.clear();
+            input.redeem_script = None;
+            input.bip32_derivation.clear();
+            input.ripemd160_preimages.clear();
+            input.sha256_preimages.clear();
+            input.hash160_preimages.clear();
+            input.hash256_preimages.clear();
+        }
+        Ok(())
+    })
+}
+
+/// OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+pub(crate) fn is_p2pkh(script_pubkey: &[u8]) -> bool {
+    script_pubkey.len() == 25
+        && script_pubkey[..3] == [0x76, 0xa9, 0x14]
+        && script_pubkey[23..] == [0x88, 0xac]
+}
+
+fn p2pkh_script_sig(input_index: usize, input: &TransparentInputShadow) -> Result<Vec<u8>, T2ZError> {
+    let mut signatures = input.partial_signatures.iter();
+    let (pubkey, signature) = match (signatures.next(), signatures.next()) {
+        (Some(entry), None) => entry,
+        (None, _) => {
+            return Err(T2ZError::InvalidInput(format!(
+                "Input {} is not signed",
+                input_index
+            )));
+        }
+        (Some(_), Some(_)) => {
+            return Err(T2ZError::InvalidInput(format!(
+                "P2PKH input {} has more than one signature",
+                input_index
+            )));
+        }
+    };
+
+    let mut script_sig = Vec::with_capacity(2 + signature.len() + pubkey.len());
+    push_data(&mut script_sig, signature);
+    push_data(&mut script_sig, pubkey);
+    Ok(script_sig)
+}
+
+/// Appends the smallest push opcode for `data` followed by `data` itself.
+///
+/// Useful when assembling scriptSigs inside a `ScriptSigFinalizer`.
+pub fn push_data(script: &mut Vec<u8>, data: &[u8]) {
+    match data.len() {
+        len @ 0..=0x4b => script.push(len as u8),
+        len @ 0x4c..=0xff => script.extend_from_slice(&[0x4c, len as u8]),
+        len @ 0x100..=0xffff => {
+            script.push(0x4d);
+            script.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        len => {
+            script.push(0x4e);
+            script.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+    }
+    script.extend_from_slice(data);
+}
@@ -0,0 +1,188 @@
+//! Checking every signature in a PCZT before extraction.
+//!
+//! `append_signature` verifies each external signature as it is added, but
+//! signatures that arrive through the Combiner, a signing package, or a
+//! PCZT edited elsewhere are not checked until a node rejects the
+//! transaction, with no hint of which input was at fault.
+//! `find_invalid_signatures` checks them all in one pass (the transaction is
+//! digested once for every sighash) and names each bad one;
+//! `finalize_and_extract_verified` refuses to extract while any remain.
+//!
+//! Only signatures that are present are checked. Missing ones are reported
+//! by the Spend Finalizer as before.
+
+use std::fmt;
+
+use orchard::primitives::redpallas::{Signature, SpendAuth, VerificationKey};
+use serde::{Deserialize, Serialize};
+
+use crate::script_sig::is_p2pkh;
+use crate::shadow::PcztShadow;
+use crate::status::pubkey_hash;
+use crate::{Pczt, T2ZError, get_shielded_sighash, transparent_sighashes};
+
+/// `SIGHASH_ALL`, the hash type every transparent sighash is computed with
+const SIGHASH_ALL: u8 = 0x01;
+
+/// A signature (or proof) in a PCZT that would not pass consensus checks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InvalidSignature {
+    /// A partial signature on a transparent input
+    Transparent {
+        input_index: usize,
+        /// Hex-encoded compressed public key the signature is filed under
+        pubkey: String,
+        reason: String,
+    },
+    /// The spend authorization signature of an Orchard action
+    OrchardSpendAuth { action_index: usize, reason: String },
+    /// The Orchard proof (checked with the `verifier` feature)
+    OrchardProof { reason: String },
+}
+
+impl fmt::Display for InvalidSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidSignature::Transparent {
+                input_index,
+                pubkey,
+                reason,
+            } => write!(
+                f,
+                "Transparent input {} signature by {}: {}",
+                input_index, pubkey, reason
+            ),
+            InvalidSignature::OrchardSpendAuth {
+                action_index,
+                reason,
+            } => write!(
+                f,
+                "Orchard action {} spend authorization: {}",
+                action_index, reason
+            ),
+            InvalidSignature::OrchardProof { reason } => write!(f, "Orchard proof: {}", reason),
+        }
+    }
+}
+
+/// Checks every signature in a PCZT against its sighash.
+///
+/// Covers transparent partial signatures (DER encoding, `SIGHASH_ALL`, the
+/// key matching a P2PKH input's address, and the ECDSA signature itself),
+/// Orchard spend authorization signatures against each action's `rk`, and,
+/// with the `verifier` feature, the Orchard proof if there is one.
+///
+/// # Returns
+/// Every invalid signature found, in input then action order (empty if all
+/// are valid). Errors only if the PCZT itself is malformed.
+pub fn find_invalid_signatures(pczt: &Pczt) -> Result<Vec<InvalidSignature>, T2ZError> {
+    let mut invalid = Vec::new();
+
+    let inputs = pczt.transparent().inputs();
+    let signed: Vec<usize> = (0..inputs.len())
+        .filter(|&i| !inputs[i].partial_signatures().is_empty())
+        .collect();
+    let sighashes = transparent_sighashes(pczt, &signed)?;
+
+    let secp = secp256k1::Secp256k1::verification_only();
+    for (&input_index, sighash) in signed.iter().zip(sighashes) {
+        let input = &inputs[input_index];
+        for (pubkey, signature) in input.partial_signatures() {
+            let check = check_transparent_signature(
+                &secp,
+                &sighash,
+                input.script_pubkey(),
+                pubkey,
+                signature,
+            );
+            if let Err(reason) = check {
+                invalid.push(InvalidSignature::Transparent {
+                    input_index,
+                    pubkey: hex::encode(pubkey),
+                    reason,
+                });
+            }
+        }
+    }
+
+    let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+    let actions = &pczt_shadow.orchard.actions;
+    if actions
+        .iter()
+        .any(|action| action.spend.spend_auth_sig.is_some())
+    {
+        let sighash = get_shielded_sighash(pczt)?;
+        for (action_index, action) in actions.iter().enumerate() {
+            let Some(signature) = action.spend.spend_auth_sig else {
+                continue;
+            };
+            let check = VerificationKey::<SpendAuth>::try_from(action.spend.rk)
+                .map_err(|_| "Invalid rk".to_string())
+                .and_then(|rk| {
+                    rk.verify(&sighash, &Signature::<SpendAuth>::from(signature))
+                        .map_err(|_| "Signature does not verify".to_string())
+                });
+            if let Err(reason) = check {
+                invalid.push(InvalidSignature::OrchardSpendAuth {
+                    action_index,
+                    reason,
+                });
+            }
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    if pczt_shadow.orchard.zkproof.is_some()
+        && let Err(e) = crate::verify_orchard_proof(pczt)
+    {
+        invalid.push(InvalidSignature::OrchardProof {
+            reason: e.to_string(),
+        });
+    }
+
+    Ok(invalid)
+}
+
+/// Checks every signature like `find_invalid_signatures`, then finalizes and
+/// extracts the transaction like `finalize_and_extract`.
+///
+/// Fails with the first invalid signature instead of producing a
+/// transaction a node would reject.
+pub fn finalize_and_extract_verified(pczt: Pczt) -> Result<Vec<u8>, T2ZError> {
+    if let Some(invalid) = find_invalid_signatures(&pczt)?.into_iter().next() {
+        return Err(T2ZError::InvalidSignature(invalid));
+    }
+    crate::finalize_and_extract(pczt)
+}
+
+fn check_transparent_signature(
+    secp: &secp256k1::Secp256k1<secp256k1::VerifyOnly>,
+    sighash: &[u8; 32],
+    script_pubkey: &[u8],
+    pubkey: &[u8; 33],
+    signature: &[u8],
+) -> Result<(), String> {
+    let pk = secp256k1::PublicKey::from_slice(pubkey)
+        .map_err(|e| format!("Invalid public key: {}", e))?;
+
+    // A valid signature by the wrong key still fails OP_EQUALVERIFY
+    if is_p2pkh(script_pubkey) && pubkey_hash(&pk) != script_pubkey[3..23] {
+        return Err("Public key does not match the input's address".to_string());
+    }
+
+    let (&hash_type, der_sig) = signature
+        .split_last()
+        .ok_or_else(|| "Signature is empty".to_string())?;
+    if hash_type != SIGHASH_ALL {
+        return Err(format!(
+            "Sighash type {:#04x} is not SIGHASH_ALL",
+            hash_type
+        ));
+    }
+    let sig = secp256k1::ecdsa::Signature::from_der(der_sig)
+        .map_err(|e| format!("Invalid DER signature: {}", e))?;
+
+    secp.verify_ecdsa(&secp256k1::Message::from_digest(*sighash), &sig, &pk)
+        .map_err(|_| "Signature does not verify".to_string())
+}
@@ -0,0 +1,162 @@
+//! Signing packages for air-gapped signers.
+//!
+//! A `SigningPackage` carries only what an offline device needs to sign the
+//! transparent inputs of a PCZT: each input's sighash, the coin it spends,
+//! and the key derivations recorded for it. The device answers with a
+//! `SigningPackageResponse` holding just the signatures, which
+//! `import_signatures` checks against the PCZT and merges in. The PCZT itself
+//! (with its Orchard bundle and proofs) never has to cross the air gap.
+//!
+//! Both messages are serde-serializable and have `to_bytes`/`from_bytes`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::shadow::PcztShadow;
+use crate::{Pczt, T2ZError, append_signature_in_place, get_sighash};
+
+/// A key derivation recorded for a transparent input
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningPackageDerivation {
+    /// 33-byte compressed public key (hex)
+    pub pubkey: String,
+    /// ZIP 32 seed fingerprint (hex)
+    pub seed_fingerprint: String,
+    /// BIP 32 derivation path (hardened indices have the high bit set)
+    pub derivation_path: Vec<u32>,
+}
+
+/// What an offline signer needs for one transparent input
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningPackageInput {
+    /// Index of the input in the PCZT
+    pub input_index: u32,
+    /// Sighash to sign (hex, SIGHASH_ALL)
+    pub sighash: String,
+    /// Previous transaction ID (hex, display order - big-endian)
+    pub prevout_txid: String,
+    /// Previous output index
+    pub prevout_index: u32,
+    /// Value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub value: u64,
+    /// Script pubkey (hex)
+    pub script_pubkey: String,
+    /// Key derivations, if the PCZT records any
+    pub derivations: Vec<SigningPackageDerivation>,
+}
+
+/// Everything an air-gapped device needs to sign a PCZT's transparent inputs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningPackage {
+    /// Transaction expiry height, for display on the device
+    pub expiry_height: u32,
+    /// One entry per transparent input, in order
+    pub inputs: Vec<SigningPackageInput>,
+}
+
+/// A signature produced by an offline signer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PackageSignature {
+    /// Index of the input it signs
+    pub input_index: u32,
+    /// 33-byte compressed public key (hex)
+    pub pubkey: String,
+    /// DER signature with the sighash type byte appended (hex)
+    pub signature: String,
+}
+
+/// The offline signer's answer to a `SigningPackage`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningPackageResponse {
+    /// Signatures, in any order (several per input for multisig)
+    pub signatures: Vec<PackageSignature>,
+}
+
+macro_rules! impl_package_bytes {
+    ($ty:ty, $name:literal) => {
+        impl $ty {
+            /// Serializes the message for transport.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                postcard::to_allocvec(self).expect("can serialize into memory")
+            }
+
+            /// Parses a message received over the wire.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+                postcard::from_bytes(bytes).map_err(|e| {
+                    T2ZError::InvalidInput(format!(concat!("Invalid ", $name, ": {:?}"), e))
+                })
+            }
+        }
+    };
+}
+
+impl_package_bytes!(SigningPackage, "signing package");
+impl_package_bytes!(SigningPackageResponse, "signing package response");
+
+/// Exports the signing package for a PCZT's transparent inputs.
+pub fn export_signing_package(pczt: &Pczt) -> Result<SigningPackage, T2ZError> {
+    let (_, shadow) = PcztShadow::from_pczt(pczt)?;
+
+    let inputs = shadow
+        .transparent
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(input_index, input)| {
+            // Reverse txid for display (internal is little-endian, display is big-endian)
+            let mut txid_bytes = input.prevout_txid;
+            txid_bytes.reverse();
+
+            Ok(SigningPackageInput {
+                input_index: input_index as u32,
+                sighash: hex::encode(get_sighash(pczt, input_index)?),
+                prevout_txid: hex::encode(txid_bytes),
+                prevout_index: input.prevout_index,
+                value: input.value,
+                script_pubkey: hex::encode(&input.script_pubkey),
+                derivations: input
+                    .bip32_derivation
+                    .iter()
+                    .map(|(pubkey, derivation)| SigningPackageDerivation {
+                        pubkey: hex::encode(pubkey),
+                        seed_fingerprint: hex::encode(derivation.seed_fingerprint),
+                        derivation_path: derivation.derivation_path.clone(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect::<Result<Vec<_>, T2ZError>>()?;
+
+    Ok(SigningPackage {
+        expiry_height: *pczt.global().expiry_height(),
+        inputs,
+    })
+}
+
+/// Merges an offline signer's signatures into the PCZT.
+///
+/// Every signature is verified against the PCZT's own sighash, so a response
+/// for a different PCZT (or a tampered package) is rejected. Nothing is
+/// merged unless all signatures are valid.
+pub fn import_signatures(
+    mut pczt: Pczt,
+    response: &SigningPackageResponse,
+) -> Result<Pczt, T2ZError> {
+    for (idx, entry) in response.signatures.iter().enumerate() {
+        let pubkey: [u8; 33] = hex::decode(&entry.pubkey)
+            .map_err(|e| {
+                T2ZError::InvalidInput(format!("Signature {}: invalid pubkey hex: {}", idx, e))
+            })?
+            .try_into()
+            .map_err(|_| {
+                T2ZError::InvalidInput(format!("Signature {}: public key must be 33 bytes", idx))
+            })?;
+        let signature = hex::decode(&entry.signature).map_err(|e| {
+            T2ZError::InvalidInput(format!("Signature {}: invalid signature hex: {}", idx, e))
+        })?;
+
+        append_signature_in_place(&mut pczt, entry.input_index as usize, &pubkey, &signature)?;
+    }
+
+    Ok(pczt)
+}
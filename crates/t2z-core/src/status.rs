@@ -0,0 +1,187 @@
+//! Who still owes signatures on a PCZT.
+//!
+//! One proposal may spend P2PKH inputs controlled by different keys (say,
+//! several deposit addresses of an exchange, each signed by its own HSM
+//! slot) alongside P2SH multisig inputs whose cosigners sign out of band.
+//! Each input is signed independently, so this works as long as every key
+//! holder eventually adds its signature; `pczt_status` says which holders
+//! have not yet, and for which inputs.
+//!
+//! Signers are identified by the HASH160 of their compressed public key (hex),
+//! the one identifier both P2PKH scripts and multisig redeem scripts commit
+//! to; `signer_id` computes it for a public key. Inputs locked by any other
+//! script are reported without signers: they are complete once finalized
+//! with a `ScriptSigFinalizer`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::script_sig::is_p2pkh;
+use crate::shadow::{PcztShadow, TransparentInputShadow};
+use crate::{Pczt, T2ZError};
+
+/// OP_CHECKMULTISIG
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+/// What locks a transparent input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputScriptKind {
+    /// Pay to one public key hash
+    P2pkh,
+    /// P2SH with a bare multisig redeem script (`m <pubkeys> n CHECKMULTISIG`)
+    P2shMultisig,
+    /// Anything else, including P2SH without a redeem script in the PCZT
+    Other,
+}
+
+/// Signing progress of one transparent input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSigningStatus {
+    /// Index of the input in the transparent bundle
+    pub index: usize,
+    /// What locks the input
+    pub kind: InputScriptKind,
+    /// Signers that can sign the input
+    pub signers: Vec<String>,
+    /// Signers that have signed it
+    pub signed_by: Vec<String>,
+    /// Signatures the input needs (0 if unknown)
+    pub signatures_required: usize,
+    /// Whether the input has all the signatures it needs, or is finalized
+    pub complete: bool,
+}
+
+/// Signing progress of a whole PCZT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcztStatus {
+    /// Every transparent input, in order
+    pub inputs: Vec<InputSigningStatus>,
+    /// Each signer that could still sign an incomplete input, with the
+    /// indices of those inputs. For multisig inputs, every cosigner that has
+    /// not signed is listed until enough have.
+    pub pending: BTreeMap<String, Vec<usize>>,
+    /// Whether every transparent input is complete
+    pub all_inputs_signed: bool,
+    /// Whether the Orchard bundle has its proof
+    pub has_orchard_proofs: bool,
+}
+
+/// The identifier `pczt_status` uses for the signer holding the 33-byte
+/// compressed public key `pubkey`.
+pub fn signer_id(pubkey: &[u8]) -> Result<String, T2ZError> {
+    let pubkey = secp256k1::PublicKey::from_slice(pubkey)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid public key: {}", e)))?;
+    Ok(hex::encode(pubkey_hash(&pubkey)))
+}
+
+/// Reports which transparent inputs of `pczt` are signed and which signers
+/// still owe signatures.
+pub fn pczt_status(pczt: &Pczt) -> Result<PcztStatus, T2ZError> {
+    let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+
+    let inputs: Vec<InputSigningStatus> = pczt_shadow
+        .transparent
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| input_status(index, input))
+        .collect();
+
+    let mut pending: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for input in inputs.iter().filter(|input| !input.complete) {
+        for signer in input
+            .signers
+            .iter()
+            .filter(|s| !input.signed_by.contains(s))
+        {
+            pending.entry(signer.clone()).or_default().push(input.index);
+        }
+    }
+
+    Ok(PcztStatus {
+        all_inputs_signed: inputs.iter().all(|input| input.complete),
+        inputs,
+        pending,
+        has_orchard_proofs: pczt_shadow.orchard.zkproof.is_some(),
+    })
+}
+
+fn input_status(index: usize, input: &TransparentInputShadow) -> InputSigningStatus {
+    let signed_keys: Vec<String> = input
+        .partial_signatures
+        .keys()
+        .filter_map(|pubkey| secp256k1::PublicKey::from_slice(pubkey).ok())
+        .map(|pubkey| hex::encode(pubkey_hash(&pubkey)))
+        .collect();
+    let finalized = input.script_sig.is_some();
+
+    let (kind, signers, signatures_required) = if is_p2pkh(&input.script_pubkey) {
+        let signer = hex::encode(&input.script_pubkey[3..23]);
+        (InputScriptKind::P2pkh, vec![signer], 1)
+    } else if let Some((threshold, pubkeys)) =
+        input.redeem_script.as_deref().and_then(parse_multisig)
+    {
+        let signers = pubkeys
+            .iter()
+            .map(|pubkey| hex::encode(pubkey_hash(pubkey)))
+            .collect();
+        (InputScriptKind::P2shMultisig, signers, threshold)
+    } else {
+        (InputScriptKind::Other, Vec::new(), 0)
+    };
+
+    let signed_by: Vec<String> = signers
+        .iter()
+        .filter(|signer| signed_keys.contains(signer))
+        .cloned()
+        .collect();
+    let complete = finalized || (signatures_required > 0 && signed_by.len() >= signatures_required);
+
+    InputSigningStatus {
+        index,
+        kind,
+        signers,
+        signed_by,
+        signatures_required,
+        complete,
+    }
+}
+
+pub(crate) fn pubkey_hash(pubkey: &secp256k1::PublicKey) -> [u8; 20] {
+    use zcash_transparent::address::TransparentAddress;
+
+    match zcash_transparent::keys::pubkey_to_address(pubkey) {
+        TransparentAddress::PublicKeyHash(hash) => hash,
+        TransparentAddress::ScriptHash(_) => unreachable!("public keys map to P2PKH addresses"),
+    }
+}
+
+/// Parses `OP_m <pubkey>... OP_n OP_CHECKMULTISIG` with compressed keys
+pub(crate) fn parse_multisig(redeem_script: &[u8]) -> Option<(usize, Vec<secp256k1::PublicKey>)> {
+    let small_int = |opcode: u8| {
+        (0x51..=0x60)
+            .contains(&opcode)
+            .then(|| (opcode - 0x50) as usize)
+    };
+
+    let (&first, rest) = redeem_script.split_first()?;
+    let (&last, rest) = rest.split_last()?;
+    let (&count, mut keys) = rest.split_last()?;
+    let threshold = small_int(first)?;
+    let count = small_int(count)?;
+    if last != OP_CHECKMULTISIG || threshold > count {
+        return None;
+    }
+
+    let mut pubkeys = Vec::with_capacity(count);
+    while let Some((&len, rest)) = keys.split_first() {
+        if len != 33 || rest.len() < 33 {
+            return None;
+        }
+        pubkeys.push(secp256k1::PublicKey::from_slice(&rest[..33]).ok()?);
+        keys = &rest[33..];
+    }
+    (pubkeys.len() == count).then_some((threshold, pubkeys))
+}
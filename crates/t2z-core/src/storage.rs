@@ -0,0 +1,299 @@
+//! Persistent storage for wallet_lite state (feature `wallet_lite`).
+//!
+//! `Storage` is a small key-value interface with atomic batches. Account
+//! state, PCZT sessions, and the broadcast queue are stored under fixed keys,
+//! so any backend that implements the trait can persist them: `MemoryStorage`
+//! for tests and ephemeral use, `SqliteStorage` (feature `sqlite`) for native
+//! apps, and the IndexedDB adapter in the WASM bindings for browsers.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::{Account, BroadcastQueue, Pczt, T2ZError, parse_pczt, serialize_pczt};
+
+/// Key prefix for serialized `Account` state
+pub const ACCOUNT_KEY_PREFIX: &[u8] = b"account/";
+
+/// Key prefix for in-progress PCZTs
+pub const PCZT_KEY_PREFIX: &[u8] = b"pczt/";
+
+/// Key of the serialized `BroadcastQueue`
+pub const BROADCAST_QUEUE_KEY: &[u8] = b"broadcast_queue";
+
+/// One write in a `WriteBatch`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    Put { key: Vec<u8>, value: Vec<u8> },
+    Delete { key: Vec<u8> },
+}
+
+/// Writes applied together or not at all
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteBatch {
+    ops: Vec<BatchOp>,
+}
+
+impl WriteBatch {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a put.
+    pub fn put(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) -> &mut Self {
+        self.ops.push(BatchOp::Put {
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Adds a delete.
+    pub fn delete(&mut self, key: impl Into<Vec<u8>>) -> &mut Self {
+        self.ops.push(BatchOp::Delete { key: key.into() });
+        self
+    }
+
+    /// The writes, in order.
+    pub fn ops(&self) -> &[BatchOp] {
+        &self.ops
+    }
+
+    /// Whether the batch has no writes.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Key-value storage with atomic batches
+pub trait Storage {
+    /// Reads the value stored under `key`.
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, T2ZError>;
+
+    /// Lists every key starting with `prefix`, in byte order.
+    fn keys_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, T2ZError>;
+
+    /// Applies every write in `batch` atomically.
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), T2ZError>;
+
+    /// Stores a single value.
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), T2ZError> {
+        let mut batch = WriteBatch::new();
+        batch.put(key, value);
+        self.write_batch(batch)
+    }
+
+    /// Deletes a single key.
+    fn delete(&self, key: &[u8]) -> Result<(), T2ZError> {
+        let mut batch = WriteBatch::new();
+        batch.delete(key);
+        self.write_batch(batch)
+    }
+}
+
+/// In-memory `Storage`
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a store holding `entries` (e.g. loaded from another backend).
+    pub fn from_entries(entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> Self {
+        MemoryStorage {
+            entries: Mutex::new(entries.into_iter().collect()),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, T2ZError> {
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn keys_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, T2ZError> {
+        Ok(self
+            .entries()
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect())
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), T2ZError> {
+        let mut entries = self.entries();
+        for op in batch.ops {
+            match op {
+                BatchOp::Put { key, value } => {
+                    entries.insert(key, value);
+                }
+                BatchOp::Delete { key } => {
+                    entries.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn prefixed(prefix: &[u8], name: &str) -> Vec<u8> {
+    [prefix, name.as_bytes()].concat()
+}
+
+/// Stores an account's state under `name`.
+pub fn save_account(storage: &dyn Storage, name: &str, account: &Account) -> Result<(), T2ZError> {
+    storage.put(&prefixed(ACCOUNT_KEY_PREFIX, name), &account.to_bytes())
+}
+
+/// Loads the account stored under `name`, if any.
+pub fn load_account(storage: &dyn Storage, name: &str) -> Result<Option<Account>, T2ZError> {
+    storage
+        .get(&prefixed(ACCOUNT_KEY_PREFIX, name))?
+        .map(|bytes| Account::from_bytes(&bytes))
+        .transpose()
+}
+
+/// Stores an in-progress PCZT under `session_id`.
+pub fn save_pczt(storage: &dyn Storage, session_id: &str, pczt: &Pczt) -> Result<(), T2ZError> {
+    storage.put(&prefixed(PCZT_KEY_PREFIX, session_id), &serialize_pczt(pczt))
+}
+
+/// Loads the PCZT stored under `session_id`, if any.
+pub fn load_pczt(storage: &dyn Storage, session_id: &str) -> Result<Option<Pczt>, T2ZError> {
+    storage
+        .get(&prefixed(PCZT_KEY_PREFIX, session_id))?
+        .map(|bytes| parse_pczt(&bytes))
+        .transpose()
+}
+
+/// Removes the PCZT stored under `session_id`.
+pub fn delete_pczt(storage: &dyn Storage, session_id: &str) -> Result<(), T2ZError> {
+    storage.delete(&prefixed(PCZT_KEY_PREFIX, session_id))
+}
+
+/// Session IDs of every stored PCZT.
+pub fn list_pczt_sessions(storage: &dyn Storage) -> Result<Vec<String>, T2ZError> {
+    storage
+        .keys_with_prefix(PCZT_KEY_PREFIX)?
+        .into_iter()
+        .map(|key| {
+            String::from_utf8(key[PCZT_KEY_PREFIX.len()..].to_vec())
+                .map_err(|_| T2ZError::Storage("Stored session ID is not UTF-8".to_string()))
+        })
+        .collect()
+}
+
+/// Stores the broadcast queue.
+///
+/// Save it after every `enqueue`, `report`, and `mark_mined`, so no signed
+/// transaction is lost if the app is killed.
+pub fn save_broadcast_queue(storage: &dyn Storage, queue: &BroadcastQueue) -> Result<(), T2ZError> {
+    storage.put(BROADCAST_QUEUE_KEY, &queue.to_bytes())
+}
+
+/// Loads the stored broadcast queue, if any.
+pub fn load_broadcast_queue(storage: &dyn Storage) -> Result<Option<BroadcastQueue>, T2ZError> {
+    storage
+        .get(BROADCAST_QUEUE_KEY)?
+        .map(|bytes| BroadcastQueue::from_bytes(&bytes))
+        .transpose()
+}
+
+/// SQLite-backed `Storage` for native apps
+#[cfg(feature = "sqlite")]
+pub struct SqliteStorage {
+    conn: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Opens (or creates) the database file at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, T2ZError> {
+        let conn = rusqlite::Connection::open(path)
+            .map_err(|e| T2ZError::Storage(format!("Failed to open database: {}", e)))?;
+        Self::init(conn)
+    }
+
+    /// Opens a private in-memory database.
+    pub fn open_in_memory() -> Result<Self, T2ZError> {
+        let conn = rusqlite::Connection::open_in_memory()
+            .map_err(|e| T2ZError::Storage(format!("Failed to open database: {}", e)))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: rusqlite::Connection) -> Result<Self, T2ZError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS t2z_kv (key BLOB PRIMARY KEY, value BLOB NOT NULL)",
+            [],
+        )
+        .map_err(|e| T2ZError::Storage(format!("Failed to create table: {}", e)))?;
+
+        Ok(SqliteStorage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, rusqlite::Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Storage for SqliteStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, T2ZError> {
+        use rusqlite::OptionalExtension;
+
+        self.conn()
+            .query_row("SELECT value FROM t2z_kv WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| T2ZError::Storage(format!("Failed to read key: {}", e)))
+    }
+
+    fn keys_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, T2ZError> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT key FROM t2z_kv WHERE substr(key, 1, ?2) = ?1 ORDER BY key")
+            .map_err(|e| T2ZError::Storage(format!("Failed to list keys: {}", e)))?;
+
+        let keys = stmt
+            .query_map(rusqlite::params![prefix, prefix.len() as i64], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<Vec<u8>>, _>>())
+            .map_err(|e| T2ZError::Storage(format!("Failed to list keys: {}", e)))?;
+
+        Ok(keys)
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), T2ZError> {
+        let mut conn = self.conn();
+        let tx = conn
+            .transaction()
+            .map_err(|e| T2ZError::Storage(format!("Failed to begin transaction: {}", e)))?;
+
+        for op in batch.ops {
+            let result = match op {
+                BatchOp::Put { key, value } => tx.execute(
+                    "INSERT OR REPLACE INTO t2z_kv (key, value) VALUES (?1, ?2)",
+                    rusqlite::params![key, value],
+                ),
+                BatchOp::Delete { key } => {
+                    tx.execute("DELETE FROM t2z_kv WHERE key = ?1", [key])
+                }
+            };
+            result.map_err(|e| T2ZError::Storage(format!("Failed to write batch: {}", e)))?;
+        }
+
+        tx.commit()
+            .map_err(|e| T2ZError::Storage(format!("Failed to commit batch: {}", e)))
+    }
+}
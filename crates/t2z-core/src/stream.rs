@@ -0,0 +1,149 @@
+//! Streaming PCZT serialization.
+//!
+//! `Pczt::serialize` and `Pczt::parse` operate on whole byte buffers, so a
+//! multi-megabyte PCZT gets copied several times over during a hex round-trip
+//! (hex string -> bytes -> PCZT and back). These helpers read and write the
+//! same wire format (4 bytes magic + 4 bytes version + postcard data) directly
+//! against `std::io` streams, plus hex adapters so bindings can go straight
+//! between a hex string and a `Pczt`.
+
+use std::io::{self, BufReader, Read, Write};
+
+use crate::{Pczt, T2ZError};
+
+/// PCZT magic bytes
+pub(crate) const PCZT_MAGIC: [u8; 4] = *b"PCZT";
+
+/// The PCZT format version written by this crate
+pub(crate) const PCZT_VERSION_1: u32 = 1;
+
+/// Scratch space postcard may borrow from while parsing (strings such as
+/// `user_address` and proprietary keys are read through it).
+const PARSE_SCRATCH_SIZE: usize = 64 * 1024;
+
+/// Serializes a PCZT into any `Write` sink without building an intermediate buffer.
+///
+/// Produces exactly the same bytes as `serialize_pczt`.
+pub fn serialize_pczt_into<W: Write>(pczt: &Pczt, writer: &mut W) -> Result<(), T2ZError> {
+    writer
+        .write_all(&PCZT_MAGIC)
+        .and_then(|_| writer.write_all(&PCZT_VERSION_1.to_le_bytes()))
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to write PCZT header: {}", e)))?;
+
+    postcard::to_io(pczt, &mut *writer)
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to serialize PCZT: {:?}", e)))?;
+
+    Ok(())
+}
+
+/// Parses a PCZT from any `Read` source.
+///
+/// The reader is consumed incrementally, so the full serialized PCZT never
+/// needs to be held in memory alongside the parsed structure.
+pub fn parse_pczt_from_reader<R: Read>(reader: R) -> Result<Pczt, T2ZError> {
+    let mut reader = BufReader::new(reader);
+
+    let mut header = [0u8; 8];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| T2ZError::InvalidInput("PCZT too short".to_string()))?;
+
+    if header[..4] != PCZT_MAGIC {
+        return Err(T2ZError::InvalidInput(
+            "Not a PCZT: invalid magic bytes".to_string(),
+        ));
+    }
+
+    let version = u32::from_le_bytes(header[4..8].try_into().expect("4 bytes"));
+    if version != PCZT_VERSION_1 {
+        return Err(T2ZError::InvalidInput(format!(
+            "Unsupported PCZT version: {}",
+            version
+        )));
+    }
+
+    let mut scratch = vec![0u8; PARSE_SCRATCH_SIZE];
+    let (pczt, _) = postcard::from_io::<Pczt, _>((reader, scratch.as_mut_slice()))
+        .map_err(|e| T2ZError::InvalidInput(format!("Failed to deserialize PCZT: {:?}", e)))?;
+
+    Ok(pczt)
+}
+
+/// `Write` adapter that hex-encodes everything written into a `String`.
+pub struct HexWriter<'a> {
+    out: &'a mut String,
+}
+
+impl<'a> HexWriter<'a> {
+    pub fn new(out: &'a mut String) -> Self {
+        Self { out }
+    }
+}
+
+impl Write for HexWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+        self.out.reserve(buf.len() * 2);
+        for byte in buf {
+            self.out.push(HEX_CHARS[(byte >> 4) as usize] as char);
+            self.out.push(HEX_CHARS[(byte & 0x0f) as usize] as char);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `Read` adapter that decodes a hex string on the fly.
+pub struct HexReader<'a> {
+    hex: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> HexReader<'a> {
+    pub fn new(hex: &'a str) -> Self {
+        Self {
+            hex: hex.as_bytes(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for HexReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = &self.hex[self.pos..];
+        let n = buf.len().min(remaining.len() / 2);
+
+        if n == 0 {
+            return if remaining.is_empty() || buf.is_empty() {
+                Ok(0)
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Odd number of hex digits",
+                ))
+            };
+        }
+
+        hex::decode_to_slice(&remaining[..n * 2], &mut buf[..n])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.pos += n * 2;
+
+        Ok(n)
+    }
+}
+
+/// Hex-encodes a PCZT without materializing the intermediate byte buffer.
+pub fn pczt_to_hex(pczt: &Pczt) -> String {
+    let mut out = String::new();
+    serialize_pczt_into(pczt, &mut HexWriter::new(&mut out)).expect("can serialize into memory");
+    out
+}
+
+/// Parses a hex-encoded PCZT, decoding the hex as the parser consumes it.
+pub fn pczt_from_hex(hex_string: &str) -> Result<Pczt, T2ZError> {
+    parse_pczt_from_reader(HexReader::new(hex_string))
+}
@@ -0,0 +1,216 @@
+//! Payment templates for recurring payouts.
+//!
+//! A `PaymentTemplate` fixes who gets paid (addresses, memos, labels), the
+//! network, and where change goes, but not how much or from which coins.
+//! It is validated once when created and can be stored (it is
+//! serde-serializable). Each cycle, `instantiate` fills in the amounts and
+//! the UTXOs on hand and proposes a fresh PCZT.
+//!
+//! Mining pools paying out block rewards use `propose_coinbase_shielding`
+//! instead: it shields coinbase UTXOs straight to several Orchard addresses,
+//! splitting what is left after the fee by weight, with no change.
+
+use serde::{Deserialize, Serialize};
+use zcash_primitives::transaction::fees::FeeRule;
+
+use crate::fees::{orchard_actions_for_outputs, split_proportionally, zip317_fee};
+use crate::options::merge_duplicate_payments;
+use crate::{
+    ChangePolicy, FeeStrategy, InputConfirmations, IssueSeverity, Network, Payment, Pczt,
+    ProposalOptions, T2ZError, TransactionRequest, TransparentInput, checked_sum,
+    propose_transaction_with_options, validate_request,
+};
+
+/// A recipient of a payment template
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TemplateRecipient {
+    /// Address string (unified address with Orchard, or transparent P2PKH/P2SH)
+    pub address: String,
+    /// Memo bytes (max 512 bytes; shielded recipients only)
+    #[serde(with = "crate::serde_bytes")]
+    pub memo: Option<Vec<u8>>,
+    /// Optional label for the payment
+    pub label: Option<String>,
+}
+
+/// Recipients of a recurring payment, without amounts or inputs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PaymentTemplate {
+    /// Network the recipients are on
+    pub network: Network,
+    /// Recipients, in the order amounts are given to `instantiate`
+    pub recipients: Vec<TemplateRecipient>,
+    /// Optional address for change (transparent or Orchard)
+    pub change_address: Option<String>,
+}
+
+impl PaymentTemplate {
+    /// Creates a template, rejecting recipients `propose_transaction` would
+    /// reject (bad addresses, wrong network, memos on transparent
+    /// recipients, oversized memos).
+    pub fn new(
+        network: Network,
+        recipients: Vec<TemplateRecipient>,
+        change_address: Option<String>,
+    ) -> Result<Self, T2ZError> {
+        let template = PaymentTemplate {
+            network,
+            recipients,
+            change_address,
+        };
+
+        // Amounts are checked per cycle; only recipient problems matter here
+        let request = template.request(&vec![0; template.recipients.len()])?;
+        if let Some(issue) = validate_request(&request, network)
+            .into_iter()
+            .find(|issue| issue.severity == IssueSeverity::Error)
+        {
+            return Err(T2ZError::InvalidInput(format!(
+                "Invalid payment template: {}",
+                issue.message
+            )));
+        }
+
+        Ok(template)
+    }
+
+    /// The transaction request for one cycle, with `amounts[i]` paid to
+    /// recipient `i`.
+    pub fn request(&self, amounts: &[u64]) -> Result<TransactionRequest, T2ZError> {
+        if amounts.len() != self.recipients.len() {
+            return Err(T2ZError::InvalidInput(format!(
+                "Template has {} recipients but {} amounts were given",
+                self.recipients.len(),
+                amounts.len()
+            )));
+        }
+
+        Ok(TransactionRequest {
+            payments: self
+                .recipients
+                .iter()
+                .zip(amounts)
+                .map(|(recipient, &amount)| Payment {
+                    address: recipient.address.clone(),
+                    amount,
+                    memo: recipient.memo.clone(),
+                    label: recipient.label.clone(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Proposes one cycle of a payment template.
+///
+/// # Arguments
+/// * `template` - Recipients, network, and change address
+/// * `amounts` - Amount in zatoshis for each recipient, in order
+/// * `transparent_inputs` - UTXOs to spend this cycle
+/// * `options` - Fee, expiry, padding, change, and policy options
+pub fn instantiate<FR>(
+    template: &PaymentTemplate,
+    amounts: &[u64],
+    transparent_inputs: &[TransparentInput],
+    options: ProposalOptions<'_, FR>,
+) -> Result<Pczt, T2ZError>
+where
+    FR: FeeRule,
+    FR::Error: std::fmt::Debug,
+{
+    propose_transaction_with_options(
+        transparent_inputs,
+        template.request(amounts)?,
+        template.change_address.as_deref(),
+        template.network,
+        options,
+    )
+}
+
+/// Shields coinbase UTXOs to pool payout addresses in one transaction.
+///
+/// Everything the inputs hold, less the ZIP 317 fee, is split across
+/// `payout_splits` in proportion to their weights (e.g. shares, or basis
+/// points), so the transaction has no change and no transparent outputs, as
+/// the consensus rules require of coinbase spends. Rounding leftovers go to
+/// the largest fractional shares.
+///
+/// # Arguments
+/// * `coinbase_utxos` - Coinbase UTXOs to shield
+/// * `payout_splits` - Orchard address and weight of each payout
+/// * `network` - Mainnet or Testnet
+/// * `options` - Expiry, padding, confirmation, and policy options; the fee
+///   and change are set here, and every input is marked as coinbase
+pub fn propose_coinbase_shielding(
+    coinbase_utxos: &[TransparentInput],
+    payout_splits: &[(String, u64)],
+    network: Network,
+    options: ProposalOptions<'_>,
+) -> Result<Pczt, T2ZError> {
+    if payout_splits.is_empty() {
+        return Err(T2ZError::InvalidInput("No payout addresses given".to_string()));
+    }
+    if payout_splits.iter().all(|(_, weight)| *weight == 0) {
+        return Err(T2ZError::InvalidInput("Payout weights are all zero".to_string()));
+    }
+
+    // Merge duplicate payout addresses (per `options.duplicate_recipients`)
+    // up front, weights and all, so the fee counts the outputs actually made
+    let payouts = merge_duplicate_payments(
+        TransactionRequest {
+            payments: payout_splits
+                .iter()
+                .map(|(address, weight)| Payment {
+                    address: address.clone(),
+                    amount: *weight,
+                    memo: None,
+                    label: None,
+                })
+                .collect(),
+        },
+        network,
+        options.duplicate_recipients,
+    )?
+    .payments;
+
+    let orchard_actions =
+        orchard_actions_for_outputs(payouts.len()).max(options.min_orchard_actions);
+    let fee = zip317_fee(coinbase_utxos.len(), 0, orchard_actions);
+    let total_input =
+        checked_sum(coinbase_utxos.iter().map(|u| u.value), "Input total")?.into_u64();
+    let payout_total = total_input.checked_sub(fee).ok_or(T2ZError::InsufficientFunds {
+        available: total_input,
+        required: fee,
+        payment: 0,
+        fee,
+    })?;
+
+    let weights: Vec<u64> = payouts.iter().map(|payout| payout.amount).collect();
+    let request = TransactionRequest {
+        payments: payouts
+            .into_iter()
+            .zip(split_proportionally(payout_total, &weights))
+            .map(|(payout, amount)| Payment { amount, ..payout })
+            .collect(),
+    };
+
+    // Keep any confirmation counts the caller gave
+    let mut input_confirmations = options.input_confirmations;
+    input_confirmations.resize(coinbase_utxos.len(), InputConfirmations::default());
+    for input in &mut input_confirmations {
+        input.coinbase = true;
+    }
+
+    propose_transaction_with_options(
+        coinbase_utxos,
+        request,
+        None,
+        network,
+        ProposalOptions {
+            fee_strategy: FeeStrategy::Fixed(fee),
+            change: ChangePolicy::Send,
+            input_confirmations,
+            ..options
+        },
+    )
+}
@@ -5,6 +5,77 @@ use crate::{parse_pczt, serialize_pczt};
 use pczt::roles::creator::Creator;
 use zcash_protocol::consensus::BranchId;
 
+/// A testnet wallet for proposal tests: a transparent key with UTXOs to
+/// spend, and an Orchard address, all derived from one seed byte
+struct TestWallet {
+    keypair: crate::TransparentKeypair,
+    orchard_address: String,
+    seed: u8,
+}
+
+impl TestWallet {
+    fn new(seed: u8) -> Self {
+        use crate::{Network, OrchardAccount, derive_transparent_keypair};
+
+        TestWallet {
+            keypair: derive_transparent_keypair(&[seed; 32], Network::Testnet, 0, 0).unwrap(),
+            orchard_address: OrchardAccount::from_seed(&[seed; 32], Network::Testnet, 0)
+                .unwrap()
+                .address(0, Network::Testnet)
+                .unwrap(),
+            seed,
+        }
+    }
+
+    /// The transparent address, for payments and change
+    fn address(&self) -> &str {
+        &self.keypair.address
+    }
+
+    /// A UTXO of the transparent key, at `index` of a transaction unique to
+    /// the seed
+    fn input(&self, index: u32, value: u64) -> crate::TransparentInput {
+        crate::TransparentInput {
+            pubkey: self.keypair.public_key.to_vec(),
+            prevout_txid: vec![self.seed; 32],
+            prevout_index: index,
+            value,
+            script_pubkey: self.keypair.script_pubkey.clone(),
+            sequence: None,
+        }
+    }
+
+    /// A single UTXO worth `value`
+    fn inputs(&self, value: u64) -> Vec<crate::TransparentInput> {
+        vec![self.input(0, value)]
+    }
+}
+
+/// A request paying `amount` to `address`
+fn pay(address: &str, amount: u64) -> crate::TransactionRequest {
+    crate::TransactionRequest {
+        payments: vec![crate::Payment {
+            address: address.to_string(),
+            amount,
+            memo: None,
+            label: None,
+        }],
+    }
+}
+
+/// A PCZT paying 100,000 zatoshis from `wallet`'s transparent key to its
+/// Orchard address, with transparent change
+fn orchard_payment_pczt(wallet: &TestWallet) -> crate::Pczt {
+    crate::propose_transaction(
+        &wallet.inputs(200_000),
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        crate::Network::Testnet,
+        3_000_000,
+    )
+    .unwrap()
+}
+
 #[test]
 fn test_pczt_basic_roundtrip() {
     let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
@@ -250,3 +321,4339 @@ fn derive_ufvk() {
     ]).unwrap();
     println!("Address: {}", ua.encode(&NetworkType::Test));
 }
+
+#[test]
+fn test_streaming_serialization_matches_buffered() {
+    use crate::{parse_pczt_from_reader, pczt_from_hex, pczt_to_hex, serialize_pczt_into};
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+    let buffered = serialize_pczt(&pczt);
+
+    let mut streamed = Vec::new();
+    serialize_pczt_into(&pczt, &mut streamed).unwrap();
+    assert_eq!(buffered, streamed);
+
+    let parsed = parse_pczt_from_reader(streamed.as_slice()).unwrap();
+    assert_eq!(serialize_pczt(&parsed), buffered);
+
+    let hex_string = pczt_to_hex(&pczt);
+    assert_eq!(hex_string, hex::encode(&buffered));
+    let from_hex = pczt_from_hex(&hex_string).unwrap();
+    assert_eq!(serialize_pczt(&from_hex), buffered);
+
+    assert!(pczt_from_hex(&hex_string[..hex_string.len() - 1]).is_err());
+}
+
+#[test]
+fn test_coordination_session_flow() {
+    use crate::{Participant, Role, SignerContribution, SigningRequest, SigningSession};
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+
+    // Assigning an input that doesn't exist is rejected
+    let bad = vec![Participant {
+        id: "alice".to_string(),
+        role: Role::Signer,
+        input_indices: vec![0],
+    }];
+    assert!(SigningSession::new("s1", &pczt, bad).is_err());
+
+    let participants = vec![
+        Participant {
+            id: "alice".to_string(),
+            role: Role::Signer,
+            input_indices: vec![],
+        },
+        Participant {
+            id: "auditor".to_string(),
+            role: Role::Observer,
+            input_indices: vec![],
+        },
+    ];
+    let mut session = SigningSession::new("s1", &pczt, participants).unwrap();
+    assert!(!session.is_complete());
+
+    let requests = session.signing_requests();
+    assert_eq!(requests.len(), 2);
+    let request = SigningRequest::from_bytes(&requests[0].to_bytes()).unwrap();
+
+    let contribution = SignerContribution::new(&request, &request.pczt().unwrap());
+    session
+        .add_contribution(SignerContribution::from_bytes(&contribution.to_bytes()).unwrap())
+        .unwrap();
+    assert!(session.add_contribution(contribution).is_err());
+
+    let observer = SignerContribution::new(&requests[1], &pczt);
+    assert!(session.add_contribution(observer).is_err());
+
+    assert!(session.is_complete());
+    let combined = session.combine().unwrap();
+    assert_eq!(serialize_pczt(&combined), serialize_pczt(&pczt));
+}
+
+#[test]
+fn test_script_sig_push_data_is_minimal() {
+    use crate::script_sig::push_data;
+
+    let mut script = Vec::new();
+    push_data(&mut script, &[0xab; 72]);
+    assert_eq!(script[0], 72);
+    assert_eq!(script.len(), 73);
+
+    let mut script = Vec::new();
+    push_data(&mut script, &[0xab; 100]);
+    assert_eq!(&script[..2], &[0x4c, 100]);
+
+    let mut script = Vec::new();
+    push_data(&mut script, &[0xab; 300]);
+    assert_eq!(&script[..3], &[0x4d, 0x2c, 0x01]);
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_sapling_params_reject_wrong_hash() {
+    use crate::sapling_params::{SAPLING_SPEND_HASH, verify_sapling_param_hash};
+
+    assert!(verify_sapling_param_hash("sapling-spend.params", b"not params", SAPLING_SPEND_HASH).is_err());
+
+    let hash = hex::encode(blake2b_simd::Params::new().hash_length(64).hash(b"abc").as_bytes());
+    assert!(verify_sapling_param_hash("test", b"abc", &hash).is_ok());
+}
+
+#[test]
+fn test_propose_rejects_expiry_in_past() {
+    use crate::{Network, T2ZError, TransactionRequest, propose_transaction_at_height};
+
+    let request = TransactionRequest { payments: vec![] };
+    let result =
+        propose_transaction_at_height(&[], request.clone(), None, Network::Testnet, 100, Some(100));
+    assert!(matches!(
+        result,
+        Err(T2ZError::ExpiryInPast {
+            expiry_height: 100,
+            current_height: 100
+        })
+    ));
+
+    // No expiry needs a chain height to pick consensus rules
+    let result = propose_transaction_at_height(&[], request, None, Network::Testnet, 0, None);
+    assert!(matches!(result, Err(T2ZError::InvalidInput(_))));
+}
+
+#[test]
+fn test_address_screen_rejects_recipient() {
+    use crate::{AddressRole, Payment, ProposalPolicy, T2ZError, TransactionRequest};
+
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: "tmBlocked".to_string(),
+            amount: 1_000,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    let screen = |address: &str, role: AddressRole| {
+        if role == AddressRole::Recipient && address == "tmBlocked" {
+            Err("sanctioned".to_string())
+        } else {
+            Ok(())
+        }
+    };
+    let policy = ProposalPolicy {
+        address_screen: Some(&screen),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        policy.check_request(&request, None),
+        Err(T2ZError::PolicyViolation(_))
+    ));
+    assert!(ProposalPolicy::default().check_request(&request, None).is_ok());
+}
+
+#[test]
+fn test_spend_limits_check_request() {
+    use crate::{Payment, SpendLimits, T2ZError, TransactionRequest};
+
+    let payment = |address: &str, amount: u64| Payment {
+        address: address.to_string(),
+        amount,
+        memo: None,
+        label: None,
+    };
+    let request = TransactionRequest {
+        payments: vec![payment("tmA", 60_000), payment("tmB", 50_000)],
+    };
+
+    let limits = SpendLimits {
+        max_per_tx: Some(100_000),
+        ..Default::default()
+    };
+    assert!(matches!(
+        limits.check_request(&request),
+        Err(T2ZError::PolicyViolation(_))
+    ));
+
+    let limits = SpendLimits {
+        allowlist: Some(vec!["tmA".to_string()]),
+        ..Default::default()
+    };
+    assert!(limits.check_request(&request).is_err());
+
+    let limits = SpendLimits {
+        max_per_tx: Some(110_000),
+        allowlist: Some(vec!["tmA".to_string(), "tmB".to_string()]),
+        denylist: vec!["tmC".to_string()],
+        ..Default::default()
+    };
+    assert!(limits.check_request(&request).is_ok());
+}
+
+#[test]
+fn test_audit_record_hashes_memos() {
+    use crate::{AuditRecord, Payment, TransactionRequest, export_audit_record};
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: "tmA".to_string(),
+            amount: 1_000,
+            memo: Some(b"invoice 42".to_vec()),
+            label: None,
+        }],
+    };
+
+    let record = export_audit_record(&pczt, &request, 1_700_000_000).unwrap();
+    let memo_hash = record.payments[0].memo_hash.as_ref().unwrap();
+    assert_eq!(memo_hash.len(), 64);
+    assert_ne!(memo_hash, &hex::encode(b"invoice 42"));
+
+    let json = record.to_json();
+    let parsed = AuditRecord::from_json(&json).unwrap();
+    assert_eq!(parsed.timestamp, 1_700_000_000);
+    assert_eq!(parsed.expiry_height, 10_000_000);
+}
+
+#[test]
+fn test_orchard_account_roundtrip() {
+    use crate::{Network, OrchardAccount};
+
+    let account = OrchardAccount::generate();
+    let restored = OrchardAccount::from_spending_key(account.spending_key_bytes()).unwrap();
+
+    assert_eq!(
+        account.address(0, Network::Testnet).unwrap(),
+        restored.address(0, Network::Testnet).unwrap()
+    );
+    assert_ne!(
+        account.address(0, Network::Testnet).unwrap(),
+        account.change_address(Network::Testnet).unwrap()
+    );
+    assert!(account.ufvk(Network::Mainnet).unwrap().starts_with("uview1"));
+
+    let seed = [7u8; 32];
+    let a = OrchardAccount::from_seed(&seed, Network::Mainnet, 0).unwrap();
+    let b = OrchardAccount::from_seed(&seed, Network::Mainnet, 1).unwrap();
+    assert_ne!(a.spending_key_bytes(), b.spending_key_bytes());
+}
+
+#[test]
+fn test_transparent_keypair_derivation() {
+    use crate::{Network, TransparentKeypair, derive_transparent_keypair};
+
+    let seed = [1u8; 32];
+    let first = derive_transparent_keypair(&seed, Network::Testnet, 0, 0).unwrap();
+    let again = derive_transparent_keypair(&seed, Network::Testnet, 0, 0).unwrap();
+    let second = derive_transparent_keypair(&seed, Network::Testnet, 0, 1).unwrap();
+
+    assert_eq!(first.address, again.address);
+    assert_ne!(first.address, second.address);
+    assert!(first.address.starts_with("tm"));
+    assert_eq!(first.script_pubkey.len(), 25);
+
+    let restored = TransparentKeypair::from_secret_key(&first.secret_key, Network::Testnet).unwrap();
+    assert_eq!(restored.public_key, first.public_key);
+    assert_eq!(restored.address, first.address);
+}
+
+#[test]
+fn test_wif_roundtrip() {
+    use crate::{Network, secret_key_from_wif, secret_key_to_wif};
+
+    // Well-known compressed mainnet WIF for the secret key 0x00..01
+    let mut secret_key = [0u8; 32];
+    secret_key[31] = 1;
+    let wif = secret_key_to_wif(&secret_key, Network::Mainnet).unwrap();
+    assert_eq!(wif, "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn");
+
+    let (decoded, network) = secret_key_from_wif(&wif).unwrap();
+    assert_eq!(decoded, secret_key);
+    assert_eq!(network, Network::Mainnet);
+
+    // Uncompressed form of the same key is rejected
+    assert!(secret_key_from_wif("5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf").is_err());
+}
+
+#[test]
+fn test_mnemonic_seed_derivation() {
+    use crate::{MnemonicLanguage, generate_mnemonic, mnemonic_to_seed, validate_mnemonic};
+
+    // BIP 39 reference vector (passphrase "TREZOR")
+    let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    let seed = mnemonic_to_seed(phrase, "TREZOR", MnemonicLanguage::English).unwrap();
+    assert_eq!(
+        hex::encode(seed),
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+    );
+
+    let generated = generate_mnemonic(24, MnemonicLanguage::Spanish).unwrap();
+    assert_eq!(generated.split_whitespace().count(), 24);
+    assert!(validate_mnemonic(&generated, MnemonicLanguage::Spanish).is_ok());
+
+    let bad = phrase.replace("about", "abandon");
+    assert!(validate_mnemonic(&bad, MnemonicLanguage::English).is_err());
+    assert!(generate_mnemonic(13, MnemonicLanguage::English).is_err());
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_account_selects_confirmed_utxos() {
+    use crate::{
+        Account, Network, Payment, T2ZError, TransactionRequest, TransparentInput,
+        derive_transparent_keypair,
+    };
+
+    let keypair = derive_transparent_keypair(&[3u8; 32], Network::Testnet, 0, 0).unwrap();
+    let utxo = |txid_byte: u8, value: u64| TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![txid_byte; 32],
+        prevout_index: 0,
+        value,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    };
+
+    let mut account = Account::new(Network::Testnet);
+    account.add_utxo(utxo(1, 50_000), Some(100));
+    account.add_utxo(utxo(2, 80_000), Some(100));
+    account.add_utxo(utxo(3, 500_000), None);
+
+    let balance = account.balance();
+    assert_eq!(balance.confirmed, 130_000);
+    assert_eq!(balance.unconfirmed, 500_000);
+
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    let selected = account.select_inputs(&request, None, 110, 1).unwrap();
+    assert_eq!(selected.len(), 2);
+    assert_eq!(selected[0].value, 80_000);
+
+    let too_much = TransactionRequest {
+        payments: vec![Payment {
+            amount: 200_000,
+            ..request.payments[0].clone()
+        }],
+    };
+    assert!(matches!(
+        account.select_inputs(&too_much, None, 110, 1),
+        Err(T2ZError::InsufficientFunds { .. })
+    ));
+
+    let mut restored = Account::from_bytes(&account.to_bytes()).unwrap();
+    assert_eq!(restored.balance(), balance);
+
+    // Reorging out block 100 unconfirms what it mined
+    assert_eq!(restored.rollback(99).len(), 2);
+    assert_eq!(restored.balance().confirmed, 0);
+    assert!(restored.spendable_utxos(110, 1).is_empty());
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_note_store_tracks_own_change() {
+    use crate::{
+        Network, NoteStatus, NoteStore, OrchardAccount, Payment, TransactionRequest,
+        TransparentInput, derive_transparent_keypair, propose_transaction,
+    };
+
+    let keypair = derive_transparent_keypair(&[45u8; 32], Network::Testnet, 0, 0).unwrap();
+    let account = OrchardAccount::from_seed(&[45u8; 32], Network::Testnet, 0).unwrap();
+    let recipient = OrchardAccount::from_seed(&[45u8; 32], Network::Testnet, 1)
+        .unwrap()
+        .address(0, Network::Testnet)
+        .unwrap();
+    let inputs = vec![TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![30u8; 32],
+        prevout_index: 0,
+        value: 250_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: recipient,
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let change_address = account.change_address(Network::Testnet).unwrap();
+    let pczt = propose_transaction(
+        &inputs,
+        request,
+        Some(&change_address),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // Only the change is ours; the payment belongs to another account
+    let txid = "ab".repeat(32);
+    let mut store = NoteStore::new();
+    let tracked = store
+        .track_pczt(&pczt, &txid, &account.ufvk(Network::Testnet).unwrap())
+        .unwrap();
+    assert_eq!(tracked.len(), 1);
+    assert_eq!(tracked[0].value, 135_000);
+    assert_eq!(tracked[0].status, NoteStatus::Pending);
+
+    let note = tracked[0].to_orchard_note().unwrap();
+    let cmx = orchard::note::ExtractedNoteCommitment::from(note.commitment()).to_bytes();
+    assert_eq!(cmx, tracked[0].cmx);
+
+    assert!(store.spendable_notes(3_000_000, 1).is_empty());
+    store.mark_confirmed(&txid, 2_999_990);
+    assert_eq!(store.spendable_notes(3_000_000, 10).len(), 1);
+
+    let mut store = NoteStore::from_bytes(&store.to_bytes()).unwrap();
+    assert!(store.mark_spent(&tracked[0].nullifier, "cd"));
+    assert!(store.spendable_notes(3_000_000, 1).is_empty());
+    assert!(!store.mark_spent(&[0u8; 32], "cd"));
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_note_store_maintains_witnesses() {
+    use crate::{
+        Network, NoteStatus, NoteStore, OrchardAccount, Payment, TransactionRequest,
+        TransparentInput, derive_transparent_keypair, propose_transaction,
+    };
+    use incrementalmerkletree::frontier::CommitmentTree;
+    use orchard::note::ExtractedNoteCommitment;
+
+    let keypair = derive_transparent_keypair(&[46u8; 32], Network::Testnet, 0, 0).unwrap();
+    let account = OrchardAccount::from_seed(&[46u8; 32], Network::Testnet, 0).unwrap();
+    let inputs = vec![TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![31u8; 32],
+        prevout_index: 0,
+        value: 250_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let change_address = account.change_address(Network::Testnet).unwrap();
+    let pczt = propose_transaction(
+        &inputs,
+        request,
+        Some(&change_address),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let commitments: Vec<[u8; 32]> = pczt
+        .orchard()
+        .actions()
+        .iter()
+        .map(|action| *action.output().cmx())
+        .collect();
+
+    let mut store = NoteStore::new();
+    let txid = "ef".repeat(32);
+    let tracked = store
+        .track_pczt(&pczt, &txid, &account.ufvk(Network::Testnet).unwrap())
+        .unwrap();
+    assert_eq!(tracked.len(), 1);
+    let nullifier = tracked[0].nullifier;
+    let cmx = Option::from(ExtractedNoteCommitment::from_bytes(&tracked[0].cmx)).unwrap();
+
+    let mut empty_tree = Vec::new();
+    zcash_primitives::merkle_tree::write_commitment_tree(
+        &CommitmentTree::<orchard::tree::MerkleHashOrchard, 32>::empty(),
+        &mut empty_tree,
+    )
+    .unwrap();
+    store.set_chain_state(2_999_980, [80u8; 32], &empty_tree).unwrap();
+    assert!(store.scan_block(2_999_982, [82u8; 32], [81u8; 32], &[]).is_err());
+
+    // The block with our transaction starts the witness and confirms the note
+    let found = store.scan_block(2_999_981, [81u8; 32], [80u8; 32], &commitments).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].status, NoteStatus::Confirmed { height: 2_999_981 });
+
+    // Later blocks move the anchor; the path follows it
+    store.scan_block(2_999_982, [82u8; 32], [81u8; 32], &commitments[..1]).unwrap();
+    let mut store = NoteStore::from_bytes(&store.to_bytes()).unwrap();
+    let path = store.merkle_path(&nullifier).unwrap();
+    assert_eq!(path.root(cmx).to_bytes(), store.anchor().unwrap());
+    assert_eq!(store.chain_height(), Some(2_999_982));
+
+    // A block on another fork is refused; rewinding past the block that
+    // mined the note puts it back to pending and drops its witness
+    assert!(matches!(
+        store.scan_block(2_999_983, [83u8; 32], [99u8; 32], &[]),
+        Err(crate::T2ZError::ChainReorg { height: 2_999_983 })
+    ));
+    let reverted = store.rewind(2_999_980).unwrap();
+    assert_eq!(reverted.len(), 1);
+    assert_eq!(reverted[0].status, NoteStatus::Pending);
+    assert!(store.merkle_path(&nullifier).is_none());
+    assert_eq!(store.chain_height(), Some(2_999_980));
+
+    // The new chain mines it again
+    let found = store.scan_block(2_999_981, [91u8; 32], [80u8; 32], &commitments).unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(store.merkle_path(&nullifier).is_some());
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_wallet_events() {
+    use crate::{
+        Account, Network, NoteStore, OrchardAccount, Outpoint, Payment, TransactionRequest,
+        TransparentInput, WalletEvent, derive_transparent_keypair, propose_transaction,
+    };
+    use std::sync::{Arc, Mutex};
+
+    let keypair = derive_transparent_keypair(&[47u8; 32], Network::Testnet, 0, 0).unwrap();
+    let orchard = OrchardAccount::from_seed(&[47u8; 32], Network::Testnet, 0).unwrap();
+    let input = TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![32u8; 32],
+        prevout_index: 0,
+        value: 250_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    };
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let mut account = Account::new(Network::Testnet);
+    account.subscribe(move |event: &WalletEvent| sink.lock().unwrap().push(event.clone()));
+    let sink = events.clone();
+    let mut store = NoteStore::new();
+    store.subscribe(move |event: &WalletEvent| sink.lock().unwrap().push(event.clone()));
+
+    // Re-reporting a UTXO with its height is not a new UTXO
+    account.add_utxo(input.clone(), None);
+    account.add_utxo(input.clone(), Some(100));
+    assert_eq!(
+        events.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![WalletEvent::UtxoAdded {
+            outpoint: Outpoint {
+                txid: vec![32u8; 32],
+                index: 0,
+            },
+            value: 250_000,
+        }]
+    );
+
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let change_address = orchard.change_address(Network::Testnet).unwrap();
+    let pczt = propose_transaction(
+        &[input.clone()],
+        request,
+        Some(&change_address),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let txid = "ef".repeat(32);
+    account.mark_broadcast(&pczt, &txid);
+    let tracked = store
+        .track_pczt(&pczt, &txid, &orchard.ufvk(Network::Testnet).unwrap())
+        .unwrap();
+    assert_eq!(
+        events.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![WalletEvent::NoteReceived {
+            note: tracked[0].clone()
+        }]
+    );
+
+    // Expiring it releases the UTXO and drops the note; a second report is
+    // a no-op
+    account.mark_failed(&txid);
+    store.mark_failed(&txid);
+    store.mark_failed(&txid);
+    let expired = WalletEvent::TxExpired { txid: txid.clone() };
+    assert_eq!(
+        events.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![expired.clone(), expired]
+    );
+
+    account.mark_broadcast(&pczt, &txid);
+    account.mark_confirmed(&txid, 3_000_001);
+    assert_eq!(
+        events.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![WalletEvent::TxConfirmed {
+            txid,
+            height: Some(3_000_001)
+        }]
+    );
+
+    // Listeners are not serialized
+    let mut restored = Account::from_bytes(&account.to_bytes()).unwrap();
+    restored.add_utxo(
+        TransparentInput {
+            prevout_index: 1,
+            ..input
+        },
+        None,
+    );
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_broadcast_queue_retries_across_endpoints() {
+    use crate::{
+        BroadcastQueue, BroadcastStatus, MemoryStorage, Network, Payment, RetryPolicy,
+        SubmitResult, TransactionRequest, TransparentInput, WalletEvent,
+        derive_transparent_keypair, finalize_and_extract, load_broadcast_queue,
+        propose_transaction, save_broadcast_queue, sign_transparent_input,
+    };
+    use std::sync::{Arc, Mutex};
+
+    let keypair = derive_transparent_keypair(&[48u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![33u8; 32],
+        prevout_index: 0,
+        value: 110_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_000).unwrap();
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+
+    let endpoints = vec!["https://a.example".to_string(), "https://b.example".to_string()];
+    let retry = RetryPolicy {
+        initial_delay_secs: 10,
+        max_delay_secs: 15,
+    };
+    let mut queue = BroadcastQueue::new(endpoints.clone(), retry).unwrap();
+    assert!(BroadcastQueue::new(vec![], retry).is_err());
+
+    let txid = queue.enqueue(&tx_bytes, 1_000).unwrap();
+    assert_eq!(queue.enqueue(&tx_bytes, 1_000).unwrap(), txid);
+    assert_eq!(queue.transactions().count(), 1);
+
+    // A failure fails over to the next endpoint immediately...
+    let attempts = queue.poll(1_000, 2_999_000);
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].endpoint, endpoints[0]);
+    assert_eq!(attempts[0].transaction, tx_bytes);
+    let failed = || SubmitResult::Failed {
+        error: "timeout".to_string(),
+    };
+    queue.report(&txid, failed(), 1_000).unwrap();
+    assert_eq!(queue.poll(1_000, 2_999_000)[0].endpoint, endpoints[1]);
+
+    // ...and a failed round backs off, doubling up to the maximum
+    queue.report(&txid, failed(), 1_000).unwrap();
+    assert!(queue.poll(1_005, 2_999_000).is_empty());
+    assert_eq!(queue.poll(1_010, 2_999_000)[0].endpoint, endpoints[0]);
+    queue.report(&txid, failed(), 1_010).unwrap();
+    assert_eq!(queue.poll(1_010, 2_999_000)[0].endpoint, endpoints[1]);
+    queue.report(&txid, failed(), 1_010).unwrap();
+    assert_eq!(queue.get(&txid).unwrap().next_attempt_at, 1_025);
+    assert_eq!(queue.get(&txid).unwrap().attempts, 4);
+
+    // The queue survives a restart
+    let storage = MemoryStorage::new();
+    save_broadcast_queue(&storage, &queue).unwrap();
+    let mut queue = load_broadcast_queue(&storage).unwrap().unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    queue.subscribe(move |event: &WalletEvent| sink.lock().unwrap().push(event.clone()));
+
+    assert!(queue.poll(1_024, 2_999_000).is_empty());
+    assert_eq!(queue.poll(1_025, 2_999_000).len(), 1);
+    queue.report(&txid, SubmitResult::Accepted, 1_025).unwrap();
+    assert_eq!(
+        queue.get(&txid).unwrap().status,
+        BroadcastStatus::Accepted {
+            endpoint: endpoints[0].clone()
+        }
+    );
+    assert!(queue.poll(2_000, 2_999_000).is_empty());
+
+    assert!(queue.mark_mined(&txid, 2_999_010));
+    assert_eq!(queue.remove_finished().len(), 1);
+    assert_eq!(queue.transactions().count(), 0);
+
+    // Unmined transactions expire at their expiry height
+    queue.enqueue(&tx_bytes, 3_000).unwrap();
+    assert!(queue.poll(3_000, 3_000_000).is_empty());
+    assert_eq!(queue.get(&txid).unwrap().status, BroadcastStatus::Expired);
+    assert!(queue.report(&txid, SubmitResult::Accepted, 3_000).is_ok());
+    assert_eq!(queue.get(&txid).unwrap().status, BroadcastStatus::Expired);
+    assert!(queue.report("00", SubmitResult::Accepted, 3_000).is_err());
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            WalletEvent::TxConfirmed {
+                txid: txid.clone(),
+                height: Some(2_999_010)
+            },
+            WalletEvent::TxExpired { txid },
+        ]
+    );
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_endpoint_pool_failover() {
+    use crate::{EndpointPool, RetryPolicy};
+
+    let urls = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    let retry = RetryPolicy {
+        initial_delay_secs: 10,
+        max_delay_secs: 40,
+    };
+    let mut pool = EndpointPool::new(urls, retry).unwrap();
+    assert!(EndpointPool::new(vec![], retry).is_err());
+    assert_eq!(pool.pick(0), "a");
+
+    // Failing endpoints drop to the back until their delay passes
+    assert!(pool.record_failure("a", "connection refused", 100));
+    assert_eq!(pool.failover_order(100), vec!["b", "c", "a"]);
+    pool.record_failure("b", "timeout", 102);
+    assert_eq!(pool.failover_order(105), vec!["c", "a", "b"]);
+    assert_eq!(pool.pick(110), "a");
+
+    // With every endpoint down, the one back soonest comes first
+    pool.record_failure("c", "timeout", 105);
+    assert_eq!(pool.failover_order(106), vec!["a", "b", "c"]);
+    assert!(!pool.endpoints()[0].is_healthy(106));
+
+    // A lagging chain tip counts as a failure, with a longer delay the
+    // second time in a row
+    assert!(pool.record_tip("c", 2_000_000, 120));
+    assert_eq!(pool.endpoints()[2].consecutive_failures, 0);
+    pool.record_tip("a", 1_999_980, 120);
+    assert_eq!(pool.endpoints()[0].consecutive_failures, 2);
+    assert_eq!(pool.endpoints()[0].retry_at, 140);
+    assert_eq!(pool.pick(130), "b");
+
+    assert!(pool.record_success("a"));
+    assert_eq!(pool.pick(130), "a");
+    assert!(!pool.record_success("d"));
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_socks5_proxy_isolates_requests() {
+    use crate::{EndpointPool, RetryPolicy, Socks5Proxy};
+
+    let mut pool = EndpointPool::new(vec!["a".to_string()], RetryPolicy::default()).unwrap();
+    assert_eq!(pool.proxy_url("t1abc"), None);
+
+    pool.set_proxy(Some(Socks5Proxy::tor()));
+    let first = pool.proxy_url("t1abc").unwrap();
+    assert!(first.starts_with("socks5h://"));
+    assert!(first.ends_with("@127.0.0.1:9050"));
+    assert_eq!(pool.proxy_url("t1abc").unwrap(), first);
+    assert_ne!(pool.proxy_url("t1def").unwrap(), first);
+
+    let shared = Socks5Proxy {
+        address: "10.0.0.1:1080".to_string(),
+        isolate_circuits: false,
+    };
+    assert_eq!(shared.url_for("t1abc"), "socks5h://10.0.0.1:1080");
+    assert_eq!(shared.url_for("t1def"), shared.url_for("t1abc"));
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_fetch_utxos_pages_and_rate_limits() {
+    use crate::{
+        Account, FetchedUtxo, Network, UtxoFetchOptions, derive_transparent_keypair,
+        fetch_utxos_for_addresses,
+    };
+    use std::collections::BTreeMap;
+
+    let keys: Vec<_> = (0..3)
+        .map(|i| derive_transparent_keypair(&[49u8; 32], Network::Testnet, 0, i).unwrap())
+        .collect();
+    let mut addresses: Vec<String> = keys.iter().map(|k| k.address.clone()).collect();
+    addresses.push(keys[0].address.clone());
+    let options = UtxoFetchOptions {
+        batch_size: 2,
+        page_size: 2,
+        requests_per_second: 2,
+    };
+    let mut fetch = fetch_utxos_for_addresses(addresses, Network::Testnet, options).unwrap();
+    let utxo = |key: usize, index: u32, height: u32| FetchedUtxo {
+        address: keys[key].address.clone(),
+        txid: vec![34u8; 32],
+        index,
+        script_pubkey: keys[key].script_pubkey.clone(),
+        value: 10_000,
+        height,
+    };
+
+    // Duplicates are dropped, and three addresses make two batches
+    let first = fetch.next_request(1000).unwrap();
+    assert_eq!(first.addresses.len(), 2);
+    let second = fetch.next_request(1000).unwrap();
+    assert_eq!(second.addresses, vec![keys[2].address.clone()]);
+    assert!(fetch.next_request(1000).is_none());
+
+    // A full page queues the next one from its highest height
+    fetch.record_page(&first, vec![utxo(0, 0, 100), utxo(1, 1, 105)]).unwrap();
+    assert!(fetch.record_page(&second, vec![utxo(0, 2, 100)]).is_err());
+    fetch.record_failure(&second).unwrap();
+    assert_eq!(fetch.next_request_at(1000), Some(1001));
+
+    let retry = fetch.next_request(1001).unwrap();
+    assert_eq!(retry, second);
+    fetch.record_page(&retry, vec![utxo(2, 2, 90)]).unwrap();
+    let next_page = fetch.next_request(1001).unwrap();
+    assert_eq!(next_page.start_height, 105);
+    fetch.record_page(&next_page, vec![utxo(1, 1, 105)]).unwrap();
+    assert!(fetch.is_done());
+    assert_eq!(fetch.utxos().count(), 3);
+    assert_eq!(fetch.total_value().unwrap(), 30_000);
+
+    let mut pubkeys: BTreeMap<String, Vec<u8>> =
+        keys.iter().map(|k| (k.address.clone(), k.public_key.to_vec())).collect();
+    let mut account = Account::new(Network::Testnet);
+    fetch.add_to_account(&mut account, &pubkeys).unwrap();
+    assert_eq!(account.utxos().count(), 3);
+    pubkeys.remove(&keys[2].address);
+    assert!(fetch.to_inputs(&pubkeys).is_err());
+
+    assert!(
+        fetch_utxos_for_addresses(vec!["not an address".to_string()], Network::Testnet, options)
+            .is_err()
+    );
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_memory_storage_persists_sessions() {
+    use crate::{
+        Account, MemoryStorage, Network, Storage, WriteBatch, list_pczt_sessions, load_account,
+        load_pczt, save_account, save_pczt,
+    };
+
+    let storage = MemoryStorage::new();
+
+    let mut batch = WriteBatch::new();
+    batch.put(b"a".to_vec(), b"1".to_vec()).put(b"b".to_vec(), b"2".to_vec());
+    batch.delete(b"a".to_vec());
+    storage.write_batch(batch).unwrap();
+    assert_eq!(storage.get(b"a").unwrap(), None);
+    assert_eq!(storage.get(b"b").unwrap(), Some(b"2".to_vec()));
+
+    save_account(&storage, "main", &Account::new(Network::Testnet)).unwrap();
+    assert!(load_account(&storage, "main").unwrap().is_some());
+    assert!(load_account(&storage, "other").unwrap().is_none());
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+    save_pczt(&storage, "session-1", &pczt).unwrap();
+    assert_eq!(list_pczt_sessions(&storage).unwrap(), vec!["session-1".to_string()]);
+    assert!(load_pczt(&storage, "session-1").unwrap().is_some());
+}
+
+#[test]
+fn test_propose_with_fixed_fee() {
+    use crate::{
+        FeeStrategy, Network, Payment, TransactionRequest, TransparentInput,
+        derive_transparent_keypair, inspect_pczt, propose_transaction_with_fee_strategy,
+    };
+
+    let keypair = derive_transparent_keypair(&[5u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![9u8; 32],
+        prevout_index: 0,
+        value: 100_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 50_000,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    let pczt = propose_transaction_with_fee_strategy(
+        &inputs,
+        request,
+        Some(&keypair.address),
+        Network::Testnet,
+        3_000_000,
+        None,
+        FeeStrategy::fixed(20_000),
+    )
+    .unwrap();
+
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.implied_fee, 20_000);
+}
+
+#[test]
+fn test_tx_builder_multiple_change_outputs() {
+    use crate::{
+        FeeStrategy, Network, TransparentInput, TxBuilder, derive_transparent_keypair,
+        inspect_pczt,
+    };
+
+    let keypair = derive_transparent_keypair(&[6u8; 32], Network::Testnet, 0, 0).unwrap();
+    let change_a = derive_transparent_keypair(&[6u8; 32], Network::Testnet, 0, 1).unwrap();
+    let change_b = derive_transparent_keypair(&[6u8; 32], Network::Testnet, 0, 2).unwrap();
+
+    let mut builder = TxBuilder::new(Network::Testnet, 3_000_000);
+    builder
+        .add_transparent_input(TransparentInput {
+            pubkey: keypair.public_key.to_vec(),
+            prevout_txid: vec![4u8; 32],
+            prevout_index: 1,
+            value: 100_000,
+            script_pubkey: keypair.script_pubkey.clone(),
+            sequence: None,
+        })
+        .unwrap();
+    builder.add_output(&keypair.address, 40_000, None).unwrap();
+
+    // Memos only go to shielded outputs
+    assert!(builder.add_output(&keypair.address, 1, Some(b"hi".to_vec())).is_err());
+
+    // Three transparent outputs: fee depends only on counts, not values
+    builder.add_output(&change_a.address, 0, None).unwrap();
+    builder.add_output(&change_b.address, 0, None).unwrap();
+    let fee = builder.fee(FeeStrategy::zip317()).unwrap();
+
+    let remaining = builder.total_input().unwrap() - 40_000 - fee;
+    let mut builder_balanced = TxBuilder::new(Network::Testnet, 3_000_000);
+    builder_balanced
+        .add_transparent_input(TransparentInput {
+            pubkey: keypair.public_key.to_vec(),
+            prevout_txid: vec![4u8; 32],
+            prevout_index: 1,
+            value: 100_000,
+            script_pubkey: keypair.script_pubkey.clone(),
+            sequence: None,
+        })
+        .unwrap();
+    builder_balanced.add_output(&keypair.address, 40_000, None).unwrap();
+    builder_balanced.add_output(&change_a.address, remaining / 2, None).unwrap();
+    builder_balanced
+        .add_output(&change_b.address, remaining - remaining / 2, None)
+        .unwrap();
+
+    let pczt = builder_balanced.into_pczt(FeeStrategy::zip317()).unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.transparent_outputs.len(), 3);
+    assert_eq!(info.implied_fee, fee);
+}
+
+#[test]
+fn test_proposal_builder_requires_expiry() {
+    use crate::{
+        Network, ProposalBuilder, T2ZError, TransparentInput, derive_transparent_keypair,
+        inspect_pczt,
+    };
+
+    let keypair = derive_transparent_keypair(&[8u8; 32], Network::Testnet, 0, 0).unwrap();
+    let builder = ProposalBuilder::new(Network::Testnet)
+        .add_input(TransparentInput {
+            pubkey: keypair.public_key.to_vec(),
+            prevout_txid: vec![2u8; 32],
+            prevout_index: 0,
+            value: 100_000,
+            script_pubkey: keypair.script_pubkey.clone(),
+            sequence: None,
+        })
+        .pay(keypair.address.clone(), 60_000)
+        .change_to(keypair.address.clone());
+
+    assert!(matches!(builder.propose(), Err(T2ZError::InvalidInput(_))));
+
+    let pczt = builder.expiry(3_000_000).propose().unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.transparent_outputs.len(), 2);
+}
+
+#[test]
+fn test_validate_request_reports_issues() {
+    use crate::{
+        IssueSeverity, Network, Payment, RequestIssueKind, TransactionRequest,
+        derive_transparent_keypair, validate_request,
+    };
+
+    let address = derive_transparent_keypair(&[9u8; 32], Network::Testnet, 0, 0)
+        .unwrap()
+        .address;
+    let payment = |address: &str, amount: u64, memo: Option<Vec<u8>>| Payment {
+        address: address.to_string(),
+        amount,
+        memo,
+        label: None,
+    };
+
+    let request = TransactionRequest {
+        payments: vec![
+            payment("not-an-address", 1_000, None),
+            payment(&address, 1_000, Some(b"hello".to_vec())),
+            payment(&address, 0, None),
+        ],
+    };
+    let issues = validate_request(&request, Network::Testnet);
+    let kinds: Vec<_> = issues
+        .iter()
+        .map(|i| (i.payment_index, i.kind, i.severity))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (Some(0), RequestIssueKind::InvalidAddress, IssueSeverity::Error),
+            (Some(1), RequestIssueKind::MemoOnTransparent, IssueSeverity::Error),
+            (Some(2), RequestIssueKind::ZeroAmount, IssueSeverity::Warning),
+            (Some(2), RequestIssueKind::DuplicateRecipient, IssueSeverity::Warning),
+        ]
+    );
+
+    let request = TransactionRequest {
+        payments: vec![payment(&address, 1_000, None)],
+    };
+    assert!(validate_request(&request, Network::Testnet).is_empty());
+    let issues = validate_request(&request, Network::Mainnet);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, RequestIssueKind::WrongNetwork);
+}
+
+#[test]
+fn test_transparent_memo_rejected_or_redirected() {
+    use crate::{
+        Network, OrchardAccount, ProposalBuilder, T2ZError, TransparentInput,
+        derive_transparent_keypair, inspect_pczt, redirect_transparent_memos,
+    };
+
+    let keypair = derive_transparent_keypair(&[10u8; 32], Network::Testnet, 0, 0).unwrap();
+    let orchard_change = OrchardAccount::from_seed(&[10u8; 32], Network::Testnet, 0)
+        .unwrap()
+        .address(0, Network::Testnet)
+        .unwrap();
+    let builder = ProposalBuilder::new(Network::Testnet)
+        .add_input(TransparentInput {
+            pubkey: keypair.public_key.to_vec(),
+            prevout_txid: vec![3u8; 32],
+            prevout_index: 0,
+            value: 200_000,
+            script_pubkey: keypair.script_pubkey.clone(),
+            sequence: None,
+        })
+        .pay_with_memo(keypair.address.clone(), 50_000, b"invoice 42".to_vec())
+        .change_to(orchard_change.clone())
+        .expiry(3_000_000);
+
+    assert!(matches!(builder.propose(), Err(T2ZError::InvalidMemo(_))));
+
+    let request = redirect_transparent_memos(builder.request(), &orchard_change).unwrap();
+    assert_eq!(request.payments.len(), 2);
+    assert_eq!(request.payments[0].memo, None);
+    assert_eq!(request.payments[1].address, orchard_change);
+    assert_eq!(request.payments[1].amount, 0);
+    assert_eq!(request.payments[1].memo.as_deref(), Some(&b"invoice 42"[..]));
+    assert!(matches!(
+        redirect_transparent_memos(builder.request(), &keypair.address),
+        Err(T2ZError::InvalidAddress(_))
+    ));
+
+    let pczt = builder.redirect_transparent_memos().propose().unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.transparent_outputs.len(), 1);
+}
+
+#[test]
+fn test_amounts_above_max_money_rejected() {
+    use crate::{
+        Network, Payment, RequestIssueKind, T2ZError, TransactionRequest, TransparentInput,
+        derive_transparent_keypair, propose_transaction, validate_request,
+    };
+
+    const MAX_MONEY: u64 = 21_000_000 * 100_000_000;
+
+    let keypair = derive_transparent_keypair(&[11u8; 32], Network::Testnet, 0, 0).unwrap();
+    let input = |index: u32, value: u64| TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![5u8; 32],
+        prevout_index: index,
+        value,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    };
+    let request = |amount: u64| TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    // A single payment above 21M ZEC
+    let result = propose_transaction(
+        &[input(0, 100_000)],
+        request(MAX_MONEY + 1),
+        None,
+        Network::Testnet,
+        3_000_000,
+    );
+    assert!(matches!(result, Err(T2ZError::AmountOutOfRange(_))));
+
+    // Inputs that are each valid but sum past 21M ZEC
+    let result = propose_transaction(
+        &[input(0, MAX_MONEY), input(1, MAX_MONEY)],
+        request(10_000),
+        Some(&keypair.address),
+        Network::Testnet,
+        3_000_000,
+    );
+    assert!(matches!(result, Err(T2ZError::AmountOutOfRange(_))));
+
+    let issues = validate_request(&request(u64::MAX), Network::Testnet);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, RequestIssueKind::AmountTooLarge);
+}
+
+#[test]
+fn test_preview_matches_proposal() {
+    use crate::{
+        Network, OrchardAccount, Payment, T2ZError, TransactionRequest, TransparentInput,
+        derive_transparent_keypair, inspect_pczt, preview_transaction, propose_transaction,
+    };
+
+    let keypair = derive_transparent_keypair(&[12u8; 32], Network::Testnet, 0, 0).unwrap();
+    let orchard = OrchardAccount::from_seed(&[12u8; 32], Network::Testnet, 0)
+        .unwrap()
+        .address(0, Network::Testnet)
+        .unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![6u8; 32],
+        prevout_index: 0,
+        value: 500_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: orchard.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    for change_address in [keypair.address.as_str(), orchard.as_str()] {
+        let preview =
+            preview_transaction(&inputs, &request, Some(change_address), Network::Testnet).unwrap();
+        let pczt = propose_transaction(
+            &inputs,
+            request.clone(),
+            Some(change_address),
+            Network::Testnet,
+            3_000_000,
+        )
+        .unwrap();
+        let info = inspect_pczt(&pczt).unwrap();
+
+        assert_eq!(preview.fee, info.implied_fee);
+        assert_eq!(preview.change, 500_000 - 100_000 - info.implied_fee);
+        assert_eq!(preview.action_count, info.num_orchard_actions);
+        assert!(preview.size > 0);
+    }
+
+    assert!(matches!(
+        preview_transaction(&inputs, &request, None, Network::Testnet),
+        Err(T2ZError::ChangeRequired { .. })
+    ));
+}
+
+#[test]
+fn test_rebuild_with_new_expiry_bumps_fee() {
+    use crate::{
+        Network, Payment, T2ZError, TransactionRequest, TransparentInput,
+        derive_transparent_keypair, inspect_pczt, propose_transaction, rebuild_with_new_expiry,
+    };
+
+    let keypair = derive_transparent_keypair(&[13u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![7u8; 32],
+        prevout_index: 0,
+        value: 300_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let change = Some(keypair.address.as_str());
+
+    let original =
+        propose_transaction(&inputs, request.clone(), change, Network::Testnet, 3_000_000)
+            .unwrap();
+    let original_fee = inspect_pczt(&original).unwrap().implied_fee;
+
+    let replacement =
+        rebuild_with_new_expiry(&request, &inputs, change, Network::Testnet, 3_000_040, 1.5)
+            .unwrap();
+    let info = inspect_pczt(&replacement).unwrap();
+    assert_eq!(info.expiry_height, 3_000_040);
+    assert_eq!(info.implied_fee, original_fee * 3 / 2);
+    assert_eq!(info.transparent_outputs.len(), 2);
+
+    assert!(matches!(
+        rebuild_with_new_expiry(&request, &inputs, change, Network::Testnet, 3_000_040, 0.5),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_expiry_of_pczt_and_transaction() {
+    use crate::{
+        Expiry, Network, Payment, TransactionRequest, TransparentInput, derive_transparent_keypair,
+        finalize_and_extract, propose_transaction, sign_transparent_input,
+    };
+
+    let keypair = derive_transparent_keypair(&[14u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![8u8; 32],
+        prevout_index: 0,
+        value: 110_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_000).unwrap();
+    let expiry = Expiry::of_pczt(&pczt);
+    assert_eq!(expiry.expiry_height, 3_000_000);
+    assert!(!expiry.is_expired(2_999_999));
+    assert!(expiry.is_expired(3_000_000));
+    assert_eq!(expiry.blocks_until_expiry(2_999_990), Some(10));
+    assert_eq!(expiry.blocks_until_expiry(3_000_005), Some(0));
+    assert_eq!(Expiry { expiry_height: 0 }.blocks_until_expiry(5), None);
+
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+    assert_eq!(Expiry::of_transaction(&tx_bytes).unwrap(), expiry);
+}
+
+#[test]
+fn test_consensus_rules_before_broadcast() {
+    use crate::{
+        Network, Payment, TransactionRequest, TransparentInput, TxIssueKind,
+        check_consensus_rules, derive_transparent_keypair, finalize_and_extract,
+        propose_transaction, sign_transparent_input,
+    };
+
+    let keypair = derive_transparent_keypair(&[53u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![38u8; 32],
+        prevout_index: 0,
+        value: 110_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_100).unwrap();
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+
+    let kinds = |height: u32| -> Vec<TxIssueKind> {
+        check_consensus_rules(&tx_bytes, Network::Testnet, height)
+            .unwrap()
+            .into_iter()
+            .map(|issue| issue.kind)
+            .collect()
+    };
+    assert!(kinds(3_000_000).is_empty());
+    // Expiring exactly `TX_EXPIRING_SOON_THRESHOLD` blocks on is still relayed
+    assert!(kinds(3_000_097).is_empty());
+    assert_eq!(kinds(3_000_098), vec![TxIssueKind::ExpiringSoon]);
+    assert_eq!(kinds(3_000_101), vec![TxIssueKind::Expired]);
+    // Before NU6 activated, the transaction's branch did not exist yet
+    assert!(kinds(2_000_000).contains(&TxIssueKind::WrongBranch));
+
+    assert!(check_consensus_rules(&[0u8; 4], Network::Testnet, 3_000_000).is_err());
+}
+
+#[test]
+fn test_dump_sighash_digests_matches_sighash() {
+    use crate::{
+        Network, Payment, TransactionRequest, TransparentInput, derive_transparent_keypair,
+        dump_sighash_digests, get_sighash, propose_transaction,
+    };
+
+    let keypair = derive_transparent_keypair(&[54u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs: Vec<TransparentInput> = (0..2)
+        .map(|i| TransparentInput {
+            pubkey: keypair.public_key.to_vec(),
+            prevout_txid: vec![39u8; 32],
+            prevout_index: i,
+            value: 60_000,
+            script_pubkey: keypair.script_pubkey.clone(),
+            sequence: None,
+        })
+        .collect();
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_000).unwrap();
+
+    let first = dump_sighash_digests(&pczt, 0).unwrap();
+    let second = dump_sighash_digests(&pczt, 1).unwrap();
+    assert_eq!(first.sighash, hex::encode(get_sighash(&pczt, 0).unwrap()));
+    assert_eq!(second.sighash, hex::encode(get_sighash(&pczt, 1).unwrap()));
+
+    // Only the per-input digest (and what commits to it) differs between inputs
+    assert_eq!(first.header_digest, second.header_digest);
+    assert_eq!(first.amounts_digest, second.amounts_digest);
+    assert_ne!(first.txin_sig_digest, second.txin_sig_digest);
+    assert_ne!(first.transparent_sig_digest, second.transparent_sig_digest);
+
+    assert!(dump_sighash_digests(&pczt, 2).is_err());
+}
+
+#[test]
+fn test_transaction_digests_of_extracted_transaction() {
+    use crate::{
+        Network, Payment, TransactionRequest, TransparentInput, compute_auth_digest,
+        compute_transaction_digests, derive_transparent_keypair, finalize_and_extract,
+        propose_transaction, sign_transparent_input,
+    };
+
+    let keypair = derive_transparent_keypair(&[55u8; 32], Network::Testnet, 0, 0).unwrap();
+    let inputs = [TransparentInput {
+        pubkey: keypair.public_key.to_vec(),
+        prevout_txid: vec![40u8; 32],
+        prevout_index: 0,
+        value: 110_000,
+        script_pubkey: keypair.script_pubkey.clone(),
+        sequence: None,
+    }];
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keypair.address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_000).unwrap();
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+
+    let auth_digest = compute_auth_digest(&tx_bytes).unwrap();
+    assert_ne!(auth_digest, [0xFF; 32]);
+
+    let digests = compute_transaction_digests(&tx_bytes).unwrap();
+    let mut displayed = auth_digest;
+    displayed.reverse();
+    assert_eq!(digests.auth_digest, hex::encode(displayed));
+    assert_eq!(digests.txid.len(), 64);
+    assert_ne!(digests.txid, digests.auth_digest);
+
+    assert!(compute_auth_digest(&[0u8; 4]).is_err());
+}
+
+#[test]
+fn test_pczt_status_tracks_mixed_signers() {
+    use crate::{
+        InputScriptKind, Network, Payment, TransactionRequest, TransparentInput,
+        derive_transparent_keypair, pczt_status, propose_transaction, sign_transparent_input,
+        signer_id, update_pczt_shadow,
+    };
+
+    let keys: Vec<_> = [56u8, 57, 58]
+        .iter()
+        .map(|&seed| derive_transparent_keypair(&[seed; 32], Network::Testnet, 0, 0).unwrap())
+        .collect();
+    let ids: Vec<String> = keys.iter().map(|k| signer_id(&k.public_key).unwrap()).collect();
+    let inputs: Vec<TransparentInput> = keys[..2]
+        .iter()
+        .enumerate()
+        .map(|(i, key)| TransparentInput {
+            pubkey: key.public_key.to_vec(),
+            prevout_txid: vec![41u8; 32],
+            prevout_index: i as u32,
+            value: 60_000,
+            script_pubkey: key.script_pubkey.clone(),
+            sequence: None,
+        })
+        .collect();
+    let request = TransactionRequest {
+        payments: vec![Payment {
+            address: keys[2].address.clone(),
+            amount: 100_000,
+            memo: None,
+            label: None,
+        }],
+    };
+    let pczt = propose_transaction(&inputs, request, None, Network::Testnet, 3_000_000).unwrap();
+
+    let status = pczt_status(&pczt).unwrap();
+    assert!(!status.all_inputs_signed);
+    assert_eq!(status.pending.len(), 2);
+    assert_eq!(status.pending[&ids[0]], vec![0]);
+    assert_eq!(status.pending[&ids[1]], vec![1]);
+
+    let pczt = sign_transparent_input(pczt, 0, &keys[0].secret_key).unwrap();
+    let status = pczt_status(&pczt).unwrap();
+    assert!(status.inputs[0].complete);
+    assert_eq!(status.inputs[0].signed_by, vec![ids[0].clone()]);
+    assert_eq!(status.pending.keys().collect::<Vec<_>>(), vec![&ids[1]]);
+
+    // Turn input 1 into a 2-of-3 multisig spend with one cosignature so far
+    let multisig = update_pczt_shadow(pczt.clone(), |shadow| {
+        let input = &mut shadow.transparent.inputs[1];
+        let mut redeem_script = vec![0x52];
+        for key in &keys {
+            redeem_script.push(33);
+            redeem_script.extend_from_slice(&key.public_key);
+        }
+        redeem_script.extend_from_slice(&[0x53, 0xae]);
+        input.redeem_script = Some(redeem_script);
+        input.script_pubkey = [&[0xa9, 0x14][..], &[0u8; 20], &[0x87]].concat();
+        input.partial_signatures.insert(keys[2].public_key, vec![0x30]);
+        Ok(())
+    })
+    .unwrap();
+    let status = pczt_status(&multisig).unwrap();
+    assert_eq!(status.inputs[1].kind, InputScriptKind::P2shMultisig);
+    assert_eq!(status.inputs[1].signatures_required, 2);
+    assert_eq!(status.inputs[1].signed_by, vec![ids[2].clone()]);
+    assert_eq!(status.pending.keys().collect::<Vec<_>>(), vec![&ids[0], &ids[1]]);
+
+    let pczt = sign_transparent_input(pczt, 1, &keys[1].secret_key).unwrap();
+    let status = pczt_status(&pczt).unwrap();
+    assert!(status.all_inputs_signed);
+    assert!(status.pending.is_empty());
+}
+
+#[test]
+fn test_spend_unconfirmed_change_uses_parent_txid() {
+    use crate::{
+        ExpiryPolicy, Network, ParentTransaction, ProposalOptions, compute_transaction_digests,
+        derive_transparent_keypair, finalize_and_extract, inspect_pczt, propose_transaction,
+        sign_transparent_input, spend_unconfirmed_change, unconfirmed_change_inputs,
+    };
+
+    let wallet = TestWallet::new(59);
+    let sender = &wallet.keypair;
+    let recipient = TestWallet::new(60);
+    let parent = propose_transaction(
+        &wallet.inputs(200_000),
+        pay(recipient.address(), 100_000),
+        Some(&sender.address),
+        Network::Testnet,
+        3_000_100,
+    )
+    .unwrap();
+
+    // 1 input, 2 transparent outputs: 10,000 fee
+    let change = unconfirmed_change_inputs(ParentTransaction::Pczt(&parent), &sender.public_key)
+        .unwrap();
+    assert_eq!(change.len(), 1);
+    assert_eq!(change[0].value, 90_000);
+
+    let options = |expiry: u32| {
+        ProposalOptions::with_expiry(ExpiryPolicy::AtHeight(expiry), Some(3_000_000)).unwrap()
+    };
+    // Built before the parent is signed
+    let child = spend_unconfirmed_change(
+        ParentTransaction::Pczt(&parent),
+        &sender.public_key,
+        pay(recipient.address(), 50_000),
+        Some(&sender.address),
+        Network::Testnet,
+        options(3_000_100),
+    )
+    .unwrap();
+
+    let signed = sign_transparent_input(parent.clone(), 0, &sender.secret_key).unwrap();
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+    let parent_txid = compute_transaction_digests(&tx_bytes).unwrap().txid;
+    let child_input = &inspect_pczt(&child).unwrap().transparent_inputs[0];
+    assert_eq!(child_input.prevout_txid, parent_txid);
+    assert_eq!(child_input.prevout_index, change[0].prevout_index);
+
+    // The raw parent gives the same outpoint
+    let from_tx =
+        unconfirmed_change_inputs(ParentTransaction::Transaction(&tx_bytes), &sender.public_key)
+            .unwrap();
+    assert_eq!(from_tx[0].prevout_txid, change[0].prevout_txid);
+
+    // The child may not outlive its parent
+    assert!(
+        spend_unconfirmed_change(
+            ParentTransaction::Pczt(&parent),
+            &sender.public_key,
+            pay(recipient.address(), 50_000),
+            Some(&sender.address),
+            Network::Testnet,
+            options(3_000_101),
+        )
+        .is_err()
+    );
+    let other = derive_transparent_keypair(&[61u8; 32], Network::Testnet, 0, 0).unwrap();
+    assert!(
+        unconfirmed_change_inputs(ParentTransaction::Pczt(&parent), &other.public_key).is_err()
+    );
+}
+
+#[test]
+fn test_amounts_as_strings_in_json() {
+    use crate::{Payment, SpendLimits, amounts_as_strings, with_amounts_as_strings};
+
+    let payment = Payment {
+        address: "t1example".to_string(),
+        amount: 2_100_000_000_000_000,
+        memo: None,
+        label: None,
+    };
+    let json = |value: &Payment| serde_json::to_value(value).unwrap();
+
+    assert!(!amounts_as_strings());
+    assert_eq!(json(&payment)["amount"], 2_100_000_000_000_000u64);
+    let as_string = with_amounts_as_strings(true, || json(&payment));
+    assert_eq!(as_string["amount"], "2100000000000000");
+    assert!(!amounts_as_strings());
+
+    // Either form reads back
+    let parsed: Payment = serde_json::from_value(as_string).unwrap();
+    assert_eq!(parsed.amount, payment.amount);
+    let parsed: Payment = serde_json::from_value(json(&payment)).unwrap();
+    assert_eq!(parsed.amount, payment.amount);
+    assert!(
+        serde_json::from_str::<Payment>(r#"{"address":"t1","amount":"-5","memo":null}"#).is_err()
+    );
+
+    // Optional amounts can still be left out, and binary encodings are unchanged
+    let limits: SpendLimits =
+        serde_json::from_str(r#"{"max_fee":"10000","denylist":[]}"#).unwrap();
+    assert_eq!(limits.max_fee, Some(10_000));
+    assert_eq!(limits.max_per_tx, None);
+    assert_eq!(
+        with_amounts_as_strings(true, || postcard::to_allocvec(&payment).unwrap()),
+        postcard::to_allocvec(&payment).unwrap()
+    );
+}
+
+#[test]
+fn test_propose_with_options() {
+    use zcash_protocol::consensus::BranchId;
+
+    use crate::{
+        ChangePolicy, Network, ProposalOptions, T2ZError, inspect_pczt,
+        propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(15);
+    let change = Some(wallet.address());
+
+    // Relative expiry, branch pinning, and Orchard padding
+    let options = ProposalOptions {
+        consensus_branch_id: Some(u32::from(BranchId::Nu6)),
+        min_orchard_actions: 4,
+        ..ProposalOptions::new(3_000_000)
+    };
+    let pczt = propose_transaction_with_options(
+        &wallet.inputs(500_000),
+        pay(&wallet.orchard_address, 100_000),
+        change,
+        Network::Testnet,
+        options.clone(),
+    )
+    .unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.expiry_height, 3_000_040);
+    assert_eq!(info.num_orchard_actions, 4);
+
+    let pinned_nu5 = ProposalOptions {
+        consensus_branch_id: Some(u32::from(BranchId::Nu5)),
+        ..options
+    };
+    assert!(matches!(
+        propose_transaction_with_options(
+            &wallet.inputs(500_000),
+            pay(&wallet.orchard_address, 100_000),
+            change,
+            Network::Testnet,
+            pinned_nu5,
+        ),
+        Err(T2ZError::InvalidInput(_))
+    ));
+
+    // 500 zatoshis left over after the 10,000 ZIP 317 fee goes to the fee
+    let dust = ProposalOptions {
+        change: ChangePolicy::DustToFee { threshold: 1_000 },
+        ..ProposalOptions::new(3_000_000)
+    };
+    let pczt = propose_transaction_with_options(
+        &wallet.inputs(110_500),
+        pay(wallet.address(), 100_000),
+        change,
+        Network::Testnet,
+        dust,
+    )
+    .unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.transparent_outputs.len(), 1);
+    assert_eq!(info.implied_fee, 10_500);
+}
+
+#[test]
+fn test_min_confirmations_gate_inputs() {
+    use crate::{
+        ConfirmationPolicy, InputConfirmations, Network, ProposalOptions, T2ZError,
+        propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(50);
+    let inputs = [wallet.input(0, 100_000), wallet.input(1, 100_000)];
+    let propose = |input_confirmations: Vec<InputConfirmations>| {
+        propose_transaction_with_options(
+            &inputs,
+            pay(wallet.address(), 150_000),
+            Some(wallet.address()),
+            Network::Testnet,
+            ProposalOptions {
+                confirmations: Some(ConfirmationPolicy::default()),
+                input_confirmations,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    // Mined at the tip is one confirmation; mempool-only is none
+    let tip = InputConfirmations::at_height(Some(3_000_000), 3_000_000, false);
+    let mempool = InputConfirmations::at_height(None, 3_000_000, false);
+    assert_eq!(tip.confirmations, 1);
+    assert!(propose(vec![tip, tip]).is_ok());
+    assert!(matches!(
+        propose(vec![tip, mempool]),
+        Err(T2ZError::InsufficientConfirmations { index: 1, confirmations: 0, required: 1 })
+    ));
+
+    // Coinbase outputs need 100 confirmations whatever the policy says
+    let young_coinbase = InputConfirmations::at_height(Some(2_999_950), 3_000_000, true);
+    assert!(matches!(
+        propose(vec![young_coinbase, tip]),
+        Err(T2ZError::InsufficientConfirmations { index: 0, required: 100, .. })
+    ));
+    let mature_coinbase = InputConfirmations::at_height(Some(2_999_901), 3_000_000, true);
+    let policy = ConfirmationPolicy::default();
+    assert!(policy.check(2, &[mature_coinbase, tip]).is_ok());
+
+    // Confirmations must be given for every input
+    assert!(matches!(propose(vec![tip]), Err(T2ZError::InvalidInput(_))));
+}
+
+#[test]
+fn test_coinbase_inputs_are_fully_shielded() {
+    use crate::{
+        ChangePolicy, InputConfirmations, Network, ProposalOptions, T2ZError, inspect_pczt,
+        propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(51);
+    let orchard = wallet.orchard_address.clone();
+    let coinbase = || ProposalOptions {
+        input_confirmations: vec![InputConfirmations {
+            confirmations: 200,
+            coinbase: true,
+        }],
+        ..ProposalOptions::new(3_000_000)
+    };
+    let propose = |value: u64, address: &str, options: ProposalOptions<'static>| {
+        propose_transaction_with_options(
+            &wallet.inputs(value),
+            pay(address, 100_000),
+            Some(wallet.address()),
+            Network::Testnet,
+            options,
+        )
+    };
+
+    // Even a few thousand zatoshis of transparent change are refused...
+    assert!(matches!(
+        propose(119_000, &orchard, coinbase()),
+        Err(T2ZError::InvalidInput(_))
+    ));
+
+    // ...unless the caller asks for them to go to the fee
+    let dust_to_fee = ProposalOptions {
+        change: ChangePolicy::DustToFee { threshold: 5_000 },
+        ..coinbase()
+    };
+    let pczt = propose(119_000, &orchard, dust_to_fee).unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert!(info.transparent_outputs.is_empty());
+    assert_eq!(info.implied_fee, 19_000);
+
+    // Without the coinbase flag, the same change is sent
+    let plain = ProposalOptions {
+        input_confirmations: vec![],
+        ..coinbase()
+    };
+    let pczt = propose(119_000, &orchard, plain).unwrap();
+    assert_eq!(inspect_pczt(&pczt).unwrap().transparent_outputs.len(), 1);
+
+    // Real change needs an Orchard change address
+    assert!(matches!(propose(500_000, &orchard, coinbase()), Err(T2ZError::InvalidInput(_))));
+
+    // Transparent payments and split change are refused outright
+    assert!(matches!(
+        propose(500_000, wallet.address(), coinbase()),
+        Err(T2ZError::InvalidInput(_))
+    ));
+    let split = ProposalOptions {
+        change: ChangePolicy::SplitTransparent {
+            transparent_address: wallet.address().to_string(),
+            transparent_amount: 10_000,
+        },
+        ..coinbase()
+    };
+    assert!(matches!(propose(500_000, &orchard, split), Err(T2ZError::InvalidInput(_))));
+}
+
+#[test]
+fn test_in_place_signing_matches_consuming_signing() {
+    use crate::{
+        Network, derive_transparent_keypair, propose_transaction, sign_transparent_input,
+        sign_transparent_input_in_place,
+    };
+
+    let wallet = TestWallet::new(16);
+    let keypair = &wallet.keypair;
+    let mut pczt = propose_transaction(
+        &wallet.inputs(110_000),
+        pay(wallet.address(), 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let unsigned = serialize_pczt(&pczt);
+
+    // Failures leave the PCZT untouched
+    let other = derive_transparent_keypair(&[17u8; 32], Network::Testnet, 0, 0).unwrap();
+    assert!(sign_transparent_input_in_place(&mut pczt, 0, &other.secret_key).is_err());
+    assert!(sign_transparent_input_in_place(&mut pczt, 1, &keypair.secret_key).is_err());
+    assert_eq!(serialize_pczt(&pczt), unsigned);
+
+    let expected = sign_transparent_input(pczt.clone(), 0, &keypair.secret_key).unwrap();
+    sign_transparent_input_in_place(&mut pczt, 0, &keypair.secret_key).unwrap();
+    assert_eq!(serialize_pczt(&pczt), serialize_pczt(&expected));
+}
+
+#[test]
+fn test_sign_transparent_inputs_with_external_signer() {
+    use crate::{
+        Network, propose_transaction, sign_transparent_input, sign_transparent_inputs_with,
+    };
+
+    let wallet = TestWallet::new(18);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &wallet.inputs(110_000),
+        pay(wallet.address(), 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let secp = secp256k1::Secp256k1::signing_only();
+    let secret_key = secp256k1::SecretKey::from_slice(&keypair.secret_key).unwrap();
+    let mut calls = Vec::new();
+    let signed =
+        sign_transparent_inputs_with(pczt.clone(), &[keypair.public_key], |i, sighash, pk| {
+            calls.push((i, *pk));
+            let message = secp256k1::Message::from_digest(*sighash);
+            let mut signature = secp.sign_ecdsa(&message, &secret_key).serialize_der().to_vec();
+            signature.push(0x01); // SIGHASH_ALL
+            Ok(signature)
+        })
+        .unwrap();
+    assert_eq!(calls, vec![(0, keypair.public_key)]);
+
+    let expected = sign_transparent_input(pczt.clone(), 0, &keypair.secret_key).unwrap();
+    assert_eq!(serialize_pczt(&signed), serialize_pczt(&expected));
+
+    // A wrong signature or key count is rejected
+    assert!(sign_transparent_inputs_with(pczt.clone(), &[], |_, _, _| Ok(vec![])).is_err());
+    assert!(
+        sign_transparent_inputs_with(pczt, &[keypair.public_key], |_, _, _| Ok(vec![0x30, 0x01]))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_signing_package_roundtrip() {
+    use crate::{
+        Network, PackageSignature, SigningPackage, SigningPackageResponse, export_signing_package,
+        import_signatures, propose_transaction, sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(19);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(3, 110_000)],
+        pay(wallet.address(), 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let package = export_signing_package(&pczt).unwrap();
+    let package = SigningPackage::from_bytes(&package.to_bytes()).unwrap();
+    assert_eq!(package.expiry_height, 3_000_000);
+    assert_eq!(package.inputs.len(), 1);
+    assert_eq!(package.inputs[0].prevout_txid, hex::encode([19u8; 32]));
+    assert_eq!(package.inputs[0].prevout_index, 3);
+    assert_eq!(package.inputs[0].value, 110_000);
+
+    // The offline device signs the sighash from the package alone
+    let sighash: [u8; 32] = hex::decode(&package.inputs[0].sighash).unwrap().try_into().unwrap();
+    let secret_key = secp256k1::SecretKey::from_slice(&keypair.secret_key).unwrap();
+    let message = secp256k1::Message::from_digest(sighash);
+    let mut signature = secp256k1::Secp256k1::signing_only()
+        .sign_ecdsa(&message, &secret_key)
+        .serialize_der()
+        .to_vec();
+    signature.push(0x01);
+
+    let response = SigningPackageResponse {
+        signatures: vec![PackageSignature {
+            input_index: 0,
+            pubkey: hex::encode(keypair.public_key),
+            signature: hex::encode(&signature),
+        }],
+    };
+    let response = SigningPackageResponse::from_bytes(&response.to_bytes()).unwrap();
+
+    let signed = import_signatures(pczt.clone(), &response).unwrap();
+    let expected = sign_transparent_input(pczt.clone(), 0, &keypair.secret_key).unwrap();
+    assert_eq!(serialize_pczt(&signed), serialize_pczt(&expected));
+
+    // A signature placed on the wrong input is rejected
+    let mut tampered = response.clone();
+    tampered.signatures[0].input_index = 1;
+    assert!(import_signatures(pczt, &tampered).is_err());
+}
+
+#[test]
+fn test_detached_proof_request_strips_transparent_data() {
+    use crate::shadow::PcztShadow;
+    use crate::{export_proof_request, import_proof};
+
+    let pczt = orchard_payment_pczt(&TestWallet::new(20));
+
+    let request = parse_pczt(&export_proof_request(&pczt).unwrap()).unwrap();
+    assert!(request.transparent().inputs().is_empty());
+    assert!(request.transparent().outputs().is_empty());
+    let num_actions = pczt.orchard().actions().len();
+    assert_eq!(request.orchard().actions().len(), num_actions);
+
+    // Proofs of the wrong size are rejected; the right size is merged as-is
+    assert!(import_proof(pczt.clone(), &[0u8; 100]).is_err());
+    let proof = vec![7u8; 2720 + 2272 * num_actions];
+    let proved = import_proof(pczt, &proof).unwrap();
+    let (_, shadow) = PcztShadow::from_pczt(&proved).unwrap();
+    assert_eq!(shadow.orchard.zkproof, Some(proof));
+}
+
+#[cfg(feature = "verifier")]
+#[test]
+fn test_verify_orchard_proof_rejects_missing_and_bogus_proofs() {
+    use crate::{import_proof, verify_orchard_proof};
+
+    let pczt = orchard_payment_pczt(&TestWallet::new(22));
+    assert!(verify_orchard_proof(&pczt).is_err());
+
+    let num_actions = pczt.orchard().actions().len();
+    let bogus = import_proof(pczt, &vec![0u8; 2720 + 2272 * num_actions]).unwrap();
+    assert!(verify_orchard_proof(&bogus).is_err());
+}
+
+#[test]
+fn test_payment_template_instantiates_each_cycle() {
+    use crate::{
+        Network, PaymentTemplate, ProposalOptions, TemplateRecipient, inspect_pczt, instantiate,
+    };
+
+    let wallet = TestWallet::new(24);
+    let keypair = &wallet.keypair;
+    let employee = wallet.orchard_address.clone();
+
+    // Memos can't go to transparent recipients, so the template is rejected up front
+    assert!(
+        PaymentTemplate::new(
+            Network::Testnet,
+            vec![TemplateRecipient {
+                address: keypair.address.clone(),
+                memo: Some(b"salary".to_vec()),
+                label: None,
+            }],
+            None,
+        )
+        .is_err()
+    );
+
+    let template = PaymentTemplate::new(
+        Network::Testnet,
+        vec![
+            TemplateRecipient {
+                address: employee,
+                memo: Some(b"salary".to_vec()),
+                label: Some("Alice".to_string()),
+            },
+            TemplateRecipient {
+                address: keypair.address.clone(),
+                memo: None,
+                label: None,
+            },
+        ],
+        Some(keypair.address.clone()),
+    )
+    .unwrap();
+
+    let inputs = wallet.inputs(1_000_000);
+    let options = || ProposalOptions::new(3_000_000);
+
+    assert!(instantiate(&template, &[100_000], &inputs, options()).is_err());
+
+    // Each cycle proposes with that cycle's amounts
+    for amounts in [[100_000, 50_000], [120_000, 60_000]] {
+        let pczt = instantiate(&template, &amounts, &inputs, options()).unwrap();
+        let info = inspect_pczt(&pczt).unwrap();
+        assert_eq!(info.expiry_height, 3_000_040);
+        assert!(info.orchard_outputs.iter().any(|o| o.value == Some(amounts[0])));
+        assert!(info.transparent_outputs.iter().any(|o| o.value == amounts[1]));
+    }
+}
+
+#[test]
+fn test_coinbase_shielding_splits_payouts() {
+    use crate::{
+        DuplicateRecipients, Network, OrchardAccount, ProposalOptions, inspect_pczt,
+        propose_coinbase_shielding,
+    };
+
+    let wallet = TestWallet::new(52);
+    let pool = OrchardAccount::from_seed(&[52u8; 32], Network::Testnet, 0).unwrap();
+    let payouts = vec![
+        (pool.address(0, Network::Testnet).unwrap(), 3),
+        (pool.address(1, Network::Testnet).unwrap(), 1),
+    ];
+    let coinbase = wallet.inputs(1_015_000);
+    let options = || ProposalOptions::new(3_000_000);
+
+    // One input and two Orchard actions cost 15,000; the rest splits 3:1
+    let pczt =
+        propose_coinbase_shielding(&coinbase, &payouts, Network::Testnet, options()).unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert!(info.transparent_outputs.is_empty());
+    assert_eq!(info.implied_fee, 15_000);
+    let mut values: Vec<u64> = info.orchard_outputs.iter().filter_map(|o| o.value).collect();
+    values.sort();
+    assert_eq!(values, vec![250_000, 750_000]);
+
+    // Merged payouts to the same address pay the fee of one output
+    let duplicates = vec![
+        (pool.address(0, Network::Testnet).unwrap(), 2),
+        (pool.address(1, Network::Testnet).unwrap(), 1),
+        (pool.address(0, Network::Testnet).unwrap(), 1),
+    ];
+    let merge = ProposalOptions {
+        duplicate_recipients: DuplicateRecipients::Merge,
+        ..options()
+    };
+    let pczt = propose_coinbase_shielding(&coinbase, &duplicates, Network::Testnet, merge).unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.implied_fee, 15_000);
+    let mut values: Vec<u64> = info
+        .orchard_outputs
+        .iter()
+        .filter_map(|o| o.value)
+        .collect();
+    values.sort();
+    assert_eq!(values, vec![250_000, 750_000]);
+
+    // Transparent payout addresses break the coinbase rules
+    let transparent = vec![(wallet.address().to_string(), 1)];
+    let propose = |payouts: &[(String, u64)]| {
+        propose_coinbase_shielding(&coinbase, payouts, Network::Testnet, options())
+    };
+    assert!(propose(&transparent).is_err());
+    assert!(propose(&[]).is_err());
+}
+
+#[test]
+fn test_proposal_sets_output_user_addresses() {
+    use crate::{Network, derive_transparent_keypair, inspect_pczt, propose_transaction};
+
+    let wallet = TestWallet::new(26);
+    let recipient = derive_transparent_keypair(&[26u8; 32], Network::Testnet, 0, 1).unwrap();
+    let mut request = pay(&wallet.orchard_address, 100_000);
+    request.payments[0].label = Some("Alice".to_string());
+    request
+        .payments
+        .extend(pay(&recipient.address, 50_000).payments);
+
+    let pczt = propose_transaction(
+        &wallet.inputs(1_000_000),
+        request,
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+
+    // Labels win; unlabeled payments and change show the address as given
+    let alice = info.orchard_outputs.iter().find(|o| o.value == Some(100_000)).unwrap();
+    assert_eq!(alice.user_address.as_deref(), Some("Alice"));
+    assert_eq!(info.transparent_outputs[0].value, 50_000);
+    assert_eq!(
+        info.transparent_outputs[0].user_address.as_deref(),
+        Some(recipient.address.as_str())
+    );
+    assert_eq!(
+        info.transparent_outputs[1].user_address.as_deref(),
+        Some(wallet.address())
+    );
+}
+
+#[test]
+fn test_duplicate_recipients_policy() {
+    use crate::{
+        DuplicateRecipients, Network, Payment, ProposalOptions, T2ZError, TransactionRequest,
+        inspect_pczt, propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(28);
+    let inputs = wallet.inputs(1_000_000);
+    let payment = |amount: u64, memo: &[u8]| Payment {
+        address: wallet.orchard_address.clone(),
+        amount,
+        memo: Some(memo.to_vec()),
+        label: None,
+    };
+    let request = || TransactionRequest {
+        payments: vec![
+            payment(100_000, b"rent"),
+            payment(50_000, b"rent"),
+            payment(25_000, b"tip"),
+        ],
+    };
+    let propose = |duplicate_recipients| {
+        propose_transaction_with_options(
+            &inputs,
+            request(),
+            Some(wallet.address()),
+            Network::Testnet,
+            ProposalOptions {
+                duplicate_recipients,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    // Kept separate: one action per payment
+    let kept = inspect_pczt(&propose(DuplicateRecipients::Keep).unwrap()).unwrap();
+    assert_eq!(kept.num_orchard_actions, 3);
+    assert_eq!(kept.implied_fee, 20_000);
+
+    assert!(matches!(propose(DuplicateRecipients::Reject), Err(T2ZError::InvalidInput(_))));
+    assert!(matches!(propose(DuplicateRecipients::Merge), Err(T2ZError::InvalidMemo(_))));
+
+    // Merged into one output (padded to the minimum of two actions), saving an action
+    let merged = inspect_pczt(&propose(DuplicateRecipients::MergeConcatMemos).unwrap()).unwrap();
+    assert_eq!(merged.num_orchard_actions, 2);
+    assert_eq!(merged.implied_fee, 15_000);
+    assert!(merged.orchard_outputs.iter().any(|o| o.value == Some(175_000)));
+}
+
+#[test]
+fn test_output_limits_report_transactions_needed() {
+    use crate::{
+        Network, OrchardAccount, OutputLimits, ProposalOptions, T2ZError, TransactionRequest,
+        propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(30);
+    let account = OrchardAccount::from_seed(&[30u8; 32], Network::Testnet, 0).unwrap();
+    let inputs = wallet.inputs(10_000_000);
+    let request = || TransactionRequest {
+        payments: (0..5)
+            .flat_map(|i| pay(&account.address(i, Network::Testnet).unwrap(), 10_000).payments)
+            .collect(),
+    };
+    let propose = |max_orchard_outputs| {
+        propose_transaction_with_options(
+            &inputs,
+            request(),
+            Some(wallet.address()),
+            Network::Testnet,
+            ProposalOptions {
+                output_limits: OutputLimits {
+                    max_orchard_outputs,
+                    ..Default::default()
+                },
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    assert!(propose(5).is_ok());
+    assert!(matches!(
+        propose(2),
+        Err(T2ZError::TooManyOutputs {
+            orchard_outputs: 5,
+            transparent_outputs: 1,
+            transactions_needed: 3,
+        })
+    ));
+}
+
+#[test]
+fn test_split_change_between_transparent_and_orchard() {
+    use crate::{
+        ChangePolicy, Network, OrchardAccount, ProposalOptions, T2ZError, inspect_pczt,
+        propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(32);
+    let account = OrchardAccount::from_seed(&[32u8; 32], Network::Testnet, 0).unwrap();
+    let orchard_change = account.address(1, Network::Testnet).unwrap();
+    let inputs = wallet.inputs(1_000_000);
+    let propose = |transparent_amount| {
+        propose_transaction_with_options(
+            &inputs,
+            pay(&wallet.orchard_address, 100_000),
+            Some(&orchard_change),
+            Network::Testnet,
+            ProposalOptions {
+                change: ChangePolicy::SplitTransparent {
+                    transparent_address: wallet.address().to_string(),
+                    transparent_amount,
+                },
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    // One transparent output and two Orchard actions: 3 logical actions
+    let info = inspect_pczt(&propose(200_000).unwrap()).unwrap();
+    assert_eq!(info.implied_fee, 15_000);
+    assert_eq!(info.transparent_outputs.len(), 1);
+    assert_eq!(info.transparent_outputs[0].value, 200_000);
+    assert!(info.orchard_outputs.iter().any(|o| o.value == Some(685_000)));
+
+    assert!(matches!(propose(990_000), Err(T2ZError::InsufficientFunds { .. })));
+}
+
+#[test]
+fn test_auto_shield_change_policy() {
+    use crate::{
+        ChangePolicy, Network, OrchardAccount, Ownership, ProposalOptions,
+        derive_transparent_keypair, identify_own_outputs, propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(34);
+    let recipient = derive_transparent_keypair(&[34u8; 32], Network::Testnet, 0, 1).unwrap();
+    let ufvk = OrchardAccount::from_seed(&[34u8; 32], Network::Testnet, 0)
+        .unwrap()
+        .ufvk(Network::Testnet)
+        .unwrap();
+
+    let inputs = wallet.inputs(1_000_000);
+    let propose = |change_address: Option<&str>| {
+        propose_transaction_with_options(
+            &inputs,
+            pay(&recipient.address, 100_000),
+            change_address,
+            Network::Testnet,
+            ProposalOptions {
+                change: ChangePolicy::AutoShield { ufvk: ufvk.clone() },
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    assert!(propose(Some(wallet.address())).is_err());
+
+    // The change is shielded to an internal address of the UFVK
+    let pczt = propose(None).unwrap();
+    let change: Vec<_> = identify_own_outputs(&pczt, &ufvk)
+        .unwrap()
+        .into_iter()
+        .filter(|o| o.ownership == Ownership::Change)
+        .collect();
+    assert_eq!(change.len(), 1);
+    assert!(change[0].value.unwrap() > 800_000);
+}
+
+#[test]
+fn test_relay_clients_merge_each_others_signatures() {
+    use crate::{
+        Network, RelayClient, RelayMessage, inspect_pczt, propose_transaction,
+        sign_transparent_input,
+    };
+
+    let alice = TestWallet::new(36);
+    let bob = TestWallet::new(37);
+    let pczt = propose_transaction(
+        &[alice.input(0, 60_000), bob.input(0, 60_000)],
+        pay(alice.address(), 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let mut alice_client = RelayClient::new("s1", "alice", pczt.clone());
+    let mut bob_client = RelayClient::new("s1", "bob", pczt.clone());
+    assert!(matches!(
+        RelayMessage::from_bytes(&alice_client.join_message()).unwrap(),
+        RelayMessage::Join { .. }
+    ));
+
+    // A forwarding relay turns each Publish into an Update for the others
+    let relay = |from: &str, message: Vec<u8>| match RelayMessage::from_bytes(&message).unwrap() {
+        RelayMessage::Publish { pczt } => RelayMessage::Update {
+            from: Some(from.to_string()),
+            pczt,
+        }
+        .to_bytes(),
+        _ => panic!("expected Publish"),
+    };
+
+    let signed = sign_transparent_input(pczt.clone(), 0, &alice.keypair.secret_key).unwrap();
+    let message = alice_client.publish(signed).unwrap();
+    bob_client.receive(&relay("alice", message)).unwrap();
+
+    let signed =
+        sign_transparent_input(bob_client.pczt().clone(), 1, &bob.keypair.secret_key).unwrap();
+    let message = bob_client.publish(signed).unwrap();
+    alice_client.receive(&relay("bob", message)).unwrap();
+
+    assert!(inspect_pczt(alice_client.pczt()).unwrap().all_inputs_signed);
+    assert!(inspect_pczt(bob_client.pczt()).unwrap().all_inputs_signed);
+
+    let error = RelayMessage::Error {
+        message: "unknown session".to_string(),
+    };
+    assert!(alice_client.receive(&error.to_bytes()).is_err());
+}
+
+#[test]
+fn test_coordination_messages_are_signed_by_participants() {
+    use crate::{
+        CoordinationMessage, CoordinationPayload, Network, Participant, Role,
+        SignedCoordinationMessage, SigningSession, derive_transparent_keypair, propose_transaction,
+        sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(38);
+    let signer = &wallet.keypair;
+    let coordinator = derive_transparent_keypair(&[38u8; 32], Network::Testnet, 0, 1).unwrap();
+    let pczt = propose_transaction(
+        &wallet.inputs(70_000),
+        pay(&coordinator.address, 50_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let participants = vec![Participant {
+        id: "signer".to_string(),
+        role: Role::Signer,
+        input_indices: vec![0],
+    }];
+
+    // Coordinator -> signer
+    let proposal = CoordinationMessage::new(
+        "s1",
+        "coordinator",
+        CoordinationPayload::Proposal {
+            pczt: pczt.serialize(),
+            participants: participants.clone(),
+        },
+    )
+    .sign(&coordinator.secret_key)
+    .unwrap();
+    let received = SignedCoordinationMessage::from_bytes(&proposal.to_bytes()).unwrap();
+    assert!(received.verify(&signer.public_key).is_err());
+    let proposed = received.verify(&coordinator.public_key).unwrap().pczt().unwrap();
+
+    // Signer -> coordinator
+    let signed = sign_transparent_input(proposed, 0, &signer.secret_key).unwrap();
+    let partial_sig = CoordinationMessage::new(
+        "s1",
+        "signer",
+        CoordinationPayload::PartialSignature {
+            pczt: signed.serialize(),
+        },
+    )
+    .sign(&signer.secret_key)
+    .unwrap();
+
+    // Anything changed in transit breaks the signature
+    let mut tampered = partial_sig.clone();
+    tampered.message.sender = "coordinator".to_string();
+    assert!(tampered.verify(&signer.public_key).is_err());
+
+    let message = partial_sig.verify(&signer.public_key).unwrap();
+    let mut session = SigningSession::new("s1", &pczt, participants).unwrap();
+    session
+        .add_contribution(message.contribution().unwrap())
+        .unwrap();
+    assert!(session.is_complete());
+}
+
+#[test]
+fn test_seeded_proposals_are_reproducible() {
+    use crate::{Network, ProposalOptions, propose_transaction_with_options};
+
+    let wallet = TestWallet::new(39);
+    let orchard = &wallet.orchard_address;
+    let propose = |rng_seed: Option<[u8; 32]>| {
+        propose_transaction_with_options(
+            &wallet.inputs(200_000),
+            pay(orchard, 100_000),
+            Some(orchard),
+            Network::Testnet,
+            ProposalOptions {
+                rng_seed,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+        .unwrap()
+        .serialize()
+    };
+
+    // Same seed, same bytes (dummy actions and their signatures included)
+    assert_eq!(propose(Some([1u8; 32])), propose(Some([1u8; 32])));
+    assert_ne!(propose(Some([1u8; 32])), propose(Some([2u8; 32])));
+    assert_ne!(propose(None), propose(None));
+}
+
+#[test]
+fn test_inspect_pczt_json_is_versioned() {
+    use crate::{
+        Network, PCZT_INFO_JSON_SCHEMA, PCZT_INFO_SCHEMA_VERSION, inspect_pczt_json,
+        propose_transaction,
+    };
+
+    let wallet = TestWallet::new(40);
+    let pczt = propose_transaction(
+        &wallet.inputs(100_000),
+        pay(wallet.address(), 50_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let info: serde_json::Value = serde_json::from_str(&inspect_pczt_json(&pczt).unwrap()).unwrap();
+    let schema: serde_json::Value = serde_json::from_str(PCZT_INFO_JSON_SCHEMA).unwrap();
+    assert_eq!(info["schema_version"], PCZT_INFO_SCHEMA_VERSION);
+    assert_eq!(schema["properties"]["schema_version"]["const"], PCZT_INFO_SCHEMA_VERSION);
+
+    // Every field the schema requires is present, and nothing it doesn't know
+    let required = schema["required"].as_array().unwrap();
+    let fields = info.as_object().unwrap();
+    assert_eq!(required.len(), fields.len());
+    assert!(required.iter().all(|f| fields.contains_key(f.as_str().unwrap())));
+    assert_eq!(info["transparent_outputs"][0]["value"], 50_000);
+}
+
+#[test]
+fn test_format_pczt_verbosity_levels() {
+    use crate::{Network, Verbosity, format_pczt, format_pczt_with_request, propose_transaction};
+
+    let wallet = TestWallet::new(41);
+    let keypair = &wallet.keypair;
+    let mut request = pay(&wallet.orchard_address, 150_000);
+    request.payments[0].memo = Some(b"invoice 42".to_vec());
+    request.payments[0].label = Some("Invoice 42".to_string());
+    let pczt = propose_transaction(
+        &[wallet.input(3, 250_000)],
+        request.clone(),
+        Some(&keypair.address),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let summary = format_pczt(&pczt, Verbosity::Summary).unwrap();
+    assert!(summary.contains("Input total:  0.0025 ZEC (250000 zat)"));
+    assert!(summary.contains("Signatures:   0/1 input(s) signed"));
+    assert!(summary.contains("Proof:        missing"));
+    assert!(!summary.contains("Inputs:"));
+
+    let normal = format_pczt(&pczt, Verbosity::Normal).unwrap();
+    assert!(normal.contains(":3  0.0025 ZEC (250000 zat)  unsigned"));
+    assert!(normal.contains("0.0015 ZEC (150000 zat)  -> Invoice 42"));
+    assert!(normal.contains(&format!("-> {}", keypair.address)));
+    assert!(normal.contains("padding action(s) not shown"));
+    assert!(!normal.contains("memo"));
+
+    // Memos come from the request; Full adds scripts and padding actions
+    let full = format_pczt_with_request(&pczt, &request, Verbosity::Full).unwrap();
+    assert!(full.contains("memo \"invoice 42\""));
+    assert!(full.contains("-> (padding)"));
+    assert!(full.contains(&format!("script {}", hex::encode(&keypair.script_pubkey))));
+
+    assert_eq!("full".parse::<Verbosity>().unwrap(), Verbosity::Full);
+    assert!("loud".parse::<Verbosity>().is_err());
+}
+
+#[test]
+fn test_fee_breakdown_explains_zip317_fee() {
+    use crate::{Network, fee_breakdown, propose_transaction};
+
+    let wallet = TestWallet::new(42);
+    let inputs = (0..3)
+        .map(|index| wallet.input(index, 50_000))
+        .collect::<Vec<_>>();
+    let pczt = propose_transaction(
+        &inputs,
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // 3 inputs vs 1 transparent output, plus 2 Orchard actions (one padding)
+    let breakdown = fee_breakdown(&pczt).unwrap();
+    assert_eq!(breakdown.transparent_inputs, 3);
+    assert_eq!(breakdown.transparent_outputs, 1);
+    assert_eq!(breakdown.orchard_actions, 2);
+    assert_eq!(breakdown.logical_actions, 5);
+    assert_eq!(breakdown.per_action_cost, 5_000);
+    assert_eq!(breakdown.grace_actions, 2);
+    assert_eq!(breakdown.fee, 25_000);
+    assert_eq!(breakdown.fee, breakdown.zip317_fee);
+}
+
+#[test]
+fn test_address_reuse_warnings() {
+    use crate::{AddressHistory, RequestIssueKind, check_address_reuse};
+
+    let wallet = TestWallet::new(43);
+    let inputs = wallet.inputs(250_000);
+    let request = pay(&wallet.orchard_address, 100_000);
+
+    // Change back to the spent address is flagged; a fresh recipient is not
+    let issues = check_address_reuse(&inputs, &request, Some(wallet.address()));
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, RequestIssueKind::PaysInputAddress);
+    assert_eq!(issues[0].payment_index, None);
+
+    // Once recorded, paying the same recipient again is flagged
+    let mut history = AddressHistory::new();
+    assert!(history.check(&inputs, &request, None).is_empty());
+    history.record(&request, None);
+    let history = AddressHistory::from_bytes(&history.to_bytes()).unwrap();
+    let issues = history.check(&inputs, &request, None);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, RequestIssueKind::AddressReused);
+    assert_eq!(issues[0].payment_index, Some(0));
+}
+
+#[test]
+fn test_split_non_round_change() {
+    use crate::{
+        ChangePolicy, ExpiryPolicy, Network, OrchardAccount, ProposalOptions, inspect_pczt,
+        non_round_split_fee, propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(44);
+    let account = OrchardAccount::from_seed(&[44u8; 32], Network::Testnet, 0).unwrap();
+    let change_address = account.address(1, Network::Testnet).unwrap();
+    let inputs = wallet.inputs(250_000);
+    let request = pay(&wallet.orchard_address, 100_000);
+    let options = || ProposalOptions {
+        change: ChangePolicy::SplitNonRound,
+        ..ProposalOptions::with_expiry(ExpiryPolicy::AtHeight(3_000_000), None).unwrap()
+    };
+
+    // A third Orchard output costs one more action
+    let extra_fee =
+        non_round_split_fee(&inputs, &request, &change_address, Network::Testnet).unwrap();
+    assert_eq!(extra_fee, 5_000);
+
+    let pczt = propose_transaction_with_options(
+        &inputs,
+        request.clone(),
+        Some(&change_address),
+        Network::Testnet,
+        options(),
+    )
+    .unwrap();
+    let info = inspect_pczt(&pczt).unwrap();
+    assert_eq!(info.implied_fee, 20_000);
+
+    let change: Vec<u64> = info
+        .orchard_outputs
+        .iter()
+        .filter(|output| output.user_address.as_deref() == Some(change_address.as_str()))
+        .filter_map(|output| output.value)
+        .collect();
+    assert_eq!(change.len(), 2);
+    assert_eq!(change.iter().sum::<u64>(), 130_000);
+    assert!(change.iter().all(|value| value % 1_000 != 0));
+
+    // Split change must go to Orchard
+    let err = propose_transaction_with_options(
+        &inputs,
+        request,
+        Some(wallet.address()),
+        Network::Testnet,
+        options(),
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_no_std_signer_matches_core() {
+    use crate::{
+        Network, get_sighash, pczt_status, propose_transaction, sign_transparent_input,
+        verify_transparent_signature,
+    };
+
+    let wallet = TestWallet::new(62);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 80_000), wallet.input(1, 80_000)],
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // Same sighash from the PCZT layout alone as from zcash_primitives
+    let mut signer_pczt = t2z_signer::Pczt::parse(&serialize_pczt(&pczt)).unwrap();
+    for input_index in 0..2 {
+        assert_eq!(
+            signer_pczt.transparent_sighash(input_index).unwrap(),
+            get_sighash(&pczt, input_index).unwrap()
+        );
+    }
+    assert!(signer_pczt.transparent_sighash(2).is_err());
+
+    // One input signed on the device, the other signed by core and appended
+    signer_pczt
+        .sign_transparent_input(0, &keypair.secret_key)
+        .unwrap();
+    let core_signed = sign_transparent_input(pczt, 1, &keypair.secret_key).unwrap();
+    let (_, core_shadow) = shadow::PcztShadow::from_pczt(&core_signed).unwrap();
+    let signature =
+        core_shadow.transparent.inputs[1].partial_signatures[&keypair.public_key].clone();
+    assert!(
+        signer_pczt
+            .append_signature(0, &keypair.public_key, &signature)
+            .is_err()
+    );
+    signer_pczt
+        .append_signature(1, &keypair.public_key, &signature)
+        .unwrap();
+
+    let signed = parse_pczt(&signer_pczt.serialize().unwrap()).unwrap();
+    let (_, signed_shadow) = shadow::PcztShadow::from_pczt(&signed).unwrap();
+    for (input_index, input) in signed_shadow.transparent.inputs.iter().enumerate() {
+        let signature = &input.partial_signatures[&keypair.public_key];
+        verify_transparent_signature(&signed, input_index, &keypair.public_key, signature).unwrap();
+    }
+    assert!(pczt_status(&signed).unwrap().all_inputs_signed);
+}
+
+#[test]
+fn test_hardened_signing_randomizes_signatures() {
+    use crate::{
+        Network, derive_transparent_keypair, propose_transaction, sign_transparent_input,
+        verify_transparent_signature,
+    };
+
+    let wallet = TestWallet::new(64);
+    let keypair = &wallet.keypair;
+    let other = derive_transparent_keypair(&[65u8; 32], Network::Testnet, 0, 0).unwrap();
+    let pczt = propose_transaction(
+        &wallet.inputs(120_000),
+        pay(&other.address, 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // On-device: different randomness, different (but valid) signatures
+    let signature_with = |randomness: [u8; 64]| {
+        let mut signer_pczt = t2z_signer::Pczt::parse(&serialize_pczt(&pczt)).unwrap();
+        signer_pczt
+            .sign_transparent_input_hardened(0, &keypair.secret_key, &randomness)
+            .unwrap();
+        signer_pczt.transparent.inputs[0].partial_signatures[&keypair.public_key].clone()
+    };
+    let first = signature_with([1u8; 64]);
+    let second = signature_with([2u8; 64]);
+    assert_ne!(first, second);
+    assert_eq!(first, signature_with([1u8; 64]));
+    for signature in [&first, &second] {
+        verify_transparent_signature(&pczt, 0, &keypair.public_key, signature).unwrap();
+    }
+
+    let signed = sign_transparent_input(pczt.clone(), 0, &keypair.secret_key).unwrap();
+    let (_, signed_shadow) = shadow::PcztShadow::from_pczt(&signed).unwrap();
+    let signature = &signed_shadow.transparent.inputs[0].partial_signatures[&keypair.public_key];
+    verify_transparent_signature(&pczt, 0, &keypair.public_key, signature).unwrap();
+
+    // In-process hardened signing refuses keys the input doesn't pay to
+    #[cfg(feature = "hardened-signing")]
+    {
+        let again = sign_transparent_input(pczt.clone(), 0, &keypair.secret_key).unwrap();
+        let (_, again_shadow) = shadow::PcztShadow::from_pczt(&again).unwrap();
+        assert_ne!(
+            &again_shadow.transparent.inputs[0].partial_signatures[&keypair.public_key],
+            signature
+        );
+        assert!(sign_transparent_input(pczt, 0, &other.secret_key).is_err());
+    }
+}
+
+#[test]
+fn test_proof_cache_reuses_proof_after_expiry_bump() {
+    use crate::{ProofCache, import_proof, update_pczt_shadow};
+
+    let wallet = TestWallet::new(66);
+    let propose = || orchard_payment_pczt(&wallet);
+    let pczt = propose();
+
+    // Stand-in for a real proof: the cache only checks the proof's size
+    let proof = vec![9u8; 2720 + 2272 * pczt.orchard().actions().len()];
+    let proved = import_proof(pczt.clone(), &proof).unwrap();
+
+    let cache = ProofCache::new(1);
+    assert!(cache.get(&pczt).unwrap().is_none());
+    cache.insert(&pczt).unwrap();
+    assert!(cache.is_empty());
+    cache.insert(&proved).unwrap();
+    assert_eq!(cache.len(), 1);
+
+    // Bumping the expiry leaves the Orchard bundle, and so the key, alone
+    let bumped = update_pczt_shadow(pczt.clone(), |pczt_shadow| {
+        pczt_shadow.global.expiry_height += 40;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        ProofCache::key(&bumped).unwrap(),
+        ProofCache::key(&pczt).unwrap()
+    );
+    let (reproved, reused) = cache.apply(bumped).unwrap();
+    assert!(reused);
+    assert_eq!(reproved.orchard().zkproof().as_deref(), Some(&proof[..]));
+
+    // A new proposal has new actions; inserting it evicts the old proof
+    let other = propose();
+    assert_ne!(
+        ProofCache::key(&other).unwrap(),
+        ProofCache::key(&pczt).unwrap()
+    );
+    let (other, reused) = cache.apply(other).unwrap();
+    assert!(!reused);
+    cache.insert(&import_proof(other, &proof).unwrap()).unwrap();
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&pczt).unwrap().is_none());
+}
+
+#[test]
+fn test_adjust_transparent_side_keeps_proved_orchard_bundle() {
+    use crate::shadow::PcztShadow;
+    use crate::{
+        FeeStrategy, Network, T2ZError, adjust_transparent_side, import_proof, propose_transaction,
+        sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(68);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 120_000)],
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let proof = vec![9u8; 2720 + 2272 * pczt.orchard().actions().len()];
+    let proved = import_proof(pczt, &proof).unwrap();
+
+    // Input 0 was spent elsewhere; two smaller ones replace it
+    let new_inputs = [wallet.input(1, 60_000), wallet.input(2, 70_000)];
+    let adjusted = adjust_transparent_side(
+        proved.clone(),
+        &new_inputs,
+        Some(&keypair.address),
+        Network::Testnet,
+        &FeeStrategy::zip317(),
+    )
+    .unwrap();
+
+    let (_, before) = PcztShadow::from_pczt(&proved).unwrap();
+    let (_, after) = PcztShadow::from_pczt(&adjusted).unwrap();
+    assert_eq!(after.orchard.zkproof.as_deref(), Some(&proof[..]));
+    assert_eq!(after.orchard.value_sum, before.orchard.value_sum);
+    for (old, new) in before.orchard.actions.iter().zip(&after.orchard.actions) {
+        assert_eq!(new.cv_net, old.cv_net);
+        assert_eq!(new.spend.nullifier, old.spend.nullifier);
+        assert_eq!(new.output.cmx, old.output.cmx);
+        assert!(new.spend.spend_auth_sig.is_some());
+    }
+
+    assert_eq!(after.transparent.inputs[0].prevout_index, 1);
+    assert_eq!(after.transparent.inputs[1].prevout_index, 2);
+    // 130_000 in, 100_000 to Orchard, a 20_000 fee (2 inputs + 2 actions)
+    assert_eq!(after.transparent.outputs.len(), 1);
+    assert_eq!(after.transparent.outputs[0].value, 10_000);
+
+    let signed = sign_transparent_input(adjusted, 0, &keypair.secret_key).unwrap();
+    sign_transparent_input(signed, 1, &keypair.secret_key).unwrap();
+
+    // The Orchard value is fixed, so the inputs must still cover it
+    assert!(matches!(
+        adjust_transparent_side(
+            proved,
+            &[wallet.input(3, 100_000)],
+            None,
+            Network::Testnet,
+            &FeeStrategy::zip317()
+        ),
+        Err(T2ZError::InsufficientFunds { .. })
+    ));
+}
+
+#[test]
+fn test_pczt_version_negotiation() {
+    use crate::{negotiate_pczt_version, parse_pczt_versioned, pczt_version, serialize_pczt_as};
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+    let serialized = serialize_pczt(&pczt);
+    assert_eq!(pczt_version(&serialized).unwrap(), 1);
+
+    let parsed = parse_pczt_versioned(&serialized).unwrap();
+    assert_eq!(parsed.version, 1);
+    assert!(parsed.warning.is_none());
+
+    // A newer version that only appended fields is read, with a warning
+    let mut newer = serialized.clone();
+    newer[4..8].copy_from_slice(&2u32.to_le_bytes());
+    newer.extend_from_slice(&[0, 0]);
+    assert!(parse_pczt(&newer).is_err());
+    let parsed = parse_pczt_versioned(&newer).unwrap();
+    assert_eq!(parsed.version, 2);
+    assert!(parsed.warning.unwrap().contains("version 2"));
+    assert_eq!(serialize_pczt(&parsed.pczt), serialized);
+
+    // Older versions, and newer ones with an incompatible body, are not
+    let mut older = serialized.clone();
+    older[4..8].copy_from_slice(&0u32.to_le_bytes());
+    assert!(parse_pczt_versioned(&older).is_err());
+    let mut truncated = newer.clone();
+    truncated.truncate(20);
+    assert!(parse_pczt_versioned(&truncated).is_err());
+
+    assert_eq!(negotiate_pczt_version(&[1, 2]).unwrap(), 1);
+    assert!(negotiate_pczt_version(&[2, 3]).is_err());
+    assert_eq!(serialize_pczt_as(&pczt, 1).unwrap(), serialized);
+    assert!(serialize_pczt_as(&pczt, 2).is_err());
+}
+
+#[test]
+#[cfg(feature = "interop")]
+fn test_check_interop_on_t2z_proposal() {
+    use crate::{Network, check_interop, propose_transaction, sign_transparent_input};
+
+    let wallet = TestWallet::new(70);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 80_000), wallet.input(1, 80_000)],
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let report = check_interop(&serialize_pczt(&pczt)).unwrap();
+    assert!(report.is_compatible(), "{:?}", report.issues);
+    assert_eq!(report.version, 1);
+    assert!(!report.finalize_checked);
+
+    // Once every input is signed the Spend Finalizer runs too; without a
+    // proof, extraction is skipped
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let signed = sign_transparent_input(signed, 1, &keypair.secret_key).unwrap();
+    let report = check_interop(&serialize_pczt(&signed)).unwrap();
+    assert!(report.is_compatible(), "{:?}", report.issues);
+    assert!(report.finalize_checked);
+    assert!(!report.extract_checked);
+
+    assert!(check_interop(b"PCZT\x01\x00\x00\x00garbage").is_err());
+}
+
+#[test]
+fn test_signing_rules_run_after_verification() {
+    use crate::{
+        ExpectedTxOut, Network, PcztInfo, SigningRules, T2ZError, TransactionRequest,
+        propose_transaction, verify_before_signing_with_rules,
+    };
+
+    let wallet = TestWallet::new(72);
+    let mut request = pay(&wallet.orchard_address, 100_000);
+    request.payments[0].memo = Some(b"invoice INV-0042".to_vec());
+    let pczt = propose_transaction(
+        &wallet.inputs(120_000),
+        request.clone(),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    // 120_000 in, 100_000 out, 15_000 fee
+    let expected_change = [ExpectedTxOut {
+        address: wallet.address().to_string(),
+        amount: 5_000,
+    }];
+
+    let has_invoice_id = |memo: &Option<Vec<u8>>| {
+        memo.as_deref()
+            .is_some_and(|memo| memo.windows(4).any(|w| w == b"INV-"))
+    };
+    let mut rules = SigningRules::new();
+    rules
+        .register(
+            "invoice",
+            move |_: &PcztInfo, request: &TransactionRequest| {
+                if request.payments.iter().all(|p| has_invoice_id(&p.memo)) {
+                    Ok(())
+                } else {
+                    Err("payment memo has no invoice ID".to_string())
+                }
+            },
+        )
+        .register("max-fee", |info: &PcztInfo, _: &TransactionRequest| {
+            if info.implied_fee <= 20_000 {
+                Ok(())
+            } else {
+                Err(format!("fee {} too high", info.implied_fee))
+            }
+        });
+    assert_eq!(rules.len(), 2);
+    verify_before_signing_with_rules(&pczt, &request, &expected_change, &rules).unwrap();
+
+    rules.register("no-orchard", |info: &PcztInfo, _: &TransactionRequest| {
+        if info.orchard_outputs.is_empty() {
+            Ok(())
+        } else {
+            Err("shielding is not allowed".to_string())
+        }
+    });
+    let err =
+        verify_before_signing_with_rules(&pczt, &request, &expected_change, &rules).unwrap_err();
+    assert!(matches!(err, T2ZError::PolicyViolation(_)));
+    assert!(err.to_string().contains("no-orchard"));
+
+    // Rules only run once the standard verification passes
+    let no_change: [ExpectedTxOut; 0] = [];
+    assert!(matches!(
+        verify_before_signing_with_rules(&pczt, &request, &no_change, &rules),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_inspect_pczt_with_ovk_decrypts_outputs() {
+    use crate::{
+        FeeStrategy, Network, OrchardAccount, TxBuilder, inspect_pczt, inspect_pczt_with_ovk,
+    };
+    use orchard::keys::Scope;
+
+    let wallet = TestWallet::new(74);
+    let sender = OrchardAccount::from_seed(&[74u8; 32], Network::Testnet, 0).unwrap();
+    let recipient = OrchardAccount::from_seed(&[75u8; 32], Network::Testnet, 0).unwrap();
+    let ovk = sender.outgoing_viewing_key(Scope::External);
+
+    let memo = b"invoice INV-7".to_vec();
+    let builder = |change: u64| {
+        let mut builder = TxBuilder::new(Network::Testnet, 3_000_000);
+        builder
+            .orchard_ovk(ovk)
+            .add_transparent_input(wallet.input(0, 120_000))
+            .unwrap()
+            .add_output(
+                &recipient.address(0, Network::Testnet).unwrap(),
+                100_000,
+                Some(memo.clone()),
+            )
+            .unwrap()
+            .add_output(wallet.address(), change, None)
+            .unwrap();
+        builder
+    };
+    let fee = builder(0).fee(FeeStrategy::zip317()).unwrap();
+    let pczt = builder(120_000 - 100_000 - fee)
+        .into_pczt(FeeStrategy::zip317())
+        .unwrap();
+
+    let info = inspect_pczt_with_ovk(&pczt, &ovk).unwrap();
+    // The padding action's dummy output is not encrypted to the OVK
+    let decrypted: Vec<_> = info
+        .orchard_outputs
+        .iter()
+        .filter_map(|output| output.decrypted.as_ref())
+        .collect();
+    assert_eq!(decrypted.len(), 1);
+    assert_eq!(decrypted[0].value, 100_000);
+    assert_eq!(
+        decrypted[0].recipient,
+        hex::encode(
+            recipient
+                .orchard_address(0, Scope::External)
+                .to_raw_address_bytes()
+        )
+    );
+    assert_eq!(decrypted[0].memo, Some(hex::encode(&memo)));
+    assert!(decrypted[0].matches_pczt);
+
+    let other_ovk = recipient.outgoing_viewing_key(Scope::External);
+    let info = inspect_pczt_with_ovk(&pczt, &other_ovk).unwrap();
+    assert!(info.orchard_outputs.iter().all(|o| o.decrypted.is_none()));
+    assert!(
+        inspect_pczt(&pczt)
+            .unwrap()
+            .orchard_outputs
+            .iter()
+            .all(|o| o.decrypted.is_none())
+    );
+}
+
+#[test]
+fn test_inspect_pczt_encodes_orchard_recipient_addresses() {
+    use crate::inspect_pczt;
+
+    let wallet = TestWallet::new(76);
+    let pczt = orchard_payment_pczt(&wallet);
+
+    let info = inspect_pczt(&pczt).unwrap();
+    let payment = info
+        .orchard_outputs
+        .iter()
+        .find(|output| output.value == Some(100_000))
+        .unwrap();
+    assert_eq!(payment.recipient_address, Some(wallet.orchard_address));
+    // Padding outputs go to random addresses, which encode all the same
+    for output in &info.orchard_outputs {
+        let address = output.recipient_address.as_ref().unwrap();
+        assert!(address.starts_with("utest1"));
+    }
+}
+
+#[test]
+fn test_sign_metrics_count_inputs() {
+    use crate::{
+        Network, propose_transaction, sign_transparent_input_with_metrics,
+        sign_transparent_inputs_with_metrics,
+    };
+
+    let wallet = TestWallet::new(78);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 70_000), wallet.input(1, 70_000)],
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    let (signed, metrics) =
+        sign_transparent_input_with_metrics(pczt.clone(), 0, &keypair.secret_key).unwrap();
+    assert_eq!(metrics.inputs_signed, 1);
+    assert_eq!(metrics.transparent_inputs, 2);
+    assert!(metrics.wall_time_ms >= 0.0);
+    assert_eq!(
+        signed.transparent().inputs()[0].partial_signatures().len(),
+        1
+    );
+
+    let secret_key = secp256k1::SecretKey::from_slice(&keypair.secret_key).unwrap();
+    let (signed, metrics) =
+        sign_transparent_inputs_with_metrics(pczt, &[keypair.public_key; 2], |_, sighash, _| {
+            let message = secp256k1::Message::from_digest(*sighash);
+            let signature = secp256k1::Secp256k1::new().sign_ecdsa(&message, &secret_key);
+            let mut signature = signature.serialize_der().to_vec();
+            signature.push(0x01);
+            Ok(signature)
+        })
+        .unwrap();
+    assert_eq!(metrics.inputs_signed, 2);
+    assert!(
+        signed
+            .transparent()
+            .inputs()
+            .iter()
+            .all(|input| input.partial_signatures().len() == 1)
+    );
+}
+#[test]
+fn test_address_ownership_proof() {
+    use crate::{
+        Network, OrchardAccount, create_address_ownership_challenge, prove_address_ownership,
+        verify_address_ownership,
+    };
+
+    let owner = OrchardAccount::from_seed(&[80u8; 32], Network::Testnet, 0).unwrap();
+    let other = OrchardAccount::from_seed(&[81u8; 32], Network::Testnet, 0).unwrap();
+    let address = owner.address(0, Network::Testnet).unwrap();
+    let ufvk = owner.ufvk(Network::Testnet).unwrap();
+    let sk_hex = hex::encode(owner.spending_key_bytes());
+
+    let challenge = create_address_ownership_challenge(&address, Network::Testnet).unwrap();
+    for key in [&ufvk, &sk_hex] {
+        let response =
+            prove_address_ownership(key, &address, &challenge.challenge, Network::Testnet).unwrap();
+        assert!(verify_address_ownership(&challenge, &response));
+    }
+
+    // Challenges are fresh, so old responses do not carry over
+    let response =
+        prove_address_ownership(&ufvk, &address, &challenge.challenge, Network::Testnet).unwrap();
+    let next = create_address_ownership_challenge(&address, Network::Testnet).unwrap();
+    assert_ne!(next.challenge, challenge.challenge);
+    assert!(!verify_address_ownership(&next, &response));
+
+    // Another key cannot answer for the address
+    let other_ufvk = other.ufvk(Network::Testnet).unwrap();
+    assert!(
+        prove_address_ownership(
+            &other_ufvk,
+            &address,
+            &challenge.challenge,
+            Network::Testnet
+        )
+        .is_err()
+    );
+}
+#[test]
+fn test_sign_and_verify_message() {
+    use crate::{Network, derive_transparent_keypair, sign_message, verify_message};
+
+    let keypair = derive_transparent_keypair(&[82u8; 32], Network::Testnet, 0, 0).unwrap();
+    let other = derive_transparent_keypair(&[82u8; 32], Network::Testnet, 0, 1).unwrap();
+    let message = b"I control this address";
+
+    let signature = sign_message(&keypair.secret_key, message).unwrap();
+    assert_eq!(
+        base64::Engine::decode(&base64::prelude::BASE64_STANDARD, &signature)
+            .unwrap()
+            .len(),
+        65
+    );
+    assert!(verify_message(&keypair.address, message, &signature).unwrap());
+
+    // The same key's mainnet address verifies too
+    let mainnet =
+        crate::TransparentKeypair::from_secret_key(&keypair.secret_key, Network::Mainnet).unwrap();
+    assert!(verify_message(&mainnet.address, message, &signature).unwrap());
+
+    assert!(!verify_message(&keypair.address, b"Something else", &signature).unwrap());
+    assert!(!verify_message(&other.address, message, &signature).unwrap());
+
+    assert!(verify_message(&keypair.address, message, "not base64!").is_err());
+    assert!(verify_message(&keypair.address, message, "AAAA").is_err());
+}
+#[test]
+fn test_find_invalid_signatures() {
+    use crate::{
+        InvalidSignature, Network, T2ZError, add_signature_via_signer,
+        finalize_and_extract_verified, find_invalid_signatures, get_sighash, propose_transaction,
+        sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(83);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 60_000), wallet.input(1, 60_000)],
+        pay(wallet.address(), 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // Unsigned inputs are left to the Spend Finalizer
+    assert_eq!(find_invalid_signatures(&pczt).unwrap(), vec![]);
+
+    let pczt = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let signed = sign_transparent_input(pczt.clone(), 1, &keypair.secret_key).unwrap();
+    assert_eq!(find_invalid_signatures(&signed).unwrap(), vec![]);
+
+    // Input 0's signature filed under input 1 passes append_signature's
+    // format checks but not verification
+    let secret_key = secp256k1::SecretKey::from_slice(&keypair.secret_key).unwrap();
+    let message = secp256k1::Message::from_digest(get_sighash(&pczt, 0).unwrap());
+    let mut signature = secp256k1::Secp256k1::new()
+        .sign_ecdsa(&message, &secret_key)
+        .serialize_der()
+        .to_vec();
+    signature.push(0x01);
+    let bad = add_signature_via_signer(&pczt, 1, &keypair.public_key, &signature).unwrap();
+
+    let invalid = find_invalid_signatures(&bad).unwrap();
+    assert_eq!(
+        invalid,
+        vec![InvalidSignature::Transparent {
+            input_index: 1,
+            pubkey: hex::encode(keypair.public_key),
+            reason: "Signature does not verify".to_string(),
+        }]
+    );
+    assert!(matches!(
+        finalize_and_extract_verified(bad),
+        Err(T2ZError::InvalidSignature(InvalidSignature::Transparent {
+            input_index: 1,
+            ..
+        }))
+    ));
+}
+#[test]
+fn test_finalize_reports_what_is_missing() {
+    use crate::{
+        Network, T2ZError, finalize_and_extract, propose_transaction, sign_transparent_input,
+        signer_id, update_pczt_shadow,
+    };
+
+    let wallet = TestWallet::new(84);
+    let keypair = &wallet.keypair;
+    let pczt = propose_transaction(
+        &[wallet.input(0, 60_000), wallet.input(1, 60_000)],
+        pay(&wallet.orchard_address, 100_000),
+        None,
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // The builder records no derivations, so the key is named by its hash
+    let half_signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    match finalize_and_extract(half_signed.clone()) {
+        Err(T2ZError::MissingSignature { input, pubkey }) => {
+            assert_eq!(input, 1);
+            assert_eq!(pubkey, signer_id(&keypair.public_key).unwrap());
+        }
+        other => panic!("Expected MissingSignature, got {:?}", other),
+    }
+
+    let signed = sign_transparent_input(half_signed, 1, &keypair.secret_key).unwrap();
+    assert!(matches!(
+        finalize_and_extract(signed.clone()),
+        Err(T2ZError::MissingProof)
+    ));
+
+    let unbalanced = update_pczt_shadow(signed, |pczt_shadow| {
+        pczt_shadow.orchard.value_sum.0 += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert!(matches!(
+        finalize_and_extract(unbalanced),
+        Err(T2ZError::ValueBalanceMismatch {
+            expected: -100_000,
+            actual: -100_001
+        })
+    ));
+}
+#[test]
+fn test_format_and_parse_zec() {
+    use crate::{AmountFormat, T2ZError, format_zec, parse_zec};
+
+    let en = AmountFormat::default();
+    let de = AmountFormat::for_locale("de-DE");
+    let fr = AmountFormat::for_locale("fr_FR");
+
+    assert_eq!(format_zec(12_345, &en), "0.00012345 ZEC");
+    assert_eq!(
+        format_zec(123_456_700_000_000, &en),
+        "1,234,567.00000000 ZEC"
+    );
+    assert_eq!(
+        format_zec(123_456_700_000_000, &de),
+        "1.234.567,00000000 ZEC"
+    );
+    assert_eq!(format_zec(150_000_000, &fr), "1,50000000 ZEC");
+
+    let short = AmountFormat {
+        min_decimals: 2,
+        with_unit: false,
+        ..de
+    };
+    assert_eq!(format_zec(150_000_000, &short), "1,50");
+    assert_eq!(format_zec(150_000_001, &short), "1,50000001");
+    assert_eq!(format_zec(0, &short), "0,00");
+
+    // Whatever is formatted parses back
+    for zatoshis in [0, 1, 12_345, 150_000_000, 123_456_700_000_000] {
+        for format in [en, de, fr, short] {
+            assert_eq!(
+                parse_zec(&format_zec(zatoshis, &format), &format).unwrap(),
+                zatoshis
+            );
+        }
+    }
+    assert_eq!(parse_zec("1 234,5 zec", &fr).unwrap(), 123_450_000_000);
+    assert_eq!(parse_zec(".5", &en).unwrap(), 50_000_000);
+    assert_eq!(parse_zec("1234,5", &de).unwrap(), 123_450_000_000);
+
+    // A group separator off the thousands reads as a mistyped decimal
+    // separator, so it is refused rather than guessed at
+    for (text, format) in [("1,5", &en), ("1.5", &de), ("1,2345", &en), (",500", &en)] {
+        assert!(matches!(
+            parse_zec(text, format),
+            Err(T2ZError::InvalidInput(_))
+        ));
+    }
+
+    assert!(matches!(
+        parse_zec("0.000000001", &en),
+        Err(T2ZError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        parse_zec("-1", &en),
+        Err(T2ZError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        parse_zec("1.2.3", &en),
+        Err(T2ZError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        parse_zec("ZEC", &en),
+        Err(T2ZError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        parse_zec("21000001", &en),
+        Err(T2ZError::AmountOutOfRange(_))
+    ));
+}
+#[test]
+fn test_zip321_vectors() {
+    use crate::zip321::test_vectors;
+    use crate::{parse_payment_uri, payment_uri};
+
+    let vectors = test_vectors();
+    assert!(vectors.iter().any(|v| v.request.is_some()));
+    assert!(vectors.iter().any(|v| v.request.is_none()));
+
+    for vector in vectors {
+        let parsed = parse_payment_uri(&vector.uri);
+        match (&vector.request, &vector.canonical_uri) {
+            (Some(request), Some(canonical_uri)) => {
+                let parsed = parsed.unwrap_or_else(|e| panic!("{}: {}", vector.name, e));
+                assert_eq!(
+                    serde_json::to_value(&parsed).unwrap(),
+                    serde_json::to_value(request).unwrap(),
+                    "{}",
+                    vector.name
+                );
+                assert_eq!(
+                    &payment_uri(request).unwrap(),
+                    canonical_uri,
+                    "{}",
+                    vector.name
+                );
+                // The canonical form parses to the same request
+                assert_eq!(
+                    serde_json::to_value(parse_payment_uri(canonical_uri).unwrap()).unwrap(),
+                    serde_json::to_value(request).unwrap(),
+                    "{}",
+                    vector.name
+                );
+            }
+            (None, None) => assert!(parsed.is_err(), "{} should not parse", vector.name),
+            _ => panic!("{}: request and canonical_uri go together", vector.name),
+        }
+    }
+}
+#[test]
+fn test_tx_defaults() {
+    use zcash_primitives::transaction::Transaction;
+    use zcash_protocol::consensus::BranchId;
+
+    use crate::{
+        Network, ProposalOptions, TransparentInput, TxDefaults, finalize_and_extract,
+        find_invalid_signatures, propose_transaction_with_options, sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(85);
+    let keypair = &wallet.keypair;
+    let inputs = [
+        TransparentInput {
+            sequence: Some(7),
+            ..wallet.input(0, 60_000)
+        },
+        wallet.input(1, 60_000),
+    ];
+    let request = pay(wallet.address(), 105_000);
+
+    // An input's own sequence is kept without any defaults
+    let options = ProposalOptions::new(3_000_000);
+    let pczt = propose_transaction_with_options(
+        &inputs,
+        request.clone(),
+        None,
+        Network::Testnet,
+        options.clone(),
+    )
+    .unwrap();
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&pczt).unwrap();
+    assert_eq!(pczt_shadow.global.tx_version, 5);
+    assert_eq!(pczt_shadow.global.version_group_id, 0x26A7_270A);
+    assert_eq!(pczt_shadow.transparent.inputs[0].sequence, Some(7));
+
+    let options = ProposalOptions {
+        tx_defaults: TxDefaults {
+            sequence: Some(0xFFFF_FFFE),
+            fallback_lock_time: Some(2_999_990),
+            ..Default::default()
+        },
+        ..options
+    };
+    let pczt = propose_transaction_with_options(&inputs, request, None, Network::Testnet, options)
+        .unwrap();
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&pczt).unwrap();
+    assert_eq!(pczt_shadow.global.fallback_lock_time, Some(2_999_990));
+    assert_eq!(pczt_shadow.transparent.inputs[0].sequence, Some(7));
+    assert_eq!(
+        pczt_shadow.transparent.inputs[1].sequence,
+        Some(0xFFFF_FFFE)
+    );
+
+    // Signatures commit to the new fields, and they reach the transaction
+    let signed = sign_transparent_input(pczt, 0, &keypair.secret_key).unwrap();
+    let signed = sign_transparent_input(signed, 1, &keypair.secret_key).unwrap();
+    assert!(find_invalid_signatures(&signed).unwrap().is_empty());
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+    let transaction = Transaction::read(&tx_bytes[..], BranchId::Nu6).unwrap();
+    assert_eq!(transaction.lock_time(), 2_999_990);
+}
+#[test]
+fn test_orchard_anchor_option() {
+    use crate::{Network, ProposalOptions, T2ZError, propose_transaction_with_options};
+
+    let wallet = TestWallet::new(86);
+    let propose = |orchard_anchor: Option<[u8; 32]>| {
+        propose_transaction_with_options(
+            &wallet.inputs(200_000),
+            pay(&wallet.orchard_address, 100_000),
+            Some(wallet.address()),
+            Network::Testnet,
+            ProposalOptions {
+                orchard_anchor,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+    };
+
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&propose(None).unwrap()).unwrap();
+    assert_eq!(
+        pczt_shadow.orchard.anchor,
+        orchard::Anchor::empty_tree().to_bytes()
+    );
+
+    let anchor = [1u8; 32];
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&propose(Some(anchor)).unwrap()).unwrap();
+    assert_eq!(pczt_shadow.orchard.anchor, anchor);
+
+    // Not a Pallas field element
+    assert!(matches!(
+        propose(Some([0xff; 32])),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+/// A one-worker pool whose jobs block until the returned sender releases
+/// them, and the number of jobs it has proved
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+fn gated_prover_pool() -> (
+    crate::ProverPool,
+    std::sync::mpsc::Sender<()>,
+    std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, mpsc};
+
+    let (release, gate) = mpsc::channel::<()>();
+    let gate = Mutex::new(gate);
+    let proved = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let proved = Arc::clone(&proved);
+        crate::ProverPool::with_prove_fn(
+            move |pczt| {
+                gate.lock().unwrap().recv().unwrap();
+                proved.fetch_add(1, Ordering::SeqCst);
+                Ok(pczt)
+            },
+            1,
+        )
+    };
+    (pool, release, proved)
+}
+
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+#[test]
+fn test_prover_pool_limits_concurrent_proofs() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use crate::ProverPool;
+
+    assert_eq!(ProverPool::with_workers(0).num_workers(), 1);
+    assert_eq!(ProverPool::with_workers(3).num_workers(), 3);
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let running = Arc::clone(&running);
+        let max_running = Arc::clone(&max_running);
+        ProverPool::with_prove_fn(
+            move |pczt| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(50));
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(pczt)
+            },
+            2,
+        )
+    };
+
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+    let handles: Vec<_> = (0..6).map(|_| pool.submit(pczt.clone())).collect();
+    for handle in handles {
+        handle.wait().unwrap();
+    }
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+#[test]
+fn test_prover_pool_cancels_queued_jobs() {
+    use std::sync::atomic::Ordering;
+
+    use crate::T2ZError;
+
+    let (pool, release, proved) = gated_prover_pool();
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+
+    // The first job holds the only worker, so the others stay queued
+    let running = pool.submit(pczt.clone());
+    let cancelled = pool.submit(pczt.clone());
+    let dropped = pool.submit(pczt.clone());
+    cancelled.cancel();
+    drop(dropped);
+
+    // Enough releases for every job, so a cancellation that fails can't hang
+    for _ in 0..3 {
+        release.send(()).unwrap();
+    }
+    assert!(running.wait().is_ok());
+    assert!(matches!(cancelled.wait(), Err(T2ZError::Proving(_))));
+
+    // Dropping the pool drains the queue; only the first job was proved
+    drop(pool);
+    assert_eq!(proved.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(all(feature = "prover", not(target_arch = "wasm32")))]
+#[test]
+fn test_prover_pool_wait_timeout() {
+    use std::time::Duration;
+
+    let (pool, release, _) = gated_prover_pool();
+    let pczt = Creator::new(BranchId::Nu6.into(), 10_000_000, 133, [0; 32], [0; 32]).build();
+
+    let handle = pool.submit(pczt);
+    assert!(handle.wait_timeout(Duration::from_millis(20)).is_none());
+    assert!(!handle.is_ready());
+
+    // Timing out doesn't cancel: the job still completes
+    release.send(()).unwrap();
+    assert!(matches!(
+        handle.wait_timeout(Duration::from_secs(10)),
+        Some(Ok(_))
+    ));
+    // The result is handed out once
+    assert!(handle.wait_timeout(Duration::from_millis(20)).is_none());
+}
+#[test]
+fn test_accept_proof_response_checks_bundle() {
+    use crate::shadow::PcztShadow;
+    use crate::{
+        ProofRequest, ProofResponse, T2ZError, accept_proof_response, create_proof_request,
+        import_proof, update_pczt_shadow,
+    };
+
+    let wallet = TestWallet::new(87);
+    let pczt = orchard_payment_pczt(&wallet);
+    let request = create_proof_request(&pczt, true).unwrap();
+    let request = ProofRequest::from_bytes(&request.to_bytes()).unwrap();
+
+    // What an honest prover sends back: the redacted PCZT plus a proof
+    let served = parse_pczt(&request.pczt).unwrap();
+    let proof = vec![7u8; 2720 + 2272 * served.orchard().actions().len()];
+    let proved = import_proof(served, &proof).unwrap();
+    let response = ProofResponse {
+        pczt: serialize_pczt(&proved),
+    };
+    let response = ProofResponse::from_bytes(&response.to_bytes()).unwrap();
+
+    // The proof lands in the client's own PCZT, metadata and all
+    let accepted = accept_proof_response(pczt.clone(), &request, &response).unwrap();
+    let (_, accepted) = PcztShadow::from_pczt(&accepted).unwrap();
+    assert_eq!(accepted.orchard.zkproof, Some(proof));
+    assert!(accepted.transparent.outputs[0].user_address.is_some());
+
+    // A prover that changes the bundle it proves is caught
+    let tampered = update_pczt_shadow(proved, |pczt_shadow| {
+        pczt_shadow.orchard.actions[0].cv_net[0] ^= 1;
+        Ok(())
+    })
+    .unwrap();
+    let tampered = ProofResponse {
+        pczt: serialize_pczt(&tampered),
+    };
+    assert!(matches!(
+        accept_proof_response(pczt.clone(), &request, &tampered),
+        Err(T2ZError::Proving(_))
+    ));
+
+    // So is a response to a request for another PCZT
+    let other = orchard_payment_pczt(&wallet);
+    assert!(matches!(
+        accept_proof_response(other, &request, &response),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_detached_proving_round_trip() {
+    use crate::{
+        export_proof_request, handle_detached_proof_request, import_proof, verify_orchard_proof,
+    };
+
+    let wallet = TestWallet::new(88);
+    let pczt = orchard_payment_pczt(&wallet);
+
+    let proof = handle_detached_proof_request(&export_proof_request(&pczt).unwrap()).unwrap();
+    let proved = import_proof(pczt, &proof).unwrap();
+    assert!(verify_orchard_proof(&proved).is_ok());
+
+    // The proof is bound to its bundle: another bundle of the same size
+    // takes it, but it doesn't verify there
+    let other = orchard_payment_pczt(&wallet);
+    assert_eq!(
+        other.orchard().actions().len(),
+        proved.orchard().actions().len()
+    );
+    let mismatched = import_proof(other, &proof).unwrap();
+    assert!(verify_orchard_proof(&mismatched).is_err());
+}
+#[test]
+fn test_finalize_spends_with_custom_finalizer() {
+    use sha2::{Digest, Sha256};
+
+    use crate::script_sig::push_data;
+    use crate::{
+        Network, ScriptSigContext, T2ZError, finalize_and_extract_with, finalize_spends_with,
+        propose_transaction, sign_transparent_input, update_pczt_shadow,
+    };
+
+    let wallet = TestWallet::new(89);
+    let pczt = propose_transaction(
+        &[wallet.input(0, 60_000), wallet.input(1, 60_000)],
+        pay(wallet.address(), 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+
+    // Lock input 1 with a bare hash lock: OP_SHA256 <hash> OP_EQUAL
+    let preimage = b"open sesame".to_vec();
+    let hash: [u8; 32] = Sha256::digest(&preimage).into();
+    let pczt = update_pczt_shadow(pczt, |pczt_shadow| {
+        let input = &mut pczt_shadow.transparent.inputs[1];
+        input.script_pubkey = [&[0xa8, 0x20][..], &hash, &[0x87]].concat();
+        input.sha256_preimages.insert(hash, preimage.clone());
+        Ok(())
+    })
+    .unwrap();
+    let pczt = sign_transparent_input(pczt, 0, &wallet.keypair.secret_key).unwrap();
+
+    let hash_lock = |input: &ScriptSigContext<'_>| -> Result<Option<Vec<u8>>, T2ZError> {
+        let [0xa8, 0x20, hash @ .., 0x87] = input.script_pubkey else {
+            return Ok(None);
+        };
+        let Some(preimage) = <[u8; 32]>::try_from(hash)
+            .ok()
+            .and_then(|hash| input.sha256_preimages.get(&hash))
+        else {
+            return Ok(None);
+        };
+        let mut script_sig = Vec::new();
+        push_data(&mut script_sig, preimage);
+        Ok(Some(script_sig))
+    };
+
+    let finalized = finalize_spends_with(pczt.clone(), &hash_lock).unwrap();
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&finalized).unwrap();
+    let mut expected = Vec::new();
+    push_data(&mut expected, &preimage);
+    assert_eq!(pczt_shadow.transparent.inputs[1].script_sig, Some(expected));
+    assert!(
+        pczt_shadow.transparent.inputs[1]
+            .sha256_preimages
+            .is_empty()
+    );
+    // The P2PKH input still gets the built-in <sig> <pubkey>
+    let p2pkh = pczt_shadow.transparent.inputs[0]
+        .script_sig
+        .as_ref()
+        .unwrap();
+    assert!(p2pkh.ends_with(&wallet.keypair.public_key));
+    assert!(finalize_and_extract_with(pczt.clone(), &hash_lock).is_ok());
+
+    // A finalizer that doesn't recognize the script fails finalization
+    let unknown = |_: &ScriptSigContext<'_>| -> Result<Option<Vec<u8>>, T2ZError> { Ok(None) };
+    assert!(matches!(
+        finalize_spends_with(pczt, &unknown),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+/// A PCZT spending one 200,000-zat Orchard note of `account` back to
+/// itself, and the index of the action that spends it.
+fn orchard_spend_pczt(account: &crate::OrchardAccount) -> (Pczt, usize) {
+    use orchard::keys::Scope;
+    use orchard::note::ExtractedNoteCommitment;
+    use orchard::tree::{MerkleHashOrchard, MerklePath};
+    use pczt::roles::io_finalizer::IoFinalizer;
+    use rand_core::OsRng;
+    use zcash_primitives::consensus::BlockHeight;
+    use zcash_primitives::transaction::builder::{BuildConfig, Builder};
+    use zcash_primitives::transaction::fees::zip317;
+    use zcash_protocol::consensus::TestNetwork;
+    use zcash_protocol::memo::MemoBytes;
+
+    use crate::disclosure::orchard_note;
+
+    let address = account.orchard_address(0, Scope::External);
+    let note = orchard_note(
+        &address.to_raw_address_bytes(),
+        200_000,
+        &[1u8; 32],
+        &[2u8; 32],
+    )
+    .unwrap();
+    // Any authentication path will do, as long as the anchor is its root
+    let sibling = MerkleHashOrchard::from_bytes(&[0u8; 32]).unwrap();
+    let merkle_path = MerklePath::from_parts(0, [sibling; 32]);
+    let anchor = merkle_path.root(ExtractedNoteCommitment::from(note.commitment()));
+
+    let mut builder = Builder::new(
+        TestNetwork,
+        BlockHeight::from_u32(3_000_000),
+        BuildConfig::Standard {
+            sapling_anchor: None,
+            orchard_anchor: Some(anchor),
+        },
+    );
+    builder
+        .add_orchard_spend::<std::convert::Infallible>(
+            account.full_viewing_key().clone(),
+            note,
+            merkle_path,
+        )
+        .unwrap();
+    builder
+        .add_orchard_output::<std::convert::Infallible>(None, address, 190_000, MemoBytes::empty())
+        .unwrap();
+    let result = builder
+        .build_for_pczt(OsRng, &zip317::FeeRule::standard())
+        .unwrap();
+    let pczt = IoFinalizer::new(Creator::build_from_parts(result.pczt_parts).unwrap())
+        .finalize_io()
+        .unwrap();
+
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&pczt).unwrap();
+    let action_index = pczt_shadow
+        .orchard
+        .actions
+        .iter()
+        .position(|action| action.spend.value == Some(200_000))
+        .unwrap();
+    (pczt, action_index)
+}
+
+#[test]
+fn test_sign_orchard_spend() {
+    use crate::{
+        OrchardAccount, T2ZError, append_orchard_spend_auth_sig, get_orchard_spend_signing_data,
+        sign_orchard_spend,
+    };
+
+    let account = OrchardAccount::from_spending_key(&[89u8; 32]).unwrap();
+    let (pczt, action_index) = orchard_spend_pczt(&account);
+    let spend_auth_sig = |pczt: &Pczt| {
+        let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(pczt).unwrap();
+        pczt_shadow.orchard.actions[action_index]
+            .spend
+            .spend_auth_sig
+    };
+    assert_eq!(spend_auth_sig(&pczt), None);
+
+    let signed =
+        sign_orchard_spend(pczt.clone(), action_index, account.spending_key_bytes()).unwrap();
+    let signature = spend_auth_sig(&signed).unwrap();
+    let signing_data = get_orchard_spend_signing_data(&signed, action_index).unwrap();
+    let rk = orchard::primitives::redpallas::VerificationKey::<
+        orchard::primitives::redpallas::SpendAuth,
+    >::try_from(signing_data.rk)
+    .unwrap();
+    assert!(rk.verify(&signing_data.sighash, &signature.into()).is_ok());
+
+    // The same signature, produced externally, is accepted for this action
+    // and no other
+    let appended = append_orchard_spend_auth_sig(pczt.clone(), action_index, &signature).unwrap();
+    assert_eq!(spend_auth_sig(&appended), Some(signature));
+    let other_index = 1 - action_index;
+    assert!(matches!(
+        append_orchard_spend_auth_sig(pczt.clone(), other_index, &signature),
+        Err(T2ZError::InvalidInput(_))
+    ));
+
+    // A key that doesn't own the note can't sign its spend
+    let wrong_account = OrchardAccount::from_spending_key(&[90u8; 32]).unwrap();
+    assert!(matches!(
+        sign_orchard_spend(pczt, action_index, wrong_account.spending_key_bytes()),
+        Err(T2ZError::Signer(_))
+    ));
+}
+#[test]
+fn test_propose_without_expiry() {
+    use zcash_primitives::transaction::Transaction;
+
+    use crate::{
+        Network, finalize_and_extract, find_invalid_signatures, propose_transaction_at_height,
+        sign_transparent_input,
+    };
+
+    let wallet = TestWallet::new(90);
+    let pczt = propose_transaction_at_height(
+        &wallet.inputs(200_000),
+        pay(wallet.address(), 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        0,
+        Some(3_000_000),
+    )
+    .unwrap();
+    let (_, pczt_shadow) = shadow::PcztShadow::from_pczt(&pczt).unwrap();
+    assert_eq!(pczt_shadow.global.expiry_height, 0);
+
+    let signed = sign_transparent_input(pczt, 0, &wallet.keypair.secret_key).unwrap();
+    assert!(find_invalid_signatures(&signed).unwrap().is_empty());
+    let tx_bytes = finalize_and_extract(signed).unwrap();
+    let transaction = Transaction::read(&tx_bytes[..], BranchId::Nu6).unwrap();
+    assert_eq!(u32::from(transaction.expiry_height()), 0);
+}
+#[test]
+fn test_attribute_fee() {
+    use crate::{FeeAttributionMethod, Network, propose_transaction};
+
+    let wallet = TestWallet::new(91);
+    let recipients: Vec<TestWallet> = (92..95).map(TestWallet::new).collect();
+    let attribute = |amounts: &[u64], method| {
+        let request = crate::TransactionRequest {
+            payments: recipients
+                .iter()
+                .zip(amounts)
+                .flat_map(|(recipient, &amount)| pay(recipient.address(), amount).payments)
+                .collect(),
+        };
+        let pczt = propose_transaction(
+            &wallet.inputs(1_000_000),
+            request.clone(),
+            Some(wallet.address()),
+            Network::Testnet,
+            3_000_000,
+        )
+        .unwrap();
+        let attribution = crate::attribute_fee(&pczt, &request, method).unwrap();
+        let fees: Vec<u64> = attribution.shares.iter().map(|share| share.fee).collect();
+        assert_eq!(fees.iter().sum::<u64>(), attribution.total_fee);
+        (attribution, fees)
+    };
+
+    // 1 input, 2 payments + change: 15,000 split 3:1
+    let (attribution, fees) = attribute(&[300_000, 100_000], FeeAttributionMethod::Proportional);
+    assert_eq!(attribution.total_fee, 15_000);
+    assert_eq!(fees, vec![11_250, 3_750]);
+    assert_eq!(attribution.shares[1].payment_index, 1);
+    assert_eq!(attribution.shares[1].address, recipients[1].address());
+
+    // 20,000 doesn't split evenly three ways; the leftover zatoshis go to
+    // the earliest payments
+    assert_eq!(
+        attribute(&[100_000; 3], FeeAttributionMethod::Proportional).1,
+        vec![6_667, 6_667, 6_666]
+    );
+
+    // A single payment bears the whole fee, whatever the method
+    for method in [
+        FeeAttributionMethod::Proportional,
+        FeeAttributionMethod::Marginal,
+    ] {
+        assert_eq!(attribute(&[100_000], method).1, vec![10_000]);
+    }
+}
+#[test]
+fn test_proposal_round_trip() {
+    use crate::{Network, Proposal, T2ZError};
+
+    let wallet = TestWallet::new(95);
+    let proposal = Proposal::new(
+        wallet.inputs(200_000),
+        pay(&wallet.orchard_address, 100_000),
+        Some(wallet.address().to_string()),
+        Network::Testnet,
+        3_000_100,
+        Some(3_000_000),
+    )
+    .unwrap();
+    assert_eq!(proposal.fee, 15_000);
+
+    // Every materialization, before or after serialization, is the same PCZT
+    let pczt = serialize_pczt(&proposal.to_pczt().unwrap());
+    let restored = Proposal::from_bytes(&proposal.to_bytes()).unwrap();
+    assert_eq!(serialize_pczt(&restored.to_pczt().unwrap()), pczt);
+    assert_eq!(serialize_pczt(&restored.to_pczt().unwrap()), pczt);
+
+    // A proposal approved for a different fee doesn't materialize
+    let mut tampered = restored.clone();
+    tampered.fee = 10_000;
+    assert!(matches!(tampered.to_pczt(), Err(T2ZError::InvalidInput(_))));
+    let mut tampered = restored;
+    tampered
+        .request
+        .payments
+        .push(pay(wallet.address(), 10_000).payments.remove(0));
+    assert!(matches!(tampered.to_pczt(), Err(T2ZError::InvalidInput(_))));
+}
+
+/// A PCZT paying 100,000 zatoshis with a memo from `wallet`'s transparent
+/// key to its Orchard address, and the index of the paying action
+fn orchard_memo_payment_pczt(wallet: &TestWallet, memo: &[u8]) -> (Pczt, usize) {
+    use crate::{Network, inspect_pczt, propose_transaction};
+
+    let mut request = pay(&wallet.orchard_address, 100_000);
+    request.payments[0].memo = Some(memo.to_vec());
+    let pczt = propose_transaction(
+        &wallet.inputs(200_000),
+        request,
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let action_index = inspect_pczt(&pczt)
+        .unwrap()
+        .orchard_outputs
+        .iter()
+        .position(|output| output.value == Some(100_000))
+        .unwrap();
+    (pczt, action_index)
+}
+
+#[test]
+fn test_payment_disclosure() {
+    use crate::{PaymentDisclosure, T2ZError, create_payment_disclosure};
+
+    let wallet = TestWallet::new(96);
+    let (pczt, action_index) = orchard_memo_payment_pczt(&wallet, b"invoice 42");
+
+    let disclosure =
+        create_payment_disclosure(&pczt, action_index, Some(b"invoice 42".to_vec())).unwrap();
+    assert_eq!(disclosure.value, 100_000);
+    assert_eq!(disclosure.txid, None);
+    assert!(disclosure.verify().is_ok());
+    let restored = PaymentDisclosure::from_bytes(&disclosure.to_bytes()).unwrap();
+    assert_eq!(restored, disclosure);
+
+    // The note fields are bound by the commitment
+    let mut tampered = disclosure.clone();
+    tampered.value = 1_000_000;
+    assert!(matches!(tampered.verify(), Err(T2ZError::InvalidInput(_))));
+    let mut tampered = disclosure;
+    tampered.rseed[0] ^= 1;
+    assert!(tampered.verify().is_err());
+
+    // The memo is checked against the encrypted output
+    for memo in [Some(b"invoice 43".to_vec()), None] {
+        assert!(matches!(
+            create_payment_disclosure(&pczt, action_index, memo),
+            Err(T2ZError::InvalidInput(_))
+        ));
+    }
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_payment_disclosure_against_transaction() {
+    use crate::{
+        T2ZError, create_payment_disclosure, finalize_and_extract, prove_transaction,
+        sign_transparent_input,
+    };
+
+    let extract = |wallet: &TestWallet, pczt| {
+        let pczt = prove_transaction(pczt).unwrap();
+        let pczt = sign_transparent_input(pczt, 0, &wallet.keypair.secret_key).unwrap();
+        let tx_bytes = finalize_and_extract(pczt.clone()).unwrap();
+        (pczt, tx_bytes)
+    };
+
+    let wallet = TestWallet::new(97);
+    let (pczt, action_index) = orchard_memo_payment_pczt(&wallet, b"invoice 42");
+    let (pczt, tx_bytes) = extract(&wallet, pczt);
+    let disclosure =
+        create_payment_disclosure(&pczt, action_index, Some(b"invoice 42".to_vec())).unwrap();
+    assert!(disclosure.txid.is_some());
+    assert!(disclosure.verify_against_transaction(&tx_bytes).is_ok());
+
+    let mut tampered = disclosure.clone();
+    tampered.value = 1_000_000;
+    assert!(tampered.verify_against_transaction(&tx_bytes).is_err());
+
+    // A different memo passes `verify`, which can't see it, but not the
+    // transaction check
+    let mut tampered = disclosure.clone();
+    tampered.memo = Some(b"invoice 43".to_vec());
+    assert!(tampered.verify().is_ok());
+    assert!(matches!(
+        tampered.verify_against_transaction(&tx_bytes),
+        Err(T2ZError::InvalidInput(_))
+    ));
+
+    // Another transaction doesn't contain the output, with or without the
+    // txid to give it away
+    let other_wallet = TestWallet::new(98);
+    let (other_pczt, _) = orchard_memo_payment_pczt(&other_wallet, b"invoice 42");
+    let (_, other_tx_bytes) = extract(&other_wallet, other_pczt);
+    assert!(
+        disclosure
+            .verify_against_transaction(&other_tx_bytes)
+            .is_err()
+    );
+    let mut without_txid = disclosure;
+    without_txid.txid = None;
+    assert!(without_txid.verify_against_transaction(&tx_bytes).is_ok());
+    assert!(matches!(
+        without_txid.verify_against_transaction(&other_tx_bytes),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_identify_own_outputs_rejects_forged_change() {
+    use orchard::keys::Scope;
+
+    use crate::{
+        Network, OrchardAccount, Ownership, T2ZError, identify_own_outputs, update_pczt_shadow,
+    };
+
+    let wallet = TestWallet::new(99);
+    let account = OrchardAccount::from_seed(&[100u8; 32], Network::Testnet, 0).unwrap();
+    let ufvk = account.ufvk(Network::Testnet).unwrap();
+
+    // The wallet pays someone else's Orchard address
+    let pczt = orchard_payment_pczt(&wallet);
+    let ownership = identify_own_outputs(&pczt, &ufvk).unwrap();
+    assert!(ownership.iter().all(|o| o.ownership == Ownership::Foreign));
+
+    // A Constructor relabels the payment as change to the account
+    let change_address = account
+        .orchard_address(0, Scope::Internal)
+        .to_raw_address_bytes();
+    let forged = update_pczt_shadow(pczt, |pczt_shadow| {
+        for action in &mut pczt_shadow.orchard.actions {
+            if action.output.value == Some(100_000) {
+                action.output.recipient = Some(change_address);
+            }
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert!(matches!(
+        identify_own_outputs(&forged, &ufvk),
+        Err(T2ZError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_key_material_zeroizes() {
+    use zeroize::Zeroize;
+
+    use crate::{Network, OrchardAccount, derive_transparent_keypair};
+
+    let mut account = OrchardAccount::from_seed(&[101u8; 32], Network::Testnet, 0).unwrap();
+    assert_eq!(
+        account.spending_key().to_bytes(),
+        account.spending_key_bytes()
+    );
+    account.zeroize();
+    assert_eq!(account.spending_key_bytes(), &[0u8; 32]);
+
+    let mut keypair = derive_transparent_keypair(&[101u8; 32], Network::Testnet, 0, 0).unwrap();
+    keypair.zeroize();
+    assert_eq!(keypair.secret_key, [0u8; 32]);
+}
+
+#[cfg(feature = "wallet_lite")]
+#[test]
+fn test_account_restores_spent_utxos_on_rollback() {
+    use crate::{Account, Network, UtxoStatus, propose_transaction};
+
+    let wallet = TestWallet::new(102);
+    let mut account = Account::new(Network::Testnet);
+    account.add_utxo(wallet.input(0, 200_000), Some(100));
+    let pczt = propose_transaction(
+        &wallet.inputs(200_000),
+        pay(wallet.address(), 100_000),
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    let txid = "ab".repeat(32);
+    let status = |account: &Account| account.utxos().next().unwrap().status.clone();
+
+    account.mark_broadcast(&pczt, &txid);
+    account.mark_confirmed(&txid, 120);
+    assert_eq!(
+        status(&account),
+        UtxoStatus::Spent {
+            txid: txid.clone(),
+            height: 120
+        }
+    );
+    assert_eq!(account.balance(), Default::default());
+    assert!(account.spendable_utxos(130, 0).is_empty());
+    // The indexer re-reporting the UTXO doesn't make it spendable again
+    account.add_utxo(wallet.input(0, 200_000), Some(100));
+    assert!(matches!(status(&account), UtxoStatus::Spent { .. }));
+
+    let restored = Account::from_bytes(&account.to_bytes()).unwrap();
+    assert_eq!(status(&restored), status(&account));
+
+    // A reorg that keeps the spending block keeps it spent
+    assert!(account.rollback(120).is_empty());
+
+    // Reorging out the spending block puts the spend back in flight, and
+    // it can then fail or be mined again
+    assert_eq!(account.rollback(119).len(), 1);
+    assert_eq!(
+        status(&account),
+        UtxoStatus::PendingSpend { txid: txid.clone() }
+    );
+    assert_eq!(account.balance().pending_spend, 200_000);
+    account.mark_failed(&txid);
+    assert_eq!(account.spendable_utxos(130, 0).len(), 1);
+}
+
+#[test]
+fn test_in_place_signing_restores_pczt_the_signer_rejects() {
+    use crate::{
+        Network, OrchardAccount, append_signature_in_place, sign_orchard_spend_in_place,
+        sign_transparent_input_in_place, update_pczt_shadow,
+    };
+
+    let wallet = TestWallet::new(103);
+    // Not a valid value commitment, so the Signer cannot parse the bundle
+    let mut pczt = update_pczt_shadow(orchard_payment_pczt(&wallet), |pczt_shadow| {
+        pczt_shadow.orchard.actions[0].cv_net = [0xff; 32];
+        Ok(())
+    })
+    .unwrap();
+    let before = serialize_pczt(&pczt);
+
+    assert!(sign_transparent_input_in_place(&mut pczt, 0, &wallet.keypair.secret_key).is_err());
+    assert_eq!(serialize_pczt(&pczt), before);
+    let account = OrchardAccount::from_seed(&[103u8; 32], Network::Testnet, 0).unwrap();
+    assert!(sign_orchard_spend_in_place(&mut pczt, 0, account.spending_key_bytes()).is_err());
+    assert_eq!(serialize_pczt(&pczt), before);
+    assert!(
+        append_signature_in_place(&mut pczt, 0, &wallet.keypair.public_key, &[0x30, 0x01]).is_err()
+    );
+    assert_eq!(serialize_pczt(&pczt), before);
+}
+
+#[test]
+fn test_seeded_padded_auto_shield_proposals_are_reproducible() {
+    use crate::{
+        ChangePolicy, Network, OrchardAccount, ProposalOptions, propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(104);
+    let ufvk = OrchardAccount::from_seed(&[104u8; 32], Network::Testnet, 0)
+        .unwrap()
+        .ufvk(Network::Testnet)
+        .unwrap();
+    let propose = |rng_seed: Option<[u8; 32]>| {
+        propose_transaction_with_options(
+            &wallet.inputs(200_000),
+            pay(&wallet.orchard_address, 100_000),
+            None,
+            Network::Testnet,
+            ProposalOptions {
+                change: ChangePolicy::AutoShield { ufvk: ufvk.clone() },
+                min_orchard_actions: 4,
+                rng_seed,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+        .unwrap()
+        .serialize()
+    };
+
+    // The padding addresses and the change diversifier come from the seed too
+    assert_eq!(propose(Some([1u8; 32])), propose(Some([1u8; 32])));
+    assert_ne!(propose(Some([1u8; 32])), propose(Some([2u8; 32])));
+}
+
+#[test]
+fn test_adjust_transparent_side_balances_and_keeps_payments_to_change_address() {
+    use crate::adjust::CHANGE_OUTPUT_KEY;
+    use crate::shadow::PcztShadow;
+    use crate::{FeeStrategy, Network, adjust_transparent_side, inspect_pczt, propose_transaction};
+
+    let wallet = TestWallet::new(105);
+    // A transparent payment to the change address beside the change itself
+    let mut request = pay(wallet.address(), 30_000);
+    request
+        .payments
+        .extend(pay(&wallet.orchard_address, 100_000).payments);
+    let pczt = propose_transaction(
+        &[wallet.input(0, 200_000)],
+        request,
+        Some(wallet.address()),
+        Network::Testnet,
+        3_000_000,
+    )
+    .unwrap();
+    assert_eq!(inspect_pczt(&pczt).unwrap().transparent_outputs.len(), 2);
+
+    let new_inputs = [wallet.input(1, 90_000), wallet.input(2, 90_000)];
+    let adjust = |fee_strategy: &FeeStrategy| {
+        adjust_transparent_side(
+            pczt.clone(),
+            &new_inputs,
+            Some(wallet.address()),
+            Network::Testnet,
+            fee_strategy,
+        )
+        .unwrap()
+    };
+    // Inputs cover the outputs, the Orchard value, and the fee to the zatoshi
+    let balance = |pczt: &crate::Pczt| {
+        let info = inspect_pczt(pczt).unwrap();
+        let outputs: u64 = info.transparent_outputs.iter().map(|o| o.value).sum();
+        assert_eq!(180_000, outputs + 100_000 + info.implied_fee);
+        info
+    };
+
+    // 180,000 in, 130,000 paid, a 20,000 fee (2 inputs + 2 actions); the
+    // payment stays and only the marked change is recomputed
+    let adjusted = adjust(&FeeStrategy::zip317());
+    let info = balance(&adjusted);
+    assert_eq!(info.implied_fee, 20_000);
+    let values: Vec<u64> = info.transparent_outputs.iter().map(|o| o.value).collect();
+    assert_eq!(values, vec![30_000, 30_000]);
+    let (_, adjusted) = PcztShadow::from_pczt(&adjusted).unwrap();
+    assert!(
+        !adjusted.transparent.outputs[0]
+            .proprietary
+            .contains_key(CHANGE_OUTPUT_KEY)
+    );
+    assert!(
+        adjusted.transparent.outputs[1]
+            .proprietary
+            .contains_key(CHANGE_OUTPUT_KEY)
+    );
+
+    // A fixed fee is paid as given, whatever the new shape
+    let info = balance(&adjust(&FeeStrategy::fixed(12_345)));
+    assert_eq!(info.implied_fee, 12_345);
+    assert_eq!(info.transparent_outputs[1].value, 37_655);
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_adjust_transparent_side_keeps_proof_valid() {
+    use crate::{
+        FeeStrategy, Network, adjust_transparent_side, finalize_and_extract, prove_transaction,
+        sign_transparent_input, verify_orchard_proof,
+    };
+
+    let wallet = TestWallet::new(106);
+    let proved = prove_transaction(orchard_payment_pczt(&wallet)).unwrap();
+
+    let adjusted = adjust_transparent_side(
+        proved,
+        &[wallet.input(1, 150_000)],
+        Some(wallet.address()),
+        Network::Testnet,
+        &FeeStrategy::zip317(),
+    )
+    .unwrap();
+
+    assert!(verify_orchard_proof(&adjusted).is_ok());
+    let signed = sign_transparent_input(adjusted, 0, &wallet.keypair.secret_key).unwrap();
+    assert!(finalize_and_extract(signed).is_ok());
+}
+
+#[test]
+fn test_padding_without_change_reaches_min_orchard_actions() {
+    use crate::{
+        ChangePolicy, Network, ProposalOptions, inspect_pczt, propose_transaction_with_options,
+    };
+
+    let wallet = TestWallet::new(107);
+    let orchard = &wallet.orchard_address;
+    let propose = |value: u64, change: ChangePolicy| {
+        let pczt = propose_transaction_with_options(
+            &wallet.inputs(value),
+            pay(orchard, 100_000),
+            Some(orchard),
+            Network::Testnet,
+            ProposalOptions {
+                min_orchard_actions: 4,
+                change,
+                ..ProposalOptions::new(3_000_000)
+            },
+        )
+        .unwrap();
+        inspect_pczt(&pczt).unwrap()
+    };
+
+    // Exactly the payment and the fee of 1 input and 4 actions: no change,
+    // so the padding alone makes up the 4 actions
+    let info = propose(125_000, ChangePolicy::Send);
+    assert_eq!(info.num_orchard_actions, 4);
+    assert_eq!(info.implied_fee, 25_000);
+
+    // Likewise when DustToFee gives the leftover to the fee
+    let info = propose(127_000, ChangePolicy::DustToFee { threshold: 5_000 });
+    assert_eq!(info.num_orchard_actions, 4);
+    assert_eq!(info.implied_fee, 27_000);
+
+    // Change takes the place of a padding output
+    let info = propose(200_000, ChangePolicy::Send);
+    assert_eq!(info.num_orchard_actions, 4);
+    assert_eq!(info.implied_fee, 25_000);
+}
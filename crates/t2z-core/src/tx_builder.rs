@@ -0,0 +1,442 @@
+//! Low-level transaction building for advanced users.
+//!
+//! `propose_transaction` covers the common case: a set of payments plus one
+//! change output, with the fee and change worked out for you. `TxBuilder`
+//! exposes the `zcash_primitives` builder underneath, using t2z's input
+//! types, for combinations `propose_transaction` does not produce: several
+//! change outputs, custom anchors, Sapling outputs next to Orchard ones. The
+//! caller is responsible for balancing the transaction (`fee()` reports what
+//! the fee rule requires); `into_pczt()` returns a PCZT ready for the usual
+//! prove/sign/extract flow.
+//!
+//! Sapling outputs need Sapling proofs, made with the parameters from
+//! `sapling_params`; `prove_transaction` only creates Orchard proofs.
+
+use std::convert::Infallible;
+
+use orchard::keys::OutgoingViewingKey;
+use rand_core::OsRng;
+use zcash_address::unified::{Container, Receiver};
+use zcash_address::{ConversionError, TryFromAddress, ZcashAddress};
+use zcash_primitives::consensus::{BlockHeight, Parameters};
+use zcash_primitives::transaction::builder::{BuildConfig, Builder};
+use zcash_primitives::transaction::fees::FeeRule;
+use zcash_protocol::consensus::{MainNetwork, NetworkType, TestNetwork};
+use zcash_protocol::value::Zatoshis;
+use zcash_transparent::address::TransparentAddress;
+
+use crate::{
+    Creator, FeeStrategy, IoFinalizer, Network, Pczt, T2ZError, TransparentInput, checked_sum,
+    checked_zatoshis, memo_bytes, transparent_input_parts, update_pczt_shadow,
+};
+
+/// An output queued on a `TxBuilder`
+#[derive(Debug, Clone)]
+enum Output {
+    Transparent {
+        address: TransparentAddress,
+        value: u64,
+    },
+    Sapling {
+        address: sapling_crypto::PaymentAddress,
+        value: u64,
+        memo: Option<Vec<u8>>,
+    },
+    Orchard {
+        address: orchard::Address,
+        value: u64,
+        memo: Option<Vec<u8>>,
+    },
+}
+
+/// Facade over `zcash_primitives::transaction::builder::Builder`
+#[derive(Debug, Clone)]
+pub struct TxBuilder {
+    network: Network,
+    target_height: u32,
+    expiry_height: Option<u32>,
+    sapling_anchor: Option<sapling_crypto::Anchor>,
+    orchard_anchor: Option<orchard::Anchor>,
+    orchard_ovk: Option<[u8; 32]>,
+    inputs: Vec<TransparentInput>,
+    outputs: Vec<Output>,
+}
+
+impl TxBuilder {
+    /// Starts a transaction that will be mined at or after `target_height`.
+    ///
+    /// The consensus branch is chosen for `target_height`; the expiry height
+    /// defaults to the builder's (`target_height` + 40).
+    pub fn new(network: Network, target_height: u32) -> Self {
+        TxBuilder {
+            network,
+            target_height,
+            expiry_height: None,
+            sapling_anchor: None,
+            orchard_anchor: None,
+            orchard_ovk: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Overrides the expiry height (0 = never expires).
+    pub fn expiry_height(&mut self, expiry_height: u32) -> &mut Self {
+        self.expiry_height = Some(expiry_height);
+        self
+    }
+
+    /// Sets the Sapling anchor (defaults to the empty tree when there are Sapling outputs).
+    pub fn sapling_anchor(&mut self, anchor: sapling_crypto::Anchor) -> &mut Self {
+        self.sapling_anchor = Some(anchor);
+        self
+    }
+
+    /// Sets the Orchard anchor (defaults to the empty tree when there are Orchard outputs).
+    pub fn orchard_anchor(&mut self, anchor: orchard::Anchor) -> &mut Self {
+        self.orchard_anchor = Some(anchor);
+        self
+    }
+
+    /// Encrypts Orchard outputs to an outgoing viewing key, so its holder
+    /// can decrypt them later (e.g. with `inspect_pczt_with_ovk`). Without
+    /// one, outputs can only be decrypted by their recipients.
+    pub fn orchard_ovk(&mut self, ovk: [u8; 32]) -> &mut Self {
+        self.orchard_ovk = Some(ovk);
+        self
+    }
+
+    /// Adds a transparent input.
+    pub fn add_transparent_input(&mut self, input: TransparentInput) -> Result<&mut Self, T2ZError> {
+        transparent_input_parts(&input)?;
+        self.inputs.push(input);
+        Ok(self)
+    }
+
+    /// Adds an output to an encoded address.
+    ///
+    /// Unified addresses pay their Orchard receiver if they have one, then
+    /// Sapling, then transparent. Memos are rejected for transparent outputs.
+    pub fn add_output(
+        &mut self,
+        address: &str,
+        value: u64,
+        memo: Option<Vec<u8>>,
+    ) -> Result<&mut Self, T2ZError> {
+        let expected_network = self.network.to_network_type();
+        let receiver = ZcashAddress::try_from_encoded(address)
+            .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address: {:?}", e)))?
+            .convert_if_network::<AnyReceiver>(expected_network)
+            .map_err(|e| T2ZError::InvalidAddress(format!("Unsupported address: {:?}", e)))?;
+
+        match receiver {
+            AnyReceiver::Orchard(address) => self.add_orchard_output(address, value, memo),
+            AnyReceiver::Sapling(address) => self.add_sapling_output(address, value, memo),
+            AnyReceiver::Transparent(address) => {
+                if memo.is_some() {
+                    return Err(T2ZError::InvalidMemo(
+                        "Transparent outputs cannot carry a memo".to_string(),
+                    ));
+                }
+                Ok(self.add_transparent_output(address, value))
+            }
+        }
+    }
+
+    /// Adds a transparent output.
+    pub fn add_transparent_output(&mut self, address: TransparentAddress, value: u64) -> &mut Self {
+        self.outputs.push(Output::Transparent { address, value });
+        self
+    }
+
+    /// Adds a Sapling output.
+    pub fn add_sapling_output(
+        &mut self,
+        address: sapling_crypto::PaymentAddress,
+        value: u64,
+        memo: Option<Vec<u8>>,
+    ) -> Result<&mut Self, T2ZError> {
+        memo_bytes(memo.as_deref())?;
+        self.outputs.push(Output::Sapling {
+            address,
+            value,
+            memo,
+        });
+        Ok(self)
+    }
+
+    /// Adds an Orchard output.
+    pub fn add_orchard_output(
+        &mut self,
+        address: orchard::Address,
+        value: u64,
+        memo: Option<Vec<u8>>,
+    ) -> Result<&mut Self, T2ZError> {
+        memo_bytes(memo.as_deref())?;
+        self.outputs.push(Output::Orchard {
+            address,
+            value,
+            memo,
+        });
+        Ok(self)
+    }
+
+    /// Total value of the inputs added so far, in zatoshis.
+    ///
+    /// Fails with `T2ZError::AmountOutOfRange` above 21M ZEC.
+    pub fn total_input(&self) -> Result<u64, T2ZError> {
+        checked_sum(self.inputs.iter().map(|i| i.value), "Input total").map(Zatoshis::into_u64)
+    }
+
+    /// Total value of the outputs added so far, in zatoshis.
+    ///
+    /// Fails with `T2ZError::AmountOutOfRange` above 21M ZEC.
+    pub fn total_output(&self) -> Result<u64, T2ZError> {
+        checked_sum(
+            self.outputs.iter().map(|output| match output {
+                Output::Transparent { value, .. }
+                | Output::Sapling { value, .. }
+                | Output::Orchard { value, .. } => *value,
+            }),
+            "Output total",
+        )
+        .map(Zatoshis::into_u64)
+    }
+
+    /// Fee the strategy requires for the inputs and outputs added so far.
+    pub fn fee<FR>(&self, fee_strategy: FeeStrategy<FR>) -> Result<u64, T2ZError>
+    where
+        FR: FeeRule,
+        FR::Error: std::fmt::Debug,
+    {
+        macro_rules! fee_with {
+            ($fee_rule:expr) => {
+                match self.network {
+                    Network::Mainnet => self.fee_with_rule(MainNetwork, $fee_rule),
+                    Network::Testnet => self.fee_with_rule(TestNetwork, $fee_rule),
+                }
+            };
+        }
+
+        match fee_strategy {
+            FeeStrategy::Zip317 => {
+                fee_with!(&zcash_primitives::transaction::fees::zip317::FeeRule::standard())
+            }
+            FeeStrategy::Fixed(fee) => Ok(fee),
+            FeeStrategy::Custom(fee_rule) => fee_with!(&fee_rule),
+        }
+    }
+
+    /// Builds the PCZT (Creator, Constructor, and IO Finalizer roles).
+    ///
+    /// Fails if inputs minus outputs is not exactly the fee the strategy requires.
+    pub fn into_pczt<FR>(self, fee_strategy: FeeStrategy<FR>) -> Result<Pczt, T2ZError>
+    where
+        FR: FeeRule,
+        FR::Error: std::fmt::Debug,
+    {
+        macro_rules! build_with {
+            ($fee_rule:expr) => {
+                match self.network {
+                    Network::Mainnet => self.build_with_rule(MainNetwork, $fee_rule),
+                    Network::Testnet => self.build_with_rule(TestNetwork, $fee_rule),
+                }
+            };
+        }
+
+        match fee_strategy {
+            FeeStrategy::Zip317 => {
+                build_with!(&zcash_primitives::transaction::fees::zip317::FeeRule::standard())
+            }
+            FeeStrategy::Fixed(fee) => {
+                let fee = checked_zatoshis(fee, "Fee")?;
+                build_with!(&zcash_primitives::transaction::fees::fixed::FeeRule::non_standard(fee))
+            }
+            FeeStrategy::Custom(fee_rule) => build_with!(&fee_rule),
+        }
+    }
+
+    fn fee_with_rule<P, FR>(&self, params: P, fee_rule: &FR) -> Result<u64, T2ZError>
+    where
+        P: Parameters,
+        FR: FeeRule,
+        FR::Error: std::fmt::Debug,
+    {
+        let builder = self.builder(params)?;
+        let fee = builder
+            .get_fee(fee_rule)
+            .map_err(|e| T2ZError::Builder(format!("Failed to calculate fee: {:?}", e)))?;
+        Ok(fee.into_u64())
+    }
+
+    fn build_with_rule<P, FR>(&self, params: P, fee_rule: &FR) -> Result<Pczt, T2ZError>
+    where
+        P: Parameters,
+        FR: FeeRule,
+        FR::Error: std::fmt::Debug,
+    {
+        let result = self
+            .builder(params)?
+            .build_for_pczt(OsRng, fee_rule)
+            .map_err(|e| T2ZError::Builder(format!("Failed to build PCZT: {:?}", e)))?;
+
+        let mut pczt = Creator::build_from_parts(result.pczt_parts)
+            .ok_or_else(|| T2ZError::Builder("Failed to create PCZT from parts".to_string()))?;
+
+        // Set before IO finalization so the dummy spend signatures commit to it
+        if let Some(expiry_height) = self.expiry_height {
+            pczt = update_pczt_shadow(pczt, |pczt_shadow| {
+                pczt_shadow.global.expiry_height = expiry_height;
+                Ok(())
+            })?;
+        }
+
+        Ok(IoFinalizer::new(pczt).finalize_io()?)
+    }
+
+    fn builder<P: Parameters>(&self, params: P) -> Result<Builder<'static, P, ()>, T2ZError> {
+        let has_sapling = self
+            .outputs
+            .iter()
+            .any(|o| matches!(o, Output::Sapling { .. }));
+        let has_orchard = self
+            .outputs
+            .iter()
+            .any(|o| matches!(o, Output::Orchard { .. }));
+
+        let mut builder = Builder::new(
+            params,
+            BlockHeight::from_u32(self.target_height),
+            BuildConfig::Standard {
+                sapling_anchor: self
+                    .sapling_anchor
+                    .or_else(|| has_sapling.then(sapling_crypto::Anchor::empty_tree)),
+                orchard_anchor: self
+                    .orchard_anchor
+                    .or_else(|| has_orchard.then(orchard::Anchor::empty_tree)),
+            },
+        );
+
+        for input in &self.inputs {
+            let (pubkey, outpoint, txout) = transparent_input_parts(input)?;
+            builder
+                .add_transparent_input(pubkey, outpoint, txout)
+                .map_err(|e| {
+                    T2ZError::Builder(format!("Failed to add transparent input: {:?}", e))
+                })?;
+        }
+
+        let orchard_ovk = self.orchard_ovk.map(OutgoingViewingKey::from);
+        for output in &self.outputs {
+            match output {
+                Output::Transparent { address, value } => builder
+                    .add_transparent_output(address, checked_zatoshis(*value, "Output value")?)
+                    .map_err(|e| {
+                        T2ZError::Builder(format!("Failed to add transparent output: {:?}", e))
+                    })?,
+                Output::Sapling {
+                    address,
+                    value,
+                    memo,
+                } => builder
+                    .add_sapling_output::<Infallible>(
+                        None,
+                        *address,
+                        checked_zatoshis(*value, "Output value")?,
+                        memo_bytes(memo.as_deref())?,
+                    )
+                    .map_err(|e| {
+                        T2ZError::Builder(format!("Failed to add Sapling output: {:?}", e))
+                    })?,
+                Output::Orchard {
+                    address,
+                    value,
+                    memo,
+                } => builder
+                    .add_orchard_output::<Infallible>(
+                        orchard_ovk.clone(),
+                        *address,
+                        *value,
+                        memo_bytes(memo.as_deref())?,
+                    )
+                    .map_err(|e| {
+                        T2ZError::Builder(format!("Failed to add Orchard output: {:?}", e))
+                    })?,
+            }
+        }
+
+        Ok(builder)
+    }
+}
+
+/// The receiver `TxBuilder::add_output` pays
+enum AnyReceiver {
+    Transparent(TransparentAddress),
+    Sapling(sapling_crypto::PaymentAddress),
+    Orchard(orchard::Address),
+}
+
+impl TryFromAddress for AnyReceiver {
+    type Error = String;
+
+    fn try_from_sapling(
+        _net: NetworkType,
+        data: [u8; 43],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        sapling_crypto::PaymentAddress::from_bytes(&data)
+            .map(AnyReceiver::Sapling)
+            .ok_or_else(|| ConversionError::User("Invalid Sapling address".to_string()))
+    }
+
+    fn try_from_unified(
+        _net: NetworkType,
+        data: zcash_address::unified::Address,
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        let receivers = data.items_as_parsed();
+
+        let orchard = receivers.iter().find_map(|r| match r {
+            Receiver::Orchard(bytes) => {
+                Option::from(orchard::Address::from_raw_address_bytes(bytes)).map(AnyReceiver::Orchard)
+            }
+            _ => None,
+        });
+        let sapling = || {
+            receivers.iter().find_map(|r| match r {
+                Receiver::Sapling(bytes) => {
+                    sapling_crypto::PaymentAddress::from_bytes(bytes).map(AnyReceiver::Sapling)
+                }
+                _ => None,
+            })
+        };
+        let transparent = || {
+            receivers.iter().find_map(|r| match r {
+                Receiver::P2pkh(hash) => Some(AnyReceiver::Transparent(
+                    TransparentAddress::PublicKeyHash(*hash),
+                )),
+                Receiver::P2sh(hash) => {
+                    Some(AnyReceiver::Transparent(TransparentAddress::ScriptHash(*hash)))
+                }
+                _ => None,
+            })
+        };
+
+        orchard
+            .or_else(sapling)
+            .or_else(transparent)
+            .ok_or_else(|| ConversionError::User("Unified address has no usable receiver".to_string()))
+    }
+
+    fn try_from_transparent_p2pkh(
+        _net: NetworkType,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(AnyReceiver::Transparent(TransparentAddress::PublicKeyHash(data)))
+    }
+
+    fn try_from_transparent_p2sh(
+        _net: NetworkType,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(AnyReceiver::Transparent(TransparentAddress::ScriptHash(data)))
+    }
+}
@@ -0,0 +1,175 @@
+//! Converting between zatoshis and ZEC for display.
+//!
+//! Every amount in this crate is in zatoshis (10^-8 ZEC). Front-ends that
+//! convert to ZEC themselves, usually through floating point, have shipped
+//! amounts off by 10^8 or by a rounding error. `format_zec` and `parse_zec`
+//! do the conversion in integers, with the decimal and digit-group separators
+//! of the user's locale.
+//!
+//! Locales are matched on their language (and a few regions), which covers
+//! the separators but not every local convention: Indian lakh grouping, for
+//! one, is written with Western thousands groups.
+
+use crate::{T2ZError, checked_zatoshis};
+
+/// Zatoshis per ZEC
+const ZATOSHIS_PER_ZEC: u64 = 100_000_000;
+
+/// Decimal places of a ZEC amount
+const ZEC_DECIMALS: usize = 8;
+
+/// How `format_zec` writes an amount and `parse_zec` reads it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountFormat {
+    /// Separates ZEC from its fraction (`.` or `,`)
+    pub decimal_separator: char,
+    /// Separates thousands in the whole ZEC, if grouped
+    pub group_separator: Option<char>,
+    /// Fraction digits always written; trailing zeros past these are
+    /// dropped. 8 writes every zatoshi.
+    pub min_decimals: usize,
+    /// Whether to append ` ZEC`
+    pub with_unit: bool,
+}
+
+impl Default for AmountFormat {
+    /// `1,234.56780000 ZEC`
+    fn default() -> Self {
+        AmountFormat {
+            decimal_separator: '.',
+            group_separator: Some(','),
+            min_decimals: ZEC_DECIMALS,
+            with_unit: true,
+        }
+    }
+}
+
+impl AmountFormat {
+    /// The separators of a BCP 47 locale (`en-US`, `de`, `fr_CH`, ...).
+    /// Unknown locales get the English ones.
+    pub fn for_locale(locale: &str) -> Self {
+        let locale = locale.replace('_', "-").to_ascii_lowercase();
+        let language = locale.split('-').next().unwrap_or_default();
+
+        let (decimal_separator, group_separator) = match (language, locale.as_str()) {
+            // Swiss German and Italian use an apostrophe
+            (_, "de-ch" | "it-ch" | "de-li") => ('.', '\u{2019}'),
+            (_, "fr-ch") => (',', '\u{202f}'),
+            (_, "es-mx" | "es-us" | "pt-mo") => ('.', ','),
+            ("fr", _) => (',', '\u{202f}'),
+            ("ru" | "uk" | "pl" | "cs" | "sk" | "fi" | "sv" | "nb" | "no" | "hu" | "bg", _) => {
+                (',', '\u{a0}')
+            }
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "ro" | "el" | "vi", _) => {
+                (',', '.')
+            }
+            _ => ('.', ','),
+        };
+
+        AmountFormat {
+            decimal_separator,
+            group_separator: Some(group_separator),
+            ..AmountFormat::default()
+        }
+    }
+}
+
+/// Formats zatoshis as ZEC, e.g. `format_zec(12_345, &AmountFormat::default())`
+/// is `"0.00012345 ZEC"`.
+pub fn format_zec(zatoshis: u64, format: &AmountFormat) -> String {
+    let whole = (zatoshis / ZATOSHIS_PER_ZEC).to_string();
+    let fraction = format!("{:08}", zatoshis % ZATOSHIS_PER_ZEC);
+    let keep = fraction
+        .trim_end_matches('0')
+        .len()
+        .max(format.min_decimals.min(ZEC_DECIMALS));
+
+    let mut out = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if let Some(separator) = format.group_separator
+            && i > 0
+            && (whole.len() - i) % 3 == 0
+        {
+            out.push(separator);
+        }
+        out.push(digit);
+    }
+    if keep > 0 {
+        out.push(format.decimal_separator);
+        out.push_str(&fraction[..keep]);
+    }
+    if format.with_unit {
+        out.push_str(" ZEC");
+    }
+    out
+}
+
+/// Parses a ZEC amount written the way `format` writes it (the unit and
+/// digit grouping are optional) into zatoshis.
+///
+/// Fails on more than 8 decimal places, anything that isn't a plain
+/// non-negative number, and amounts above 21M ZEC. Group separators must
+/// fall every three digits; anywhere else (`"1,5"` in English) they are
+/// more likely a mistyped decimal separator, so the amount is refused as
+/// ambiguous.
+pub fn parse_zec(text: &str, format: &AmountFormat) -> Result<u64, T2ZError> {
+    let invalid = || T2ZError::InvalidInput(format!("Invalid ZEC amount: {:?}", text));
+
+    let number = text.trim();
+    let number = match number.get(number.len().saturating_sub(3)..) {
+        Some(unit) if unit.eq_ignore_ascii_case("zec") => number[..number.len() - 3].trim_end(),
+        _ => number,
+    };
+
+    let (whole, fraction) = match number.split_once(format.decimal_separator) {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (number, ""),
+    };
+    // Grouping is optional, and people type a plain space for the locale's
+    // (non-breaking) one
+    let groups: Vec<&str> = whole
+        .split(|c: char| {
+            Some(c) == format.group_separator
+                || (format.group_separator.is_some_and(char::is_whitespace) && c == ' ')
+        })
+        .collect();
+    if groups.len() > 1
+        && (!(1..=3).contains(&groups[0].len()) || groups[1..].iter().any(|g| g.len() != 3))
+    {
+        return Err(T2ZError::InvalidInput(format!(
+            "Ambiguous ZEC amount {:?}: digits must be grouped in threes",
+            text
+        )));
+    }
+    let whole = groups.concat();
+
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > ZEC_DECIMALS {
+        return Err(T2ZError::InvalidInput(format!(
+            "ZEC amounts have at most {} decimal places: {:?}",
+            ZEC_DECIMALS, text
+        )));
+    }
+
+    let out_of_range = || T2ZError::AmountOutOfRange(format!("{} exceeds 21M ZEC", text));
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| out_of_range())?
+    };
+    let fraction: u64 = format!("{:0<8}", fraction).parse().map_err(|_| invalid())?;
+    let zatoshis = whole
+        .checked_mul(ZATOSHIS_PER_ZEC)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or_else(out_of_range)?;
+
+    checked_zatoshis(zatoshis, "Amount")?;
+    Ok(zatoshis)
+}
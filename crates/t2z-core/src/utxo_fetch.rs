@@ -0,0 +1,290 @@
+//! Batched UTXO lookup for many addresses (feature `wallet_lite`).
+//!
+//! Exchanges sweeping deposit addresses need the UTXOs of thousands of
+//! addresses at once. Asking lightwalletd one address at a time is slow, and
+//! one `GetAddressUtxos` call for all of them can exceed what the server
+//! returns in a reply. `fetch_utxos_for_addresses` plans the lookup instead:
+//! addresses are split into batches, each batch is paged with `startHeight` /
+//! `maxEntries`, and requests are handed out no faster than the configured
+//! rate.
+//!
+//! Like the rest of `wallet_lite`, this makes no requests itself. Callers
+//! loop on `next_request`, send each `UtxoPageRequest` to an endpoint (an
+//! `EndpointPool` can choose which), and hand the reply to `record_page` or
+//! the error to `record_failure`. Once `is_done`, `utxos` holds the
+//! consolidated set, deduplicated across pages, ready for `Account::add_utxo`
+//! and coin selection.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Account, Network, Outpoint, T2ZError, TransparentInput, checked_sum, parse_transparent_address,
+};
+
+/// How to split up and pace a UTXO lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoFetchOptions {
+    /// Addresses per request
+    pub batch_size: usize,
+    /// UTXOs per page (`maxEntries`)
+    pub page_size: u32,
+    /// Requests handed out per second
+    pub requests_per_second: u32,
+}
+
+impl Default for UtxoFetchOptions {
+    fn default() -> Self {
+        UtxoFetchOptions {
+            batch_size: 100,
+            page_size: 1000,
+            requests_per_second: 5,
+        }
+    }
+}
+
+/// One `GetAddressUtxos` call to make
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoPageRequest {
+    /// Addresses to look up
+    pub addresses: Vec<String>,
+    /// Lowest block height to return UTXOs from
+    pub start_height: u32,
+    /// Most UTXOs to return (0 for no limit)
+    pub max_entries: u32,
+}
+
+/// A UTXO returned by the indexer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedUtxo {
+    /// Address the UTXO pays
+    pub address: String,
+    /// Transaction ID (32 bytes, internal byte order as in `TransparentInput`)
+    pub txid: Vec<u8>,
+    /// Output index
+    pub index: u32,
+    /// scriptPubKey of the output
+    pub script_pubkey: Vec<u8>,
+    /// Output value in zatoshis
+    #[serde(with = "crate::amounts::amount")]
+    pub value: u64,
+    /// Height the UTXO was mined at
+    pub height: u32,
+}
+
+impl FetchedUtxo {
+    /// The output this UTXO is.
+    pub fn outpoint(&self) -> Outpoint {
+        Outpoint {
+            txid: self.txid.clone(),
+            index: self.index,
+        }
+    }
+
+    /// The UTXO as a spendable input, given the public key of its address.
+    pub fn to_input(&self, pubkey: Vec<u8>) -> TransparentInput {
+        TransparentInput {
+            pubkey,
+            prevout_txid: self.txid.clone(),
+            prevout_index: self.index,
+            value: self.value,
+            script_pubkey: self.script_pubkey.clone(),
+            sequence: None,
+        }
+    }
+}
+
+/// A UTXO lookup in progress
+#[derive(Debug, Clone)]
+pub struct UtxoFetch {
+    options: UtxoFetchOptions,
+    queued: VecDeque<UtxoPageRequest>,
+    in_flight: Vec<UtxoPageRequest>,
+    window_start: u64,
+    sent_in_window: u32,
+    utxos: BTreeMap<Outpoint, FetchedUtxo>,
+}
+
+/// Plans a lookup of the UTXOs of `addresses` (transparent addresses on
+/// `network`). Duplicate addresses are looked up once.
+pub fn fetch_utxos_for_addresses(
+    addresses: Vec<String>,
+    network: Network,
+    options: UtxoFetchOptions,
+) -> Result<UtxoFetch, T2ZError> {
+    if options.batch_size == 0 || options.requests_per_second == 0 {
+        return Err(T2ZError::InvalidInput(
+            "Batch size and request rate must be nonzero".to_string(),
+        ));
+    }
+
+    let mut unique = BTreeSet::new();
+    let mut ordered = Vec::new();
+    for address in addresses {
+        let parsed = zcash_address::ZcashAddress::try_from_encoded(&address).map_err(|e| {
+            T2ZError::InvalidAddress(format!("Invalid address {}: {:?}", address, e))
+        })?;
+        parse_transparent_address(&parsed, network.to_network_type())?;
+        if unique.insert(address.clone()) {
+            ordered.push(address);
+        }
+    }
+
+    let queued = ordered
+        .chunks(options.batch_size)
+        .map(|batch| UtxoPageRequest {
+            addresses: batch.to_vec(),
+            start_height: 0,
+            max_entries: options.page_size,
+        })
+        .collect();
+
+    Ok(UtxoFetch {
+        options,
+        queued,
+        in_flight: Vec::new(),
+        window_start: 0,
+        sent_in_window: 0,
+        utxos: BTreeMap::new(),
+    })
+}
+
+impl UtxoFetch {
+    /// The next request to make at `now` (Unix seconds), or `None` if the
+    /// rate limit is reached (try again at `next_request_at`) or nothing is
+    /// left to request.
+    pub fn next_request(&mut self, now: u64) -> Option<UtxoPageRequest> {
+        if now != self.window_start {
+            self.window_start = now;
+            self.sent_in_window = 0;
+        }
+        if self.sent_in_window >= self.options.requests_per_second {
+            return None;
+        }
+        let request = self.queued.pop_front()?;
+        self.sent_in_window += 1;
+        self.in_flight.push(request.clone());
+        Some(request)
+    }
+
+    /// When (Unix seconds) `next_request` can next hand out a request, or
+    /// `None` if none is queued (some may still be in flight).
+    pub fn next_request_at(&self, now: u64) -> Option<u64> {
+        if self.queued.is_empty() {
+            None
+        } else if now == self.window_start
+            && self.sent_in_window >= self.options.requests_per_second
+        {
+            Some(now + 1)
+        } else {
+            Some(now)
+        }
+    }
+
+    /// Records the reply to `request`. A full page means there may be more,
+    /// so the rest is queued starting at the highest height in the page;
+    /// UTXOs seen twice across pages are kept once.
+    pub fn record_page(
+        &mut self,
+        request: &UtxoPageRequest,
+        utxos: Vec<FetchedUtxo>,
+    ) -> Result<(), T2ZError> {
+        if let Some(utxo) = utxos
+            .iter()
+            .find(|u| !request.addresses.contains(&u.address))
+        {
+            return Err(T2ZError::InvalidInput(format!(
+                "Indexer returned a UTXO for unrequested address {}",
+                utxo.address
+            )));
+        }
+        self.take_in_flight(request)?;
+
+        let full = request.max_entries > 0 && utxos.len() >= request.max_entries as usize;
+        let mut highest = request.start_height;
+        for utxo in utxos {
+            highest = highest.max(utxo.height);
+            self.utxos.insert(utxo.outpoint(), utxo);
+        }
+
+        if full {
+            let next = if highest > request.start_height {
+                UtxoPageRequest {
+                    start_height: highest,
+                    ..request.clone()
+                }
+            } else {
+                // The whole page is at one height; ask for more at once
+                UtxoPageRequest {
+                    max_entries: request.max_entries.saturating_mul(2),
+                    ..request.clone()
+                }
+            };
+            self.queued.push_back(next);
+        }
+        Ok(())
+    }
+
+    /// Records that `request` failed; it is queued again.
+    pub fn record_failure(&mut self, request: &UtxoPageRequest) -> Result<(), T2ZError> {
+        self.take_in_flight(request)?;
+        self.queued.push_front(request.clone());
+        Ok(())
+    }
+
+    /// Whether every page has been fetched.
+    pub fn is_done(&self) -> bool {
+        self.queued.is_empty() && self.in_flight.is_empty()
+    }
+
+    /// Every UTXO found so far, in outpoint order.
+    pub fn utxos(&self) -> impl Iterator<Item = &FetchedUtxo> {
+        self.utxos.values()
+    }
+
+    /// Total value of the UTXOs found so far.
+    pub fn total_value(&self) -> Result<u64, T2ZError> {
+        Ok(checked_sum(self.utxos.values().map(|u| u.value), "UTXO total")?.into_u64())
+    }
+
+    /// The UTXOs found as spendable inputs. `pubkeys` maps each address to
+    /// its public key; every address with UTXOs needs one.
+    pub fn to_inputs(
+        &self,
+        pubkeys: &BTreeMap<String, Vec<u8>>,
+    ) -> Result<Vec<TransparentInput>, T2ZError> {
+        self.utxos
+            .values()
+            .map(|utxo| {
+                let pubkey = pubkeys.get(&utxo.address).ok_or_else(|| {
+                    T2ZError::InvalidInput(format!("No public key for {}", utxo.address))
+                })?;
+                Ok(utxo.to_input(pubkey.clone()))
+            })
+            .collect()
+    }
+
+    /// Adds the UTXOs found to `account` as confirmed at their heights.
+    pub fn add_to_account(
+        &self,
+        account: &mut Account,
+        pubkeys: &BTreeMap<String, Vec<u8>>,
+    ) -> Result<(), T2ZError> {
+        let inputs = self.to_inputs(pubkeys)?;
+        for (input, utxo) in inputs.into_iter().zip(self.utxos.values()) {
+            account.add_utxo(input, Some(utxo.height));
+        }
+        Ok(())
+    }
+
+    fn take_in_flight(&mut self, request: &UtxoPageRequest) -> Result<(), T2ZError> {
+        let pos = self
+            .in_flight
+            .iter()
+            .position(|r| r == request)
+            .ok_or_else(|| T2ZError::InvalidInput("Request is not in flight".to_string()))?;
+        self.in_flight.swap_remove(pos);
+        Ok(())
+    }
+}
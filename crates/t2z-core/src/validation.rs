@@ -0,0 +1,442 @@
+//! Up-front validation of ZIP 321 transaction requests.
+//!
+//! `validate_request` reports everything wrong with a request at once, without
+//! needing inputs, so a UI can flag problems while the user is still typing.
+//! `propose_transaction` stops at the first error instead.
+//!
+//! `AddressHistory` adds privacy warnings for address reuse: paying (or
+//! sending change) back to an address being spent from, or to an address
+//! used in an earlier proposal of the same session.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use zcash_address::{ConversionError, TryFromAddress, ZcashAddress};
+use zcash_protocol::consensus::NetworkType;
+use zcash_protocol::value::Zatoshis;
+
+use crate::{Network, T2ZError, TransactionRequest, TransparentInput};
+
+/// How serious a `RequestIssue` is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// `propose_transaction` would reject the request
+    Error,
+    /// The request is valid but probably not what the user meant
+    Warning,
+}
+
+/// What is wrong with a request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestIssueKind {
+    /// The request has no payments
+    NoPayments,
+    /// The address does not parse
+    InvalidAddress,
+    /// The address is for the other network
+    WrongNetwork,
+    /// The address has neither a transparent nor an Orchard receiver
+    UnsupportedAddress,
+    /// A memo on a payment that will be sent to a transparent receiver
+    MemoOnTransparent,
+    /// A memo longer than 512 bytes
+    MemoTooLong,
+    /// A payment of zero zatoshis
+    ZeroAmount,
+    /// A payment of more than 21M ZEC
+    AmountTooLarge,
+    /// The same address appears in more than one payment
+    DuplicateRecipient,
+    /// The payment amounts sum to more than 21M ZEC
+    TotalOverflow,
+    /// A recipient or the change address is one of the inputs' addresses
+    PaysInputAddress,
+    /// A recipient or the change address was used in an earlier proposal
+    AddressReused,
+}
+
+/// One problem found by `validate_request`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestIssue {
+    /// Index of the payment the issue is about (`None` for the whole request)
+    pub payment_index: Option<usize>,
+    /// What is wrong
+    pub kind: RequestIssueKind,
+    /// Whether proposing would fail
+    pub severity: IssueSeverity,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl RequestIssue {
+    fn error(payment_index: Option<usize>, kind: RequestIssueKind, message: String) -> Self {
+        RequestIssue {
+            payment_index,
+            kind,
+            severity: IssueSeverity::Error,
+            message,
+        }
+    }
+}
+
+/// Receivers of an address, and the network it was encoded for
+struct AddressInfo {
+    network: NetworkType,
+    transparent: bool,
+    orchard: bool,
+}
+
+impl TryFromAddress for AddressInfo {
+    type Error = ();
+
+    fn try_from_sapling(
+        network: NetworkType,
+        _data: [u8; 43],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressInfo {
+            network,
+            transparent: false,
+            orchard: false,
+        })
+    }
+
+    fn try_from_unified(
+        network: NetworkType,
+        data: zcash_address::unified::Address,
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        use zcash_address::unified::{Container, Receiver};
+
+        let receivers = data.items_as_parsed();
+        Ok(AddressInfo {
+            network,
+            transparent: receivers
+                .iter()
+                .any(|r| matches!(r, Receiver::P2pkh(_) | Receiver::P2sh(_))),
+            orchard: receivers.iter().any(|r| matches!(r, Receiver::Orchard(_))),
+        })
+    }
+
+    fn try_from_transparent_p2pkh(
+        network: NetworkType,
+        _data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressInfo {
+            network,
+            transparent: true,
+            orchard: false,
+        })
+    }
+
+    fn try_from_transparent_p2sh(
+        network: NetworkType,
+        _data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(AddressInfo {
+            network,
+            transparent: true,
+            orchard: false,
+        })
+    }
+}
+
+/// Checks a transaction request without selecting inputs.
+///
+/// Returns every issue found (empty if the request is fine). Issues with
+/// `IssueSeverity::Error` would make `propose_transaction` fail.
+///
+/// # Arguments
+/// * `request` - ZIP 321 transaction request
+/// * `network` - Network the payments are meant for
+pub fn validate_request(request: &TransactionRequest, network: Network) -> Vec<RequestIssue> {
+    let mut issues = Vec::new();
+
+    if request.payments.is_empty() {
+        issues.push(RequestIssue::error(
+            None,
+            RequestIssueKind::NoPayments,
+            "No payments specified".to_string(),
+        ));
+    }
+
+    let expected_network = network.to_network_type();
+    let mut total = Some(Zatoshis::ZERO);
+
+    for (idx, payment) in request.payments.iter().enumerate() {
+        let index = Some(idx);
+
+        match ZcashAddress::try_from_encoded(&payment.address) {
+            Err(e) => issues.push(RequestIssue::error(
+                index,
+                RequestIssueKind::InvalidAddress,
+                format!("Payment {} address is invalid: {:?}", idx, e),
+            )),
+            Ok(address) => match address.convert::<AddressInfo>() {
+                Err(_) => issues.push(RequestIssue::error(
+                    index,
+                    RequestIssueKind::UnsupportedAddress,
+                    format!("Payment {} address type is not supported", idx),
+                )),
+                Ok(info) if info.network != expected_network => issues.push(RequestIssue::error(
+                    index,
+                    RequestIssueKind::WrongNetwork,
+                    format!("Payment {} address is for a different network", idx),
+                )),
+                Ok(info) if !info.transparent && !info.orchard => {
+                    issues.push(RequestIssue::error(
+                        index,
+                        RequestIssueKind::UnsupportedAddress,
+                        format!(
+                            "Payment {} address cannot receive transparent or Orchard funds",
+                            idx
+                        ),
+                    ))
+                }
+                // Addresses with a transparent receiver are paid transparently
+                Ok(info) if info.transparent && payment.memo.is_some() => {
+                    issues.push(RequestIssue::error(
+                        index,
+                        RequestIssueKind::MemoOnTransparent,
+                        format!(
+                            "Payment {} has a memo but pays a transparent receiver, which cannot carry one",
+                            idx
+                        ),
+                    ))
+                }
+                Ok(_) => {}
+            },
+        }
+
+        if let Some(memo) = &payment.memo
+            && memo.len() > 512
+        {
+            issues.push(RequestIssue::error(
+                index,
+                RequestIssueKind::MemoTooLong,
+                format!("Payment {} memo exceeds 512 bytes ({} bytes)", idx, memo.len()),
+            ));
+        }
+
+        let amount = Zatoshis::from_u64(payment.amount).ok();
+        if amount.is_none() {
+            issues.push(RequestIssue::error(
+                index,
+                RequestIssueKind::AmountTooLarge,
+                format!("Payment {} amount {} exceeds 21M ZEC", idx, payment.amount),
+            ));
+        }
+
+        if payment.amount == 0 {
+            issues.push(RequestIssue {
+                payment_index: index,
+                kind: RequestIssueKind::ZeroAmount,
+                severity: IssueSeverity::Warning,
+                message: format!("Payment {} has a zero amount", idx),
+            });
+        }
+
+        if let Some(first) = request.payments[..idx]
+            .iter()
+            .position(|p| p.address == payment.address)
+        {
+            issues.push(RequestIssue {
+                payment_index: index,
+                kind: RequestIssueKind::DuplicateRecipient,
+                severity: IssueSeverity::Warning,
+                message: format!("Payment {} pays the same address as payment {}", idx, first),
+            });
+        }
+
+        total = total.zip(amount).and_then(|(t, a)| t + a);
+    }
+
+    // A single oversized payment is already reported above
+    if total.is_none() && !issues.iter().any(|i| i.kind == RequestIssueKind::AmountTooLarge) {
+        issues.push(RequestIssue::error(
+            None,
+            RequestIssueKind::TotalOverflow,
+            "Payment total exceeds 21M ZEC".to_string(),
+        ));
+    }
+
+    issues
+}
+
+/// A receiver, compared across address encodings (a unified address shares
+/// receivers with its transparent and Orchard parts)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Receiver {
+    P2pkh([u8; 20]),
+    P2sh([u8; 20]),
+    Sapling(Vec<u8>),
+    Orchard(Vec<u8>),
+}
+
+/// Every receiver of an address
+struct Receivers(Vec<Receiver>);
+
+impl TryFromAddress for Receivers {
+    type Error = ();
+
+    fn try_from_sapling(
+        _network: NetworkType,
+        data: [u8; 43],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Receivers(vec![Receiver::Sapling(data.to_vec())]))
+    }
+
+    fn try_from_unified(
+        _network: NetworkType,
+        data: zcash_address::unified::Address,
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        use zcash_address::unified::{self, Container};
+
+        Ok(Receivers(
+            data.items_as_parsed()
+                .iter()
+                .filter_map(|receiver| match receiver {
+                    unified::Receiver::P2pkh(hash) => Some(Receiver::P2pkh(*hash)),
+                    unified::Receiver::P2sh(hash) => Some(Receiver::P2sh(*hash)),
+                    unified::Receiver::Sapling(data) => Some(Receiver::Sapling(data.to_vec())),
+                    unified::Receiver::Orchard(data) => Some(Receiver::Orchard(data.to_vec())),
+                    unified::Receiver::Unknown { .. } => None,
+                })
+                .collect(),
+        ))
+    }
+
+    fn try_from_transparent_p2pkh(
+        _network: NetworkType,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Receivers(vec![Receiver::P2pkh(data)]))
+    }
+
+    fn try_from_transparent_p2sh(
+        _network: NetworkType,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<Self::Error>> {
+        Ok(Receivers(vec![Receiver::P2sh(data)]))
+    }
+}
+
+/// Receivers of an encoded address (none if it does not parse; that is
+/// `validate_request`'s to report)
+fn receivers(address: &str) -> Vec<Receiver> {
+    ZcashAddress::try_from_encoded(address)
+        .ok()
+        .and_then(|address| address.convert::<Receivers>().ok())
+        .map(|receivers| receivers.0)
+        .unwrap_or_default()
+}
+
+/// The receiver a P2PKH or P2SH script pays
+fn script_receiver(script_pubkey: &[u8]) -> Option<Receiver> {
+    match script_pubkey {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] => hash.try_into().ok().map(Receiver::P2pkh),
+        [0xa9, 0x14, hash @ .., 0x87] => hash.try_into().ok().map(Receiver::P2sh),
+        _ => None,
+    }
+}
+
+/// Addresses used by earlier proposals in a session, for reuse warnings.
+///
+/// Serializable so wallets can keep it across restarts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddressHistory {
+    seen: BTreeSet<Receiver>,
+}
+
+impl AddressHistory {
+    /// An empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Warns about recipients or a change address that reuse an input's
+    /// address or an address from an earlier recorded proposal.
+    ///
+    /// # Arguments
+    /// * `inputs` - UTXOs the proposal spends
+    /// * `request` - ZIP 321 transaction request
+    /// * `change_address` - Change address, if any
+    pub fn check(
+        &self,
+        inputs: &[TransparentInput],
+        request: &TransactionRequest,
+        change_address: Option<&str>,
+    ) -> Vec<RequestIssue> {
+        let input_receivers: Vec<_> = inputs
+            .iter()
+            .filter_map(|input| script_receiver(&input.script_pubkey))
+            .collect();
+
+        let destinations = request
+            .payments
+            .iter()
+            .enumerate()
+            .map(|(idx, payment)| (Some(idx), payment.address.as_str()))
+            .chain(change_address.map(|address| (None, address)));
+
+        let mut issues = Vec::new();
+        for (payment_index, address) in destinations {
+            let what = match payment_index {
+                Some(idx) => format!("Payment {}", idx),
+                None => "The change address".to_string(),
+            };
+            let receivers = receivers(address);
+
+            if receivers.iter().any(|r| input_receivers.contains(r)) {
+                issues.push(RequestIssue {
+                    payment_index,
+                    kind: RequestIssueKind::PaysInputAddress,
+                    severity: IssueSeverity::Warning,
+                    message: format!("{} pays an address this transaction spends from", what),
+                });
+            } else if receivers.iter().any(|r| self.seen.contains(r)) {
+                issues.push(RequestIssue {
+                    payment_index,
+                    kind: RequestIssueKind::AddressReused,
+                    severity: IssueSeverity::Warning,
+                    message: format!("{} pays an address used in an earlier proposal", what),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Records a proposal's recipients and change address.
+    pub fn record(&mut self, request: &TransactionRequest, change_address: Option<&str>) {
+        let addresses = request
+            .payments
+            .iter()
+            .map(|payment| payment.address.as_str())
+            .chain(change_address);
+        for address in addresses {
+            self.seen.extend(receivers(address));
+        }
+    }
+
+    /// Serializes the history for storage.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses a serialized history.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid address history: {:?}", e)))
+    }
+}
+
+/// Warns about recipients or a change address that reuse an input's address
+/// (`AddressHistory::check` without a session).
+pub fn check_address_reuse(
+    inputs: &[TransparentInput],
+    request: &TransactionRequest,
+    change_address: Option<&str>,
+) -> Vec<RequestIssue> {
+    AddressHistory::new().check(inputs, request, change_address)
+}
@@ -0,0 +1,93 @@
+//! Orchard proof verification without the proving key.
+//!
+//! Enabled by the `verifier` feature, which `prover` implies. A
+//! coordinator or auditor built with `verifier` alone can check the proofs in
+//! PCZTs coming back from (possibly remote) provers, while the verifying key
+//! is a fraction of the proving key's build time and memory.
+
+use orchard::bundle::Flags;
+use orchard::circuit::Instance;
+use orchard::note::{ExtractedNoteCommitment, Nullifier};
+use orchard::primitives::redpallas::{SpendAuth, VerificationKey};
+use orchard::tree::Anchor;
+use orchard::value::ValueCommitment;
+
+use crate::shadow::PcztShadow;
+use crate::{OrchardVerifyingKey, Pczt, T2ZError};
+
+/// Orchard verifying key cache
+static ORCHARD_VK: once_cell::sync::OnceCell<OrchardVerifyingKey> =
+    once_cell::sync::OnceCell::new();
+
+/// Builds the Orchard circuit verifying key (cached after the first call)
+///
+/// Like the proving key, it is derived from the circuit itself, so nothing
+/// is downloaded.
+pub fn load_orchard_verifying_key() -> &'static OrchardVerifyingKey {
+    ORCHARD_VK.get_or_init(OrchardVerifyingKey::build)
+}
+
+/// Check if the verifying key is already loaded
+pub fn is_verifying_key_loaded() -> bool {
+    ORCHARD_VK.get().is_some()
+}
+
+/// Verifies the Orchard proof in a PCZT against its actions.
+///
+/// Fails if the PCZT has no Orchard proof, or if the proof does not prove
+/// this bundle's actions (anchor, value commitments, nullifiers, randomized
+/// keys, note commitments, and flags).
+pub fn verify_orchard_proof(pczt: &Pczt) -> Result<(), T2ZError> {
+    let (_, pczt_shadow) = PcztShadow::from_pczt(pczt)?;
+    let bundle = &pczt_shadow.orchard;
+
+    let proof = bundle
+        .zkproof
+        .as_ref()
+        .ok_or_else(|| T2ZError::InvalidInput("PCZT has no Orchard proof".to_string()))?;
+
+    let flags = Flags::from_byte(bundle.flags)
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard flags".to_string()))?;
+    let anchor = Option::<Anchor>::from(Anchor::from_bytes(bundle.anchor))
+        .ok_or_else(|| T2ZError::InvalidInput("Invalid Orchard anchor".to_string()))?;
+
+    let instances = bundle
+        .actions
+        .iter()
+        .enumerate()
+        .map(|(idx, action)| {
+            let invalid = |field: &str| {
+                T2ZError::InvalidInput(format!("Orchard action {} has an invalid {}", idx, field))
+            };
+
+            let cv_net = Option::<ValueCommitment>::from(ValueCommitment::from_bytes(
+                &action.cv_net,
+            ))
+            .ok_or_else(|| invalid("cv_net"))?;
+            let nullifier = Option::<Nullifier>::from(Nullifier::from_bytes(
+                &action.spend.nullifier,
+            ))
+            .ok_or_else(|| invalid("nullifier"))?;
+            let rk = VerificationKey::<SpendAuth>::try_from(action.spend.rk)
+                .map_err(|_| invalid("rk"))?;
+            let cmx = Option::<ExtractedNoteCommitment>::from(
+                ExtractedNoteCommitment::from_bytes(&action.output.cmx),
+            )
+            .ok_or_else(|| invalid("cmx"))?;
+
+            Ok(Instance::from_parts(
+                anchor,
+                cv_net,
+                nullifier,
+                rk,
+                cmx,
+                flags.spends_enabled(),
+                flags.outputs_enabled(),
+            ))
+        })
+        .collect::<Result<Vec<_>, T2ZError>>()?;
+
+    orchard::Proof::new(proof.clone())
+        .verify(load_orchard_verifying_key(), &instances)
+        .map_err(|e| T2ZError::Proving(format!("Orchard proof is invalid: {:?}", e)))
+}
@@ -0,0 +1,117 @@
+//! PCZT format version handling.
+//!
+//! Every serialized PCZT starts with `PCZT` and a little-endian `u32` format
+//! version. `parse_pczt` only accepts the version this crate writes, which
+//! is the safe default for signers. Coordinators passing PCZTs between t2z
+//! and other implementations (Zashi, zcash_client_backend) can use these
+//! helpers instead:
+//! - `pczt_version` reads the version without parsing the rest
+//! - `parse_pczt_versioned` also accepts versions newer than this crate
+//!   knows, when the body still decodes with the newest known layout (the
+//!   newer version only appended fields, i.e. a minor revision), and says
+//!   so in a warning; fields it does not know are dropped
+//! - `negotiate_pczt_version` picks the highest version both sides support,
+//!   and `serialize_pczt_as` writes it, so a peer is never sent a PCZT it
+//!   cannot read
+
+use crate::stream::{PCZT_MAGIC, PCZT_VERSION_1};
+use crate::{Pczt, T2ZError};
+
+/// PCZT format versions this crate reads and writes, oldest first
+pub const SUPPORTED_PCZT_VERSIONS: &[u32] = &[PCZT_VERSION_1];
+
+/// A parsed PCZT with the format version it was serialized with
+#[derive(Debug, Clone)]
+pub struct VersionedPczt {
+    pub pczt: Pczt,
+    /// Format version from the PCZT's header
+    pub version: u32,
+    /// Set when the version is newer than this crate supports and the PCZT
+    /// was read with the newest layout it knows
+    pub warning: Option<String>,
+}
+
+/// Reads the format version from a serialized PCZT's header.
+pub fn pczt_version(pczt_bytes: &[u8]) -> Result<u32, T2ZError> {
+    if pczt_bytes.len() < 8 {
+        return Err(T2ZError::InvalidInput("PCZT too short".to_string()));
+    }
+    if pczt_bytes[..4] != PCZT_MAGIC {
+        return Err(T2ZError::InvalidInput(
+            "Not a PCZT: invalid magic bytes".to_string(),
+        ));
+    }
+    Ok(u32::from_le_bytes(
+        pczt_bytes[4..8].try_into().expect("4 bytes"),
+    ))
+}
+
+/// Parses a PCZT of any supported version, or of a newer version whose
+/// body is readable as the newest supported one (with a warning).
+pub fn parse_pczt_versioned(pczt_bytes: &[u8]) -> Result<VersionedPczt, T2ZError> {
+    let version = pczt_version(pczt_bytes)?;
+    let newest = newest_supported_version();
+
+    if SUPPORTED_PCZT_VERSIONS.contains(&version) {
+        return Ok(VersionedPczt {
+            pczt: Pczt::parse(pczt_bytes)?,
+            version,
+            warning: None,
+        });
+    }
+    if version < newest {
+        return Err(unsupported_version(version));
+    }
+
+    let mut downgraded = pczt_bytes.to_vec();
+    downgraded[4..8].copy_from_slice(&newest.to_le_bytes());
+    let pczt = Pczt::parse(&downgraded).map_err(|_| unsupported_version(version))?;
+
+    Ok(VersionedPczt {
+        pczt,
+        version,
+        warning: Some(format!(
+            "PCZT version {} is newer than the supported version {}; fields added since \
+             version {} were ignored",
+            version, newest, newest
+        )),
+    })
+}
+
+/// The highest PCZT version supported both by this crate and by a peer
+/// (e.g. another wallet's PCZT implementation).
+pub fn negotiate_pczt_version(peer_versions: &[u32]) -> Result<u32, T2ZError> {
+    SUPPORTED_PCZT_VERSIONS
+        .iter()
+        .rev()
+        .find(|version| peer_versions.contains(version))
+        .copied()
+        .ok_or_else(|| {
+            T2ZError::InvalidInput(format!(
+                "No PCZT version in common: peer supports {:?}, t2z supports {:?}",
+                peer_versions, SUPPORTED_PCZT_VERSIONS
+            ))
+        })
+}
+
+/// Serializes a PCZT as the given format version, typically the result of
+/// `negotiate_pczt_version`.
+pub fn serialize_pczt_as(pczt: &Pczt, version: u32) -> Result<Vec<u8>, T2ZError> {
+    match version {
+        PCZT_VERSION_1 => Ok(pczt.serialize()),
+        _ => Err(unsupported_version(version)),
+    }
+}
+
+fn newest_supported_version() -> u32 {
+    *SUPPORTED_PCZT_VERSIONS
+        .last()
+        .expect("at least one version")
+}
+
+fn unsupported_version(version: u32) -> T2ZError {
+    T2ZError::InvalidInput(format!(
+        "Unsupported PCZT version: {} (supported: {:?})",
+        version, SUPPORTED_PCZT_VERSIONS
+    ))
+}
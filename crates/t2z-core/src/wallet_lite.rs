@@ -0,0 +1,331 @@
+//! Minimal wallet state for bindings users (feature `wallet_lite`).
+//!
+//! An `Account` tracks the transparent UTXOs a shielding wallet can spend,
+//! moves them through broadcast and confirmation, and selects inputs for a
+//! transaction request. It holds no keys and talks to no network: callers feed
+//! it UTXOs from their indexer and report what happened to the transactions
+//! they built.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Listeners;
+use crate::fees::{orchard_actions_for_outputs, zip317_fee};
+use crate::{
+    Network, Pczt, T2ZError, TransactionRequest, TransparentInput, WalletEvent, WalletListener,
+    checked_sum,
+};
+
+/// Reference to a transaction output
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Outpoint {
+    /// Transaction ID (32 bytes, internal byte order as in `TransparentInput`)
+    pub txid: Vec<u8>,
+    /// Output index
+    pub index: u32,
+}
+
+/// Where a tracked UTXO is in its lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UtxoStatus {
+    /// Seen in the mempool only
+    Unconfirmed,
+    /// Mined at the given height
+    Confirmed { height: u32 },
+    /// Spent by a broadcast transaction that has not been mined yet
+    PendingSpend { txid: String },
+    /// Spent by a transaction mined at the given height. Kept so a reorg
+    /// can bring it back; drop it with `remove_utxo` once that is no longer
+    /// possible.
+    Spent { txid: String, height: u32 },
+}
+
+/// A transparent UTXO held by an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedUtxo {
+    /// Everything needed to spend the UTXO
+    pub input: TransparentInput,
+    /// Lifecycle status
+    pub status: UtxoStatus,
+}
+
+impl TrackedUtxo {
+    /// Outpoint of this UTXO
+    pub fn outpoint(&self) -> Outpoint {
+        Outpoint {
+            txid: self.input.prevout_txid.clone(),
+            index: self.input.prevout_index,
+        }
+    }
+}
+
+/// Account balance by status (zatoshis)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountBalance {
+    /// Mined UTXOs
+    #[serde(with = "crate::amounts::amount")]
+    pub confirmed: u64,
+    /// Mempool-only UTXOs
+    #[serde(with = "crate::amounts::amount")]
+    pub unconfirmed: u64,
+    /// UTXOs spent by transactions awaiting confirmation
+    #[serde(with = "crate::amounts::amount")]
+    pub pending_spend: u64,
+}
+
+/// Transparent UTXO state for one account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    /// Network the account's addresses belong to
+    pub network: Network,
+    utxos: BTreeMap<Outpoint, TrackedUtxo>,
+    #[serde(skip)]
+    listeners: Listeners,
+}
+
+impl Account {
+    /// Creates an empty account.
+    pub fn new(network: Network) -> Self {
+        Account {
+            network,
+            utxos: BTreeMap::new(),
+            listeners: Listeners::default(),
+        }
+    }
+
+    /// Calls `listener` with every `UtxoAdded`, `TxConfirmed`, and
+    /// `TxExpired` event from now on.
+    pub fn subscribe(&mut self, listener: impl WalletListener + 'static) {
+        self.listeners.subscribe(listener);
+    }
+
+    /// Adds (or updates) a UTXO reported by an indexer.
+    ///
+    /// # Arguments
+    /// * `input` - The UTXO as a spendable input
+    /// * `confirmed_height` - Mined height, or `None` if only in the mempool
+    pub fn add_utxo(&mut self, input: TransparentInput, confirmed_height: Option<u32>) {
+        let status = match confirmed_height {
+            Some(height) => UtxoStatus::Confirmed { height },
+            None => UtxoStatus::Unconfirmed,
+        };
+        let utxo = TrackedUtxo { input, status };
+
+        match self.utxos.get_mut(&utxo.outpoint()) {
+            // Never resurrect a UTXO we have already spent
+            Some(existing)
+                if matches!(
+                    existing.status,
+                    UtxoStatus::PendingSpend { .. } | UtxoStatus::Spent { .. }
+                ) => {}
+            Some(existing) => *existing = utxo,
+            None => {
+                let event = WalletEvent::UtxoAdded {
+                    outpoint: utxo.outpoint(),
+                    value: utxo.input.value,
+                };
+                self.utxos.insert(utxo.outpoint(), utxo);
+                self.listeners.emit(event);
+            }
+        }
+    }
+
+    /// Removes a UTXO (e.g. spent elsewhere, or reorged out).
+    pub fn remove_utxo(&mut self, outpoint: &Outpoint) -> Option<TrackedUtxo> {
+        self.utxos.remove(outpoint)
+    }
+
+    /// All tracked UTXOs, in outpoint order.
+    pub fn utxos(&self) -> impl Iterator<Item = &TrackedUtxo> {
+        self.utxos.values()
+    }
+
+    /// Balance by status.
+    pub fn balance(&self) -> AccountBalance {
+        let mut balance = AccountBalance::default();
+        for utxo in self.utxos.values() {
+            let bucket = match utxo.status {
+                UtxoStatus::Confirmed { .. } => &mut balance.confirmed,
+                UtxoStatus::Unconfirmed => &mut balance.unconfirmed,
+                UtxoStatus::PendingSpend { .. } => &mut balance.pending_spend,
+                UtxoStatus::Spent { .. } => continue,
+            };
+            *bucket = bucket.saturating_add(utxo.input.value);
+        }
+        balance
+    }
+
+    /// UTXOs with at least `min_confirmations` at `tip_height`, largest first.
+    pub fn spendable_utxos(&self, tip_height: u32, min_confirmations: u32) -> Vec<&TrackedUtxo> {
+        let mut spendable: Vec<&TrackedUtxo> = self
+            .utxos
+            .values()
+            .filter(|utxo| match utxo.status {
+                UtxoStatus::Confirmed { height } => {
+                    tip_height.saturating_sub(height).saturating_add(1) >= min_confirmations
+                }
+                UtxoStatus::Unconfirmed => min_confirmations == 0,
+                UtxoStatus::PendingSpend { .. } | UtxoStatus::Spent { .. } => false,
+            })
+            .collect();
+        spendable.sort_by(|a, b| b.input.value.cmp(&a.input.value));
+        spendable
+    }
+
+    /// Selects inputs covering `request` plus the ZIP 317 fee, largest first.
+    ///
+    /// The fee estimate assumes a change output to `change_address` (or to a
+    /// transparent address if none is given), so the selection still covers
+    /// the fee when `propose_transaction` adds change.
+    pub fn select_inputs(
+        &self,
+        request: &TransactionRequest,
+        change_address: Option<&str>,
+        tip_height: u32,
+        min_confirmations: u32,
+    ) -> Result<Vec<TransparentInput>, T2ZError> {
+        let payment_total =
+            checked_sum(request.payments.iter().map(|p| p.amount), "Payment total")?.into_u64();
+
+        let mut transparent_outputs = 0;
+        let mut orchard_outputs = 0;
+        for payment in &request.payments {
+            if is_transparent_address(&payment.address)? {
+                transparent_outputs += 1;
+            } else {
+                orchard_outputs += 1;
+            }
+        }
+        match change_address {
+            Some(address) if !is_transparent_address(address)? => orchard_outputs += 1,
+            _ => transparent_outputs += 1,
+        }
+
+        let mut selected = Vec::new();
+        let mut available = 0u64;
+        let mut fee = 0;
+
+        for utxo in self.spendable_utxos(tip_height, min_confirmations) {
+            selected.push(utxo.input.clone());
+            available = available.saturating_add(utxo.input.value);
+            fee = zip317_fee(
+                selected.len(),
+                transparent_outputs,
+                orchard_actions_for_outputs(orchard_outputs),
+            );
+            if available >= payment_total.saturating_add(fee) {
+                return Ok(selected);
+            }
+        }
+
+        Err(T2ZError::InsufficientFunds {
+            available,
+            required: payment_total.saturating_add(fee),
+            payment: payment_total,
+            fee,
+        })
+    }
+
+    /// Marks the UTXOs spent by `pczt` as pending under `txid`.
+    ///
+    /// Call this once the transaction has been broadcast.
+    pub fn mark_broadcast(&mut self, pczt: &Pczt, txid: &str) {
+        for input in pczt.transparent().inputs() {
+            let outpoint = Outpoint {
+                txid: input.prevout_txid().to_vec(),
+                index: *input.prevout_index(),
+            };
+            if let Some(utxo) = self.utxos.get_mut(&outpoint) {
+                utxo.status = UtxoStatus::PendingSpend {
+                    txid: txid.to_string(),
+                };
+            }
+        }
+    }
+
+    /// Marks the UTXOs spent by `txid` as spent, now that it has been mined
+    /// at `height`.
+    pub fn mark_confirmed(&mut self, txid: &str, height: u32) {
+        let mut confirmed = false;
+        for utxo in self.utxos.values_mut() {
+            if matches!(&utxo.status, UtxoStatus::PendingSpend { txid: t } if t == txid) {
+                utxo.status = UtxoStatus::Spent {
+                    txid: txid.to_string(),
+                    height,
+                };
+                confirmed = true;
+            }
+        }
+        if confirmed {
+            self.listeners.emit(WalletEvent::TxConfirmed {
+                txid: txid.to_string(),
+                height: Some(height),
+            });
+        }
+    }
+
+    /// Releases the UTXOs spent by `txid` (rejected or expired).
+    ///
+    /// Released UTXOs return as unconfirmed until the indexer reports them
+    /// again with their height.
+    pub fn mark_failed(&mut self, txid: &str) {
+        let mut released = false;
+        for utxo in self.utxos.values_mut() {
+            if matches!(&utxo.status, UtxoStatus::PendingSpend { txid: t } if t == txid) {
+                utxo.status = UtxoStatus::Unconfirmed;
+                released = true;
+            }
+        }
+        if released {
+            self.listeners.emit(WalletEvent::TxExpired {
+                txid: txid.to_string(),
+            });
+        }
+    }
+
+    /// Undoes confirmations above `height` after a chain reorganization:
+    /// UTXOs mined in the abandoned blocks go back to unconfirmed (and so
+    /// stop being spendable with `min_confirmations > 0`) until the indexer
+    /// reports them again from the new chain. UTXOs spent by transactions
+    /// mined in those blocks go back to pending, as the transactions may be
+    /// mined again. Returns the outpoints of both.
+    ///
+    /// UTXOs that do not reappear should be dropped with `remove_utxo`.
+    pub fn rollback(&mut self, height: u32) -> Vec<Outpoint> {
+        let mut reverted = Vec::new();
+        for (outpoint, utxo) in self.utxos.iter_mut() {
+            match &utxo.status {
+                UtxoStatus::Confirmed { height: mined } if *mined > height => {
+                    utxo.status = UtxoStatus::Unconfirmed;
+                }
+                UtxoStatus::Spent {
+                    txid,
+                    height: mined,
+                } if *mined > height => {
+                    utxo.status = UtxoStatus::PendingSpend { txid: txid.clone() };
+                }
+                _ => continue,
+            }
+            reverted.push(outpoint.clone());
+        }
+        reverted
+    }
+
+    /// Serializes the account state.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("can serialize into memory")
+    }
+
+    /// Parses serialized account state.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, T2ZError> {
+        postcard::from_bytes(bytes)
+            .map_err(|e| T2ZError::InvalidInput(format!("Invalid account state: {:?}", e)))
+    }
+}
+
+fn is_transparent_address(address: &str) -> Result<bool, T2ZError> {
+    let address = zcash_address::ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Invalid address {}: {:?}", address, e)))?;
+    Ok(address.can_receive_as(zcash_protocol::PoolType::TRANSPARENT))
+}
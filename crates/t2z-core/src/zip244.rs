@@ -0,0 +1,262 @@
+//! Intermediate ZIP 244 digests for cross-checking signers.
+//!
+//! A hardware wallet that computes its own sighash (rather than signing the
+//! one from `get_sighash` blindly) has to reproduce every step of ZIP 244's
+//! signature digest. When its result differs, the only useful question is
+//! which step went wrong. `dump_sighash_digests` returns every intermediate
+//! digest for one transparent input so the two implementations can be
+//! compared byte by byte, and doubles as a generator of test vectors.
+//!
+//! Only `SIGHASH_ALL` is covered, as that is all the builder produces.
+//!
+//! For extracted transactions, `compute_auth_digest` and
+//! `compute_transaction_digests` give the ZIP 244 authorizing data
+//! commitment alongside the txid, as explorers and `getrawtransaction`
+//! report them.
+
+use blake2b_simd::{Hash, Params};
+use serde::{Deserialize, Serialize};
+use zcash_primitives::transaction::{Transaction, txid::TxIdDigester};
+use zcash_protocol::consensus::BranchId;
+
+use crate::{Pczt, T2ZError};
+
+const ZCASH_TX_PERSONALIZATION_PREFIX: &[u8; 12] = b"ZcashTxHash_";
+const ZCASH_TRANSPARENT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdTranspaHash";
+const ZCASH_TRANSPARENT_AMOUNTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxTrAmountsHash";
+const ZCASH_TRANSPARENT_SCRIPTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxTrScriptsHash";
+const ZCASH_TRANSPARENT_INPUT_HASH_PERSONALIZATION: &[u8; 16] = b"Zcash___TxInHash";
+const ZCASH_SAPLING_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSaplingHash";
+const ZCASH_ORCHARD_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrchardHash";
+
+/// `SIGHASH_ALL`, the only hash type the builder uses
+const SIGHASH_ALL: u8 = 0x01;
+
+/// Every digest that goes into one transparent input's ZIP 244 sighash.
+/// All values are hex-encoded 32-byte BLAKE2b-256 hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SighashDigests {
+    /// Transparent input the digests are for
+    pub input_index: usize,
+    /// Hash type byte committed to (`SIGHASH_ALL`)
+    pub hash_type: u8,
+    /// Consensus branch ID in the final personalization
+    pub consensus_branch_id: u32,
+    /// T.1 `header_digest`
+    pub header_digest: String,
+    /// S.2a `prevouts_sig_digest`
+    pub prevouts_digest: String,
+    /// S.2b `amounts_sig_digest`
+    pub amounts_digest: String,
+    /// S.2c `scriptpubkeys_sig_digest`
+    pub script_pubkeys_digest: String,
+    /// S.2d `sequence_sig_digest`
+    pub sequence_digest: String,
+    /// S.2e `outputs_sig_digest`
+    pub outputs_digest: String,
+    /// S.2g `txin_sig_digest` for this input
+    pub txin_sig_digest: String,
+    /// S.2 `transparent_sig_digest`
+    pub transparent_sig_digest: String,
+    /// T.3 `sapling_digest`
+    pub sapling_digest: String,
+    /// T.4 `orchard_digest`
+    pub orchard_digest: String,
+    /// The signature hash itself (same as `get_sighash`)
+    pub sighash: String,
+}
+
+/// The two ZIP 244 digests of a transaction, hex-encoded in the reversed
+/// byte order RPCs and explorers display them in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionDigests {
+    /// Transaction ID (commits to the effecting data)
+    pub txid: String,
+    /// Authorizing data commitment (commits to signatures and proofs)
+    pub auth_digest: String,
+}
+
+/// Computes the ZIP 244 authorizing data commitment of the raw transaction
+/// `tx_bytes`, in internal byte order. v4 transactions have no such
+/// commitment and get the all-`0xFF` placeholder ZIP 244 assigns them.
+pub fn compute_auth_digest(tx_bytes: &[u8]) -> Result<[u8; 32], T2ZError> {
+    let transaction = read_transaction(tx_bytes)?;
+    Ok(auth_digest(&transaction))
+}
+
+/// Computes the txid and auth digest of the raw transaction `tx_bytes`.
+pub fn compute_transaction_digests(tx_bytes: &[u8]) -> Result<TransactionDigests, T2ZError> {
+    let transaction = read_transaction(tx_bytes)?;
+    let mut auth_digest = auth_digest(&transaction);
+    auth_digest.reverse();
+    Ok(TransactionDigests {
+        txid: transaction.txid().to_string(),
+        auth_digest: hex::encode(auth_digest),
+    })
+}
+
+/// Parses an extracted transaction
+pub(crate) fn read_transaction(tx_bytes: &[u8]) -> Result<Transaction, T2ZError> {
+    // v5 transactions carry their own branch ID; this one only applies to v4
+    Transaction::read(tx_bytes, BranchId::Nu6)
+        .map_err(|e| T2ZError::InvalidInput(format!("Invalid transaction: {}", e)))
+}
+
+fn auth_digest(transaction: &Transaction) -> [u8; 32] {
+    if transaction.version().has_orchard() {
+        transaction
+            .auth_commitment()
+            .as_bytes()
+            .try_into()
+            .expect("auth digest is 32 bytes")
+    } else {
+        [0xFF; 32]
+    }
+}
+
+/// Computes the ZIP 244 intermediate digests for the transparent input at
+/// `input_index`, ending in the same sighash `get_sighash` returns.
+pub fn dump_sighash_digests(pczt: &Pczt, input_index: usize) -> Result<SighashDigests, T2ZError> {
+    let tx_data = pczt.clone().into_effects().ok_or_else(|| {
+        T2ZError::InvalidInput("Failed to convert PCZT to transaction data".to_string())
+    })?;
+    let txid_parts = tx_data.digest(TxIdDigester);
+
+    let inputs = pczt.transparent().inputs();
+    let input = inputs
+        .get(input_index)
+        .ok_or_else(|| T2ZError::InvalidInput(format!("Invalid input index: {}", input_index)))?;
+    let transparent = txid_parts.transparent_digests.as_ref().ok_or_else(|| {
+        T2ZError::InvalidInput("Transaction has no transparent inputs".to_string())
+    })?;
+
+    let mut amounts = hasher(ZCASH_TRANSPARENT_AMOUNTS_HASH_PERSONALIZATION);
+    let mut scripts = hasher(ZCASH_TRANSPARENT_SCRIPTS_HASH_PERSONALIZATION);
+    for input in inputs {
+        amounts.update(&input.value().to_le_bytes());
+        update_with_script(&mut scripts, input.script_pubkey());
+    }
+    let amounts_digest = amounts.finalize();
+    let script_pubkeys_digest = scripts.finalize();
+
+    let mut txin = hasher(ZCASH_TRANSPARENT_INPUT_HASH_PERSONALIZATION);
+    txin.update(input.prevout_txid());
+    txin.update(&input.prevout_index().to_le_bytes());
+    txin.update(&input.value().to_le_bytes());
+    update_with_script(&mut txin, input.script_pubkey());
+    txin.update(&input.sequence().unwrap_or(u32::MAX).to_le_bytes());
+    let txin_sig_digest = txin.finalize();
+
+    let mut transparent_sig = hasher(ZCASH_TRANSPARENT_HASH_PERSONALIZATION);
+    transparent_sig.update(&[SIGHASH_ALL]);
+    transparent_sig.update(transparent.prevouts_digest.as_bytes());
+    transparent_sig.update(amounts_digest.as_bytes());
+    transparent_sig.update(script_pubkeys_digest.as_bytes());
+    transparent_sig.update(transparent.sequence_digest.as_bytes());
+    transparent_sig.update(transparent.outputs_digest.as_bytes());
+    transparent_sig.update(txin_sig_digest.as_bytes());
+    let transparent_sig_digest = transparent_sig.finalize();
+
+    let sapling_digest = txid_parts
+        .sapling_digest
+        .unwrap_or_else(|| hasher(ZCASH_SAPLING_HASH_PERSONALIZATION).finalize());
+    let orchard_digest = txid_parts
+        .orchard_digest
+        .unwrap_or_else(|| hasher(ZCASH_ORCHARD_HASH_PERSONALIZATION).finalize());
+
+    let consensus_branch_id = u32::from(tx_data.consensus_branch_id());
+    let mut sighash = hasher(&tx_personalization(consensus_branch_id));
+    sighash.update(txid_parts.header_digest.as_bytes());
+    sighash.update(transparent_sig_digest.as_bytes());
+    sighash.update(sapling_digest.as_bytes());
+    sighash.update(orchard_digest.as_bytes());
+
+    Ok(SighashDigests {
+        input_index,
+        hash_type: SIGHASH_ALL,
+        consensus_branch_id,
+        header_digest: to_hex(&txid_parts.header_digest),
+        prevouts_digest: to_hex(&transparent.prevouts_digest),
+        amounts_digest: to_hex(&amounts_digest),
+        script_pubkeys_digest: to_hex(&script_pubkeys_digest),
+        sequence_digest: to_hex(&transparent.sequence_digest),
+        outputs_digest: to_hex(&transparent.outputs_digest),
+        txin_sig_digest: to_hex(&txin_sig_digest),
+        transparent_sig_digest: to_hex(&transparent_sig_digest),
+        sapling_digest: to_hex(&sapling_digest),
+        orchard_digest: to_hex(&orchard_digest),
+        sighash: to_hex(&sighash.finalize()),
+    })
+}
+
+/// Transaction ID (internal byte order) the PCZT will have once extracted.
+///
+/// v5 txids commit only to effecting data, so this is known before any
+/// input is signed.
+pub(crate) fn pczt_txid(pczt: &Pczt) -> Result<[u8; 32], T2ZError> {
+    let tx_data = pczt.clone().into_effects().ok_or_else(|| {
+        T2ZError::InvalidInput("Failed to convert PCZT to transaction data".to_string())
+    })?;
+    if !tx_data.version().has_orchard() {
+        return Err(T2ZError::InvalidInput(
+            "Only v5 transaction IDs can be computed before signing".to_string(),
+        ));
+    }
+    let txid_parts = tx_data.digest(TxIdDigester);
+
+    let mut transparent = hasher(ZCASH_TRANSPARENT_HASH_PERSONALIZATION);
+    if let Some(digests) = &txid_parts.transparent_digests {
+        transparent.update(digests.prevouts_digest.as_bytes());
+        transparent.update(digests.sequence_digest.as_bytes());
+        transparent.update(digests.outputs_digest.as_bytes());
+    }
+    let sapling_digest = txid_parts
+        .sapling_digest
+        .unwrap_or_else(|| hasher(ZCASH_SAPLING_HASH_PERSONALIZATION).finalize());
+    let orchard_digest = txid_parts
+        .orchard_digest
+        .unwrap_or_else(|| hasher(ZCASH_ORCHARD_HASH_PERSONALIZATION).finalize());
+
+    let consensus_branch_id = u32::from(tx_data.consensus_branch_id());
+    let mut txid = hasher(&tx_personalization(consensus_branch_id));
+    txid.update(txid_parts.header_digest.as_bytes());
+    txid.update(transparent.finalize().as_bytes());
+    txid.update(sapling_digest.as_bytes());
+    txid.update(orchard_digest.as_bytes());
+    Ok(txid
+        .finalize()
+        .as_bytes()
+        .try_into()
+        .expect("txid is 32 bytes"))
+}
+
+/// "ZcashTxHash_" followed by the consensus branch ID
+fn tx_personalization(consensus_branch_id: u32) -> [u8; 16] {
+    let mut personalization = [0; 16];
+    personalization[..12].copy_from_slice(ZCASH_TX_PERSONALIZATION_PREFIX);
+    personalization[12..].copy_from_slice(&consensus_branch_id.to_le_bytes());
+    personalization
+}
+
+fn hasher(personalization: &[u8; 16]) -> blake2b_simd::State {
+    Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .to_state()
+}
+
+/// Hashes `script` with its CompactSize length prefix
+fn update_with_script(state: &mut blake2b_simd::State, script: &[u8]) {
+    let len = script.len();
+    match len {
+        0..=0xfc => state.update(&[len as u8]),
+        0xfd..=0xffff => state.update(&[0xfd]).update(&(len as u16).to_le_bytes()),
+        0x1_0000..=0xffff_ffff => state.update(&[0xfe]).update(&(len as u32).to_le_bytes()),
+        _ => state.update(&[0xff]).update(&(len as u64).to_le_bytes()),
+    };
+    state.update(script);
+}
+
+fn to_hex(hash: &Hash) -> String {
+    hex::encode(hash.as_bytes())
+}
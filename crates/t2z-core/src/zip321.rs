@@ -0,0 +1,474 @@
+//! ZIP 321 payment URIs, and test vectors for bindings.
+//!
+//! `parse_payment_uri` reads a `zcash:` URI into a `TransactionRequest`, and
+//! `payment_uri` writes one back. Wallet front-ends often parse URIs
+//! themselves before handing payments to t2z; `test_vectors` returns the
+//! official ZIP 321 vectors plus t2z-specific edge cases, so the Go, Kotlin
+//! and JS test suites can assert that their parsing agrees with this one.
+//!
+//! `TransactionRequest` has nowhere to keep a payment's `message`, so
+//! messages are accepted and dropped. Every payment needs an `amount`: a
+//! URI that leaves it to the payer is rejected. Addresses are checked for
+//! their encoding only; `validate_request` checks them against a network.
+
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use serde::{Deserialize, Serialize};
+use zcash_address::ZcashAddress;
+
+use crate::{AmountFormat, Payment, T2ZError, TransactionRequest, format_zec, parse_zec};
+
+/// The largest `paramindex` ZIP 321 allows
+const MAX_PARAM_INDEX: usize = 9999;
+
+/// Maximum memo size in bytes
+const MAX_MEMO_BYTES: usize = 512;
+
+/// Amounts in URIs: plain decimal ZEC
+const URI_AMOUNT: AmountFormat = AmountFormat {
+    decimal_separator: '.',
+    group_separator: None,
+    min_decimals: 0,
+    with_unit: false,
+};
+
+/// One ZIP 321 test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Zip321Vector {
+    /// What the vector exercises
+    pub name: String,
+    /// The URI to parse
+    pub uri: String,
+    /// What `uri` parses to, or `None` if parsing must fail
+    pub request: Option<TransactionRequest>,
+    /// What `payment_uri` writes for `request` (`None` if `request` is)
+    pub canonical_uri: Option<String>,
+}
+
+/// Parses a ZIP 321 `zcash:` payment URI.
+pub fn parse_payment_uri(uri: &str) -> Result<TransactionRequest, T2ZError> {
+    let rest = uri
+        .get(..6)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("zcash:"))
+        .map(|_| &uri[6..])
+        .ok_or_else(|| T2ZError::InvalidInput("Payment URI must start with zcash:".to_string()))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut params: BTreeMap<usize, BTreeMap<&str, &str>> = BTreeMap::new();
+    if !path.is_empty() {
+        params.entry(0).or_default().insert("address", path);
+    }
+
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').ok_or_else(|| {
+            T2ZError::InvalidInput(format!("Payment URI parameter {:?} has no value", param))
+        })?;
+        let (name, index) = match key.split_once('.') {
+            Some((name, index)) => (name, parse_param_index(index)?),
+            None => (key, 0),
+        };
+
+        match name {
+            "address" | "amount" | "memo" | "label" | "message" => {}
+            _ if name.starts_with("req-") => {
+                return Err(T2ZError::InvalidInput(format!(
+                    "Unsupported required parameter {:?}",
+                    name
+                )));
+            }
+            // Optional parameters this crate doesn't know may be ignored
+            _ => continue,
+        }
+        if params
+            .entry(index)
+            .or_default()
+            .insert(name, value)
+            .is_some()
+        {
+            return Err(T2ZError::InvalidInput(format!(
+                "Payment URI parameter {:?} appears twice",
+                key
+            )));
+        }
+    }
+
+    if params.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "Payment URI has no payments".to_string(),
+        ));
+    }
+
+    let payments = params
+        .into_iter()
+        .map(|(index, params)| parse_payment(index, &params))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(TransactionRequest { payments })
+}
+
+/// Writes a request as a ZIP 321 payment URI.
+///
+/// A single payment goes in the URI path (`zcash:<address>?amount=...`);
+/// several are numbered from 0, the first without a `paramindex`.
+pub fn payment_uri(request: &TransactionRequest) -> Result<String, T2ZError> {
+    if request.payments.is_empty() {
+        return Err(T2ZError::InvalidInput(
+            "Payment request has no payments".to_string(),
+        ));
+    }
+    if request.payments.len() > MAX_PARAM_INDEX + 1 {
+        return Err(T2ZError::InvalidInput(format!(
+            "A payment URI holds at most {} payments",
+            MAX_PARAM_INDEX + 1
+        )));
+    }
+
+    let single = request.payments.len() == 1;
+    let mut uri = String::from("zcash:");
+    let mut params = Vec::new();
+    for (index, payment) in request.payments.iter().enumerate() {
+        let suffix = if index == 0 {
+            String::new()
+        } else {
+            format!(".{}", index)
+        };
+        if single {
+            uri.push_str(&payment.address);
+        } else {
+            params.push(format!("address{}={}", suffix, payment.address));
+        }
+        params.push(format!(
+            "amount{}={}",
+            suffix,
+            format_zec(payment.amount, &URI_AMOUNT)
+        ));
+        if let Some(memo) = &payment.memo {
+            if memo.len() > MAX_MEMO_BYTES {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} memo exceeds {} bytes ({} bytes)",
+                    index,
+                    MAX_MEMO_BYTES,
+                    memo.len()
+                )));
+            }
+            params.push(format!("memo{}={}", suffix, BASE64_URL.encode(memo)));
+        }
+        if let Some(label) = &payment.label {
+            params.push(format!("label{}={}", suffix, percent_encode(label)));
+        }
+    }
+
+    uri.push('?');
+    uri.push_str(&params.join("&"));
+    Ok(uri)
+}
+
+/// The official ZIP 321 test vectors, and t2z's own edge cases.
+///
+/// Each vector's `uri` should parse to `request` (or fail to parse if that
+/// is `None`), and `request` should serialize to `canonical_uri`.
+pub fn test_vectors() -> Vec<Zip321Vector> {
+    const TADDR: &str = "tmEZhbWHTpdKMw5it8YDspUXSMGQyFwovpU";
+    const ZADDR: &str =
+        "ztestsapling10yy2ex5dcqkclhc7z7yrnjq2z6feyjad56ptwlfgmy77dmaqqrl9gyhprdx59qgmsnyfska2kez";
+
+    let payment = |address: &str, amount: u64, memo: Option<&[u8]>, label: Option<&str>| Payment {
+        address: address.to_string(),
+        amount,
+        memo: memo.map(<[u8]>::to_vec),
+        label: label.map(str::to_string),
+    };
+    let valid =
+        |name: &str, uri: String, payments: Vec<Payment>, canonical_uri: String| Zip321Vector {
+            name: name.to_string(),
+            uri,
+            request: Some(TransactionRequest { payments }),
+            canonical_uri: Some(canonical_uri),
+        };
+    let invalid = |name: &str, uri: String| Zip321Vector {
+        name: name.to_string(),
+        uri,
+        request: None,
+        canonical_uri: None,
+    };
+
+    let max_memo = vec![0xf6; MAX_MEMO_BYTES];
+    let long_memo = vec![0xf6; MAX_MEMO_BYTES + 1];
+
+    vec![
+        // From ZIP 321
+        valid(
+            "zip321_single_payment",
+            format!(
+                "zcash:{}?amount=1&memo=VGhpcyBpcyBhIHNpbXBsZSBtZW1vLg&message=Thank%20you%20for%20your%20purchase",
+                ZADDR
+            ),
+            vec![payment(
+                ZADDR,
+                100_000_000,
+                Some(b"This is a simple memo.".as_slice()),
+                None,
+            )],
+            format!(
+                "zcash:{}?amount=1&memo=VGhpcyBpcyBhIHNpbXBsZSBtZW1vLg",
+                ZADDR
+            ),
+        ),
+        valid(
+            "zip321_multiple_payments",
+            format!(
+                "zcash:?address={}&amount=123.456&address.1={}&amount.1=0.789&memo.1=VGhpcyBpcyBhIHVuaWNvZGUgbWVtbyDinKjwn6aE8J-PhvCfjok",
+                TADDR, ZADDR
+            ),
+            vec![
+                payment(TADDR, 12_345_600_000, None, None),
+                payment(
+                    ZADDR,
+                    78_900_000,
+                    Some("This is a unicode memo ✨🦄🏆🎉".as_bytes()),
+                    None,
+                ),
+            ],
+            format!(
+                "zcash:?address={}&amount=123.456&address.1={}&amount.1=0.789&memo.1=VGhpcyBpcyBhIHVuaWNvZGUgbWVtbyDinKjwn6aE8J-PhvCfjok",
+                TADDR, ZADDR
+            ),
+        ),
+        invalid(
+            "zip321_missing_address",
+            format!(
+                "zcash:?amount=3491405.05201255&address.1={}&amount.1=5740296.87793245",
+                ZADDR
+            ),
+        ),
+        invalid(
+            "zip321_duplicate_parameter",
+            format!(
+                "zcash:?address={}&amount=1&amount=2&address.1={}&amount.1=2",
+                TADDR, ZADDR
+            ),
+        ),
+        invalid(
+            "zip321_zero_paramindex",
+            format!("zcash:?address.0={}&amount.0=2", ZADDR),
+        ),
+        invalid(
+            "zip321_memo_to_transparent",
+            format!(
+                "zcash:?address={}&amount=1&memo=VGhpcyBpcyBhIHNpbXBsZSBtZW1vLg",
+                TADDR
+            ),
+        ),
+        invalid(
+            "zip321_too_many_decimals",
+            format!("zcash:{}?amount=0.000000001", TADDR),
+        ),
+        invalid(
+            "zip321_negative_amount",
+            format!("zcash:{}?amount=-1", TADDR),
+        ),
+        // t2z edge cases
+        valid(
+            "max_memo",
+            format!(
+                "zcash:{}?amount=0.0001&memo={}",
+                ZADDR,
+                BASE64_URL.encode(&max_memo)
+            ),
+            vec![payment(ZADDR, 10_000, Some(max_memo.as_slice()), None)],
+            format!(
+                "zcash:{}?amount=0.0001&memo={}",
+                ZADDR,
+                BASE64_URL.encode(&max_memo)
+            ),
+        ),
+        invalid(
+            "memo_too_long",
+            format!(
+                "zcash:{}?amount=0.0001&memo={}",
+                ZADDR,
+                BASE64_URL.encode(&long_memo)
+            ),
+        ),
+        valid(
+            "paramindex_gaps",
+            format!(
+                "zcash:?address.42={}&amount.42=3&address={}&amount=1&address.5={}&amount.5=2",
+                TADDR, TADDR, ZADDR
+            ),
+            vec![
+                payment(TADDR, 100_000_000, None, None),
+                payment(ZADDR, 200_000_000, None, None),
+                payment(TADDR, 300_000_000, None, None),
+            ],
+            format!(
+                "zcash:?address={}&amount=1&address.1={}&amount.1=2&address.2={}&amount.2=3",
+                TADDR, ZADDR, TADDR
+            ),
+        ),
+        valid(
+            "max_paramindex",
+            format!("zcash:?address.9999={}&amount.9999=1", TADDR),
+            vec![payment(TADDR, 100_000_000, None, None)],
+            format!("zcash:{}?amount=1", TADDR),
+        ),
+        invalid(
+            "paramindex_too_large",
+            format!("zcash:?address.10000={}&amount.10000=1", TADDR),
+        ),
+        invalid(
+            "paramindex_leading_zero",
+            format!("zcash:?address.01={}&amount.01=1", TADDR),
+        ),
+        valid(
+            "label_percent_encoding",
+            format!(
+                "ZCASH:{}?amount=21000000&label=Invoice%20%2342%20%2F%20caf%C3%A9&x-unknown=1",
+                TADDR
+            ),
+            vec![payment(
+                TADDR,
+                2_100_000_000_000_000,
+                None,
+                Some("Invoice #42 / café"),
+            )],
+            format!(
+                "zcash:{}?amount=21000000&label=Invoice%20%2342%20%2F%20caf%C3%A9",
+                TADDR
+            ),
+        ),
+        invalid(
+            "amount_above_max_money",
+            format!("zcash:{}?amount=21000000.00000001", TADDR),
+        ),
+        invalid("missing_amount", format!("zcash:{}", TADDR)),
+        invalid(
+            "amount_without_integer_part",
+            format!("zcash:{}?amount=.5", TADDR),
+        ),
+        invalid(
+            "unknown_required_parameter",
+            format!("zcash:{}?amount=1&req-futurefeature=1", TADDR),
+        ),
+        invalid(
+            "invalid_address",
+            "zcash:tmNotAnAddress?amount=1".to_string(),
+        ),
+    ]
+}
+
+/// `paramindex`: 1 to 9999, without leading zeros
+fn parse_param_index(index: &str) -> Result<usize, T2ZError> {
+    let invalid = || T2ZError::InvalidInput(format!("Invalid paramindex {:?}", index));
+    if index.starts_with('0') || !index.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    index
+        .parse()
+        .ok()
+        .filter(|index| (1..=MAX_PARAM_INDEX).contains(index))
+        .ok_or_else(invalid)
+}
+
+fn parse_payment(index: usize, params: &BTreeMap<&str, &str>) -> Result<Payment, T2ZError> {
+    let address = params
+        .get("address")
+        .copied()
+        .ok_or_else(|| T2ZError::InvalidInput(format!("Payment {} has no address", index)))?;
+    let parsed = ZcashAddress::try_from_encoded(address)
+        .map_err(|e| T2ZError::InvalidAddress(format!("Payment {}: {:?}", index, e)))?;
+
+    let amount = params
+        .get("amount")
+        .copied()
+        .ok_or_else(|| T2ZError::InvalidInput(format!("Payment {} has no amount", index)))?;
+    // ZIP 321 amounts are digits, with an optional fraction after a dot
+    let well_formed = match amount.split_once('.') {
+        Some((whole, fraction)) => !whole.is_empty() && !fraction.is_empty(),
+        None => !amount.is_empty(),
+    };
+    if !well_formed || !amount.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return Err(T2ZError::InvalidInput(format!(
+            "Payment {} amount {:?} is not a decimal number",
+            index, amount
+        )));
+    }
+    let amount = parse_zec(amount, &URI_AMOUNT)?;
+
+    let memo = params
+        .get("memo")
+        .map(|memo| {
+            if !parsed.can_receive_memo() {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} is to a transparent address, which cannot receive a memo",
+                    index
+                )));
+            }
+            let memo = BASE64_URL.decode(memo).map_err(|e| {
+                T2ZError::InvalidMemo(format!("Payment {} memo is not base64url: {}", index, e))
+            })?;
+            if memo.len() > MAX_MEMO_BYTES {
+                return Err(T2ZError::InvalidMemo(format!(
+                    "Payment {} memo exceeds {} bytes ({} bytes)",
+                    index,
+                    MAX_MEMO_BYTES,
+                    memo.len()
+                )));
+            }
+            Ok(memo)
+        })
+        .transpose()?;
+
+    let label = params
+        .get("label")
+        .map(|label| percent_decode(label))
+        .transpose()?;
+    if let Some(message) = params.get("message") {
+        percent_decode(message)?;
+    }
+
+    Ok(Payment {
+        address: address.to_string(),
+        amount,
+        memo,
+        label,
+    })
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters and the
+/// sub-delimiters ZIP 321 allows in values
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => out.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'\'' | b'(' | b')' | b'*' | b'+' | b','
+            | b';' | b':' | b'@' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn percent_decode(text: &str) -> Result<String, T2ZError> {
+    let invalid = || T2ZError::InvalidInput(format!("Invalid percent-encoding in {:?}", text));
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).expect("hex digits are ASCII");
+            bytes.push(u8::from_str_radix(hex, 16).expect("two hex digits"));
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
@@ -0,0 +1,29 @@
+/** Which prover was loaded: the native addon, or the `@d4mr/t2z-wasm-prover` fallback */
+export const backend: 'native' | 'wasm';
+
+/** Pre-build the Orchard proving key (~10 seconds, cached afterwards). */
+export function prebuild_proving_key(): void;
+
+/** Check if the proving key has been built and cached. */
+export function is_proving_key_ready(): boolean;
+
+/** Add Orchard proofs to a serialized PCZT. */
+export function prove_pczt(pczt_bytes: Uint8Array): Uint8Array;
+
+/** Add Orchard proofs to a hex-encoded PCZT. */
+export function prove_pczt_hex(pczt_hex: string): string;
+
+/** Estimated peak memory (bytes) proving a serialized PCZT needs, including the proving key if it isn't built yet. */
+export function estimate_proving_memory(pczt_bytes: Uint8Array): bigint;
+
+/** Add Orchard proofs only if the estimated peak memory fits within `max_memory_bytes`. */
+export function prove_pczt_with_budget(pczt_bytes: Uint8Array, max_memory_bytes: bigint): Uint8Array;
+
+/** Add Orchard proofs off the event loop (native backend only; the WASM fallback blocks). */
+export function prove_pczt_async(pczt_bytes: Uint8Array): Promise<Uint8Array>;
+
+/** Whether the prover was compiled with WASM SIMD (always false for the native backend). */
+export function simd_enabled(): boolean;
+
+/** Get the library version */
+export function version(): string;
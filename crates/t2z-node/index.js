@@ -0,0 +1,45 @@
+// Loads the native addon for this platform if one was built or installed,
+// and falls back to the WASM prover (same functions) otherwise.
+
+const fs = require('fs');
+const path = require('path');
+
+function loadNative() {
+  if (process.env.T2Z_PROVER_BACKEND === 'wasm') {
+    return null;
+  }
+  // `napi build --platform` names binaries t2z-node.<platform>-<arch>[-abi].node
+  const prefix = `t2z-node.${process.platform}-${process.arch}`;
+  const binary = fs
+    .readdirSync(__dirname)
+    .find((file) => file.startsWith(prefix) && file.endsWith('.node'));
+  if (!binary) {
+    return null;
+  }
+  try {
+    return require(path.join(__dirname, binary));
+  } catch (e) {
+    // e.g. built against a different libc; the WASM prover still works
+    return null;
+  }
+}
+
+const native = loadNative();
+// `just build-wasm-prover-node` builds the fallback for Node's module loader
+const prover = native || require('@d4mr/t2z-wasm-prover/pkg-node/t2z_wasm_prover.js');
+
+module.exports = {
+  backend: native ? 'native' : 'wasm',
+  prebuild_proving_key: prover.prebuild_proving_key,
+  is_proving_key_ready: prover.is_proving_key_ready,
+  prove_pczt: prover.prove_pczt,
+  prove_pczt_hex: prover.prove_pczt_hex,
+  estimate_proving_memory: prover.estimate_proving_memory,
+  prove_pczt_with_budget: prover.prove_pczt_with_budget,
+  // The WASM prover has no thread pool; it proves on the calling thread
+  prove_pczt_async: native
+    ? native.prove_pczt_async
+    : async (pcztBytes) => prover.prove_pczt(pcztBytes),
+  simd_enabled: prover.simd_enabled,
+  version: prover.version,
+};
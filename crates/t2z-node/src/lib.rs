@@ -0,0 +1,141 @@
+//! T2Z Node - native Orchard prover for Node.js
+//!
+//! Halo 2 proving runs several times faster natively than in WebAssembly.
+//! This addon exposes the same functions as `t2z-wasm-prover`, so Node
+//! users can swap it in without code changes; the `index.js` shim in the
+//! npm package loads it when a binary for the platform is present and falls
+//! back to `@d4mr/t2z-wasm-prover` otherwise:
+//!
+//! ```js
+//! const prover = require('@d4mr/t2z-node');
+//! console.log(prover.backend); // 'native' or 'wasm'
+//! const proved = core.WasmPczt.from_bytes(prover.prove_pczt(pczt.to_bytes()));
+//! ```
+//!
+//! `prove_pczt_async` proves on the libuv thread pool instead of blocking
+//! the event loop. It is only available from the native backend.
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Pre-build the Orchard proving key (~10 seconds, cached afterwards).
+#[napi(js_name = "prebuild_proving_key")]
+pub fn prebuild_proving_key() {
+    t2z_core::load_orchard_proving_key();
+}
+
+/// Check if the proving key has been built and cached.
+#[napi(js_name = "is_proving_key_ready")]
+pub fn is_proving_key_ready() -> bool {
+    t2z_core::is_proving_key_loaded()
+}
+
+/// Add Orchard proofs to a serialized PCZT.
+///
+/// # Arguments
+/// * `pczt_bytes` - Serialized PCZT (e.g. from `WasmPczt.to_bytes()` in the core module)
+///
+/// # Returns
+/// The serialized PCZT with proofs added
+#[napi(js_name = "prove_pczt")]
+pub fn prove_pczt(pczt_bytes: Buffer) -> Result<Buffer> {
+    prove_bytes(&pczt_bytes).map(Buffer::from)
+}
+
+/// Add Orchard proofs to a hex-encoded PCZT (convenience method)
+#[napi(js_name = "prove_pczt_hex")]
+pub fn prove_pczt_hex(pczt_hex: String) -> Result<String> {
+    let pczt = t2z_core::pczt_from_hex(&pczt_hex)
+        .map_err(|e| Error::from_reason(format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved = t2z_core::prove_transaction(pczt)
+        .map_err(|e| Error::from_reason(format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(t2z_core::pczt_to_hex(&proved))
+}
+
+/// Estimate the peak memory (in bytes) proving a serialized PCZT would need.
+///
+/// Includes building the proving key if it isn't cached yet, and is 0 if no
+/// proof is needed.
+#[napi(js_name = "estimate_proving_memory")]
+pub fn estimate_proving_memory(pczt_bytes: Buffer) -> Result<BigInt> {
+    let pczt = t2z_core::parse_pczt(&pczt_bytes)
+        .map_err(|e| Error::from_reason(format!("Failed to parse PCZT: {}", e)))?;
+    Ok(BigInt::from(t2z_core::estimate_pczt_proving_memory(&pczt)))
+}
+
+/// Add Orchard proofs to a serialized PCZT, only if the estimated peak
+/// memory fits within `max_memory_bytes`.
+#[napi(js_name = "prove_pczt_with_budget")]
+pub fn prove_pczt_with_budget(pczt_bytes: Buffer, max_memory_bytes: BigInt) -> Result<Buffer> {
+    let (negative, max_memory_bytes, lossless) = max_memory_bytes.get_u64();
+    if negative || !lossless {
+        return Err(Error::from_reason(
+            "max_memory_bytes must fit in an unsigned 64-bit integer",
+        ));
+    }
+
+    let pczt = t2z_core::parse_pczt(&pczt_bytes)
+        .map_err(|e| Error::from_reason(format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved =
+        t2z_core::prove_transaction_with_budget(pczt, t2z_core::ProvingBudget { max_memory_bytes })
+            .map_err(|e| Error::from_reason(format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(Buffer::from(t2z_core::serialize_pczt(&proved)))
+}
+
+/// Proving on the libuv thread pool, for `prove_pczt_async`
+pub struct ProveTask {
+    pczt_bytes: Vec<u8>,
+}
+
+impl Task for ProveTask {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        prove_bytes(&self.pczt_bytes)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(Buffer::from(output))
+    }
+}
+
+/// Add Orchard proofs to a serialized PCZT without blocking the event loop.
+///
+/// # Returns
+/// A promise resolving to the serialized PCZT with proofs added
+#[napi(js_name = "prove_pczt_async")]
+pub fn prove_pczt_async(pczt_bytes: Buffer) -> AsyncTask<ProveTask> {
+    AsyncTask::new(ProveTask {
+        pczt_bytes: pczt_bytes.to_vec(),
+    })
+}
+
+/// Whether this module was compiled with WASM SIMD (`simd128`).
+///
+/// Always false here; kept so code written against `t2z-wasm-prover` runs
+/// unchanged.
+#[napi(js_name = "simd_enabled")]
+pub fn simd_enabled() -> bool {
+    false
+}
+
+/// Get the library version
+#[napi]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+fn prove_bytes(pczt_bytes: &[u8]) -> Result<Vec<u8>> {
+    let pczt = t2z_core::parse_pczt(pczt_bytes)
+        .map_err(|e| Error::from_reason(format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved = t2z_core::prove_transaction(pczt)
+        .map_err(|e| Error::from_reason(format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(t2z_core::serialize_pczt(&proved))
+}
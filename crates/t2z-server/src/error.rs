@@ -0,0 +1,40 @@
+//! How library errors map onto response codes, shared by the gRPC and
+//! JSON/HTTP front ends so the two agree.
+
+use t2z_core::T2ZError;
+
+/// What kind of failure a `T2ZError` is, from the client's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorClass {
+    /// The request is malformed (gRPC `INVALID_ARGUMENT`, HTTP 400)
+    InvalidArgument,
+    /// The request is well formed but can't be carried out as things stand
+    /// (gRPC `FAILED_PRECONDITION`, HTTP 422)
+    FailedPrecondition,
+    /// A policy refused the request (gRPC `PERMISSION_DENIED`, HTTP 403)
+    PermissionDenied,
+    /// Anything else (gRPC `INTERNAL`, HTTP 500)
+    Internal,
+}
+
+impl From<&T2ZError> for ErrorClass {
+    fn from(e: &T2ZError) -> Self {
+        match e {
+            T2ZError::InvalidInput(_)
+            | T2ZError::InvalidAddress(_)
+            | T2ZError::InvalidMemo(_)
+            | T2ZError::Parse(_)
+            | T2ZError::AmountOutOfRange(_)
+            | T2ZError::ExpiryInPast { .. }
+            | T2ZError::TooManyOutputs { .. } => ErrorClass::InvalidArgument,
+            T2ZError::InsufficientFunds { .. }
+            | T2ZError::ChangeRequired { .. }
+            | T2ZError::InsufficientConfirmations { .. }
+            | T2ZError::MissingSignature { .. }
+            | T2ZError::MissingProof
+            | T2ZError::MemoryBudgetExceeded { .. } => ErrorClass::FailedPrecondition,
+            T2ZError::PolicyViolation(_) => ErrorClass::PermissionDenied,
+            _ => ErrorClass::Internal,
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! gRPC service (`t2z.v1.T2z`, see `proto/t2z.proto`).
+//!
+//! Every call is self-contained: PCZTs and signing sessions travel as bytes
+//! in each request, so the server keeps no state and any number of replicas
+//! can sit behind a load balancer.
+
+use tonic::{Request, Response, Status};
+
+use crate::error::ErrorClass;
+
+use t2z_core::{
+    ExpiryPolicy, Network, Participant, Pczt, ProposalOptions, Role, SignerContribution,
+    SigningSession, T2ZError, TransactionRequest,
+};
+
+/// Generated protobuf types and service traits
+pub mod proto {
+    tonic::include_proto!("t2z.v1");
+}
+
+pub use proto::t2z_server::T2zServer;
+
+/// The `T2z` gRPC service
+#[derive(Debug, Clone, Copy, Default)]
+pub struct T2zService;
+
+impl T2zService {
+    /// Wraps the service for `tonic::transport::Server::add_service`.
+    pub fn into_server(self) -> T2zServer<Self> {
+        T2zServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::t2z_server::T2z for T2zService {
+    async fn propose(
+        &self,
+        request: Request<proto::ProposeRequest>,
+    ) -> Result<Response<proto::PcztMessage>, Status> {
+        let req = request.into_inner();
+        let network = parse_network(req.network())?;
+
+        let inputs = req
+            .inputs
+            .into_iter()
+            .map(|input| t2z_core::TransparentInput {
+                pubkey: input.pubkey,
+                prevout_txid: input.prevout_txid,
+                prevout_index: input.prevout_index,
+                value: input.value,
+                script_pubkey: input.script_pubkey,
+                sequence: input.sequence,
+            })
+            .collect::<Vec<_>>();
+        let request = TransactionRequest {
+            payments: req
+                .payments
+                .into_iter()
+                .map(|payment| t2z_core::Payment {
+                    address: payment.address,
+                    amount: payment.amount,
+                    memo: payment.memo,
+                    label: payment.label,
+                })
+                .collect(),
+        };
+
+        let expiry = match (req.expiry_height, req.expiry_delta) {
+            (Some(_), Some(_)) => {
+                return Err(Status::invalid_argument("Set expiry_height or expiry_delta, not both"));
+            }
+            (Some(height), None) => ExpiryPolicy::AtHeight(height),
+            (None, Some(blocks)) => ExpiryPolicy::AfterBlocks(blocks),
+            (None, None) => ExpiryPolicy::default(),
+        };
+        let options = ProposalOptions::with_expiry(expiry, req.current_height).map_err(status)?;
+
+        let pczt = t2z_core::propose_transaction_with_options(
+            &inputs,
+            request,
+            req.change_address.as_deref(),
+            network,
+            options,
+        )
+        .map_err(status)?;
+        Ok(pczt_response(&pczt))
+    }
+
+    async fn prove(
+        &self,
+        request: Request<proto::PcztMessage>,
+    ) -> Result<Response<proto::PcztMessage>, Status> {
+        #[cfg(feature = "prover")]
+        {
+            let pczt = parse_pczt(&request.into_inner().pczt)?;
+            // Proving takes seconds of CPU; keep it off the async workers
+            let pczt = tokio::task::spawn_blocking(move || t2z_core::prove_transaction(pczt))
+                .await
+                .map_err(|e| Status::internal(format!("Proving task failed: {}", e)))?
+                .map_err(status)?;
+            Ok(pczt_response(&pczt))
+        }
+
+        #[cfg(not(feature = "prover"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented("This server was built without the prover feature"))
+        }
+    }
+
+    async fn get_sighash(
+        &self,
+        request: Request<proto::SighashRequest>,
+    ) -> Result<Response<proto::SighashResponse>, Status> {
+        let req = request.into_inner();
+        let pczt = parse_pczt(&req.pczt)?;
+
+        let sighash = t2z_core::get_sighash(&pczt, req.input_index as usize).map_err(status)?;
+        Ok(Response::new(proto::SighashResponse {
+            sighash: sighash.to_vec(),
+        }))
+    }
+
+    async fn append_signature(
+        &self,
+        request: Request<proto::AppendSignatureRequest>,
+    ) -> Result<Response<proto::PcztMessage>, Status> {
+        let req = request.into_inner();
+        let pczt = parse_pczt(&req.pczt)?;
+        let pubkey: [u8; 33] = req
+            .pubkey
+            .try_into()
+            .map_err(|_| Status::invalid_argument("Public key must be 33 bytes (compressed)"))?;
+
+        let pczt =
+            t2z_core::append_signature(pczt, req.input_index as usize, &pubkey, &req.signature)
+                .map_err(status)?;
+        Ok(pczt_response(&pczt))
+    }
+
+    async fn combine(
+        &self,
+        request: Request<proto::CombineRequest>,
+    ) -> Result<Response<proto::PcztMessage>, Status> {
+        let pczts = request
+            .into_inner()
+            .pczts
+            .iter()
+            .map(|bytes| parse_pczt(bytes))
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let pczt = t2z_core::combine(pczts).map_err(status)?;
+        Ok(pczt_response(&pczt))
+    }
+
+    async fn inspect(
+        &self,
+        request: Request<proto::PcztMessage>,
+    ) -> Result<Response<proto::InspectResponse>, Status> {
+        let pczt = parse_pczt(&request.into_inner().pczt)?;
+
+        let info_json = t2z_core::inspect_pczt_json(&pczt).map_err(status)?;
+        Ok(Response::new(proto::InspectResponse { info_json }))
+    }
+
+    async fn finalize_and_extract(
+        &self,
+        request: Request<proto::PcztMessage>,
+    ) -> Result<Response<proto::TransactionMessage>, Status> {
+        let pczt = parse_pczt(&request.into_inner().pczt)?;
+
+        // Verifying the Orchard proof takes a while; keep it off the async workers
+        let transaction = tokio::task::spawn_blocking(move || t2z_core::finalize_and_extract(pczt))
+            .await
+            .map_err(|e| Status::internal(format!("Finalizing task failed: {}", e)))?
+            .map_err(status)?;
+        Ok(Response::new(proto::TransactionMessage { transaction }))
+    }
+
+    async fn open_session(
+        &self,
+        request: Request<proto::OpenSessionRequest>,
+    ) -> Result<Response<proto::SessionResponse>, Status> {
+        let req = request.into_inner();
+        let pczt = parse_pczt(&req.pczt)?;
+
+        let participants = req
+            .participants
+            .into_iter()
+            .map(|p| {
+                let role = match p.role() {
+                    proto::Role::Signer => Role::Signer,
+                    proto::Role::Prover => Role::Prover,
+                    proto::Role::Observer => Role::Observer,
+                    proto::Role::Unspecified => {
+                        return Err(Status::invalid_argument(format!(
+                            "Participant {} has no role",
+                            p.id
+                        )));
+                    }
+                };
+                Ok(Participant {
+                    id: p.id,
+                    role,
+                    input_indices: p.input_indices,
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let session = SigningSession::new(req.session_id, &pczt, participants).map_err(status)?;
+        Ok(session_response(&session))
+    }
+
+    async fn add_contribution(
+        &self,
+        request: Request<proto::AddContributionRequest>,
+    ) -> Result<Response<proto::SessionResponse>, Status> {
+        let req = request.into_inner();
+        let mut session = SigningSession::from_bytes(&req.session).map_err(status)?;
+        let contribution = SignerContribution::from_bytes(&req.contribution).map_err(status)?;
+
+        session.add_contribution(contribution).map_err(status)?;
+        Ok(session_response(&session))
+    }
+
+    async fn combine_session(
+        &self,
+        request: Request<proto::SessionMessage>,
+    ) -> Result<Response<proto::PcztMessage>, Status> {
+        let session = SigningSession::from_bytes(&request.into_inner().session).map_err(status)?;
+
+        let pczt = session.combine().map_err(status)?;
+        Ok(pczt_response(&pczt))
+    }
+}
+
+fn parse_network(network: proto::Network) -> Result<Network, Status> {
+    match network {
+        proto::Network::Mainnet => Ok(Network::Mainnet),
+        proto::Network::Testnet => Ok(Network::Testnet),
+        proto::Network::Unspecified => Err(Status::invalid_argument("Network is required")),
+    }
+}
+
+fn parse_pczt(bytes: &[u8]) -> Result<Pczt, Status> {
+    t2z_core::parse_pczt(bytes).map_err(status)
+}
+
+fn pczt_response(pczt: &Pczt) -> Response<proto::PcztMessage> {
+    Response::new(proto::PcztMessage {
+        pczt: t2z_core::serialize_pczt(pczt),
+    })
+}
+
+/// The session plus the requests still waiting on a contribution
+fn session_response(session: &SigningSession) -> Response<proto::SessionResponse> {
+    let pending = session.pending_participants();
+    let signing_requests = session
+        .signing_requests()
+        .into_iter()
+        .filter(|r| pending.iter().any(|p| p.id == r.participant_id))
+        .map(|r| r.to_bytes())
+        .collect();
+
+    Response::new(proto::SessionResponse {
+        session: session.to_bytes(),
+        signing_requests,
+        complete: session.is_complete(),
+    })
+}
+
+/// Maps library errors onto gRPC status codes.
+fn status(e: T2ZError) -> Status {
+    let message = e.to_string();
+    match ErrorClass::from(&e) {
+        ErrorClass::InvalidArgument => Status::invalid_argument(message),
+        ErrorClass::FailedPrecondition => Status::failed_precondition(message),
+        ErrorClass::PermissionDenied => Status::permission_denied(message),
+        ErrorClass::Internal => Status::internal(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::t2z_server::T2z;
+
+    #[tokio::test]
+    async fn propose_requires_network() {
+        let result = T2zService
+            .propose(Request::new(proto::ProposeRequest::default()))
+            .await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn malformed_pczt_is_invalid_argument() {
+        let result = T2zService
+            .inspect(Request::new(proto::PcztMessage {
+                pczt: vec![1, 2, 3],
+            }))
+            .await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
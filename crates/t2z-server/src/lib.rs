@@ -0,0 +1,45 @@
+//! T2Z Server - T2Z as a network service
+//!
+//! Runs proposing, proving, signing coordination, and inspection behind a
+//! single hardened service, so backends in any language can use t2z without
+//! embedding the library. PCZTs cross the wire as serialized bytes.
+//!
+//! Two front ends share the same stateless operations:
+//! - `grpc` (default feature): `proto/t2z.proto`, served by the
+//!   `t2z-server` binary on `T2Z_LISTEN_ADDR` (default `127.0.0.1:50051`)
+//! - `rest`: JSON over HTTP described by `openapi.yaml`, served on
+//!   `T2Z_HTTP_ADDR` (default `127.0.0.1:8080`)
+//!
+//! The `relay` feature adds a WebSocket relay (`GET /v1/relay`, also on
+//! `T2Z_HTTP_ADDR`) that combines the PCZTs participants publish and pushes
+//! each update to everyone in the session. Unlike the services above it
+//! keeps sessions in memory, so run a single instance.
+//!
+//! To embed a service in your own server:
+//!
+//! ```ignore
+//! tonic::transport::Server::builder()
+//!     .add_service(t2z_server::grpc::T2zService.into_server())
+//!     .serve(addr)
+//!     .await?;
+//!
+//! let app = axum::Router::new()
+//!     .nest("/t2z", t2z_server::rest::router())
+//!     .merge(t2z_server::relay::router());
+//! ```
+
+// Error classification shared by the services - in separate file
+#[cfg(any(feature = "grpc", feature = "rest"))]
+mod error;
+
+// gRPC service - in separate file
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+// JSON/HTTP adapter - in separate file
+#[cfg(feature = "rest")]
+pub mod rest;
+
+// WebSocket signing relay - in separate file
+#[cfg(feature = "relay")]
+pub mod relay;
@@ -0,0 +1,57 @@
+#[cfg(any(feature = "grpc", feature = "rest", feature = "relay"))]
+use std::net::SocketAddr;
+
+/// gRPC address served when `T2Z_LISTEN_ADDR` is not set
+#[cfg(feature = "grpc")]
+const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:50051";
+
+/// HTTP address served when `T2Z_HTTP_ADDR` is not set
+#[cfg(any(feature = "rest", feature = "relay"))]
+const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:8080";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Build the proving key in the background so the first Prove call
+    // doesn't pay for it
+    #[cfg(feature = "prover")]
+    tokio::task::spawn_blocking(t2z_core::load_orchard_proving_key);
+
+    #[cfg(feature = "grpc")]
+    let grpc = {
+        let addr = listen_addr("T2Z_LISTEN_ADDR", DEFAULT_LISTEN_ADDR)?;
+        eprintln!("t2z-server: gRPC listening on {}", addr);
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(t2z_server::grpc::T2zService.into_server())
+                .serve(addr),
+        )
+    };
+
+    #[cfg(any(feature = "rest", feature = "relay"))]
+    let http = {
+        let app = axum::Router::new();
+        #[cfg(feature = "rest")]
+        let app = app.merge(t2z_server::rest::router());
+        #[cfg(feature = "relay")]
+        let app = app.merge(t2z_server::relay::router());
+
+        let addr = listen_addr("T2Z_HTTP_ADDR", DEFAULT_HTTP_ADDR)?;
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        eprintln!("t2z-server: HTTP listening on {}", addr);
+        tokio::spawn(async move { axum::serve(listener, app).await })
+    };
+
+    #[cfg(feature = "grpc")]
+    grpc.await??;
+    #[cfg(any(feature = "rest", feature = "relay"))]
+    http.await??;
+
+    Ok(())
+}
+
+#[cfg(any(feature = "grpc", feature = "rest", feature = "relay"))]
+fn listen_addr(var: &str, default: &str) -> Result<SocketAddr, std::net::AddrParseError> {
+    std::env::var(var)
+        .unwrap_or_else(|_| default.to_string())
+        .parse()
+}
@@ -0,0 +1,176 @@
+//! Reference WebSocket relay for live multi-party signing (`GET /v1/relay`).
+//!
+//! Speaks `t2z_core::RelayMessage` in binary frames. A client's first
+//! message must be `Join`; the relay answers with the session's combined
+//! PCZT so far, if any. Every `Publish` is merged into the session's PCZT
+//! with the Combiner and the result is sent to all participants (the
+//! publisher included) as an `Update`. A PCZT that does not combine with the
+//! session's is answered with an `Error` to its publisher only.
+//!
+//! Sessions live in memory and are dropped when their last participant
+//! disconnects.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use axum::routing::get;
+use tokio::sync::broadcast;
+
+use t2z_core::{Pczt, RelayMessage, T2ZError};
+
+/// Updates buffered per session before slow participants start skipping
+const SESSION_CHANNEL_CAPACITY: usize = 64;
+
+struct Session {
+    pczt: Option<Pczt>,
+    updates: broadcast::Sender<Vec<u8>>,
+}
+
+/// Relay state shared by all connections
+#[derive(Default)]
+pub struct Relay {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Relay {
+    /// Subscribes to a session, creating it if needed, and returns its
+    /// combined PCZT so far.
+    fn join(&self, session_id: &str) -> (broadcast::Receiver<Vec<u8>>, Option<Vec<u8>>) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let session = sessions.entry(session_id.to_string()).or_insert_with(|| Session {
+            pczt: None,
+            updates: broadcast::channel(SESSION_CHANNEL_CAPACITY).0,
+        });
+        (
+            session.updates.subscribe(),
+            session.pczt.as_ref().map(Pczt::serialize),
+        )
+    }
+
+    /// Combines a published PCZT into the session and broadcasts the result.
+    fn publish(&self, session_id: &str, from: &str, pczt: &[u8]) -> Result<(), T2ZError> {
+        let pczt = t2z_core::parse_pczt(pczt)?;
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| T2ZError::InvalidInput("Unknown session".to_string()))?;
+
+        let combined = match session.pczt.take() {
+            Some(current) => match t2z_core::combine(vec![current.clone(), pczt]) {
+                Ok(combined) => combined,
+                Err(e) => {
+                    session.pczt = Some(current);
+                    return Err(e);
+                }
+            },
+            None => pczt,
+        };
+
+        let update = RelayMessage::Update {
+            from: Some(from.to_string()),
+            pczt: combined.serialize(),
+        };
+        session.pczt = Some(combined);
+        // Nobody listening just means everyone has left
+        let _ = session.updates.send(update.to_bytes());
+        Ok(())
+    }
+
+    /// Drops a session once its last participant has left.
+    fn leave(&self, session_id: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if sessions
+            .get(session_id)
+            .is_some_and(|session| session.updates.receiver_count() == 0)
+        {
+            sessions.remove(session_id);
+        }
+    }
+}
+
+/// The relay route, to serve directly or merge into another router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/v1/relay", get(upgrade))
+        .with_state(Arc::new(Relay::default()))
+}
+
+async fn upgrade(ws: WebSocketUpgrade, State(relay): State<Arc<Relay>>) -> Response {
+    ws.on_upgrade(move |socket| serve(socket, relay))
+}
+
+async fn serve(mut socket: WebSocket, relay: Arc<Relay>) {
+    let (session_id, participant_id) = match socket.recv().await {
+        Some(Ok(Message::Binary(bytes))) => match RelayMessage::from_bytes(&bytes) {
+            Ok(RelayMessage::Join {
+                session_id,
+                participant_id,
+            }) => (session_id, participant_id),
+            _ => {
+                let _ = send_error(&mut socket, "Expected a Join message").await;
+                return;
+            }
+        },
+        _ => return,
+    };
+
+    let (mut updates, current) = relay.join(&session_id);
+    if let Some(pczt) = current {
+        let update = RelayMessage::Update { from: None, pczt };
+        if socket.send(Message::Binary(update.to_bytes())).await.is_err() {
+            drop(updates);
+            relay.leave(&session_id);
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Binary(bytes))) => {
+                    let result = match RelayMessage::from_bytes(&bytes) {
+                        Ok(RelayMessage::Publish { pczt }) => relay
+                            .publish(&session_id, &participant_id, &pczt)
+                            .map_err(|e| e.to_string()),
+                        Ok(_) => Err("Expected a Publish message".to_string()),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    if let Err(message) = result
+                        && send_error(&mut socket, &message).await.is_err()
+                    {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum; text frames are ignored
+                Some(Ok(_)) => {}
+            },
+            update = updates.recv() => match update {
+                Ok(bytes) => {
+                    if socket.send(Message::Binary(bytes)).await.is_err() {
+                        break;
+                    }
+                }
+                // Later updates carry the whole combined PCZT, so skipped
+                // ones lose nothing
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+
+    drop(updates);
+    relay.leave(&session_id);
+}
+
+async fn send_error(socket: &mut WebSocket, message: &str) -> Result<(), axum::Error> {
+    let error = RelayMessage::Error {
+        message: message.to_string(),
+    };
+    socket.send(Message::Binary(error.to_bytes())).await
+}
@@ -0,0 +1,576 @@
+//! JSON/HTTP adapter (see `openapi.yaml`, served at `GET /openapi.yaml`).
+//!
+//! Mirrors the gRPC service for back offices that integrate over HTTP.
+//! Binary fields (PCZTs, keys, signatures, memos, sessions) are hex strings,
+//! as in the WASM and UniFFI bindings. Errors are `{"error": "..."}` with a
+//! 4xx status for bad requests and 5xx for server failures.
+
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorClass;
+
+use t2z_core::{
+    ExpiryPolicy, Network, Participant, Pczt, PcztInfo, ProposalOptions, SignerContribution,
+    SigningSession, T2ZError, TransactionRequest,
+};
+
+/// OpenAPI description of these routes
+const OPENAPI_SPEC: &str = include_str!("../openapi.yaml");
+
+/// Routes of the JSON API, to serve directly or nest into another router.
+pub fn router() -> Router {
+    Router::new()
+        .route("/openapi.yaml", get(openapi))
+        .route("/v1/propose", post(propose))
+        .route("/v1/prove", post(prove))
+        .route("/v1/sighash", post(sighash))
+        .route("/v1/append-signature", post(append_signature))
+        .route("/v1/combine", post(combine))
+        .route("/v1/inspect", post(inspect))
+        .route("/v1/finalize", post(finalize))
+        .route("/v1/sessions/open", post(open_session))
+        .route("/v1/sessions/contribute", post(add_contribution))
+        .route("/v1/sessions/combine", post(combine_session))
+}
+
+/// An error response
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<T2ZError> for ApiError {
+    fn from(e: T2ZError) -> Self {
+        let status = match ErrorClass::from(&e) {
+            ErrorClass::InvalidArgument => StatusCode::BAD_REQUEST,
+            ErrorClass::FailedPrecondition => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorClass::PermissionDenied => StatusCode::FORBIDDEN,
+            ErrorClass::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError {
+            status,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(serde_json::json!({ "error": self.message })),
+        )
+            .into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+pub struct TransparentInputJson {
+    /// Compressed public key (hex, 33 bytes)
+    pub pubkey: String,
+    /// Previous transaction ID (hex, internal byte order)
+    pub prevout_txid: String,
+    pub prevout_index: u32,
+    /// Value in zatoshis, as a number or a decimal string
+    #[serde(with = "t2z_core::amounts::amount")]
+    pub value: u64,
+    /// Script pubkey (hex)
+    pub script_pubkey: String,
+    pub sequence: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentJson {
+    pub address: String,
+    /// Amount in zatoshis, as a number or a decimal string
+    #[serde(with = "t2z_core::amounts::amount")]
+    pub amount: u64,
+    /// Memo (hex, max 512 bytes)
+    pub memo: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProposeJson {
+    pub inputs: Vec<TransparentInputJson>,
+    pub payments: Vec<PaymentJson>,
+    pub change_address: Option<String>,
+    /// "mainnet" or "testnet"
+    pub network: String,
+    pub expiry_height: Option<u32>,
+    pub expiry_delta: Option<u32>,
+    pub current_height: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PcztJson {
+    /// Serialized PCZT (hex)
+    pub pczt: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SighashRequestJson {
+    pub pczt: String,
+    pub input_index: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SighashJson {
+    /// Sighash (hex)
+    pub sighash: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AppendSignatureJson {
+    pub pczt: String,
+    pub input_index: u32,
+    /// Compressed public key (hex, 33 bytes)
+    pub pubkey: String,
+    /// DER signature with the sighash type byte appended (hex)
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CombineJson {
+    pub pczts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionJson {
+    /// Raw transaction (hex)
+    pub transaction: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenSessionJson {
+    pub session_id: String,
+    pub pczt: String,
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddContributionJson {
+    /// Serialized signing session (hex)
+    pub session: String,
+    /// Serialized signer contribution (hex)
+    pub contribution: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionJson {
+    /// Serialized signing session (hex)
+    pub session: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponseJson {
+    /// Updated serialized signing session (hex)
+    pub session: String,
+    /// Serialized signing requests (hex) for participants that still owe a
+    /// contribution
+    pub signing_requests: Vec<String>,
+    pub complete: bool,
+}
+
+async fn openapi() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/yaml")], OPENAPI_SPEC)
+}
+
+async fn propose(Json(req): Json<ProposeJson>) -> ApiResult<PcztJson> {
+    let network = match req.network.as_str() {
+        "mainnet" => Network::Mainnet,
+        "testnet" => Network::Testnet,
+        _ => return Err(ApiError::bad_request("Network must be 'mainnet' or 'testnet'")),
+    };
+
+    let inputs = req
+        .inputs
+        .iter()
+        .map(|input| {
+            Ok(t2z_core::TransparentInput {
+                pubkey: decode_hex(&input.pubkey, "pubkey")?,
+                prevout_txid: decode_hex(&input.prevout_txid, "prevout_txid")?,
+                prevout_index: input.prevout_index,
+                value: input.value,
+                script_pubkey: decode_hex(&input.script_pubkey, "script_pubkey")?,
+                sequence: input.sequence,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let payments = req
+        .payments
+        .into_iter()
+        .map(|payment| {
+            Ok(t2z_core::Payment {
+                memo: payment
+                    .memo
+                    .map(|memo| decode_hex(&memo, "memo"))
+                    .transpose()?,
+                address: payment.address,
+                amount: payment.amount,
+                label: payment.label,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    let expiry = match (req.expiry_height, req.expiry_delta) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::bad_request("Set expiry_height or expiry_delta, not both"));
+        }
+        (Some(height), None) => ExpiryPolicy::AtHeight(height),
+        (None, Some(blocks)) => ExpiryPolicy::AfterBlocks(blocks),
+        (None, None) => ExpiryPolicy::default(),
+    };
+    let options = ProposalOptions::with_expiry(expiry, req.current_height)?;
+
+    let pczt = t2z_core::propose_transaction_with_options(
+        &inputs,
+        TransactionRequest { payments },
+        req.change_address.as_deref(),
+        network,
+        options,
+    )?;
+    Ok(pczt_json(&pczt))
+}
+
+async fn prove(Json(req): Json<PcztJson>) -> ApiResult<PcztJson> {
+    #[cfg(feature = "prover")]
+    {
+        let pczt = parse_pczt(&req.pczt)?;
+        // Proving takes seconds of CPU; keep it off the async workers
+        let pczt = tokio::task::spawn_blocking(move || t2z_core::prove_transaction(pczt))
+            .await
+            .map_err(|e| ApiError {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: format!("Proving task failed: {}", e),
+            })??;
+        Ok(pczt_json(&pczt))
+    }
+
+    #[cfg(not(feature = "prover"))]
+    {
+        let _ = req;
+        Err(ApiError {
+            status: StatusCode::NOT_IMPLEMENTED,
+            message: "This server was built without the prover feature".to_string(),
+        })
+    }
+}
+
+async fn sighash(Json(req): Json<SighashRequestJson>) -> ApiResult<SighashJson> {
+    let pczt = parse_pczt(&req.pczt)?;
+
+    let sighash = t2z_core::get_sighash(&pczt, req.input_index as usize)?;
+    Ok(Json(SighashJson {
+        sighash: hex::encode(sighash),
+    }))
+}
+
+async fn append_signature(Json(req): Json<AppendSignatureJson>) -> ApiResult<PcztJson> {
+    let pczt = parse_pczt(&req.pczt)?;
+    let pubkey: [u8; 33] = decode_hex(&req.pubkey, "pubkey")?
+        .try_into()
+        .map_err(|_| ApiError::bad_request("Public key must be 33 bytes (compressed)"))?;
+    let signature = decode_hex(&req.signature, "signature")?;
+
+    let pczt = t2z_core::append_signature(pczt, req.input_index as usize, &pubkey, &signature)?;
+    Ok(pczt_json(&pczt))
+}
+
+async fn combine(Json(req): Json<CombineJson>) -> ApiResult<PcztJson> {
+    let pczts = req
+        .pczts
+        .iter()
+        .map(|pczt| parse_pczt(pczt))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    let pczt = t2z_core::combine(pczts)?;
+    Ok(pczt_json(&pczt))
+}
+
+async fn inspect(Json(req): Json<PcztJson>) -> ApiResult<PcztInfo> {
+    let pczt = parse_pczt(&req.pczt)?;
+
+    Ok(Json(t2z_core::inspect_pczt(&pczt)?))
+}
+
+async fn finalize(Json(req): Json<PcztJson>) -> ApiResult<TransactionJson> {
+    let pczt = parse_pczt(&req.pczt)?;
+
+    // Verifying the Orchard proof takes a while; keep it off the async workers
+    let transaction = tokio::task::spawn_blocking(move || t2z_core::finalize_and_extract(pczt))
+        .await
+        .map_err(|e| ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Finalizing task failed: {}", e),
+        })??;
+    Ok(Json(TransactionJson {
+        transaction: hex::encode(transaction),
+    }))
+}
+
+async fn open_session(Json(req): Json<OpenSessionJson>) -> ApiResult<SessionResponseJson> {
+    let pczt = parse_pczt(&req.pczt)?;
+
+    let session = SigningSession::new(req.session_id, &pczt, req.participants)?;
+    Ok(session_json(&session))
+}
+
+async fn add_contribution(Json(req): Json<AddContributionJson>) -> ApiResult<SessionResponseJson> {
+    let mut session = SigningSession::from_bytes(&decode_hex(&req.session, "session")?)?;
+    let contribution =
+        SignerContribution::from_bytes(&decode_hex(&req.contribution, "contribution")?)?;
+
+    session.add_contribution(contribution)?;
+    Ok(session_json(&session))
+}
+
+async fn combine_session(Json(req): Json<SessionJson>) -> ApiResult<PcztJson> {
+    let session = SigningSession::from_bytes(&decode_hex(&req.session, "session")?)?;
+
+    let pczt = session.combine()?;
+    Ok(pczt_json(&pczt))
+}
+
+fn decode_hex(value: &str, field: &str) -> Result<Vec<u8>, ApiError> {
+    hex::decode(value).map_err(|e| ApiError::bad_request(format!("Invalid {} hex: {}", field, e)))
+}
+
+fn parse_pczt(pczt_hex: &str) -> Result<Pczt, ApiError> {
+    Ok(t2z_core::parse_pczt(&decode_hex(pczt_hex, "pczt")?)?)
+}
+
+fn pczt_json(pczt: &Pczt) -> Json<PcztJson> {
+    Json(PcztJson {
+        pczt: hex::encode(t2z_core::serialize_pczt(pczt)),
+    })
+}
+
+/// The session plus the requests still waiting on a contribution
+fn session_json(session: &SigningSession) -> Json<SessionResponseJson> {
+    let pending = session.pending_participants();
+    let signing_requests = session
+        .signing_requests()
+        .into_iter()
+        .filter(|r| pending.iter().any(|p| p.id == r.participant_id))
+        .map(|r| hex::encode(r.to_bytes()))
+        .collect();
+
+    Json(SessionResponseJson {
+        session: hex::encode(session.to_bytes()),
+        signing_requests,
+        complete: session.is_complete(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::{Value, json};
+    use t2z_core::TransparentKeypair;
+    use tower::ServiceExt;
+
+    /// POSTs `body` to `path`, returning the status and the JSON response
+    async fn post(path: &str, body: Value) -> (StatusCode, Value) {
+        let response = router()
+            .oneshot(
+                Request::post(path)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    /// A testnet key, at `address_index` of one test seed
+    fn keypair(address_index: u32) -> TransparentKeypair {
+        t2z_core::derive_transparent_keypair(&[1; 32], Network::Testnet, 0, address_index).unwrap()
+    }
+
+    /// A request spending a 200,000 zatoshi UTXO of `payer` to pay `amount`
+    /// to another address, with change back to `payer`
+    fn propose_json(payer: &TransparentKeypair, amount: Value) -> Value {
+        json!({
+            "inputs": [{
+                "pubkey": hex::encode(payer.public_key),
+                "prevout_txid": hex::encode([1u8; 32]),
+                "prevout_index": 0,
+                "value": 200_000,
+                "script_pubkey": hex::encode(&payer.script_pubkey),
+            }],
+            "payments": [{ "address": keypair(1).address, "amount": amount }],
+            "change_address": payer.address,
+            "network": "testnet",
+            "current_height": 3_000_000,
+        })
+    }
+
+    /// The PCZT (hex) paying 100,000 zatoshis from `payer`
+    async fn proposed_pczt(payer: &TransparentKeypair) -> String {
+        let (status, body) = post("/v1/propose", propose_json(payer, json!(100_000))).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        body["pczt"].as_str().unwrap().to_string()
+    }
+
+    /// Signs input 0 of `pczt` through the sighash and append-signature
+    /// routes, as an external signer would
+    async fn signed_pczt(payer: &TransparentKeypair, pczt: &str) -> String {
+        let (status, body) = post("/v1/sighash", json!({ "pczt": pczt, "input_index": 0 })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let sighash: [u8; 32] = hex::decode(body["sighash"].as_str().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        let secret_key = secp256k1::SecretKey::from_slice(&payer.secret_key).unwrap();
+        let mut signature = secp256k1::Secp256k1::signing_only()
+            .sign_ecdsa(&secp256k1::Message::from_digest(sighash), &secret_key)
+            .serialize_der()
+            .to_vec();
+        signature.push(0x01); // SIGHASH_ALL
+
+        let (status, body) = post(
+            "/v1/append-signature",
+            json!({
+                "pczt": pczt,
+                "input_index": 0,
+                "pubkey": hex::encode(payer.public_key),
+                "signature": hex::encode(signature),
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        body["pczt"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn propose_returns_pczt() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        assert!(t2z_core::parse_pczt(&hex::decode(pczt).unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn propose_accepts_amounts_as_strings() {
+        let mut request = propose_json(&keypair(0), json!("100000"));
+        request["inputs"][0]["value"] = json!("200000");
+
+        let (status, body) = post("/v1/propose", request).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    #[tokio::test]
+    async fn propose_rejects_unknown_network() {
+        let mut request = propose_json(&keypair(0), json!(100_000));
+        request["network"] = json!("regtest");
+
+        let (status, body) = post("/v1/propose", request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("Network"));
+    }
+
+    #[tokio::test]
+    async fn propose_beyond_inputs_is_unprocessable() {
+        let (status, _) = post("/v1/propose", propose_json(&keypair(0), json!(1_000_000))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn sighash_rejects_unknown_input() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        let (status, _) = post("/v1/sighash", json!({ "pczt": pczt, "input_index": 5 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn append_signature_rejects_uncompressed_length_pubkey() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        let (status, body) = post(
+            "/v1/append-signature",
+            json!({
+                "pczt": pczt,
+                "input_index": 0,
+                "pubkey": hex::encode([2u8; 32]),
+                "signature": "3001",
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("33 bytes"));
+    }
+
+    #[cfg(feature = "prover")]
+    #[tokio::test]
+    async fn prove_returns_pczt() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        let (status, body) = post("/v1/prove", json!({ "pczt": pczt })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert!(body["pczt"].is_string());
+    }
+
+    #[cfg(feature = "prover")]
+    #[tokio::test]
+    async fn prove_rejects_malformed_pczt() {
+        let (status, _) = post("/v1/prove", json!({ "pczt": "010203" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(not(feature = "prover"))]
+    #[tokio::test]
+    async fn prove_without_prover_is_not_implemented() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        let (status, _) = post("/v1/prove", json!({ "pczt": pczt })).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn finalize_returns_transaction() {
+        let payer = keypair(0);
+        let pczt = signed_pczt(&payer, &proposed_pczt(&payer).await).await;
+
+        let (status, body) = post("/v1/finalize", json!({ "pczt": pczt })).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert!(
+            !hex::decode(body["transaction"].as_str().unwrap())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn finalize_unsigned_pczt_is_unprocessable() {
+        let pczt = proposed_pczt(&keypair(0)).await;
+
+        let (status, body) = post("/v1/finalize", json!({ "pczt": pczt })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["error"].as_str().unwrap().contains("not signed"));
+    }
+}
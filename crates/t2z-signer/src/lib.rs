@@ -0,0 +1,244 @@
+//! T2Z Signer - PCZT signing for embedded devices
+//!
+//! The part of `t2z-core` a hardware wallet or secure element needs to sign
+//! a T2Z PCZT on-device: parsing, the ZIP 244 sighash of each transparent
+//! input, and adding (verified) signatures. There is no proving, building,
+//! or networking, and only `alloc` is required.
+//!
+//! ```ignore
+//! let mut pczt = Pczt::parse(&pczt_bytes)?;
+//! for input_index in 0..pczt.transparent.inputs.len() {
+//!     pczt.sign_transparent_input(input_index, &secret_key)?;
+//! }
+//! let signed_bytes = pczt.serialize()?;
+//! ```
+//!
+//! The signed bytes go back to the coordinator, which finalizes them with
+//! `t2z-core` as if `append_signature` had been called there.
+//!
+//! # Features
+//! - `std` (default): `std::error::Error` for `Error`. Disable it for
+//!   `no_std` targets.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey, ecdsa};
+use serde::{Deserialize, Serialize};
+
+// PCZT layout structs - in separate file
+pub mod shadow;
+
+// ZIP 244 transparent sighash - in separate file
+mod sighash;
+
+use shadow::{GlobalShadow, OrchardBundleShadow, SaplingBundleShadow, TransparentBundleShadow};
+
+/// Magic bytes every serialized PCZT starts with
+const MAGIC_BYTES: &[u8; 4] = b"PCZT";
+
+/// The PCZT format version this crate reads and writes
+const PCZT_VERSION_1: u32 = 1;
+
+/// `SIGHASH_ALL`, the only hash type T2Z transactions use
+pub const SIGHASH_ALL: u8 = 0x01;
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Not a PCZT
+    InvalidFormat,
+    /// A PCZT format version other than 1
+    UnsupportedVersion(u32),
+    /// The PCZT could not be decoded
+    Decode(postcard::Error),
+    /// The PCZT could not be encoded
+    Encode(postcard::Error),
+    /// No transparent input at this index
+    InvalidInputIndex(usize),
+    /// The input asks for a hash type other than `SIGHASH_ALL`
+    UnsupportedSighashType(u8),
+    /// The inputs require lock times of incompatible types
+    InvalidLockTime,
+    /// A value balance does not fit in an `i64`
+    ValueOutOfRange,
+    /// A note ciphertext is too short
+    InvalidCiphertext,
+    InvalidPublicKey,
+    InvalidSecretKey,
+    /// Not a DER-encoded signature followed by a hash type byte
+    InvalidSignature,
+    /// The signature does not verify against the input's sighash
+    VerificationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidFormat => f.write_str("Not a PCZT"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "Unsupported PCZT version: {}", version)
+            }
+            Error::Decode(e) => write!(f, "Failed to deserialize PCZT: {}", e),
+            Error::Encode(e) => write!(f, "Failed to serialize PCZT: {}", e),
+            Error::InvalidInputIndex(index) => write!(f, "Invalid input index: {}", index),
+            Error::UnsupportedSighashType(hash_type) => {
+                write!(f, "Unsupported sighash type: {:#04x}", hash_type)
+            }
+            Error::InvalidLockTime => f.write_str("Inputs require conflicting lock time types"),
+            Error::ValueOutOfRange => f.write_str("Value balance out of range"),
+            Error::InvalidCiphertext => f.write_str("Note ciphertext too short"),
+            Error::InvalidPublicKey => f.write_str("Invalid public key"),
+            Error::InvalidSecretKey => f.write_str("Invalid secret key"),
+            Error::InvalidSignature => f.write_str("Invalid DER signature"),
+            Error::VerificationFailed => f.write_str("Signature verification failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+// ============================================================================
+// PCZT
+// ============================================================================
+
+/// A parsed PCZT, with every field readable and writable
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pczt {
+    pub global: GlobalShadow,
+    pub transparent: TransparentBundleShadow,
+    pub sapling: SaplingBundleShadow,
+    pub orchard: OrchardBundleShadow,
+}
+
+impl Pczt {
+    /// Parses a serialized PCZT.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        // PCZT format: 4 bytes magic + 4 bytes version + postcard data
+        if bytes.len() < 8 || &bytes[..4] != MAGIC_BYTES {
+            return Err(Error::InvalidFormat);
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().expect("4 bytes"));
+        if version != PCZT_VERSION_1 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        postcard::from_bytes(&bytes[8..]).map_err(Error::Decode)
+    }
+
+    /// Serializes the PCZT, for `t2z-core`'s `parse_pczt` (or any PCZT parser).
+    pub fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let data = postcard::to_allocvec(self).map_err(Error::Encode)?;
+
+        let mut bytes = Vec::with_capacity(8 + data.len());
+        bytes.extend_from_slice(MAGIC_BYTES);
+        bytes.extend_from_slice(&PCZT_VERSION_1.to_le_bytes());
+        bytes.extend_from_slice(&data);
+        Ok(bytes)
+    }
+
+    /// Gets the ZIP 244 sighash of a transparent input, the same value
+    /// `t2z-core`'s `get_sighash` returns.
+    pub fn transparent_sighash(&self, input_index: usize) -> Result<[u8; 32], Error> {
+        sighash::transparent_sighash(self, input_index)
+    }
+
+    /// Adds a signature (DER with the sighash type byte appended) to a
+    /// transparent input, after checking it against the input's sighash.
+    pub fn append_signature(
+        &mut self,
+        input_index: usize,
+        pubkey: &[u8; 33],
+        signature: &[u8],
+    ) -> Result<(), Error> {
+        let pk = PublicKey::from_slice(pubkey).map_err(|_| Error::InvalidPublicKey)?;
+
+        let (&hash_type, der_sig) = signature.split_last().ok_or(Error::InvalidSignature)?;
+        if hash_type != SIGHASH_ALL {
+            return Err(Error::UnsupportedSighashType(hash_type));
+        }
+        let sig = ecdsa::Signature::from_der(der_sig).map_err(|_| Error::InvalidSignature)?;
+
+        let message = Message::from_digest(self.transparent_sighash(input_index)?);
+        Secp256k1::verification_only()
+            .verify_ecdsa(&message, &sig, &pk)
+            .map_err(|_| Error::VerificationFailed)?;
+
+        self.transparent.inputs[input_index]
+            .partial_signatures
+            .insert(*pubkey, signature.to_vec());
+        Ok(())
+    }
+
+    /// Signs a transparent input with a 32-byte secp256k1 private key.
+    ///
+    /// Signing is deterministic (RFC 6979), so no randomness source is needed.
+    pub fn sign_transparent_input(
+        &mut self,
+        input_index: usize,
+        secret_key: &[u8; 32],
+    ) -> Result<(), Error> {
+        self.sign(input_index, secret_key, None)
+    }
+
+    /// Signs a transparent input with side-channel countermeasures, given 64
+    /// fresh bytes from the device's random number generator.
+    ///
+    /// The first 32 bytes re-randomize (blind) the secp256k1 context, the
+    /// last 32 are mixed into the nonce (RFC 6979 section 3.6), and the
+    /// signature is verified before it is added, so a fault injected while
+    /// signing cannot leak the key. Signatures are then not reproducible.
+    pub fn sign_transparent_input_hardened(
+        &mut self,
+        input_index: usize,
+        secret_key: &[u8; 32],
+        randomness: &[u8; 64],
+    ) -> Result<(), Error> {
+        self.sign(input_index, secret_key, Some(randomness))
+    }
+
+    fn sign(
+        &mut self,
+        input_index: usize,
+        secret_key: &[u8; 32],
+        randomness: Option<&[u8; 64]>,
+    ) -> Result<(), Error> {
+        let secret_key = SecretKey::from_slice(secret_key).map_err(|_| Error::InvalidSecretKey)?;
+        let message = Message::from_digest(self.transparent_sighash(input_index)?);
+
+        let mut secp = Secp256k1::new();
+        let signature = match randomness {
+            Some(randomness) => {
+                let (seed, aux_rand) = randomness.split_at(32);
+                secp.seeded_randomize(seed.try_into().expect("32 bytes"));
+                let signature = secp.sign_ecdsa_with_noncedata(
+                    &message,
+                    &secret_key,
+                    aux_rand.try_into().expect("32 bytes"),
+                );
+                let pubkey = PublicKey::from_secret_key(&secp, &secret_key);
+                secp.verify_ecdsa(&message, &signature, &pubkey)
+                    .map_err(|_| Error::VerificationFailed)?;
+                signature
+            }
+            None => secp.sign_ecdsa(&message, &secret_key),
+        };
+
+        let pubkey = PublicKey::from_secret_key(&secp, &secret_key).serialize();
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(SIGHASH_ALL);
+
+        self.transparent.inputs[input_index]
+            .partial_signatures
+            .insert(pubkey, signature);
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! ZIP 244 signature digest of a transparent input, computed from the PCZT
+//! layout alone.
+//!
+//! `t2z-core` gets the same value from `zcash_primitives`, which needs
+//! `std`; this is the dependency-free path for devices. Only `SIGHASH_ALL`
+//! is supported, as that is all the builder produces.
+
+use blake2b_simd::{Hash, Params, State};
+
+use crate::shadow::{
+    GlobalShadow, OrchardBundleShadow, SaplingBundleShadow, TransparentBundleShadow,
+    TransparentInputShadow,
+};
+use crate::{Error, Pczt, SIGHASH_ALL};
+
+const ZCASH_TX_PERSONALIZATION_PREFIX: &[u8; 12] = b"ZcashTxHash_";
+const ZCASH_HEADERS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdHeadersHash";
+
+const ZCASH_TRANSPARENT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdTranspaHash";
+const ZCASH_PREVOUTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdPrevoutHash";
+const ZCASH_SEQUENCE_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSequencHash";
+const ZCASH_OUTPUTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOutputsHash";
+const ZCASH_TRANSPARENT_AMOUNTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxTrAmountsHash";
+const ZCASH_TRANSPARENT_SCRIPTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxTrScriptsHash";
+const ZCASH_TRANSPARENT_INPUT_HASH_PERSONALIZATION: &[u8; 16] = b"Zcash___TxInHash";
+
+const ZCASH_SAPLING_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSaplingHash";
+const ZCASH_SAPLING_SPENDS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSSpendsHash";
+const ZCASH_SAPLING_SPENDS_COMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSSpendCHash";
+const ZCASH_SAPLING_SPENDS_NONCOMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSSpendNHash";
+const ZCASH_SAPLING_OUTPUTS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSOutputHash";
+const ZCASH_SAPLING_OUTPUTS_COMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSOutC__Hash";
+const ZCASH_SAPLING_OUTPUTS_MEMOS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSOutM__Hash";
+const ZCASH_SAPLING_OUTPUTS_NONCOMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSOutN__Hash";
+
+const ZCASH_ORCHARD_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrchardHash";
+const ZCASH_ORCHARD_ACTIONS_COMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrcActCHash";
+const ZCASH_ORCHARD_ACTIONS_MEMOS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrcActMHash";
+const ZCASH_ORCHARD_ACTIONS_NONCOMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrcActNHash";
+
+/// `fOverwintered` flag set in the header of every v5 transaction
+const OVERWINTERED_FLAG: u32 = 1 << 31;
+
+/// End of the compact part of a note ciphertext
+const COMPACT_NOTE_SIZE: usize = 52;
+/// End of the memo part of a note ciphertext
+const MEMO_END: usize = COMPACT_NOTE_SIZE + 512;
+
+/// Computes the ZIP 244 sighash of the transparent input at `input_index`.
+pub(crate) fn transparent_sighash(pczt: &Pczt, input_index: usize) -> Result<[u8; 32], Error> {
+    let input = pczt
+        .transparent
+        .inputs
+        .get(input_index)
+        .ok_or(Error::InvalidInputIndex(input_index))?;
+    if input.sighash_type != SIGHASH_ALL {
+        return Err(Error::UnsupportedSighashType(input.sighash_type));
+    }
+
+    let consensus_branch_id = pczt.global.consensus_branch_id;
+    let mut sighash = hasher(&tx_personalization(consensus_branch_id));
+    sighash.update(header_digest(&pczt.global, &pczt.transparent.inputs)?.as_bytes());
+    sighash.update(transparent_sig_digest(&pczt.transparent, input).as_bytes());
+    sighash.update(sapling_digest(&pczt.sapling)?.as_bytes());
+    sighash.update(orchard_digest(&pczt.orchard)?.as_bytes());
+
+    Ok(sighash
+        .finalize()
+        .as_bytes()
+        .try_into()
+        .expect("sighash is 32 bytes"))
+}
+
+/// T.1 `header_digest`
+fn header_digest(global: &GlobalShadow, inputs: &[TransparentInputShadow]) -> Result<Hash, Error> {
+    let mut h = hasher(ZCASH_HEADERS_HASH_PERSONALIZATION);
+    h.update(&(global.tx_version | OVERWINTERED_FLAG).to_le_bytes());
+    h.update(&global.version_group_id.to_le_bytes());
+    h.update(&global.consensus_branch_id.to_le_bytes());
+    h.update(&lock_time(global, inputs)?.to_le_bytes());
+    h.update(&global.expiry_height.to_le_bytes());
+    Ok(h.finalize())
+}
+
+/// The transaction's `nLockTime`, as the pczt crate's Transaction Extractor
+/// will choose it: the highest lock time the inputs require, of a type every
+/// input accepts (heights win if both are), or the fallback if none do.
+fn lock_time(global: &GlobalShadow, inputs: &[TransparentInputShadow]) -> Result<u32, Error> {
+    let time_unsupported = inputs
+        .iter()
+        .any(|i| i.required_height_lock_time.is_some() && i.required_time_lock_time.is_none());
+    let height_unsupported = inputs
+        .iter()
+        .any(|i| i.required_time_lock_time.is_some() && i.required_height_lock_time.is_none());
+    let required_time = inputs
+        .iter()
+        .filter_map(|i| i.required_time_lock_time)
+        .max();
+    let required_height = inputs
+        .iter()
+        .filter_map(|i| i.required_height_lock_time)
+        .max();
+
+    match (required_time, required_height) {
+        (None, None) => Ok(global.fallback_lock_time.unwrap_or(0)),
+        (_, Some(height)) if !height_unsupported => Ok(height),
+        (Some(time), _) if !time_unsupported => Ok(time),
+        _ => Err(Error::InvalidLockTime),
+    }
+}
+
+/// S.2 `transparent_sig_digest` for `SIGHASH_ALL`
+fn transparent_sig_digest(
+    bundle: &TransparentBundleShadow,
+    input: &TransparentInputShadow,
+) -> Hash {
+    let mut prevouts = hasher(ZCASH_PREVOUTS_HASH_PERSONALIZATION);
+    let mut amounts = hasher(ZCASH_TRANSPARENT_AMOUNTS_HASH_PERSONALIZATION);
+    let mut scripts = hasher(ZCASH_TRANSPARENT_SCRIPTS_HASH_PERSONALIZATION);
+    let mut sequences = hasher(ZCASH_SEQUENCE_HASH_PERSONALIZATION);
+    for input in &bundle.inputs {
+        prevouts.update(&input.prevout_txid);
+        prevouts.update(&input.prevout_index.to_le_bytes());
+        amounts.update(&input.value.to_le_bytes());
+        update_with_script(&mut scripts, &input.script_pubkey);
+        sequences.update(&input.sequence.unwrap_or(u32::MAX).to_le_bytes());
+    }
+
+    let mut outputs = hasher(ZCASH_OUTPUTS_HASH_PERSONALIZATION);
+    for output in &bundle.outputs {
+        outputs.update(&output.value.to_le_bytes());
+        update_with_script(&mut outputs, &output.script_pubkey);
+    }
+
+    let mut txin = hasher(ZCASH_TRANSPARENT_INPUT_HASH_PERSONALIZATION);
+    txin.update(&input.prevout_txid);
+    txin.update(&input.prevout_index.to_le_bytes());
+    txin.update(&input.value.to_le_bytes());
+    update_with_script(&mut txin, &input.script_pubkey);
+    txin.update(&input.sequence.unwrap_or(u32::MAX).to_le_bytes());
+
+    let mut h = hasher(ZCASH_TRANSPARENT_HASH_PERSONALIZATION);
+    h.update(&[SIGHASH_ALL]);
+    h.update(prevouts.finalize().as_bytes());
+    h.update(amounts.finalize().as_bytes());
+    h.update(scripts.finalize().as_bytes());
+    h.update(sequences.finalize().as_bytes());
+    h.update(outputs.finalize().as_bytes());
+    h.update(txin.finalize().as_bytes());
+    h.finalize()
+}
+
+/// T.3 `sapling_digest`
+fn sapling_digest(bundle: &SaplingBundleShadow) -> Result<Hash, Error> {
+    let mut h = hasher(ZCASH_SAPLING_HASH_PERSONALIZATION);
+    if bundle.spends.is_empty() && bundle.outputs.is_empty() {
+        return Ok(h.finalize());
+    }
+
+    let mut spends = hasher(ZCASH_SAPLING_SPENDS_HASH_PERSONALIZATION);
+    if !bundle.spends.is_empty() {
+        let mut compact = hasher(ZCASH_SAPLING_SPENDS_COMPACT_HASH_PERSONALIZATION);
+        let mut noncompact = hasher(ZCASH_SAPLING_SPENDS_NONCOMPACT_HASH_PERSONALIZATION);
+        for spend in &bundle.spends {
+            compact.update(&spend.nullifier);
+            noncompact.update(&spend.cv);
+            noncompact.update(&bundle.anchor);
+            noncompact.update(&spend.rk);
+        }
+        spends.update(compact.finalize().as_bytes());
+        spends.update(noncompact.finalize().as_bytes());
+    }
+
+    let mut outputs = hasher(ZCASH_SAPLING_OUTPUTS_HASH_PERSONALIZATION);
+    if !bundle.outputs.is_empty() {
+        let mut compact = hasher(ZCASH_SAPLING_OUTPUTS_COMPACT_HASH_PERSONALIZATION);
+        let mut memos = hasher(ZCASH_SAPLING_OUTPUTS_MEMOS_HASH_PERSONALIZATION);
+        let mut noncompact = hasher(ZCASH_SAPLING_OUTPUTS_NONCOMPACT_HASH_PERSONALIZATION);
+        for output in &bundle.outputs {
+            let (compact_part, memo, rest) = split_ciphertext(&output.enc_ciphertext)?;
+            compact.update(&output.cmu);
+            compact.update(&output.ephemeral_key);
+            compact.update(compact_part);
+            memos.update(memo);
+            noncompact.update(&output.cv);
+            noncompact.update(rest);
+            noncompact.update(&output.out_ciphertext);
+        }
+        outputs.update(compact.finalize().as_bytes());
+        outputs.update(memos.finalize().as_bytes());
+        outputs.update(noncompact.finalize().as_bytes());
+    }
+
+    let value_balance = i64::try_from(bundle.value_sum).map_err(|_| Error::ValueOutOfRange)?;
+    h.update(spends.finalize().as_bytes());
+    h.update(outputs.finalize().as_bytes());
+    h.update(&value_balance.to_le_bytes());
+    Ok(h.finalize())
+}
+
+/// T.4 `orchard_digest`
+fn orchard_digest(bundle: &OrchardBundleShadow) -> Result<Hash, Error> {
+    let mut h = hasher(ZCASH_ORCHARD_HASH_PERSONALIZATION);
+    if bundle.actions.is_empty() {
+        return Ok(h.finalize());
+    }
+
+    let mut compact = hasher(ZCASH_ORCHARD_ACTIONS_COMPACT_HASH_PERSONALIZATION);
+    let mut memos = hasher(ZCASH_ORCHARD_ACTIONS_MEMOS_HASH_PERSONALIZATION);
+    let mut noncompact = hasher(ZCASH_ORCHARD_ACTIONS_NONCOMPACT_HASH_PERSONALIZATION);
+    for action in &bundle.actions {
+        let (compact_part, memo, rest) = split_ciphertext(&action.output.enc_ciphertext)?;
+        compact.update(&action.spend.nullifier);
+        compact.update(&action.output.cmx);
+        compact.update(&action.output.ephemeral_key);
+        compact.update(compact_part);
+        memos.update(memo);
+        noncompact.update(&action.cv_net);
+        noncompact.update(&action.spend.rk);
+        noncompact.update(rest);
+        noncompact.update(&action.output.out_ciphertext);
+    }
+
+    // value_sum is (magnitude, is_negative)
+    let (magnitude, is_negative) = bundle.value_sum;
+    let magnitude = i64::try_from(magnitude).map_err(|_| Error::ValueOutOfRange)?;
+    let value_balance = if is_negative { -magnitude } else { magnitude };
+
+    h.update(compact.finalize().as_bytes());
+    h.update(memos.finalize().as_bytes());
+    h.update(noncompact.finalize().as_bytes());
+    h.update(&[bundle.flags]);
+    h.update(&value_balance.to_le_bytes());
+    h.update(&bundle.anchor);
+    Ok(h.finalize())
+}
+
+/// Splits a note ciphertext into its compact part, memo, and the rest
+fn split_ciphertext(enc_ciphertext: &[u8]) -> Result<(&[u8], &[u8], &[u8]), Error> {
+    if enc_ciphertext.len() < MEMO_END {
+        return Err(Error::InvalidCiphertext);
+    }
+    let (compact, rest) = enc_ciphertext.split_at(COMPACT_NOTE_SIZE);
+    let (memo, rest) = rest.split_at(MEMO_END - COMPACT_NOTE_SIZE);
+    Ok((compact, memo, rest))
+}
+
+/// "ZcashTxHash_" followed by the consensus branch ID
+fn tx_personalization(consensus_branch_id: u32) -> [u8; 16] {
+    let mut personalization = [0; 16];
+    personalization[..12].copy_from_slice(ZCASH_TX_PERSONALIZATION_PREFIX);
+    personalization[12..].copy_from_slice(&consensus_branch_id.to_le_bytes());
+    personalization
+}
+
+fn hasher(personalization: &[u8; 16]) -> State {
+    Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .to_state()
+}
+
+/// Hashes `script` with its CompactSize length prefix
+fn update_with_script(state: &mut State, script: &[u8]) {
+    let len = script.len();
+    match len {
+        0..=0xfc => state.update(&[len as u8]),
+        0xfd..=0xffff => state.update(&[0xfd]).update(&(len as u16).to_le_bytes()),
+        0x1_0000..=0xffff_ffff => state.update(&[0xfe]).update(&(len as u32).to_le_bytes()),
+        _ => state.update(&[0xff]).update(&(len as u64).to_le_bytes()),
+    };
+    state.update(script);
+}
@@ -3,9 +3,11 @@
 //! This crate provides UniFFI bindings for the T2Z library,
 //! enabling Zcash transparent-to-shielded transactions in Go, Kotlin, and Java.
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use t2z_core::{Pczt, T2ZError};
 
+mod task;
+
 // UniFFI scaffolding
 uniffi::setup_scaffolding!();
 
@@ -17,11 +19,42 @@ uniffi::setup_scaffolding!();
 pub enum UniffiError {
     #[error("Error: {msg}")]
     Error { msg: String },
+    /// A deadline passed before the operation finished
+    #[error("Timeout: {msg}")]
+    Timeout { msg: String },
+    /// A transparent input still needs a signature by pubkey (hex public
+    /// key, or its HASH160 if the PCZT doesn't record the key)
+    #[error("Transparent input {input} is not signed (key {pubkey})")]
+    MissingSignature { input: u32, pubkey: String },
+    /// The PCZT has to be proved before it can be extracted
+    #[error("Missing proof: {msg}")]
+    MissingProof { msg: String },
+    /// The Orchard bundle's value balance doesn't match its actions
+    #[error("Orchard value balance is {actual} zatoshis but its actions sum to {expected}")]
+    ValueBalanceMismatch { expected: i64, actual: i64 },
 }
 
 impl From<T2ZError> for UniffiError {
     fn from(e: T2ZError) -> Self {
-        UniffiError::Error { msg: e.to_string() }
+        match e {
+            T2ZError::MissingSignature { input, pubkey } => UniffiError::MissingSignature {
+                input: input as u32,
+                pubkey,
+            },
+            T2ZError::MissingProof => UniffiError::MissingProof { msg: e.to_string() },
+            T2ZError::ValueBalanceMismatch { expected, actual } => {
+                UniffiError::ValueBalanceMismatch { expected, actual }
+            }
+            e => UniffiError::Error { msg: e.to_string() },
+        }
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for UniffiError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        UniffiError::Error {
+            msg: format!("Callback failed: {}", e.reason),
+        }
     }
 }
 
@@ -104,6 +137,17 @@ impl UniffiPayment {
     }
 }
 
+impl From<t2z_core::Payment> for UniffiPayment {
+    fn from(payment: t2z_core::Payment) -> Self {
+        UniffiPayment {
+            address: payment.address,
+            amount: payment.amount,
+            memo: payment.memo.map(hex::encode),
+            label: payment.label,
+        }
+    }
+}
+
 /// Transaction request per ZIP 321 specification
 /// See: https://zips.z.cash/zip-0321
 #[derive(Debug, Clone, uniffi::Record)]
@@ -112,6 +156,18 @@ pub struct UniffiTransactionRequest {
     pub payments: Vec<UniffiPayment>,
 }
 
+impl From<t2z_core::TransactionRequest> for UniffiTransactionRequest {
+    fn from(request: t2z_core::TransactionRequest) -> Self {
+        UniffiTransactionRequest {
+            payments: request
+                .payments
+                .into_iter()
+                .map(UniffiPayment::from)
+                .collect(),
+        }
+    }
+}
+
 impl UniffiTransactionRequest {
     fn to_core(&self) -> Result<t2z_core::TransactionRequest, UniffiError> {
         let payments: Result<Vec<t2z_core::Payment>, UniffiError> =
@@ -146,9 +202,35 @@ impl UniffiExpectedTxOut {
 // UniFFI PCZT Object
 // ============================================================================
 
+/// A PCZT that can be shared between threads
+///
+/// Functions taking a `UniffiPczt` return a new one and leave it unchanged;
+/// the `*_in_place` methods update it without copying, for services applying
+/// many signatures to one large PCZT.
 #[derive(uniffi::Object)]
 pub struct UniffiPczt {
-    inner: Pczt,
+    inner: RwLock<Pczt>,
+}
+
+impl UniffiPczt {
+    fn new(pczt: Pczt) -> Arc<Self> {
+        Arc::new(UniffiPczt {
+            inner: RwLock::new(pczt),
+        })
+    }
+
+    /// Copy of the PCZT, taken without holding the lock afterwards
+    fn snapshot(&self) -> Pczt {
+        self.read().clone()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Pczt> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Pczt> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 #[uniffi::export]
@@ -157,7 +239,7 @@ impl UniffiPczt {
     #[uniffi::constructor]
     pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
         let pczt = t2z_core::parse_pczt(&bytes)?;
-        Ok(Arc::new(UniffiPczt { inner: pczt }))
+        Ok(UniffiPczt::new(pczt))
     }
 
     /// Creates a UniffiPczt from hex string
@@ -171,12 +253,70 @@ impl UniffiPczt {
 
     /// Serializes the PCZT to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        t2z_core::serialize_pczt(&self.inner)
+        t2z_core::serialize_pczt(&self.read())
     }
 
     /// Serializes the PCZT to hex string
     pub fn to_hex(&self) -> String {
-        hex::encode(t2z_core::serialize_pczt(&self.inner))
+        hex::encode(t2z_core::serialize_pczt(&self.read()))
+    }
+
+    /// Copies the PCZT into a new, independent handle
+    pub fn duplicate(&self) -> Arc<Self> {
+        UniffiPczt::new(self.snapshot())
+    }
+
+    /// Signs a transparent input, updating this PCZT
+    ///
+    /// Same as `sign_transparent_input`, without copying the PCZT. On failure
+    /// it is left unchanged.
+    pub fn sign_transparent_input_in_place(
+        &self,
+        input_index: u32,
+        secret_key_hex: String,
+    ) -> Result<(), UniffiError> {
+        let secret_key = parse_secret_key(&secret_key_hex)?;
+        Ok(t2z_core::sign_transparent_input_in_place(
+            &mut self.write(),
+            input_index as usize,
+            &secret_key,
+        )?)
+    }
+
+    /// Appends an external signature to a transparent input, updating this PCZT
+    ///
+    /// Same as `append_signature`, without copying the PCZT. On failure it is
+    /// left unchanged.
+    pub fn append_signature_in_place(
+        &self,
+        input_index: u32,
+        pubkey_hex: String,
+        signature_hex: String,
+    ) -> Result<(), UniffiError> {
+        let (pubkey, signature) = parse_external_signature(&pubkey_hex, &signature_hex)?;
+        Ok(t2z_core::append_signature_in_place(
+            &mut self.write(),
+            input_index as usize,
+            &pubkey,
+            &signature,
+        )?)
+    }
+
+    /// Signs an Orchard spend, updating this PCZT
+    ///
+    /// Same as `sign_orchard_spend`, without copying the PCZT. On failure it
+    /// is left unchanged.
+    pub fn sign_orchard_spend_in_place(
+        &self,
+        action_index: u32,
+        spending_key_hex: String,
+    ) -> Result<(), UniffiError> {
+        let spending_key = parse_spending_key(&spending_key_hex)?;
+        Ok(t2z_core::sign_orchard_spend_in_place(
+            &mut self.write(),
+            action_index as usize,
+            &spending_key,
+        )?)
     }
 }
 
@@ -192,6 +332,9 @@ impl UniffiPczt {
 /// * `change_address` - Optional address for change (transparent or Orchard)
 /// * `network` - "mainnet" or "testnet"
 /// * `expiry_height` - Transaction expiry height
+///
+/// For fee, change, padding, confirmation, and spend-limit options, use
+/// propose_transaction_with_options.
 #[uniffi::export]
 pub fn propose_transaction(
     inputs_to_spend: Vec<UniffiTransparentInput>,
@@ -223,150 +366,3362 @@ pub fn propose_transaction(
         network,
         expiry_height,
     )?;
-    Ok(Arc::new(UniffiPczt { inner: pczt }))
+    Ok(UniffiPczt::new(pczt))
 }
 
-/// Verifies the PCZT matches the original transaction request before signing
-///
-/// Per spec: this may be skipped if the same entity created and is signing the PCZT
-/// with no third-party involvement.
+/// A saved transaction proposal that can be reviewed and materialized later
+#[derive(uniffi::Object)]
+pub struct UniffiProposal {
+    inner: t2z_core::Proposal,
+}
+
+#[uniffi::export]
+impl UniffiProposal {
+    /// Creates a UniffiProposal from raw bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        let proposal = t2z_core::Proposal::from_bytes(&bytes)?;
+        Ok(Arc::new(UniffiProposal { inner: proposal }))
+    }
+
+    /// Serializes the proposal to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// ZIP 317 fee the transaction pays, in zatoshis
+    pub fn fee(&self) -> u64 {
+        self.inner.fee
+    }
+
+    /// Builds the PCZT for this proposal (fails if the fee would differ)
+    pub fn to_pczt(&self) -> Result<Arc<UniffiPczt>, UniffiError> {
+        let pczt = self.inner.to_pczt()?;
+        Ok(UniffiPczt::new(pczt))
+    }
+}
+
+/// Creates a serializable proposal
 ///
-/// # Arguments
-/// * `pczt` - The PCZT to verify
-/// * `transaction_request` - Original ZIP 321 payment request
-/// * `expected_change` - List of expected change outputs (address + amount)
+/// Same arguments as propose_transaction, plus the optional current chain
+/// height used for expiry validation.
 #[uniffi::export]
-pub fn verify_before_signing(
-    pczt: Arc<UniffiPczt>,
+pub fn create_proposal(
+    inputs_to_spend: Vec<UniffiTransparentInput>,
     transaction_request: UniffiTransactionRequest,
-    expected_change: Vec<UniffiExpectedTxOut>,
-) -> Result<(), UniffiError> {
-    let request = transaction_request.to_core()?;
-    let core_expected_change: Vec<t2z_core::ExpectedTxOut> =
-        expected_change.iter().map(|c| c.to_core()).collect();
+    change_address: Option<String>,
+    network: String,
+    expiry_height: u32,
+    current_height: Option<u32>,
+) -> Result<Arc<UniffiProposal>, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        inputs_to_spend.iter().map(|i| i.to_core()).collect();
 
-    t2z_core::verify_before_signing(&pczt.inner, &request, &core_expected_change)?;
-    Ok(())
+    let network = match network.as_str() {
+        "mainnet" => t2z_core::Network::Mainnet,
+        "testnet" => t2z_core::Network::Testnet,
+        _ => {
+            return Err(UniffiError::Error {
+                msg: "Network must be 'mainnet' or 'testnet'".to_string(),
+            })
+        }
+    };
+
+    let proposal = t2z_core::Proposal::new(
+        inputs?,
+        transaction_request.to_core()?,
+        change_address,
+        network,
+        expiry_height,
+        current_height,
+    )?;
+    Ok(Arc::new(UniffiProposal { inner: proposal }))
 }
 
-/// Gets the sighash for a transparent input
+/// Fluent proposal builder
 ///
-/// The returned sighash should be signed externally, then the signature
-/// appended using append_signature.
+/// Each method updates the builder and returns it, so calls can be chained.
+#[derive(uniffi::Object)]
+pub struct UniffiProposalBuilder {
+    inner: std::sync::Mutex<t2z_core::ProposalBuilder>,
+}
+
+impl UniffiProposalBuilder {
+    fn update(
+        self: Arc<Self>,
+        f: impl FnOnce(t2z_core::ProposalBuilder) -> t2z_core::ProposalBuilder,
+    ) -> Arc<Self> {
+        {
+            let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            *inner = f(inner.clone());
+        }
+        self
+    }
+}
+
 #[uniffi::export]
-pub fn get_sighash(pczt: Arc<UniffiPczt>, input_index: u32) -> Result<String, UniffiError> {
-    let sighash = t2z_core::get_sighash(&pczt.inner, input_index as usize)?;
-    Ok(hex::encode(sighash))
+impl UniffiProposalBuilder {
+    /// Starts an empty proposal for "mainnet" or "testnet"
+    #[uniffi::constructor]
+    pub fn new(network: String) -> Result<Arc<Self>, UniffiError> {
+        Ok(Arc::new(UniffiProposalBuilder {
+            inner: std::sync::Mutex::new(t2z_core::ProposalBuilder::new(parse_network(
+                &network,
+            )?)),
+        }))
+    }
+
+    /// Adds a transparent input to spend
+    pub fn add_input(self: Arc<Self>, input: UniffiTransparentInput) -> Result<Arc<Self>, UniffiError> {
+        let input = input.to_core()?;
+        Ok(self.update(|b| b.add_input(input)))
+    }
+
+    /// Pays amount zatoshis to address
+    pub fn pay(self: Arc<Self>, address: String, amount: u64) -> Arc<Self> {
+        self.update(|b| b.pay(address, amount))
+    }
+
+    /// Adds a fully specified payment (memo, label)
+    pub fn add_payment(self: Arc<Self>, payment: UniffiPayment) -> Result<Arc<Self>, UniffiError> {
+        let payment = payment.to_core()?;
+        Ok(self.update(|b| b.add_payment(payment)))
+    }
+
+    /// Sends change to address
+    pub fn change_to(self: Arc<Self>, address: String) -> Arc<Self> {
+        self.update(|b| b.change_to(address))
+    }
+
+    /// Sets the expiry height (0 for no expiry). Required.
+    pub fn expiry(self: Arc<Self>, expiry_height: u32) -> Arc<Self> {
+        self.update(|b| b.expiry(expiry_height))
+    }
+
+    /// Sets the chain tip height used to validate the expiry
+    pub fn current_height(self: Arc<Self>, current_height: u32) -> Arc<Self> {
+        self.update(|b| b.current_height(current_height))
+    }
+
+    /// Sends memos on transparent payments to the Orchard change address
+    /// instead of failing
+    pub fn redirect_transparent_memos(self: Arc<Self>) -> Arc<Self> {
+        self.update(|b| b.redirect_transparent_memos())
+    }
+
+    /// Pays exactly fee zatoshis instead of the ZIP 317 fee
+    pub fn fixed_fee(self: Arc<Self>, fee: u64) -> Arc<Self> {
+        self.update(|b| b.fee(t2z_core::FeeStrategy::fixed(fee)))
+    }
+
+    /// Validates the proposal and builds the PCZT
+    pub fn propose(&self) -> Result<Arc<UniffiPczt>, UniffiError> {
+        let pczt = self
+            .inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .propose()?;
+        Ok(UniffiPczt::new(pczt))
+    }
 }
 
-/// Appends a signature to a transparent input
+/// Options for propose_transaction_with_options (all optional)
 ///
-/// # Arguments
-/// * `pczt` - The PCZT
-/// * `input_index` - Index of the input to sign
-/// * `pubkey_hex` - Compressed public key (33 bytes, hex)
-/// * `signature_hex` - DER-encoded ECDSA signature (hex)
-#[uniffi::export]
-pub fn append_signature(
-    pczt: Arc<UniffiPczt>,
-    input_index: u32,
-    pubkey_hex: String,
-    signature_hex: String,
-) -> Result<Arc<UniffiPczt>, UniffiError> {
-    let pubkey_bytes = hex::decode(&pubkey_hex).map_err(|e| UniffiError::Error {
-        msg: format!("Invalid pubkey hex: {}", e),
-    })?;
+/// Defaults: ZIP 317 fee, expiry 40 blocks after current_height, no branch
+/// pinning or padding, and all leftover value sent to change.
+#[derive(Debug, Clone, Default, uniffi::Record)]
+pub struct UniffiProposalOptions {
+    /// Pay exactly this fee (zatoshis) instead of the ZIP 317 fee
+    #[uniffi(default = None)]
+    pub fixed_fee: Option<u64>,
+    /// Expire at exactly this height (0 for no expiry)
+    #[uniffi(default = None)]
+    pub expiry_height: Option<u32>,
+    /// Expire this many blocks after current_height
+    #[uniffi(default = None)]
+    pub expiry_delta: Option<u32>,
+    /// Current chain tip height
+    #[uniffi(default = None)]
+    pub current_height: Option<u32>,
+    /// Fail unless the transaction is built for this consensus branch ID
+    #[uniffi(default = None)]
+    pub consensus_branch_id: Option<u32>,
+    /// Pad the Orchard bundle to at least this many actions
+    #[uniffi(default = None)]
+    pub min_orchard_actions: Option<u32>,
+    /// Add leftovers below this many zatoshis to the fee instead of creating
+    /// a change output
+    #[uniffi(default = None)]
+    pub dust_threshold: Option<u64>,
+    /// Send exactly split_change_amount zatoshis of the change to this
+    /// transparent address and the rest to the change address
+    #[uniffi(default = None)]
+    pub split_change_address: Option<String>,
+    #[uniffi(default = None)]
+    pub split_change_amount: Option<u64>,
+    /// Send all change to a fresh internal Orchard address of this UFVK
+    /// (change_address must then be None)
+    #[uniffi(default = None)]
+    pub auto_shield_ufvk: Option<String>,
+    /// Split the change into two non-round Orchard notes to the (Orchard)
+    /// change address; see non_round_split_fee for what it costs
+    #[uniffi(default = None)]
+    pub split_non_round_change: Option<bool>,
+    /// What happens to several payments to the same Orchard receiver:
+    /// "keep" (default), "reject", "merge", or "merge_concat_memos"
+    #[uniffi(default = None)]
+    pub duplicate_recipients: Option<String>,
+    /// Most Orchard outputs (counting change) in one transaction (default 32)
+    #[uniffi(default = None)]
+    pub max_orchard_outputs: Option<u32>,
+    /// Most transparent outputs (counting change) in one transaction
+    /// (default 1000)
+    #[uniffi(default = None)]
+    pub max_transparent_outputs: Option<u32>,
+    /// nSequence of inputs that don't set their own (default 0xFFFFFFFF)
+    #[uniffi(default = None)]
+    pub default_sequence: Option<u32>,
+    /// Transaction version to build; only 5 is supported so far
+    #[uniffi(default = None)]
+    pub tx_version: Option<u32>,
+    /// Lock time used when no input requires one (default 0)
+    #[uniffi(default = None)]
+    pub fallback_lock_time: Option<u32>,
+    /// Refuse inputs with fewer confirmations than this; needs
+    /// input_confirmations
+    #[uniffi(default = None)]
+    pub min_confirmations: Option<u32>,
+    /// Confirmations coinbase inputs need (default and at least 100)
+    #[uniffi(default = None)]
+    pub min_coinbase_confirmations: Option<u32>,
+    /// Confirmations of each input, in order. Spending a coinbase input
+    /// makes the transaction fully shielded: transparent payments and change
+    /// are refused.
+    #[uniffi(default = None)]
+    pub input_confirmations: Option<Vec<UniffiInputConfirmations>>,
+    /// Seed all randomness (32 bytes) so the same inputs give a byte-identical
+    /// PCZT. For golden-file tests only: the seed reveals the note secrets.
+    #[uniffi(default = None)]
+    pub rng_seed: Option<Vec<u8>>,
+    /// Build the Orchard bundle against this anchor (32 bytes, the note
+    /// commitment tree root) instead of the empty tree's
+    #[uniffi(default = None)]
+    pub orchard_anchor: Option<Vec<u8>>,
+    /// Refuse requests paying more than this many zatoshis in total
+    /// (excluding change and fee)
+    #[uniffi(default = None)]
+    pub max_payment_total: Option<u64>,
+    /// Refuse transactions paying a fee above this many zatoshis
+    #[uniffi(default = None)]
+    pub max_fee: Option<u64>,
+    /// If set, every recipient must be one of these addresses
+    #[uniffi(default = None)]
+    pub allowlist: Option<Vec<String>>,
+    /// Recipients that are always refused
+    #[uniffi(default = None)]
+    pub denylist: Option<Vec<String>>,
+}
 
-    if pubkey_bytes.len() != 33 {
-        return Err(UniffiError::Error {
-            msg: "Public key must be 33 bytes".to_string(),
-        });
+/// Where one input's UTXO is in the chain
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiInputConfirmations {
+    /// Blocks mined on top of the output, counting its own (0 if unmined)
+    pub confirmations: u32,
+    /// Whether the output is from a coinbase transaction
+    pub coinbase: bool,
+}
+
+impl UniffiProposalOptions {
+    /// The spend limits to pass to to_core, if any are set
+    fn spend_limits(&self) -> Option<t2z_core::SpendLimits> {
+        if self.max_payment_total.is_none()
+            && self.max_fee.is_none()
+            && self.allowlist.is_none()
+            && self.denylist.is_none()
+        {
+            return None;
+        }
+        Some(t2z_core::SpendLimits {
+            max_per_tx: self.max_payment_total,
+            max_fee: self.max_fee,
+            allowlist: self.allowlist.clone(),
+            denylist: self.denylist.clone().unwrap_or_default(),
+        })
     }
 
-    let pubkey: [u8; 33] = pubkey_bytes.try_into().unwrap();
+    fn to_core<'a>(
+        &self,
+        spend_limits: Option<&'a t2z_core::SpendLimits>,
+    ) -> Result<t2z_core::ProposalOptions<'a>, UniffiError> {
+        let expiry = match (self.expiry_height, self.expiry_delta) {
+            (Some(_), Some(_)) => {
+                return Err(UniffiError::Error {
+                    msg: "Set expiry_height or expiry_delta, not both".to_string(),
+                });
+            }
+            (Some(height), None) => t2z_core::ExpiryPolicy::AtHeight(height),
+            (None, Some(blocks)) => t2z_core::ExpiryPolicy::AfterBlocks(blocks),
+            (None, None) => t2z_core::ExpiryPolicy::default(),
+        };
+        let mut options = t2z_core::ProposalOptions {
+            consensus_branch_id: self.consensus_branch_id,
+            min_orchard_actions: self.min_orchard_actions.unwrap_or(0) as usize,
+            policy: t2z_core::ProposalPolicy {
+                address_screen: None,
+                spend_limits,
+            },
+            ..t2z_core::ProposalOptions::with_expiry(expiry, self.current_height)?
+        };
 
-    let signature_bytes = hex::decode(&signature_hex).map_err(|e| UniffiError::Error {
-        msg: format!("Invalid signature hex: {}", e),
-    })?;
+        if let Some(fee) = self.fixed_fee {
+            options.fee_strategy = t2z_core::FeeStrategy::fixed(fee);
+        }
+        options.change = match (
+            self.dust_threshold,
+            &self.split_change_address,
+            self.split_change_amount,
+            &self.auto_shield_ufvk,
+            self.split_non_round_change.unwrap_or(false),
+        ) {
+            (None, None, None, None, false) => t2z_core::ChangePolicy::Send,
+            (Some(threshold), None, None, None, false) => {
+                t2z_core::ChangePolicy::DustToFee { threshold }
+            }
+            (None, Some(address), Some(amount), None, false) => {
+                t2z_core::ChangePolicy::SplitTransparent {
+                    transparent_address: address.clone(),
+                    transparent_amount: amount,
+                }
+            }
+            (None, None, None, Some(ufvk), false) => {
+                t2z_core::ChangePolicy::AutoShield { ufvk: ufvk.clone() }
+            }
+            (None, None, None, None, true) => t2z_core::ChangePolicy::SplitNonRound,
+            _ => {
+                return Err(UniffiError::Error {
+                    msg: "Set one of dust_threshold, split_change_address with \
+                          split_change_amount, auto_shield_ufvk, or split_non_round_change"
+                        .to_string(),
+                });
+            }
+        };
+        if let Some(max) = self.max_orchard_outputs {
+            options.output_limits.max_orchard_outputs = max as usize;
+        }
+        if let Some(max) = self.max_transparent_outputs {
+            options.output_limits.max_transparent_outputs = max as usize;
+        }
+        if let Some(mode) = &self.duplicate_recipients {
+            options.duplicate_recipients = match mode.as_str() {
+                "keep" => t2z_core::DuplicateRecipients::Keep,
+                "reject" => t2z_core::DuplicateRecipients::Reject,
+                "merge" => t2z_core::DuplicateRecipients::Merge,
+                "merge_concat_memos" => t2z_core::DuplicateRecipients::MergeConcatMemos,
+                _ => {
+                    return Err(UniffiError::Error {
+                        msg: "duplicate_recipients must be 'keep', 'reject', 'merge', or \
+                              'merge_concat_memos'"
+                            .to_string(),
+                    });
+                }
+            };
+        }
+        options.tx_defaults.sequence = self.default_sequence;
+        options.tx_defaults.fallback_lock_time = self.fallback_lock_time;
+        options.tx_defaults.version = match self.tx_version {
+            None | Some(5) => t2z_core::TxVersion::V5,
+            Some(version) => {
+                return Err(UniffiError::Error {
+                    msg: format!("Unsupported transaction version {}", version),
+                });
+            }
+        };
+        if self.min_confirmations.is_some() || self.min_coinbase_confirmations.is_some() {
+            let defaults = t2z_core::ConfirmationPolicy::default();
+            options.confirmations = Some(t2z_core::ConfirmationPolicy {
+                min_confirmations: self.min_confirmations.unwrap_or(defaults.min_confirmations),
+                min_coinbase_confirmations: self
+                    .min_coinbase_confirmations
+                    .unwrap_or(defaults.min_coinbase_confirmations),
+            });
+        }
+        if let Some(inputs) = &self.input_confirmations {
+            options.input_confirmations = inputs
+                .iter()
+                .map(|input| t2z_core::InputConfirmations {
+                    confirmations: input.confirmations,
+                    coinbase: input.coinbase,
+                })
+                .collect();
+        }
+        if let Some(seed) = &self.rng_seed {
+            let seed: [u8; 32] = seed.as_slice().try_into().map_err(|_| UniffiError::Error {
+                msg: "rng_seed must be 32 bytes".to_string(),
+            })?;
+            options.rng_seed = Some(seed);
+        }
+        if let Some(anchor) = &self.orchard_anchor {
+            let anchor: [u8; 32] =
+                anchor
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| UniffiError::Error {
+                        msg: "orchard_anchor must be 32 bytes".to_string(),
+                    })?;
+            options.orchard_anchor = Some(anchor);
+        }
 
-    let signed = t2z_core::append_signature(
-        pczt.inner.clone(),
-        input_index as usize,
-        &pubkey,
-        &signature_bytes,
-    )?;
-    Ok(Arc::new(UniffiPczt { inner: signed }))
+        Ok(options)
+    }
 }
 
-/// Proves a transaction (builds proving key automatically, ~10 seconds first call)
+/// Proposes a transaction with fee, expiry, padding, and change options
 ///
-/// This uses Halo 2, which requires NO external downloads or trusted setup.
-/// The proving key is built programmatically and cached for subsequent calls.
+/// Takes the same arguments as propose_transaction, with the expiry coming
+/// from options.
 #[uniffi::export]
-pub fn prove_transaction(pczt: Arc<UniffiPczt>) -> Result<Arc<UniffiPczt>, UniffiError> {
-    let proved = t2z_core::prove_transaction(pczt.inner.clone())?;
-    Ok(Arc::new(UniffiPczt { inner: proved }))
+pub fn propose_transaction_with_options(
+    inputs_to_spend: Vec<UniffiTransparentInput>,
+    transaction_request: UniffiTransactionRequest,
+    change_address: Option<String>,
+    network: String,
+    options: UniffiProposalOptions,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        inputs_to_spend.iter().map(|i| i.to_core()).collect();
+    let request = transaction_request.to_core()?;
+    let spend_limits = options.spend_limits();
+
+    let pczt = t2z_core::propose_transaction_with_options(
+        &inputs?,
+        request,
+        change_address.as_deref(),
+        parse_network(&network)?,
+        options.to_core(spend_limits.as_ref())?,
+    )?;
+
+    Ok(UniffiPczt::new(pczt))
+}
+
+/// A recipient of a payment template (a payment without an amount)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiTemplateRecipient {
+    pub address: String,
+    /// Optional memo (hex encoded, max 512 bytes)
+    pub memo: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Recipients of a recurring payment, defined once and proposed each cycle
+#[derive(uniffi::Object)]
+pub struct UniffiPaymentTemplate {
+    inner: t2z_core::PaymentTemplate,
 }
 
-/// Signs a transparent input with the provided private key
 #[uniffi::export]
-pub fn sign_transparent_input(
-    pczt: Arc<UniffiPczt>,
-    input_index: u32,
-    secret_key_hex: String,
-) -> Result<Arc<UniffiPczt>, UniffiError> {
-    let secret_key_bytes = hex::decode(&secret_key_hex).map_err(|e| UniffiError::Error {
-        msg: format!("Invalid secret key hex: {}", e),
-    })?;
+impl UniffiPaymentTemplate {
+    /// Creates a template, rejecting invalid recipients up front
+    #[uniffi::constructor]
+    pub fn new(
+        network: String,
+        recipients: Vec<UniffiTemplateRecipient>,
+        change_address: Option<String>,
+    ) -> Result<Arc<Self>, UniffiError> {
+        let recipients = recipients
+            .into_iter()
+            .map(|r| {
+                let memo = match r.memo {
+                    Some(memo_hex) => Some(hex::decode(&memo_hex).map_err(|e| {
+                        UniffiError::Error {
+                            msg: format!("Invalid memo hex: {}", e),
+                        }
+                    })?),
+                    None => None,
+                };
+                Ok(t2z_core::TemplateRecipient {
+                    address: r.address,
+                    memo,
+                    label: r.label,
+                })
+            })
+            .collect::<Result<Vec<_>, UniffiError>>()?;
 
-    if secret_key_bytes.len() != 32 {
-        return Err(UniffiError::Error {
-            msg: "Secret key must be 32 bytes".to_string(),
-        });
+        let inner =
+            t2z_core::PaymentTemplate::new(parse_network(&network)?, recipients, change_address)?;
+        Ok(Arc::new(UniffiPaymentTemplate { inner }))
     }
 
-    let mut secret_key = [0u8; 32];
-    secret_key.copy_from_slice(&secret_key_bytes);
+    /// Proposes one cycle, paying `amounts[i]` zatoshis to recipient `i`
+    pub fn instantiate(
+        &self,
+        amounts: Vec<u64>,
+        inputs_to_spend: Vec<UniffiTransparentInput>,
+        options: UniffiProposalOptions,
+    ) -> Result<Arc<UniffiPczt>, UniffiError> {
+        let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+            inputs_to_spend.iter().map(|i| i.to_core()).collect();
+        let spend_limits = options.spend_limits();
 
-    let signed =
-        t2z_core::sign_transparent_input(pczt.inner.clone(), input_index as usize, &secret_key)?;
-    Ok(Arc::new(UniffiPczt { inner: signed }))
+        let pczt = t2z_core::instantiate(
+            &self.inner,
+            &amounts,
+            &inputs?,
+            options.to_core(spend_limits.as_ref())?,
+        )?;
+        Ok(UniffiPczt::new(pczt))
+    }
 }
 
-/// Combines multiple PCZTs into one
-#[uniffi::export]
-pub fn combine_pczts(pczt_list: Vec<Arc<UniffiPczt>>) -> Result<Arc<UniffiPczt>, UniffiError> {
-    let pczts: Vec<Pczt> = pczt_list.iter().map(|p| p.inner.clone()).collect();
-    let combined = t2z_core::combine(pczts)?;
-    Ok(Arc::new(UniffiPczt { inner: combined }))
+/// One pool payout: an Orchard address and its share of the reward
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiPayoutSplit {
+    pub address: String,
+    /// Weight relative to the other payouts (e.g. shares, or basis points)
+    pub weight: u64,
 }
 
-/// Finalizes the PCZT and extracts the transaction bytes
+/// Shields coinbase UTXOs to pool payout addresses in one transaction
+///
+/// Everything the inputs hold, less the ZIP 317 fee, is split across the
+/// payouts by weight, with no change. options supplies expiry, padding, and
+/// confirmation settings.
 #[uniffi::export]
-pub fn finalize_and_extract(pczt: Arc<UniffiPczt>) -> Result<Vec<u8>, UniffiError> {
-    let tx_bytes = t2z_core::finalize_and_extract(pczt.inner.clone())?;
-    Ok(tx_bytes)
+pub fn propose_coinbase_shielding(
+    coinbase_utxos: Vec<UniffiTransparentInput>,
+    payout_splits: Vec<UniffiPayoutSplit>,
+    network: String,
+    options: UniffiProposalOptions,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        coinbase_utxos.iter().map(|i| i.to_core()).collect();
+    let splits: Vec<(String, u64)> = payout_splits
+        .into_iter()
+        .map(|split| (split.address, split.weight))
+        .collect();
+    let spend_limits = options.spend_limits();
+
+    let pczt = t2z_core::propose_coinbase_shielding(
+        &inputs?,
+        &splits,
+        parse_network(&network)?,
+        options.to_core(spend_limits.as_ref())?,
+    )?;
+    Ok(UniffiPczt::new(pczt))
 }
 
-/// Finalizes the PCZT and extracts the transaction as hex string
+/// Proposes a child transaction spending the transparent change of parent
+/// before it confirms
+///
+/// parent may be signed or not (v5 txids do not cover signatures);
+/// change_pubkey_hex is the public key of the parent's change address. The
+/// child may not expire later than the parent.
 #[uniffi::export]
-pub fn finalize_and_extract_hex(pczt: Arc<UniffiPczt>) -> Result<String, UniffiError> {
-    let tx_bytes = finalize_and_extract(pczt)?;
-    Ok(hex::encode(tx_bytes))
+pub fn spend_unconfirmed_change(
+    parent: Arc<UniffiPczt>,
+    change_pubkey_hex: String,
+    transaction_request: UniffiTransactionRequest,
+    change_address: Option<String>,
+    network: String,
+    options: UniffiProposalOptions,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let parent = parent.read();
+    spend_change_of(
+        t2z_core::ParentTransaction::Pczt(&*parent),
+        &change_pubkey_hex,
+        transaction_request,
+        change_address,
+        &network,
+        options,
+    )
 }
 
-/// Check if the proving key has been built and cached
+/// Like spend_unconfirmed_change, for a parent given as the raw transaction
+/// that was broadcast
 #[uniffi::export]
-pub fn is_proving_key_ready() -> bool {
-    t2z_core::is_proving_key_loaded()
+pub fn spend_unconfirmed_change_from_tx(
+    parent_tx_bytes: Vec<u8>,
+    change_pubkey_hex: String,
+    transaction_request: UniffiTransactionRequest,
+    change_address: Option<String>,
+    network: String,
+    options: UniffiProposalOptions,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    spend_change_of(
+        t2z_core::ParentTransaction::Transaction(&parent_tx_bytes),
+        &change_pubkey_hex,
+        transaction_request,
+        change_address,
+        &network,
+        options,
+    )
 }
 
-/// Pre-build the Orchard proving key
+fn spend_change_of(
+    parent: t2z_core::ParentTransaction<'_>,
+    change_pubkey_hex: &str,
+    transaction_request: UniffiTransactionRequest,
+    change_address: Option<String>,
+    network: &str,
+    options: UniffiProposalOptions,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let change_pubkey = parse_pubkey(change_pubkey_hex)?;
+    let spend_limits = options.spend_limits();
+    let pczt = t2z_core::spend_unconfirmed_change(
+        parent,
+        &change_pubkey,
+        transaction_request.to_core()?,
+        change_address.as_deref(),
+        parse_network(network)?,
+        options.to_core(spend_limits.as_ref())?,
+    )?;
+    Ok(UniffiPczt::new(pczt))
+}
+
+/// Verifies the PCZT matches the original transaction request before signing
 ///
-/// Call this at application startup to avoid blocking during transaction proving.
+/// Per spec: this may be skipped if the same entity created and is signing the PCZT
+/// with no third-party involvement.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to verify
+/// * `transaction_request` - Original ZIP 321 payment request
+/// * `expected_change` - List of expected change outputs (address + amount)
 #[uniffi::export]
-pub fn prebuild_proving_key() {
-    t2z_core::load_orchard_proving_key();
+pub fn verify_before_signing(
+    pczt: Arc<UniffiPczt>,
+    transaction_request: UniffiTransactionRequest,
+    expected_change: Vec<UniffiExpectedTxOut>,
+) -> Result<(), UniffiError> {
+    let request = transaction_request.to_core()?;
+    let core_expected_change: Vec<t2z_core::ExpectedTxOut> =
+        expected_change.iter().map(|c| c.to_core()).collect();
+
+    t2z_core::verify_before_signing(&pczt.read(), &request, &core_expected_change)?;
+    Ok(())
+}
+
+/// Organization-specific check run by `verify_before_signing_with_rule`
+///
+/// Implemented in the host language.
+#[uniffi::export(callback_interface)]
+pub trait SigningRule: Send + Sync {
+    /// Returns a reason to refuse signing, or None to allow it
+    ///
+    /// `pczt_info_json` is the PCZT as `inspect_pczt_json` describes it; the
+    /// request's recipients and amounts have been matched against it (memos
+    /// are encrypted in the PCZT and only the request's are available).
+    fn check(
+        &self,
+        pczt_info_json: String,
+        transaction_request: UniffiTransactionRequest,
+    ) -> Option<String>;
+}
+
+/// `verify_before_signing`, then a host-language rule
+///
+/// Fails with a policy violation carrying the rule's reason if it refuses.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to verify
+/// * `transaction_request` - Original ZIP 321 payment request
+/// * `expected_change` - List of expected change outputs (address + amount)
+/// * `rule` - Host-language signing rule
+#[uniffi::export]
+pub fn verify_before_signing_with_rule(
+    pczt: Arc<UniffiPczt>,
+    transaction_request: UniffiTransactionRequest,
+    expected_change: Vec<UniffiExpectedTxOut>,
+    rule: Box<dyn SigningRule>,
+) -> Result<(), UniffiError> {
+    let request = transaction_request.to_core()?;
+    let core_expected_change: Vec<t2z_core::ExpectedTxOut> =
+        expected_change.iter().map(|c| c.to_core()).collect();
+
+    let pczt = pczt.read();
+    let pczt_info_json = t2z_core::inspect_pczt_json(&pczt)?;
+    let mut rules = t2z_core::SigningRules::new();
+    rules.register(
+        "host",
+        move |_: &t2z_core::PcztInfo, _: &t2z_core::TransactionRequest| match rule
+            .check(pczt_info_json.clone(), transaction_request.clone())
+        {
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        },
+    );
+
+    t2z_core::verify_before_signing_with_rules(&pczt, &request, &core_expected_change, &rules)?;
+    Ok(())
+}
+
+/// Summarizes a PCZT (inputs, outputs, fee, signing and proving status) as
+/// JSON conforming to `pczt_info_json_schema()`
+#[uniffi::export]
+pub fn inspect_pczt_json(pczt: Arc<UniffiPczt>) -> Result<String, UniffiError> {
+    Ok(t2z_core::inspect_pczt_json(&pczt.read())?)
+}
+
+/// `inspect_pczt_json`, with each Orchard output the OVK can decrypt
+/// described under `decrypted` (value, recipient, and memo as encrypted)
+///
+/// # Arguments
+/// * `pczt` - The PCZT to inspect
+/// * `ovk_hex` - Orchard outgoing viewing key (32 bytes, hex)
+#[uniffi::export]
+pub fn inspect_pczt_json_with_ovk(
+    pczt: Arc<UniffiPczt>,
+    ovk_hex: String,
+) -> Result<String, UniffiError> {
+    let ovk: [u8; 32] = hex::decode(&ovk_hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| UniffiError::Error {
+            msg: "OVK must be 32 bytes of hex".to_string(),
+        })?;
+    let info = t2z_core::inspect_pczt_with_ovk(&pczt.read(), &ovk)?;
+    Ok(serde_json::to_string(&info).expect("PCZT info serializes to JSON"))
+}
+
+/// Signing progress of one transparent input
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiInputSigningStatus {
+    pub index: u32,
+    /// "p2pkh", "p2sh_multisig" or "other"
+    pub kind: String,
+    /// Signer IDs (see signer_id) that can sign the input
+    pub signers: Vec<String>,
+    /// Signer IDs that have signed it
+    pub signed_by: Vec<String>,
+    /// Signatures the input needs (0 if unknown)
+    pub signatures_required: u32,
+    pub complete: bool,
+}
+
+/// Signing progress of a PCZT
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiPcztStatus {
+    pub inputs: Vec<UniffiInputSigningStatus>,
+    /// Signer ID -> input indices it can still sign
+    pub pending: std::collections::HashMap<String, Vec<u32>>,
+    pub all_inputs_signed: bool,
+    pub has_orchard_proofs: bool,
+}
+
+/// Reports which transparent inputs are signed and which signers still owe
+/// signatures
+#[uniffi::export]
+pub fn pczt_status(pczt: Arc<UniffiPczt>) -> Result<UniffiPcztStatus, UniffiError> {
+    let status = t2z_core::pczt_status(&pczt.read())?;
+    Ok(UniffiPcztStatus {
+        inputs: status
+            .inputs
+            .into_iter()
+            .map(|input| UniffiInputSigningStatus {
+                index: input.index as u32,
+                kind: match input.kind {
+                    t2z_core::InputScriptKind::P2pkh => "p2pkh",
+                    t2z_core::InputScriptKind::P2shMultisig => "p2sh_multisig",
+                    t2z_core::InputScriptKind::Other => "other",
+                }
+                .to_string(),
+                signers: input.signers,
+                signed_by: input.signed_by,
+                signatures_required: input.signatures_required as u32,
+                complete: input.complete,
+            })
+            .collect(),
+        pending: status
+            .pending
+            .into_iter()
+            .map(|(signer, inputs)| (signer, inputs.into_iter().map(|i| i as u32).collect()))
+            .collect(),
+        all_inputs_signed: status.all_inputs_signed,
+        has_orchard_proofs: status.has_orchard_proofs,
+    })
+}
+
+/// The signer ID pczt_status uses for a compressed public key
+#[uniffi::export]
+pub fn signer_id(pubkey: Vec<u8>) -> Result<String, UniffiError> {
+    Ok(t2z_core::signer_id(&pubkey)?)
+}
+
+/// Renders a PCZT as readable multi-line text for logs and support tickets
+///
+/// # Arguments
+/// * `pczt` - The PCZT to render
+/// * `verbosity` - "summary", "normal", or "full"
+/// * `transaction_request` - The request the PCZT was proposed from, to show
+///   payment memos (PCZTs only hold memos encrypted)
+#[uniffi::export]
+pub fn format_pczt(
+    pczt: Arc<UniffiPczt>,
+    verbosity: String,
+    transaction_request: Option<UniffiTransactionRequest>,
+) -> Result<String, UniffiError> {
+    let verbosity: t2z_core::Verbosity = verbosity.parse()?;
+    let pczt = pczt.read();
+    Ok(match transaction_request {
+        Some(request) => t2z_core::format_pczt_with_request(&pczt, &request.to_core()?, verbosity)?,
+        None => t2z_core::format_pczt(&pczt, verbosity)?,
+    })
+}
+
+/// The JSON Schema for `inspect_pczt_json` output
+#[uniffi::export]
+pub fn pczt_info_json_schema() -> String {
+    t2z_core::PCZT_INFO_JSON_SCHEMA.to_string()
+}
+
+/// Gets the sighash for a transparent input
+///
+/// The returned sighash should be signed externally, then the signature
+/// appended using append_signature.
+#[uniffi::export]
+pub fn get_sighash(pczt: Arc<UniffiPczt>, input_index: u32) -> Result<String, UniffiError> {
+    let sighash = t2z_core::get_sighash(&pczt.read(), input_index as usize)?;
+    Ok(hex::encode(sighash))
+}
+
+/// The intermediate ZIP 244 digests behind a transparent input's sighash
+/// (all hex)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSighashDigests {
+    pub input_index: u32,
+    pub hash_type: u8,
+    pub consensus_branch_id: u32,
+    pub header_digest: String,
+    pub prevouts_digest: String,
+    pub amounts_digest: String,
+    pub script_pubkeys_digest: String,
+    pub sequence_digest: String,
+    pub outputs_digest: String,
+    pub txin_sig_digest: String,
+    pub transparent_sig_digest: String,
+    pub sapling_digest: String,
+    pub orchard_digest: String,
+    pub sighash: String,
+}
+
+/// Gets every intermediate ZIP 244 digest behind a transparent input's
+/// sighash, to cross-check a hardware wallet's own implementation
+#[uniffi::export]
+pub fn dump_sighash_digests(
+    pczt: Arc<UniffiPczt>,
+    input_index: u32,
+) -> Result<UniffiSighashDigests, UniffiError> {
+    let d = t2z_core::dump_sighash_digests(&pczt.read(), input_index as usize)?;
+    Ok(UniffiSighashDigests {
+        input_index: d.input_index as u32,
+        hash_type: d.hash_type,
+        consensus_branch_id: d.consensus_branch_id,
+        header_digest: d.header_digest,
+        prevouts_digest: d.prevouts_digest,
+        amounts_digest: d.amounts_digest,
+        script_pubkeys_digest: d.script_pubkeys_digest,
+        sequence_digest: d.sequence_digest,
+        outputs_digest: d.outputs_digest,
+        txin_sig_digest: d.txin_sig_digest,
+        transparent_sig_digest: d.transparent_sig_digest,
+        sapling_digest: d.sapling_digest,
+        orchard_digest: d.orchard_digest,
+        sighash: d.sighash,
+    })
+}
+
+/// Appends a signature to a transparent input
+///
+/// # Arguments
+/// * `pczt` - The PCZT
+/// * `input_index` - Index of the input to sign
+/// * `pubkey_hex` - Compressed public key (33 bytes, hex)
+/// * `signature_hex` - DER-encoded ECDSA signature (hex)
+#[uniffi::export]
+pub fn append_signature(
+    pczt: Arc<UniffiPczt>,
+    input_index: u32,
+    pubkey_hex: String,
+    signature_hex: String,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let (pubkey, signature_bytes) = parse_external_signature(&pubkey_hex, &signature_hex)?;
+
+    let signed = t2z_core::append_signature(
+        pczt.snapshot(),
+        input_index as usize,
+        &pubkey,
+        &signature_bytes,
+    )?;
+    Ok(UniffiPczt::new(signed))
+}
+
+/// External signer for transparent inputs (HSM, hardware wallet, remote service)
+///
+/// Implemented in the host language and passed to `sign_with`.
+#[uniffi::export(callback_interface)]
+pub trait TransparentSigner: Send + Sync {
+    /// Signs the sighash (32 bytes, hex) of input `input_index` with the key
+    /// for `pubkey_hex` (33 bytes), returning the DER signature with the
+    /// sighash type byte appended, as hex
+    fn sign(
+        &self,
+        input_index: u32,
+        sighash_hex: String,
+        pubkey_hex: String,
+    ) -> Result<String, UniffiError>;
+}
+
+/// Signs every transparent input through an external signer
+///
+/// Calls `signer.sign` once per input, in order, and verifies and appends
+/// each signature like `append_signature`. Stops at the first error.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `pubkeys_hex` - Public key (33 bytes, hex) of each transparent input, in order
+/// * `signer` - Host-language signer
+#[uniffi::export]
+pub fn sign_with(
+    pczt: Arc<UniffiPczt>,
+    pubkeys_hex: Vec<String>,
+    signer: Box<dyn TransparentSigner>,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let pubkeys = pubkeys_hex
+        .iter()
+        .map(|pubkey_hex| parse_pubkey(pubkey_hex))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Keeps the host's own error (rather than a T2ZError) if its signer fails
+    let mut signer_error = None;
+    let result = t2z_core::sign_transparent_inputs_with(
+        pczt.snapshot(),
+        &pubkeys,
+        |input_index, sighash, pubkey| {
+            let signature_hex = signer
+                .sign(input_index as u32, hex::encode(sighash), hex::encode(pubkey))
+                .map_err(|e| {
+                    let msg = e.to_string();
+                    signer_error = Some(e);
+                    T2ZError::InvalidInput(msg)
+                })?;
+            hex::decode(&signature_hex)
+                .map_err(|e| T2ZError::InvalidInput(format!("Invalid signature hex: {}", e)))
+        },
+    );
+
+    match (result, signer_error) {
+        (Ok(signed), _) => Ok(UniffiPczt::new(signed)),
+        (Err(_), Some(e)) => Err(e),
+        (Err(e), None) => Err(e.into()),
+    }
+}
+
+/// A key derivation recorded for a transparent input
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSigningPackageDerivation {
+    /// 33-byte compressed public key (hex)
+    pub pubkey: String,
+    /// ZIP 32 seed fingerprint (hex)
+    pub seed_fingerprint: String,
+    /// BIP 32 derivation path (hardened indices have the high bit set)
+    pub derivation_path: Vec<u32>,
+}
+
+/// What an offline signer needs for one transparent input
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSigningPackageInput {
+    pub input_index: u32,
+    /// Sighash to sign (hex, SIGHASH_ALL)
+    pub sighash: String,
+    /// Previous transaction ID (hex, display order)
+    pub prevout_txid: String,
+    pub prevout_index: u32,
+    pub value: u64,
+    /// Script pubkey (hex)
+    pub script_pubkey: String,
+    pub derivations: Vec<UniffiSigningPackageDerivation>,
+}
+
+/// Everything an air-gapped device needs to sign a PCZT's transparent inputs
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSigningPackage {
+    pub expiry_height: u32,
+    pub inputs: Vec<UniffiSigningPackageInput>,
+}
+
+/// A signature produced by an offline signer
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiPackageSignature {
+    pub input_index: u32,
+    /// 33-byte compressed public key (hex)
+    pub pubkey: String,
+    /// DER signature with the sighash type byte appended (hex)
+    pub signature: String,
+}
+
+/// Exports what an air-gapped signer needs, without the rest of the PCZT
+#[uniffi::export]
+pub fn export_signing_package(pczt: Arc<UniffiPczt>) -> Result<UniffiSigningPackage, UniffiError> {
+    let package = t2z_core::export_signing_package(&pczt.read())?;
+
+    Ok(UniffiSigningPackage {
+        expiry_height: package.expiry_height,
+        inputs: package
+            .inputs
+            .into_iter()
+            .map(|input| UniffiSigningPackageInput {
+                input_index: input.input_index,
+                sighash: input.sighash,
+                prevout_txid: input.prevout_txid,
+                prevout_index: input.prevout_index,
+                value: input.value,
+                script_pubkey: input.script_pubkey,
+                derivations: input
+                    .derivations
+                    .into_iter()
+                    .map(|d| UniffiSigningPackageDerivation {
+                        pubkey: d.pubkey,
+                        seed_fingerprint: d.seed_fingerprint,
+                        derivation_path: d.derivation_path,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    })
+}
+
+/// Merges an air-gapped signer's signatures into the PCZT
+///
+/// Every signature is verified against the PCZT's sighash first.
+#[uniffi::export]
+pub fn import_signatures(
+    pczt: Arc<UniffiPczt>,
+    signatures: Vec<UniffiPackageSignature>,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let response = t2z_core::SigningPackageResponse {
+        signatures: signatures
+            .into_iter()
+            .map(|s| t2z_core::PackageSignature {
+                input_index: s.input_index,
+                pubkey: s.pubkey,
+                signature: s.signature,
+            })
+            .collect(),
+    };
+
+    let signed = t2z_core::import_signatures(pczt.snapshot(), &response)?;
+    Ok(UniffiPczt::new(signed))
+}
+
+/// Data needed to produce an Orchard spend authorization signature externally
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiOrchardSpendSigningData {
+    /// Shielded sighash to sign (hex)
+    pub sighash: String,
+    /// Spend authorization randomizer (hex); sign with `ask + alpha`
+    pub alpha: String,
+    /// Randomized verification key the signature must verify under (hex)
+    pub rk: String,
+}
+
+/// Gets the data needed to sign an Orchard spend externally
+///
+/// The device produces a RedPallas SpendAuth signature over `sighash` with
+/// `ask + alpha`, which is then appended using append_orchard_spend_auth_sig.
+#[uniffi::export]
+pub fn get_orchard_spend_signing_data(
+    pczt: Arc<UniffiPczt>,
+    action_index: u32,
+) -> Result<UniffiOrchardSpendSigningData, UniffiError> {
+    let data = t2z_core::get_orchard_spend_signing_data(&pczt.read(), action_index as usize)?;
+    Ok(UniffiOrchardSpendSigningData {
+        sighash: hex::encode(data.sighash),
+        alpha: hex::encode(data.alpha),
+        rk: hex::encode(data.rk),
+    })
+}
+
+/// Appends a RedPallas spend authorization signature to an Orchard action
+///
+/// # Arguments
+/// * `pczt` - The PCZT
+/// * `action_index` - Index of the Orchard action
+/// * `signature_hex` - 64-byte RedPallas signature (hex)
+#[uniffi::export]
+pub fn append_orchard_spend_auth_sig(
+    pczt: Arc<UniffiPczt>,
+    action_index: u32,
+    signature_hex: String,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let signature: [u8; 64] = hex::decode(&signature_hex)
+        .map_err(|e| UniffiError::Error {
+            msg: format!("Invalid signature hex: {}", e),
+        })?
+        .try_into()
+        .map_err(|_| UniffiError::Error {
+            msg: "Signature must be 64 bytes".to_string(),
+        })?;
+
+    let signed = t2z_core::append_orchard_spend_auth_sig(
+        pczt.snapshot(),
+        action_index as usize,
+        &signature,
+    )?;
+    Ok(UniffiPczt::new(signed))
+}
+
+/// Signs an Orchard spend with an Orchard spending key (32 bytes, hex)
+#[uniffi::export]
+pub fn sign_orchard_spend(
+    pczt: Arc<UniffiPczt>,
+    action_index: u32,
+    spending_key_hex: String,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let spending_key = parse_spending_key(&spending_key_hex)?;
+
+    let signed =
+        t2z_core::sign_orchard_spend(pczt.snapshot(), action_index as usize, &spending_key)?;
+    Ok(UniffiPczt::new(signed))
+}
+
+/// Proves a transaction (builds proving key automatically, ~10 seconds first call)
+///
+/// This uses Halo 2, which requires NO external downloads or trusted setup.
+/// The proving key is built programmatically and cached for subsequent calls.
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn prove_transaction(pczt: Arc<UniffiPczt>) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let proved = t2z_core::prove_transaction(pczt.snapshot())?;
+    Ok(UniffiPczt::new(proved))
+}
+
+/// Prover thread shared by the async exports (one proof at a time)
+#[cfg(feature = "prover")]
+fn prover_pool() -> &'static t2z_core::ProverPool {
+    static POOL: std::sync::OnceLock<t2z_core::ProverPool> = std::sync::OnceLock::new();
+    POOL.get_or_init(|| t2z_core::ProverPool::with_workers(1))
+}
+
+/// Proves a transaction without blocking the calling thread
+///
+/// Kotlin: a suspend function. Cancelling the coroutine before proving starts
+/// removes the PCZT from the queue; a proof already running completes on the
+/// prover thread and is discarded.
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub async fn prove_transaction_async(
+    pczt: Arc<UniffiPczt>,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let proved = prover_pool().submit(pczt.snapshot()).await?;
+    Ok(UniffiPczt::new(proved))
+}
+
+/// Proves a transaction, giving up after `timeout_ms` milliseconds
+///
+/// Fails with `UniffiError::Timeout` if the proof isn't done in time. A proof
+/// that has not started yet is dropped from the queue; one already running
+/// completes on the prover thread and is discarded.
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn prove_transaction_with_timeout(
+    pczt: Arc<UniffiPczt>,
+    timeout_ms: u64,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    start_proving(pczt).wait(timeout_ms)
+}
+
+/// A proof running on the shared prover thread
+///
+/// Go: wait in a goroutine and call `Cancel()` when the context is done, or
+/// pass the context's remaining time to `Wait`.
+#[cfg(feature = "prover")]
+#[derive(uniffi::Object)]
+pub struct UniffiProofTask {
+    handle: t2z_core::ProofHandle,
+    finished: std::sync::atomic::AtomicBool,
+}
+
+#[cfg(feature = "prover")]
+#[uniffi::export]
+impl UniffiProofTask {
+    /// Waits up to `timeout_ms` milliseconds for the proved PCZT
+    ///
+    /// Fails with `UniffiError::Timeout` if it isn't ready yet; the proof
+    /// keeps going and can be waited on again. The result is returned once.
+    pub fn wait(&self, timeout_ms: u64) -> Result<Arc<UniffiPczt>, UniffiError> {
+        use std::sync::atomic::Ordering;
+
+        if self.finished.load(Ordering::Acquire) {
+            return Err(UniffiError::Error {
+                msg: "Proof result was already returned".to_string(),
+            });
+        }
+
+        match self.handle.wait_timeout(std::time::Duration::from_millis(timeout_ms)) {
+            Some(result) => {
+                self.finished.store(true, Ordering::Release);
+                Ok(UniffiPczt::new(result?))
+            }
+            None => Err(UniffiError::Timeout {
+                msg: format!("Proof not ready after {} ms", timeout_ms),
+            }),
+        }
+    }
+
+    /// Whether the proof has finished (successfully or not)
+    pub fn is_ready(&self) -> bool {
+        self.handle.is_ready()
+    }
+
+    /// Cancels the proof if it has not started yet
+    ///
+    /// `wait` then fails with a cancellation error. A proof already running
+    /// still completes and can be waited on.
+    pub fn cancel(&self) {
+        self.handle.cancel();
+    }
+}
+
+/// Queues a PCZT on the shared prover thread and returns immediately
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn start_proving(pczt: Arc<UniffiPczt>) -> Arc<UniffiProofTask> {
+    Arc::new(UniffiProofTask {
+        handle: prover_pool().submit(pczt.snapshot()),
+        finished: std::sync::atomic::AtomicBool::new(false),
+    })
+}
+
+/// Estimates the peak memory (bytes) proving this PCZT would need
+///
+/// Includes building the proving key if it isn't cached yet. Returns 0 if
+/// no proof is needed.
+#[uniffi::export]
+pub fn estimate_proving_memory(pczt: Arc<UniffiPczt>) -> u64 {
+    t2z_core::estimate_pczt_proving_memory(&pczt.read())
+}
+
+/// Proves a transaction only if the estimated peak memory fits within `max_memory_bytes`
+///
+/// Fails without doing any work otherwise, so constrained devices can
+/// fall back to delegated proving.
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn prove_transaction_with_budget(
+    pczt: Arc<UniffiPczt>,
+    max_memory_bytes: u64,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let proved = t2z_core::prove_transaction_with_budget(
+        pczt.snapshot(),
+        t2z_core::ProvingBudget { max_memory_bytes },
+    )?;
+    Ok(UniffiPczt::new(proved))
+}
+
+/// What proving a PCZT cost (see `t2z_core::ProveMetrics`)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiProveMetrics {
+    /// Total wall time, including building the proving key
+    pub wall_time_ms: f64,
+    /// Wall time spent building the proving key (0 if it was cached)
+    pub proving_key_ms: f64,
+    pub proving_key_built: bool,
+    pub orchard_actions: u32,
+    /// Size of the Orchard proof (0 if none was needed)
+    pub proof_bytes: u64,
+    pub estimated_memory_bytes: u64,
+    /// Peak memory of the process, where measurable (a high-water mark)
+    pub peak_memory_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiProveResult {
+    pub pczt: Arc<UniffiPczt>,
+    pub metrics: UniffiProveMetrics,
+}
+
+/// Proves a transaction like `prove_transaction`, and reports what it cost
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn prove_transaction_with_metrics(
+    pczt: Arc<UniffiPczt>,
+) -> Result<UniffiProveResult, UniffiError> {
+    let (proved, metrics) = t2z_core::prove_transaction_with_metrics(pczt.snapshot())?;
+    Ok(UniffiProveResult {
+        pczt: UniffiPczt::new(proved),
+        metrics: UniffiProveMetrics {
+            wall_time_ms: metrics.wall_time_ms,
+            proving_key_ms: metrics.proving_key_ms,
+            proving_key_built: metrics.proving_key_built,
+            orchard_actions: metrics.orchard_actions as u32,
+            proof_bytes: metrics.proof_bytes as u64,
+            estimated_memory_bytes: metrics.estimated_memory_bytes,
+            peak_memory_bytes: metrics.peak_memory_bytes,
+        },
+    })
+}
+
+/// Signs a transparent input with the provided private key
+#[uniffi::export]
+pub fn sign_transparent_input(
+    pczt: Arc<UniffiPczt>,
+    input_index: u32,
+    secret_key_hex: String,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let secret_key = parse_secret_key(&secret_key_hex)?;
+
+    let signed =
+        t2z_core::sign_transparent_input(pczt.snapshot(), input_index as usize, &secret_key)?;
+    Ok(UniffiPczt::new(signed))
+}
+
+/// What signing cost (see `t2z_core::SignMetrics`)
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSignMetrics {
+    pub wall_time_ms: f64,
+    pub inputs_signed: u32,
+    pub transparent_inputs: u32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiSignResult {
+    pub pczt: Arc<UniffiPczt>,
+    pub metrics: UniffiSignMetrics,
+}
+
+/// Signs a transparent input like `sign_transparent_input`, and reports what it cost
+#[uniffi::export]
+pub fn sign_transparent_input_with_metrics(
+    pczt: Arc<UniffiPczt>,
+    input_index: u32,
+    secret_key_hex: String,
+) -> Result<UniffiSignResult, UniffiError> {
+    let secret_key = parse_secret_key(&secret_key_hex)?;
+
+    let (signed, metrics) = t2z_core::sign_transparent_input_with_metrics(
+        pczt.snapshot(),
+        input_index as usize,
+        &secret_key,
+    )?;
+    Ok(UniffiSignResult {
+        pczt: UniffiPczt::new(signed),
+        metrics: UniffiSignMetrics {
+            wall_time_ms: metrics.wall_time_ms,
+            inputs_signed: metrics.inputs_signed as u32,
+            transparent_inputs: metrics.transparent_inputs as u32,
+        },
+    })
+}
+
+/// Exports an audit record (JSON) describing a PCZT at signing time
+///
+/// # Arguments
+/// * `pczt` - The PCZT being signed
+/// * `transaction_request` - Original ZIP 321 payment request
+/// * `timestamp` - Event time in Unix seconds
+#[uniffi::export]
+pub fn export_audit_record(
+    pczt: Arc<UniffiPczt>,
+    transaction_request: UniffiTransactionRequest,
+    timestamp: u64,
+) -> Result<String, UniffiError> {
+    let request = transaction_request.to_core()?;
+    let record = t2z_core::export_audit_record(&pczt.read(), &request, timestamp)?;
+    Ok(record.to_json())
+}
+
+/// Creates a payment disclosure (proof of payment) for an Orchard output
+///
+/// # Arguments
+/// * `pczt` - The final (unredacted) PCZT containing the output
+/// * `action_index` - Index of the Orchard action holding the payment
+/// * `memo_hex` - Memo attached to the payment (hex), if any
+#[uniffi::export]
+pub fn create_payment_disclosure(
+    pczt: Arc<UniffiPczt>,
+    action_index: u32,
+    memo_hex: Option<String>,
+) -> Result<Vec<u8>, UniffiError> {
+    let memo = memo_hex
+        .map(|m| {
+            hex::decode(m).map_err(|e| UniffiError::Error {
+                msg: format!("Invalid memo hex: {}", e),
+            })
+        })
+        .transpose()?;
+
+    let disclosure =
+        t2z_core::create_payment_disclosure(&pczt.read(), action_index as usize, memo)?;
+    Ok(disclosure.to_bytes())
+}
+
+/// Verifies a payment disclosure against the raw transaction it refers to
+#[uniffi::export]
+pub fn verify_payment_disclosure(disclosure: Vec<u8>, tx_bytes: Vec<u8>) -> Result<(), UniffiError> {
+    let disclosure = t2z_core::PaymentDisclosure::from_bytes(&disclosure)?;
+    disclosure.verify_against_transaction(&tx_bytes)?;
+    Ok(())
+}
+
+/// Ownership of one PCZT output relative to a viewing key
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiOutputOwnership {
+    /// "transparent" or "orchard"
+    pub pool: String,
+    /// Transparent output index, or Orchard action index
+    pub index: u32,
+    /// Value in zatoshis, if known
+    pub value: Option<u64>,
+    /// "change", "own_external", or "foreign"
+    pub ownership: String,
+}
+
+/// Identifies which outputs of a PCZT belong to a unified full viewing key
+#[uniffi::export]
+pub fn identify_own_outputs(
+    pczt: Arc<UniffiPczt>,
+    ufvk: String,
+) -> Result<Vec<UniffiOutputOwnership>, UniffiError> {
+    let outputs = t2z_core::identify_own_outputs(&pczt.read(), &ufvk)?;
+    Ok(outputs
+        .into_iter()
+        .map(|o| UniffiOutputOwnership {
+            pool: match o.pool {
+                t2z_core::OutputPool::Transparent => "transparent",
+                t2z_core::OutputPool::Orchard => "orchard",
+            }
+            .to_string(),
+            index: o.index as u32,
+            value: o.value,
+            ownership: match o.ownership {
+                t2z_core::Ownership::Change => "change",
+                t2z_core::Ownership::OwnExternal => "own_external",
+                t2z_core::Ownership::Foreign => "foreign",
+            }
+            .to_string(),
+        })
+        .collect())
+}
+
+/// A challenge for `prove_address_ownership`, kept by the verifier
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiAddressOwnershipChallenge {
+    /// The address whose ownership is being proved
+    pub address: String,
+    /// Sent to the prover (32 bytes, hex)
+    pub challenge: String,
+    /// The answer only the address's owner can compute (32 bytes, hex).
+    /// Keep it from the prover.
+    pub expected_response: String,
+}
+
+/// Creates a fresh challenge for the owner of an Orchard (unified) address
+#[uniffi::export]
+pub fn create_address_ownership_challenge(
+    address: String,
+    network: String,
+) -> Result<UniffiAddressOwnershipChallenge, UniffiError> {
+    let challenge =
+        t2z_core::create_address_ownership_challenge(&address, parse_network(&network)?)?;
+    Ok(UniffiAddressOwnershipChallenge {
+        address: challenge.address,
+        challenge: hex::encode(challenge.challenge),
+        expected_response: hex::encode(challenge.expected_response),
+    })
+}
+
+/// Answers an ownership challenge, returning the response (32 bytes, hex)
+///
+/// # Arguments
+/// * `ufvk_or_sk` - Unified full viewing key, or hex Orchard spending key
+/// * `address` - The address being proved
+/// * `challenge_hex` - The verifier's challenge
+/// * `network` - "mainnet" or "testnet"
+#[uniffi::export]
+pub fn prove_address_ownership(
+    ufvk_or_sk: String,
+    address: String,
+    challenge_hex: String,
+    network: String,
+) -> Result<String, UniffiError> {
+    let challenge = parse_hash(&challenge_hex, "Challenge")?;
+    let response = t2z_core::prove_address_ownership(
+        &ufvk_or_sk,
+        &address,
+        &challenge,
+        parse_network(&network)?,
+    )?;
+    Ok(hex::encode(response))
+}
+
+/// Checks the prover's response (hex) to a challenge
+#[uniffi::export]
+pub fn verify_address_ownership(
+    challenge: UniffiAddressOwnershipChallenge,
+    response_hex: String,
+) -> Result<bool, UniffiError> {
+    let challenge = t2z_core::AddressOwnershipChallenge {
+        address: challenge.address,
+        challenge: parse_hash(&challenge.challenge, "Challenge")?,
+        expected_response: parse_hash(&challenge.expected_response, "Expected response")?,
+    };
+    let response = parse_hash(&response_hex, "Response")?;
+    Ok(t2z_core::verify_address_ownership(&challenge, &response))
+}
+
+/// An Orchard account: spending key, viewing key, and address derivation
+///
+/// The spending key is zeroized when the object is dropped.
+#[derive(uniffi::Object)]
+pub struct UniffiOrchardAccount {
+    inner: t2z_core::OrchardAccount,
+}
+
+#[uniffi::export]
+impl UniffiOrchardAccount {
+    /// Generates a new account from a random spending key
+    #[uniffi::constructor]
+    pub fn generate() -> Arc<Self> {
+        Arc::new(UniffiOrchardAccount {
+            inner: t2z_core::OrchardAccount::generate(),
+        })
+    }
+
+    /// Restores an account from its 32-byte spending key
+    #[uniffi::constructor]
+    pub fn from_spending_key(spending_key: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        let bytes: [u8; 32] = spending_key.try_into().map_err(|_| UniffiError::Error {
+            msg: "Spending key must be 32 bytes".to_string(),
+        })?;
+        let inner = t2z_core::OrchardAccount::from_spending_key(&bytes)?;
+        Ok(Arc::new(UniffiOrchardAccount { inner }))
+    }
+
+    /// Derives an account from a seed (ZIP 32)
+    #[uniffi::constructor]
+    pub fn from_seed(seed: Vec<u8>, network: String, account: u32) -> Result<Arc<Self>, UniffiError> {
+        let inner = t2z_core::OrchardAccount::from_seed(&seed, parse_network(&network)?, account)?;
+        Ok(Arc::new(UniffiOrchardAccount { inner }))
+    }
+
+    /// The raw spending key (keep secret!)
+    pub fn spending_key(&self) -> Vec<u8> {
+        self.inner.spending_key_bytes().to_vec()
+    }
+
+    /// Unified full viewing key (uview1... / uviewtest1...)
+    pub fn ufvk(&self, network: String) -> Result<String, UniffiError> {
+        Ok(self.inner.ufvk(parse_network(&network)?)?)
+    }
+
+    /// Unified address (Orchard receiver) at a diversifier index
+    pub fn address(&self, index: u32, network: String) -> Result<String, UniffiError> {
+        Ok(self.inner.address(index, parse_network(&network)?)?)
+    }
+
+    /// Unified address (Orchard receiver) for change
+    pub fn change_address(&self, network: String) -> Result<String, UniffiError> {
+        Ok(self.inner.change_address(parse_network(&network)?)?)
+    }
+}
+
+fn parse_network(network: &str) -> Result<t2z_core::Network, UniffiError> {
+    match network {
+        "mainnet" => Ok(t2z_core::Network::Mainnet),
+        "testnet" => Ok(t2z_core::Network::Testnet),
+        _ => Err(UniffiError::Error {
+            msg: "Network must be 'mainnet' or 'testnet'".to_string(),
+        }),
+    }
+}
+
+fn parse_secret_key(secret_key_hex: &str) -> Result<[u8; 32], UniffiError> {
+    hex::decode(secret_key_hex)
+        .map_err(|e| UniffiError::Error {
+            msg: format!("Invalid secret key hex: {}", e),
+        })?
+        .try_into()
+        .map_err(|_| UniffiError::Error {
+            msg: "Secret key must be 32 bytes".to_string(),
+        })
+}
+
+fn parse_spending_key(spending_key_hex: &str) -> Result<[u8; 32], UniffiError> {
+    hex::decode(spending_key_hex)
+        .map_err(|e| UniffiError::Error {
+            msg: format!("Invalid spending key hex: {}", e),
+        })?
+        .try_into()
+        .map_err(|_| UniffiError::Error {
+            msg: "Spending key must be 32 bytes".to_string(),
+        })
+}
+
+/// Parses 32 bytes of hex, naming `what` in the error
+fn parse_hash(hash_hex: &str, what: &str) -> Result<[u8; 32], UniffiError> {
+    hex::decode(hash_hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| UniffiError::Error {
+            msg: format!("{} must be 32 bytes of hex", what),
+        })
+}
+
+fn parse_pubkey(pubkey_hex: &str) -> Result<[u8; 33], UniffiError> {
+    hex::decode(pubkey_hex)
+        .map_err(|e| UniffiError::Error {
+            msg: format!("Invalid pubkey hex: {}", e),
+        })?
+        .try_into()
+        .map_err(|_| UniffiError::Error {
+            msg: "Public key must be 33 bytes".to_string(),
+        })
+}
+
+/// Parses a 33-byte public key and a DER signature with sighash type byte
+fn parse_external_signature(
+    pubkey_hex: &str,
+    signature_hex: &str,
+) -> Result<([u8; 33], Vec<u8>), UniffiError> {
+    let pubkey = parse_pubkey(pubkey_hex)?;
+
+    let signature = hex::decode(signature_hex).map_err(|e| UniffiError::Error {
+        msg: format!("Invalid signature hex: {}", e),
+    })?;
+
+    Ok((pubkey, signature))
+}
+
+/// A transparent keypair with its P2PKH address
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiTransparentKeypair {
+    /// Secret key (32 bytes as hex string, keep secret!)
+    pub secret_key: String,
+    /// Compressed public key (33 bytes as hex string)
+    pub public_key: String,
+    /// P2PKH address
+    pub address: String,
+    /// P2PKH script pubkey (hex encoded)
+    pub script_pubkey: String,
+}
+
+impl From<&t2z_core::TransparentKeypair> for UniffiTransparentKeypair {
+    fn from(keypair: &t2z_core::TransparentKeypair) -> Self {
+        UniffiTransparentKeypair {
+            secret_key: hex::encode(keypair.secret_key),
+            public_key: hex::encode(keypair.public_key),
+            address: keypair.address.clone(),
+            script_pubkey: hex::encode(&keypair.script_pubkey),
+        }
+    }
+}
+
+/// Generates a random transparent keypair
+#[uniffi::export]
+pub fn generate_transparent_keypair(network: String) -> Result<UniffiTransparentKeypair, UniffiError> {
+    let keypair = t2z_core::generate_transparent_keypair(parse_network(&network)?);
+    Ok((&keypair).into())
+}
+
+/// Derives a transparent keypair from a seed (m/44'/coin_type'/account'/0/address_index)
+#[uniffi::export]
+pub fn derive_transparent_keypair(
+    seed: Vec<u8>,
+    network: String,
+    account: u32,
+    address_index: u32,
+) -> Result<UniffiTransparentKeypair, UniffiError> {
+    let keypair = t2z_core::derive_transparent_keypair(
+        &seed,
+        parse_network(&network)?,
+        account,
+        address_index,
+    )?;
+    Ok((&keypair).into())
+}
+
+/// A secret key imported from WIF
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiWifKey {
+    /// Secret key (32 bytes as hex string), as sign_transparent_input expects
+    pub secret_key: String,
+    /// "mainnet" or "testnet"
+    pub network: String,
+}
+
+/// Imports a WIF secret key (e.g. from a zcashd dumpwallet file)
+#[uniffi::export]
+pub fn secret_key_from_wif(wif: String) -> Result<UniffiWifKey, UniffiError> {
+    let (secret_key, network) = t2z_core::secret_key_from_wif(&wif)?;
+    Ok(UniffiWifKey {
+        secret_key: hex::encode(secret_key),
+        network: match network {
+            t2z_core::Network::Mainnet => "mainnet".to_string(),
+            t2z_core::Network::Testnet => "testnet".to_string(),
+        },
+    })
+}
+
+/// Exports a secret key (32 bytes as hex string) as WIF
+#[uniffi::export]
+pub fn secret_key_to_wif(secret_key_hex: String, network: String) -> Result<String, UniffiError> {
+    let secret_key = parse_secret_key(&secret_key_hex)?;
+
+    Ok(t2z_core::secret_key_to_wif(&secret_key, parse_network(&network)?)?)
+}
+
+/// Formats zatoshis as ZEC with the separators of a BCP 47 locale (English
+/// if None), e.g. "0,00012345 ZEC" for "de-DE"; min_decimals (default 8)
+/// is how many fraction digits to keep when the rest are zeros
+#[uniffi::export]
+pub fn format_zec(zatoshis: u64, locale: Option<String>, min_decimals: Option<u8>) -> String {
+    t2z_core::format_zec(zatoshis, &amount_format(locale, min_decimals))
+}
+
+/// Parses a ZEC amount typed in a locale's format (unit and grouping
+/// optional) into zatoshis
+#[uniffi::export]
+pub fn parse_zec(text: String, locale: Option<String>) -> Result<u64, UniffiError> {
+    Ok(t2z_core::parse_zec(&text, &amount_format(locale, None))?)
+}
+
+fn amount_format(locale: Option<String>, min_decimals: Option<u8>) -> t2z_core::AmountFormat {
+    let mut format = locale
+        .map(|locale| t2z_core::AmountFormat::for_locale(&locale))
+        .unwrap_or_default();
+    if let Some(min_decimals) = min_decimals {
+        format.min_decimals = min_decimals as usize;
+    }
+    format
+}
+
+/// Signs a message with a transparent secret key (zcashd signmessage),
+/// returning the base64 signature
+#[uniffi::export]
+pub fn sign_message(secret_key_hex: String, message: String) -> Result<String, UniffiError> {
+    let secret_key = parse_secret_key(&secret_key_hex)?;
+
+    Ok(t2z_core::sign_message(&secret_key, message.as_bytes())?)
+}
+
+/// Checks a signed message against a transparent P2PKH address (zcashd
+/// verifymessage); false if another key signed it
+#[uniffi::export]
+pub fn verify_message(
+    address: String,
+    message: String,
+    signature: String,
+) -> Result<bool, UniffiError> {
+    Ok(t2z_core::verify_message(
+        &address,
+        message.as_bytes(),
+        &signature,
+    )?)
+}
+
+/// Generates a new BIP 39 mnemonic phrase
+///
+/// word_count is 12, 15, 18, 21, or 24; language is "english",
+/// "chinese_simplified", "chinese_traditional", "czech", "french", "italian",
+/// "japanese", "korean", "portuguese", or "spanish"
+#[uniffi::export]
+pub fn generate_mnemonic(word_count: u32, language: String) -> Result<String, UniffiError> {
+    let language: t2z_core::MnemonicLanguage = language.parse()?;
+    Ok(t2z_core::generate_mnemonic(word_count, language)?)
+}
+
+/// Validates a mnemonic phrase (words and checksum)
+#[uniffi::export]
+pub fn validate_mnemonic(phrase: String, language: String) -> Result<(), UniffiError> {
+    let language: t2z_core::MnemonicLanguage = language.parse()?;
+    Ok(t2z_core::validate_mnemonic(&phrase, language)?)
+}
+
+/// Derives the 64-byte seed from a mnemonic phrase and passphrase
+#[uniffi::export]
+pub fn mnemonic_to_seed(
+    phrase: String,
+    passphrase: String,
+    language: String,
+) -> Result<Vec<u8>, UniffiError> {
+    let language: t2z_core::MnemonicLanguage = language.parse()?;
+    Ok(t2z_core::mnemonic_to_seed(&phrase, &passphrase, language)?.to_vec())
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiTransparentInput {
+    fn from_core(input: &t2z_core::TransparentInput) -> Self {
+        UniffiTransparentInput {
+            pubkey: hex::encode(&input.pubkey),
+            prevout_txid: hex::encode(&input.prevout_txid),
+            prevout_index: input.prevout_index,
+            value: input.value,
+            script_pubkey: hex::encode(&input.script_pubkey),
+            sequence: input.sequence,
+        }
+    }
+}
+
+/// Account balance by status (zatoshis)
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiAccountBalance {
+    pub confirmed: u64,
+    pub unconfirmed: u64,
+    pub pending_spend: u64,
+}
+
+/// Minimal account state: tracks transparent UTXOs through broadcast and
+/// confirmation and selects inputs for transaction requests
+#[cfg(feature = "wallet_lite")]
+#[derive(uniffi::Object)]
+pub struct UniffiAccount {
+    inner: std::sync::Mutex<t2z_core::Account>,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiAccount {
+    fn wrap(mut account: t2z_core::Account) -> Arc<Self> {
+        let events = EventRelay::default();
+        account.subscribe(events.sink());
+        Arc::new(UniffiAccount {
+            inner: std::sync::Mutex::new(account),
+            events,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::Account> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export]
+impl UniffiAccount {
+    /// Creates an empty account for "mainnet" or "testnet"
+    #[uniffi::constructor]
+    pub fn new(network: String) -> Result<Arc<Self>, UniffiError> {
+        Ok(UniffiAccount::wrap(t2z_core::Account::new(parse_network(&network)?)))
+    }
+
+    /// Restores account state from bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        Ok(UniffiAccount::wrap(t2z_core::Account::from_bytes(&bytes)?))
+    }
+
+    /// Serializes account state to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// Calls listener with every utxo_added, tx_confirmed, and tx_expired
+    /// event from now on, after the change is applied
+    pub fn subscribe(&self, listener: Box<dyn WalletEventListener>) {
+        self.events.subscribe(listener);
+    }
+
+    /// Adds or updates a UTXO (confirmed_height is None if mempool-only)
+    pub fn add_utxo(
+        &self,
+        input: UniffiTransparentInput,
+        confirmed_height: Option<u32>,
+    ) -> Result<(), UniffiError> {
+        let input = input.to_core()?;
+        self.lock().add_utxo(input, confirmed_height);
+        self.events.dispatch();
+        Ok(())
+    }
+
+    /// Balance by status
+    pub fn balance(&self) -> UniffiAccountBalance {
+        let balance = self.lock().balance();
+        UniffiAccountBalance {
+            confirmed: balance.confirmed,
+            unconfirmed: balance.unconfirmed,
+            pending_spend: balance.pending_spend,
+        }
+    }
+
+    /// Selects inputs covering the request plus the ZIP 317 fee
+    pub fn select_inputs(
+        &self,
+        transaction_request: UniffiTransactionRequest,
+        change_address: Option<String>,
+        tip_height: u32,
+        min_confirmations: u32,
+    ) -> Result<Vec<UniffiTransparentInput>, UniffiError> {
+        let request = transaction_request.to_core()?;
+        let selected = self.lock().select_inputs(
+            &request,
+            change_address.as_deref(),
+            tip_height,
+            min_confirmations,
+        )?;
+        Ok(selected.iter().map(UniffiTransparentInput::from_core).collect())
+    }
+
+    /// Marks the UTXOs spent by a broadcast PCZT as pending
+    pub fn mark_broadcast(&self, pczt: Arc<UniffiPczt>, txid: String) {
+        self.lock().mark_broadcast(&pczt.read(), &txid);
+    }
+
+    /// Marks the UTXOs spent by a transaction mined at height as spent
+    pub fn mark_confirmed(&self, txid: String, height: u32) {
+        self.lock().mark_confirmed(&txid, height);
+        self.events.dispatch();
+    }
+
+    /// Releases the UTXOs spent by a rejected or expired transaction
+    pub fn mark_failed(&self, txid: String) {
+        self.lock().mark_failed(&txid);
+        self.events.dispatch();
+    }
+
+    /// Undoes confirmations above height after a reorg; mined UTXOs go back
+    /// to unconfirmed and spent ones to pending. Returns how many were
+    /// affected.
+    pub fn rollback(&self, height: u32) -> u32 {
+        self.lock().rollback(height).len() as u32
+    }
+}
+
+/// An Orchard note paid to the wallet by one of its own transactions
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiTrackedNote {
+    /// Transaction that created the note (hex, display order)
+    pub txid: String,
+    /// Index of the Orchard action containing the note
+    pub action_index: u32,
+    /// Note value in zatoshis
+    pub value: u64,
+    /// Nullifier revealed when the note is spent (hex)
+    pub nullifier: String,
+    /// "pending", "confirmed", or "spent"
+    pub status: String,
+    /// Mined height, if confirmed
+    pub height: Option<u32>,
+    /// Spending transaction, if spent
+    pub spent_by: Option<String>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiTrackedNote {
+    fn from_core(note: &t2z_core::TrackedNote) -> Self {
+        let (status, height, spent_by) = match &note.status {
+            t2z_core::NoteStatus::Pending => ("pending", None, None),
+            t2z_core::NoteStatus::Confirmed { height } => ("confirmed", Some(*height), None),
+            t2z_core::NoteStatus::Spent { txid } => ("spent", None, Some(txid.clone())),
+        };
+        UniffiTrackedNote {
+            txid: note.txid.clone(),
+            action_index: note.action_index,
+            value: note.value,
+            nullifier: hex::encode(note.nullifier),
+            status: status.to_string(),
+            height,
+            spent_by,
+        }
+    }
+}
+
+/// Orchard notes created by this wallet's own proposals (usually change),
+/// tracked from the PCZT so they can be spent later without a rescan
+#[cfg(feature = "wallet_lite")]
+#[derive(uniffi::Object)]
+pub struct UniffiNoteStore {
+    inner: std::sync::Mutex<t2z_core::NoteStore>,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiNoteStore {
+    fn wrap(mut store: t2z_core::NoteStore) -> Arc<Self> {
+        let events = EventRelay::default();
+        store.subscribe(events.sink());
+        Arc::new(UniffiNoteStore {
+            inner: std::sync::Mutex::new(store),
+            events,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::NoteStore> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export]
+impl UniffiNoteStore {
+    /// Creates an empty store
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        UniffiNoteStore::wrap(t2z_core::NoteStore::new())
+    }
+
+    /// Restores a store from bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        Ok(UniffiNoteStore::wrap(t2z_core::NoteStore::from_bytes(&bytes)?))
+    }
+
+    /// Serializes the store to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// Calls listener with every note_received, tx_confirmed, and tx_expired
+    /// event from now on, after the change is applied
+    pub fn subscribe(&self, listener: Box<dyn WalletEventListener>) {
+        self.events.subscribe(listener);
+    }
+
+    /// Tracks the Orchard outputs of a broadcast PCZT that pay an address of
+    /// the UFVK; returns the tracked notes
+    pub fn track_pczt(
+        &self,
+        pczt: Arc<UniffiPczt>,
+        txid: String,
+        ufvk: String,
+    ) -> Result<Vec<UniffiTrackedNote>, UniffiError> {
+        let notes = self.lock().track_pczt(&pczt.read(), &txid, &ufvk)?;
+        self.events.dispatch();
+        Ok(notes.iter().map(UniffiTrackedNote::from_core).collect())
+    }
+
+    /// All tracked notes
+    pub fn notes(&self) -> Vec<UniffiTrackedNote> {
+        self.lock().notes().map(UniffiTrackedNote::from_core).collect()
+    }
+
+    /// Confirmed, unspent notes with enough confirmations, largest first
+    pub fn spendable_notes(
+        &self,
+        tip_height: u32,
+        min_confirmations: u32,
+    ) -> Vec<UniffiTrackedNote> {
+        self.lock()
+            .spendable_notes(tip_height, min_confirmations)
+            .into_iter()
+            .map(UniffiTrackedNote::from_core)
+            .collect()
+    }
+
+    /// Marks the notes created by a transaction as mined at height
+    pub fn mark_confirmed(&self, txid: String, height: u32) {
+        self.lock().mark_confirmed(&txid, height);
+        self.events.dispatch();
+    }
+
+    /// Drops the notes created by a rejected or expired transaction
+    pub fn mark_failed(&self, txid: String) {
+        self.lock().mark_failed(&txid);
+        self.events.dispatch();
+    }
+
+    /// Marks the note with this nullifier (hex) as spent; returns whether it
+    /// was tracked
+    pub fn mark_spent(&self, nullifier: String, txid: String) -> Result<bool, UniffiError> {
+        let nullifier: [u8; 32] = hex::decode(&nullifier)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| UniffiError::Error {
+                msg: "Nullifier must be 32 bytes of hex".to_string(),
+            })?;
+        Ok(self.lock().mark_spent(&nullifier, &txid))
+    }
+
+    /// Starts following the chain after block height (hash in internal byte
+    /// order), from the Orchard note commitment tree as of that block
+    /// (lightwalletd's orchardTree, hex-decoded)
+    pub fn set_chain_state(
+        &self,
+        height: u32,
+        hash: Vec<u8>,
+        orchard_tree: Vec<u8>,
+    ) -> Result<(), UniffiError> {
+        Ok(self.lock().set_chain_state(height, block_hash(hash)?, &orchard_tree)?)
+    }
+
+    /// Appends a block's Orchard note commitments (32 bytes each, in block
+    /// order) to the tree and the notes' witnesses; returns the tracked
+    /// notes found in it. Fails if prev_hash is not the last scanned block's
+    /// hash: rewind past the fork first.
+    pub fn scan_block(
+        &self,
+        height: u32,
+        hash: Vec<u8>,
+        prev_hash: Vec<u8>,
+        commitments: Vec<Vec<u8>>,
+    ) -> Result<Vec<UniffiTrackedNote>, UniffiError> {
+        let commitments = commitments
+            .into_iter()
+            .map(|cmx| {
+                cmx.try_into().map_err(|_| UniffiError::Error {
+                    msg: "Note commitments must be 32 bytes".to_string(),
+                })
+            })
+            .collect::<Result<Vec<[u8; 32]>, _>>()?;
+
+        let found = self.lock().scan_block(
+            height,
+            block_hash(hash)?,
+            block_hash(prev_hash)?,
+            &commitments,
+        )?;
+        self.events.dispatch();
+        Ok(found.iter().map(UniffiTrackedNote::from_core).collect())
+    }
+
+    /// Returns to the state after block height after a reorg; notes mined in
+    /// the undone blocks go back to pending and are returned
+    pub fn rewind(&self, height: u32) -> Result<Vec<UniffiTrackedNote>, UniffiError> {
+        let reverted = self.lock().rewind(height)?;
+        Ok(reverted.iter().map(UniffiTrackedNote::from_core).collect())
+    }
+
+    /// Height of the last block scanned, if following the chain
+    pub fn chain_height(&self) -> Option<u32> {
+        self.lock().chain_height()
+    }
+
+    /// Current Orchard anchor (32 bytes), if following the chain
+    pub fn anchor(&self) -> Option<Vec<u8>> {
+        self.lock().anchor().map(|anchor| anchor.to_vec())
+    }
+}
+
+/// A submission UniffiBroadcastQueue::poll wants made
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiBroadcastAttempt {
+    /// Transaction ID (hex), to pass back when reporting
+    pub txid: String,
+    /// Endpoint to submit to
+    pub endpoint: String,
+    /// The raw transaction
+    pub transaction: Vec<u8>,
+    /// SOCKS5 proxy URL to submit through, if the queue has a proxy
+    pub proxy: Option<String>,
+}
+
+/// A transaction held by a UniffiBroadcastQueue
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiQueuedTransaction {
+    /// Transaction ID (hex, display order)
+    pub txid: String,
+    /// Last height the transaction can be mined at (0 for no expiry)
+    pub expiry_height: u32,
+    /// "pending", "accepted", "mined", "expired", or "rejected"
+    pub status: String,
+    /// Endpoint of the latest submission (the one that accepted it, if
+    /// accepted)
+    pub endpoint: Option<String>,
+    /// Mined height, if mined
+    pub height: Option<u32>,
+    /// Failed submissions so far
+    pub attempts: u32,
+    /// Error from the last failed submission (the rejection reason, if
+    /// rejected)
+    pub last_error: Option<String>,
+    /// Earliest time of the next submission (Unix seconds)
+    pub next_attempt_at: u64,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiQueuedTransaction {
+    fn from_core(tx: &t2z_core::QueuedTransaction) -> Self {
+        let (status, height) = match &tx.status {
+            t2z_core::BroadcastStatus::Pending => ("pending", None),
+            t2z_core::BroadcastStatus::Accepted { .. } => ("accepted", None),
+            t2z_core::BroadcastStatus::Mined { height } => ("mined", Some(*height)),
+            t2z_core::BroadcastStatus::Expired => ("expired", None),
+            t2z_core::BroadcastStatus::Rejected { .. } => ("rejected", None),
+        };
+        UniffiQueuedTransaction {
+            txid: tx.txid.clone(),
+            expiry_height: tx.expiry_height,
+            status: status.to_string(),
+            endpoint: tx.endpoint.clone(),
+            height,
+            attempts: tx.attempts,
+            last_error: tx.last_error.clone(),
+            next_attempt_at: tx.next_attempt_at,
+        }
+    }
+}
+
+/// Finalized transactions waiting to be broadcast: picks which endpoint to
+/// submit each one to and when, failing over between endpoints and backing
+/// off once all of them fail. Does no networking itself: poll returns the
+/// submissions that are due, and the caller reports how each went.
+#[cfg(feature = "wallet_lite")]
+#[derive(uniffi::Object)]
+pub struct UniffiBroadcastQueue {
+    inner: std::sync::Mutex<t2z_core::BroadcastQueue>,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiBroadcastQueue {
+    fn wrap(mut queue: t2z_core::BroadcastQueue) -> Arc<Self> {
+        let events = EventRelay::default();
+        queue.subscribe(events.sink());
+        Arc::new(UniffiBroadcastQueue {
+            inner: std::sync::Mutex::new(queue),
+            events,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::BroadcastQueue> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn report(
+        &self,
+        txid: &str,
+        result: t2z_core::SubmitResult,
+        now: u64,
+    ) -> Result<(), UniffiError> {
+        self.lock().report(txid, result, now)?;
+        self.events.dispatch();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export]
+impl UniffiBroadcastQueue {
+    /// Creates an empty queue for endpoints, in order of preference. Retry
+    /// delays default to 10 seconds, doubling up to 10 minutes.
+    #[uniffi::constructor]
+    pub fn new(
+        endpoints: Vec<String>,
+        initial_delay_secs: Option<u64>,
+        max_delay_secs: Option<u64>,
+    ) -> Result<Arc<Self>, UniffiError> {
+        let defaults = t2z_core::RetryPolicy::default();
+        let retry = t2z_core::RetryPolicy {
+            initial_delay_secs: initial_delay_secs.unwrap_or(defaults.initial_delay_secs),
+            max_delay_secs: max_delay_secs.unwrap_or(defaults.max_delay_secs),
+        };
+        Ok(UniffiBroadcastQueue::wrap(t2z_core::BroadcastQueue::new(endpoints, retry)?))
+    }
+
+    /// Restores a queue from bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        Ok(UniffiBroadcastQueue::wrap(t2z_core::BroadcastQueue::from_bytes(&bytes)?))
+    }
+
+    /// Serializes the queue to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// Replaces the endpoints, forgetting their health
+    pub fn set_endpoints(&self, endpoints: Vec<String>) -> Result<(), UniffiError> {
+        Ok(self.lock().set_endpoints(endpoints)?)
+    }
+
+    /// Health of each endpoint, in order of preference
+    pub fn endpoints(&self) -> Vec<UniffiEndpointHealth> {
+        self.lock()
+            .endpoints()
+            .endpoints()
+            .iter()
+            .map(UniffiEndpointHealth::from_core)
+            .collect()
+    }
+
+    /// Records a health check that reported chain tip tip_height, so
+    /// broadcasts avoid endpoints lagging behind the others
+    pub fn record_tip(&self, url: String, tip_height: u32, now: u64) -> bool {
+        self.lock().endpoints_mut().record_tip(&url, tip_height, now)
+    }
+
+    /// Submits through a SOCKS5 proxy (host:port, e.g. Tor's
+    /// "127.0.0.1:9050"), with a separate circuit per transaction unless
+    /// isolate_circuits is false; None to connect directly. Attempts then
+    /// carry the proxy URL to use.
+    pub fn set_proxy(&self, address: Option<String>, isolate_circuits: bool) {
+        self.lock()
+            .endpoints_mut()
+            .set_proxy(socks5_proxy(address, isolate_circuits));
+    }
+
+    /// Calls listener with every tx_confirmed and tx_expired event from now
+    /// on, after the change is applied
+    pub fn subscribe(&self, listener: Box<dyn WalletEventListener>) {
+        self.events.subscribe(listener);
+    }
+
+    /// Queues a finalized transaction; returns its txid. now is the current
+    /// time in Unix seconds.
+    pub fn enqueue(&self, tx_bytes: Vec<u8>, now: u64) -> Result<String, UniffiError> {
+        Ok(self.lock().enqueue(&tx_bytes, now)?)
+    }
+
+    /// Expires transactions that can no longer be mined after tip_height and
+    /// returns the submissions due at now; report each one's result
+    pub fn poll(&self, now: u64, tip_height: u32) -> Vec<UniffiBroadcastAttempt> {
+        let attempts = self.lock().poll(now, tip_height);
+        self.events.dispatch();
+        attempts
+            .into_iter()
+            .map(|attempt| UniffiBroadcastAttempt {
+                txid: attempt.txid,
+                endpoint: attempt.endpoint,
+                transaction: attempt.transaction,
+                proxy: attempt.proxy,
+            })
+            .collect()
+    }
+
+    /// The endpoint accepted the transaction (or already had it)
+    pub fn report_accepted(&self, txid: String, now: u64) -> Result<(), UniffiError> {
+        self.report(&txid, t2z_core::SubmitResult::Accepted, now)
+    }
+
+    /// The endpoint could not be reached; the next attempt goes elsewhere
+    pub fn report_failed(&self, txid: String, error: String, now: u64) -> Result<(), UniffiError> {
+        self.report(&txid, t2z_core::SubmitResult::Failed { error }, now)
+    }
+
+    /// The endpoint rejected the transaction as invalid; it will not be
+    /// retried
+    pub fn report_rejected(
+        &self,
+        txid: String,
+        reason: String,
+        now: u64,
+    ) -> Result<(), UniffiError> {
+        self.report(&txid, t2z_core::SubmitResult::Rejected { reason }, now)
+    }
+
+    /// Records that a transaction was mined; returns whether it was queued
+    pub fn mark_mined(&self, txid: String, height: u32) -> bool {
+        let queued = self.lock().mark_mined(&txid, height);
+        self.events.dispatch();
+        queued
+    }
+
+    /// All queued transactions
+    pub fn transactions(&self) -> Vec<UniffiQueuedTransaction> {
+        self.lock().transactions().map(UniffiQueuedTransaction::from_core).collect()
+    }
+
+    /// Removes and returns the mined, expired, and rejected transactions
+    pub fn remove_finished(&self) -> Vec<UniffiQueuedTransaction> {
+        self.lock()
+            .remove_finished()
+            .iter()
+            .map(UniffiQueuedTransaction::from_core)
+            .collect()
+    }
+}
+
+/// What an endpoint pool knows about one endpoint
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiEndpointHealth {
+    pub url: String,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    /// Time (Unix seconds) the endpoint returns to rotation
+    pub retry_at: u64,
+    /// Error from the last failure
+    pub last_error: Option<String>,
+    /// Chain tip height from the last health check
+    pub tip_height: Option<u32>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiEndpointHealth {
+    fn from_core(endpoint: &t2z_core::EndpointHealth) -> Self {
+        UniffiEndpointHealth {
+            url: endpoint.url.clone(),
+            consecutive_failures: endpoint.consecutive_failures,
+            retry_at: endpoint.retry_at,
+            last_error: endpoint.last_error.clone(),
+            tip_height: endpoint.tip_height,
+        }
+    }
+}
+
+/// lightwalletd or RPC endpoints in order of preference, with health
+/// tracking: failing or lagging endpoints drop out of rotation for a while.
+/// Does no networking itself: ask it which endpoint to use and report how
+/// each request went.
+#[cfg(feature = "wallet_lite")]
+#[derive(uniffi::Object)]
+pub struct UniffiEndpointPool {
+    inner: std::sync::Mutex<t2z_core::EndpointPool>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiEndpointPool {
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::EndpointPool> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export]
+impl UniffiEndpointPool {
+    /// Creates a pool of urls, most preferred first. A failing endpoint
+    /// stays out of rotation for 10 seconds by default, doubling on each
+    /// consecutive failure up to 10 minutes.
+    #[uniffi::constructor]
+    pub fn new(
+        urls: Vec<String>,
+        initial_delay_secs: Option<u64>,
+        max_delay_secs: Option<u64>,
+    ) -> Result<Arc<Self>, UniffiError> {
+        let defaults = t2z_core::RetryPolicy::default();
+        let retry = t2z_core::RetryPolicy {
+            initial_delay_secs: initial_delay_secs.unwrap_or(defaults.initial_delay_secs),
+            max_delay_secs: max_delay_secs.unwrap_or(defaults.max_delay_secs),
+        };
+        Ok(Arc::new(UniffiEndpointPool {
+            inner: std::sync::Mutex::new(t2z_core::EndpointPool::new(urls, retry)?),
+        }))
+    }
+
+    /// Restores a pool from bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        Ok(Arc::new(UniffiEndpointPool {
+            inner: std::sync::Mutex::new(t2z_core::EndpointPool::from_bytes(&bytes)?),
+        }))
+    }
+
+    /// Serializes the pool, health included
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// The endpoint to use at now (Unix seconds)
+    pub fn pick(&self, now: u64) -> String {
+        self.lock().pick(now).to_string()
+    }
+
+    /// Every endpoint in the order to try them at now
+    pub fn failover_order(&self, now: u64) -> Vec<String> {
+        self.lock().failover_order(now).into_iter().map(str::to_string).collect()
+    }
+
+    /// Records a successful request; returns whether url is in the pool
+    pub fn record_success(&self, url: String) -> bool {
+        self.lock().record_success(&url)
+    }
+
+    /// Records a failed request, taking url out of rotation for a while;
+    /// returns whether it is in the pool
+    pub fn record_failure(&self, url: String, error: String, now: u64) -> bool {
+        self.lock().record_failure(&url, &error, now)
+    }
+
+    /// Records a health check that reported chain tip tip_height; an
+    /// endpoint lagging the others counts as failing
+    pub fn record_tip(&self, url: String, tip_height: u32, now: u64) -> bool {
+        self.lock().record_tip(&url, tip_height, now)
+    }
+
+    /// Health of each endpoint, in order of preference
+    pub fn endpoints(&self) -> Vec<UniffiEndpointHealth> {
+        self.lock().endpoints().iter().map(UniffiEndpointHealth::from_core).collect()
+    }
+
+    /// Routes requests through a SOCKS5 proxy (host:port, e.g. Tor's
+    /// "127.0.0.1:9050"), isolating circuits per request unless
+    /// isolate_circuits is false; None to connect directly
+    pub fn set_proxy(&self, address: Option<String>, isolate_circuits: bool) {
+        self.lock().set_proxy(socks5_proxy(address, isolate_circuits));
+    }
+
+    /// Proxy URL (socks5h://...) for a request, with credentials derived
+    /// from isolation_key (e.g. the address being looked up) when isolating
+    /// circuits; None without a proxy
+    pub fn proxy_url(&self, isolation_key: String) -> Option<String> {
+        self.lock().proxy_url(&isolation_key)
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+fn socks5_proxy(address: Option<String>, isolate_circuits: bool) -> Option<t2z_core::Socks5Proxy> {
+    address.map(|address| t2z_core::Socks5Proxy {
+        address,
+        isolate_circuits,
+    })
+}
+
+/// One GetAddressUtxos call a UniffiUtxoFetch wants made
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiUtxoPageRequest {
+    pub addresses: Vec<String>,
+    /// Lowest block height to return UTXOs from
+    pub start_height: u32,
+    /// Most UTXOs to return (0 for no limit)
+    pub max_entries: u32,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiUtxoPageRequest {
+    fn from_core(request: t2z_core::UtxoPageRequest) -> Self {
+        UniffiUtxoPageRequest {
+            addresses: request.addresses,
+            start_height: request.start_height,
+            max_entries: request.max_entries,
+        }
+    }
+
+    fn to_core(&self) -> t2z_core::UtxoPageRequest {
+        t2z_core::UtxoPageRequest {
+            addresses: self.addresses.clone(),
+            start_height: self.start_height,
+            max_entries: self.max_entries,
+        }
+    }
+}
+
+/// A UTXO returned by the indexer
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiFetchedUtxo {
+    pub address: String,
+    /// Transaction ID (32 bytes as hex string, internal byte order)
+    pub txid: String,
+    pub index: u32,
+    /// Script pubkey (hex encoded)
+    pub script_pubkey: String,
+    /// Value in zatoshis
+    pub value: u64,
+    /// Height the UTXO was mined at
+    pub height: u32,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiFetchedUtxo {
+    fn from_core(utxo: &t2z_core::FetchedUtxo) -> Self {
+        UniffiFetchedUtxo {
+            address: utxo.address.clone(),
+            txid: hex::encode(&utxo.txid),
+            index: utxo.index,
+            script_pubkey: hex::encode(&utxo.script_pubkey),
+            value: utxo.value,
+            height: utxo.height,
+        }
+    }
+
+    fn to_core(&self) -> Result<t2z_core::FetchedUtxo, UniffiError> {
+        let txid = hex::decode(&self.txid).map_err(|e| UniffiError::Error {
+            msg: format!("Invalid txid hex: {}", e),
+        })?;
+        let script_pubkey = hex::decode(&self.script_pubkey).map_err(|e| UniffiError::Error {
+            msg: format!("Invalid script_pubkey hex: {}", e),
+        })?;
+        Ok(t2z_core::FetchedUtxo {
+            address: self.address.clone(),
+            txid,
+            index: self.index,
+            script_pubkey,
+            value: self.value,
+            height: self.height,
+        })
+    }
+}
+
+/// A batched, paginated, rate-limited lookup of many addresses' UTXOs.
+/// Does no networking itself: send each request to lightwalletd's
+/// GetAddressUtxos and report the reply.
+#[cfg(feature = "wallet_lite")]
+#[derive(uniffi::Object)]
+pub struct UniffiUtxoFetch {
+    inner: std::sync::Mutex<t2z_core::UtxoFetch>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl UniffiUtxoFetch {
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::UtxoFetch> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export]
+impl UniffiUtxoFetch {
+    /// Plans a lookup of addresses on "mainnet" or "testnet". By default,
+    /// 100 addresses per request, 1000 UTXOs per page, and 5 requests per
+    /// second.
+    #[uniffi::constructor]
+    pub fn new(
+        addresses: Vec<String>,
+        network: String,
+        batch_size: Option<u32>,
+        page_size: Option<u32>,
+        requests_per_second: Option<u32>,
+    ) -> Result<Arc<Self>, UniffiError> {
+        let defaults = t2z_core::UtxoFetchOptions::default();
+        let options = t2z_core::UtxoFetchOptions {
+            batch_size: batch_size.map_or(defaults.batch_size, |size| size as usize),
+            page_size: page_size.unwrap_or(defaults.page_size),
+            requests_per_second: requests_per_second.unwrap_or(defaults.requests_per_second),
+        };
+        let fetch =
+            t2z_core::fetch_utxos_for_addresses(addresses, parse_network(&network)?, options)?;
+        Ok(Arc::new(UniffiUtxoFetch {
+            inner: std::sync::Mutex::new(fetch),
+        }))
+    }
+
+    /// The next request to make at now (Unix seconds), or None if rate
+    /// limited or nothing is left to request
+    pub fn next_request(&self, now: u64) -> Option<UniffiUtxoPageRequest> {
+        self.lock().next_request(now).map(UniffiUtxoPageRequest::from_core)
+    }
+
+    /// When next_request can next hand out a request; None if none is
+    /// queued
+    pub fn next_request_at(&self, now: u64) -> Option<u64> {
+        self.lock().next_request_at(now)
+    }
+
+    /// Records the reply to request
+    pub fn record_page(
+        &self,
+        request: UniffiUtxoPageRequest,
+        utxos: Vec<UniffiFetchedUtxo>,
+    ) -> Result<(), UniffiError> {
+        let utxos = utxos.iter().map(UniffiFetchedUtxo::to_core).collect::<Result<_, _>>()?;
+        Ok(self.lock().record_page(&request.to_core(), utxos)?)
+    }
+
+    /// Records that request failed, queueing it again
+    pub fn record_failure(&self, request: UniffiUtxoPageRequest) -> Result<(), UniffiError> {
+        Ok(self.lock().record_failure(&request.to_core())?)
+    }
+
+    /// Whether every page has been fetched
+    pub fn is_done(&self) -> bool {
+        self.lock().is_done()
+    }
+
+    /// Every UTXO found so far
+    pub fn utxos(&self) -> Vec<UniffiFetchedUtxo> {
+        self.lock().utxos().map(UniffiFetchedUtxo::from_core).collect()
+    }
+
+    /// Total value of the UTXOs found so far, in zatoshis
+    pub fn total_value(&self) -> Result<u64, UniffiError> {
+        Ok(self.lock().total_value()?)
+    }
+
+    /// The UTXOs found as inputs, given pubkeys mapping each address to its
+    /// public key (hex)
+    pub fn to_inputs(
+        &self,
+        pubkeys: std::collections::HashMap<String, String>,
+    ) -> Result<Vec<UniffiTransparentInput>, UniffiError> {
+        let inputs = self.lock().to_inputs(&parse_pubkey_map(pubkeys)?)?;
+        Ok(inputs.iter().map(UniffiTransparentInput::from_core).collect())
+    }
+
+    /// Adds the UTXOs found to account as confirmed at their heights
+    pub fn add_to_account(
+        &self,
+        account: Arc<UniffiAccount>,
+        pubkeys: std::collections::HashMap<String, String>,
+    ) -> Result<(), UniffiError> {
+        let pubkeys = parse_pubkey_map(pubkeys)?;
+        self.lock().add_to_account(&mut account.lock(), &pubkeys)?;
+        account.events.dispatch();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+fn parse_pubkey_map(
+    pubkeys: std::collections::HashMap<String, String>,
+) -> Result<std::collections::BTreeMap<String, Vec<u8>>, UniffiError> {
+    pubkeys
+        .into_iter()
+        .map(|(address, pubkey)| {
+            let pubkey = hex::decode(&pubkey).map_err(|e| UniffiError::Error {
+                msg: format!("Invalid pubkey hex: {}", e),
+            })?;
+            Ok((address, pubkey))
+        })
+        .collect()
+}
+
+/// Receives wallet state changes from a UniffiAccount, UniffiNoteStore, or
+/// UniffiBroadcastQueue
+///
+/// Implemented in the host language and passed to `subscribe`.
+#[cfg(feature = "wallet_lite")]
+#[uniffi::export(callback_interface)]
+pub trait WalletEventListener: Send + Sync {
+    /// Called on the thread that made the change, once it is applied
+    fn on_event(&self, event: UniffiWalletEvent);
+}
+
+/// A change to wallet state
+#[cfg(feature = "wallet_lite")]
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiWalletEvent {
+    /// "utxo_added", "note_received", "tx_confirmed", or "tx_expired"
+    pub kind: String,
+    /// The transaction confirmed or expired, or the added UTXO's
+    /// transaction (hex, internal byte order)
+    pub txid: Option<String>,
+    /// Output index of the added UTXO
+    pub index: Option<u32>,
+    /// Value of the added UTXO
+    pub value: Option<u64>,
+    /// Mined height, if known (note stores report it, accounts do not)
+    pub height: Option<u32>,
+    /// The received note
+    pub note: Option<UniffiTrackedNote>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl From<t2z_core::WalletEvent> for UniffiWalletEvent {
+    fn from(event: t2z_core::WalletEvent) -> Self {
+        let empty = |kind: &str| UniffiWalletEvent {
+            kind: kind.to_string(),
+            txid: None,
+            index: None,
+            value: None,
+            height: None,
+            note: None,
+        };
+        match event {
+            t2z_core::WalletEvent::UtxoAdded { outpoint, value } => UniffiWalletEvent {
+                txid: Some(hex::encode(&outpoint.txid)),
+                index: Some(outpoint.index),
+                value: Some(value),
+                ..empty("utxo_added")
+            },
+            t2z_core::WalletEvent::NoteReceived { note } => UniffiWalletEvent {
+                txid: Some(note.txid.clone()),
+                value: Some(note.value),
+                note: Some(UniffiTrackedNote::from_core(&note)),
+                ..empty("note_received")
+            },
+            t2z_core::WalletEvent::TxConfirmed { txid, height } => UniffiWalletEvent {
+                txid: Some(txid),
+                height,
+                ..empty("tx_confirmed")
+            },
+            t2z_core::WalletEvent::TxExpired { txid } => UniffiWalletEvent {
+                txid: Some(txid),
+                ..empty("tx_expired")
+            },
+        }
+    }
+}
+
+/// Queues core wallet events and hands them to host listeners once the
+/// account or store lock is released, so listeners can call back into it
+#[cfg(feature = "wallet_lite")]
+#[derive(Default)]
+struct EventRelay {
+    queue: Arc<std::sync::Mutex<Vec<t2z_core::WalletEvent>>>,
+    listeners: std::sync::Mutex<Vec<Arc<dyn WalletEventListener>>>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl EventRelay {
+    /// Core listener feeding the queue
+    fn sink(&self) -> impl t2z_core::WalletListener + 'static {
+        let queue = self.queue.clone();
+        move |event: &t2z_core::WalletEvent| {
+            queue.lock().unwrap_or_else(|e| e.into_inner()).push(event.clone());
+        }
+    }
+
+    fn subscribe(&self, listener: Box<dyn WalletEventListener>) {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::from(listener));
+    }
+
+    fn dispatch(&self) {
+        let events = std::mem::take(&mut *self.queue.lock().unwrap_or_else(|e| e.into_inner()));
+        let listeners = self.listeners.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        for event in events {
+            for listener in &listeners {
+                listener.on_event(UniffiWalletEvent::from(event.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+fn block_hash(bytes: Vec<u8>) -> Result<[u8; 32], UniffiError> {
+    bytes.try_into().map_err(|_| UniffiError::Error {
+        msg: "Block hash must be 32 bytes".to_string(),
+    })
+}
+
+/// SQLite-file storage for account state and PCZT sessions
+#[cfg(feature = "sqlite")]
+#[derive(uniffi::Object)]
+pub struct UniffiSqliteStorage {
+    inner: t2z_core::SqliteStorage,
+}
+
+#[cfg(feature = "sqlite")]
+#[uniffi::export]
+impl UniffiSqliteStorage {
+    /// Opens (or creates) the database file at path
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, UniffiError> {
+        Ok(Arc::new(UniffiSqliteStorage {
+            inner: t2z_core::SqliteStorage::open(path)?,
+        }))
+    }
+
+    /// Stores an account's state under name
+    pub fn save_account(&self, name: String, account: Arc<UniffiAccount>) -> Result<(), UniffiError> {
+        Ok(t2z_core::save_account(&self.inner, &name, &account.lock())?)
+    }
+
+    /// Loads the account stored under name, if any
+    pub fn load_account(&self, name: String) -> Result<Option<Arc<UniffiAccount>>, UniffiError> {
+        let account = t2z_core::load_account(&self.inner, &name)?;
+        Ok(account.map(UniffiAccount::wrap))
+    }
+
+    /// Stores the broadcast queue
+    pub fn save_broadcast_queue(
+        &self,
+        queue: Arc<UniffiBroadcastQueue>,
+    ) -> Result<(), UniffiError> {
+        Ok(t2z_core::save_broadcast_queue(&self.inner, &queue.lock())?)
+    }
+
+    /// Loads the stored broadcast queue, if any
+    pub fn load_broadcast_queue(&self) -> Result<Option<Arc<UniffiBroadcastQueue>>, UniffiError> {
+        let queue = t2z_core::load_broadcast_queue(&self.inner)?;
+        Ok(queue.map(UniffiBroadcastQueue::wrap))
+    }
+
+    /// Stores an in-progress PCZT under session_id
+    pub fn save_pczt(&self, session_id: String, pczt: Arc<UniffiPczt>) -> Result<(), UniffiError> {
+        Ok(t2z_core::save_pczt(&self.inner, &session_id, &pczt.read())?)
+    }
+
+    /// Loads the PCZT stored under session_id, if any
+    pub fn load_pczt(&self, session_id: String) -> Result<Option<Arc<UniffiPczt>>, UniffiError> {
+        let pczt = t2z_core::load_pczt(&self.inner, &session_id)?;
+        Ok(pczt.map(UniffiPczt::new))
+    }
+
+    /// Removes the PCZT stored under session_id
+    pub fn delete_pczt(&self, session_id: String) -> Result<(), UniffiError> {
+        Ok(t2z_core::delete_pczt(&self.inner, &session_id)?)
+    }
+
+    /// Session IDs of every stored PCZT
+    pub fn list_pczt_sessions(&self) -> Result<Vec<String>, UniffiError> {
+        Ok(t2z_core::list_pczt_sessions(&self.inner)?)
+    }
+}
+
+/// A problem found by validate_request
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiRequestIssue {
+    /// Index of the payment, or None for the whole request
+    pub payment_index: Option<u32>,
+    /// e.g. "invalid_address", "memo_on_transparent", "duplicate_recipient"
+    pub kind: String,
+    /// "error" (proposing would fail) or "warning"
+    pub severity: String,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// Checks a transaction request before selecting any inputs
+#[uniffi::export]
+pub fn validate_request(
+    transaction_request: UniffiTransactionRequest,
+    network: String,
+) -> Result<Vec<UniffiRequestIssue>, UniffiError> {
+    let request = transaction_request.to_core()?;
+    let issues = t2z_core::validate_request(&request, parse_network(&network)?);
+
+    Ok(issues.into_iter().map(UniffiRequestIssue::from).collect())
+}
+
+impl From<t2z_core::RequestIssue> for UniffiRequestIssue {
+    fn from(issue: t2z_core::RequestIssue) -> Self {
+        UniffiRequestIssue {
+            payment_index: issue.payment_index.map(|i| i as u32),
+            kind: match issue.kind {
+                t2z_core::RequestIssueKind::NoPayments => "no_payments",
+                t2z_core::RequestIssueKind::InvalidAddress => "invalid_address",
+                t2z_core::RequestIssueKind::WrongNetwork => "wrong_network",
+                t2z_core::RequestIssueKind::UnsupportedAddress => "unsupported_address",
+                t2z_core::RequestIssueKind::MemoOnTransparent => "memo_on_transparent",
+                t2z_core::RequestIssueKind::MemoTooLong => "memo_too_long",
+                t2z_core::RequestIssueKind::ZeroAmount => "zero_amount",
+                t2z_core::RequestIssueKind::AmountTooLarge => "amount_too_large",
+                t2z_core::RequestIssueKind::DuplicateRecipient => "duplicate_recipient",
+                t2z_core::RequestIssueKind::TotalOverflow => "total_overflow",
+                t2z_core::RequestIssueKind::PaysInputAddress => "pays_input_address",
+                t2z_core::RequestIssueKind::AddressReused => "address_reused",
+            }
+            .to_string(),
+            severity: match issue.severity {
+                t2z_core::IssueSeverity::Error => "error",
+                t2z_core::IssueSeverity::Warning => "warning",
+            }
+            .to_string(),
+            message: issue.message,
+        }
+    }
+}
+
+/// Parses a ZIP 321 zcash: payment URI (memos come back as hex)
+#[uniffi::export]
+pub fn parse_payment_uri(uri: String) -> Result<UniffiTransactionRequest, UniffiError> {
+    Ok(t2z_core::parse_payment_uri(&uri)?.into())
+}
+
+/// Writes a transaction request as a ZIP 321 zcash: payment URI
+#[uniffi::export]
+pub fn payment_uri(transaction_request: UniffiTransactionRequest) -> Result<String, UniffiError> {
+    Ok(t2z_core::payment_uri(&transaction_request.to_core()?)?)
+}
+
+/// One ZIP 321 test case from zip321_test_vectors
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiZip321Vector {
+    /// What the vector exercises
+    pub name: String,
+    /// The URI to parse
+    pub uri: String,
+    /// What parse_payment_uri returns for uri, or None if it must fail
+    pub request: Option<UniffiTransactionRequest>,
+    /// What payment_uri returns for request, or None
+    pub canonical_uri: Option<String>,
+}
+
+/// The ZIP 321 test vectors the core parser is tested against, for checking
+/// a front-end's own URI handling
+#[uniffi::export]
+pub fn zip321_test_vectors() -> Vec<UniffiZip321Vector> {
+    t2z_core::zip321::test_vectors()
+        .into_iter()
+        .map(|vector| UniffiZip321Vector {
+            name: vector.name,
+            uri: vector.uri,
+            request: vector.request.map(UniffiTransactionRequest::from),
+            canonical_uri: vector.canonical_uri,
+        })
+        .collect()
+}
+
+/// A problem found by check_consensus_rules
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiTxIssue {
+    /// Index of the transparent input or output, or None
+    pub index: Option<u32>,
+    /// e.g. "too_large", "wrong_branch", "expired", "non_standard_output"
+    pub kind: String,
+    /// Human-readable description
+    pub message: String,
+}
+
+/// Checks a signed transaction for problems that would get it rejected if
+/// broadcast for inclusion at height (usually the tip plus one)
+#[uniffi::export]
+pub fn check_consensus_rules(
+    tx_bytes: Vec<u8>,
+    network: String,
+    height: u32,
+) -> Result<Vec<UniffiTxIssue>, UniffiError> {
+    let issues = t2z_core::check_consensus_rules(&tx_bytes, parse_network(&network)?, height)?;
+    Ok(issues.into_iter().map(UniffiTxIssue::from).collect())
+}
+
+/// Computes the ZIP 244 auth digest of a transaction, in internal byte
+/// order (all 0xFF for v4 transactions)
+#[uniffi::export]
+pub fn compute_auth_digest(tx_bytes: Vec<u8>) -> Result<Vec<u8>, UniffiError> {
+    Ok(t2z_core::compute_auth_digest(&tx_bytes)?.to_vec())
+}
+
+/// The txid and auth digest of a transaction, hex in the byte order RPCs
+/// and explorers display
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiTransactionDigests {
+    pub txid: String,
+    pub auth_digest: String,
+}
+
+/// Computes the txid and auth digest of a transaction
+#[uniffi::export]
+pub fn compute_transaction_digests(
+    tx_bytes: Vec<u8>,
+) -> Result<UniffiTransactionDigests, UniffiError> {
+    let digests = t2z_core::compute_transaction_digests(&tx_bytes)?;
+    Ok(UniffiTransactionDigests {
+        txid: digests.txid,
+        auth_digest: digests.auth_digest,
+    })
+}
+
+impl From<t2z_core::TxIssue> for UniffiTxIssue {
+    fn from(issue: t2z_core::TxIssue) -> Self {
+        UniffiTxIssue {
+            index: issue.index.map(|i| i as u32),
+            kind: match issue.kind {
+                t2z_core::TxIssueKind::TooLarge => "too_large",
+                t2z_core::TxIssueKind::UnsupportedVersion => "unsupported_version",
+                t2z_core::TxIssueKind::WrongBranch => "wrong_branch",
+                t2z_core::TxIssueKind::Expired => "expired",
+                t2z_core::TxIssueKind::ExpiringSoon => "expiring_soon",
+                t2z_core::TxIssueKind::InvalidExpiry => "invalid_expiry",
+                t2z_core::TxIssueKind::Empty => "empty",
+                t2z_core::TxIssueKind::ValueOutOfRange => "value_out_of_range",
+                t2z_core::TxIssueKind::NonStandardOutput => "non_standard_output",
+                t2z_core::TxIssueKind::UnsignedInput => "unsigned_input",
+                t2z_core::TxIssueKind::NonStandardInput => "non_standard_input",
+            }
+            .to_string(),
+            message: issue.message,
+        }
+    }
+}
+
+/// Addresses used by earlier proposals in a session, for privacy warnings
+/// about address reuse
+#[derive(uniffi::Object)]
+pub struct UniffiAddressHistory {
+    inner: std::sync::Mutex<t2z_core::AddressHistory>,
+}
+
+impl UniffiAddressHistory {
+    fn lock(&self) -> std::sync::MutexGuard<'_, t2z_core::AddressHistory> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[uniffi::export]
+impl UniffiAddressHistory {
+    /// Creates an empty history
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(UniffiAddressHistory {
+            inner: std::sync::Mutex::new(t2z_core::AddressHistory::new()),
+        })
+    }
+
+    /// Restores a history from bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, UniffiError> {
+        let history = t2z_core::AddressHistory::from_bytes(&bytes)?;
+        Ok(Arc::new(UniffiAddressHistory {
+            inner: std::sync::Mutex::new(history),
+        }))
+    }
+
+    /// Serializes the history to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// Warns about recipients or a change address that reuse an input's
+    /// address or an address from an earlier recorded proposal
+    pub fn check(
+        &self,
+        inputs_to_spend: Vec<UniffiTransparentInput>,
+        transaction_request: UniffiTransactionRequest,
+        change_address: Option<String>,
+    ) -> Result<Vec<UniffiRequestIssue>, UniffiError> {
+        let inputs = inputs_to_spend
+            .iter()
+            .map(|i| i.to_core())
+            .collect::<Result<Vec<_>, _>>()?;
+        let request = transaction_request.to_core()?;
+
+        let issues = self.lock().check(&inputs, &request, change_address.as_deref());
+        Ok(issues.into_iter().map(UniffiRequestIssue::from).collect())
+    }
+
+    /// Records a proposal's recipients and change address
+    pub fn record(
+        &self,
+        transaction_request: UniffiTransactionRequest,
+        change_address: Option<String>,
+    ) -> Result<(), UniffiError> {
+        let request = transaction_request.to_core()?;
+        self.lock().record(&request, change_address.as_deref());
+        Ok(())
+    }
+}
+
+/// Fee and change a transaction request would result in
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiPreview {
+    /// ZIP 317 fee in zatoshis
+    pub fee: u64,
+    /// Change in zatoshis
+    pub change: u64,
+    /// Estimated transaction size in bytes
+    pub size: u64,
+    /// Orchard actions (drives proving time)
+    pub action_count: u32,
+}
+
+/// Previews the fee and change of a transaction without building it
+#[uniffi::export]
+pub fn preview_transaction(
+    inputs_to_spend: Vec<UniffiTransparentInput>,
+    transaction_request: UniffiTransactionRequest,
+    change_address: Option<String>,
+    network: String,
+) -> Result<UniffiPreview, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        inputs_to_spend.iter().map(|i| i.to_core()).collect();
+    let request = transaction_request.to_core()?;
+
+    let preview = t2z_core::preview_transaction(
+        &inputs?,
+        &request,
+        change_address.as_deref(),
+        parse_network(&network)?,
+    )?;
+
+    Ok(UniffiPreview {
+        fee: preview.fee,
+        change: preview.change,
+        size: preview.size as u64,
+        action_count: preview.action_count as u32,
+    })
+}
+
+/// Extra ZIP 317 fee, in zatoshis, of splitting the change into two
+/// non-round Orchard notes (split_non_round_change): 5000 when the second
+/// note needs its own action, 0 when it replaces a padding action
+#[uniffi::export]
+pub fn non_round_split_fee(
+    inputs_to_spend: Vec<UniffiTransparentInput>,
+    transaction_request: UniffiTransactionRequest,
+    change_address: String,
+    network: String,
+) -> Result<u64, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        inputs_to_spend.iter().map(|i| i.to_core()).collect();
+    let request = transaction_request.to_core()?;
+
+    Ok(t2z_core::non_round_split_fee(
+        &inputs?,
+        &request,
+        &change_address,
+        parse_network(&network)?,
+    )?)
+}
+
+/// Re-proposes a transaction that expired unmined, with a new expiry and a
+/// fee bump (ZIP 317 fee times fee_multiplier, at least 1.0)
+#[uniffi::export]
+pub fn rebuild_with_new_expiry(
+    original_request: UniffiTransactionRequest,
+    inputs_to_spend: Vec<UniffiTransparentInput>,
+    change_address: Option<String>,
+    network: String,
+    new_expiry: u32,
+    fee_multiplier: f64,
+) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let inputs: Result<Vec<t2z_core::TransparentInput>, UniffiError> =
+        inputs_to_spend.iter().map(|i| i.to_core()).collect();
+    let request = original_request.to_core()?;
+
+    let pczt = t2z_core::rebuild_with_new_expiry(
+        &request,
+        &inputs?,
+        change_address.as_deref(),
+        parse_network(&network)?,
+        new_expiry,
+        fee_multiplier,
+    )?;
+
+    Ok(UniffiPczt::new(pczt))
+}
+
+/// Combines multiple PCZTs into one
+#[uniffi::export]
+pub fn combine_pczts(pczt_list: Vec<Arc<UniffiPczt>>) -> Result<Arc<UniffiPczt>, UniffiError> {
+    let pczts: Vec<Pczt> = pczt_list.iter().map(|p| p.snapshot()).collect();
+    let combined = t2z_core::combine(pczts)?;
+    Ok(UniffiPczt::new(combined))
+}
+
+/// A signature in a PCZT that does not verify
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UniffiInvalidSignature {
+    /// "transparent", "orchard_spend_auth" or "orchard_proof"
+    pub kind: String,
+    /// Transparent input or Orchard action index, None for the proof
+    pub index: Option<u32>,
+    /// Hex public key of a transparent signature
+    pub pubkey: Option<String>,
+    /// Why it does not verify
+    pub reason: String,
+}
+
+impl From<t2z_core::InvalidSignature> for UniffiInvalidSignature {
+    fn from(invalid: t2z_core::InvalidSignature) -> Self {
+        match invalid {
+            t2z_core::InvalidSignature::Transparent {
+                input_index,
+                pubkey,
+                reason,
+            } => UniffiInvalidSignature {
+                kind: "transparent".to_string(),
+                index: Some(input_index as u32),
+                pubkey: Some(pubkey),
+                reason,
+            },
+            t2z_core::InvalidSignature::OrchardSpendAuth {
+                action_index,
+                reason,
+            } => UniffiInvalidSignature {
+                kind: "orchard_spend_auth".to_string(),
+                index: Some(action_index as u32),
+                pubkey: None,
+                reason,
+            },
+            t2z_core::InvalidSignature::OrchardProof { reason } => UniffiInvalidSignature {
+                kind: "orchard_proof".to_string(),
+                index: None,
+                pubkey: None,
+                reason,
+            },
+        }
+    }
+}
+
+/// Checks every signature in the PCZT against its sighash, returning the
+/// ones that do not verify (empty if all do)
+#[uniffi::export]
+pub fn find_invalid_signatures(
+    pczt: Arc<UniffiPczt>,
+) -> Result<Vec<UniffiInvalidSignature>, UniffiError> {
+    let invalid = t2z_core::find_invalid_signatures(&pczt.snapshot())?;
+    Ok(invalid
+        .into_iter()
+        .map(UniffiInvalidSignature::from)
+        .collect())
+}
+
+/// Checks every signature, then finalizes the PCZT and extracts the
+/// transaction bytes; fails naming the first bad signature
+#[uniffi::export]
+pub fn finalize_and_extract_verified(pczt: Arc<UniffiPczt>) -> Result<Vec<u8>, UniffiError> {
+    Ok(t2z_core::finalize_and_extract_verified(pczt.snapshot())?)
+}
+
+/// Finalizes the PCZT and extracts the transaction bytes
+#[uniffi::export]
+pub fn finalize_and_extract(pczt: Arc<UniffiPczt>) -> Result<Vec<u8>, UniffiError> {
+    let tx_bytes = t2z_core::finalize_and_extract(pczt.snapshot())?;
+    Ok(tx_bytes)
+}
+
+/// Finalizes the PCZT and extracts the transaction without blocking the
+/// calling thread
+///
+/// Extraction verifies the Orchard proof, which can take seconds. Kotlin: a
+/// suspend function; cancelling it stops waiting for the result.
+#[uniffi::export]
+pub async fn finalize_and_extract_async(pczt: Arc<UniffiPczt>) -> Result<Vec<u8>, UniffiError> {
+    let pczt = pczt.snapshot();
+    let tx_bytes = task::spawn_blocking(move || t2z_core::finalize_and_extract(pczt)).await?;
+    Ok(tx_bytes)
+}
+
+/// Finalizes the PCZT and extracts the transaction as hex string
+#[uniffi::export]
+pub fn finalize_and_extract_hex(pczt: Arc<UniffiPczt>) -> Result<String, UniffiError> {
+    let tx_bytes = finalize_and_extract(pczt)?;
+    Ok(hex::encode(tx_bytes))
+}
+
+/// Whether the PCZT's transaction can no longer be mined after current_height
+#[uniffi::export]
+pub fn pczt_is_expired(pczt: Arc<UniffiPczt>, current_height: u32) -> bool {
+    t2z_core::Expiry::of_pczt(&pczt.read()).is_expired(current_height)
+}
+
+/// Blocks after current_height that can still include the PCZT's transaction
+/// (0 once expired), or None if it never expires
+#[uniffi::export]
+pub fn pczt_blocks_until_expiry(pczt: Arc<UniffiPczt>, current_height: u32) -> Option<u32> {
+    t2z_core::Expiry::of_pczt(&pczt.read()).blocks_until_expiry(current_height)
+}
+
+/// Whether an extracted transaction can no longer be mined after current_height
+#[uniffi::export]
+pub fn transaction_is_expired(tx_bytes: Vec<u8>, current_height: u32) -> Result<bool, UniffiError> {
+    Ok(t2z_core::Expiry::of_transaction(&tx_bytes)?.is_expired(current_height))
+}
+
+/// Blocks after current_height that can still include an extracted
+/// transaction, or None if it never expires
+#[uniffi::export]
+pub fn transaction_blocks_until_expiry(
+    tx_bytes: Vec<u8>,
+    current_height: u32,
+) -> Result<Option<u32>, UniffiError> {
+    Ok(t2z_core::Expiry::of_transaction(&tx_bytes)?.blocks_until_expiry(current_height))
+}
+
+/// Whether a raw transaction is a coinbase transaction, whose outputs can
+/// only be spent by fully shielded transactions
+#[uniffi::export]
+pub fn is_coinbase_transaction(tx_bytes: Vec<u8>) -> Result<bool, UniffiError> {
+    Ok(t2z_core::is_coinbase_transaction(&tx_bytes)?)
+}
+
+/// Check if the proving key has been built and cached
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn is_proving_key_ready() -> bool {
+    t2z_core::is_proving_key_loaded()
+}
+
+/// Pre-build the Orchard proving key
+///
+/// Call this at application startup to avoid blocking during transaction proving.
+#[cfg(feature = "prover")]
+#[uniffi::export]
+pub fn prebuild_proving_key() {
+    t2z_core::load_orchard_proving_key();
+}
+
+/// Pre-build the Orchard verifying key (much cheaper than the proving key)
+#[cfg(feature = "verifier")]
+#[uniffi::export]
+pub fn prebuild_verifying_key() {
+    t2z_core::load_orchard_verifying_key();
+}
+
+/// Verifies the Orchard proof in a PCZT
+///
+/// Available in verifier-only builds, which don't include the proving key.
+#[cfg(feature = "verifier")]
+#[uniffi::export]
+pub fn verify_orchard_proof(pczt: Arc<UniffiPczt>) -> Result<(), UniffiError> {
+    Ok(t2z_core::verify_orchard_proof(&pczt.read())?)
 }
 
 /// Gets the version of the library
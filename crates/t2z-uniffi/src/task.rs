@@ -0,0 +1,62 @@
+//! Background threads for async exports.
+//!
+//! UniFFI async functions are polled by the foreign language's executor (e.g.
+//! a Kotlin coroutine), so blocking work must not run inside `poll`. A
+//! `BlockingTask` runs it on its own thread and completes when it is done.
+//! Dropping the task (a cancelled coroutine) detaches the thread, which
+//! finishes and discards its result instead of pinning the caller.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+struct TaskState<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Future for work running on a background thread
+pub(crate) struct BlockingTask<T> {
+    state: Arc<Mutex<TaskState<T>>>,
+}
+
+/// Runs `f` on a new thread.
+pub(crate) fn spawn_blocking<T, F>(f: F) -> BlockingTask<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let state = Arc::new(Mutex::new(TaskState {
+        result: None,
+        waker: None,
+    }));
+
+    let thread_state = Arc::clone(&state);
+    thread::spawn(move || {
+        let result = f();
+        let mut state = thread_state.lock().unwrap_or_else(|e| e.into_inner());
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+
+    BlockingTask { state }
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
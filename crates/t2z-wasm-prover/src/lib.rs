@@ -0,0 +1,117 @@
+//! T2Z WASM Prover - separately loadable Orchard prover for T2Z
+//!
+//! The Halo 2 proving code dominates the size of the full `t2z-wasm` bundle.
+//! This crate ships it as its own WebAssembly module so wallets that only
+//! propose, inspect, or sign PCZTs can load a core bundle built without the
+//! `prover` feature, and fetch this module lazily when they actually shield.
+//!
+//! PCZTs cross the module boundary as serialized bytes (or hex):
+//!
+//! ```js
+//! const core = await import('@d4mr/t2z-wasm');
+//! const pczt = core.propose_transaction(...);
+//!
+//! const prover = await import('@d4mr/t2z-wasm-prover');
+//! const proved = core.WasmPczt.from_bytes(prover.prove_pczt(pczt.to_bytes()));
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+/// Initialize the prover module. Sets up panic hooks for better error messages.
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+/// Pre-build the Orchard proving key (~10 seconds, cached afterwards).
+///
+/// Call this in a web worker right after loading the module so the first
+/// `prove_pczt` call doesn't pay the circuit-building cost.
+#[wasm_bindgen]
+pub fn prebuild_proving_key() {
+    t2z_core::load_orchard_proving_key();
+}
+
+/// Check if the proving key has been built and cached.
+#[wasm_bindgen]
+pub fn is_proving_key_ready() -> bool {
+    t2z_core::is_proving_key_loaded()
+}
+
+/// Add Orchard proofs to a serialized PCZT.
+///
+/// # Arguments
+/// * `pczt_bytes` - Serialized PCZT (e.g. from `WasmPczt.to_bytes()` in the core module)
+///
+/// # Returns
+/// The serialized PCZT with proofs added
+#[wasm_bindgen]
+pub fn prove_pczt(pczt_bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+    let pczt = t2z_core::parse_pczt(pczt_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved = t2z_core::prove_transaction(pczt)
+        .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(t2z_core::serialize_pczt(&proved))
+}
+
+/// Add Orchard proofs to a hex-encoded PCZT (convenience method)
+#[wasm_bindgen]
+pub fn prove_pczt_hex(pczt_hex: &str) -> Result<String, JsError> {
+    let pczt = t2z_core::pczt_from_hex(pczt_hex)
+        .map_err(|e| JsError::new(&format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved = t2z_core::prove_transaction(pczt)
+        .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(t2z_core::pczt_to_hex(&proved))
+}
+
+/// Estimate the peak memory (in bytes) proving a serialized PCZT would need.
+///
+/// Includes building the proving key if it isn't cached yet, and is 0 if no
+/// proof is needed.
+#[wasm_bindgen]
+pub fn estimate_proving_memory(pczt_bytes: &[u8]) -> Result<u64, JsError> {
+    let pczt = t2z_core::parse_pczt(pczt_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to parse PCZT: {}", e)))?;
+    Ok(t2z_core::estimate_pczt_proving_memory(&pczt))
+}
+
+/// Add Orchard proofs to a serialized PCZT, only if the estimated peak
+/// memory fits within `max_memory_bytes`.
+///
+/// Fails without doing any work otherwise, so the caller can delegate
+/// proving instead.
+#[wasm_bindgen]
+pub fn prove_pczt_with_budget(
+    pczt_bytes: &[u8],
+    max_memory_bytes: u64,
+) -> Result<Vec<u8>, JsError> {
+    let pczt = t2z_core::parse_pczt(pczt_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to parse PCZT: {}", e)))?;
+
+    let proved =
+        t2z_core::prove_transaction_with_budget(pczt, t2z_core::ProvingBudget { max_memory_bytes })
+            .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+
+    Ok(t2z_core::serialize_pczt(&proved))
+}
+
+/// Whether this module was compiled with WASM SIMD (`simd128`).
+///
+/// The SIMD build lets LLVM vectorize the field arithmetic in the Halo 2
+/// prover; the scalar build runs everywhere. Loaders can use this to confirm
+/// which variant they ended up with.
+#[wasm_bindgen]
+pub fn simd_enabled() -> bool {
+    cfg!(target_feature = "simd128")
+}
+
+/// Get the library version
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
@@ -0,0 +1,30 @@
+export interface WorkerProgress {
+  stage: 'proving_key' | 'proving' | 'signing';
+  done: number;
+  total: number;
+}
+
+export interface WorkerJobOptions {
+  onProgress?: (progress: WorkerProgress) => void;
+}
+
+/** Runs proving and signing in dedicated workers that call `serve_worker()`. */
+export class T2zWorkerPool {
+  constructor(createWorker: () => Worker, options?: { size?: number });
+
+  /** Builds the proving key in every worker, so the first proof is fast. */
+  prebuild(options?: WorkerJobOptions): Promise<void>;
+
+  /** Proves a serialized PCZT. The bytes' buffer is transferred to the worker. */
+  prove(pcztBytes: Uint8Array, options?: WorkerJobOptions): Promise<Uint8Array>;
+
+  /** Signs transparent inputs, with one hex private key (or null to skip) per input. */
+  sign(
+    pcztBytes: Uint8Array,
+    secretKeys: (string | null)[],
+    options?: WorkerJobOptions,
+  ): Promise<Uint8Array>;
+
+  /** Stops every worker; pending jobs are rejected. */
+  terminate(): void;
+}
@@ -0,0 +1,146 @@
+// Main-thread side of the t2z worker protocol (see src/worker.rs).
+//
+// Copied into the npm package as `@d4mr/t2z-wasm/worker-pool.js` by
+// `just package-worker-pool`. Each worker runs a script like:
+//
+//   import { serve_worker } from '@d4mr/t2z-wasm';
+//   serve_worker();
+
+const PROTOCOL_VERSION = 1;
+
+export class T2zWorkerPool {
+  /**
+   * @param {() => Worker} createWorker Spawns a worker running serve_worker()
+   * @param {{ size?: number }} [options] Number of workers (default 1)
+   */
+  constructor(createWorker, { size = 1 } = {}) {
+    this.nextId = 0;
+    this.queue = [];
+    this.jobs = new Map();
+    this.workers = Array.from({ length: Math.max(1, size) }, () => this.spawn(createWorker));
+  }
+
+  /** Builds the proving key in every worker, so the first proof is fast. */
+  prebuild({ onProgress } = {}) {
+    return Promise.all(
+      this.workers.map((slot) => this.run({ op: 'prebuild' }, [], onProgress, slot)),
+    ).then(() => undefined);
+  }
+
+  /**
+   * Proves a serialized PCZT. The bytes' buffer is transferred to the
+   * worker, so pass a copy if you still need them.
+   * @returns {Promise<Uint8Array>} The proved PCZT
+   */
+  prove(pcztBytes, { onProgress } = {}) {
+    return this.run({ op: 'prove', pczt: pcztBytes }, [pcztBytes.buffer], onProgress);
+  }
+
+  /**
+   * Signs transparent inputs, with one hex private key (or null to skip)
+   * per input.
+   * @returns {Promise<Uint8Array>} The signed PCZT
+   */
+  sign(pcztBytes, secretKeys, { onProgress } = {}) {
+    return this.run(
+      { op: 'sign', pczt: pcztBytes, secret_keys: secretKeys },
+      [pcztBytes.buffer],
+      onProgress,
+    );
+  }
+
+  /** Stops every worker; pending jobs are rejected. */
+  terminate() {
+    for (const slot of this.workers) {
+      slot.worker.terminate();
+    }
+    const error = new Error('Worker pool terminated');
+    for (const job of this.jobs.values()) {
+      job.reject(error);
+    }
+    for (const job of this.queue) {
+      job.reject(error);
+    }
+    this.jobs.clear();
+    this.queue = [];
+  }
+
+  spawn(createWorker) {
+    const slot = { worker: createWorker(), ready: false, busy: false };
+    slot.worker.onmessage = (event) => this.receive(slot, event.data);
+    slot.worker.onerror = (event) => this.fail(slot, new Error(event.message || 'Worker error'));
+    return slot;
+  }
+
+  // Runs a job on `slot`, or on the next idle worker if none is given
+  run(request, transfer, onProgress, slot) {
+    return new Promise((resolve, reject) => {
+      this.queue.push({ request, transfer, onProgress, slot, resolve, reject });
+      this.dispatch();
+    });
+  }
+
+  dispatch() {
+    for (let i = 0; i < this.queue.length; ) {
+      const job = this.queue[i];
+      const slot = job.slot || this.workers.find((s) => s.ready && !s.busy);
+      if (!slot || !slot.ready || slot.busy) {
+        i += 1;
+        continue;
+      }
+      this.queue.splice(i, 1);
+      const id = this.nextId++;
+      slot.busy = true;
+      this.jobs.set(id, { ...job, slot });
+      slot.worker.postMessage({ id, ...job.request }, job.transfer);
+    }
+  }
+
+  receive(slot, message) {
+    if (message.type === 'ready') {
+      if (message.protocol !== PROTOCOL_VERSION) {
+        this.fail(slot, new Error(`Unsupported worker protocol ${message.protocol}`));
+        return;
+      }
+      slot.ready = true;
+      this.dispatch();
+      return;
+    }
+
+    const job = this.jobs.get(message.id);
+    if (!job) {
+      return;
+    }
+    if (message.type === 'progress') {
+      job.onProgress?.({ stage: message.stage, done: message.done, total: message.total });
+      return;
+    }
+
+    this.jobs.delete(message.id);
+    slot.busy = false;
+    if (message.type === 'error') {
+      job.reject(new Error(message.message));
+    } else {
+      job.resolve(message.pczt);
+    }
+    this.dispatch();
+  }
+
+  // Rejects the worker's current job and any queued for it specifically;
+  // the worker stays in the pool
+  fail(slot, error) {
+    for (const [id, job] of this.jobs) {
+      if (job.slot === slot) {
+        this.jobs.delete(id);
+        job.reject(error);
+      }
+    }
+    if (!slot.ready) {
+      const pinned = this.queue.filter((job) => job.slot === slot);
+      this.queue = this.queue.filter((job) => job.slot !== slot);
+      pinned.forEach((job) => job.reject(error));
+    }
+    slot.busy = false;
+    this.dispatch();
+  }
+}
@@ -0,0 +1,243 @@
+//! IndexedDB-backed `Storage` for browsers (feature `wallet_lite`).
+//!
+//! IndexedDB is asynchronous while `t2z_core::Storage` is not, so the adapter
+//! loads the whole object store into memory when opened, serves reads from
+//! that copy, and writes every batch through to IndexedDB as a single
+//! readwrite transaction (which IndexedDB commits atomically). Call `flush()`
+//! to wait until the last batch is durable.
+
+use std::cell::RefCell;
+
+use t2z_core::{MemoryStorage, Storage, T2ZError, WriteBatch};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{WasmAccount, WasmBroadcastQueue, WasmPczt};
+
+const STORE_NAME: &str = "kv";
+const DB_VERSION: u32 = 1;
+
+/// Resolves with the request's result once it succeeds.
+async fn await_request(request: &web_sys::IdbRequest) -> Result<JsValue, JsError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let req = request.clone();
+        let on_success = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = resolve.call1(&JsValue::NULL, &req.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let on_error = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = reject.call0(&JsValue::NULL);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+
+    JsFuture::from(promise)
+        .await
+        .map_err(|_| JsError::new("IndexedDB request failed"))
+}
+
+/// Promise that settles when the transaction commits or fails.
+///
+/// Created right after the writes are queued, before control returns to the
+/// event loop, so the completion event cannot be missed.
+fn transaction_promise(transaction: &web_sys::IdbTransaction) -> js_sys::Promise {
+    js_sys::Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = resolve.call0(&JsValue::NULL);
+        });
+        let abort_reject = reject.clone();
+        let on_error = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = reject.call0(&JsValue::NULL);
+        });
+        let on_abort = Closure::once_into_js(move |_: web_sys::Event| {
+            let _ = abort_reject.call0(&JsValue::NULL);
+        });
+        transaction.set_oncomplete(Some(on_complete.unchecked_ref()));
+        transaction.set_onerror(Some(on_error.unchecked_ref()));
+        transaction.set_onabort(Some(on_abort.unchecked_ref()));
+    })
+}
+
+/// Persistent storage for accounts and PCZT sessions, backed by IndexedDB.
+#[wasm_bindgen]
+pub struct WasmIndexedDbStorage {
+    db: web_sys::IdbDatabase,
+    cache: MemoryStorage,
+    last_write: RefCell<Option<js_sys::Promise>>,
+}
+
+#[wasm_bindgen]
+impl WasmIndexedDbStorage {
+    /// Open (or create) the IndexedDB database `name` and load its contents.
+    #[wasm_bindgen]
+    pub async fn open(name: String) -> Result<WasmIndexedDbStorage, JsError> {
+        let factory: web_sys::IdbFactory =
+            js_sys::Reflect::get(&js_sys::global(), &"indexedDB".into())
+                .ok()
+                .and_then(|f| f.dyn_into().ok())
+                .ok_or_else(|| JsError::new("IndexedDB is not available"))?;
+
+        let open_request = factory
+            .open_with_u32(&name, DB_VERSION)
+            .map_err(|_| JsError::new("Failed to open IndexedDB database"))?;
+
+        let upgrade_request = open_request.clone();
+        let on_upgrade = Closure::once_into_js(move |_: web_sys::Event| {
+            if let Ok(db) = upgrade_request
+                .result()
+                .and_then(|r| r.dyn_into::<web_sys::IdbDatabase>())
+                && !db.object_store_names().contains(STORE_NAME)
+            {
+                let _ = db.create_object_store(STORE_NAME);
+            }
+        });
+        open_request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+        let db: web_sys::IdbDatabase = await_request(&open_request)
+            .await?
+            .dyn_into()
+            .map_err(|_| JsError::new("Failed to open IndexedDB database"))?;
+
+        let store = db
+            .transaction_with_str(STORE_NAME)
+            .and_then(|tx| tx.object_store(STORE_NAME))
+            .map_err(|_| JsError::new("Failed to read IndexedDB store"))?;
+
+        let keys_request = store
+            .get_all_keys()
+            .map_err(|_| JsError::new("Failed to read IndexedDB keys"))?;
+        let values_request = store
+            .get_all()
+            .map_err(|_| JsError::new("Failed to read IndexedDB values"))?;
+
+        let keys: js_sys::Array = await_request(&keys_request).await?.unchecked_into();
+        let values: js_sys::Array = await_request(&values_request).await?.unchecked_into();
+
+        let entries = keys.iter().zip(values.iter()).map(|(key, value)| {
+            (
+                js_sys::Uint8Array::new(&key).to_vec(),
+                js_sys::Uint8Array::new(&value).to_vec(),
+            )
+        });
+
+        Ok(WasmIndexedDbStorage {
+            db,
+            cache: MemoryStorage::from_entries(entries),
+            last_write: RefCell::new(None),
+        })
+    }
+
+    /// Promise that resolves once the last batch has been committed to IndexedDB
+    ///
+    /// Batches commit in order, so this covers every earlier write too.
+    #[wasm_bindgen]
+    pub fn flush(&self) -> js_sys::Promise {
+        self.last_write
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| js_sys::Promise::resolve(&JsValue::UNDEFINED))
+    }
+
+    /// Store an account's state under `name`
+    #[wasm_bindgen]
+    pub fn save_account(&self, name: &str, account: &WasmAccount) -> Result<(), JsError> {
+        t2z_core::save_account(self, name, &account.inner)
+            .map_err(|e| JsError::new(&format!("Failed to save account: {}", e)))
+    }
+
+    /// Load the account stored under `name`, if any
+    #[wasm_bindgen]
+    pub fn load_account(&self, name: &str) -> Result<Option<WasmAccount>, JsError> {
+        let account = t2z_core::load_account(self, name)
+            .map_err(|e| JsError::new(&format!("Failed to load account: {}", e)))?;
+        Ok(account.map(WasmAccount::wrap))
+    }
+
+    /// Store the broadcast queue
+    #[wasm_bindgen]
+    pub fn save_broadcast_queue(&self, queue: &WasmBroadcastQueue) -> Result<(), JsError> {
+        t2z_core::save_broadcast_queue(self, &queue.inner)
+            .map_err(|e| JsError::new(&format!("Failed to save broadcast queue: {}", e)))
+    }
+
+    /// Load the stored broadcast queue, if any
+    #[wasm_bindgen]
+    pub fn load_broadcast_queue(&self) -> Result<Option<WasmBroadcastQueue>, JsError> {
+        let queue = t2z_core::load_broadcast_queue(self)
+            .map_err(|e| JsError::new(&format!("Failed to load broadcast queue: {}", e)))?;
+        Ok(queue.map(WasmBroadcastQueue::wrap))
+    }
+
+    /// Store an in-progress PCZT under `session_id`
+    #[wasm_bindgen]
+    pub fn save_pczt(&self, session_id: &str, pczt: &WasmPczt) -> Result<(), JsError> {
+        t2z_core::save_pczt(self, session_id, &pczt.inner)
+            .map_err(|e| JsError::new(&format!("Failed to save PCZT: {}", e)))
+    }
+
+    /// Load the PCZT stored under `session_id`, if any
+    #[wasm_bindgen]
+    pub fn load_pczt(&self, session_id: &str) -> Result<Option<WasmPczt>, JsError> {
+        let pczt = t2z_core::load_pczt(self, session_id)
+            .map_err(|e| JsError::new(&format!("Failed to load PCZT: {}", e)))?;
+        Ok(pczt.map(|inner| WasmPczt { inner }))
+    }
+
+    /// Remove the PCZT stored under `session_id`
+    #[wasm_bindgen]
+    pub fn delete_pczt(&self, session_id: &str) -> Result<(), JsError> {
+        t2z_core::delete_pczt(self, session_id)
+            .map_err(|e| JsError::new(&format!("Failed to delete PCZT: {}", e)))
+    }
+
+    /// Session IDs of every stored PCZT
+    #[wasm_bindgen]
+    pub fn list_pczt_sessions(&self) -> Result<Vec<String>, JsError> {
+        t2z_core::list_pczt_sessions(self)
+            .map_err(|e| JsError::new(&format!("Failed to list PCZT sessions: {}", e)))
+    }
+}
+
+impl Storage for WasmIndexedDbStorage {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, T2ZError> {
+        self.cache.get(key)
+    }
+
+    fn keys_with_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, T2ZError> {
+        self.cache.keys_with_prefix(prefix)
+    }
+
+    fn write_batch(&self, batch: WriteBatch) -> Result<(), T2ZError> {
+        let storage_error = |what: &str| T2ZError::Storage(format!("IndexedDB {} failed", what));
+
+        let transaction = self
+            .db
+            .transaction_with_str_and_mode(STORE_NAME, web_sys::IdbTransactionMode::Readwrite)
+            .map_err(|_| storage_error("transaction"))?;
+        let store = transaction
+            .object_store(STORE_NAME)
+            .map_err(|_| storage_error("object store"))?;
+
+        for op in batch.ops() {
+            match op {
+                t2z_core::BatchOp::Put { key, value } => {
+                    let key = js_sys::Uint8Array::from(key.as_slice());
+                    let value = js_sys::Uint8Array::from(value.as_slice());
+                    store
+                        .put_with_key(&value, &key)
+                        .map_err(|_| storage_error("put"))?;
+                }
+                t2z_core::BatchOp::Delete { key } => {
+                    let key = js_sys::Uint8Array::from(key.as_slice());
+                    store.delete(&key).map_err(|_| storage_error("delete"))?;
+                }
+            }
+        }
+
+        // Only update the in-memory copy once IndexedDB has accepted every write
+        self.cache.write_batch(batch)?;
+        *self.last_write.borrow_mut() = Some(transaction_promise(&transaction));
+        Ok(())
+    }
+}
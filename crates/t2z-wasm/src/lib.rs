@@ -7,8 +7,20 @@
 
 use wasm_bindgen::prelude::*;
 
+// IndexedDB storage adapter - in separate file
+#[cfg(feature = "wallet_lite")]
+mod indexed_db;
+
+#[cfg(feature = "wallet_lite")]
+pub use indexed_db::WasmIndexedDbStorage;
+
 mod utils;
 
+// Worker pool message handling - in separate file
+mod worker;
+
+pub use worker::{WORKER_PROTOCOL_VERSION, serve_worker};
+
 // Re-export core types for documentation
 pub use t2z_core::{Network, Payment, T2ZError, TransactionRequest, TransparentInput};
 
@@ -43,17 +55,39 @@ pub fn init_panic_hook() {
 /// # Important
 /// Unlike Sapling which requires downloading ~50MB proving keys,
 /// Orchard uses Halo 2 and builds the circuit programmatically - no downloads needed!
+#[cfg(feature = "prover")]
 #[wasm_bindgen]
 pub fn prebuild_proving_key() {
     t2z_core::load_orchard_proving_key();
 }
 
 /// Check if the proving key has been built and cached.
+#[cfg(feature = "prover")]
 #[wasm_bindgen]
 pub fn is_proving_key_ready() -> bool {
     t2z_core::is_proving_key_loaded()
 }
 
+/// Pre-build the Orchard verifying key.
+///
+/// Much cheaper than the proving key, and available in builds without it
+/// (feature `verifier`). Cached globally like the proving key.
+#[cfg(feature = "verifier")]
+#[wasm_bindgen]
+pub fn prebuild_verifying_key() {
+    t2z_core::load_orchard_verifying_key();
+}
+
+/// Verify the Orchard proof in a PCZT.
+///
+/// Throws if the PCZT has no proof or the proof doesn't match its actions.
+#[cfg(feature = "verifier")]
+#[wasm_bindgen]
+pub fn verify_orchard_proof(pczt: &WasmPczt) -> Result<(), JsError> {
+    t2z_core::verify_orchard_proof(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to verify Orchard proof: {}", e)))
+}
+
 // ============================================================================
 // WASM-friendly Input Types
 // ============================================================================
@@ -202,6 +236,17 @@ impl WasmPayment {
     }
 }
 
+impl From<t2z_core::Payment> for WasmPayment {
+    fn from(payment: t2z_core::Payment) -> Self {
+        Self {
+            address: payment.address,
+            amount: payment.amount,
+            memo: payment.memo.map(hex::encode),
+            label: payment.label,
+        }
+    }
+}
+
 impl WasmPayment {
     fn to_core(&self) -> Result<t2z_core::Payment, JsError> {
         let memo = if let Some(memo_hex) = &self.memo {
@@ -288,11 +333,14 @@ impl WasmPczt {
     }
 
     /// Parse a PCZT from a hex string
+    ///
+    /// The hex is decoded as the parser consumes it, so no intermediate
+    /// byte buffer is allocated.
     #[wasm_bindgen]
     pub fn from_hex(hex_string: &str) -> Result<WasmPczt, JsError> {
-        let bytes = hex::decode(hex_string)
-            .map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?;
-        Self::from_bytes(&bytes)
+        let pczt = t2z_core::pczt_from_hex(hex_string)
+            .map_err(|e| JsError::new(&format!("Failed to parse PCZT: {}", e)))?;
+        Ok(WasmPczt { inner: pczt })
     }
 
     /// Serialize the PCZT to bytes
@@ -302,9 +350,11 @@ impl WasmPczt {
     }
 
     /// Serialize the PCZT to a hex string
+    ///
+    /// Streams directly into the output string without an intermediate byte buffer.
     #[wasm_bindgen]
     pub fn to_hex(&self) -> String {
-        hex::encode(self.to_bytes())
+        t2z_core::pczt_to_hex(&self.inner)
     }
 
     /// Clone this PCZT
@@ -372,372 +422,3198 @@ pub fn propose_transaction(
     Ok(WasmPczt { inner: pczt })
 }
 
-/// Prove the transaction (adds Orchard proofs).
-///
-/// This builds the Halo 2 circuit proving key on first call (~10 seconds),
-/// then caches it for subsequent calls. No downloads required!
+/// A saved transaction proposal (inputs, payments, change, network, expiry, fee).
 ///
-/// # Arguments
-/// * `pczt` - The PCZT to prove
-///
-/// # Returns
-/// The PCZT with proofs added
+/// Serialize it for review/approval, then call `to_pczt` (possibly on a
+/// different machine) to materialize the PCZT.
 #[wasm_bindgen]
-pub fn prove_transaction(pczt: &WasmPczt) -> Result<WasmPczt, JsError> {
-    let proved = t2z_core::prove_transaction(pczt.inner.clone())
-        .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
-    Ok(WasmPczt { inner: proved })
+pub struct WasmProposal {
+    inner: t2z_core::Proposal,
 }
 
-/// Sign a transparent input with the provided private key.
-///
-/// This is a convenience function that combines `get_sighash` and signing internally.
-/// For external signing (HSM/hardware wallets), use `get_sighash` and `append_signature`.
-///
-/// # Arguments
-/// * `pczt` - The PCZT to sign
-/// * `input_index` - Index of the transparent input to sign
-/// * `secret_key_hex` - 32-byte private key as hex string
-///
-/// # Returns
-/// The PCZT with the signature added
 #[wasm_bindgen]
-pub fn sign_transparent_input(
-    pczt: &WasmPczt,
-    input_index: u32,
-    secret_key_hex: &str,
-) -> Result<WasmPczt, JsError> {
-    let secret_key_bytes = hex::decode(secret_key_hex)
-        .map_err(|e| JsError::new(&format!("Invalid secret key hex: {}", e)))?;
+impl WasmProposal {
+    /// Parse a proposal from bytes
+    #[wasm_bindgen(constructor)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmProposal, JsError> {
+        let proposal = t2z_core::Proposal::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse proposal: {}", e)))?;
+        Ok(WasmProposal { inner: proposal })
+    }
 
-    if secret_key_bytes.len() != 32 {
-        return Err(JsError::new("Secret key must be 32 bytes"));
+    /// Parse a proposal from a hex string
+    #[wasm_bindgen]
+    pub fn from_hex(hex_string: &str) -> Result<WasmProposal, JsError> {
+        let bytes =
+            hex::decode(hex_string).map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?;
+        Self::from_bytes(&bytes)
     }
 
-    let mut secret_key = [0u8; 32];
-    secret_key.copy_from_slice(&secret_key_bytes);
+    /// Serialize the proposal to bytes
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
 
-    let signed =
-        t2z_core::sign_transparent_input(pczt.inner.clone(), input_index as usize, &secret_key)
-            .map_err(|e| JsError::new(&format!("Failed to sign input: {}", e)))?;
+    /// Serialize the proposal to a hex string
+    #[wasm_bindgen]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.inner.to_bytes())
+    }
 
-    Ok(WasmPczt { inner: signed })
-}
+    /// ZIP 317 fee the transaction pays, in zatoshis
+    #[wasm_bindgen(getter)]
+    pub fn fee(&self) -> u64 {
+        self.inner.fee
+    }
 
-/// Get the sighash for a transparent input (ZIP 244).
-///
-/// Use this for external signing (HSM/hardware wallets):
-/// 1. Call `get_sighash` to get the 32-byte hash
-/// 2. Sign the hash externally with ECDSA secp256k1
-/// 3. Call `append_signature` with the result
-///
-/// # Arguments
-/// * `pczt` - The PCZT
-/// * `input_index` - Index of the transparent input
-///
-/// # Returns
-/// 32-byte sighash as hex string
-#[wasm_bindgen]
-pub fn get_sighash(pczt: &WasmPczt, input_index: u32) -> Result<String, JsError> {
-    let sighash = t2z_core::get_sighash(&pczt.inner, input_index as usize)
-        .map_err(|e| JsError::new(&format!("Failed to get sighash: {}", e)))?;
-    Ok(hex::encode(sighash))
+    /// Build the PCZT for this proposal (fails if the fee would differ)
+    #[wasm_bindgen]
+    pub fn to_pczt(&self) -> Result<WasmPczt, JsError> {
+        let pczt = self
+            .inner
+            .to_pczt()
+            .map_err(|e| JsError::new(&format!("Failed to materialize proposal: {}", e)))?;
+        Ok(WasmPczt { inner: pczt })
+    }
 }
 
-/// Append a pre-computed signature to a transparent input.
-///
-/// The signature should be created by signing the output of `get_sighash`
-/// with ECDSA secp256k1, then appending the sighash type byte (0x01 for SIGHASH_ALL).
-///
-/// # Arguments
-/// * `pczt` - The PCZT to update
-/// * `input_index` - Index of the transparent input
-/// * `pubkey_hex` - 33-byte compressed public key as hex
-/// * `signature_hex` - DER-encoded signature + sighash type byte as hex
+/// Create a serializable proposal without building the PCZT for signing yet.
 ///
-/// # Returns
-/// Updated PCZT with the signature added
+/// Takes the same arguments as `propose_transaction`, plus the optional
+/// current chain height used for expiry validation.
 #[wasm_bindgen]
-pub fn append_signature(
-    pczt: &WasmPczt,
-    input_index: u32,
-    pubkey_hex: &str,
-    signature_hex: &str,
-) -> Result<WasmPczt, JsError> {
-    let pubkey_bytes = hex::decode(pubkey_hex)
-        .map_err(|e| JsError::new(&format!("Invalid pubkey hex: {}", e)))?;
-
-    if pubkey_bytes.len() != 33 {
-        return Err(JsError::new("Public key must be 33 bytes (compressed)"));
-    }
+pub fn create_proposal(
+    inputs: Vec<WasmTransparentInput>,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+    expiry_height: u32,
+    current_height: Option<u32>,
+) -> Result<WasmProposal, JsError> {
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        inputs.iter().map(|i| i.to_core()).collect();
 
-    let mut pubkey = [0u8; 33];
-    pubkey.copy_from_slice(&pubkey_bytes);
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
 
-    let signature = hex::decode(signature_hex)
-        .map_err(|e| JsError::new(&format!("Invalid signature hex: {}", e)))?;
+    let network = match network {
+        "mainnet" => t2z_core::Network::Mainnet,
+        "testnet" => t2z_core::Network::Testnet,
+        _ => return Err(JsError::new("Network must be 'mainnet' or 'testnet'")),
+    };
 
-    let updated = t2z_core::append_signature(
-        pczt.inner.clone(),
-        input_index as usize,
-        &pubkey,
-        &signature,
+    let proposal = t2z_core::Proposal::new(
+        core_inputs?,
+        t2z_core::TransactionRequest {
+            payments: core_payments?,
+        },
+        change_address,
+        network,
+        expiry_height,
+        current_height,
     )
-    .map_err(|e| JsError::new(&format!("Failed to append signature: {}", e)))?;
+    .map_err(|e| JsError::new(&format!("Failed to create proposal: {}", e)))?;
 
-    Ok(WasmPczt { inner: updated })
+    Ok(WasmProposal { inner: proposal })
 }
 
-/// Verify the PCZT matches the original transaction request before signing.
-///
-/// This is an important security check for multi-party transaction construction.
-/// It verifies:
-/// - All requested payments are present
-/// - No unexpected outputs were added
-/// - Change output matches expectations (if any)
+/// Fluent proposal builder.
 ///
-/// # Arguments
-/// * `pczt` - The PCZT to verify
-/// * `payments` - The original payments array used to create the PCZT
-/// * `change_address` - Expected change address (optional)
-/// * `change_amount` - Expected change amount in zatoshis (optional)
+/// ```js
+/// const pczt = new WasmProposalBuilder("testnet")
+///   .add_input(input)
+///   .pay("utest1...", 100000n)
+///   .change_to("tm...")
+///   .expiry(3000100)
+///   .propose();
+/// ```
 ///
-/// # Returns
-/// Ok if verification passes, error with details otherwise
+/// Each chaining method consumes the builder and returns a new one.
 #[wasm_bindgen]
-pub fn verify_before_signing(
-    pczt: &WasmPczt,
-    payments: Vec<WasmPayment>,
-    expected_change: Vec<WasmExpectedTxOut>,
-) -> Result<(), JsError> {
-    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
-        payments.iter().map(|p| p.to_core()).collect();
-    let core_payments = core_payments?;
+pub struct WasmProposalBuilder {
+    inner: t2z_core::ProposalBuilder,
+}
 
-    let core_expected_change: Vec<t2z_core::ExpectedTxOut> =
-        expected_change.iter().map(|c| c.to_core()).collect();
+#[wasm_bindgen]
+impl WasmProposalBuilder {
+    /// Start an empty proposal for "mainnet" or "testnet"
+    #[wasm_bindgen(constructor)]
+    pub fn new(network: &str) -> Result<WasmProposalBuilder, JsError> {
+        Ok(WasmProposalBuilder {
+            inner: t2z_core::ProposalBuilder::new(parse_network(network)?),
+        })
+    }
 
-    let request = t2z_core::TransactionRequest {
-        payments: core_payments,
-    };
+    /// Add a transparent input to spend
+    #[wasm_bindgen]
+    pub fn add_input(self, input: &WasmTransparentInput) -> Result<WasmProposalBuilder, JsError> {
+        Ok(WasmProposalBuilder {
+            inner: self.inner.add_input(input.to_core()?),
+        })
+    }
 
-    t2z_core::verify_before_signing(&pczt.inner, &request, &core_expected_change)
-        .map_err(|e| JsError::new(&format!("Verification failed: {}", e)))
-}
+    /// Pay `amount` zatoshis to `address`
+    #[wasm_bindgen]
+    pub fn pay(self, address: String, amount: u64) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.pay(address, amount),
+        }
+    }
 
-/// Combine multiple PCZTs into one.
-///
-/// Useful for multi-party transaction construction.
-///
-/// # Arguments
-/// * `pczts` - Array of PCZTs to combine
-///
-/// # Returns
-/// Combined PCZT
-#[wasm_bindgen]
-pub fn combine(pczts: Vec<WasmPczt>) -> Result<WasmPczt, JsError> {
-    let core_pczts: Vec<t2z_core::Pczt> = pczts.into_iter().map(|p| p.inner).collect();
+    /// Add a fully specified payment (memo, label)
+    #[wasm_bindgen]
+    pub fn add_payment(self, payment: &WasmPayment) -> Result<WasmProposalBuilder, JsError> {
+        Ok(WasmProposalBuilder {
+            inner: self.inner.add_payment(payment.to_core()?),
+        })
+    }
 
-    let combined = t2z_core::combine(core_pczts)
-        .map_err(|e| JsError::new(&format!("Failed to combine PCZTs: {}", e)))?;
+    /// Send change to `address`
+    #[wasm_bindgen]
+    pub fn change_to(self, address: String) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.change_to(address),
+        }
+    }
 
-    Ok(WasmPczt { inner: combined })
+    /// Set the expiry height (0 for no expiry). Required.
+    #[wasm_bindgen]
+    pub fn expiry(self, expiry_height: u32) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.expiry(expiry_height),
+        }
+    }
+
+    /// Set the chain tip height used to validate the expiry
+    #[wasm_bindgen]
+    pub fn current_height(self, current_height: u32) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.current_height(current_height),
+        }
+    }
+
+    /// Send memos on transparent payments to the Orchard change address
+    /// instead of failing
+    #[wasm_bindgen]
+    pub fn redirect_transparent_memos(self) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.redirect_transparent_memos(),
+        }
+    }
+
+    /// Pay exactly `fee` zatoshis instead of the ZIP 317 fee
+    #[wasm_bindgen]
+    pub fn fixed_fee(self, fee: u64) -> WasmProposalBuilder {
+        WasmProposalBuilder {
+            inner: self.inner.fee(t2z_core::FeeStrategy::fixed(fee)),
+        }
+    }
+
+    /// Validate the proposal and build the PCZT
+    #[wasm_bindgen]
+    pub fn propose(&self) -> Result<WasmPczt, JsError> {
+        let pczt = self
+            .inner
+            .propose()
+            .map_err(|e| JsError::new(&format!("Failed to propose transaction: {}", e)))?;
+        Ok(WasmPczt { inner: pczt })
+    }
 }
 
-/// Finalize the PCZT and extract the raw transaction bytes.
-///
-/// This implements the Spend Finalizer and Transaction Extractor roles.
-/// The returned bytes are ready to be broadcast to the Zcash network.
+/// Options for `propose_transaction_with_options`.
 ///
-/// # Arguments
-/// * `pczt` - The fully signed and proved PCZT
+/// ```js
+/// const options = new WasmProposalOptions(3000000);
+/// options.set_expiry_delta(40);
+/// options.set_min_orchard_actions(4);
+/// const pczt = propose_transaction_with_options(inputs, payments, change, "testnet", options);
+/// ```
 ///
-/// # Returns
-/// Raw transaction bytes ready for broadcast
+/// Defaults: ZIP 317 fee, expiry 40 blocks after the current height, no
+/// branch pinning or padding, and all leftover value sent to change.
 #[wasm_bindgen]
-pub fn finalize_and_extract(pczt: &WasmPczt) -> Result<Vec<u8>, JsError> {
-    t2z_core::finalize_and_extract(pczt.inner.clone())
-        .map_err(|e| JsError::new(&format!("Failed to finalize transaction: {}", e)))
+pub struct WasmProposalOptions {
+    inner: t2z_core::ProposalOptions<'static>,
 }
 
-/// Finalize and extract as hex string (convenience method)
 #[wasm_bindgen]
-pub fn finalize_and_extract_hex(pczt: &WasmPczt) -> Result<String, JsError> {
-    let bytes = finalize_and_extract(pczt)?;
-    Ok(hex::encode(bytes))
-}
+impl WasmProposalOptions {
+    /// Default options at chain tip `current_height`
+    #[wasm_bindgen(constructor)]
+    pub fn new(current_height: u32) -> WasmProposalOptions {
+        WasmProposalOptions {
+            inner: t2z_core::ProposalOptions::new(current_height),
+        }
+    }
 
-/// Get the library version
-#[wasm_bindgen]
-pub fn version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
-}
+    /// Pay exactly `fee` zatoshis instead of the ZIP 317 fee
+    #[wasm_bindgen]
+    pub fn set_fixed_fee(&mut self, fee: u64) {
+        self.inner.fee_strategy = t2z_core::FeeStrategy::fixed(fee);
+    }
 
-// ============================================================================
-// Test Address Generation
-// ============================================================================
+    /// Expire at exactly `expiry_height`
+    #[wasm_bindgen]
+    pub fn set_expiry_height(&mut self, expiry_height: u32) {
+        self.inner.expiry = t2z_core::ExpiryPolicy::AtHeight(expiry_height);
+    }
 
-/// Generate a random test Orchard address.
-///
-/// This generates a new random spending key and derives a unified address
-/// containing an Orchard receiver. Useful for testing.
-///
-/// # Arguments
-/// * `network` - "mainnet" or "testnet"
-///
-/// # Returns
-/// A valid unified address string with an Orchard receiver
+    /// Expire `blocks` blocks after the current height
+    #[wasm_bindgen]
+    pub fn set_expiry_delta(&mut self, blocks: u32) {
+        self.inner.expiry = t2z_core::ExpiryPolicy::AfterBlocks(blocks);
+    }
+
+    /// Never expire
+    #[wasm_bindgen]
+    pub fn set_no_expiry(&mut self) {
+        self.inner.expiry = t2z_core::ExpiryPolicy::Never;
+    }
+
+    /// Current chain tip height
+    #[wasm_bindgen]
+    pub fn set_current_height(&mut self, current_height: u32) {
+        self.inner.current_height = Some(current_height);
+    }
+
+    /// Fail unless the transaction is built for this consensus branch ID
+    #[wasm_bindgen]
+    pub fn set_consensus_branch_id(&mut self, branch_id: u32) {
+        self.inner.consensus_branch_id = Some(branch_id);
+    }
+
+    /// Pad the Orchard bundle to at least this many actions
+    #[wasm_bindgen]
+    pub fn set_min_orchard_actions(&mut self, min_actions: u32) {
+        self.inner.min_orchard_actions = min_actions as usize;
+    }
+
+    /// Add leftovers below `threshold` zatoshis to the fee instead of
+    /// creating a change output
+    #[wasm_bindgen]
+    pub fn set_dust_threshold(&mut self, threshold: u64) {
+        self.inner.change = t2z_core::ChangePolicy::DustToFee { threshold };
+    }
+
+    /// Send exactly `transparent_amount` zatoshis of the change to
+    /// `transparent_address` and the rest to the change address
+    #[wasm_bindgen]
+    pub fn set_split_change(&mut self, transparent_address: String, transparent_amount: u64) {
+        self.inner.change = t2z_core::ChangePolicy::SplitTransparent {
+            transparent_address,
+            transparent_amount,
+        };
+    }
+
+    /// Send all change to a fresh internal Orchard address of `ufvk` (the
+    /// change address argument must then be omitted)
+    #[wasm_bindgen]
+    pub fn set_auto_shield(&mut self, ufvk: String) {
+        self.inner.change = t2z_core::ChangePolicy::AutoShield { ufvk };
+    }
+
+    /// Split the change into two non-round Orchard notes to the (Orchard)
+    /// change address; see `non_round_split_fee` for what it costs
+    #[wasm_bindgen]
+    pub fn set_split_non_round_change(&mut self) {
+        self.inner.change = t2z_core::ChangePolicy::SplitNonRound;
+    }
+
+    /// Most Orchard and transparent outputs (counting change) in one
+    /// transaction; defaults to 32 and 1000
+    #[wasm_bindgen]
+    pub fn set_output_limits(&mut self, max_orchard_outputs: u32, max_transparent_outputs: u32) {
+        self.inner.output_limits = t2z_core::OutputLimits {
+            max_orchard_outputs: max_orchard_outputs as usize,
+            max_transparent_outputs: max_transparent_outputs as usize,
+        };
+    }
+
+    /// nSequence of inputs that don't set their own (default 0xFFFFFFFF)
+    #[wasm_bindgen]
+    pub fn set_default_sequence(&mut self, sequence: u32) {
+        self.inner.tx_defaults.sequence = Some(sequence);
+    }
+
+    /// Transaction version to build; only 5 is supported so far
+    #[wasm_bindgen]
+    pub fn set_tx_version(&mut self, version: u32) -> Result<(), JsError> {
+        self.inner.tx_defaults.version = match version {
+            5 => t2z_core::TxVersion::V5,
+            _ => {
+                return Err(JsError::new(&format!(
+                    "Unsupported transaction version {}",
+                    version
+                )));
+            }
+        };
+        Ok(())
+    }
+
+    /// Lock time used when no input requires one (default 0)
+    #[wasm_bindgen]
+    pub fn set_fallback_lock_time(&mut self, lock_time: u32) {
+        self.inner.tx_defaults.fallback_lock_time = Some(lock_time);
+    }
+
+    /// What happens to several payments to the same Orchard receiver:
+    /// "keep" (default), "reject", "merge", or "merge_concat_memos"
+    #[wasm_bindgen]
+    pub fn set_duplicate_recipients(&mut self, mode: &str) -> Result<(), JsError> {
+        self.inner.duplicate_recipients = match mode {
+            "keep" => t2z_core::DuplicateRecipients::Keep,
+            "reject" => t2z_core::DuplicateRecipients::Reject,
+            "merge" => t2z_core::DuplicateRecipients::Merge,
+            "merge_concat_memos" => t2z_core::DuplicateRecipients::MergeConcatMemos,
+            _ => {
+                return Err(JsError::new(
+                    "Mode must be 'keep', 'reject', 'merge', or 'merge_concat_memos'",
+                ));
+            }
+        };
+        Ok(())
+    }
+
+    /// Refuse inputs with fewer than `min_confirmations` confirmations
+    /// (coinbase inputs: `min_coinbase_confirmations`, default and at least
+    /// 100). Needs `set_input_confirmations`.
+    #[wasm_bindgen]
+    pub fn set_min_confirmations(
+        &mut self,
+        min_confirmations: u32,
+        min_coinbase_confirmations: Option<u32>,
+    ) {
+        self.inner.confirmations = Some(t2z_core::ConfirmationPolicy {
+            min_confirmations,
+            min_coinbase_confirmations: min_coinbase_confirmations
+                .unwrap_or(t2z_core::COINBASE_MATURITY),
+        });
+    }
+
+    /// Confirmations of each input, in order: an array of `{ confirmations,
+    /// coinbase }`. Spending a coinbase input makes the transaction fully
+    /// shielded: transparent payments and change are refused.
+    #[wasm_bindgen]
+    pub fn set_input_confirmations(&mut self, inputs: JsValue) -> Result<(), JsError> {
+        self.inner.input_confirmations = serde_wasm_bindgen::from_value(inputs)
+            .map_err(|e| JsError::new(&format!("Failed to parse input confirmations: {}", e)))?;
+        Ok(())
+    }
+
+    /// Build the Orchard bundle against this anchor (32 bytes, the note
+    /// commitment tree root) instead of the empty tree's
+    #[wasm_bindgen]
+    pub fn set_orchard_anchor(&mut self, anchor: &[u8]) -> Result<(), JsError> {
+        let anchor: [u8; 32] = anchor
+            .try_into()
+            .map_err(|_| JsError::new("Orchard anchor must be 32 bytes"))?;
+        self.inner.orchard_anchor = Some(anchor);
+        Ok(())
+    }
+
+    /// Seed all randomness (32 bytes) so the same inputs give a byte-identical
+    /// PCZT. For golden-file tests only: the seed reveals the note secrets.
+    #[wasm_bindgen]
+    pub fn set_rng_seed(&mut self, seed: &[u8]) -> Result<(), JsError> {
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| JsError::new("RNG seed must be 32 bytes"))?;
+        self.inner.rng_seed = Some(seed);
+        Ok(())
+    }
+}
+
+/// Propose a transaction with fee, expiry, padding, and change options.
 ///
-/// # Warning
-/// The spending key is discarded - you cannot spend funds sent to this address!
-/// Only use for testing receive functionality.
+/// Takes the same arguments as `propose_transaction`, with the expiry
+/// coming from `options`.
 #[wasm_bindgen]
-pub fn generate_test_address(network: &str) -> Result<String, JsError> {
-    use orchard::keys::{FullViewingKey, Scope, SpendingKey};
-    use rand_core::RngCore;
-    use zcash_address::unified::{self, Encoding};
-    use zcash_protocol::consensus::NetworkType;
-
-    let network_type = match network {
-        "mainnet" => NetworkType::Main,
-        "testnet" => NetworkType::Test,
-        _ => return Err(JsError::new("Network must be 'mainnet' or 'testnet'")),
+pub fn propose_transaction_with_options(
+    inputs: Vec<WasmTransparentInput>,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+    options: &WasmProposalOptions,
+) -> Result<WasmPczt, JsError> {
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        inputs.iter().map(|i| i.to_core()).collect();
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
     };
 
-    // Generate random bytes for spending key
-    let mut rng = rand_core::OsRng;
+    let pczt = t2z_core::propose_transaction_with_options(
+        &core_inputs?,
+        request,
+        change_address.as_deref(),
+        parse_network(network)?,
+        options.inner.clone(),
+    )
+    .map_err(|e| JsError::new(&format!("Failed to propose transaction: {}", e)))?;
+
+    Ok(WasmPczt { inner: pczt })
+}
+
+/// A recipient of a payment template (a payment without an amount)
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct WasmTemplateRecipient {
+    address: String,
+    /// Optional memo (hex encoded, max 512 bytes)
+    memo: Option<String>,
+    label: Option<String>,
+}
 
-    // Create spending key from random bytes (loop until valid)
-    let sk: SpendingKey = loop {
-        let mut attempt = [0u8; 32];
-        rng.fill_bytes(&mut attempt);
-        let ct_option = SpendingKey::from_bytes(attempt);
-        if ct_option.is_some().into() {
-            break ct_option.unwrap();
+#[wasm_bindgen]
+impl WasmTemplateRecipient {
+    #[wasm_bindgen(constructor)]
+    pub fn new(address: String, memo: Option<String>, label: Option<String>) -> Self {
+        Self {
+            address,
+            memo,
+            label,
         }
-    };
+    }
+}
+
+impl WasmTemplateRecipient {
+    fn to_core(&self) -> Result<t2z_core::TemplateRecipient, JsError> {
+        let memo = match &self.memo {
+            Some(memo_hex) => Some(
+                hex::decode(memo_hex)
+                    .map_err(|e| JsError::new(&format!("Invalid memo hex: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        Ok(t2z_core::TemplateRecipient {
+            address: self.address.clone(),
+            memo,
+            label: self.label.clone(),
+        })
+    }
+}
 
-    // Derive full viewing key and address
-    let fvk = FullViewingKey::from(&sk as &SpendingKey);
-    let address = fvk.address_at(0u32, Scope::External);
+/// Recipients of a recurring payment, defined once and proposed each cycle.
+///
+/// Recipients are validated when the template is created; each call to
+/// `instantiate` supplies that cycle's amounts and UTXOs.
+#[wasm_bindgen]
+pub struct WasmPaymentTemplate {
+    inner: t2z_core::PaymentTemplate,
+}
 
-    // Get the raw address bytes
-    let orchard_bytes = address.to_raw_address_bytes();
+#[wasm_bindgen]
+impl WasmPaymentTemplate {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        network: &str,
+        recipients: Vec<WasmTemplateRecipient>,
+        change_address: Option<String>,
+    ) -> Result<WasmPaymentTemplate, JsError> {
+        let recipients: Result<Vec<t2z_core::TemplateRecipient>, JsError> =
+            recipients.iter().map(|r| r.to_core()).collect();
+
+        let inner =
+            t2z_core::PaymentTemplate::new(parse_network(network)?, recipients?, change_address)
+                .map_err(|e| JsError::new(&format!("Failed to create template: {}", e)))?;
+
+        Ok(WasmPaymentTemplate { inner })
+    }
 
-    // Create unified address with just the Orchard receiver
-    let ua = unified::Address::try_from_items(vec![unified::Receiver::Orchard(orchard_bytes)])
-        .map_err(|e| JsError::new(&format!("Failed to create unified address: {:?}", e)))?;
+    /// Propose one cycle, paying `amounts[i]` zatoshis to recipient `i`
+    pub fn instantiate(
+        &self,
+        amounts: Vec<u64>,
+        inputs: Vec<WasmTransparentInput>,
+        options: &WasmProposalOptions,
+    ) -> Result<WasmPczt, JsError> {
+        let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+            inputs.iter().map(|i| i.to_core()).collect();
 
-    // Encode for the network
-    let encoded = ua.encode(&network_type);
+        let pczt =
+            t2z_core::instantiate(&self.inner, &amounts, &core_inputs?, options.inner.clone())
+                .map_err(|e| JsError::new(&format!("Failed to propose transaction: {}", e)))?;
 
-    Ok(encoded)
+        Ok(WasmPczt { inner: pczt })
+    }
 }
 
-/// Generate a test keypair (address + spending key).
+/// Shield coinbase UTXOs to pool payout addresses in one transaction.
 ///
-/// Returns an object with:
-/// - `address`: Unified address with Orchard receiver
-/// - `spending_key`: Hex-encoded spending key (keep secret!)
+/// Everything the inputs hold, less the ZIP 317 fee, is split across the
+/// Orchard `payout_addresses` in proportion to `payout_weights`, with no
+/// change. `options` supplies expiry, padding, and confirmation settings.
+#[wasm_bindgen]
+pub fn propose_coinbase_shielding(
+    coinbase_utxos: Vec<WasmTransparentInput>,
+    payout_addresses: Vec<String>,
+    payout_weights: Vec<u64>,
+    network: &str,
+    options: &WasmProposalOptions,
+) -> Result<WasmPczt, JsError> {
+    if payout_addresses.len() != payout_weights.len() {
+        return Err(JsError::new("Give one weight per payout address"));
+    }
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        coinbase_utxos.iter().map(|i| i.to_core()).collect();
+    let splits: Vec<(String, u64)> = payout_addresses.into_iter().zip(payout_weights).collect();
+
+    let pczt = t2z_core::propose_coinbase_shielding(
+        &core_inputs?,
+        &splits,
+        parse_network(network)?,
+        options.inner.clone(),
+    )
+    .map_err(|e| JsError::new(&format!("Failed to propose transaction: {}", e)))?;
+
+    Ok(WasmPczt { inner: pczt })
+}
+
+/// Propose a child transaction spending the transparent change of `parent`
+/// before it confirms.
 ///
-/// # Warning
-/// This is for testing only. Store the spending key securely if you want
-/// to be able to spend funds sent to the address.
+/// `parent` may be signed or not (v5 txids do not cover signatures).
+/// `change_pubkey_hex` is the public key of the parent's change address.
+/// The child may not expire later than the parent.
 #[wasm_bindgen]
-pub fn generate_test_keypair(network: &str) -> Result<JsValue, JsError> {
-    use orchard::keys::{FullViewingKey, Scope, SpendingKey};
-    use rand_core::RngCore;
-    use zcash_address::unified::{self, Encoding};
-    use zcash_protocol::consensus::NetworkType;
-
-    let network_type = match network {
-        "mainnet" => NetworkType::Main,
-        "testnet" => NetworkType::Test,
-        _ => return Err(JsError::new("Network must be 'mainnet' or 'testnet'")),
-    };
+pub fn spend_unconfirmed_change(
+    parent: &WasmPczt,
+    change_pubkey_hex: &str,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+    options: &WasmProposalOptions,
+) -> Result<WasmPczt, JsError> {
+    spend_change_of(
+        t2z_core::ParentTransaction::Pczt(&parent.inner),
+        change_pubkey_hex,
+        payments,
+        change_address,
+        network,
+        options,
+    )
+}
 
-    // Generate random bytes and create spending key (loop until valid)
-    let mut rng = rand_core::OsRng;
-    let (sk, sk_bytes): (SpendingKey, [u8; 32]) = loop {
-        let mut attempt = [0u8; 32];
-        rng.fill_bytes(&mut attempt);
-        let ct_option = SpendingKey::from_bytes(attempt);
-        if ct_option.is_some().into() {
-            break (ct_option.unwrap(), attempt);
-        }
+/// Like `spend_unconfirmed_change`, for a parent given as the raw
+/// transaction (hex) that was broadcast.
+#[wasm_bindgen]
+pub fn spend_unconfirmed_change_from_tx(
+    parent_tx_hex: &str,
+    change_pubkey_hex: &str,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+    options: &WasmProposalOptions,
+) -> Result<WasmPczt, JsError> {
+    let tx_bytes = hex::decode(parent_tx_hex)
+        .map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    spend_change_of(
+        t2z_core::ParentTransaction::Transaction(&tx_bytes),
+        change_pubkey_hex,
+        payments,
+        change_address,
+        network,
+        options,
+    )
+}
+
+fn spend_change_of(
+    parent: t2z_core::ParentTransaction<'_>,
+    change_pubkey_hex: &str,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+    options: &WasmProposalOptions,
+) -> Result<WasmPczt, JsError> {
+    let change_pubkey = parse_pubkey(change_pubkey_hex)?;
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
     };
 
-    // Derive full viewing key and address
-    let fvk = FullViewingKey::from(&sk as &SpendingKey);
-    let address = fvk.address_at(0u32, Scope::External);
+    let pczt = t2z_core::spend_unconfirmed_change(
+        parent,
+        &change_pubkey,
+        request,
+        change_address.as_deref(),
+        parse_network(network)?,
+        options.inner.clone(),
+    )
+    .map_err(|e| JsError::new(&format!("Failed to propose transaction: {}", e)))?;
 
-    // Get the raw address bytes
-    let orchard_bytes = address.to_raw_address_bytes();
+    Ok(WasmPczt { inner: pczt })
+}
 
-    // Create unified address with just the Orchard receiver
-    let ua = unified::Address::try_from_items(vec![unified::Receiver::Orchard(orchard_bytes)])
-        .map_err(|e| JsError::new(&format!("Failed to create unified address: {:?}", e)))?;
+/// Prove the transaction (adds Orchard proofs).
+///
+/// This builds the Halo 2 circuit proving key on first call (~10 seconds),
+/// then caches it for subsequent calls. No downloads required!
+///
+/// # Arguments
+/// * `pczt` - The PCZT to prove
+///
+/// # Returns
+/// The PCZT with proofs added
+#[cfg(feature = "prover")]
+#[wasm_bindgen]
+pub fn prove_transaction(pczt: &WasmPczt) -> Result<WasmPczt, JsError> {
+    let proved = t2z_core::prove_transaction(pczt.inner.clone())
+        .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+    Ok(WasmPczt { inner: proved })
+}
 
-    // Encode for the network
-    let encoded = ua.encode(&network_type);
+/// Prove a PCZT like `prove_transaction`, and report what it cost.
+///
+/// # Returns
+/// An object with `pczt` (the proved `WasmPczt`) and `metrics`:
+/// `wall_time_ms`, `proving_key_ms`, `proving_key_built`, `orchard_actions`,
+/// `proof_bytes`, `estimated_memory_bytes`, and `peak_memory_bytes` (the
+/// size of the module's memory, which only grows)
+#[cfg(feature = "prover")]
+#[wasm_bindgen]
+pub fn prove_transaction_with_metrics(pczt: &WasmPczt) -> Result<JsValue, JsError> {
+    let estimated_memory_bytes = t2z_core::estimate_pczt_proving_memory(&pczt.inner);
+    let start = js_sys::Date::now();
+    let proving_key_built = !t2z_core::is_proving_key_loaded();
+    t2z_core::load_orchard_proving_key();
+    let proving_key_ms = js_sys::Date::now() - start;
 
-    // Serialize the full viewing key as 96 bytes (ak, nk, rivk)
-    let fvk_bytes = fvk.to_bytes();
-    
-    // Create a unified full viewing key (UFVK) with just the Orchard component
-    // This encodes to uview1... (mainnet) or uviewtest1... (testnet)
-    let ufvk = unified::Ufvk::try_from_items(vec![unified::Fvk::Orchard(fvk_bytes)])
-        .map_err(|e| JsError::new(&format!("Failed to create UFVK: {:?}", e)))?;
-    let ufvk_encoded = ufvk.encode(&network_type);
-    
-    // Return as JS object with address, spending key, and viewing key
-    let obj = js_sys::Object::new();
-    js_sys::Reflect::set(&obj, &"address".into(), &encoded.into())
-        .map_err(|_| JsError::new("Failed to set address"))?;
-    js_sys::Reflect::set(&obj, &"spending_key".into(), &hex::encode(sk_bytes).into())
-        .map_err(|_| JsError::new("Failed to set spending_key"))?;
-    js_sys::Reflect::set(&obj, &"full_viewing_key".into(), &ufvk_encoded.into())
-        .map_err(|_| JsError::new("Failed to set full_viewing_key"))?;
-    // Also include raw hex for debugging
-    js_sys::Reflect::set(&obj, &"full_viewing_key_hex".into(), &hex::encode(fvk_bytes).into())
-        .map_err(|_| JsError::new("Failed to set full_viewing_key_hex"))?;
+    let (proved, mut metrics) = t2z_core::prove_transaction_with_metrics(pczt.inner.clone())
+        .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+    // Instant is unavailable here, so the core leaves the times at 0, and
+    // it only saw the proving key after it was built above
+    metrics.wall_time_ms = js_sys::Date::now() - start;
+    metrics.proving_key_ms = proving_key_ms;
+    metrics.proving_key_built = proving_key_built;
+    metrics.estimated_memory_bytes = estimated_memory_bytes;
+
+    with_metrics(
+        WasmPczt { inner: proved },
+        serde_wasm_bindgen::to_value(&metrics),
+    )
+}
 
-    Ok(obj.into())
+/// Prove a PCZT only if the estimated peak memory fits within
+/// `max_memory_bytes`.
+///
+/// Fails without doing any work otherwise (the error names both figures),
+/// so the caller can hand the PCZT to a remote prover instead of running
+/// the tab out of memory. The budget covers building the proving key if it
+/// isn't cached yet.
+#[cfg(feature = "prover")]
+#[wasm_bindgen]
+pub fn prove_transaction_with_budget(
+    pczt: &WasmPczt,
+    max_memory_bytes: u64,
+) -> Result<WasmPczt, JsError> {
+    let proved = t2z_core::prove_transaction_with_budget(
+        pczt.inner.clone(),
+        t2z_core::ProvingBudget { max_memory_bytes },
+    )
+    .map_err(|e| JsError::new(&format!("Failed to prove transaction: {}", e)))?;
+    Ok(WasmPczt { inner: proved })
 }
 
-// ============================================================================
-// PCZT Inspection
-// ============================================================================
+/// Estimate the peak memory (in bytes) proving this PCZT would need.
+///
+/// Includes building the proving key if it isn't cached yet. Browsers cap
+/// WASM memory (typically 2-4 GB), so use this to decide whether to prove
+/// locally or hand the PCZT to a remote prover. Returns 0 if no proof is needed.
+#[wasm_bindgen]
+pub fn estimate_proving_memory(pczt: &WasmPczt) -> u64 {
+    t2z_core::estimate_pczt_proving_memory(&pczt.inner)
+}
 
-/// Inspect a PCZT and return detailed information about its contents.
+/// Sign a transparent input with the provided private key.
 ///
-/// Returns a JSON object with:
-/// - `expiry_height`: Transaction expiry height
-/// - `transparent_inputs`: Array of transparent inputs (txid, value, script, signed status)
-/// - `transparent_outputs`: Array of transparent outputs (value, script, address)
-/// - `orchard_outputs`: Array of Orchard outputs (value, recipient, address)
-/// - `total_input`: Total input value in zatoshis
-/// - `total_transparent_output`: Total transparent output value
-/// - `total_orchard_output`: Total Orchard output value
-/// - `implied_fee`: Calculated fee (inputs - outputs)
-/// - `num_orchard_actions`: Number of Orchard actions
-/// - `all_inputs_signed`: Whether all transparent inputs have signatures
-/// - `has_orchard_proofs`: Whether Orchard bundle has proofs
+/// This is a convenience function that combines `get_sighash` and signing internally.
+/// For external signing (HSM/hardware wallets), use `get_sighash` and `append_signature`.
 ///
-/// This is useful for:
-/// - Displaying transaction details before signing
-/// - Getting the actual fee and change amounts after propose_transaction
-/// - Verifying the transaction matches expectations
-/// - Checking signing/proving progress
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `input_index` - Index of the transparent input to sign
+/// * `secret_key_hex` - 32-byte private key as hex string
+///
+/// # Returns
+/// The PCZT with the signature added
 #[wasm_bindgen]
-pub fn inspect_pczt(pczt_hex: &str) -> Result<JsValue, JsError> {
-    let pczt_bytes = hex::decode(pczt_hex)
-        .map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?;
-    
-    let info = t2z_core::inspect_pczt_bytes(&pczt_bytes)
-        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))?;
-    
-    // Convert to JS value using serde
-    serde_wasm_bindgen::to_value(&info)
-        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+pub fn sign_transparent_input(
+    pczt: &WasmPczt,
+    input_index: u32,
+    secret_key_hex: &str,
+) -> Result<WasmPczt, JsError> {
+    let secret_key_bytes = hex::decode(secret_key_hex)
+        .map_err(|e| JsError::new(&format!("Invalid secret key hex: {}", e)))?;
+
+    if secret_key_bytes.len() != 32 {
+        return Err(JsError::new("Secret key must be 32 bytes"));
+    }
+
+    let mut secret_key = [0u8; 32];
+    secret_key.copy_from_slice(&secret_key_bytes);
+
+    let signed =
+        t2z_core::sign_transparent_input(pczt.inner.clone(), input_index as usize, &secret_key)
+            .map_err(|e| JsError::new(&format!("Failed to sign input: {}", e)))?;
+
+    Ok(WasmPczt { inner: signed })
+}
+
+/// Sign a transparent input like `sign_transparent_input`, and report what
+/// it cost.
+///
+/// # Returns
+/// An object with `pczt` (the signed `WasmPczt`) and `metrics`:
+/// `wall_time_ms`, `inputs_signed`, and `transparent_inputs`
+#[wasm_bindgen]
+pub fn sign_transparent_input_with_metrics(
+    pczt: &WasmPczt,
+    input_index: u32,
+    secret_key_hex: &str,
+) -> Result<JsValue, JsError> {
+    let start = js_sys::Date::now();
+    let signed = sign_transparent_input(pczt, input_index, secret_key_hex)?;
+
+    let metrics = t2z_core::SignMetrics {
+        wall_time_ms: js_sys::Date::now() - start,
+        inputs_signed: 1,
+        transparent_inputs: signed.inner.transparent().inputs().len(),
+    };
+    with_metrics(signed, serde_wasm_bindgen::to_value(&metrics))
+}
+
+/// `{ pczt, metrics }` for the `*_with_metrics` functions
+fn with_metrics(
+    pczt: WasmPczt,
+    metrics: Result<JsValue, serde_wasm_bindgen::Error>,
+) -> Result<JsValue, JsError> {
+    let metrics =
+        metrics.map_err(|e| JsError::new(&format!("Failed to serialize metrics: {}", e)))?;
+
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"pczt".into(), &pczt.into())
+        .map_err(|_| JsError::new("Failed to build result"))?;
+    js_sys::Reflect::set(&result, &"metrics".into(), &metrics)
+        .map_err(|_| JsError::new("Failed to build result"))?;
+    Ok(result.into())
+}
+
+/// Get the sighash for a transparent input (ZIP 244).
+///
+/// Use this for external signing (HSM/hardware wallets):
+/// 1. Call `get_sighash` to get the 32-byte hash
+/// 2. Sign the hash externally with ECDSA secp256k1
+/// 3. Call `append_signature` with the result
+///
+/// # Arguments
+/// * `pczt` - The PCZT
+/// * `input_index` - Index of the transparent input
+///
+/// # Returns
+/// 32-byte sighash as hex string
+#[wasm_bindgen]
+pub fn get_sighash(pczt: &WasmPczt, input_index: u32) -> Result<String, JsError> {
+    let sighash = t2z_core::get_sighash(&pczt.inner, input_index as usize)
+        .map_err(|e| JsError::new(&format!("Failed to get sighash: {}", e)))?;
+    Ok(hex::encode(sighash))
+}
+
+/// Get every intermediate ZIP 244 digest behind a transparent input's
+/// sighash, to cross-check a hardware wallet's own implementation.
+///
+/// Returns an object of hex digests: `header_digest`, `prevouts_digest`,
+/// `amounts_digest`, `script_pubkeys_digest`, `sequence_digest`,
+/// `outputs_digest`, `txin_sig_digest`, `transparent_sig_digest`,
+/// `sapling_digest`, `orchard_digest` and `sighash`, plus `input_index`,
+/// `hash_type` and `consensus_branch_id`.
+#[wasm_bindgen]
+pub fn dump_sighash_digests(pczt: &WasmPczt, input_index: u32) -> Result<JsValue, JsError> {
+    let digests = t2z_core::dump_sighash_digests(&pczt.inner, input_index as usize)
+        .map_err(|e| JsError::new(&format!("Failed to compute digests: {}", e)))?;
+    serde_wasm_bindgen::to_value(&digests)
+        .map_err(|e| JsError::new(&format!("Failed to serialize digests: {}", e)))
+}
+
+/// Append a pre-computed signature to a transparent input.
+///
+/// The signature should be created by signing the output of `get_sighash`
+/// with ECDSA secp256k1, then appending the sighash type byte (0x01 for SIGHASH_ALL).
+///
+/// # Arguments
+/// * `pczt` - The PCZT to update
+/// * `input_index` - Index of the transparent input
+/// * `pubkey_hex` - 33-byte compressed public key as hex
+/// * `signature_hex` - DER-encoded signature + sighash type byte as hex
+///
+/// # Returns
+/// Updated PCZT with the signature added
+#[wasm_bindgen]
+pub fn append_signature(
+    pczt: &WasmPczt,
+    input_index: u32,
+    pubkey_hex: &str,
+    signature_hex: &str,
+) -> Result<WasmPczt, JsError> {
+    let pubkey = parse_pubkey(pubkey_hex)?;
+
+    let signature = hex::decode(signature_hex)
+        .map_err(|e| JsError::new(&format!("Invalid signature hex: {}", e)))?;
+
+    let updated = t2z_core::append_signature(
+        pczt.inner.clone(),
+        input_index as usize,
+        &pubkey,
+        &signature,
+    )
+    .map_err(|e| JsError::new(&format!("Failed to append signature: {}", e)))?;
+
+    Ok(WasmPczt { inner: updated })
+}
+
+/// Sign every transparent input through an external signer.
+///
+/// `signer` is called as `signer(inputIndex, sighashHex, pubkeyHex)` once per
+/// input, in order, and must return (or resolve to) the DER signature with the
+/// sighash type byte appended, as hex. Each call is awaited before the next,
+/// so WebAuthn prompts and remote signers see one request at a time.
+///
+/// # Arguments
+/// * `pczt` - The PCZT (left unchanged)
+/// * `pubkeys_hex` - Public key (33 bytes, hex) of each transparent input, in order
+/// * `signer` - Sync or async signing function
+///
+/// # Returns
+/// Promise resolving to the signed PCZT; rejects with the signer's error if
+/// a call fails
+#[wasm_bindgen]
+pub fn sign_with(
+    pczt: &WasmPczt,
+    pubkeys_hex: Vec<String>,
+    signer: js_sys::Function,
+) -> Result<js_sys::Promise, JsError> {
+    let pubkeys = pubkeys_hex
+        .iter()
+        .map(|pubkey_hex| parse_pubkey(pubkey_hex))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let input_count = pczt.inner.transparent().inputs().len();
+    if pubkeys.len() != input_count {
+        return Err(JsError::new(&format!(
+            "Expected {} public keys, one per transparent input (got {})",
+            input_count,
+            pubkeys.len()
+        )));
+    }
+
+    // Copied so the caller's PCZT stays usable while signing is pending
+    let mut pczt = pczt.inner.clone();
+
+    Ok(wasm_bindgen_futures::future_to_promise(async move {
+        for (input_index, pubkey) in pubkeys.iter().enumerate() {
+            let sighash = t2z_core::get_sighash(&pczt, input_index)
+                .map_err(|e| JsError::new(&format!("Failed to get sighash: {}", e)))?;
+
+            let returned = signer.call3(
+                &JsValue::NULL,
+                &JsValue::from(input_index as u32),
+                &hex::encode(sighash).into(),
+                &hex::encode(pubkey).into(),
+            )?;
+            // Plain return values are accepted as well as promises
+            let promise = js_sys::Promise::resolve(&returned);
+            let signature_hex = wasm_bindgen_futures::JsFuture::from(promise)
+                .await?
+                .as_string()
+                .ok_or_else(|| JsError::new("Signer must return a hex signature string"))?;
+
+            let signature = hex::decode(&signature_hex)
+                .map_err(|e| JsError::new(&format!("Invalid signature hex: {}", e)))?;
+            t2z_core::append_signature_in_place(&mut pczt, input_index, pubkey, &signature)
+                .map_err(|e| JsError::new(&format!("Failed to append signature: {}", e)))?;
+        }
+
+        Ok(WasmPczt { inner: pczt }.into())
+    }))
+}
+
+/// Export what an air-gapped signer needs, without the rest of the PCZT.
+///
+/// Returns an object with `expiry_height` and `inputs`, one per transparent
+/// input, each with `input_index`, `sighash` (hex), `prevout_txid` (hex),
+/// `prevout_index`, `value`, `script_pubkey` (hex), and `derivations`
+/// (`pubkey`, `seed_fingerprint`, `derivation_path`).
+#[wasm_bindgen]
+pub fn export_signing_package(pczt: &WasmPczt) -> Result<JsValue, JsError> {
+    let package = t2z_core::export_signing_package(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to export signing package: {}", e)))?;
+    serde_wasm_bindgen::to_value(&package)
+        .map_err(|e| JsError::new(&format!("Failed to serialize signing package: {}", e)))
+}
+
+/// Merge an air-gapped signer's signatures into the PCZT.
+///
+/// `response` is `{ signatures: [{ input_index, pubkey, signature }] }` with
+/// hex `pubkey` and `signature` (DER plus sighash type byte). Every signature
+/// is verified against the PCZT's sighash first.
+#[wasm_bindgen]
+pub fn import_signatures(pczt: &WasmPczt, response: JsValue) -> Result<WasmPczt, JsError> {
+    let response: t2z_core::SigningPackageResponse = serde_wasm_bindgen::from_value(response)
+        .map_err(|e| JsError::new(&format!("Invalid signing package response: {}", e)))?;
+
+    let signed = t2z_core::import_signatures(pczt.inner.clone(), &response)
+        .map_err(|e| JsError::new(&format!("Failed to import signatures: {}", e)))?;
+
+    Ok(WasmPczt { inner: signed })
+}
+
+/// One participant of a relayed signing session (e.g. over a WebSocket).
+///
+/// The client only produces and consumes binary messages; the page owns the
+/// socket:
+///
+/// ```js
+/// const client = new WasmRelayClient("session-1", "alice", pczt);
+/// const ws = new WebSocket("wss://relay.example/v1/relay");
+/// ws.binaryType = "arraybuffer";
+/// ws.onopen = () => ws.send(client.join_message());
+/// ws.onmessage = (e) => client.receive(new Uint8Array(e.data));
+/// // after signing locally:
+/// ws.send(client.publish(signedPczt));
+/// ```
+#[wasm_bindgen]
+pub struct WasmRelayClient {
+    inner: t2z_core::RelayClient,
+}
+
+#[wasm_bindgen]
+impl WasmRelayClient {
+    #[wasm_bindgen(constructor)]
+    pub fn new(session_id: String, participant_id: String, pczt: &WasmPczt) -> WasmRelayClient {
+        WasmRelayClient {
+            inner: t2z_core::RelayClient::new(session_id, participant_id, pczt.inner.clone()),
+        }
+    }
+
+    /// The message to send when the connection opens
+    #[wasm_bindgen]
+    pub fn join_message(&self) -> Vec<u8> {
+        self.inner.join_message()
+    }
+
+    /// Merge a locally updated PCZT and get the message announcing it
+    #[wasm_bindgen]
+    pub fn publish(&mut self, pczt: &WasmPczt) -> Result<Vec<u8>, JsError> {
+        self.inner
+            .publish(pczt.inner.clone())
+            .map_err(|e| JsError::new(&format!("Failed to publish PCZT: {}", e)))
+    }
+
+    /// Handle a message from the relay, merging any update into the PCZT
+    #[wasm_bindgen]
+    pub fn receive(&mut self, message: &[u8]) -> Result<(), JsError> {
+        self.inner
+            .receive(message)
+            .map_err(|e| JsError::new(&format!("Failed to handle relay message: {}", e)))
+    }
+
+    /// The current PCZT, with every update merged in
+    #[wasm_bindgen]
+    pub fn pczt(&self) -> WasmPczt {
+        WasmPczt {
+            inner: self.inner.pczt().clone(),
+        }
+    }
+}
+
+/// Data needed to produce an Orchard spend authorization signature externally
+#[wasm_bindgen]
+pub struct WasmOrchardSpendSigningData {
+    sighash: String,
+    alpha: String,
+    rk: String,
+}
+
+#[wasm_bindgen]
+impl WasmOrchardSpendSigningData {
+    /// Shielded sighash to sign (hex)
+    #[wasm_bindgen(getter)]
+    pub fn sighash(&self) -> String {
+        self.sighash.clone()
+    }
+
+    /// Spend authorization randomizer (hex); sign with `ask + alpha`
+    #[wasm_bindgen(getter)]
+    pub fn alpha(&self) -> String {
+        self.alpha.clone()
+    }
+
+    /// Randomized verification key the signature must verify under (hex)
+    #[wasm_bindgen(getter)]
+    pub fn rk(&self) -> String {
+        self.rk.clone()
+    }
+}
+
+/// Get the data needed to sign an Orchard spend externally.
+///
+/// Use this for hardware wallets holding the spend authorizing key:
+/// 1. Call `get_orchard_spend_signing_data` for the action
+/// 2. Produce a RedPallas SpendAuth signature over `sighash` with `ask + alpha`
+/// 3. Call `append_orchard_spend_auth_sig` with the result
+///
+/// # Arguments
+/// * `pczt` - The PCZT
+/// * `action_index` - Index of the Orchard action
+#[wasm_bindgen]
+pub fn get_orchard_spend_signing_data(
+    pczt: &WasmPczt,
+    action_index: u32,
+) -> Result<WasmOrchardSpendSigningData, JsError> {
+    let data = t2z_core::get_orchard_spend_signing_data(&pczt.inner, action_index as usize)
+        .map_err(|e| JsError::new(&format!("Failed to get Orchard signing data: {}", e)))?;
+
+    Ok(WasmOrchardSpendSigningData {
+        sighash: hex::encode(data.sighash),
+        alpha: hex::encode(data.alpha),
+        rk: hex::encode(data.rk),
+    })
+}
+
+/// Append a pre-computed RedPallas spend authorization signature to an Orchard action.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to update
+/// * `action_index` - Index of the Orchard action
+/// * `signature_hex` - 64-byte RedPallas signature as hex
+///
+/// # Returns
+/// Updated PCZT with the signature added
+#[wasm_bindgen]
+pub fn append_orchard_spend_auth_sig(
+    pczt: &WasmPczt,
+    action_index: u32,
+    signature_hex: &str,
+) -> Result<WasmPczt, JsError> {
+    let signature: [u8; 64] = hex::decode(signature_hex)
+        .map_err(|e| JsError::new(&format!("Invalid signature hex: {}", e)))?
+        .try_into()
+        .map_err(|_| JsError::new("Signature must be 64 bytes"))?;
+
+    let updated = t2z_core::append_orchard_spend_auth_sig(
+        pczt.inner.clone(),
+        action_index as usize,
+        &signature,
+    )
+    .map_err(|e| JsError::new(&format!("Failed to append Orchard signature: {}", e)))?;
+
+    Ok(WasmPczt { inner: updated })
+}
+
+/// Sign an Orchard spend with an Orchard spending key.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to sign
+/// * `action_index` - Index of the Orchard action to sign
+/// * `spending_key_hex` - 32-byte Orchard spending key as hex string
+///
+/// # Returns
+/// The PCZT with the spend authorization signature added
+#[wasm_bindgen]
+pub fn sign_orchard_spend(
+    pczt: &WasmPczt,
+    action_index: u32,
+    spending_key_hex: &str,
+) -> Result<WasmPczt, JsError> {
+    let spending_key: [u8; 32] = hex::decode(spending_key_hex)
+        .map_err(|e| JsError::new(&format!("Invalid spending key hex: {}", e)))?
+        .try_into()
+        .map_err(|_| JsError::new("Spending key must be 32 bytes"))?;
+
+    let signed =
+        t2z_core::sign_orchard_spend(pczt.inner.clone(), action_index as usize, &spending_key)
+            .map_err(|e| JsError::new(&format!("Failed to sign Orchard spend: {}", e)))?;
+
+    Ok(WasmPczt { inner: signed })
+}
+
+/// Verify the PCZT matches the original transaction request before signing.
+///
+/// This is an important security check for multi-party transaction construction.
+/// It verifies:
+/// - All requested payments are present
+/// - No unexpected outputs were added
+/// - Change output matches expectations (if any)
+///
+/// # Arguments
+/// * `pczt` - The PCZT to verify
+/// * `payments` - The original payments array used to create the PCZT
+/// * `change_address` - Expected change address (optional)
+/// * `change_amount` - Expected change amount in zatoshis (optional)
+///
+/// # Returns
+/// Ok if verification passes, error with details otherwise
+#[wasm_bindgen]
+pub fn verify_before_signing(
+    pczt: &WasmPczt,
+    payments: Vec<WasmPayment>,
+    expected_change: Vec<WasmExpectedTxOut>,
+) -> Result<(), JsError> {
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let core_payments = core_payments?;
+
+    let core_expected_change: Vec<t2z_core::ExpectedTxOut> =
+        expected_change.iter().map(|c| c.to_core()).collect();
+
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments,
+    };
+
+    t2z_core::verify_before_signing(&pczt.inner, &request, &core_expected_change)
+        .map_err(|e| JsError::new(&format!("Verification failed: {}", e)))
+}
+
+/// `verify_before_signing`, then an organization-specific rule.
+///
+/// `rule` is called as `rule(pcztInfo, payments)`, with `pcztInfo` as
+/// `inspect_pczt` returns it, after the payments' recipients and amounts have
+/// been matched against the PCZT (memos are encrypted in the PCZT, so only
+/// the payments' own memos are available). It returns a string to refuse
+/// signing, with the reason, or nothing to allow it.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to verify
+/// * `payments` - The original payments array used to create the PCZT
+/// * `expected_change` - Expected change outputs
+/// * `rule` - Synchronous rule function
+#[wasm_bindgen]
+pub fn verify_before_signing_with_rule(
+    pczt: &WasmPczt,
+    payments: Vec<WasmPayment>,
+    expected_change: Vec<WasmExpectedTxOut>,
+    rule: js_sys::Function,
+) -> Result<(), JsError> {
+    let js_payments = js_sys::Array::new();
+    for payment in &payments {
+        js_payments.push(&JsValue::from(payment.clone()));
+    }
+    verify_before_signing(pczt, payments, expected_change)?;
+
+    let info = t2z_core::inspect_pczt(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))?;
+    let info = serde_wasm_bindgen::to_value(&info)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))?;
+
+    let returned = rule
+        .call2(&JsValue::NULL, &info, &js_payments)
+        .map_err(|e| JsError::new(&format!("Signing rule threw: {:?}", e)))?;
+    match returned.as_string() {
+        Some(reason) => Err(JsError::new(&format!("Signing rule rejected: {}", reason))),
+        None => Ok(()),
+    }
+}
+
+/// Combine multiple PCZTs into one.
+///
+/// Useful for multi-party transaction construction.
+///
+/// # Arguments
+/// * `pczts` - Array of PCZTs to combine
+///
+/// # Returns
+/// Combined PCZT
+#[wasm_bindgen]
+pub fn combine(pczts: Vec<WasmPczt>) -> Result<WasmPczt, JsError> {
+    let core_pczts: Vec<t2z_core::Pczt> = pczts.into_iter().map(|p| p.inner).collect();
+
+    let combined = t2z_core::combine(core_pczts)
+        .map_err(|e| JsError::new(&format!("Failed to combine PCZTs: {}", e)))?;
+
+    Ok(WasmPczt { inner: combined })
+}
+
+/// Finalize the PCZT and extract the raw transaction bytes.
+///
+/// This implements the Spend Finalizer and Transaction Extractor roles.
+/// The returned bytes are ready to be broadcast to the Zcash network.
+///
+/// # Arguments
+/// * `pczt` - The fully signed and proved PCZT
+///
+/// # Returns
+/// Raw transaction bytes ready for broadcast
+#[wasm_bindgen]
+pub fn finalize_and_extract(pczt: &WasmPczt) -> Result<Vec<u8>, JsError> {
+    t2z_core::finalize_and_extract(pczt.inner.clone())
+        .map_err(|e| JsError::new(&format!("Failed to finalize transaction: {}", e)))
+}
+
+/// Check every signature in the PCZT against its sighash.
+///
+/// # Returns
+/// An array of the signatures that do not verify (empty if all do), each
+/// `{ kind, reason }` plus `input_index` and `pubkey` for `"transparent"`
+/// or `action_index` for `"orchard_spend_auth"` (`"orchard_proof"` has no
+/// index)
+#[wasm_bindgen]
+pub fn find_invalid_signatures(pczt: &WasmPczt) -> Result<JsValue, JsError> {
+    let invalid = t2z_core::find_invalid_signatures(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to check signatures: {}", e)))?;
+    serde_wasm_bindgen::to_value(&invalid)
+        .map_err(|e| JsError::new(&format!("Failed to serialize signatures: {}", e)))
+}
+
+/// Like `finalize_and_extract`, but first checks every signature and fails
+/// naming the first one that does not verify
+#[wasm_bindgen]
+pub fn finalize_and_extract_verified(pczt: &WasmPczt) -> Result<Vec<u8>, JsError> {
+    t2z_core::finalize_and_extract_verified(pczt.inner.clone())
+        .map_err(|e| JsError::new(&format!("Failed to finalize transaction: {}", e)))
+}
+
+/// Finalize and extract as hex string (convenience method)
+#[wasm_bindgen]
+pub fn finalize_and_extract_hex(pczt: &WasmPczt) -> Result<String, JsError> {
+    let bytes = finalize_and_extract(pczt)?;
+    Ok(hex::encode(bytes))
+}
+
+/// Whether the PCZT's transaction can no longer be mined after `current_height`
+#[wasm_bindgen]
+pub fn pczt_is_expired(pczt: &WasmPczt, current_height: u32) -> bool {
+    t2z_core::Expiry::of_pczt(&pczt.inner).is_expired(current_height)
+}
+
+/// Blocks after `current_height` that can still include the PCZT's
+/// transaction (0 once expired), or undefined if it never expires
+#[wasm_bindgen]
+pub fn pczt_blocks_until_expiry(pczt: &WasmPczt, current_height: u32) -> Option<u32> {
+    t2z_core::Expiry::of_pczt(&pczt.inner).blocks_until_expiry(current_height)
+}
+
+/// Whether an extracted transaction (hex) can no longer be mined after
+/// `current_height`
+#[wasm_bindgen]
+pub fn transaction_is_expired(tx_hex: &str, current_height: u32) -> Result<bool, JsError> {
+    Ok(transaction_expiry(tx_hex)?.is_expired(current_height))
+}
+
+/// Blocks after `current_height` that can still include an extracted
+/// transaction (hex), or undefined if it never expires
+#[wasm_bindgen]
+pub fn transaction_blocks_until_expiry(
+    tx_hex: &str,
+    current_height: u32,
+) -> Result<Option<u32>, JsError> {
+    Ok(transaction_expiry(tx_hex)?.blocks_until_expiry(current_height))
+}
+
+fn transaction_expiry(tx_hex: &str) -> Result<t2z_core::Expiry, JsError> {
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    t2z_core::Expiry::of_transaction(&tx_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to read transaction: {}", e)))
+}
+
+/// Whether a raw transaction (hex) is a coinbase transaction, whose outputs
+/// can only be spent by fully shielded transactions
+#[wasm_bindgen]
+pub fn is_coinbase_transaction(tx_hex: &str) -> Result<bool, JsError> {
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    t2z_core::is_coinbase_transaction(&tx_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to read transaction: {}", e)))
+}
+
+/// Get the library version
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+// ============================================================================
+// Orchard Account Key Management
+// ============================================================================
+
+fn parse_network(network: &str) -> Result<t2z_core::Network, JsError> {
+    match network {
+        "mainnet" => Ok(t2z_core::Network::Mainnet),
+        "testnet" => Ok(t2z_core::Network::Testnet),
+        _ => Err(JsError::new("Network must be 'mainnet' or 'testnet'")),
+    }
+}
+
+/// Parses 32 bytes of hex, naming `what` in the error
+fn parse_hash(hash_hex: &str, what: &str) -> Result<[u8; 32], JsError> {
+    hex::decode(hash_hex)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| JsError::new(&format!("{} must be 32 bytes of hex", what)))
+}
+
+fn parse_pubkey(pubkey_hex: &str) -> Result<[u8; 33], JsError> {
+    hex::decode(pubkey_hex)
+        .map_err(|e| JsError::new(&format!("Invalid pubkey hex: {}", e)))?
+        .try_into()
+        .map_err(|_| JsError::new("Public key must be 33 bytes (compressed)"))
+}
+
+/// An Orchard account: spending key, viewing key, and address derivation.
+///
+/// The spending key is zeroized when the account is freed (call `free()`
+/// when you are done with it).
+#[wasm_bindgen]
+pub struct WasmOrchardAccount {
+    inner: t2z_core::OrchardAccount,
+}
+
+#[wasm_bindgen]
+impl WasmOrchardAccount {
+    /// Generate a new account from a random spending key
+    #[wasm_bindgen]
+    pub fn generate() -> WasmOrchardAccount {
+        WasmOrchardAccount {
+            inner: t2z_core::OrchardAccount::generate(),
+        }
+    }
+
+    /// Restore an account from a hex-encoded 32-byte spending key
+    #[wasm_bindgen]
+    pub fn from_spending_key(spending_key_hex: &str) -> Result<WasmOrchardAccount, JsError> {
+        let bytes: [u8; 32] = hex::decode(spending_key_hex)
+            .map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?
+            .try_into()
+            .map_err(|_| JsError::new("Spending key must be 32 bytes"))?;
+
+        let inner = t2z_core::OrchardAccount::from_spending_key(&bytes)
+            .map_err(|e| JsError::new(&format!("Failed to load account: {}", e)))?;
+        Ok(WasmOrchardAccount { inner })
+    }
+
+    /// Derive an account from a seed (ZIP 32)
+    ///
+    /// # Arguments
+    /// * `seed` - Seed bytes (e.g. from a BIP 39 mnemonic)
+    /// * `network` - "mainnet" or "testnet"
+    /// * `account` - ZIP 32 account index
+    #[wasm_bindgen]
+    pub fn from_seed(
+        seed: &[u8],
+        network: &str,
+        account: u32,
+    ) -> Result<WasmOrchardAccount, JsError> {
+        let inner = t2z_core::OrchardAccount::from_seed(seed, parse_network(network)?, account)
+            .map_err(|e| JsError::new(&format!("Failed to derive account: {}", e)))?;
+        Ok(WasmOrchardAccount { inner })
+    }
+
+    /// Hex-encoded spending key (keep secret!)
+    #[wasm_bindgen(getter)]
+    pub fn spending_key(&self) -> String {
+        hex::encode(self.inner.spending_key_bytes())
+    }
+
+    /// Unified full viewing key (uview1... / uviewtest1...)
+    #[wasm_bindgen]
+    pub fn ufvk(&self, network: &str) -> Result<String, JsError> {
+        self.inner
+            .ufvk(parse_network(network)?)
+            .map_err(|e| JsError::new(&format!("Failed to encode UFVK: {}", e)))
+    }
+
+    /// Unified address (Orchard receiver) at a diversifier index
+    #[wasm_bindgen]
+    pub fn address(&self, index: u32, network: &str) -> Result<String, JsError> {
+        self.inner
+            .address(index, parse_network(network)?)
+            .map_err(|e| JsError::new(&format!("Failed to derive address: {}", e)))
+    }
+
+    /// Unified address (Orchard receiver) for change
+    #[wasm_bindgen]
+    pub fn change_address(&self, network: &str) -> Result<String, JsError> {
+        self.inner
+            .change_address(parse_network(network)?)
+            .map_err(|e| JsError::new(&format!("Failed to derive change address: {}", e)))
+    }
+}
+
+/// Generate a random test Orchard address.
+///
+/// Thin wrapper over `WasmOrchardAccount.generate()` for tests that only
+/// need somewhere to send funds.
+///
+/// # Arguments
+/// * `network` - "mainnet" or "testnet"
+///
+/// # Returns
+/// A valid unified address string with an Orchard receiver
+///
+/// # Warning
+/// The spending key is discarded - you cannot spend funds sent to this address!
+/// Use `WasmOrchardAccount` if you need to keep the key.
+#[wasm_bindgen]
+pub fn generate_test_address(network: &str) -> Result<String, JsError> {
+    t2z_core::OrchardAccount::generate()
+        .address(0, parse_network(network)?)
+        .map_err(|e| JsError::new(&format!("Failed to create unified address: {}", e)))
+}
+
+/// Generate a test keypair (address + spending key).
+///
+/// Returns an object with:
+/// - `address`: Unified address with Orchard receiver
+/// - `spending_key`: Hex-encoded spending key (keep secret!)
+/// - `full_viewing_key`: Unified full viewing key
+/// - `full_viewing_key_hex`: Raw 96-byte Orchard FVK (hex)
+///
+/// Prefer `WasmOrchardAccount`, which keeps the key out of plain JS objects.
+#[wasm_bindgen]
+pub fn generate_test_keypair(network: &str) -> Result<JsValue, JsError> {
+    let network = parse_network(network)?;
+    let account = t2z_core::OrchardAccount::generate();
+
+    let address = account
+        .address(0, network)
+        .map_err(|e| JsError::new(&format!("Failed to create unified address: {}", e)))?;
+    let ufvk = account
+        .ufvk(network)
+        .map_err(|e| JsError::new(&format!("Failed to create UFVK: {}", e)))?;
+    let fvk_bytes = account.full_viewing_key().to_bytes();
+
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"address".into(), &address.into())
+        .map_err(|_| JsError::new("Failed to set address"))?;
+    js_sys::Reflect::set(
+        &obj,
+        &"spending_key".into(),
+        &hex::encode(account.spending_key_bytes()).into(),
+    )
+    .map_err(|_| JsError::new("Failed to set spending_key"))?;
+    js_sys::Reflect::set(&obj, &"full_viewing_key".into(), &ufvk.into())
+        .map_err(|_| JsError::new("Failed to set full_viewing_key"))?;
+    js_sys::Reflect::set(&obj, &"full_viewing_key_hex".into(), &hex::encode(fvk_bytes).into())
+        .map_err(|_| JsError::new("Failed to set full_viewing_key_hex"))?;
+
+    Ok(obj.into())
+}
+
+fn transparent_keypair_to_js(keypair: &t2z_core::TransparentKeypair) -> Result<JsValue, JsError> {
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"secret_key".into(), &hex::encode(keypair.secret_key).into())
+        .map_err(|_| JsError::new("Failed to set secret_key"))?;
+    js_sys::Reflect::set(&obj, &"public_key".into(), &hex::encode(keypair.public_key).into())
+        .map_err(|_| JsError::new("Failed to set public_key"))?;
+    js_sys::Reflect::set(&obj, &"address".into(), &keypair.address.clone().into())
+        .map_err(|_| JsError::new("Failed to set address"))?;
+    js_sys::Reflect::set(
+        &obj,
+        &"script_pubkey".into(),
+        &hex::encode(&keypair.script_pubkey).into(),
+    )
+    .map_err(|_| JsError::new("Failed to set script_pubkey"))?;
+
+    Ok(obj.into())
+}
+
+/// Generate a random transparent keypair.
+///
+/// Returns an object with:
+/// - `secret_key`: Hex-encoded 32-byte secret key (keep secret!)
+/// - `public_key`: Hex-encoded compressed public key
+/// - `address`: P2PKH address
+/// - `script_pubkey`: Hex-encoded P2PKH script
+///
+/// # Arguments
+/// * `network` - "mainnet" or "testnet"
+#[wasm_bindgen]
+pub fn generate_transparent_keypair(network: &str) -> Result<JsValue, JsError> {
+    let keypair = t2z_core::generate_transparent_keypair(parse_network(network)?);
+    transparent_keypair_to_js(&keypair)
+}
+
+/// Derive a transparent keypair from a seed (m/44'/coin_type'/account'/0/address_index).
+///
+/// Returns the same object as `generate_transparent_keypair`.
+#[wasm_bindgen]
+pub fn derive_transparent_keypair(
+    seed: &[u8],
+    network: &str,
+    account: u32,
+    address_index: u32,
+) -> Result<JsValue, JsError> {
+    let keypair =
+        t2z_core::derive_transparent_keypair(seed, parse_network(network)?, account, address_index)
+            .map_err(|e| JsError::new(&format!("Failed to derive keypair: {}", e)))?;
+    transparent_keypair_to_js(&keypair)
+}
+
+/// Import a WIF secret key (e.g. from a zcashd `dumpwallet` file).
+///
+/// Returns an object with:
+/// - `secret_key`: Hex-encoded 32-byte secret key, as `sign_transparent_input` expects
+/// - `network`: "mainnet" or "testnet"
+#[wasm_bindgen]
+pub fn secret_key_from_wif(wif: &str) -> Result<JsValue, JsError> {
+    let (secret_key, network) = t2z_core::secret_key_from_wif(wif)
+        .map_err(|e| JsError::new(&format!("Failed to import WIF: {}", e)))?;
+
+    let network = match network {
+        t2z_core::Network::Mainnet => "mainnet",
+        t2z_core::Network::Testnet => "testnet",
+    };
+
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"secret_key".into(), &hex::encode(secret_key).into())
+        .map_err(|_| JsError::new("Failed to set secret_key"))?;
+    js_sys::Reflect::set(&obj, &"network".into(), &network.into())
+        .map_err(|_| JsError::new("Failed to set network"))?;
+
+    Ok(obj.into())
+}
+
+/// Export a hex-encoded 32-byte secret key as WIF.
+#[wasm_bindgen]
+pub fn secret_key_to_wif(secret_key_hex: &str, network: &str) -> Result<String, JsError> {
+    let secret_key: [u8; 32] = hex::decode(secret_key_hex)
+        .map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?
+        .try_into()
+        .map_err(|_| JsError::new("Secret key must be 32 bytes"))?;
+
+    t2z_core::secret_key_to_wif(&secret_key, parse_network(network)?)
+        .map_err(|e| JsError::new(&format!("Failed to export WIF: {}", e)))
+}
+
+/// Sign a message with a transparent secret key (zcashd `signmessage`).
+///
+/// # Returns
+/// The signature (base64), verifiable against the key's P2PKH address
+#[wasm_bindgen]
+pub fn sign_message(secret_key_hex: &str, message: &str) -> Result<String, JsError> {
+    let secret_key = parse_hash(secret_key_hex, "Secret key")?;
+    t2z_core::sign_message(&secret_key, message.as_bytes())
+        .map_err(|e| JsError::new(&format!("Failed to sign message: {}", e)))
+}
+
+/// Check a signed message against a transparent P2PKH address (zcashd
+/// `verifymessage`). Returns false if another key signed it.
+#[wasm_bindgen]
+pub fn verify_message(address: &str, message: &str, signature: &str) -> Result<bool, JsError> {
+    t2z_core::verify_message(address, message.as_bytes(), signature)
+        .map_err(|e| JsError::new(&format!("Failed to verify message: {}", e)))
+}
+
+/// Generate a new BIP 39 mnemonic phrase.
+///
+/// # Arguments
+/// * `word_count` - 12, 15, 18, 21, or 24
+/// * `language` - "english", "chinese_simplified", "chinese_traditional", "czech",
+///   "french", "italian", "japanese", "korean", "portuguese", or "spanish"
+#[wasm_bindgen]
+pub fn generate_mnemonic(word_count: u32, language: &str) -> Result<String, JsError> {
+    let language: t2z_core::MnemonicLanguage =
+        language.parse().map_err(|e| JsError::new(&format!("{}", e)))?;
+    t2z_core::generate_mnemonic(word_count, language)
+        .map_err(|e| JsError::new(&format!("Failed to generate mnemonic: {}", e)))
+}
+
+/// Validate a mnemonic phrase (words and checksum). Throws if invalid.
+#[wasm_bindgen]
+pub fn validate_mnemonic(phrase: &str, language: &str) -> Result<(), JsError> {
+    let language: t2z_core::MnemonicLanguage =
+        language.parse().map_err(|e| JsError::new(&format!("{}", e)))?;
+    t2z_core::validate_mnemonic(phrase, language).map_err(|e| JsError::new(&format!("{}", e)))
+}
+
+/// Derive the 64-byte seed from a mnemonic phrase and passphrase.
+///
+/// Pass the seed to `WasmOrchardAccount.from_seed` or `derive_transparent_keypair`.
+#[wasm_bindgen]
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str, language: &str) -> Result<Vec<u8>, JsError> {
+    let language: t2z_core::MnemonicLanguage =
+        language.parse().map_err(|e| JsError::new(&format!("{}", e)))?;
+    let seed = t2z_core::mnemonic_to_seed(phrase, passphrase, language)
+        .map_err(|e| JsError::new(&format!("Failed to derive seed: {}", e)))?;
+    Ok(seed.to_vec())
+}
+
+// ============================================================================
+// PCZT Inspection
+// ============================================================================
+
+/// Inspect a PCZT and return detailed information about its contents.
+///
+/// Returns a JSON object with:
+/// - `schema_version`: Version of this structure (see `pczt_info_json_schema`)
+/// - `expiry_height`: Transaction expiry height
+/// - `transparent_inputs`: Array of transparent inputs (txid, value, script, signed status)
+/// - `transparent_outputs`: Array of transparent outputs (value, script, address)
+/// - `orchard_outputs`: Array of Orchard outputs (value, recipient, address)
+/// - `total_input`: Total input value in zatoshis
+/// - `total_transparent_output`: Total transparent output value
+/// - `total_orchard_output`: Total Orchard output value
+/// - `implied_fee`: Calculated fee (inputs - outputs)
+/// - `num_orchard_actions`: Number of Orchard actions
+/// - `all_inputs_signed`: Whether all transparent inputs have signatures
+/// - `has_orchard_proofs`: Whether Orchard bundle has proofs
+///
+/// This is useful for:
+/// - Displaying transaction details before signing
+/// - Getting the actual fee and change amounts after propose_transaction
+/// - Verifying the transaction matches expectations
+/// - Checking signing/proving progress
+#[wasm_bindgen]
+pub fn inspect_pczt(pczt_hex: &str) -> Result<JsValue, JsError> {
+    let pczt_bytes = hex::decode(pczt_hex)
+        .map_err(|e| JsError::new(&format!("Invalid hex: {}", e)))?;
+    
+    let info = t2z_core::inspect_pczt_bytes(&pczt_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))?;
+    
+    // Convert to JS value using serde
+    serde_wasm_bindgen::to_value(&info)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Inspect a PCZT and return the same information as a JSON string that
+/// conforms to `pczt_info_json_schema()` (versioned by `schema_version`).
+#[wasm_bindgen]
+pub fn inspect_pczt_json(pczt: &WasmPczt) -> Result<String, JsError> {
+    t2z_core::inspect_pczt_json(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))
+}
+
+/// Inspect a PCZT like `inspect_pczt`, also decrypting each Orchard output
+/// with the sender's outgoing viewing key.
+///
+/// Outputs the OVK can decrypt get a `decrypted` field with the value,
+/// recipient, and memo (hex) actually encrypted, and `matches_pczt`, whether
+/// they agree with the PCZT's unencrypted fields.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to inspect
+/// * `ovk_hex` - Orchard outgoing viewing key (32 bytes, hex)
+#[wasm_bindgen]
+pub fn inspect_pczt_with_ovk(pczt: &WasmPczt, ovk_hex: &str) -> Result<JsValue, JsError> {
+    let ovk: [u8; 32] = hex::decode(ovk_hex)
+        .map_err(|e| JsError::new(&format!("Invalid OVK hex: {}", e)))?
+        .try_into()
+        .map_err(|_| JsError::new("OVK must be 32 bytes"))?;
+
+    let info = t2z_core::inspect_pczt_with_ovk(&pczt.inner, &ovk)
+        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))?;
+    serde_wasm_bindgen::to_value(&info)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Return every zatoshi amount as a decimal string (e.g. `"100000"`) instead
+/// of a number from now on, in objects and JSON alike, so amounts never pass
+/// through a lossy double. Amounts read from objects may use either form.
+#[wasm_bindgen]
+pub fn set_amounts_as_strings(enabled: bool) {
+    t2z_core::set_amounts_as_strings(enabled);
+}
+
+/// Format zatoshis as ZEC with a locale's separators, e.g.
+/// `format_zec(12345n, "de-DE")` is `"0,00012345 ZEC"`.
+///
+/// # Arguments
+/// * `zatoshis` - The amount, as a bigint
+/// * `locale` - BCP 47 locale such as `navigator.language` (English if omitted)
+/// * `min_decimals` - Fraction digits to keep when the rest are zeros (default 8)
+#[wasm_bindgen]
+pub fn format_zec(zatoshis: u64, locale: Option<String>, min_decimals: Option<u8>) -> String {
+    t2z_core::format_zec(zatoshis, &amount_format(locale, min_decimals))
+}
+
+/// Parse a ZEC amount typed in a locale's format (`"1.234,5"` for
+/// `"de-DE"`, unit and grouping optional) into zatoshis, as a bigint.
+#[wasm_bindgen]
+pub fn parse_zec(text: &str, locale: Option<String>) -> Result<u64, JsError> {
+    t2z_core::parse_zec(text, &amount_format(locale, None))
+        .map_err(|e| JsError::new(&format!("Failed to parse amount: {}", e)))
+}
+
+fn amount_format(locale: Option<String>, min_decimals: Option<u8>) -> t2z_core::AmountFormat {
+    let mut format = locale
+        .map(|locale| t2z_core::AmountFormat::for_locale(&locale))
+        .unwrap_or_default();
+    if let Some(min_decimals) = min_decimals {
+        format.min_decimals = min_decimals as usize;
+    }
+    format
+}
+
+/// Report which transparent inputs are signed and which signers still owe
+/// signatures, for transactions spending inputs of several keys or multisigs.
+///
+/// Signers are identified by `signer_id(pubkey_hex)`. Returns an object with:
+/// - `inputs`: Per input `{ index, kind, signers, signed_by,
+///   signatures_required, complete }` (`kind` is "p2pkh", "p2sh_multisig"
+///   or "other")
+/// - `pending`: Map from signer ID to the input indices it can still sign
+/// - `all_inputs_signed`, `has_orchard_proofs`
+#[wasm_bindgen]
+pub fn pczt_status(pczt: &WasmPczt) -> Result<JsValue, JsError> {
+    let status = t2z_core::pczt_status(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to inspect PCZT: {}", e)))?;
+    serde_wasm_bindgen::to_value(&status)
+        .map_err(|e| JsError::new(&format!("Failed to serialize status: {}", e)))
+}
+
+/// The signer ID `pczt_status` uses for a compressed public key (hex).
+#[wasm_bindgen]
+pub fn signer_id(pubkey_hex: &str) -> Result<String, JsError> {
+    let pubkey = hex::decode(pubkey_hex)
+        .map_err(|e| JsError::new(&format!("Invalid public key hex: {}", e)))?;
+    t2z_core::signer_id(&pubkey)
+        .map_err(|e| JsError::new(&format!("Failed to compute signer ID: {}", e)))
+}
+
+/// Render a PCZT as readable multi-line text for logs and support tickets.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to render
+/// * `verbosity` - "summary", "normal", or "full"
+#[wasm_bindgen]
+pub fn format_pczt(pczt: &WasmPczt, verbosity: &str) -> Result<String, JsError> {
+    let verbosity: t2z_core::Verbosity =
+        verbosity.parse().map_err(|e| JsError::new(&format!("{}", e)))?;
+    t2z_core::format_pczt(&pczt.inner, verbosity)
+        .map_err(|e| JsError::new(&format!("Failed to format PCZT: {}", e)))
+}
+
+/// Like `format_pczt`, showing the memos of the payments the PCZT was
+/// proposed from (PCZTs only hold memos encrypted).
+#[wasm_bindgen]
+pub fn format_pczt_with_payments(
+    pczt: &WasmPczt,
+    payments: Vec<WasmPayment>,
+    verbosity: &str,
+) -> Result<String, JsError> {
+    let verbosity: t2z_core::Verbosity =
+        verbosity.parse().map_err(|e| JsError::new(&format!("{}", e)))?;
+    let request = t2z_core::TransactionRequest {
+        payments: payments.iter().map(|p| p.to_core()).collect::<Result<_, _>>()?,
+    };
+    t2z_core::format_pczt_with_request(&pczt.inner, &request, verbosity)
+        .map_err(|e| JsError::new(&format!("Failed to format PCZT: {}", e)))
+}
+
+/// The JSON Schema for `inspect_pczt_json` output.
+#[wasm_bindgen]
+pub fn pczt_info_json_schema() -> String {
+    t2z_core::PCZT_INFO_JSON_SCHEMA.to_string()
+}
+
+/// Attribute the transaction fee across payments (for accounting).
+///
+/// # Arguments
+/// * `pczt` - PCZT returned by `propose_transaction`
+/// * `payments` - The payments array used to create the PCZT
+/// * `method` - "proportional" (by amount) or "marginal" (by each payment's ZIP 317 cost)
+///
+/// # Returns
+/// Report with `total_fee`, `method`, and per-payment `shares`
+#[wasm_bindgen]
+pub fn attribute_fee(
+    pczt: &WasmPczt,
+    payments: Vec<WasmPayment>,
+    method: &str,
+) -> Result<JsValue, JsError> {
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    let method = match method {
+        "proportional" => t2z_core::FeeAttributionMethod::Proportional,
+        "marginal" => t2z_core::FeeAttributionMethod::Marginal,
+        _ => return Err(JsError::new("Method must be 'proportional' or 'marginal'")),
+    };
+
+    let report = t2z_core::attribute_fee(&pczt.inner, &request, method)
+        .map_err(|e| JsError::new(&format!("Failed to attribute fee: {}", e)))?;
+
+    serde_wasm_bindgen::to_value(&report)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Explain a PCZT's fee in ZIP 317 terms ("why 15,000 zatoshis?").
+///
+/// # Returns
+/// `{ fee, per_input_cost, per_action_cost, grace_actions }` plus
+/// `zip317_fee`, `logical_actions`, `transparent_inputs`,
+/// `transparent_outputs`, and `orchard_actions`. The fee is
+/// `per_action_cost` times the larger of `logical_actions` and
+/// `grace_actions`, where each P2PKH input costs `per_input_cost`. The
+/// amounts are strings under `set_amounts_as_strings(true)`.
+#[wasm_bindgen(js_name = getFeeBreakdown)]
+pub fn get_fee_breakdown(pczt: &WasmPczt) -> Result<JsValue, JsError> {
+    let breakdown = t2z_core::fee_breakdown(&pczt.inner)
+        .map_err(|e| JsError::new(&format!("Failed to break down fee: {}", e)))?;
+
+    serde_wasm_bindgen::to_value(&breakdown)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Export an audit record (JSON) describing a PCZT at signing time.
+///
+/// # Arguments
+/// * `pczt` - The PCZT being signed
+/// * `payments` - The payments array used to create the PCZT
+/// * `timestamp` - Event time in Unix seconds (e.g. `Math.floor(Date.now() / 1000)`)
+///
+/// # Returns
+/// JSON string with inputs, outputs, fee, memo hashes, and txid (once computable)
+#[wasm_bindgen]
+pub fn export_audit_record(
+    pczt: &WasmPczt,
+    payments: Vec<WasmPayment>,
+    timestamp: u64,
+) -> Result<String, JsError> {
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    let record = t2z_core::export_audit_record(&pczt.inner, &request, timestamp)
+        .map_err(|e| JsError::new(&format!("Failed to export audit record: {}", e)))?;
+
+    Ok(record.to_json())
+}
+
+/// Create a payment disclosure for an Orchard output (proof of payment).
+///
+/// # Arguments
+/// * `pczt` - The final (unredacted) PCZT containing the output
+/// * `action_index` - Index of the Orchard action holding the payment
+/// * `memo_hex` - Memo attached to the payment (hex), if any
+///
+/// # Returns
+/// Serialized disclosure as hex, to hand to the verifying party
+#[wasm_bindgen]
+pub fn create_payment_disclosure(
+    pczt: &WasmPczt,
+    action_index: u32,
+    memo_hex: Option<String>,
+) -> Result<String, JsError> {
+    let memo = memo_hex
+        .map(|m| hex::decode(m).map_err(|e| JsError::new(&format!("Invalid memo hex: {}", e))))
+        .transpose()?;
+
+    let disclosure = t2z_core::create_payment_disclosure(&pczt.inner, action_index as usize, memo)
+        .map_err(|e| JsError::new(&format!("Failed to create payment disclosure: {}", e)))?;
+
+    Ok(hex::encode(disclosure.to_bytes()))
+}
+
+/// Verify a payment disclosure against the raw transaction it refers to.
+///
+/// # Arguments
+/// * `disclosure_hex` - Disclosure from `create_payment_disclosure`
+/// * `tx_hex` - The raw transaction as hex
+#[wasm_bindgen]
+pub fn verify_payment_disclosure(disclosure_hex: &str, tx_hex: &str) -> Result<(), JsError> {
+    let disclosure_bytes = hex::decode(disclosure_hex)
+        .map_err(|e| JsError::new(&format!("Invalid disclosure hex: {}", e)))?;
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+
+    t2z_core::PaymentDisclosure::from_bytes(&disclosure_bytes)
+        .and_then(|d| d.verify_against_transaction(&tx_bytes))
+        .map_err(|e| JsError::new(&format!("Payment disclosure verification failed: {}", e)))
+}
+
+/// Identify which outputs of a PCZT belong to a unified full viewing key.
+///
+/// # Arguments
+/// * `pczt` - The PCZT to inspect
+/// * `ufvk` - Encoded unified full viewing key
+///
+/// # Returns
+/// Array of `{ pool, index, value, ownership }`, where ownership is
+/// "Change", "OwnExternal", or "Foreign"
+#[wasm_bindgen]
+pub fn identify_own_outputs(pczt: &WasmPczt, ufvk: &str) -> Result<JsValue, JsError> {
+    let outputs = t2z_core::identify_own_outputs(&pczt.inner, ufvk)
+        .map_err(|e| JsError::new(&format!("Failed to identify outputs: {}", e)))?;
+
+    serde_wasm_bindgen::to_value(&outputs)
+        .map_err(|e| JsError::new(&format!("Failed to serialize: {}", e)))
+}
+
+/// Create a fresh challenge for the owner of an Orchard (unified) address.
+///
+/// Send `challenge` to the prover and keep the whole object to check the
+/// response with `verify_address_ownership`.
+///
+/// # Returns
+/// `{ address, challenge, expected_response }`, with hex `challenge` and
+/// `expected_response` (keep it from the prover)
+#[wasm_bindgen]
+pub fn create_address_ownership_challenge(
+    address: &str,
+    network: &str,
+) -> Result<JsValue, JsError> {
+    let challenge = t2z_core::create_address_ownership_challenge(address, parse_network(network)?)
+        .map_err(|e| JsError::new(&format!("Failed to create challenge: {}", e)))?;
+
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"address".into(), &challenge.address.into())
+        .map_err(|_| JsError::new("Failed to set address"))?;
+    js_sys::Reflect::set(
+        &obj,
+        &"challenge".into(),
+        &hex::encode(challenge.challenge).into(),
+    )
+    .map_err(|_| JsError::new("Failed to set challenge"))?;
+    js_sys::Reflect::set(
+        &obj,
+        &"expected_response".into(),
+        &hex::encode(challenge.expected_response).into(),
+    )
+    .map_err(|_| JsError::new("Failed to set expected_response"))?;
+    Ok(obj.into())
+}
+
+/// Answer an address ownership challenge.
+///
+/// # Arguments
+/// * `ufvk_or_sk` - Unified full viewing key, or hex Orchard spending key
+/// * `address` - The address being proved
+/// * `challenge_hex` - The verifier's `challenge`
+/// * `network` - "mainnet" or "testnet"
+///
+/// # Returns
+/// The response (32 bytes, hex) to send back to the verifier
+#[wasm_bindgen]
+pub fn prove_address_ownership(
+    ufvk_or_sk: &str,
+    address: &str,
+    challenge_hex: &str,
+    network: &str,
+) -> Result<String, JsError> {
+    let challenge = parse_hash(challenge_hex, "Challenge")?;
+    let response =
+        t2z_core::prove_address_ownership(ufvk_or_sk, address, &challenge, parse_network(network)?)
+            .map_err(|e| JsError::new(&format!("Failed to prove ownership: {}", e)))?;
+    Ok(hex::encode(response))
+}
+
+/// Check the prover's response (hex) against a challenge from
+/// `create_address_ownership_challenge`.
+#[wasm_bindgen]
+pub fn verify_address_ownership(challenge: JsValue, response_hex: &str) -> Result<bool, JsError> {
+    let field = |name: &str| {
+        js_sys::Reflect::get(&challenge, &name.into())
+            .ok()
+            .and_then(|value| value.as_string())
+            .ok_or_else(|| JsError::new(&format!("Challenge has no {}", name)))
+    };
+    let challenge = t2z_core::AddressOwnershipChallenge {
+        address: field("address")?,
+        challenge: parse_hash(&field("challenge")?, "Challenge")?,
+        expected_response: parse_hash(&field("expected_response")?, "Expected response")?,
+    };
+    let response = parse_hash(response_hex, "Response")?;
+    Ok(t2z_core::verify_address_ownership(&challenge, &response))
+}
+
+// ============================================================================
+// Wallet Lite Account
+// ============================================================================
+
+#[cfg(feature = "wallet_lite")]
+impl WasmTransparentInput {
+    fn from_core(input: &t2z_core::TransparentInput) -> Self {
+        Self {
+            pubkey: hex::encode(&input.pubkey),
+            prevout_txid: hex::encode(&input.prevout_txid),
+            prevout_index: input.prevout_index,
+            value: input.value,
+            script_pubkey: hex::encode(&input.script_pubkey),
+            sequence: input.sequence,
+        }
+    }
+}
+
+/// Minimal account state: tracks transparent UTXOs through broadcast and
+/// confirmation and selects inputs for transaction requests.
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmAccount {
+    inner: t2z_core::Account,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl WasmAccount {
+    fn wrap(mut inner: t2z_core::Account) -> WasmAccount {
+        let events = EventRelay::default();
+        inner.subscribe(events.sink());
+        WasmAccount { inner, events }
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmAccount {
+    /// Create an empty account for "mainnet" or "testnet"
+    #[wasm_bindgen(constructor)]
+    pub fn new(network: &str) -> Result<WasmAccount, JsError> {
+        Ok(WasmAccount::wrap(t2z_core::Account::new(parse_network(network)?)))
+    }
+
+    /// Restore account state from bytes
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmAccount, JsError> {
+        let inner = t2z_core::Account::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse account: {}", e)))?;
+        Ok(WasmAccount::wrap(inner))
+    }
+
+    /// Serialize account state to bytes
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// Dispatch "utxo_added", "tx_confirmed", and "tx_expired" events to
+    /// `target` from now on, as `CustomEvent`s with the event in `detail`
+    #[wasm_bindgen]
+    pub fn subscribe(&mut self, target: web_sys::EventTarget) {
+        self.events.targets.push(target);
+    }
+
+    /// Add or update a UTXO (`confirmed_height` undefined if mempool-only)
+    #[wasm_bindgen]
+    pub fn add_utxo(
+        &mut self,
+        input: &WasmTransparentInput,
+        confirmed_height: Option<u32>,
+    ) -> Result<(), JsError> {
+        self.inner.add_utxo(input.to_core()?, confirmed_height);
+        self.events.dispatch();
+        Ok(())
+    }
+
+    /// Balance by status: `{ confirmed, unconfirmed, pending_spend }`
+    #[wasm_bindgen]
+    pub fn balance(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner.balance())
+            .map_err(|e| JsError::new(&format!("Failed to serialize balance: {}", e)))
+    }
+
+    /// Select inputs covering the payments plus the ZIP 317 fee
+    #[wasm_bindgen]
+    pub fn select_inputs(
+        &self,
+        payments: Vec<WasmPayment>,
+        change_address: Option<String>,
+        tip_height: u32,
+        min_confirmations: u32,
+    ) -> Result<Vec<WasmTransparentInput>, JsError> {
+        let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+            payments.iter().map(|p| p.to_core()).collect();
+        let request = t2z_core::TransactionRequest {
+            payments: core_payments?,
+        };
+
+        let selected = self
+            .inner
+            .select_inputs(&request, change_address.as_deref(), tip_height, min_confirmations)
+            .map_err(|e| JsError::new(&format!("Failed to select inputs: {}", e)))?;
+        Ok(selected.iter().map(WasmTransparentInput::from_core).collect())
+    }
+
+    /// Mark the UTXOs spent by a broadcast PCZT as pending
+    #[wasm_bindgen]
+    pub fn mark_broadcast(&mut self, pczt: &WasmPczt, txid: &str) {
+        self.inner.mark_broadcast(&pczt.inner, txid);
+    }
+
+    /// Mark the UTXOs spent by a transaction mined at `height` as spent
+    #[wasm_bindgen]
+    pub fn mark_confirmed(&mut self, txid: &str, height: u32) {
+        self.inner.mark_confirmed(txid, height);
+        self.events.dispatch();
+    }
+
+    /// Release the UTXOs spent by a rejected or expired transaction
+    #[wasm_bindgen]
+    pub fn mark_failed(&mut self, txid: &str) {
+        self.inner.mark_failed(txid);
+        self.events.dispatch();
+    }
+
+    /// Undo confirmations above `height` after a reorg; mined UTXOs go back
+    /// to unconfirmed and spent ones to pending. Returns how many were
+    /// affected.
+    #[wasm_bindgen]
+    pub fn rollback(&mut self, height: u32) -> u32 {
+        self.inner.rollback(height).len() as u32
+    }
+}
+
+/// Orchard notes created by this wallet's own proposals (usually change),
+/// tracked from the PCZT so they can be spent later without a rescan
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmNoteStore {
+    inner: t2z_core::NoteStore,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl WasmNoteStore {
+    fn wrap(mut inner: t2z_core::NoteStore) -> WasmNoteStore {
+        let events = EventRelay::default();
+        inner.subscribe(events.sink());
+        WasmNoteStore { inner, events }
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmNoteStore {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmNoteStore {
+        WasmNoteStore::wrap(t2z_core::NoteStore::new())
+    }
+
+    /// Restore a store saved with `to_bytes`
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmNoteStore, JsError> {
+        let inner = t2z_core::NoteStore::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse note store: {}", e)))?;
+        Ok(WasmNoteStore::wrap(inner))
+    }
+
+    /// Serialize the store to bytes
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// Dispatch "note_received", "tx_confirmed", and "tx_expired" events to
+    /// `target` from now on, as `CustomEvent`s with the event in `detail`
+    #[wasm_bindgen]
+    pub fn subscribe(&mut self, target: web_sys::EventTarget) {
+        self.events.targets.push(target);
+    }
+
+    /// Track the Orchard outputs of a broadcast PCZT that pay an address of
+    /// `ufvk`; returns the tracked notes
+    #[wasm_bindgen]
+    pub fn track_pczt(
+        &mut self,
+        pczt: &WasmPczt,
+        txid: &str,
+        ufvk: &str,
+    ) -> Result<JsValue, JsError> {
+        let notes = self
+            .inner
+            .track_pczt(&pczt.inner, txid, ufvk)
+            .map_err(|e| JsError::new(&format!("Failed to track notes: {}", e)))?;
+        self.events.dispatch();
+        serde_wasm_bindgen::to_value(&notes)
+            .map_err(|e| JsError::new(&format!("Failed to serialize notes: {}", e)))
+    }
+
+    /// All tracked notes
+    #[wasm_bindgen]
+    pub fn notes(&self) -> Result<JsValue, JsError> {
+        let notes: Vec<_> = self.inner.notes().collect();
+        serde_wasm_bindgen::to_value(&notes)
+            .map_err(|e| JsError::new(&format!("Failed to serialize notes: {}", e)))
+    }
+
+    /// Confirmed, unspent notes with enough confirmations, largest first
+    #[wasm_bindgen]
+    pub fn spendable_notes(
+        &self,
+        tip_height: u32,
+        min_confirmations: u32,
+    ) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner.spendable_notes(tip_height, min_confirmations))
+            .map_err(|e| JsError::new(&format!("Failed to serialize notes: {}", e)))
+    }
+
+    /// Mark the notes created by a transaction as mined at `height`
+    #[wasm_bindgen]
+    pub fn mark_confirmed(&mut self, txid: &str, height: u32) {
+        self.inner.mark_confirmed(txid, height);
+        self.events.dispatch();
+    }
+
+    /// Drop the notes created by a rejected or expired transaction
+    #[wasm_bindgen]
+    pub fn mark_failed(&mut self, txid: &str) {
+        self.inner.mark_failed(txid);
+        self.events.dispatch();
+    }
+
+    /// Mark the note with this nullifier (32 bytes) as spent; returns
+    /// whether it was tracked
+    #[wasm_bindgen]
+    pub fn mark_spent(&mut self, nullifier: &[u8], txid: &str) -> Result<bool, JsError> {
+        let nullifier: [u8; 32] = nullifier
+            .try_into()
+            .map_err(|_| JsError::new("Nullifier must be 32 bytes"))?;
+        Ok(self.inner.mark_spent(&nullifier, txid))
+    }
+
+    /// Start following the chain after block `height` (`hash` in internal
+    /// byte order), from the Orchard note commitment tree as of that block
+    /// (lightwalletd's `orchardTree`, hex-decoded)
+    #[wasm_bindgen]
+    pub fn set_chain_state(
+        &mut self,
+        height: u32,
+        hash: &[u8],
+        orchard_tree: &[u8],
+    ) -> Result<(), JsError> {
+        self.inner
+            .set_chain_state(height, block_hash(hash)?, orchard_tree)
+            .map_err(|e| JsError::new(&format!("Failed to set chain state: {}", e)))
+    }
+
+    /// Append a block's Orchard note commitments (32 bytes each,
+    /// concatenated in block order) to the tree and the notes' witnesses;
+    /// returns the tracked notes found in it. Throws if `prev_hash` is not
+    /// the last scanned block's hash: `rewind` past the fork first.
+    #[wasm_bindgen]
+    pub fn scan_block(
+        &mut self,
+        height: u32,
+        hash: &[u8],
+        prev_hash: &[u8],
+        commitments: &[u8],
+    ) -> Result<JsValue, JsError> {
+        if commitments.len() % 32 != 0 {
+            return Err(JsError::new("Commitments must be a multiple of 32 bytes"));
+        }
+        let commitments: Vec<[u8; 32]> = commitments
+            .chunks_exact(32)
+            .map(|cmx| cmx.try_into().expect("32-byte chunk"))
+            .collect();
+
+        let found = self
+            .inner
+            .scan_block(height, block_hash(hash)?, block_hash(prev_hash)?, &commitments)
+            .map_err(|e| JsError::new(&format!("Failed to scan block: {}", e)))?;
+        self.events.dispatch();
+        serde_wasm_bindgen::to_value(&found)
+            .map_err(|e| JsError::new(&format!("Failed to serialize notes: {}", e)))
+    }
+
+    /// Return to the state after block `height` after a reorg; notes mined
+    /// in the undone blocks go back to pending and are returned
+    #[wasm_bindgen]
+    pub fn rewind(&mut self, height: u32) -> Result<JsValue, JsError> {
+        let reverted = self
+            .inner
+            .rewind(height)
+            .map_err(|e| JsError::new(&format!("Failed to rewind: {}", e)))?;
+        serde_wasm_bindgen::to_value(&reverted)
+            .map_err(|e| JsError::new(&format!("Failed to serialize notes: {}", e)))
+    }
+
+    /// Height of the last block scanned, if following the chain
+    #[wasm_bindgen]
+    pub fn chain_height(&self) -> Option<u32> {
+        self.inner.chain_height()
+    }
+
+    /// Current Orchard anchor (32 bytes), if following the chain
+    #[wasm_bindgen]
+    pub fn anchor(&self) -> Option<Vec<u8>> {
+        self.inner.anchor().map(|anchor| anchor.to_vec())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+impl Default for WasmNoteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finalized transactions waiting to be broadcast: picks which endpoint to
+/// submit each one to and when, failing over between endpoints and backing
+/// off once all of them fail. Does no networking itself.
+///
+/// ```javascript
+/// const queue = new WasmBroadcastQueue(["https://zec.rocks:443", "https://na.zec.rocks:443"]);
+/// queue.enqueue(txBytes, now);
+/// for (const attempt of queue.poll(now, tipHeight)) {
+///   try {
+///     await sendTransaction(attempt.endpoint, attempt.transaction);
+///     queue.report_accepted(attempt.txid, now);
+///   } catch (e) {
+///     queue.report_failed(attempt.txid, String(e), now);
+///   }
+/// }
+/// ```
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmBroadcastQueue {
+    inner: t2z_core::BroadcastQueue,
+    events: EventRelay,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl WasmBroadcastQueue {
+    fn wrap(mut inner: t2z_core::BroadcastQueue) -> WasmBroadcastQueue {
+        let events = EventRelay::default();
+        inner.subscribe(events.sink());
+        WasmBroadcastQueue { inner, events }
+    }
+
+    fn report(
+        &mut self,
+        txid: &str,
+        result: t2z_core::SubmitResult,
+        now: u64,
+    ) -> Result<(), JsError> {
+        self.inner
+            .report(txid, result, now)
+            .map_err(|e| JsError::new(&format!("Failed to report submission: {}", e)))?;
+        self.events.dispatch();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmBroadcastQueue {
+    /// Create an empty queue for `endpoints`, in order of preference.
+    /// Retry delays default to 10 seconds, doubling up to 10 minutes.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        endpoints: Vec<String>,
+        initial_delay_secs: Option<u64>,
+        max_delay_secs: Option<u64>,
+    ) -> Result<WasmBroadcastQueue, JsError> {
+        let defaults = t2z_core::RetryPolicy::default();
+        let retry = t2z_core::RetryPolicy {
+            initial_delay_secs: initial_delay_secs.unwrap_or(defaults.initial_delay_secs),
+            max_delay_secs: max_delay_secs.unwrap_or(defaults.max_delay_secs),
+        };
+        let inner = t2z_core::BroadcastQueue::new(endpoints, retry)
+            .map_err(|e| JsError::new(&format!("Failed to create broadcast queue: {}", e)))?;
+        Ok(WasmBroadcastQueue::wrap(inner))
+    }
+
+    /// Restore a queue saved with `to_bytes`
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmBroadcastQueue, JsError> {
+        let inner = t2z_core::BroadcastQueue::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse broadcast queue: {}", e)))?;
+        Ok(WasmBroadcastQueue::wrap(inner))
+    }
+
+    /// Serialize the queue to bytes
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// Replace the endpoints, forgetting their health
+    #[wasm_bindgen]
+    pub fn set_endpoints(&mut self, endpoints: Vec<String>) -> Result<(), JsError> {
+        self.inner
+            .set_endpoints(endpoints)
+            .map_err(|e| JsError::new(&format!("Failed to set endpoints: {}", e)))
+    }
+
+    /// Health of each endpoint, in order of preference: `{ url,
+    /// consecutive_failures, retry_at, last_error, tip_height }`
+    #[wasm_bindgen]
+    pub fn endpoints(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(self.inner.endpoints().endpoints())
+            .map_err(|e| JsError::new(&format!("Failed to serialize endpoints: {}", e)))
+    }
+
+    /// Record a health check that reported chain tip `tip_height`, so
+    /// broadcasts avoid endpoints lagging behind the others
+    #[wasm_bindgen]
+    pub fn record_tip(&mut self, url: &str, tip_height: u32, now: u64) -> bool {
+        self.inner.endpoints_mut().record_tip(url, tip_height, now)
+    }
+
+    /// Submit through a SOCKS5 proxy (`host:port`, e.g. Tor's
+    /// "127.0.0.1:9050"), with a separate circuit per transaction unless
+    /// `isolate_circuits` is false; undefined to connect directly. Attempts
+    /// then carry the proxy URL to use.
+    #[wasm_bindgen]
+    pub fn set_proxy(&mut self, address: Option<String>, isolate_circuits: Option<bool>) {
+        self.inner
+            .endpoints_mut()
+            .set_proxy(socks5_proxy(address, isolate_circuits));
+    }
+
+    /// Dispatch "tx_confirmed" and "tx_expired" events to `target` from now
+    /// on, as `CustomEvent`s with the event in `detail`
+    #[wasm_bindgen]
+    pub fn subscribe(&mut self, target: web_sys::EventTarget) {
+        self.events.targets.push(target);
+    }
+
+    /// Queue a finalized transaction; returns its txid
+    ///
+    /// # Arguments
+    /// * `tx_bytes` - The raw transaction (e.g. from `finalize_and_extract`)
+    /// * `now` - Current time in Unix seconds
+    #[wasm_bindgen]
+    pub fn enqueue(&mut self, tx_bytes: &[u8], now: u64) -> Result<String, JsError> {
+        self.inner
+            .enqueue(tx_bytes, now)
+            .map_err(|e| JsError::new(&format!("Failed to queue transaction: {}", e)))
+    }
+
+    /// Expire transactions that can no longer be mined after `tip_height`
+    /// and return the submissions due at `now`; report each one's result
+    #[wasm_bindgen]
+    pub fn poll(&mut self, now: u64, tip_height: u32) -> Vec<WasmBroadcastAttempt> {
+        let attempts = self.inner.poll(now, tip_height);
+        self.events.dispatch();
+        attempts
+            .into_iter()
+            .map(|attempt| WasmBroadcastAttempt { inner: attempt })
+            .collect()
+    }
+
+    /// The endpoint accepted the transaction (or already had it)
+    #[wasm_bindgen]
+    pub fn report_accepted(&mut self, txid: &str, now: u64) -> Result<(), JsError> {
+        self.report(txid, t2z_core::SubmitResult::Accepted, now)
+    }
+
+    /// The endpoint could not be reached; the next attempt goes elsewhere
+    #[wasm_bindgen]
+    pub fn report_failed(&mut self, txid: &str, error: String, now: u64) -> Result<(), JsError> {
+        self.report(txid, t2z_core::SubmitResult::Failed { error }, now)
+    }
+
+    /// The endpoint rejected the transaction as invalid; it will not be
+    /// retried
+    #[wasm_bindgen]
+    pub fn report_rejected(&mut self, txid: &str, reason: String, now: u64) -> Result<(), JsError> {
+        self.report(txid, t2z_core::SubmitResult::Rejected { reason }, now)
+    }
+
+    /// Record that a transaction was mined; returns whether it was queued
+    #[wasm_bindgen]
+    pub fn mark_mined(&mut self, txid: &str, height: u32) -> bool {
+        let queued = self.inner.mark_mined(txid, height);
+        self.events.dispatch();
+        queued
+    }
+
+    /// All queued transactions: `{ txid, transaction, expiry_height, status,
+    /// attempts, last_error, next_attempt_at }`
+    #[wasm_bindgen]
+    pub fn transactions(&self) -> Result<JsValue, JsError> {
+        let transactions: Vec<_> = self.inner.transactions().collect();
+        serde_wasm_bindgen::to_value(&transactions)
+            .map_err(|e| JsError::new(&format!("Failed to serialize transactions: {}", e)))
+    }
+
+    /// Remove and return the mined, expired, and rejected transactions
+    #[wasm_bindgen]
+    pub fn remove_finished(&mut self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner.remove_finished())
+            .map_err(|e| JsError::new(&format!("Failed to serialize transactions: {}", e)))
+    }
+}
+
+/// lightwalletd or RPC endpoints in order of preference, with health
+/// tracking: failing or lagging endpoints drop out of rotation for a while.
+/// Does no networking itself.
+///
+/// ```javascript
+/// const pool = new WasmEndpointPool(["https://zec.rocks:443", "https://na.zec.rocks:443"]);
+/// for (const url of pool.failover_order(now)) {
+///   try {
+///     const utxos = await fetchUtxos(url, address);
+///     pool.record_success(url);
+///     break;
+///   } catch (e) {
+///     pool.record_failure(url, String(e), now);
+///   }
+/// }
+/// ```
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmEndpointPool {
+    inner: t2z_core::EndpointPool,
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmEndpointPool {
+    /// Create a pool of `urls`, most preferred first. A failing endpoint
+    /// stays out of rotation for 10 seconds by default, doubling on each
+    /// consecutive failure up to 10 minutes.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        urls: Vec<String>,
+        initial_delay_secs: Option<u64>,
+        max_delay_secs: Option<u64>,
+    ) -> Result<WasmEndpointPool, JsError> {
+        let defaults = t2z_core::RetryPolicy::default();
+        let retry = t2z_core::RetryPolicy {
+            initial_delay_secs: initial_delay_secs.unwrap_or(defaults.initial_delay_secs),
+            max_delay_secs: max_delay_secs.unwrap_or(defaults.max_delay_secs),
+        };
+        let inner = t2z_core::EndpointPool::new(urls, retry)
+            .map_err(|e| JsError::new(&format!("Failed to create endpoint pool: {}", e)))?;
+        Ok(WasmEndpointPool { inner })
+    }
+
+    /// Restore a pool saved with `to_bytes`
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmEndpointPool, JsError> {
+        let inner = t2z_core::EndpointPool::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse endpoint pool: {}", e)))?;
+        Ok(WasmEndpointPool { inner })
+    }
+
+    /// Serialize the pool, health included
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// The endpoint to use at `now` (Unix seconds)
+    #[wasm_bindgen]
+    pub fn pick(&self, now: u64) -> String {
+        self.inner.pick(now).to_string()
+    }
+
+    /// Every endpoint in the order to try them at `now`
+    #[wasm_bindgen]
+    pub fn failover_order(&self, now: u64) -> Vec<String> {
+        self.inner.failover_order(now).into_iter().map(str::to_string).collect()
+    }
+
+    /// Record a successful request; returns whether `url` is in the pool
+    #[wasm_bindgen]
+    pub fn record_success(&mut self, url: &str) -> bool {
+        self.inner.record_success(url)
+    }
+
+    /// Record a failed request, taking `url` out of rotation for a while;
+    /// returns whether it is in the pool
+    #[wasm_bindgen]
+    pub fn record_failure(&mut self, url: &str, error: &str, now: u64) -> bool {
+        self.inner.record_failure(url, error, now)
+    }
+
+    /// Record a health check that reported chain tip `tip_height`; an
+    /// endpoint lagging the others counts as failing
+    #[wasm_bindgen]
+    pub fn record_tip(&mut self, url: &str, tip_height: u32, now: u64) -> bool {
+        self.inner.record_tip(url, tip_height, now)
+    }
+
+    /// Health of each endpoint, in order of preference: `{ url,
+    /// consecutive_failures, retry_at, last_error, tip_height }`
+    #[wasm_bindgen]
+    pub fn endpoints(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(self.inner.endpoints())
+            .map_err(|e| JsError::new(&format!("Failed to serialize endpoints: {}", e)))
+    }
+
+    /// Route requests through a SOCKS5 proxy (`host:port`, e.g. Tor's
+    /// "127.0.0.1:9050"), isolating circuits per request unless
+    /// `isolate_circuits` is false; undefined to connect directly.
+    /// Browsers cannot use SOCKS proxies; this is for Node clients.
+    #[wasm_bindgen]
+    pub fn set_proxy(&mut self, address: Option<String>, isolate_circuits: Option<bool>) {
+        self.inner.set_proxy(socks5_proxy(address, isolate_circuits));
+    }
+
+    /// Proxy URL (`socks5h://...`) for a request, with credentials derived
+    /// from `isolation_key` (e.g. the address being looked up) when
+    /// isolating circuits; undefined without a proxy
+    #[wasm_bindgen]
+    pub fn proxy_url(&self, isolation_key: &str) -> Option<String> {
+        self.inner.proxy_url(isolation_key)
+    }
+}
+
+/// A batched, paginated, rate-limited lookup of many addresses' UTXOs.
+/// Does no networking itself: send each request to lightwalletd's
+/// `GetAddressUtxos` and report the reply.
+///
+/// ```javascript
+/// const fetch = new WasmUtxoFetch(depositAddresses, "mainnet");
+/// while (!fetch.is_done()) {
+///   const request = fetch.next_request(now());
+///   if (!request) { await sleep(200); continue; }
+///   try {
+///     fetch.record_page(request, await getAddressUtxos(request));
+///   } catch (e) {
+///     fetch.record_failure(request);
+///   }
+/// }
+/// fetch.add_to_account(account, pubkeysByAddress);
+/// ```
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmUtxoFetch {
+    inner: t2z_core::UtxoFetch,
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmUtxoFetch {
+    /// Plan a lookup of `addresses` on "mainnet" or "testnet". By default,
+    /// 100 addresses per request, 1000 UTXOs per page, and 5 requests per
+    /// second.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        addresses: Vec<String>,
+        network: &str,
+        batch_size: Option<usize>,
+        page_size: Option<u32>,
+        requests_per_second: Option<u32>,
+    ) -> Result<WasmUtxoFetch, JsError> {
+        let defaults = t2z_core::UtxoFetchOptions::default();
+        let options = t2z_core::UtxoFetchOptions {
+            batch_size: batch_size.unwrap_or(defaults.batch_size),
+            page_size: page_size.unwrap_or(defaults.page_size),
+            requests_per_second: requests_per_second.unwrap_or(defaults.requests_per_second),
+        };
+        let inner =
+            t2z_core::fetch_utxos_for_addresses(addresses, parse_network(network)?, options)
+                .map_err(|e| JsError::new(&format!("Failed to plan UTXO fetch: {}", e)))?;
+        Ok(WasmUtxoFetch { inner })
+    }
+
+    /// The next request to make at `now` (Unix seconds): `{ addresses,
+    /// start_height, max_entries }`, or undefined if rate limited or
+    /// nothing is left to request
+    #[wasm_bindgen]
+    pub fn next_request(&mut self, now: u64) -> Result<JsValue, JsError> {
+        match self.inner.next_request(now) {
+            Some(request) => serde_wasm_bindgen::to_value(&request)
+                .map_err(|e| JsError::new(&format!("Failed to serialize request: {}", e))),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// When `next_request` can next hand out a request; undefined if none
+    /// is queued
+    #[wasm_bindgen]
+    pub fn next_request_at(&self, now: u64) -> Option<u64> {
+        self.inner.next_request_at(now)
+    }
+
+    /// Record the reply to `request`: an array of `{ address, txid,
+    /// index, script_pubkey, value, height }` (`txid` and `script_pubkey`
+    /// as byte arrays)
+    #[wasm_bindgen]
+    pub fn record_page(&mut self, request: JsValue, utxos: JsValue) -> Result<(), JsError> {
+        let request: t2z_core::UtxoPageRequest = serde_wasm_bindgen::from_value(request)
+            .map_err(|e| JsError::new(&format!("Failed to parse request: {}", e)))?;
+        let utxos: Vec<t2z_core::FetchedUtxo> = serde_wasm_bindgen::from_value(utxos)
+            .map_err(|e| JsError::new(&format!("Failed to parse UTXOs: {}", e)))?;
+        self.inner
+            .record_page(&request, utxos)
+            .map_err(|e| JsError::new(&format!("Failed to record page: {}", e)))
+    }
+
+    /// Record that `request` failed, queueing it again
+    #[wasm_bindgen]
+    pub fn record_failure(&mut self, request: JsValue) -> Result<(), JsError> {
+        let request: t2z_core::UtxoPageRequest = serde_wasm_bindgen::from_value(request)
+            .map_err(|e| JsError::new(&format!("Failed to parse request: {}", e)))?;
+        self.inner
+            .record_failure(&request)
+            .map_err(|e| JsError::new(&format!("Failed to record failure: {}", e)))
+    }
+
+    /// Whether every page has been fetched
+    #[wasm_bindgen]
+    pub fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    /// Every UTXO found so far
+    #[wasm_bindgen]
+    pub fn utxos(&self) -> Result<JsValue, JsError> {
+        let utxos: Vec<_> = self.inner.utxos().collect();
+        serde_wasm_bindgen::to_value(&utxos)
+            .map_err(|e| JsError::new(&format!("Failed to serialize UTXOs: {}", e)))
+    }
+
+    /// Total value of the UTXOs found so far, in zatoshis
+    #[wasm_bindgen]
+    pub fn total_value(&self) -> Result<u64, JsError> {
+        self.inner
+            .total_value()
+            .map_err(|e| JsError::new(&format!("Failed to total UTXOs: {}", e)))
+    }
+
+    /// The UTXOs found as inputs, given `pubkeys`: an object mapping each
+    /// address to its public key (hex)
+    #[wasm_bindgen]
+    pub fn to_inputs(&self, pubkeys: JsValue) -> Result<Vec<WasmTransparentInput>, JsError> {
+        let inputs = self
+            .inner
+            .to_inputs(&parse_pubkey_map(pubkeys)?)
+            .map_err(|e| JsError::new(&format!("Failed to build inputs: {}", e)))?;
+        Ok(inputs
+            .into_iter()
+            .map(|input| {
+                WasmTransparentInput::new(
+                    hex::encode(&input.pubkey),
+                    hex::encode(&input.prevout_txid),
+                    input.prevout_index,
+                    input.value,
+                    hex::encode(&input.script_pubkey),
+                    input.sequence,
+                )
+            })
+            .collect())
+    }
+
+    /// Add the UTXOs found to `account` as confirmed at their heights
+    #[wasm_bindgen]
+    pub fn add_to_account(
+        &self,
+        account: &mut WasmAccount,
+        pubkeys: JsValue,
+    ) -> Result<(), JsError> {
+        self.inner
+            .add_to_account(&mut account.inner, &parse_pubkey_map(pubkeys)?)
+            .map_err(|e| JsError::new(&format!("Failed to add UTXOs: {}", e)))?;
+        account.events.dispatch();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+fn parse_pubkey_map(
+    pubkeys: JsValue,
+) -> Result<std::collections::BTreeMap<String, Vec<u8>>, JsError> {
+    let pubkeys: std::collections::BTreeMap<String, String> =
+        serde_wasm_bindgen::from_value(pubkeys)
+            .map_err(|e| JsError::new(&format!("Failed to parse pubkeys: {}", e)))?;
+    pubkeys
+        .into_iter()
+        .map(|(address, pubkey)| {
+            let pubkey = hex::decode(&pubkey)
+                .map_err(|e| JsError::new(&format!("Invalid pubkey hex: {}", e)))?;
+            Ok((address, pubkey))
+        })
+        .collect()
+}
+
+#[cfg(feature = "wallet_lite")]
+fn socks5_proxy(
+    address: Option<String>,
+    isolate_circuits: Option<bool>,
+) -> Option<t2z_core::Socks5Proxy> {
+    address.map(|address| t2z_core::Socks5Proxy {
+        address,
+        isolate_circuits: isolate_circuits.unwrap_or(true),
+    })
+}
+
+/// A submission `WasmBroadcastQueue.poll` wants made
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+pub struct WasmBroadcastAttempt {
+    inner: t2z_core::BroadcastAttempt,
+}
+
+#[cfg(feature = "wallet_lite")]
+#[wasm_bindgen]
+impl WasmBroadcastAttempt {
+    /// Transaction ID (hex), to pass back when reporting
+    #[wasm_bindgen(getter)]
+    pub fn txid(&self) -> String {
+        self.inner.txid.clone()
+    }
+
+    /// Endpoint to submit to
+    #[wasm_bindgen(getter)]
+    pub fn endpoint(&self) -> String {
+        self.inner.endpoint.clone()
+    }
+
+    /// The raw transaction
+    #[wasm_bindgen(getter)]
+    pub fn transaction(&self) -> Vec<u8> {
+        self.inner.transaction.clone()
+    }
+
+    /// The raw transaction as hex (for `sendrawtransaction`)
+    #[wasm_bindgen(getter)]
+    pub fn transaction_hex(&self) -> String {
+        hex::encode(&self.inner.transaction)
+    }
+
+    /// SOCKS5 proxy URL to submit through, if the queue has a proxy
+    #[wasm_bindgen(getter)]
+    pub fn proxy(&self) -> Option<String> {
+        self.inner.proxy.clone()
+    }
+}
+
+#[cfg(feature = "wallet_lite")]
+fn block_hash(bytes: &[u8]) -> Result<[u8; 32], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new("Block hash must be 32 bytes"))
+}
+
+/// Queues core wallet events and dispatches them to the subscribed
+/// `EventTarget`s once the change that raised them is applied
+#[cfg(feature = "wallet_lite")]
+#[derive(Default)]
+struct EventRelay {
+    queue: std::sync::Arc<std::sync::Mutex<Vec<t2z_core::WalletEvent>>>,
+    targets: Vec<web_sys::EventTarget>,
+}
+
+#[cfg(feature = "wallet_lite")]
+impl EventRelay {
+    /// Core listener feeding the queue
+    fn sink(&self) -> impl t2z_core::WalletListener + 'static {
+        let queue = self.queue.clone();
+        move |event: &t2z_core::WalletEvent| {
+            queue.lock().unwrap_or_else(|e| e.into_inner()).push(event.clone());
+        }
+    }
+
+    /// Listener exceptions are reported by `dispatchEvent` itself and do not
+    /// undo the change, so nothing here fails.
+    fn dispatch(&self) {
+        let events = std::mem::take(&mut *self.queue.lock().unwrap_or_else(|e| e.into_inner()));
+        for event in events {
+            let kind = match event {
+                t2z_core::WalletEvent::UtxoAdded { .. } => "utxo_added",
+                t2z_core::WalletEvent::NoteReceived { .. } => "note_received",
+                t2z_core::WalletEvent::TxConfirmed { .. } => "tx_confirmed",
+                t2z_core::WalletEvent::TxExpired { .. } => "tx_expired",
+            };
+            let init = web_sys::CustomEventInit::new();
+            init.set_detail(&serde_wasm_bindgen::to_value(&event).unwrap_or(JsValue::UNDEFINED));
+
+            for target in &self.targets {
+                if let Ok(custom) = web_sys::CustomEvent::new_with_event_init_dict(kind, &init) {
+                    let _ = target.dispatch_event(&custom);
+                }
+            }
+        }
+    }
+}
+
+/// Check payments before selecting any inputs.
+///
+/// Returns an array of issues (empty if the request is fine), each with:
+/// - `payment_index`: Index of the payment, or null for the whole request
+/// - `kind`: e.g. "invalid_address", "memo_on_transparent", "duplicate_recipient"
+/// - `severity`: "error" (proposing would fail) or "warning"
+/// - `message`: Human-readable description
+#[wasm_bindgen]
+pub fn validate_request(payments: Vec<WasmPayment>, network: &str) -> Result<JsValue, JsError> {
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    let issues = t2z_core::validate_request(&request, parse_network(network)?);
+    serde_wasm_bindgen::to_value(&issues)
+        .map_err(|e| JsError::new(&format!("Failed to serialize issues: {}", e)))
+}
+
+/// Parse a ZIP 321 `zcash:` payment URI into payments (memos as hex).
+#[wasm_bindgen]
+pub fn parse_payment_uri(uri: &str) -> Result<Vec<WasmPayment>, JsError> {
+    let request = t2z_core::parse_payment_uri(uri)
+        .map_err(|e| JsError::new(&format!("Failed to parse payment URI: {}", e)))?;
+    Ok(request
+        .payments
+        .into_iter()
+        .map(WasmPayment::from)
+        .collect())
+}
+
+/// Write payments as a ZIP 321 `zcash:` payment URI.
+#[wasm_bindgen]
+pub fn payment_uri(payments: Vec<WasmPayment>) -> Result<String, JsError> {
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    t2z_core::payment_uri(&request)
+        .map_err(|e| JsError::new(&format!("Failed to write payment URI: {}", e)))
+}
+
+/// The ZIP 321 test vectors the core parser is tested against, for checking
+/// a front-end's own URI handling.
+///
+/// Returns an array of objects with:
+/// - `name`: What the vector exercises
+/// - `uri`: The URI to parse
+/// - `payments`: What `parse_payment_uri(uri)` returns, or null if it must fail
+/// - `canonical_uri`: What `payment_uri(payments)` returns, or null
+#[wasm_bindgen]
+pub fn zip321_test_vectors() -> Result<JsValue, JsError> {
+    let vectors = js_sys::Array::new();
+    for vector in t2z_core::zip321::test_vectors() {
+        let payments = match vector.request {
+            Some(request) => {
+                let payments = js_sys::Array::new();
+                for payment in request.payments {
+                    payments.push(&JsValue::from(WasmPayment::from(payment)));
+                }
+                payments.into()
+            }
+            None => JsValue::NULL,
+        };
+
+        let obj = js_sys::Object::new();
+        js_sys::Reflect::set(&obj, &"name".into(), &vector.name.into())
+            .map_err(|_| JsError::new("Failed to set name"))?;
+        js_sys::Reflect::set(&obj, &"uri".into(), &vector.uri.into())
+            .map_err(|_| JsError::new("Failed to set uri"))?;
+        js_sys::Reflect::set(&obj, &"payments".into(), &payments)
+            .map_err(|_| JsError::new("Failed to set payments"))?;
+        js_sys::Reflect::set(&obj, &"canonical_uri".into(), &vector.canonical_uri.into())
+            .map_err(|_| JsError::new("Failed to set canonical_uri"))?;
+        vectors.push(&obj);
+    }
+    Ok(vectors.into())
+}
+
+/// Check a signed transaction (hex) for problems that would get it rejected
+/// if broadcast for inclusion at `height` (usually the tip plus one).
+///
+/// Returns an array of issues (empty if none found), each with:
+/// - `index`: Index of the transparent input or output, or null
+/// - `kind`: e.g. "too_large", "wrong_branch", "expired", "non_standard_output"
+/// - `message`: Human-readable description
+#[wasm_bindgen]
+pub fn check_consensus_rules(tx_hex: &str, network: &str, height: u32) -> Result<JsValue, JsError> {
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    let issues = t2z_core::check_consensus_rules(&tx_bytes, parse_network(network)?, height)
+        .map_err(|e| JsError::new(&format!("Failed to read transaction: {}", e)))?;
+    serde_wasm_bindgen::to_value(&issues)
+        .map_err(|e| JsError::new(&format!("Failed to serialize issues: {}", e)))
+}
+
+/// Compute the ZIP 244 auth digest of a transaction (hex), in internal byte
+/// order. v4 transactions get the all-`ff` placeholder.
+#[wasm_bindgen]
+pub fn compute_auth_digest(tx_hex: &str) -> Result<String, JsError> {
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    let digest = t2z_core::compute_auth_digest(&tx_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to read transaction: {}", e)))?;
+    Ok(hex::encode(digest))
+}
+
+/// Compute the txid and auth digest of a transaction (hex).
+///
+/// Returns `{ txid, auth_digest }`, both hex in the byte order RPCs and
+/// explorers display.
+#[wasm_bindgen]
+pub fn compute_transaction_digests(tx_hex: &str) -> Result<JsValue, JsError> {
+    let tx_bytes =
+        hex::decode(tx_hex).map_err(|e| JsError::new(&format!("Invalid transaction hex: {}", e)))?;
+    let digests = t2z_core::compute_transaction_digests(&tx_bytes)
+        .map_err(|e| JsError::new(&format!("Failed to read transaction: {}", e)))?;
+    serde_wasm_bindgen::to_value(&digests)
+        .map_err(|e| JsError::new(&format!("Failed to serialize digests: {}", e)))
+}
+
+/// Addresses used by earlier proposals in a session, for privacy warnings
+/// about address reuse.
+///
+/// ```javascript
+/// const history = new WasmAddressHistory();
+/// const warnings = history.check(inputs, payments, changeAddress);
+/// // ... propose and broadcast ...
+/// history.record(payments, changeAddress);
+/// ```
+#[wasm_bindgen]
+pub struct WasmAddressHistory {
+    inner: t2z_core::AddressHistory,
+}
+
+#[wasm_bindgen]
+impl WasmAddressHistory {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmAddressHistory {
+        WasmAddressHistory {
+            inner: t2z_core::AddressHistory::new(),
+        }
+    }
+
+    /// Restore a history saved with `to_bytes`
+    #[wasm_bindgen]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmAddressHistory, JsError> {
+        let inner = t2z_core::AddressHistory::from_bytes(bytes)
+            .map_err(|e| JsError::new(&format!("Failed to parse address history: {}", e)))?;
+        Ok(WasmAddressHistory { inner })
+    }
+
+    /// Serialize the history for storage
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// Warnings (same shape as `validate_request` issues, kind
+    /// "pays_input_address" or "address_reused") for recipients or a change
+    /// address that reuse an input's address or an earlier proposal's
+    #[wasm_bindgen]
+    pub fn check(
+        &self,
+        inputs: Vec<WasmTransparentInput>,
+        payments: Vec<WasmPayment>,
+        change_address: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let inputs = inputs
+            .iter()
+            .map(|i| i.to_core())
+            .collect::<Result<Vec<_>, _>>()?;
+        let request = t2z_core::TransactionRequest {
+            payments: payments.iter().map(|p| p.to_core()).collect::<Result<_, _>>()?,
+        };
+
+        let issues = self.inner.check(&inputs, &request, change_address.as_deref());
+        serde_wasm_bindgen::to_value(&issues)
+            .map_err(|e| JsError::new(&format!("Failed to serialize issues: {}", e)))
+    }
+
+    /// Record a proposal's recipients and change address
+    #[wasm_bindgen]
+    pub fn record(
+        &mut self,
+        payments: Vec<WasmPayment>,
+        change_address: Option<String>,
+    ) -> Result<(), JsError> {
+        let request = t2z_core::TransactionRequest {
+            payments: payments.iter().map(|p| p.to_core()).collect::<Result<_, _>>()?,
+        };
+        self.inner.record(&request, change_address.as_deref());
+        Ok(())
+    }
+}
+
+impl Default for WasmAddressHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Preview the fee and change of a transaction without building it
+///
+/// Cheap enough to call on every edit. Fails in the same cases as
+/// `propose_transaction`. Returns an object with:
+/// - `fee`: ZIP 317 fee in zatoshis
+/// - `change`: Change in zatoshis
+/// - `size`: Estimated transaction size in bytes
+/// - `action_count`: Orchard actions (drives proving time)
+#[wasm_bindgen]
+pub fn preview_transaction(
+    inputs: Vec<WasmTransparentInput>,
+    payments: Vec<WasmPayment>,
+    change_address: Option<String>,
+    network: &str,
+) -> Result<JsValue, JsError> {
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        inputs.iter().map(|i| i.to_core()).collect();
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    let preview = t2z_core::preview_transaction(
+        &core_inputs?,
+        &request,
+        change_address.as_deref(),
+        parse_network(network)?,
+    )
+    .map_err(|e| JsError::new(&format!("Failed to preview transaction: {}", e)))?;
+
+    serde_wasm_bindgen::to_value(&preview)
+        .map_err(|e| JsError::new(&format!("Failed to serialize preview: {}", e)))
+}
+
+/// Extra ZIP 317 fee, in zatoshis, of splitting the change into two
+/// non-round Orchard notes (`set_split_non_round_change`): 5000 when the
+/// second note needs its own action, 0 when it replaces a padding action
+#[wasm_bindgen]
+pub fn non_round_split_fee(
+    inputs: Vec<WasmTransparentInput>,
+    payments: Vec<WasmPayment>,
+    change_address: &str,
+    network: &str,
+) -> Result<u64, JsError> {
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        inputs.iter().map(|i| i.to_core()).collect();
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    t2z_core::non_round_split_fee(&core_inputs?, &request, change_address, parse_network(network)?)
+        .map_err(|e| JsError::new(&format!("Failed to preview split fee: {}", e)))
+}
+
+/// Re-propose a transaction that expired unmined, with a new expiry and a
+/// fee bump
+///
+/// Spends the same inputs and pays the same payments as the original. The fee
+/// is the ZIP 317 fee times `fee_multiplier` (at least 1.0), rounded up.
+#[wasm_bindgen]
+pub fn rebuild_with_new_expiry(
+    payments: Vec<WasmPayment>,
+    inputs: Vec<WasmTransparentInput>,
+    change_address: Option<String>,
+    network: &str,
+    new_expiry: u32,
+    fee_multiplier: f64,
+) -> Result<WasmPczt, JsError> {
+    let core_inputs: Result<Vec<t2z_core::TransparentInput>, JsError> =
+        inputs.iter().map(|i| i.to_core()).collect();
+    let core_payments: Result<Vec<t2z_core::Payment>, JsError> =
+        payments.iter().map(|p| p.to_core()).collect();
+    let request = t2z_core::TransactionRequest {
+        payments: core_payments?,
+    };
+
+    let pczt = t2z_core::rebuild_with_new_expiry(
+        &request,
+        &core_inputs?,
+        change_address.as_deref(),
+        parse_network(network)?,
+        new_expiry,
+        fee_multiplier,
+    )
+    .map_err(|e| JsError::new(&format!("Failed to rebuild transaction: {}", e)))?;
+
+    Ok(WasmPczt { inner: pczt })
 }
@@ -0,0 +1,204 @@
+//! Dedicated-worker side of the worker pool (`js/worker-pool.js`).
+//!
+//! Proving blocks for seconds and building the proving key for ~10 more, so
+//! web wallets run them off the main thread. `serve_worker` turns a
+//! dedicated worker into a prover/signer speaking a small message protocol,
+//! and the `T2zWorkerPool` companion (copied into the npm package as
+//! `worker-pool.js`) drives a pool of them from the main thread.
+//!
+//! PCZTs travel as `Uint8Array`s whose buffers are transferred, not copied.
+//!
+//! Requests (main thread to worker), each with a caller-chosen numeric `id`:
+//! - `{ id, op: "prebuild" }` builds the proving key
+//! - `{ id, op: "prove", pczt }` proves a PCZT
+//! - `{ id, op: "sign", pczt, secret_keys }` signs transparent inputs, with
+//!   one hex private key (or `null` to skip the input) per input
+//!
+//! Replies (worker to main thread):
+//! - `{ type: "ready", protocol }` once the worker accepts requests
+//! - `{ id, type: "progress", stage, done, total }` with `stage` one of
+//!   `"proving_key"`, `"proving"`, or `"signing"`
+//! - `{ id, type: "result", pczt }` (`pczt` is absent for `prebuild`)
+//! - `{ id, type: "error", message }`
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+
+/// Version of the worker message protocol, sent in the `ready` message
+pub const WORKER_PROTOCOL_VERSION: u32 = 1;
+
+/// Serve pool requests in this dedicated worker. Call once, from the worker
+/// script, after the module is loaded:
+///
+/// ```js
+/// import { serve_worker } from '@d4mr/t2z-wasm';
+/// serve_worker();
+/// ```
+#[wasm_bindgen]
+pub fn serve_worker() -> Result<(), JsError> {
+    let scope: web_sys::DedicatedWorkerGlobalScope = js_sys::global()
+        .dyn_into()
+        .map_err(|_| JsError::new("serve_worker must be called in a dedicated worker"))?;
+
+    let reply_scope = scope.clone();
+    let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |event| {
+        handle_request(&reply_scope, &event.data());
+    });
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // The handler lives as long as the worker
+    on_message.forget();
+
+    let ready = js_sys::Object::new();
+    set(&ready, "type", &"ready".into());
+    set(&ready, "protocol", &WORKER_PROTOCOL_VERSION.into());
+    scope
+        .post_message(&ready)
+        .map_err(|_| JsError::new("Failed to post ready message"))
+}
+
+fn handle_request(scope: &web_sys::DedicatedWorkerGlobalScope, request: &JsValue) {
+    let id = get(request, "id");
+    let reply = js_sys::Object::new();
+    set(&reply, "id", &id);
+
+    match run_request(scope, &id, request) {
+        Ok(Some(pczt_bytes)) => {
+            let pczt = js_sys::Uint8Array::from(pczt_bytes.as_slice());
+            set(&reply, "type", &"result".into());
+            set(&reply, "pczt", &pczt);
+            let transfer = js_sys::Array::of1(&pczt.buffer());
+            let _ = scope.post_message_with_transfer(&reply, &transfer);
+        }
+        Ok(None) => {
+            set(&reply, "type", &"result".into());
+            let _ = scope.post_message(&reply);
+        }
+        Err(message) => {
+            set(&reply, "type", &"error".into());
+            set(&reply, "message", &message.into());
+            let _ = scope.post_message(&reply);
+        }
+    }
+}
+
+fn run_request(
+    scope: &web_sys::DedicatedWorkerGlobalScope,
+    id: &JsValue,
+    request: &JsValue,
+) -> Result<Option<Vec<u8>>, String> {
+    let progress = |stage: &str, done: usize, total: usize| {
+        let message = js_sys::Object::new();
+        set(&message, "id", id);
+        set(&message, "type", &"progress".into());
+        set(&message, "stage", &stage.into());
+        set(&message, "done", &(done as u32).into());
+        set(&message, "total", &(total as u32).into());
+        let _ = scope.post_message(&message);
+    };
+
+    let op = get(request, "op").as_string().unwrap_or_default();
+    match op.as_str() {
+        "prebuild" => {
+            prebuild(&progress)?;
+            Ok(None)
+        }
+        "prove" => prove(&pczt_arg(request)?, &progress).map(Some),
+        "sign" => {
+            let secret_keys = get(request, "secret_keys");
+            if !js_sys::Array::is_array(&secret_keys) {
+                return Err("secret_keys must be an array".to_string());
+            }
+            let secret_keys: Vec<Option<String>> = js_sys::Array::from(&secret_keys)
+                .iter()
+                .map(|key| key.as_string())
+                .collect();
+            sign(&pczt_arg(request)?, &secret_keys, &progress).map(Some)
+        }
+        _ => Err(format!("Unknown worker op: {:?}", op)),
+    }
+}
+
+#[cfg(feature = "prover")]
+fn prebuild(progress: &dyn Fn(&str, usize, usize)) -> Result<(), String> {
+    if !t2z_core::is_proving_key_loaded() {
+        progress("proving_key", 0, 1);
+        t2z_core::load_orchard_proving_key();
+    }
+    progress("proving_key", 1, 1);
+    Ok(())
+}
+
+#[cfg(feature = "prover")]
+fn prove(pczt_bytes: &[u8], progress: &dyn Fn(&str, usize, usize)) -> Result<Vec<u8>, String> {
+    let pczt =
+        t2z_core::parse_pczt(pczt_bytes).map_err(|e| format!("Failed to parse PCZT: {}", e))?;
+    prebuild(progress)?;
+
+    progress("proving", 0, 1);
+    let proved = t2z_core::prove_transaction(pczt)
+        .map_err(|e| format!("Failed to prove transaction: {}", e))?;
+    progress("proving", 1, 1);
+    Ok(t2z_core::serialize_pczt(&proved))
+}
+
+#[cfg(not(feature = "prover"))]
+const NO_PROVER: &str = "This build has no prover; use the default t2z-wasm build";
+
+#[cfg(not(feature = "prover"))]
+fn prebuild(_progress: &dyn Fn(&str, usize, usize)) -> Result<(), String> {
+    Err(NO_PROVER.to_string())
+}
+
+#[cfg(not(feature = "prover"))]
+fn prove(_pczt_bytes: &[u8], _progress: &dyn Fn(&str, usize, usize)) -> Result<Vec<u8>, String> {
+    Err(NO_PROVER.to_string())
+}
+
+fn sign(
+    pczt_bytes: &[u8],
+    secret_keys: &[Option<String>],
+    progress: &dyn Fn(&str, usize, usize),
+) -> Result<Vec<u8>, String> {
+    let mut pczt =
+        t2z_core::parse_pczt(pczt_bytes).map_err(|e| format!("Failed to parse PCZT: {}", e))?;
+    let input_count = pczt.transparent().inputs().len();
+    if secret_keys.len() != input_count {
+        return Err(format!(
+            "Expected {} secret keys, one per transparent input (got {})",
+            input_count,
+            secret_keys.len()
+        ));
+    }
+
+    for (input_index, secret_key_hex) in secret_keys.iter().enumerate() {
+        progress("signing", input_index, input_count);
+        let Some(secret_key_hex) = secret_key_hex else {
+            continue;
+        };
+        let secret_key: [u8; 32] = hex::decode(secret_key_hex)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| format!("Invalid secret key for input {}", input_index))?;
+        pczt = t2z_core::sign_transparent_input(pczt, input_index, &secret_key)
+            .map_err(|e| format!("Failed to sign input {}: {}", input_index, e))?;
+    }
+    progress("signing", input_count, input_count);
+
+    Ok(t2z_core::serialize_pczt(&pczt))
+}
+
+/// The request's `pczt`, as bytes
+fn pczt_arg(request: &JsValue) -> Result<Vec<u8>, String> {
+    get(request, "pczt")
+        .dyn_into::<js_sys::Uint8Array>()
+        .map(|pczt| pczt.to_vec())
+        .map_err(|_| "pczt must be a Uint8Array".to_string())
+}
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(object, &key.into()).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn set(object: &JsValue, key: &str, value: &JsValue) {
+    let _ = js_sys::Reflect::set(object, &key.into(), value);
+}